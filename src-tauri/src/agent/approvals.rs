@@ -0,0 +1,109 @@
+//! Which tool calls run without asking: the `[auto_approve]` config, or the
+//! session setting from `agent_set_auto_approve`. Calls in a category that is
+//! not auto-approved wait for `agent_respond_tool_approval`.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::tools::ToolName;
+use crate::config::AutoApproveConfig;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalCategory {
+    Edits,
+    Bash,
+    Network,
+}
+
+/// Tools that can need approval; every other tool always runs.
+const CATEGORIES: &[(ToolName, ApprovalCategory)] = &[
+    (ToolName::WriteFile, ApprovalCategory::Edits),
+    (ToolName::EditFile, ApprovalCategory::Edits),
+    (ToolName::MultiEdit, ApprovalCategory::Edits),
+    (ToolName::NotebookEdit, ApprovalCategory::Edits),
+    (ToolName::Scaffold, ApprovalCategory::Edits),
+    (ToolName::Bash, ApprovalCategory::Bash),
+    (ToolName::Docker, ApprovalCategory::Bash),
+    (ToolName::WebFetch, ApprovalCategory::Network),
+    (ToolName::SearchWeb, ApprovalCategory::Network),
+    (ToolName::LookupDocs, ApprovalCategory::Network),
+];
+
+impl ApprovalCategory {
+    pub fn of(tool: ToolName) -> Option<Self> {
+        CATEGORIES
+            .iter()
+            .find(|(name, _)| *name == tool)
+            .map(|(_, category)| *category)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApprovalCategory::Edits => "edits",
+            ApprovalCategory::Bash => "bash",
+            ApprovalCategory::Network => "network",
+        }
+    }
+
+    fn is_auto_approved(&self, settings: &AutoApproveConfig) -> bool {
+        match self {
+            ApprovalCategory::Edits => settings.edits,
+            ApprovalCategory::Bash => settings.bash,
+            ApprovalCategory::Network => settings.network,
+        }
+    }
+}
+
+/// The category a call to `tool` needs the user's approval for, or None if it may run.
+pub fn needs_approval(settings: &AutoApproveConfig, tool: ToolName) -> Option<ApprovalCategory> {
+    ApprovalCategory::of(tool).filter(|category| !category.is_auto_approved(settings))
+}
+
+/// Tools that need approval, by name. Runs that cannot ask (sub-agents)
+/// are not offered them.
+pub fn tools_needing_approval(settings: &AutoApproveConfig) -> Vec<String> {
+    CATEGORIES
+        .iter()
+        .filter(|(_, category)| !category.is_auto_approved(settings))
+        .map(|(tool, _)| tool.as_str().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_runs_every_tool_without_asking() {
+        let settings = AutoApproveConfig::default();
+        assert!(!settings.plans);
+        assert_eq!(needs_approval(&settings, ToolName::Bash), None);
+        assert!(tools_needing_approval(&settings).is_empty());
+    }
+
+    #[test]
+    fn test_only_disabled_categories_need_approval() {
+        let settings = AutoApproveConfig {
+            plans: true,
+            edits: true,
+            bash: false,
+            network: false,
+        };
+        assert_eq!(needs_approval(&settings, ToolName::EditFile), None);
+        assert_eq!(
+            needs_approval(&settings, ToolName::Docker),
+            Some(ApprovalCategory::Bash)
+        );
+        assert_eq!(
+            needs_approval(&settings, ToolName::WebFetch),
+            Some(ApprovalCategory::Network)
+        );
+        assert_eq!(needs_approval(&settings, ToolName::ReadFile), None);
+        assert_eq!(
+            tools_needing_approval(&settings),
+            vec!["bash", "docker", "web_fetch", "search_web", "lookup_docs"]
+        );
+    }
+}
@@ -0,0 +1,153 @@
+//! Attribution trailer for AI-generated files and commit messages.
+
+use std::path::Path;
+
+use crate::config::AttributionConfig;
+
+const TRAILER_KEY: &str = "Generated-by";
+
+/// Trailer content for one run: tool label, model and date.
+#[derive(Debug, Clone)]
+pub struct Attribution {
+    label: String,
+    model: String,
+    date: String,
+}
+
+impl Attribution {
+    pub fn new(label: &str, model: &str, date: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            model: model.to_string(),
+            date: date.to_string(),
+        }
+    }
+
+    /// Returns None when attribution is disabled in config.
+    pub fn from_config(config: &AttributionConfig, model: &str) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        Some(Self::new(&config.label, model, &date))
+    }
+
+    /// Git-style trailer line, e.g. `Generated-by: devflow (claude-sonnet-4) 2025-01-31`.
+    pub fn trailer(&self) -> String {
+        format!(
+            "{}: {} ({}) {}",
+            TRAILER_KEY, self.label, self.model, self.date
+        )
+    }
+
+    /// Appends the trailer as a comment using the file's comment syntax.
+    /// Files without a known comment syntax (e.g. JSON) and files that
+    /// already carry a trailer are returned unchanged.
+    pub fn apply_to_file(&self, path: &Path, content: &str) -> String {
+        let Some((open, close)) = comment_syntax(path) else {
+            return content.to_string();
+        };
+
+        let marker = format!("{}: {} (", TRAILER_KEY, self.label);
+        if content.contains(&marker) {
+            return content.to_string();
+        }
+
+        let mut result = content.to_string();
+        if !result.is_empty() && !result.ends_with('\n') {
+            result.push('\n');
+        }
+        result.push_str(open);
+        result.push(' ');
+        result.push_str(&self.trailer());
+        if let Some(close) = close {
+            result.push(' ');
+            result.push_str(close);
+        }
+        result.push('\n');
+        result
+    }
+
+    /// Instruction appended to the commit prompt so the agent adds the trailer.
+    pub fn commit_instruction(&self) -> String {
+        format!(
+            "End the commit message with this trailer, separated from the body by a blank line:\n\n{}",
+            self.trailer()
+        )
+    }
+}
+
+fn comment_syntax(path: &Path) -> Option<(&'static str, Option<&'static str>)> {
+    let file_name = path.file_name()?.to_str()?;
+    if matches!(file_name, "Makefile" | "Dockerfile" | "Justfile") {
+        return Some(("#", None));
+    }
+
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    match ext.as_str() {
+        "rs" | "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" | "go" | "java" | "kt" | "kts" | "c"
+        | "h" | "cc" | "cpp" | "hpp" | "cs" | "swift" | "scala" | "dart" | "php" => {
+            Some(("//", None))
+        }
+        "py" | "rb" | "sh" | "bash" | "zsh" | "fish" | "yaml" | "yml" | "toml" | "pl" | "r"
+        | "ex" | "exs" | "ps1" | "tf" => Some(("#", None)),
+        "sql" | "lua" | "hs" => Some(("--", None)),
+        "md" | "html" | "htm" | "xml" | "svg" | "vue" => Some(("<!--", Some("-->"))),
+        "css" | "scss" | "less" => Some(("/*", Some("*/"))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attribution() -> Attribution {
+        Attribution::new("devflow", "claude-sonnet-4", "2025-01-31")
+    }
+
+    #[test]
+    fn test_disabled_config_yields_none() {
+        let config = AttributionConfig::default();
+        assert!(Attribution::from_config(&config, "model").is_none());
+    }
+
+    #[test]
+    fn test_trailer_format() {
+        assert_eq!(
+            attribution().trailer(),
+            "Generated-by: devflow (claude-sonnet-4) 2025-01-31"
+        );
+    }
+
+    #[test]
+    fn test_apply_to_file_uses_comment_syntax() {
+        let a = attribution();
+        assert_eq!(
+            a.apply_to_file(Path::new("src/main.rs"), "fn main() {}"),
+            "fn main() {}\n// Generated-by: devflow (claude-sonnet-4) 2025-01-31\n"
+        );
+        assert!(a
+            .apply_to_file(Path::new("script.py"), "print(1)\n")
+            .ends_with("# Generated-by: devflow (claude-sonnet-4) 2025-01-31\n"));
+        assert!(a
+            .apply_to_file(Path::new("README.md"), "# Title\n")
+            .ends_with("<!-- Generated-by: devflow (claude-sonnet-4) 2025-01-31 -->\n"));
+    }
+
+    #[test]
+    fn test_apply_to_file_skips_unknown_and_existing() {
+        let a = attribution();
+        assert_eq!(a.apply_to_file(Path::new("data.json"), "{}"), "{}");
+
+        let once = a.apply_to_file(Path::new("lib.ts"), "export {};\n");
+        assert_eq!(a.apply_to_file(Path::new("lib.ts"), &once), once);
+    }
+
+    #[test]
+    fn test_commit_instruction_contains_trailer() {
+        assert!(attribution()
+            .commit_instruction()
+            .contains("Generated-by: devflow (claude-sonnet-4) 2025-01-31"));
+    }
+}
@@ -1,26 +1,54 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
 use std::sync::{Arc, PoisonError, RwLock};
 
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, State, Webview};
+use tokio_util::sync::CancellationToken;
 
+use super::conversation::Conversation;
+use super::error::AgentError;
+use super::events::EventSink;
+use super::feedback::{rate_message, Rating};
+use super::indexes::IndexStatus;
 use super::memory::LoadResult;
-use super::state::AgentState;
+use super::models::{model_info, ModelInfo};
+use super::prompt_preview::{effective_prompt, memory_without_session, EffectivePrompt};
+use super::provider::ProviderAdapter;
+use super::providers::catalog::{list_provider_models, ProviderModels};
+use super::providers::replay::{replay_transcript, ReplaySummary};
+use super::review::{
+    run_pr_review as review_pr, run_security_review as review_security, ReviewCommentDraft,
+    SecurityFinding,
+};
+use super::run_journal::{
+    find_interrupted, recover, InterruptedRun, RecoveryAction, RecoveryResult,
+};
+use super::run_options::RunOptions;
+use super::state::{AgentState, WindowState, WindowStates};
+use super::tools::{ArchivedPlan, CompactedContext, PlanApproval, TodoList};
 use super::types::{
-    AgentStatus, AgentStatusPayload, ChatMessage, MemoryLoadedPayload, MemoryWarningPayload,
+    AgentStatus, AgentStatusPayload, ChatMessage, ConversationRewoundPayload, MemoryLoadedPayload,
+    MemoryWarningPayload, MessageRole, SendOptions, SteeringMessage,
 };
-use super::usage::{SessionUsageTracker, UsageTotals};
+use super::usage::{LatencyStats, SessionUsage, ToolStats};
+use super::usage_history::{aggregate_daily, format_csv, UsageExportRange, UsageHistory};
+use crate::config::{AutoApproveConfig, ConfigService};
+use crate::git::commands::apply_syntax_highlighting;
+use crate::git::{diff_texts, FileDiff};
 
 fn lock_error<T>(_: PoisonError<T>) -> String {
     "Lock poisoned".to_string()
 }
 
-fn emit_memory_result(app_handle: &AppHandle, result: &LoadResult) {
+fn emit_memory_result(events: &EventSink, result: &LoadResult) {
     match result {
         LoadResult::Loaded {
             path,
             byte_len,
             truncated,
         } => {
-            let _ = app_handle.emit(
+            let _ = events.emit(
                 "memory-loaded",
                 MemoryLoadedPayload {
                     path: path.clone(),
@@ -31,7 +59,7 @@ fn emit_memory_result(app_handle: &AppHandle, result: &LoadResult) {
         }
         LoadResult::NotFound => {}
         LoadResult::Error(message) => {
-            let _ = app_handle.emit(
+            let _ = events.emit(
                 "memory-warning",
                 MemoryWarningPayload {
                     message: message.clone(),
@@ -44,12 +72,170 @@ fn emit_memory_result(app_handle: &AppHandle, result: &LoadResult) {
 #[tauri::command]
 pub async fn agent_send_message(
     app_handle: AppHandle,
-    state: State<'_, RwLock<AgentState>>,
-    usage_tracker: State<'_, Arc<SessionUsageTracker>>,
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+    messages: Vec<ChatMessage>,
+    system_prompt: Option<String>,
+    options: Option<SendOptions>,
+) -> Result<(), String> {
+    run_agent(
+        EventSink::to_window(app_handle, webview.label()),
+        &windows.get(webview.label()),
+        &project_path,
+        messages,
+        system_prompt,
+        options,
+    )
+    .await
+}
+
+/// Drops the reply to `message_id` (or, for an assistant message, the message
+/// itself) and everything after it, then runs the agent again. The discarded
+/// messages stay archived in the session's conversation file.
+#[tauri::command]
+pub async fn agent_regenerate_from(
+    app_handle: AppHandle,
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+    message_id: String,
+    system_prompt: Option<String>,
+    options: Option<SendOptions>,
+) -> Result<(), String> {
+    let window = windows.get(webview.label());
+    let events = EventSink::to_window(app_handle, webview.label());
+    let messages = rewind_conversation(&events, &window.agent, &project_path, |conversation| {
+        conversation.regenerate_from(&message_id)
+    })?;
+    run_agent(
+        events,
+        &window,
+        &project_path,
+        messages,
+        system_prompt,
+        options,
+    )
+    .await
+}
+
+/// Replaces the user message `message_id` with `new_text`, archives what
+/// followed it and runs the agent again.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn agent_edit_message(
+    app_handle: AppHandle,
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+    message_id: String,
+    new_text: String,
+    system_prompt: Option<String>,
+    options: Option<SendOptions>,
+) -> Result<(), String> {
+    let window = windows.get(webview.label());
+    let events = EventSink::to_window(app_handle, webview.label());
+    let messages = rewind_conversation(&events, &window.agent, &project_path, |conversation| {
+        conversation.edit_message(&message_id, &new_text)
+    })?;
+    run_agent(
+        events,
+        &window,
+        &project_path,
+        messages,
+        system_prompt,
+        options,
+    )
+    .await
+}
+
+/// Rates an assistant reply of the current session, with an optional comment.
+#[tauri::command]
+pub fn agent_rate_message(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
     project_path: String,
+    message_id: String,
+    rating: Rating,
+    comment: Option<String>,
+) -> Result<(), String> {
+    let state = windows.agent(webview.label());
+    let session_id = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.session.id().to_string()
+    };
+    rate_message(
+        Path::new(&project_path),
+        &session_id,
+        &message_id,
+        rating,
+        comment,
+    )
+}
+
+/// Truncates the current session's persisted conversation with `rewind`,
+/// saves it and tells the frontend which messages the next run starts from.
+fn rewind_conversation(
+    events: &EventSink,
+    state: &RwLock<AgentState>,
+    project_path: &str,
+    rewind: impl FnOnce(&mut Conversation) -> Result<Vec<ChatMessage>, String>,
+) -> Result<Vec<ChatMessage>, String> {
+    let session_id = {
+        let state_guard = state.read().map_err(lock_error)?;
+        if state_guard.is_running {
+            return Err("Agent is already processing a request".to_string());
+        }
+        state_guard.session.id().to_string()
+    };
+
+    let mut conversation = Conversation::load(Path::new(project_path), &session_id);
+    let messages = rewind(&mut conversation)?;
+    conversation
+        .save()
+        .map_err(|e| format!("Failed to save conversation: {}", e))?;
+
+    let _ = events.emit(
+        "agent-conversation-rewound",
+        ConversationRewoundPayload {
+            messages: messages.clone(),
+        },
+    );
+    Ok(messages)
+}
+
+/// Records the provider, model and session title in the recent projects list.
+fn remember_session(project_path: &str, adapter: &dyn ProviderAdapter, messages: &[ChatMessage]) {
+    let Some(first) = messages
+        .iter()
+        .find(|message| matches!(message.role, MessageRole::User))
+    else {
+        return;
+    };
+    let result = ConfigService::new().and_then(|service| {
+        let mut config = service.load_app_config()?;
+        config.state.record_session(
+            project_path,
+            adapter.provider(),
+            adapter.model(),
+            &first.get_text(),
+        );
+        service.save_app_config(&config)
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to update recent projects: {}", e);
+    }
+}
+
+async fn run_agent(
+    events: EventSink,
+    window: &WindowState,
+    project_path: &str,
     messages: Vec<ChatMessage>,
     system_prompt: Option<String>,
+    options: Option<SendOptions>,
 ) -> Result<(), String> {
+    let state = &window.agent;
     // Use read lock to check state, then write lock to initialize and start
     let (adapter, session, cancel_token, memory) = {
         // First, check with read lock
@@ -58,7 +244,7 @@ pub async fn agent_send_message(
             if state_guard.is_running {
                 return Err("Agent is already processing a request".to_string());
             }
-            state_guard.needs_reload(&project_path)
+            state_guard.needs_reload(project_path)
         };
 
         // If we need to modify state, get write lock
@@ -71,13 +257,13 @@ pub async fn agent_send_message(
 
         if needs_reload {
             let load_result = state_guard
-                .initialize(&project_path)
+                .initialize(project_path)
                 .map_err(|e| e.to_string())?;
-            emit_memory_result(&app_handle, &load_result);
+            emit_memory_result(&events, &load_result);
         } else {
             // Check if memory file has changed
             if let Some(reload_result) = state_guard.reload_memory_if_changed() {
-                emit_memory_result(&app_handle, &reload_result);
+                emit_memory_result(&events, &reload_result);
             }
         }
 
@@ -90,15 +276,29 @@ pub async fn agent_send_message(
         (adapter, session, token, memory)
     };
 
+    remember_session(project_path, adapter.as_ref(), &messages);
+
+    // Leftovers from a previous run were never injected; the frontend resends them
+    session.take_queued_messages().await;
+    session.resume();
+    session.begin_turn().await;
+
     use super::provider::ExecutionContext;
 
     let ctx = ExecutionContext {
         session,
         cancel_token,
-        usage_tracker: Arc::clone(&*usage_tracker),
+        usage_tracker: Arc::clone(&window.usage),
     };
     let result = adapter
-        .send_message(messages, system_prompt, memory, ctx, app_handle)
+        .send_message(
+            messages,
+            system_prompt,
+            memory,
+            options.unwrap_or_default(),
+            ctx,
+            events,
+        )
         .await;
 
     // Mark run as finished
@@ -110,11 +310,54 @@ pub async fn agent_send_message(
     result.map_err(|e| e.to_string())
 }
 
+/// The system prompt the next run would send, for the main agent or the
+/// given sub-agent type, with estimated tokens per section. `variables` stand
+/// in for the ones sent with a message.
+#[tauri::command]
+pub async fn debug_get_effective_prompt(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+    agent_type: Option<String>,
+    variables: Option<HashMap<String, String>>,
+) -> Result<EffectivePrompt, String> {
+    let state = windows.agent(webview.label());
+    // The session's memory and compacted context apply only to its own project
+    let current = {
+        let state_guard = state.read().map_err(lock_error)?;
+        (state_guard.project_path.as_deref() == Some(project_path.as_str())).then(|| {
+            (
+                state_guard.get_memory_for_injection(),
+                state_guard.get_session(),
+            )
+        })
+    };
+    let path = Path::new(&project_path);
+    let (memory, compacted) = match current {
+        Some((memory, session)) => (memory, session.get_compacted().await),
+        None => (
+            Some(memory_without_session(path)),
+            CompactedContext::default(),
+        ),
+    };
+
+    effective_prompt(
+        path,
+        agent_type.as_deref(),
+        memory,
+        &compacted,
+        &variables.unwrap_or_default(),
+    )
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn agent_cancel(
     app_handle: AppHandle,
-    state: State<'_, RwLock<AgentState>>,
+    windows: State<'_, WindowStates>,
+    webview: Webview,
 ) -> Result<(), String> {
+    let state = windows.agent(webview.label());
     let mut state_guard = state.write().map_err(lock_error)?;
 
     if !state_guard.is_running {
@@ -124,7 +367,7 @@ pub fn agent_cancel(
     state_guard.cancel();
 
     // Emit status update
-    let _ = app_handle.emit(
+    let _ = EventSink::to_window(app_handle, webview.label()).emit(
         "agent-status",
         AgentStatusPayload::new(AgentStatus::Cancelled, None),
     );
@@ -132,46 +375,250 @@ pub fn agent_cancel(
     Ok(())
 }
 
+/// Pause the running agent once its in-flight tool calls finish, before the next
+/// API request. Returns false if nothing is running or it is already paused.
 #[tauri::command]
-pub fn agent_is_running(state: State<'_, RwLock<AgentState>>) -> Result<bool, String> {
+pub fn agent_pause(windows: State<'_, WindowStates>, webview: Webview) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
+    let state_guard = state.read().map_err(lock_error)?;
+    if !state_guard.is_running {
+        return Ok(false);
+    }
+    Ok(state_guard.get_session().pause())
+}
+
+#[tauri::command]
+pub fn agent_resume(windows: State<'_, WindowStates>, webview: Webview) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
+    let state_guard = state.read().map_err(lock_error)?;
+    Ok(state_guard.get_session().resume())
+}
+
+/// Compact the conversation when the next message is sent, even if it is
+/// below the compaction threshold.
+#[tauri::command]
+pub fn agent_compact_context(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+) -> Result<(), String> {
+    let state = windows.agent(webview.label());
+    let state_guard = state.read().map_err(lock_error)?;
+    state_guard.get_session().request_compaction();
+    Ok(())
+}
+
+/// Make compactions in this session wait for `agent_respond_compaction`
+/// after their `agent-compaction-preview`, or apply them right away.
+#[tauri::command]
+pub fn agent_set_confirm_compaction(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    enabled: bool,
+) -> Result<(), String> {
+    let state = windows.agent(webview.label());
+    let state_guard = state.read().map_err(lock_error)?;
+    state_guard.get_session().set_confirm_compaction(enabled);
+    Ok(())
+}
+
+/// Accept or reject the compaction shown in `agent-compaction-preview`; a
+/// rejected one leaves the session context as it was. Returns false if no
+/// compaction is waiting.
+#[tauri::command]
+pub async fn agent_respond_compaction(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    approved: bool,
+) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+    Ok(session.respond_compaction_approval(approved).await)
+}
+
+/// Undo the session's last compaction: its summary and facts are replaced by
+/// the context from before it, and the messages it summarized are sent in
+/// full again. Returns false if there is nothing to restore.
+#[tauri::command]
+pub async fn agent_restore_compacted_context(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+
+    let mut conversation = Conversation::load(Path::new(&project_path), session.id());
+    let Some(backup) = conversation.compaction.take() else {
+        return Ok(false);
+    };
+    conversation.save().map_err(|e| e.to_string())?;
+    session.set_compacted(backup.previous).await;
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn agent_is_running(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
     let state_guard = state.read().map_err(lock_error)?;
     Ok(state_guard.is_running)
 }
 
 #[tauri::command]
-pub fn agent_clear_state(state: State<'_, RwLock<AgentState>>) -> Result<(), String> {
+pub fn agent_clear_state(windows: State<'_, WindowStates>, webview: Webview) -> Result<(), String> {
+    let state = windows.agent(webview.label());
     let mut state_guard = state.write().map_err(lock_error)?;
     state_guard.clear();
     Ok(())
 }
 
+/// Queue a user message for the running agent. It is added to the conversation
+/// before the next API request. Returns false if no run is in progress.
 #[tauri::command]
-pub async fn agent_approve_plan(state: State<'_, RwLock<AgentState>>) -> Result<bool, String> {
+pub async fn agent_queue_message(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    message_id: String,
+    content: String,
+) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
+    if content.trim().is_empty() {
+        return Err("Message must not be empty".to_string());
+    }
+
     let session = {
         let state_guard = state.read().map_err(lock_error)?;
+        if !state_guard.is_running {
+            return Ok(false);
+        }
         state_guard.get_session()
     };
 
-    let result = session.approve_plan().await;
+    session
+        .queue_message(SteeringMessage {
+            id: message_id,
+            content,
+        })
+        .await;
+    Ok(true)
+}
+
+/// Approves plan `plan_id`, or the oldest waiting plan. False if there is
+/// no such plan waiting.
+#[tauri::command]
+pub async fn agent_approve_plan(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    plan_id: Option<u32>,
+) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+
+    let result = session.approve_plan(plan_id).await;
     Ok(result)
 }
 
+/// Rejects plan `plan_id`, or the oldest waiting plan, like `agent_approve_plan`.
 #[tauri::command]
 pub async fn agent_reject_plan(
-    state: State<'_, RwLock<AgentState>>,
+    windows: State<'_, WindowStates>,
+    webview: Webview,
     reason: Option<String>,
+    plan_id: Option<u32>,
 ) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
     let session = {
         let state_guard = state.read().map_err(lock_error)?;
         state_guard.get_session()
     };
 
-    let result = session.reject_plan(reason).await;
+    let result = session.reject_plan(plan_id, reason).await;
     Ok(result)
 }
 
+/// Effective auto-approval settings: the session setting if set, otherwise
+/// the project's `[auto_approve]` config.
+#[tauri::command]
+pub async fn agent_get_auto_approve(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+) -> Result<AutoApproveConfig, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+
+    match session.get_auto_approve().await {
+        Some(settings) => Ok(settings),
+        None => ConfigService::load_project_config(Path::new(&project_path))
+            .map(|config| config.auto_approve)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Change what runs without asking for the current session, without
+/// touching the config file. Pass None to return to the project config.
+/// Applies from the next batch of tool calls, also in a running turn.
+#[tauri::command]
+pub async fn agent_set_auto_approve(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+    settings: Option<AutoApproveConfig>,
+) -> Result<AutoApproveConfig, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+
+    session.set_auto_approve(settings).await;
+    agent_get_auto_approve(windows, webview, project_path).await
+}
+
+/// Answer an `agent-tool-approval` request. Returns false if the call is no
+/// longer waiting.
+#[tauri::command]
+pub async fn agent_respond_tool_approval(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    tool_use_id: String,
+    approved: bool,
+    reason: Option<String>,
+) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+
+    let approval = if approved {
+        PlanApproval::Approved
+    } else {
+        PlanApproval::Rejected(reason)
+    };
+    Ok(session.respond_tool_approval(&tool_use_id, approval).await)
+}
+
 #[tauri::command]
-pub async fn agent_has_pending_plan(state: State<'_, RwLock<AgentState>>) -> Result<bool, String> {
+pub async fn agent_has_pending_plan(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+) -> Result<bool, String> {
+    let state = windows.agent(webview.label());
     let session = {
         let state_guard = state.read().map_err(lock_error)?;
         state_guard.get_session()
@@ -180,12 +627,297 @@ pub async fn agent_has_pending_plan(state: State<'_, RwLock<AgentState>>) -> Res
     Ok(session.has_pending_plan().await)
 }
 
+/// Files the agent wrote or edited this turn, each diffed against its content
+/// before the turn's first edit. Changes made through bash are not tracked.
+#[tauri::command]
+pub async fn agent_get_pending_changes(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+) -> Result<Vec<FileDiff>, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+
+    let mut diffs = Vec::new();
+    for snapshot in session.turn_snapshots().await {
+        let current = match tokio::fs::read_to_string(&snapshot.absolute).await {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("Failed to read {}: {}", snapshot.path, e)),
+        };
+        let diff = diff_texts(
+            &snapshot.path,
+            snapshot.original.as_deref(),
+            current.as_deref(),
+        );
+        if !diff.hunks.is_empty() {
+            diffs.push(apply_syntax_highlighting(diff));
+        }
+    }
+    Ok(diffs)
+}
+
+/// Session token totals, broken down into the main conversation, compaction
+/// and each sub-agent type.
+#[tauri::command]
+pub fn get_session_usage(windows: State<'_, WindowStates>, webview: Webview) -> SessionUsage {
+    windows.usage(webview.label()).get_session_usage()
+}
+
+/// Per-tool call counts, durations, error rates and output sizes for the session.
+#[tauri::command]
+pub fn get_tool_stats(windows: State<'_, WindowStates>, webview: Webview) -> Vec<ToolStats> {
+    windows.usage(webview.label()).get_tool_stats()
+}
+
+/// Average time to first token and output throughput of the session's model responses.
+#[tauri::command]
+pub fn get_latency_stats(windows: State<'_, WindowStates>, webview: Webview) -> LatencyStats {
+    windows.usage(webview.label()).get_latency_stats()
+}
+
+/// Context window, output limit, vision support and pricing of `model`, None if unknown.
+#[tauri::command]
+pub fn get_model_info(model: String) -> Option<ModelInfo> {
+    model_info(&model)
+}
+
+/// Models offered by the project's provider and any other provider with an API key set.
+#[tauri::command]
+pub async fn providers_list_models(project_path: String) -> Result<Vec<ProviderModels>, String> {
+    let config =
+        ConfigService::load_project_config(Path::new(&project_path)).map_err(|e| e.to_string())?;
+    Ok(list_provider_models(&config).await)
+}
+
+#[tauri::command]
+pub fn reset_session_usage(windows: State<'_, WindowStates>, webview: Webview) {
+    windows.usage(webview.label()).reset();
+}
+
+/// Export persisted usage history as CSV, one row per day, project and model.
 #[tauri::command]
-pub fn get_session_usage(tracker: State<'_, Arc<SessionUsageTracker>>) -> UsageTotals {
-    tracker.get_totals()
+pub fn usage_export_csv(range: UsageExportRange) -> Result<String, String> {
+    let history = UsageHistory::new().map_err(|e| e.to_string())?;
+    let records = history.load().map_err(|e| e.to_string())?;
+    let rows = aggregate_daily(&records, &range)?;
+    Ok(format_csv(&rows))
+}
+
+/// The most recent run the app stopped in the middle of (crash or kill),
+/// to offer recovery when the project opens. None if every run ended.
+#[tauri::command]
+pub fn get_interrupted_run(project_path: String) -> Option<InterruptedRun> {
+    find_interrupted(Path::new(&project_path)).map(|record| InterruptedRun::from(&record))
+}
+
+/// Restores the files the interrupted run changed, returns its conversation
+/// to resume from, or discards it. The run is forgotten either way. On
+/// resume the current session takes over the run's todo list and plans.
+#[tauri::command]
+pub async fn recover_last_run(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+    action: RecoveryAction,
+) -> Result<RecoveryResult, String> {
+    let result = recover(Path::new(&project_path), action)?;
+    if !result.todos.is_empty() || !result.plans.is_empty() {
+        let session = {
+            let state = windows.agent(webview.label());
+            let state_guard = state.read().map_err(lock_error)?;
+            state_guard.get_session()
+        };
+        session.restore_todos(result.todos.clone()).await;
+        session.restore_plan_history(result.plans.clone()).await;
+    }
+    Ok(result)
 }
 
+/// Plans approved or rejected in the session, oldest first.
 #[tauri::command]
-pub fn reset_session_usage(tracker: State<'_, Arc<SessionUsageTracker>>) {
-    tracker.reset();
+pub async fn agent_get_plan_history(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+) -> Result<Vec<ArchivedPlan>, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+
+    Ok(session.plan_history().await)
+}
+
+/// The session's todo list, with when each item last changed and which
+/// unfinished items have gone stale.
+#[tauri::command]
+pub async fn agent_get_todos(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+) -> Result<TodoList, String> {
+    let state = windows.agent(webview.label());
+    let session = {
+        let state_guard = state.read().map_err(lock_error)?;
+        state_guard.get_session()
+    };
+
+    Ok(session.todo_list().await)
+}
+
+/// Regenerates the project map added to the system prompt and returns it.
+/// Applies from the next message.
+#[tauri::command]
+pub fn agent_refresh_project_map(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+) -> Result<String, String> {
+    let state = windows.agent(webview.label());
+    let state_guard = state.read().map_err(lock_error)?;
+    Ok(state_guard.refresh_project_map(&project_path))
+}
+
+/// Freshness and size of the project map and the embeddings index.
+#[tauri::command]
+pub fn agent_index_status(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+) -> Result<IndexStatus, String> {
+    let state = windows.agent(webview.label());
+    let state_guard = state.read().map_err(lock_error)?;
+    Ok(state_guard.index_status(&project_path))
+}
+
+/// Effective model and loop parameters for the current session.
+#[tauri::command]
+pub fn agent_get_run_options(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+) -> Result<RunOptions, String> {
+    let state = windows.agent(webview.label());
+    let state_guard = state.read().map_err(lock_error)?;
+    state_guard
+        .get_run_options(&project_path)
+        .map_err(|e| e.to_string())
+}
+
+/// Override parameters for the current session without touching the config
+/// file. Pass None to return to the project config. Applies from the next message.
+#[tauri::command]
+pub fn agent_set_run_options(
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+    project_path: String,
+    options: Option<RunOptions>,
+) -> Result<RunOptions, String> {
+    let state = windows.agent(webview.label());
+    let mut state_guard = state.write().map_err(lock_error)?;
+    state_guard
+        .set_run_options(&project_path, options)
+        .map_err(|e| e.to_string())?;
+    state_guard
+        .get_run_options(&project_path)
+        .map_err(|e| e.to_string())
+}
+
+/// Replay a recorded transcript run without calling the provider. With
+/// `mock_tools`, recorded tool outputs are used instead of executing tools.
+#[tauri::command]
+pub async fn agent_replay_transcript(
+    project_path: String,
+    session_id: String,
+    run_index: Option<usize>,
+    mock_tools: bool,
+) -> Result<ReplaySummary, String> {
+    replay_transcript(
+        Path::new(&project_path),
+        &session_id,
+        run_index.unwrap_or(0),
+        mock_tools,
+        CancellationToken::new(),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Runs `review` as the window's agent run, so `agent_cancel` and closing the
+/// window cancel it like a chat run.
+async fn run_in_window<T, F, Fut>(
+    windows: &WindowStates,
+    label: &str,
+    review: F,
+) -> Result<T, String>
+where
+    F: FnOnce(CancellationToken) -> Fut,
+    Fut: Future<Output = Result<T, AgentError>>,
+{
+    let state = windows.agent(label);
+    let cancel_token = {
+        let mut state_guard = state.write().map_err(lock_error)?;
+        if state_guard.is_running {
+            return Err("Agent is already processing a request".to_string());
+        }
+        state_guard.start_run()
+    };
+
+    let result = review(cancel_token).await;
+
+    {
+        let mut state_guard = state.write().map_err(lock_error)?;
+        state_guard.finish_run();
+    }
+    result.map_err(|e| e.to_string())
+}
+
+/// Run the security review agent over the working tree changes, or over a
+/// revision range such as `main...HEAD`.
+#[tauri::command]
+pub async fn run_security_review(
+    project_path: String,
+    range: Option<String>,
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+) -> Result<Vec<SecurityFinding>, String> {
+    let usage = windows.usage(webview.label());
+    run_in_window(&windows, webview.label(), |cancel_token| async move {
+        review_security(
+            Path::new(&project_path),
+            range.as_deref(),
+            &cancel_token,
+            usage,
+        )
+        .await
+    })
+    .await
+}
+
+/// Run the PR review agent over `range` when given, otherwise `base_ref...HEAD`,
+/// optionally limited to `files`, and return draft inline comments.
+#[tauri::command]
+pub async fn run_pr_review(
+    project_path: String,
+    base_ref: Option<String>,
+    range: Option<String>,
+    files: Option<Vec<String>>,
+    windows: State<'_, WindowStates>,
+    webview: Webview,
+) -> Result<Vec<ReviewCommentDraft>, String> {
+    let usage = windows.usage(webview.label());
+    run_in_window(&windows, webview.label(), |cancel_token| async move {
+        review_pr(
+            Path::new(&project_path),
+            base_ref.as_deref(),
+            range.as_deref(),
+            &files.unwrap_or_default(),
+            &cancel_token,
+            usage,
+        )
+        .await
+    })
+    .await
 }
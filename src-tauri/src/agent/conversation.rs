@@ -0,0 +1,300 @@
+//! The conversation of a session as last sent to the agent, persisted under
+//! `.devflow/sessions/<session>.json` so a turn can be edited or regenerated.
+//! Messages dropped by a rewind are archived in the same file rather than lost,
+//! ratings of replies are kept next to them, and so are the messages and the
+//! session context from before the last compaction, so it can be undone, and
+//! the todo list and the plans decided on as the last run left them.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::feedback::MessageRating;
+use super::tools::{ArchivedPlan, CompactedContext, TrackedTodo};
+use super::types::{ChatMessage, MessageRole};
+
+const SESSIONS_DIR: &str = ".devflow/sessions";
+
+/// Messages discarded by an edit or regeneration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedBranch {
+    /// RFC 3339 UTC timestamp
+    pub archived_at: String,
+    pub messages: Vec<ChatMessage>,
+}
+
+/// What the last compaction replaced, for `agent_restore_compacted_context`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompactionBackup {
+    /// RFC 3339 UTC timestamp
+    pub compacted_at: String,
+    /// Session context before the compaction
+    pub previous: CompactedContext,
+    /// Messages the compaction summarized
+    pub messages: Vec<ChatMessage>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Conversation {
+    pub messages: Vec<ChatMessage>,
+    #[serde(default)]
+    pub archived: Vec<ArchivedBranch>,
+    #[serde(default)]
+    pub ratings: Vec<MessageRating>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compaction: Option<CompactionBackup>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub todos: Vec<TrackedTodo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plans: Vec<ArchivedPlan>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Conversation {
+    /// Loads the session's conversation, empty if none was recorded.
+    pub fn load(project_path: &Path, session_id: &str) -> Self {
+        let path = project_path
+            .join(SESSIONS_DIR)
+            .join(format!("{}.json", session_id));
+        let loaded = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Conversation>(&content).ok());
+        match loaded {
+            Some(conversation) => Self {
+                path,
+                ..conversation
+            },
+            None => Self {
+                path,
+                ..Self::default()
+            },
+        }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, content)?;
+        fs::rename(&temp, &self.path)
+    }
+
+    /// Records the history sent with a run; the frontend owns the history,
+    /// so it replaces what was recorded before.
+    pub fn record_request(
+        project_path: &Path,
+        session_id: &str,
+        messages: &[ChatMessage],
+    ) -> io::Result<()> {
+        let mut conversation = Self::load(project_path, session_id);
+        conversation.messages = messages.to_vec();
+        conversation.save()
+    }
+
+    /// Appends what a run added after the request: assistant messages closed
+    /// by steering, the steering messages and the reply it ended with.
+    pub fn record_reply(
+        project_path: &Path,
+        session_id: &str,
+        messages: Vec<ChatMessage>,
+    ) -> io::Result<()> {
+        let mut conversation = Self::load(project_path, session_id);
+        conversation.messages.extend(messages);
+        conversation.save()
+    }
+
+    /// Replaces the recorded todo list and plan history.
+    pub fn record_progress(
+        project_path: &Path,
+        session_id: &str,
+        todos: &[TrackedTodo],
+        plans: &[ArchivedPlan],
+    ) -> io::Result<()> {
+        let mut conversation = Self::load(project_path, session_id);
+        conversation.todos = todos.to_vec();
+        conversation.plans = plans.to_vec();
+        conversation.save()
+    }
+
+    /// Keeps what a compaction is about to replace; only the last one is kept.
+    pub fn record_compaction(
+        project_path: &Path,
+        session_id: &str,
+        previous: &CompactedContext,
+        messages: &[&ChatMessage],
+    ) -> io::Result<()> {
+        let mut conversation = Self::load(project_path, session_id);
+        conversation.compaction = Some(CompactionBackup {
+            compacted_at: Utc::now().to_rfc3339(),
+            previous: previous.clone(),
+            messages: messages.iter().map(|m| (*m).clone()).collect(),
+        });
+        conversation.save()
+    }
+
+    /// Truncates the conversation so the reply to `message_id` can be
+    /// generated again: a user message is kept, an assistant message is
+    /// dropped along with everything after it. Returns the messages to resend.
+    pub fn regenerate_from(&mut self, message_id: &str) -> Result<Vec<ChatMessage>, String> {
+        let index = self.position(message_id)?;
+        let keep = match self.messages[index].role {
+            MessageRole::User => index + 1,
+            MessageRole::Assistant => index,
+        };
+        match self.messages[..keep].last() {
+            Some(message) if matches!(message.role, MessageRole::User) => {}
+            _ => return Err("No user message to regenerate a reply to".to_string()),
+        }
+        self.truncate(keep);
+        Ok(self.messages.clone())
+    }
+
+    /// Replaces the user message `message_id` with `new_text`, dropping
+    /// everything after it. Returns the messages to resend.
+    pub fn edit_message(
+        &mut self,
+        message_id: &str,
+        new_text: &str,
+    ) -> Result<Vec<ChatMessage>, String> {
+        if new_text.trim().is_empty() {
+            return Err("Message must not be empty".to_string());
+        }
+        let index = self.position(message_id)?;
+        if !matches!(self.messages[index].role, MessageRole::User) {
+            return Err("Only user messages can be edited".to_string());
+        }
+        self.truncate(index);
+        self.messages
+            .push(ChatMessage::new(MessageRole::User, new_text.to_string()));
+        Ok(self.messages.clone())
+    }
+
+    pub(crate) fn position(&self, message_id: &str) -> Result<usize, String> {
+        self.messages
+            .iter()
+            .position(|message| message.id == message_id)
+            .ok_or_else(|| format!("Message not found in this session: {}", message_id))
+    }
+
+    /// Keeps the first `len` messages and archives the rest.
+    fn truncate(&mut self, len: usize) {
+        let discarded = self.messages.split_off(len);
+        if !discarded.is_empty() {
+            self.archived.push(ArchivedBranch {
+                archived_at: Utc::now().to_rfc3339(),
+                messages: discarded,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn conversation() -> Conversation {
+        let mut conversation = Conversation::default();
+        for (id, role) in [
+            ("u1", MessageRole::User),
+            ("a1", MessageRole::Assistant),
+            ("u2", MessageRole::User),
+            ("a2", MessageRole::Assistant),
+        ] {
+            let mut message = ChatMessage::new(role, id.to_string());
+            message.id = id.to_string();
+            conversation.messages.push(message);
+        }
+        conversation
+    }
+
+    fn ids(messages: &[ChatMessage]) -> Vec<&str> {
+        messages.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_regenerate_from_assistant_and_user_messages() {
+        let mut from_reply = conversation();
+        let messages = from_reply.regenerate_from("a1").unwrap();
+        assert_eq!(ids(&messages), vec!["u1"]);
+        assert_eq!(from_reply.archived.len(), 1);
+        assert_eq!(
+            ids(&from_reply.archived[0].messages),
+            vec!["a1", "u2", "a2"]
+        );
+
+        let mut from_request = conversation();
+        let messages = from_request.regenerate_from("u2").unwrap();
+        assert_eq!(ids(&messages), vec!["u1", "a1", "u2"]);
+        assert_eq!(ids(&from_request.archived[0].messages), vec!["a2"]);
+
+        assert!(conversation().regenerate_from("missing").is_err());
+    }
+
+    #[test]
+    fn test_edit_message_replaces_and_archives() {
+        let mut conversation = conversation();
+        let messages = conversation.edit_message("u2", "try again").unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(ids(&messages[..2]), vec!["u1", "a1"]);
+        assert_eq!(messages[2].get_text(), "try again");
+        assert_eq!(ids(&conversation.archived[0].messages), vec!["u2", "a2"]);
+
+        assert!(conversation.edit_message("a1", "nope").is_err());
+        assert!(conversation.edit_message("u1", "  ").is_err());
+    }
+
+    #[test]
+    fn test_record_keeps_archive() {
+        let temp = TempDir::new().unwrap();
+        let mut rewound = conversation();
+        rewound.path = Conversation::load(temp.path(), "s1").path;
+        let messages = rewound.regenerate_from("a2").unwrap();
+        rewound.save().unwrap();
+
+        Conversation::record_request(temp.path(), "s1", &messages).unwrap();
+        let reply = ChatMessage::new(MessageRole::Assistant, "again".to_string());
+        Conversation::record_reply(temp.path(), "s1", vec![reply]).unwrap();
+
+        let loaded = Conversation::load(temp.path(), "s1");
+        assert_eq!(loaded.messages.len(), 4);
+        assert_eq!(loaded.messages[3].get_text(), "again");
+        assert_eq!(loaded.archived.len(), 1);
+    }
+
+    #[test]
+    fn test_record_compaction_keeps_last() {
+        let temp = TempDir::new().unwrap();
+        let messages = conversation().messages;
+        let compacted: Vec<&ChatMessage> = messages[..2].iter().collect();
+        Conversation::record_request(temp.path(), "s1", &messages).unwrap();
+        Conversation::record_compaction(
+            temp.path(),
+            "s1",
+            &CompactedContext::default(),
+            &compacted,
+        )
+        .unwrap();
+
+        let previous = CompactedContext {
+            summary: Some("Set up the project".to_string()),
+            facts: Vec::new(),
+        };
+        Conversation::record_compaction(temp.path(), "s1", &previous, &compacted[..1]).unwrap();
+
+        let loaded = Conversation::load(temp.path(), "s1");
+        assert_eq!(loaded.messages.len(), 4);
+        let backup = loaded.compaction.unwrap();
+        assert_eq!(
+            backup.previous.summary.as_deref(),
+            Some("Set up the project")
+        );
+        assert_eq!(ids(&backup.messages), vec!["u1"]);
+    }
+}
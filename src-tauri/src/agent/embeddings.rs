@@ -0,0 +1,200 @@
+//! Embedding models for the semantic index: the Gemini embedding API or a
+//! local Ollama server, chosen by the project's `[index]` section.
+
+use std::env;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::error::AgentError;
+use super::http::build_http_client;
+use crate::config::{EmbeddingProvider, IndexConfig, NetworkConfig};
+
+const GEMINI_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+const GEMINI_DEFAULT_MODEL: &str = "text-embedding-004";
+const GEMINI_DEFAULT_API_KEY_ENV: &str = "GEMINI_API_KEY";
+/// batchEmbedContents accepts at most this many texts per request
+const GEMINI_MAX_BATCH: usize = 100;
+
+const OLLAMA_BASE_URL: &str = "http://localhost:11434";
+const OLLAMA_DEFAULT_MODEL: &str = "nomic-embed-text";
+const OLLAMA_MAX_BATCH: usize = 32;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// What a text is embedded for; some models embed queries and documents differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EmbeddingTask {
+    Document,
+    Query,
+}
+
+#[async_trait]
+pub(crate) trait Embedder: Send + Sync {
+    /// Identifies the model; vectors from different models are not comparable.
+    fn model_id(&self) -> String;
+
+    /// One vector per text, in order.
+    async fn embed(
+        &self,
+        texts: &[String],
+        task: EmbeddingTask,
+    ) -> Result<Vec<Vec<f32>>, AgentError>;
+}
+
+pub(crate) struct EmbeddingClient {
+    client: Client,
+    provider: EmbeddingProvider,
+    model: String,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl EmbeddingClient {
+    pub fn from_config(config: &IndexConfig, network: &NetworkConfig) -> Result<Self, AgentError> {
+        let (default_base, default_model) = match config.provider {
+            EmbeddingProvider::Gemini => (GEMINI_BASE_URL, GEMINI_DEFAULT_MODEL),
+            EmbeddingProvider::Ollama => (OLLAMA_BASE_URL, OLLAMA_DEFAULT_MODEL),
+        };
+        let api_key = match config.provider {
+            EmbeddingProvider::Gemini => {
+                let key_env = config
+                    .api_key_env
+                    .as_deref()
+                    .unwrap_or(GEMINI_DEFAULT_API_KEY_ENV);
+                Some(
+                    env::var(key_env)
+                        .map_err(|_| AgentError::MissingApiKey(key_env.to_string()))?,
+                )
+            }
+            EmbeddingProvider::Ollama => None,
+        };
+
+        Ok(Self {
+            client: build_http_client(network, Some(REQUEST_TIMEOUT))?,
+            provider: config.provider,
+            model: config
+                .model
+                .clone()
+                .unwrap_or_else(|| default_model.to_string()),
+            base_url: config
+                .base_url
+                .as_deref()
+                .unwrap_or(default_base)
+                .trim_end_matches('/')
+                .to_string(),
+            api_key,
+        })
+    }
+
+    async fn embed_gemini(
+        &self,
+        texts: &[String],
+        task: EmbeddingTask,
+    ) -> Result<Vec<Vec<f32>>, AgentError> {
+        #[derive(Deserialize)]
+        struct Embedding {
+            values: Vec<f32>,
+        }
+        #[derive(Deserialize)]
+        struct Response {
+            embeddings: Vec<Embedding>,
+        }
+
+        let task_type = match task {
+            EmbeddingTask::Document => "RETRIEVAL_DOCUMENT",
+            EmbeddingTask::Query => "RETRIEVAL_QUERY",
+        };
+        let model = format!("models/{}", self.model);
+        let requests: Vec<_> = texts
+            .iter()
+            .map(|text| {
+                json!({
+                    "model": model,
+                    "content": { "parts": [{ "text": text }] },
+                    "taskType": task_type,
+                })
+            })
+            .collect();
+        let url = format!("{}/v1beta/{}:batchEmbedContents", self.base_url, model);
+
+        let response: Response = self.post(&url, json!({ "requests": requests })).await?;
+        Ok(response.embeddings.into_iter().map(|e| e.values).collect())
+    }
+
+    async fn embed_ollama(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AgentError> {
+        #[derive(Deserialize)]
+        struct Response {
+            embeddings: Vec<Vec<f32>>,
+        }
+
+        let url = format!("{}/api/embed", self.base_url);
+        let response: Response = self
+            .post(&url, json!({ "model": self.model, "input": texts }))
+            .await?;
+        Ok(response.embeddings)
+    }
+
+    async fn post<T: for<'de> Deserialize<'de>>(
+        &self,
+        url: &str,
+        body: serde_json::Value,
+    ) -> Result<T, AgentError> {
+        let mut request = self.client.post(url).json(&body);
+        // Header rather than `key=` so request errors cannot echo the key
+        if let Some(api_key) = &self.api_key {
+            request = request.header("x-goog-api-key", api_key);
+        }
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AgentError::ApiError(format!(
+                "Embedding request failed ({}): {}",
+                status, body
+            )));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+#[async_trait]
+impl Embedder for EmbeddingClient {
+    fn model_id(&self) -> String {
+        let provider = match self.provider {
+            EmbeddingProvider::Gemini => "gemini",
+            EmbeddingProvider::Ollama => "ollama",
+        };
+        format!("{}/{}", provider, self.model)
+    }
+
+    async fn embed(
+        &self,
+        texts: &[String],
+        task: EmbeddingTask,
+    ) -> Result<Vec<Vec<f32>>, AgentError> {
+        let max_batch = match self.provider {
+            EmbeddingProvider::Gemini => GEMINI_MAX_BATCH,
+            EmbeddingProvider::Ollama => OLLAMA_MAX_BATCH,
+        };
+        let mut vectors = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(max_batch) {
+            let embedded = match self.provider {
+                EmbeddingProvider::Gemini => self.embed_gemini(batch, task).await?,
+                EmbeddingProvider::Ollama => self.embed_ollama(batch).await?,
+            };
+            if embedded.len() != batch.len() {
+                return Err(AgentError::ApiError(format!(
+                    "Embedding API returned {} vectors for {} texts",
+                    embedded.len(),
+                    batch.len()
+                )));
+            }
+            vectors.extend(embedded);
+        }
+        Ok(vectors)
+    }
+}
@@ -6,7 +6,10 @@ pub enum AgentError {
     MissingApiKey(String),
 
     #[error("HTTP error: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(reqwest::Error),
+
+    #[error("Connection error: {0}")]
+    Connection(String),
 
     #[error("API error: {0}")]
     ApiError(String),
@@ -23,15 +26,42 @@ pub enum AgentError {
     #[error("Tool execution timed out")]
     ToolTimeout,
 
+    #[error("Command timed out after {secs}s and was killed. Output so far:\n{output}")]
+    CommandTimeout { secs: u64, output: String },
+
     #[error("Invalid tool input: {0}")]
     InvalidToolInput(String),
 
     #[error("Unknown tool: {0}")]
     UnknownTool(String),
 
+    #[error("Invalid structured output: {0}")]
+    InvalidStructuredOutput(String),
+
+    #[error("Budget exceeded: {0}")]
+    BudgetExceeded(String),
+
+    #[error("Request too large: {0}")]
+    RequestTooLarge(String),
+
+    #[error("Git error: {0}")]
+    Git(#[from] crate::git::GitError),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
     #[error("Operation cancelled")]
     Cancelled,
 }
+
+impl From<reqwest::Error> for AgentError {
+    /// Network-level failures (connect, timeout, dropped body) are kept distinct
+    /// from other HTTP errors so callers can retry them.
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_connect() || err.is_timeout() || err.is_request() || err.is_body() {
+            AgentError::Connection(err.to_string())
+        } else {
+            AgentError::Http(err)
+        }
+    }
+}
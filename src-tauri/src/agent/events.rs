@@ -0,0 +1,38 @@
+//! Where a run's UI events go. Agent state is kept per window, so a run only
+//! reports to the window that started it.
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, EventTarget, Runtime, Wry};
+
+#[derive(Clone)]
+pub struct EventSink<R: Runtime = Wry> {
+    app_handle: AppHandle<R>,
+    /// Label of the webview window to send to; None sends to every window
+    window: Option<String>,
+}
+
+impl<R: Runtime> EventSink<R> {
+    pub fn to_window(app_handle: AppHandle<R>, label: &str) -> Self {
+        Self {
+            app_handle,
+            window: Some(label.to_string()),
+        }
+    }
+
+    pub fn broadcast(app_handle: AppHandle<R>) -> Self {
+        Self {
+            app_handle,
+            window: None,
+        }
+    }
+
+    pub fn emit<S: Serialize + Clone>(&self, event: &str, payload: S) -> tauri::Result<()> {
+        match &self.window {
+            Some(label) => {
+                self.app_handle
+                    .emit_to(EventTarget::webview_window(label.as_str()), event, payload)
+            }
+            None => self.app_handle.emit(event, payload),
+        }
+    }
+}
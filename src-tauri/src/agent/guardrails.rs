@@ -0,0 +1,137 @@
+//! Size checks run before a provider request is built, so an oversized
+//! conversation fails with an actionable error instead of an API 400.
+
+use crate::agent::error::AgentError;
+use crate::agent::types::{ChatContentBlock, ChatMessage, MessageRole};
+use crate::config::ExecutionConfig;
+
+fn block_bytes(block: &ChatContentBlock) -> usize {
+    match block {
+        ChatContentBlock::Text { text } => text.len(),
+        ChatContentBlock::ToolUse {
+            tool_name,
+            tool_input,
+            output,
+            ..
+        } => {
+            tool_name.len()
+                + tool_input.to_string().len()
+                + output.as_ref().map(String::len).unwrap_or(0)
+        }
+    }
+}
+
+fn message_bytes(message: &ChatMessage) -> usize {
+    message.content_blocks.iter().map(block_bytes).sum()
+}
+
+/// Checks `messages` and `system_prompt` against the size limits in `execution`.
+///
+/// User messages are held to `max_message_bytes` individually (pasted file
+/// contents count towards this), and the whole request to
+/// `max_conversation_bytes`.
+pub(crate) fn check_request_size(
+    messages: &[ChatMessage],
+    system_prompt: &str,
+    execution: &ExecutionConfig,
+) -> Result<(), AgentError> {
+    let max_message = execution.max_message_bytes as usize;
+    for (index, message) in messages.iter().enumerate() {
+        if !matches!(message.role, MessageRole::User) {
+            continue;
+        }
+        let size = message_bytes(message);
+        if size > max_message {
+            return Err(AgentError::RequestTooLarge(format!(
+                "message {} is {} bytes, over the {} byte limit (execution.max_message_bytes). \
+                 Shorten it or reference files by path instead of pasting them",
+                index + 1,
+                size,
+                max_message
+            )));
+        }
+    }
+
+    let max_total = execution.max_conversation_bytes as usize;
+    let total = system_prompt.len() + messages.iter().map(message_bytes).sum::<usize>();
+    if total > max_total {
+        return Err(AgentError::RequestTooLarge(format!(
+            "conversation is {} bytes, over the {} byte limit (execution.max_conversation_bytes). \
+             Start a new conversation or remove large messages",
+            total, max_total
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ShellKind;
+
+    fn limits(message: u64, conversation: u64) -> ExecutionConfig {
+        ExecutionConfig {
+            timeout_secs: 30,
+            max_timeout_secs: 600,
+            plan_approval_timeout_secs: 1800,
+            max_tool_iterations: 50,
+            max_agent_depth: 3,
+            max_message_bytes: message,
+            max_conversation_bytes: conversation,
+            max_tool_result_bytes: 1024,
+            max_read_bytes: 1024,
+            disabled_tools: Vec::new(),
+            depth_disabled_tools: Vec::new(),
+            shell: ShellKind::Auto,
+            allowed_paths: Vec::new(),
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_within_limits() {
+        let messages = vec![
+            ChatMessage::new(MessageRole::User, "hello".to_string()),
+            ChatMessage::new(MessageRole::Assistant, "hi".to_string()),
+        ];
+        assert!(check_request_size(&messages, "system", &limits(10, 100)).is_ok());
+    }
+
+    #[test]
+    fn test_oversized_user_message() {
+        let messages = vec![
+            ChatMessage::new(MessageRole::User, "short".to_string()),
+            ChatMessage::new(MessageRole::User, "x".repeat(11)),
+        ];
+        let err = check_request_size(&messages, "", &limits(10, 100)).unwrap_err();
+        assert!(matches!(err, AgentError::RequestTooLarge(_)));
+        let msg = err.to_string();
+        assert!(msg.contains("message 2 is 11 bytes"));
+        assert!(msg.contains("execution.max_message_bytes"));
+    }
+
+    #[test]
+    fn test_assistant_messages_skip_message_limit() {
+        let messages = vec![ChatMessage::new(MessageRole::Assistant, "x".repeat(50))];
+        assert!(check_request_size(&messages, "", &limits(10, 100)).is_ok());
+    }
+
+    #[test]
+    fn test_conversation_counts_system_prompt_and_tool_output() {
+        let messages = vec![ChatMessage::with_blocks(
+            MessageRole::Assistant,
+            vec![ChatContentBlock::ToolUse {
+                tool_use_id: "t1".to_string(),
+                tool_name: "bash".to_string(),
+                tool_input: serde_json::json!({}),
+                output: Some("y".repeat(40)),
+                is_error: None,
+            }],
+        )];
+        assert!(check_request_size(&messages, "", &limits(10, 100)).is_ok());
+
+        let err = check_request_size(&messages, &"s".repeat(60), &limits(10, 100)).unwrap_err();
+        assert!(err.to_string().contains("execution.max_conversation_bytes"));
+    }
+}
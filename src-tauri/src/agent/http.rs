@@ -0,0 +1,142 @@
+use std::fs;
+use std::time::Duration;
+
+use reqwest::{Certificate, Client, ClientBuilder, NoProxy, Proxy};
+
+use crate::agent::error::AgentError;
+use crate::config::{NetworkConfig, ProviderTimeouts};
+
+/// Builds a reqwest client honoring the project's proxy and CA settings.
+/// Without an explicit proxy, reqwest falls back to HTTP(S)_PROXY/NO_PROXY env vars.
+pub(crate) fn build_http_client(
+    network: &NetworkConfig,
+    timeout: Option<Duration>,
+) -> Result<Client, AgentError> {
+    let mut builder = Client::builder();
+
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+
+    finish_builder(builder, network)
+}
+
+/// Builds the client for provider API calls. Streaming responses can run for
+/// minutes, so only connect and per-read timeouts are applied.
+pub(crate) fn build_provider_client(
+    network: &NetworkConfig,
+    timeouts: &ProviderTimeouts,
+) -> Result<Client, AgentError> {
+    let builder = Client::builder()
+        .connect_timeout(Duration::from_secs(timeouts.connect_secs))
+        .read_timeout(Duration::from_secs(timeouts.read_secs));
+
+    finish_builder(builder, network)
+}
+
+fn finish_builder(
+    mut builder: ClientBuilder,
+    network: &NetworkConfig,
+) -> Result<Client, AgentError> {
+    if let Some(proxy_url) = network.proxy.as_deref().filter(|p| !p.trim().is_empty()) {
+        let proxy = Proxy::all(proxy_url.trim()).map_err(|e| {
+            AgentError::ConfigError(format!("Invalid proxy URL '{}': {}", proxy_url, e))
+        })?;
+        let no_proxy = network.no_proxy.as_deref().and_then(NoProxy::from_string);
+        builder = builder.proxy(proxy.no_proxy(no_proxy));
+    }
+
+    if let Some(ca_path) = network.ca_cert_path.as_deref() {
+        for cert in load_certificates(ca_path)? {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| AgentError::ConfigError(format!("Failed to create HTTP client: {}", e)))
+}
+
+fn load_certificates(path: &str) -> Result<Vec<Certificate>, AgentError> {
+    let pem = fs::read(path).map_err(|e| {
+        AgentError::ConfigError(format!("Failed to read CA certificate '{}': {}", path, e))
+    })?;
+
+    let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+        AgentError::ConfigError(format!("Invalid CA certificate '{}': {}", path, e))
+    })?;
+
+    if certs.is_empty() {
+        return Err(AgentError::ConfigError(format!(
+            "No certificates found in '{}'",
+            path
+        )));
+    }
+
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_network_builds_client() {
+        assert!(build_http_client(&NetworkConfig::default(), None).is_ok());
+    }
+
+    #[test]
+    fn test_proxy_with_no_proxy_builds_client() {
+        let network = NetworkConfig {
+            proxy: Some("http://proxy.internal:3128".to_string()),
+            no_proxy: Some("localhost,127.0.0.1,.corp.example".to_string()),
+            ca_cert_path: None,
+        };
+        assert!(build_http_client(&network, Some(Duration::from_secs(5))).is_ok());
+    }
+
+    #[test]
+    fn test_provider_client_with_timeouts() {
+        let timeouts = ProviderTimeouts {
+            connect_secs: 5,
+            read_secs: 60,
+            slow_response_secs: 20,
+            stream_idle_secs: 90,
+        };
+        assert!(build_provider_client(&NetworkConfig::default(), &timeouts).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_proxy_is_config_error() {
+        let network = NetworkConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        let result = build_http_client(&network, None);
+        assert!(matches!(result, Err(AgentError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_missing_ca_cert_is_config_error() {
+        let network = NetworkConfig {
+            ca_cert_path: Some("/nonexistent/ca.pem".to_string()),
+            ..Default::default()
+        };
+        let result = build_http_client(&network, None);
+        assert!(matches!(result, Err(AgentError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_empty_ca_bundle_is_config_error() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("empty.pem");
+        fs::write(&path, "").unwrap();
+
+        let network = NetworkConfig {
+            ca_cert_path: Some(path.to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        let result = build_http_client(&network, None);
+        assert!(matches!(result, Err(AgentError::ConfigError(_))));
+    }
+}
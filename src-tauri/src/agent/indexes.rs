@@ -0,0 +1,277 @@
+//! Project indexes kept current by a file watcher: the project map and, when
+//! `[index]` is enabled, the embeddings index. Changed paths are collected
+//! until the project has been quiet for a moment, then applied incrementally;
+//! only large bursts (a branch switch) fall back to a full pass.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use ts_rs::TS;
+
+use super::embeddings::EmbeddingClient;
+use super::error::AgentError;
+use super::project_map::{is_watched, project_files_in, ProjectMap};
+use super::semantic_index::SemanticIndex;
+use crate::config::ConfigService;
+
+/// Quiet period before collected changes are applied
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// Larger batches of changes are applied as a full pass
+const MAX_INCREMENTAL_PATHS: usize = 500;
+
+/// Freshness and size of the project's indexes.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct IndexStatus {
+    /// Whether file changes are applied as they happen
+    pub watching: bool,
+    /// None until a session has started for the project
+    pub project_map: Option<ProjectMapStatus>,
+    /// None until an embeddings index has been built
+    pub embeddings: Option<EmbeddingsStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ProjectMapStatus {
+    pub files: u32,
+    /// RFC 3339 UTC timestamp of the last update
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EmbeddingsStatus {
+    /// `[index] enabled`; a disabled index is kept on disk but not updated
+    pub enabled: bool,
+    pub model: String,
+    pub files: u32,
+    pub chunks: u32,
+    #[ts(type = "number")]
+    pub size_bytes: u64,
+    /// RFC 3339 UTC timestamp of the last write
+    pub updated_at: Option<String>,
+    /// Changed files not embedded yet, e.g. after a failed request
+    pub pending_files: u32,
+}
+
+struct Shared {
+    map: ProjectMap,
+    map_updated_at: DateTime<Utc>,
+    /// Changed files the embeddings index has not taken in yet
+    unembedded: BTreeSet<String>,
+}
+
+pub struct ProjectIndexes {
+    project_path: PathBuf,
+    shared: Arc<Mutex<Shared>>,
+    /// None if the watcher could not start; the map then changes only on refresh
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl ProjectIndexes {
+    /// Maps the project and starts watching it.
+    pub fn start(project_path: &Path) -> Self {
+        let shared = Arc::new(Mutex::new(Shared {
+            map: ProjectMap::generate(project_path),
+            map_updated_at: Utc::now(),
+            unembedded: BTreeSet::new(),
+        }));
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let root = project_path.to_path_buf();
+        // Some platforms report events under the canonical path
+        let canonical = project_path.canonicalize().ok();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            for path in &event.paths {
+                let relative = path
+                    .strip_prefix(&root)
+                    .ok()
+                    .or_else(|| path.strip_prefix(canonical.as_ref()?).ok());
+                let Some(relative) = relative else {
+                    continue;
+                };
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                if !relative.is_empty() && is_watched(&relative) {
+                    let _ = tx.send(relative);
+                }
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(project_path, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+
+        let watcher = match watcher {
+            Ok(watcher) => {
+                tauri::async_runtime::spawn(apply_changes(
+                    project_path.to_path_buf(),
+                    Arc::clone(&shared),
+                    rx,
+                ));
+                Some(watcher)
+            }
+            Err(e) => {
+                log::warn!("Not watching {} for changes: {}", project_path.display(), e);
+                None
+            }
+        };
+
+        Self {
+            project_path: project_path.to_path_buf(),
+            shared,
+            watcher,
+        }
+    }
+
+    pub fn map_for_injection(&self) -> String {
+        self.lock().map.format_for_injection()
+    }
+
+    /// Regenerates the map from a full listing and returns it.
+    pub fn refresh_map(&self) -> String {
+        let map = ProjectMap::generate(&self.project_path);
+        let text = map.text().to_string();
+        let mut shared = self.lock();
+        shared.map = map;
+        shared.map_updated_at = Utc::now();
+        text
+    }
+
+    pub fn status(&self) -> IndexStatus {
+        let (map, pending) = {
+            let shared = self.lock();
+            (
+                ProjectMapStatus {
+                    files: shared.map.file_count() as u32,
+                    updated_at: shared.map_updated_at.to_rfc3339(),
+                },
+                shared.unembedded.len(),
+            )
+        };
+        IndexStatus {
+            watching: self.watcher.is_some(),
+            project_map: Some(map),
+            embeddings: embeddings_status(&self.project_path, pending),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Shared> {
+        // The watcher task only swaps in finished values, so a poisoned lock is still usable
+        self.shared.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Status of the project's embeddings index on disk, None if there is none.
+pub fn embeddings_status(project_path: &Path, pending_files: usize) -> Option<EmbeddingsStatus> {
+    let index = SemanticIndex::load(project_path);
+    let metadata = fs::metadata(index.path()).ok()?;
+    let enabled = ConfigService::load_project_config(project_path)
+        .map(|config| config.index.enabled)
+        .unwrap_or(false);
+    Some(EmbeddingsStatus {
+        enabled,
+        model: index.model().to_string(),
+        files: index.file_count() as u32,
+        chunks: index.chunk_count() as u32,
+        size_bytes: metadata.len(),
+        updated_at: metadata
+            .modified()
+            .ok()
+            .map(|modified| DateTime::<Utc>::from(modified).to_rfc3339()),
+        pending_files: pending_files as u32,
+    })
+}
+
+/// Applies watcher events until the watcher is dropped.
+async fn apply_changes(
+    project_path: PathBuf,
+    shared: Arc<Mutex<Shared>>,
+    mut rx: UnboundedReceiver<String>,
+) {
+    while let Some(first) = rx.recv().await {
+        let mut changed = BTreeSet::from([first]);
+        // Take in the whole burst (a save, a checkout) before applying it
+        loop {
+            match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                Ok(Some(path)) => {
+                    changed.insert(path);
+                }
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+
+        let changed: Vec<String> = changed.into_iter().collect();
+        let full = changed.len() > MAX_INCREMENTAL_PATHS;
+        // Listed before locking, so status calls do not wait on git
+        let (map, present) = if full {
+            (Some(ProjectMap::generate(&project_path)), Vec::new())
+        } else {
+            (None, project_files_in(&project_path, &changed))
+        };
+        let pending: Vec<String> = {
+            let mut shared = shared.lock().unwrap_or_else(|e| e.into_inner());
+            match map {
+                Some(map) => shared.map = map,
+                None => shared.map.apply_changes(&changed, present),
+            }
+            shared.map_updated_at = Utc::now();
+            shared.unembedded.extend(changed);
+            shared.unembedded.iter().cloned().collect()
+        };
+
+        match update_embeddings(&project_path, &pending, full).await {
+            Ok(true) => {
+                let mut shared = shared.lock().unwrap_or_else(|e| e.into_inner());
+                for path in &pending {
+                    shared.unembedded.remove(path);
+                }
+            }
+            // Disabled: nothing to catch up on once it is enabled, the first search rescans
+            Ok(false) => shared
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .unembedded
+                .clear(),
+            Err(e) => log::warn!("Embeddings index not updated, will retry: {}", e),
+        }
+    }
+}
+
+/// Updates the embeddings index for `paths` if `[index]` is enabled,
+/// returning whether it is.
+async fn update_embeddings(
+    project_path: &Path,
+    paths: &[String],
+    full: bool,
+) -> Result<bool, AgentError> {
+    let config = ConfigService::load_project_config(project_path)
+        .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+    if !config.index.enabled {
+        return Ok(false);
+    }
+    let client = EmbeddingClient::from_config(&config.index, &config.network)?;
+    let mut index = SemanticIndex::load(project_path);
+    if full || paths.len() > MAX_INCREMENTAL_PATHS {
+        index.update(project_path, &config.index, &client).await?;
+    } else {
+        index
+            .update_paths(project_path, &config.index, &client, paths)
+            .await?;
+    }
+    Ok(true)
+}
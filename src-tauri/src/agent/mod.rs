@@ -1,21 +1,50 @@
+mod approvals;
+pub mod attribution;
 pub mod commands;
+mod conversation;
+mod embeddings;
 pub mod error;
+pub mod events;
+mod feedback;
+mod guardrails;
+mod http;
+mod indexes;
 mod memory;
+pub mod models;
+mod project_map;
+mod prompt_preview;
 pub mod prompts;
 pub mod provider;
 pub mod providers;
+mod rate_limit;
+mod request_cache;
+pub mod review;
+pub mod rng;
+pub mod run_journal;
+pub mod run_options;
+mod semantic_index;
 mod state;
+pub mod structured;
 pub mod tools;
+pub mod transcript;
+mod turn_summary;
 pub mod types;
 pub mod usage;
+pub mod usage_history;
 
+pub(crate) use approvals::tools_needing_approval;
+pub use indexes::{EmbeddingsStatus, IndexStatus, ProjectMapStatus};
 pub use memory::{LoadResult as MemoryLoadResult, MemoryState};
+pub use models::ModelInfo;
 pub use prompts::{
     get_agent_prompts, get_agent_type, get_agent_type_infos, get_all_agent_types,
     get_default_agent_type, AgentType, AgentTypeInfo,
 };
 pub use provider::ProviderAdapter;
 pub use providers::{DEFAULT_EXTRACTION_PROMPT, DEFAULT_SYSTEM_PROMPT};
-pub use state::AgentState;
+pub use state::{AgentState, WindowStates};
 pub use tools::get_tool_descriptions;
-pub use usage::{AgentUsagePayload, SessionUsageTracker, TokenUsage, UsageSource, UsageTotals};
+pub use usage::{
+    AgentUsagePayload, SessionUsage, SessionUsageTracker, SourceUsage, TokenUsage, ToolStats,
+    UsageSource, UsageTotals,
+};
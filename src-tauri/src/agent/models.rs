@@ -0,0 +1,254 @@
+//! Known model metadata: context window, output limit, vision support and pricing.
+//!
+//! Entries match by model name prefix so dated snapshots (e.g.
+//! `claude-sonnet-4-20250514`) resolve to their family. More specific
+//! prefixes must come before the shorter ones they extend.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+struct ModelSpec {
+    prefix: &'static str,
+    provider: &'static str,
+    context_window: u32,
+    max_output_tokens: u32,
+    supports_vision: bool,
+    /// USD per million input tokens
+    input_price: f64,
+    /// USD per million output tokens
+    output_price: f64,
+}
+
+const MODELS: &[ModelSpec] = &[
+    ModelSpec {
+        prefix: "claude-opus-4",
+        provider: "anthropic",
+        context_window: 200_000,
+        max_output_tokens: 32_000,
+        supports_vision: true,
+        input_price: 15.0,
+        output_price: 75.0,
+    },
+    ModelSpec {
+        prefix: "claude-sonnet-4",
+        provider: "anthropic",
+        context_window: 200_000,
+        max_output_tokens: 64_000,
+        supports_vision: true,
+        input_price: 3.0,
+        output_price: 15.0,
+    },
+    ModelSpec {
+        prefix: "claude-3-7-sonnet",
+        provider: "anthropic",
+        context_window: 200_000,
+        max_output_tokens: 64_000,
+        supports_vision: true,
+        input_price: 3.0,
+        output_price: 15.0,
+    },
+    ModelSpec {
+        prefix: "claude-3-5-sonnet",
+        provider: "anthropic",
+        context_window: 200_000,
+        max_output_tokens: 8_192,
+        supports_vision: true,
+        input_price: 3.0,
+        output_price: 15.0,
+    },
+    ModelSpec {
+        prefix: "claude-3-5-haiku",
+        provider: "anthropic",
+        context_window: 200_000,
+        max_output_tokens: 8_192,
+        supports_vision: true,
+        input_price: 0.8,
+        output_price: 4.0,
+    },
+    ModelSpec {
+        prefix: "claude-3-haiku",
+        provider: "anthropic",
+        context_window: 200_000,
+        max_output_tokens: 4_096,
+        supports_vision: true,
+        input_price: 0.25,
+        output_price: 1.25,
+    },
+    ModelSpec {
+        prefix: "gemini-2.5-pro",
+        provider: "gemini",
+        context_window: 1_048_576,
+        max_output_tokens: 65_536,
+        supports_vision: true,
+        input_price: 1.25,
+        output_price: 10.0,
+    },
+    ModelSpec {
+        prefix: "gemini-2.5-flash",
+        provider: "gemini",
+        context_window: 1_048_576,
+        max_output_tokens: 65_536,
+        supports_vision: true,
+        input_price: 0.3,
+        output_price: 2.5,
+    },
+    ModelSpec {
+        prefix: "gemini-2.0-flash-lite",
+        provider: "gemini",
+        context_window: 1_048_576,
+        max_output_tokens: 8_192,
+        supports_vision: true,
+        input_price: 0.075,
+        output_price: 0.3,
+    },
+    ModelSpec {
+        prefix: "gemini-2.0-flash",
+        provider: "gemini",
+        context_window: 1_048_576,
+        max_output_tokens: 8_192,
+        supports_vision: true,
+        input_price: 0.1,
+        output_price: 0.4,
+    },
+    ModelSpec {
+        prefix: "gemini-1.5-pro",
+        provider: "gemini",
+        context_window: 2_097_152,
+        max_output_tokens: 8_192,
+        supports_vision: true,
+        input_price: 1.25,
+        output_price: 5.0,
+    },
+    ModelSpec {
+        prefix: "gemini-1.5-flash",
+        provider: "gemini",
+        context_window: 1_048_576,
+        max_output_tokens: 8_192,
+        supports_vision: true,
+        input_price: 0.075,
+        output_price: 0.3,
+    },
+];
+
+fn lookup(model: &str) -> Option<&'static ModelSpec> {
+    MODELS.iter().find(|spec| model.starts_with(spec.prefix))
+}
+
+/// Metadata for a known model.
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct ModelInfo {
+    pub model: String,
+    pub provider: String,
+    /// Maximum input tokens per request
+    pub context_window: u32,
+    /// Maximum output tokens per response
+    pub max_output_tokens: u32,
+    pub supports_vision: bool,
+    /// USD per million input tokens
+    pub input_price_per_mtok: f64,
+    /// USD per million output tokens
+    pub output_price_per_mtok: f64,
+}
+
+/// Metadata for `model`, None if it is not in the registry.
+pub fn model_info(model: &str) -> Option<ModelInfo> {
+    lookup(model).map(|spec| ModelInfo {
+        model: model.to_string(),
+        provider: spec.provider.to_string(),
+        context_window: spec.context_window,
+        max_output_tokens: spec.max_output_tokens,
+        supports_vision: spec.supports_vision,
+        input_price_per_mtok: spec.input_price,
+        output_price_per_mtok: spec.output_price,
+    })
+}
+
+/// Context window of `model`, None if unknown.
+pub fn context_window(model: &str) -> Option<u32> {
+    lookup(model).map(|spec| spec.context_window)
+}
+
+/// (input, output) USD per million tokens, None if unknown.
+pub fn pricing(model: &str) -> Option<(f64, f64)> {
+    lookup(model).map(|spec| (spec.input_price, spec.output_price))
+}
+
+/// Highest sampling temperature `provider`'s API accepts.
+pub fn max_temperature(provider: &str) -> f32 {
+    if provider.eq_ignore_ascii_case("anthropic") {
+        1.0
+    } else {
+        2.0
+    }
+}
+
+/// `temperature` capped to what `provider` accepts, e.g. from a config
+/// written for another provider.
+pub fn clamp_temperature(provider: &str, temperature: f32) -> f32 {
+    let max = max_temperature(provider);
+    if temperature > max {
+        log::warn!(
+            "temperature {} exceeds the {} maximum of {}, using {}",
+            temperature,
+            provider,
+            max,
+            max
+        );
+        return max;
+    }
+    temperature
+}
+
+/// `max_tokens` capped to what `model` can produce; unknown models are left as configured.
+pub fn clamp_max_tokens(model: &str, max_tokens: u32) -> u32 {
+    match lookup(model) {
+        Some(spec) if max_tokens > spec.max_output_tokens => {
+            log::warn!(
+                "max_tokens {} exceeds the {} output limit of {}, using {}",
+                max_tokens,
+                model,
+                spec.max_output_tokens,
+                spec.max_output_tokens
+            );
+            spec.max_output_tokens
+        }
+        _ => max_tokens,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dated_snapshot_matches_family() {
+        let info = model_info("claude-sonnet-4-20250514").unwrap();
+        assert_eq!(info.provider, "anthropic");
+        assert_eq!(info.context_window, 200_000);
+        assert_eq!(info.input_price_per_mtok, 3.0);
+        assert!(model_info("unknown-model").is_none());
+    }
+
+    #[test]
+    fn test_longer_prefix_wins() {
+        assert_eq!(pricing("gemini-2.0-flash-lite-001"), Some((0.075, 0.3)));
+        assert_eq!(pricing("gemini-2.0-flash-001"), Some((0.1, 0.4)));
+        assert_eq!(context_window("gemini-1.5-pro-002"), Some(2_097_152));
+    }
+
+    #[test]
+    fn test_clamp_max_tokens() {
+        assert_eq!(clamp_max_tokens("claude-3-5-haiku-20241022", 16_000), 8_192);
+        assert_eq!(clamp_max_tokens("claude-3-5-haiku-20241022", 4_096), 4_096);
+        assert_eq!(clamp_max_tokens("custom-model", 100_000), 100_000);
+    }
+
+    #[test]
+    fn test_clamp_temperature() {
+        assert_eq!(clamp_temperature("anthropic", 1.5), 1.0);
+        assert_eq!(clamp_temperature("anthropic", 0.7), 0.7);
+        assert_eq!(clamp_temperature("gemini", 1.5), 1.5);
+        assert_eq!(max_temperature("Anthropic"), 1.0);
+    }
+}
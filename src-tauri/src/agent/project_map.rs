@@ -0,0 +1,456 @@
+//! Project map: a gitignore-aware overview of the project (directory tree,
+//! languages, build files and entry points) added to the system prompt, so a
+//! conversation does not start by listing directories. Kept current from
+//! file watcher events by `apply_changes`. In workspace mode the files of each
+//! `[workspace]` root are included under the root's name.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use walkdir::WalkDir;
+
+use crate::config::ConfigService;
+use crate::git::GitService;
+
+/// Directory levels shown in the tree
+const MAX_TREE_DEPTH: usize = 2;
+/// Tree lines kept; the rest are summarized in one line
+const MAX_TREE_ENTRIES: usize = 60;
+/// Build files and entry points listed
+const MAX_KEY_FILES: usize = 20;
+/// Files considered when the project is not a git repository
+const MAX_WALK_FILES: usize = 20_000;
+
+/// Skipped when walking a project that is not a git repository
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "out",
+    "vendor",
+    "__pycache__",
+    "venv",
+];
+
+const BUILD_FILES: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "setup.py",
+    "requirements.txt",
+    "go.mod",
+    "Makefile",
+    "CMakeLists.txt",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "Gemfile",
+    "composer.json",
+    "Dockerfile",
+    "docker-compose.yml",
+    "compose.yaml",
+    "tsconfig.json",
+    "vite.config.ts",
+    "tauri.conf.json",
+];
+
+const ENTRY_POINTS: &[&str] = &[
+    "main.rs",
+    "lib.rs",
+    "main.go",
+    "main.py",
+    "__main__.py",
+    "app.py",
+    "manage.py",
+    "main.ts",
+    "main.tsx",
+    "index.ts",
+    "index.tsx",
+    "index.js",
+    "main.js",
+    "server.js",
+    "server.ts",
+];
+
+fn language(extension: &str) -> Option<&'static str> {
+    let language = match extension {
+        "rs" => "Rust",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" | "pyi" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" => "C++",
+        "cs" => "C#",
+        "css" | "scss" => "CSS",
+        "html" => "HTML",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        "sql" => "SQL",
+        "sh" | "bash" => "Shell",
+        _ => return None,
+    };
+    Some(language)
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectMap {
+    files: BTreeSet<String>,
+    /// Workspace root names, the first path segment of their files
+    roots: Vec<String>,
+    text: String,
+}
+
+impl ProjectMap {
+    pub fn generate(project_path: &Path) -> Self {
+        let mut files: BTreeSet<String> = project_files(project_path).into_iter().collect();
+        let roots = workspace_roots(project_path);
+        for (name, root) in &roots {
+            files.extend(
+                project_files(root)
+                    .into_iter()
+                    .map(|file| format!("{}/{}", name, file)),
+            );
+        }
+        let roots: Vec<String> = roots.into_iter().map(|(name, _)| name).collect();
+        Self {
+            text: render(&files, &roots),
+            files,
+            roots,
+        }
+    }
+
+    /// Updates the map for `changed` paths (files or directories, created,
+    /// modified or removed), given `present`: the project files now at or
+    /// below them, from `project_files_in`.
+    pub fn apply_changes(&mut self, changed: &[String], present: Vec<String>) {
+        self.files
+            .retain(|file| !changed.iter().any(|path| is_under(file, path)));
+        self.files.extend(present);
+        self.text = render(&self.files, &self.roots);
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn format_for_injection(&self) -> String {
+        format!("<project-map>\n{}\n</project-map>", self.text)
+    }
+}
+
+/// The `[workspace]` roots of the project as (name, directory).
+fn workspace_roots(project_path: &Path) -> Vec<(String, PathBuf)> {
+    ConfigService::load_project_config(project_path)
+        .map(|config| config.workspace.root_paths(project_path))
+        .unwrap_or_default()
+}
+
+/// The map text: the summary, plus the workspace repos if there are any.
+fn render(files: &BTreeSet<String>, roots: &[String]) -> String {
+    let summary = summarize(files);
+    if roots.is_empty() {
+        return summary;
+    }
+    let roots: Vec<String> = roots.iter().map(|name| format!("{}/", name)).collect();
+    format!(
+        "{}\nWorkspace repos: {} (paths starting with a repo name are in that repo)",
+        summary,
+        roots.join(", ")
+    )
+}
+
+/// The project's files as relative paths with `/` separators: tracked and
+/// untracked-but-not-ignored files in a git repository, otherwise a walk that
+/// skips hidden and build directories.
+pub(crate) fn project_files(project_path: &Path) -> Vec<String> {
+    project_files_in(project_path, &[])
+}
+
+/// Like `project_files`, limited to files at or below `paths` (all files if empty).
+pub(crate) fn project_files_in(project_path: &Path, paths: &[String]) -> Vec<String> {
+    let files = match GitService::open(project_path).and_then(|git| git.list_files_in(paths)) {
+        Ok(files) => files,
+        Err(_) if paths.is_empty() => walk_files(project_path, ""),
+        Err(_) => paths
+            .iter()
+            .flat_map(|path| walk_files(project_path, path))
+            .collect(),
+    };
+    // ls-files --cached keeps listing tracked files deleted from the working tree
+    files
+        .into_iter()
+        .filter(|file| project_path.join(file).is_file())
+        .collect()
+}
+
+/// Whether `file` is `path` or inside it (relative paths with `/` separators).
+pub(crate) fn is_under(file: &str, path: &str) -> bool {
+    file.strip_prefix(path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Whether changes to `path` can matter to the map: paths in git metadata,
+/// devflow's own files and dependency or build output directories are skipped
+/// so builds do not flood the watcher.
+pub(crate) fn is_watched(path: &str) -> bool {
+    let first = path.split('/').next().unwrap_or_default();
+    first != ".git" && first != ".devflow" && !in_skipped_dir(path, false)
+}
+
+/// Whether one of the directories `file` is in is skipped by the walk:
+/// a build directory, or with `hidden`, any dot directory.
+fn in_skipped_dir(file: &str, hidden: bool) -> bool {
+    let dirs = file.rsplit_once('/').map_or("", |(dirs, _)| dirs);
+    dirs.split('/')
+        .any(|dir| (hidden && dir.starts_with('.')) || SKIPPED_DIRS.contains(&dir))
+}
+
+/// Files below `start` (relative to the project, "" for the root), skipping
+/// hidden and build directories at any level.
+fn walk_files(project_path: &Path, start: &str) -> Vec<String> {
+    WalkDir::new(project_path.join(start))
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || !(name.starts_with('.')
+                    || (entry.file_type().is_dir() && SKIPPED_DIRS.contains(&name.as_ref())))
+        })
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(project_path).ok()?;
+            Some(relative.to_string_lossy().replace('\\', "/"))
+        })
+        // The start directory's own ancestors are not filtered by the walk
+        .filter(|file| !in_skipped_dir(file, true))
+        .take(MAX_WALK_FILES)
+        .collect()
+}
+
+/// The map text for `files`, relative paths with `/` separators.
+fn summarize<'a>(files: impl IntoIterator<Item = &'a String>) -> String {
+    let mut languages: BTreeMap<&str, usize> = BTreeMap::new();
+    // Keyed by path segments so subdirectories sort right after their parent
+    let mut dirs: BTreeMap<Vec<&str>, usize> = BTreeMap::new();
+    let mut root_files = Vec::new();
+    let mut build_files = Vec::new();
+    let mut entry_points = Vec::new();
+    let mut count = 0;
+
+    for file in files {
+        count += 1;
+        let (parent, name) = file.rsplit_once('/').unwrap_or(("", file.as_str()));
+        let extension = name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+        if let Some(language) = extension.as_deref().and_then(language) {
+            *languages.entry(language).or_default() += 1;
+        }
+        if BUILD_FILES.contains(&name) {
+            build_files.push(file.as_str());
+        }
+        if ENTRY_POINTS.contains(&name) {
+            entry_points.push(file.as_str());
+        }
+
+        if parent.is_empty() {
+            root_files.push(name);
+            continue;
+        }
+        // Count the file in each of its ancestors up to the tree depth
+        let segments: Vec<&str> = parent.split('/').collect();
+        for depth in 1..=segments.len().min(MAX_TREE_DEPTH) {
+            *dirs.entry(segments[..depth].to_vec()).or_default() += 1;
+        }
+    }
+
+    let mut sections = vec![format!("{} files", count)];
+
+    if !languages.is_empty() {
+        let mut languages: Vec<_> = languages.into_iter().collect();
+        languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        let languages: Vec<String> = languages
+            .iter()
+            .map(|(language, count)| format!("{} ({})", language, count))
+            .collect();
+        sections.push(format!("Languages: {}", languages.join(", ")));
+    }
+    if !build_files.is_empty() {
+        sections.push(format!("Build files: {}", key_files(&build_files)));
+    }
+    if !entry_points.is_empty() {
+        sections.push(format!("Entry points: {}", key_files(&entry_points)));
+    }
+
+    let mut tree: Vec<String> = dirs
+        .iter()
+        .map(|(segments, count)| {
+            format!(
+                "{}{}/ ({} files)",
+                "  ".repeat(segments.len() - 1),
+                segments[segments.len() - 1],
+                count
+            )
+        })
+        .collect();
+    tree.extend(root_files.iter().map(|name| name.to_string()));
+    if tree.len() > MAX_TREE_ENTRIES {
+        let hidden = tree.len() - MAX_TREE_ENTRIES;
+        tree.truncate(MAX_TREE_ENTRIES);
+        tree.push(format!("... ({} more entries)", hidden));
+    }
+    if !tree.is_empty() {
+        sections.push(format!("Tree:\n{}", tree.join("\n")));
+    }
+
+    sections.join("\n")
+}
+
+/// Shortest paths first, so top-level files lead.
+fn key_files(files: &[&str]) -> String {
+    let mut files = files.to_vec();
+    files.sort_by_key(|file| (file.matches('/').count(), *file));
+    let extra = files.len().saturating_sub(MAX_KEY_FILES);
+    files.truncate(MAX_KEY_FILES);
+    let mut listed = files.join(", ");
+    if extra > 0 {
+        listed.push_str(&format!(" and {} more", extra));
+    }
+    listed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn test_summarize() {
+        let map = summarize(&files(&[
+            "package.json",
+            "README.md",
+            "src/main.tsx",
+            "src/components/App.tsx",
+            "src/components/deep/Button.tsx",
+            "src-tauri/Cargo.toml",
+            "src-tauri/src/main.rs",
+            "src-tauri/src/lib.rs",
+        ]));
+
+        assert_eq!(
+            map,
+            "8 files\n\
+             Languages: TypeScript (3), Rust (2)\n\
+             Build files: package.json, src-tauri/Cargo.toml\n\
+             Entry points: src/main.tsx, src-tauri/src/lib.rs, src-tauri/src/main.rs\n\
+             Tree:\n\
+             src/ (3 files)\n\
+             \x20\x20components/ (2 files)\n\
+             src-tauri/ (3 files)\n\
+             \x20\x20src/ (2 files)\n\
+             package.json\n\
+             README.md"
+        );
+    }
+
+    #[test]
+    fn test_summarize_caps_tree() {
+        let paths: Vec<String> = (0..100).map(|i| format!("dir{:03}/file.txt", i)).collect();
+        let map = summarize(&paths);
+        assert!(map.contains("dir000/ (1 files)"));
+        assert!(!map.contains("dir099/"));
+        assert!(map.ends_with("... (40 more entries)"));
+    }
+
+    #[test]
+    fn test_apply_changes() {
+        let mut map = ProjectMap {
+            files: files(&["src/a.rs", "src/old/b.rs", "src/old/c.rs", "README.md"])
+                .into_iter()
+                .collect(),
+            roots: Vec::new(),
+            text: String::new(),
+        };
+        // src/old was moved to src/new, src/a.rs was edited
+        map.apply_changes(
+            &files(&["src/old", "src/new", "src/a.rs"]),
+            files(&["src/new/b.rs", "src/a.rs"]),
+        );
+        assert_eq!(
+            map.files.iter().collect::<Vec<_>>(),
+            vec!["README.md", "src/a.rs", "src/new/b.rs"]
+        );
+        assert!(map.text().starts_with("3 files"));
+    }
+
+    #[test]
+    fn test_is_under_and_is_watched() {
+        assert!(is_under("src/a.rs", "src"));
+        assert!(is_under("src", "src"));
+        assert!(!is_under("src-tauri/a.rs", "src"));
+
+        assert!(is_watched("src/main.rs"));
+        assert!(is_watched("scripts/build"));
+        assert!(!is_watched(".git/index"));
+        assert!(!is_watched(".devflow/index/embeddings.json"));
+        assert!(!is_watched("src-tauri/target/debug/app"));
+    }
+
+    #[test]
+    fn test_generate_without_git_skips_hidden_and_build_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        for dir in ["src", "node_modules/pkg", ".cache"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        std::fs::write(root.join("Cargo.toml"), "").unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        std::fs::write(root.join("node_modules/pkg/index.js"), "").unwrap();
+        std::fs::write(root.join(".cache/data"), "").unwrap();
+
+        let map = ProjectMap::generate(root);
+        assert!(map.text().starts_with("2 files"));
+        assert!(map.text().contains("Entry points: src/main.rs"));
+        assert!(!map.text().contains("node_modules"));
+        assert!(map
+            .format_for_injection()
+            .starts_with("<project-map>\n2 files"));
+    }
+
+    #[test]
+    fn test_generate_includes_workspace_roots() {
+        let temp = tempfile::tempdir().unwrap();
+        let project = temp.path().join("frontend");
+        let backend = temp.path().join("backend");
+        std::fs::create_dir_all(project.join(".devflow")).unwrap();
+        std::fs::create_dir_all(backend.join("src")).unwrap();
+        std::fs::write(project.join("package.json"), "").unwrap();
+        std::fs::write(backend.join("src/main.rs"), "").unwrap();
+        std::fs::write(
+            project.join(".devflow/config.toml"),
+            "[agent]\nprovider = \"anthropic\"\nmodel = \"test\"\n\n\
+             [execution]\ntimeout_secs = 30\nmax_tool_iterations = 5\n\n\
+             [[workspace.roots]]\nname = \"backend\"\npath = \"../backend\"\n",
+        )
+        .unwrap();
+
+        let map = ProjectMap::generate(&project);
+        assert!(map.text().contains("Entry points: backend/src/main.rs"));
+        assert!(map.text().ends_with(
+            "Workspace repos: backend/ (paths starting with a repo name are in that repo)"
+        ));
+    }
+}
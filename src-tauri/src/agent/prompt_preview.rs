@@ -0,0 +1,184 @@
+//! The system prompt a run would send, assembled the same way as the run
+//! itself and split into sections with token estimates, for debugging
+//! prompts without inspecting requests.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use super::error::AgentError;
+use super::memory::MemoryState;
+use super::project_map::ProjectMap;
+use super::prompts::resolve_agent_type;
+use super::providers::compaction::{estimate_tokens, format_compacted_context};
+use super::providers::{system_prompt_sections, DEFAULT_SYSTEM_PROMPT};
+use super::tools::{agent_system_prompt, CompactedContext};
+use crate::config::ConfigService;
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct EffectivePrompt {
+    /// None for the main agent
+    pub agent_type: Option<String>,
+    /// The full system prompt, sections joined as sent
+    pub prompt: String,
+    pub sections: Vec<PromptSection>,
+    /// Estimated (chars / 4), like the compaction threshold
+    pub total_tokens: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PromptSection {
+    /// "app", "memory", "pre", "custom" (a sub-agent's own prompt), "post" or "compacted"
+    pub name: String,
+    pub text: String,
+    pub tokens: u32,
+}
+
+/// Assembles the system prompt for the main agent or, with `agent_type`,
+/// for that sub-agent. `memory` and `compacted` come from the main agent's
+/// session; sub-agents use neither.
+pub fn effective_prompt(
+    project_path: &Path,
+    agent_type: Option<&str>,
+    memory: Option<String>,
+    compacted: &CompactedContext,
+    variables: &HashMap<String, String>,
+) -> Result<EffectivePrompt, AgentError> {
+    let config = ConfigService::load_project_config(project_path)
+        .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+
+    let mut sections = match agent_type {
+        Some(id) => {
+            let agent = resolve_agent_type(id, &config.custom_agents)
+                .ok_or_else(|| AgentError::ConfigError(format!("Unknown agent type '{}'", id)))?;
+            let agent_prompt = agent_system_prompt(&agent, project_path, &config, variables)?;
+            system_prompt_sections(
+                DEFAULT_SYSTEM_PROMPT,
+                &config.prompts,
+                Some(agent_prompt),
+                None,
+                variables,
+            )?
+        }
+        None => system_prompt_sections(
+            DEFAULT_SYSTEM_PROMPT,
+            &config.prompts,
+            None,
+            memory.as_deref(),
+            variables,
+        )?,
+    };
+    if agent_type.is_none() && (compacted.summary.is_some() || !compacted.facts.is_empty()) {
+        sections.push(("compacted", format_compacted_context(compacted)));
+    }
+
+    let prompt = sections
+        .iter()
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Ok(EffectivePrompt {
+        agent_type: agent_type.map(str::to_string),
+        total_tokens: estimate_tokens(&prompt),
+        prompt,
+        sections: sections
+            .into_iter()
+            .map(|(name, text)| PromptSection {
+                name: name.to_string(),
+                tokens: estimate_tokens(&text),
+                text,
+            })
+            .collect(),
+    })
+}
+
+/// AGENTS.md and the project map as a new session for the project would
+/// inject them.
+pub fn memory_without_session(project_path: &Path) -> String {
+    let (memory, _) = MemoryState::load(project_path);
+    memory
+        .format_for_injection()
+        .into_iter()
+        .chain(Some(
+            ProjectMap::generate(project_path).format_for_injection(),
+        ))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::types::{CompactedFact, FactCategory};
+
+    fn project(extra: &str) -> tempfile::TempDir {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join(".devflow")).unwrap();
+        std::fs::write(
+            temp.path().join(".devflow/config.toml"),
+            format!(
+                "[agent]\nprovider = \"anthropic\"\nmodel = \"test\"\n\n\
+                 [execution]\ntimeout_secs = 30\nmax_tool_iterations = 5\n\n{}",
+                extra
+            ),
+        )
+        .unwrap();
+        temp
+    }
+
+    #[test]
+    fn test_main_agent_sections_in_order() {
+        let temp = project("[prompts]\npre = \"On {VAR:branch}\"\npost = \"Be brief\"");
+        let compacted = CompactedContext {
+            summary: Some("Fixed the parser".to_string()),
+            facts: vec![CompactedFact {
+                category: FactCategory::Decision,
+                content: "Use nom".to_string(),
+            }],
+        };
+        let variables = HashMap::from([("branch".to_string(), "main".to_string())]);
+
+        let preview = effective_prompt(
+            temp.path(),
+            None,
+            Some("<project-memory>notes</project-memory>".to_string()),
+            &compacted,
+            &variables,
+        )
+        .unwrap();
+
+        let names: Vec<&str> = preview.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["app", "memory", "pre", "post", "compacted"]);
+        assert_eq!(preview.sections[2].text, "On main");
+        assert!(preview.prompt.ends_with(&preview.sections[4].text));
+        assert_eq!(preview.total_tokens, estimate_tokens(&preview.prompt));
+    }
+
+    #[test]
+    fn test_sub_agent_prompt_skips_session_context() {
+        let temp = project("");
+        let compacted = CompactedContext {
+            summary: Some("ignored".to_string()),
+            facts: Vec::new(),
+        };
+
+        let preview = effective_prompt(
+            temp.path(),
+            Some("summarize"),
+            Some("ignored".to_string()),
+            &compacted,
+            &HashMap::new(),
+        )
+        .unwrap();
+
+        let names: Vec<&str> = preview.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["app", "custom"]);
+        assert!(
+            effective_prompt(temp.path(), Some("nope"), None, &compacted, &HashMap::new()).is_err()
+        );
+    }
+}
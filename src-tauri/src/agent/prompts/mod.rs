@@ -2,7 +2,15 @@
 
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+
+use super::error::AgentError;
+use crate::config::CustomAgentConfig;
+
+mod overrides;
+
+pub use overrides::AgentPromptOverrides;
 
 /// Pre-compiled regex for {TOOL:name} patterns
 static TOOL_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{TOOL:(\w+)\}").unwrap());
@@ -10,6 +18,13 @@ static TOOL_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{TOOL:(\w+)\}").un
 /// Pre-compiled regex for {AGENT:id} patterns
 static AGENT_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{AGENT:(\w+)\}").unwrap());
 
+/// Pre-compiled regex for {VAR:name} patterns
+static VAR_PATTERN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{VAR:(\w+)\}").unwrap());
+
+/// Built-in placeholders, matched after interpolation to find unresolved ones
+static BUILTIN_PATTERN: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{(?:ALLOWED_TOOLS|PROJECT_PATH|CURRENT_DATE)\}").unwrap());
+
 /// Agent prompt files compiled into the binary
 mod agent_prompts {
     pub const EXPLORE: &str = include_str!("agents/explore.md");
@@ -17,6 +32,7 @@ mod agent_prompts {
     pub const SUMMARIZE: &str = include_str!("agents/summarize.md");
     pub const BASH_SUMMARIZE: &str = include_str!("agents/bash_summarize.md");
     pub const SESSION_TITLE: &str = include_str!("agents/session_title.md");
+    pub const WEB_EXTRACT: &str = include_str!("agents/web_extract.md");
     pub const PR_REVIEW: &str = include_str!("agents/pr_review.md");
     pub const PR_COMMENTS: &str = include_str!("agents/pr_comments.md");
     pub const SECURITY_REVIEW: &str = include_str!("agents/security_review.md");
@@ -65,19 +81,20 @@ impl Default for AgentFlags {
     }
 }
 
-/// Definition of an agent type
+/// Definition of an agent type; built-in ones are `'static`, custom ones
+/// borrow from the project config.
 #[derive(Debug, Clone)]
-pub struct AgentType {
+pub struct AgentType<'a> {
     /// Unique identifier
-    pub id: &'static str,
+    pub id: &'a str,
     /// Human-readable name
-    pub name: &'static str,
+    pub name: &'a str,
     /// Brief description
-    pub description: &'static str,
+    pub description: &'a str,
     /// System prompt content
-    pub prompt: &'static str,
+    pub prompt: &'a str,
     /// Allowed tool names
-    pub allowed_tools: &'static [&'static str],
+    pub allowed_tools: Cow<'a, [&'a str]>,
     /// Behavioral flags
     pub flags: AgentFlags,
 }
@@ -85,17 +102,22 @@ pub struct AgentType {
 /// Tools for read-only exploration
 const EXPLORE_TOOLS: &[&str] = &[
     "read_file",
+    "outline_file",
+    "semantic_search",
     "list_directory",
     "glob",
     "grep",
     "bash",
     "web_fetch",
     "search_web",
+    "lookup_docs",
 ];
 
 /// Tools for planning (includes dispatch_agent for sub-exploration)
 const PLAN_TOOLS: &[&str] = &[
     "read_file",
+    "outline_file",
+    "semantic_search",
     "list_directory",
     "glob",
     "grep",
@@ -104,7 +126,7 @@ const PLAN_TOOLS: &[&str] = &[
 ];
 
 /// Tools for PR review
-const PR_REVIEW_TOOLS: &[&str] = &["read_file", "glob", "grep", "bash"];
+const PR_REVIEW_TOOLS: &[&str] = &["read_file", "outline_file", "glob", "grep", "bash"];
 
 /// Tools for PR comments
 const PR_COMMENTS_TOOLS: &[&str] = &["bash", "web_fetch"];
@@ -112,18 +134,27 @@ const PR_COMMENTS_TOOLS: &[&str] = &["bash", "web_fetch"];
 /// Tools for security review
 const SECURITY_REVIEW_TOOLS: &[&str] = &["read_file", "glob", "grep"];
 
+/// Tools that change files, which make an agent not read-only
+const WRITE_TOOLS: &[&str] = &[
+    "write_file",
+    "edit_file",
+    "multi_edit",
+    "notebook_edit",
+    "scaffold",
+];
+
 /// No tools (text processing only)
 const NO_TOOLS: &[&str] = &[];
 
 /// Static registry of all agent types
-static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
+static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType<'static>>> = Lazy::new(|| {
     let agents = vec![
         AgentType {
             id: "explore",
             name: "Explore",
             description: "Fast codebase exploration, file search, read-only analysis",
             prompt: agent_prompts::EXPLORE,
-            allowed_tools: EXPLORE_TOOLS,
+            allowed_tools: Cow::Borrowed(EXPLORE_TOOLS),
             flags: AgentFlags {
                 read_only: true,
                 can_background: true,
@@ -135,7 +166,7 @@ static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
             name: "Plan",
             description: "Software architect for designing implementation plans",
             prompt: agent_prompts::PLAN,
-            allowed_tools: PLAN_TOOLS,
+            allowed_tools: Cow::Borrowed(PLAN_TOOLS),
             flags: AgentFlags {
                 read_only: true,
                 can_background: false,
@@ -147,7 +178,7 @@ static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
             name: "Summarize",
             description: "Conversation summarization for context compaction",
             prompt: agent_prompts::SUMMARIZE,
-            allowed_tools: NO_TOOLS,
+            allowed_tools: Cow::Borrowed(NO_TOOLS),
             flags: AgentFlags {
                 read_only: true,
                 can_background: false,
@@ -159,7 +190,7 @@ static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
             name: "Bash Summarize",
             description: "Summarize long bash command outputs",
             prompt: agent_prompts::BASH_SUMMARIZE,
-            allowed_tools: NO_TOOLS,
+            allowed_tools: Cow::Borrowed(NO_TOOLS),
             flags: AgentFlags {
                 read_only: true,
                 can_background: false,
@@ -171,7 +202,19 @@ static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
             name: "Session Title",
             description: "Generate session titles and branch names",
             prompt: agent_prompts::SESSION_TITLE,
-            allowed_tools: NO_TOOLS,
+            allowed_tools: Cow::Borrowed(NO_TOOLS),
+            flags: AgentFlags {
+                read_only: true,
+                can_background: false,
+                no_tools: true,
+            },
+        },
+        AgentType {
+            id: "web-extract",
+            name: "Web Extract",
+            description: "Answer web_fetch prompts from fetched page content",
+            prompt: agent_prompts::WEB_EXTRACT,
+            allowed_tools: Cow::Borrowed(NO_TOOLS),
             flags: AgentFlags {
                 read_only: true,
                 can_background: false,
@@ -183,7 +226,7 @@ static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
             name: "PR Review",
             description: "Review pull requests",
             prompt: agent_prompts::PR_REVIEW,
-            allowed_tools: PR_REVIEW_TOOLS,
+            allowed_tools: Cow::Borrowed(PR_REVIEW_TOOLS),
             flags: AgentFlags {
                 read_only: true,
                 can_background: false,
@@ -195,7 +238,7 @@ static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
             name: "PR Comments",
             description: "Fetch and analyze PR comments",
             prompt: agent_prompts::PR_COMMENTS,
-            allowed_tools: PR_COMMENTS_TOOLS,
+            allowed_tools: Cow::Borrowed(PR_COMMENTS_TOOLS),
             flags: AgentFlags {
                 read_only: true,
                 can_background: false,
@@ -207,7 +250,7 @@ static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
             name: "Security Review",
             description: "Security-focused code review",
             prompt: agent_prompts::SECURITY_REVIEW,
-            allowed_tools: SECURITY_REVIEW_TOOLS,
+            allowed_tools: Cow::Borrowed(SECURITY_REVIEW_TOOLS),
             flags: AgentFlags {
                 read_only: true,
                 can_background: false,
@@ -220,22 +263,50 @@ static AGENT_REGISTRY: Lazy<HashMap<&'static str, AgentType>> = Lazy::new(|| {
 });
 
 /// Get an agent type by ID
-pub fn get_agent_type(id: &str) -> Option<&'static AgentType> {
+pub fn get_agent_type(id: &str) -> Option<&'static AgentType<'static>> {
     AGENT_REGISTRY.get(id)
 }
 
 /// Get the default agent type (explore)
-pub fn get_default_agent_type() -> &'static AgentType {
+pub fn get_default_agent_type() -> &'static AgentType<'static> {
     AGENT_REGISTRY
         .get("explore")
         .expect("explore agent must exist")
 }
 
 /// Get all registered agent types
-pub fn get_all_agent_types() -> Vec<&'static AgentType> {
+pub fn get_all_agent_types() -> Vec<&'static AgentType<'static>> {
     AGENT_REGISTRY.values().collect()
 }
 
+/// The agent type `id` names: a built-in one, or else one from the
+/// project's `[custom_agents]`.
+pub fn resolve_agent_type<'a>(
+    id: &str,
+    custom_agents: &'a HashMap<String, CustomAgentConfig>,
+) -> Option<AgentType<'a>> {
+    if let Some(agent) = get_agent_type(id) {
+        return Some(agent.clone());
+    }
+    let (id, agent) = custom_agents.get_key_value(id)?;
+    let allowed_tools: Vec<&str> = match &agent.tools {
+        Some(tools) => tools.iter().map(|tool| tool.as_str()).collect(),
+        None => EXPLORE_TOOLS.to_vec(),
+    };
+    Some(AgentType {
+        id,
+        name: id,
+        description: &agent.description,
+        prompt: &agent.prompt,
+        flags: AgentFlags {
+            read_only: !allowed_tools.iter().any(|tool| WRITE_TOOLS.contains(tool)),
+            can_background: false,
+            no_tools: allowed_tools.is_empty(),
+        },
+        allowed_tools: Cow::Owned(allowed_tools),
+    })
+}
+
 /// Get a formatted list of agent types for use in prompts
 pub fn get_agent_types_description() -> String {
     let mut desc = String::new();
@@ -265,12 +336,14 @@ pub fn get_agent_type_infos() -> Vec<AgentTypeInfo> {
         .collect()
 }
 
-/// Replace {ALLOWED_TOOLS}, {PROJECT_PATH}, {CURRENT_DATE}, {TOOL:name}, {AGENT:id} in prompt.
+/// Replace {ALLOWED_TOOLS}, {PROJECT_PATH}, {CURRENT_DATE}, {TOOL:name}, {AGENT:id}
+/// and {VAR:name} in prompt. Fails if a placeholder is left unresolved.
 pub fn interpolate_prompt(
     prompt: &str,
     allowed_tools: &[&str],
     project_path: Option<&str>,
-) -> String {
+    variables: &HashMap<String, String>,
+) -> Result<String, AgentError> {
     let mut result = prompt.to_string();
 
     // Replace {ALLOWED_TOOLS}
@@ -299,7 +372,37 @@ pub fn interpolate_prompt(
         })
         .to_string();
 
-    result
+    let result = interpolate_variables(&result, variables)?;
+    check_resolved(&result, &BUILTIN_PATTERN)?;
+    Ok(result)
+}
+
+/// Replace {VAR:name} in prompt with `variables`, failing if one is not defined.
+pub fn interpolate_variables(
+    prompt: &str,
+    variables: &HashMap<String, String>,
+) -> Result<String, AgentError> {
+    let result = VAR_PATTERN
+        .replace_all(prompt, |caps: &regex::Captures| {
+            let whole = caps.get(0).map_or("", |m| m.as_str());
+            variables
+                .get(&caps[1])
+                .map_or_else(|| whole.to_string(), |value| value.clone())
+        })
+        .to_string();
+    check_resolved(&result, &VAR_PATTERN)?;
+    Ok(result)
+}
+
+fn check_resolved(prompt: &str, pattern: &Regex) -> Result<(), AgentError> {
+    let unresolved: BTreeSet<&str> = pattern.find_iter(prompt).map(|m| m.as_str()).collect();
+    if unresolved.is_empty() {
+        return Ok(());
+    }
+    Err(AgentError::ConfigError(format!(
+        "Unresolved prompt placeholders: {}. Define variables under [prompts.variables] or send them with the message",
+        unresolved.into_iter().collect::<Vec<_>>().join(", ")
+    )))
 }
 
 /// Format a system reminder for injection
@@ -327,6 +430,7 @@ mod tests {
             "summarize",
             "bash-summarize",
             "session-title",
+            "web-extract",
             "pr-review",
             "pr-comments",
             "security-review",
@@ -336,6 +440,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolve_custom_agent_type() {
+        let custom = HashMap::from([
+            (
+                "migration-check".to_string(),
+                CustomAgentConfig {
+                    description: "Checks database migrations".to_string(),
+                    prompt: "Review the migrations in {PROJECT_PATH}.".to_string(),
+                    tools: Some(vec!["read_file".to_string(), "edit_file".to_string()]),
+                },
+            ),
+            (
+                "explore".to_string(),
+                CustomAgentConfig {
+                    description: "Shadowed".to_string(),
+                    prompt: "Ignored".to_string(),
+                    tools: None,
+                },
+            ),
+        ]);
+
+        let agent = resolve_agent_type("migration-check", &custom).unwrap();
+        assert_eq!(agent.id, "migration-check");
+        assert_eq!(&*agent.allowed_tools, &["read_file", "edit_file"]);
+        assert!(!agent.flags.read_only);
+        assert!(!agent.flags.no_tools);
+
+        let builtin = resolve_agent_type("explore", &custom).unwrap();
+        assert_eq!(builtin.prompt, agent_prompts::EXPLORE);
+        assert!(resolve_agent_type("unknown", &custom).is_none());
+    }
+
     #[test]
     fn test_default_agent() {
         let agent = get_default_agent_type();
@@ -369,7 +505,8 @@ mod tests {
     #[test]
     fn test_interpolate_prompt() {
         let prompt = "Tools: {ALLOWED_TOOLS}, Date: {CURRENT_DATE}";
-        let result = interpolate_prompt(prompt, &["read_file", "glob"], None);
+        let result =
+            interpolate_prompt(prompt, &["read_file", "glob"], None, &HashMap::new()).unwrap();
         assert!(result.contains("read_file, glob"));
         assert!(result.contains("202")); // Year prefix
     }
@@ -377,14 +514,15 @@ mod tests {
     #[test]
     fn test_interpolate_prompt_with_project_path() {
         let prompt = "Path: {PROJECT_PATH}";
-        let result = interpolate_prompt(prompt, &[], Some("/test/project"));
+        let result =
+            interpolate_prompt(prompt, &[], Some("/test/project"), &HashMap::new()).unwrap();
         assert!(result.contains("/test/project"));
     }
 
     #[test]
     fn test_interpolate_prompt_tool_pattern() {
         let prompt = "Use {TOOL:read_file} to read files";
-        let result = interpolate_prompt(prompt, &[], None);
+        let result = interpolate_prompt(prompt, &[], None, &HashMap::new()).unwrap();
         assert!(result.contains("read_file"));
         assert!(!result.contains("{TOOL:"));
     }
@@ -392,11 +530,32 @@ mod tests {
     #[test]
     fn test_interpolate_prompt_agent_pattern() {
         let prompt = "Dispatch {AGENT:explore} agent";
-        let result = interpolate_prompt(prompt, &[], None);
+        let result = interpolate_prompt(prompt, &[], None, &HashMap::new()).unwrap();
         assert!(result.contains("explore"));
         assert!(!result.contains("{AGENT:"));
     }
 
+    #[test]
+    fn test_interpolate_prompt_variables() {
+        let variables = HashMap::from([("branch".to_string(), "main".to_string())]);
+        let prompt = "Work on {VAR:branch} in {PROJECT_PATH}";
+        let result = interpolate_prompt(prompt, &[], Some("/p"), &variables).unwrap();
+        assert_eq!(result, "Work on main in /p");
+    }
+
+    #[test]
+    fn test_interpolate_prompt_rejects_unresolved_placeholders() {
+        let err = interpolate_prompt("See {VAR:ticket_url}", &[], None, &HashMap::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("{VAR:ticket_url}"), "{}", err);
+
+        let err = interpolate_prompt("In {PROJECT_PATH}", &[], None, &HashMap::new())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("{PROJECT_PATH}"), "{}", err);
+    }
+
     #[test]
     fn test_format_system_reminder() {
         let reminder = format_system_reminder("Test reminder");
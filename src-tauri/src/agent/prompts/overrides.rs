@@ -0,0 +1,194 @@
+//! Project changes to the built-in agent prompts, from config
+//! (`agent_prompts`, `agent_prompt_notes`) or from files in
+//! `.devflow/agents/overrides/`: `<agent-id>.md` replaces a prompt and
+//! `<agent-id>.append.md` is appended to it.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::{get_agent_type, get_all_agent_types, AgentType};
+use crate::agent::error::AgentError;
+use crate::config::ProjectConfig;
+
+const OVERRIDES_DIR: &str = ".devflow/agents/overrides";
+const APPEND_SUFFIX: &str = ".append.md";
+
+#[derive(Debug, Clone, Default)]
+pub struct AgentPromptOverrides {
+    /// Replacement prompts by agent type id
+    replace: HashMap<String, String>,
+    /// Text appended to prompts by agent type id
+    append: HashMap<String, String>,
+}
+
+impl AgentPromptOverrides {
+    /// Collects the project's overrides, failing on unknown agent types and
+    /// on a prompt replaced both in config and by a file. Blank entries are
+    /// ignored so a cleared field falls back to the default.
+    pub fn load(project_path: &Path, config: &ProjectConfig) -> Result<Self, AgentError> {
+        let mut overrides = Self::default();
+        for (id, prompt) in config.agent_prompts.iter().flatten() {
+            overrides.insert_replace(id, prompt, "agent_prompts")?;
+        }
+        for (id, note) in &config.agent_prompt_notes {
+            overrides.insert_append(id, note, "agent_prompt_notes")?;
+        }
+
+        let dir = project_path.join(OVERRIDES_DIR);
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Ok(overrides);
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect();
+        files.sort();
+
+        for path in files {
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let source = format!("{}/{}", OVERRIDES_DIR, name);
+            let (id, append) = match name.strip_suffix(APPEND_SUFFIX) {
+                Some(id) => (id, true),
+                None => match name.strip_suffix(".md") {
+                    Some(id) => (id, false),
+                    None => continue,
+                },
+            };
+            let content = fs::read_to_string(&path).map_err(|e| {
+                AgentError::ConfigError(format!("Failed to read {}: {}", source, e))
+            })?;
+            if append {
+                if overrides.append.contains_key(id) {
+                    return Err(AgentError::ConfigError(format!(
+                        "Prompt notes for '{}' are set in both agent_prompt_notes and {}",
+                        id, source
+                    )));
+                }
+                overrides.insert_append(id, &content, &source)?;
+            } else {
+                if overrides.replace.contains_key(id) {
+                    return Err(AgentError::ConfigError(format!(
+                        "Prompt for '{}' is replaced in both agent_prompts and {}",
+                        id, source
+                    )));
+                }
+                overrides.insert_replace(id, &content, &source)?;
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// The prompt `agent` runs with: its replacement or built-in prompt, then
+    /// any appended text.
+    pub fn prompt_for(&self, agent: &AgentType) -> String {
+        let prompt = self
+            .replace
+            .get(agent.id)
+            .map(|p| p.as_str())
+            .unwrap_or(agent.prompt);
+        match self.append.get(agent.id) {
+            Some(note) => format!("{}\n\n{}", prompt.trim_end(), note.trim()),
+            None => prompt.to_string(),
+        }
+    }
+
+    fn insert_replace(&mut self, id: &str, prompt: &str, source: &str) -> Result<(), AgentError> {
+        check_agent_type(id, source)?;
+        if !prompt.trim().is_empty() {
+            self.replace.insert(id.to_string(), prompt.to_string());
+        }
+        Ok(())
+    }
+
+    fn insert_append(&mut self, id: &str, note: &str, source: &str) -> Result<(), AgentError> {
+        check_agent_type(id, source)?;
+        if !note.trim().is_empty() {
+            self.append.insert(id.to_string(), note.to_string());
+        }
+        Ok(())
+    }
+}
+
+fn check_agent_type(id: &str, source: &str) -> Result<(), AgentError> {
+    if get_agent_type(id).is_some() {
+        return Ok(());
+    }
+    let mut known: Vec<&str> = get_all_agent_types().iter().map(|a| a.id).collect();
+    known.sort();
+    Err(AgentError::ConfigError(format!(
+        "Unknown agent type '{}' in {}; expected one of: {}",
+        id,
+        source,
+        known.join(", ")
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(extra: &str) -> ProjectConfig {
+        toml::from_str(&format!(
+            r#"
+[agent]
+provider = "anthropic"
+model = "test"
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 5
+
+{extra}
+"#
+        ))
+        .expect("valid test config")
+    }
+
+    fn write_override(project: &Path, name: &str, content: &str) {
+        let dir = project.join(OVERRIDES_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(name), content).unwrap();
+    }
+
+    #[test]
+    fn test_config_and_files_replace_and_append() {
+        let temp = TempDir::new().unwrap();
+        write_override(temp.path(), "plan.md", "Custom plan prompt");
+        write_override(temp.path(), "explore.append.md", "Prefer src/ first.");
+        write_override(temp.path(), "README.txt", "ignored");
+        let config = config("[agent_prompts]\nsummarize = \"Short summaries\"");
+
+        let overrides = AgentPromptOverrides::load(temp.path(), &config).unwrap();
+        let prompt = |id| overrides.prompt_for(get_agent_type(id).unwrap());
+        assert_eq!(prompt("plan"), "Custom plan prompt");
+        assert_eq!(prompt("summarize"), "Short summaries");
+        let explore = prompt("explore");
+        assert!(explore.starts_with(get_agent_type("explore").unwrap().prompt.trim_end()));
+        assert!(explore.ends_with("\n\nPrefer src/ first."));
+    }
+
+    #[test]
+    fn test_unknown_agent_type_is_rejected() {
+        let temp = TempDir::new().unwrap();
+        write_override(temp.path(), "reviewer.md", "Review things");
+
+        let err = AgentPromptOverrides::load(temp.path(), &config(""))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unknown agent type 'reviewer'"), "{}", err);
+    }
+
+    #[test]
+    fn test_replacing_in_config_and_file_is_rejected() {
+        let temp = TempDir::new().unwrap();
+        write_override(temp.path(), "plan.md", "From file");
+        let config = config("[agent_prompts]\nplan = \"From config\"");
+
+        assert!(AgentPromptOverrides::load(temp.path(), &config).is_err());
+    }
+}
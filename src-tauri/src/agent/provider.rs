@@ -1,13 +1,13 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use tauri::AppHandle;
 use tokio_util::sync::CancellationToken;
 
 use super::error::AgentError;
+use super::events::EventSink;
 use super::tools::SessionState;
-use super::types::{ChatMessage, ToolDefinition};
-use super::usage::SessionUsageTracker;
+use super::types::{ChatMessage, SendOptions, ToolDefinition};
+use super::usage::{SessionUsageTracker, TokenUsage};
 
 /// Result from headless execution
 #[derive(Debug)]
@@ -16,6 +16,8 @@ pub struct HeadlessResult {
     pub tool_calls_made: u32,
     /// Stop reason from the final response (end_turn, tool_use, max_tokens)
     pub stop_reason: Option<String>,
+    /// Parsed answer when a response schema was requested, already validated
+    pub structured: Option<serde_json::Value>,
 }
 
 /// Runtime context for agent execution
@@ -33,19 +35,33 @@ pub trait ProviderAdapter: Send + Sync {
         messages: Vec<ChatMessage>,
         system_prompt: Option<String>,
         memory: Option<String>,
+        options: SendOptions,
         ctx: ExecutionContext,
-        app_handle: AppHandle,
+        events: EventSink,
     ) -> Result<(), AgentError>;
 
-    /// Run headless without UI - for sub-agents
+    /// Run headless without UI - for sub-agents and automations.
+    /// With a `response_schema`, the final answer is returned as validated JSON.
     async fn run_headless(
         &self,
         messages: Vec<ChatMessage>,
         system_prompt: Option<String>,
         memory: Option<String>,
         tools: Vec<ToolDefinition>,
+        response_schema: Option<serde_json::Value>,
         ctx: ExecutionContext,
     ) -> Result<HeadlessResult, AgentError>;
 
+    /// One-shot completion without tools, the kind used to extract facts
+    /// when compacting; returns the text with its token usage.
+    async fn complete(
+        &self,
+        prompt: String,
+        cancel_token: &CancellationToken,
+    ) -> Result<(String, TokenUsage), AgentError>;
+
     fn model(&self) -> &str;
+
+    /// Provider id from the config, e.g. "anthropic"
+    fn provider(&self) -> &str;
 }
@@ -3,112 +3,234 @@ mod types;
 use std::env;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use futures::StreamExt;
 use reqwest::Client;
-use tauri::{AppHandle, Emitter};
+use tauri::Runtime;
 use tokio_util::sync::CancellationToken;
-use uuid::Uuid;
 
+use crate::agent::attribution::Attribution;
 use crate::agent::error::AgentError;
+use crate::agent::events::EventSink;
+use crate::agent::http::build_provider_client;
+use crate::agent::models;
 use crate::agent::provider::{ExecutionContext, HeadlessResult, ProviderAdapter};
-use crate::agent::tools::{get_tool_definitions, SessionState};
+use crate::agent::rate_limit::{self, estimate_request_tokens, shared_rate_limiter, RateLimiter};
+use crate::agent::request_cache::{self, shared_request_cache, RequestCache};
+use crate::agent::structured::STRUCTURED_OUTPUT_TOOL;
+use crate::agent::tools::ToolDescriptionOverrides;
 use crate::agent::types::{
-    AgentCancelledPayload, AgentChunkPayload, AgentCompletePayload, AgentErrorPayload, AgentStatus,
-    ChatMessage, ContentBlockStartPayload, ContentBlockType, ToolDefinition,
+    AgentChunkPayload, AgentStatus, ChatMessage, ContentBlockStartPayload, ContentBlockType,
+    SendOptions, ToolChoice, ToolDefinition,
+};
+use crate::agent::usage::TokenUsage;
+use crate::agent::usage_history::record_usage;
+use crate::config::{
+    AgentConfig, AutoApproveConfig, ExecutionConfig, LoggingConfig, NetworkConfig, ProjectConfig,
+    PromptsConfig, ProviderTimeouts, WorkspaceConfig,
 };
-use crate::agent::usage::{SessionUsageTracker, UsageSource};
-use crate::config::{AgentConfig, ExecutionConfig, PromptsConfig};
 
 use super::{
-    build_system_prompt, check_iteration_limit,
-    compaction::{format_compacted_context, get_context_limit, maybe_compact, CompactionContext},
-    create_executor, emit_status, emit_usage, execute_tool_calls,
+    compaction::{get_compaction_threshold, get_context_limit},
+    compaction_adapter, emit_error, emit_rate_limited, emit_slow_response, emit_status,
     headless::{
         HeadlessResponse, HeadlessStreamer, ToolCall as HeadlessToolCall,
         ToolResult as HeadlessToolResult,
     },
-    run_headless_loop, HeadlessContext, StreamContext, StreamingState, ToolCall,
+    orchestration,
+    sse::{self, SseEvent},
+    ProviderRuntime, RunSettings, SlowResponseWatch, StreamContext, StreamedTurn,
 };
 use types::{
     AnthropicErrorResponse, AnthropicEvent, AnthropicMessage, AnthropicRequest, ContentBlock,
     ContentDelta, MessageContent, StreamedResponse,
 };
 
-const API_URL: &str = "https://api.anthropic.com/v1/messages";
-const API_VERSION: &str = "2023-06-01";
+pub(super) const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+pub(super) const API_VERSION: &str = "2023-06-01";
+
+/// Appends user text to the conversation, joining a trailing user turn (e.g.
+/// tool results) since the API requires roles to alternate.
+fn push_user_text(conversation: &mut Vec<AnthropicMessage>, text: String) {
+    if let Some(last) = conversation.last_mut().filter(|m| m.role == "user") {
+        let block = ContentBlock::Text { text };
+        match &mut last.content {
+            MessageContent::Blocks(blocks) => blocks.push(block),
+            MessageContent::Text(existing) => {
+                let existing = ContentBlock::Text {
+                    text: std::mem::take(existing),
+                };
+                last.content = MessageContent::Blocks(vec![existing, block]);
+            }
+        }
+        return;
+    }
+
+    conversation.push(AnthropicMessage {
+        role: "user".to_string(),
+        content: MessageContent::Text(text),
+    });
+}
 
 pub struct AnthropicAdapter {
     client: Client,
     config: AgentConfig,
     prompts: PromptsConfig,
     execution: ExecutionConfig,
+    network: NetworkConfig,
+    attribution: Option<Attribution>,
+    logging: LoggingConfig,
+    auto_approve: AutoApproveConfig,
+    workspace: WorkspaceConfig,
+    timeouts: ProviderTimeouts,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request_cache: Option<Arc<RequestCache>>,
     api_key: String,
+    api_url: String,
     project_path: PathBuf,
     app_system_prompt: &'static str,
     context_limit: u32,
+    compaction_threshold: f64,
     extraction_prompt: Option<String>,
+    compaction_adapter: Option<Arc<dyn ProviderAdapter>>,
+    tool_descriptions: ToolDescriptionOverrides,
+}
+
+/// `tool_choice` request field; None leaves the API default (auto).
+fn request_tool_choice(choice: ToolChoice) -> Option<serde_json::Value> {
+    match choice {
+        ToolChoice::Auto => None,
+        ToolChoice::Required => Some(serde_json::json!({"type": "any"})),
+        ToolChoice::None => Some(serde_json::json!({"type": "none"})),
+    }
 }
 
 impl AnthropicAdapter {
     pub fn new(
-        config: AgentConfig,
-        prompts: PromptsConfig,
-        execution: ExecutionConfig,
+        project_config: ProjectConfig,
         project_path: PathBuf,
         app_system_prompt: &'static str,
-        extraction_prompt: Option<String>,
     ) -> Result<Self, AgentError> {
+        let timeouts = project_config.provider_timeouts();
+        let tool_descriptions = ToolDescriptionOverrides::from_config(&project_config);
+        let rate_limiter = shared_rate_limiter(
+            &project_config.agent.provider,
+            &project_config.provider_rate_limit(),
+        );
+        let request_cache = shared_request_cache(
+            &project_config.agent.provider,
+            &project_config.request_cache,
+        );
+        let compaction_adapter = compaction_adapter(&project_config, &project_path)?;
+        let mut config = project_config.agent;
+        config.max_tokens = models::clamp_max_tokens(&config.model, config.max_tokens);
+        config.temperature = config
+            .temperature
+            .map(|temperature| models::clamp_temperature("anthropic", temperature));
         let api_key = env::var(&config.api_key_env)
             .map_err(|_| AgentError::MissingApiKey(config.api_key_env.clone()))?;
 
-        let context_limit = get_context_limit(config.context_limit);
+        let context_limit = get_context_limit(config.context_limit, &config.model);
+        let compaction_threshold = get_compaction_threshold(config.compaction_threshold);
+        let attribution = Attribution::from_config(&project_config.attribution, &config.model);
+        let api_url = format!(
+            "{}/v1/messages",
+            config
+                .base_url
+                .as_deref()
+                .unwrap_or(DEFAULT_BASE_URL)
+                .trim_end_matches('/')
+        );
 
         Ok(Self {
-            client: Client::new(),
+            client: build_provider_client(&project_config.network, &timeouts)?,
             config,
-            prompts,
-            execution,
+            prompts: project_config.prompts,
+            execution: project_config.execution,
+            network: project_config.network,
+            attribution,
+            logging: project_config.logging,
+            auto_approve: project_config.auto_approve,
+            workspace: project_config.workspace,
+            timeouts,
+            rate_limiter,
+            request_cache,
             api_key,
+            api_url,
             project_path,
             app_system_prompt,
             context_limit,
-            extraction_prompt,
+            compaction_threshold,
+            extraction_prompt: project_config.extraction_prompt,
+            compaction_adapter,
+            tool_descriptions,
         })
     }
 
-    async fn stream_response(
+    fn top_p(&self) -> Option<f32> {
+        self.config.anthropic.as_ref()?.top_p
+    }
+
+    fn stop_sequences(&self) -> Vec<String> {
+        self.config
+            .anthropic
+            .as_ref()
+            .map(|o| o.stop_sequences.clone())
+            .unwrap_or_default()
+    }
+
+    async fn stream_response<R: Runtime>(
         &self,
         messages: &[AnthropicMessage],
         system: Option<String>,
-        ctx: &StreamContext<'_>,
+        tools: &[ToolDefinition],
+        tool_choice: ToolChoice,
+        ctx: &StreamContext<'_, R>,
     ) -> Result<StreamedResponse, AgentError> {
         if ctx.cancel_token.is_cancelled() {
             return Err(AgentError::Cancelled);
         }
 
-        let tools = get_tool_definitions();
-
         let request = AnthropicRequest {
             model: self.config.model.clone(),
             max_tokens: self.config.max_tokens,
             messages: messages.to_vec(),
             stream: true,
+            temperature: self.config.temperature,
+            top_p: self.top_p(),
+            stop_sequences: self.stop_sequences(),
             system,
-            tools: Some(tools),
+            tools: Some(tools.to_vec()),
+            tool_choice: request_tool_choice(tool_choice),
         };
 
-        emit_status(ctx.app_handle, AgentStatus::Thinking, None);
+        let estimated_tokens = estimate_request_tokens(&request);
+        rate_limit::acquire(
+            self.rate_limiter.as_deref(),
+            estimated_tokens,
+            ctx.cancel_token,
+            |wait| emit_rate_limited(ctx.events, wait),
+        )
+        .await?;
 
-        let response = self
-            .client
-            .post(API_URL)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", API_VERSION)
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
+        emit_status(ctx.events, AgentStatus::Thinking, None);
+
+        let threshold = Duration::from_secs(self.timeouts.slow_response_secs);
+        let watch = SlowResponseWatch::new(threshold, |elapsed| {
+            emit_slow_response(ctx.events, elapsed, threshold)
+        });
+
+        let response = watch
+            .guard(
+                self.client
+                    .post(&self.api_url)
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", API_VERSION)
+                    .header("content-type", "application/json")
+                    .json(&request)
+                    .send(),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -122,72 +244,42 @@ impl AnthropicAdapter {
         }
 
         let mut streamed = StreamedResponse::new();
-        let mut stream = response.bytes_stream();
-        let mut buffer = String::new();
         let mut first_text_chunk = true;
-
-        loop {
-            if ctx.cancel_token.is_cancelled() {
-                return Err(AgentError::Cancelled);
+        let idle_timeout = Duration::from_secs(self.timeouts.stream_idle_secs);
+        sse::read_events(response, ctx.cancel_token, &watch, idle_timeout, |event| {
+            let is_text = self.process_sse_event(event, ctx, &mut streamed);
+            if streamed.first_token_at.is_none() && streamed.block_count() > 0 {
+                streamed.first_token_at = Some(Instant::now());
             }
-
-            tokio::select! {
-                _ = ctx.cancel_token.cancelled() => {
-                    return Err(AgentError::Cancelled);
-                }
-                chunk_result = stream.next() => {
-                    match chunk_result {
-                        Some(Ok(chunk)) => {
-                            buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                            while let Some(event_end) = buffer.find("\n\n") {
-                                let event_data = buffer[..event_end].to_string();
-                                buffer = buffer[event_end + 2..].to_string();
-
-                                let is_text = self.process_sse_event(&event_data, ctx, &mut streamed);
-
-                                if is_text && first_text_chunk {
-                                    emit_status(ctx.app_handle, AgentStatus::Streaming, None);
-                                    first_text_chunk = false;
-                                }
-                            }
-                        }
-                        Some(Err(e)) => {
-                            return Err(AgentError::Http(e));
-                        }
-                        None => break,
-                    }
-                }
+            if is_text && first_text_chunk {
+                emit_status(ctx.events, AgentStatus::Streaming, None);
+                first_text_chunk = false;
             }
-        }
+        })
+        .await?;
 
+        rate_limit::settle(
+            self.rate_limiter.as_deref(),
+            estimated_tokens,
+            streamed.usage,
+        );
         Ok(streamed)
     }
 
-    fn process_sse_event(
+    fn process_sse_event<R: Runtime>(
         &self,
-        event_data: &str,
-        ctx: &StreamContext<'_>,
+        sse_event: &SseEvent,
+        ctx: &StreamContext<'_, R>,
         streamed: &mut StreamedResponse,
     ) -> bool {
-        let mut data = None;
         let mut is_text_delta = false;
 
-        for line in event_data.lines() {
-            if let Some(suffix) = line.strip_prefix("data: ") {
-                data = Some(suffix.to_string());
-            }
-        }
-
-        let Some(data) = data else { return false };
-
-        if data == "[DONE]" {
+        if sse_event.data == "[DONE]" {
             return false;
         }
 
-        let event: AnthropicEvent = match serde_json::from_str(&data) {
-            Ok(e) => e,
-            Err(_) => return false,
+        let Some(event) = sse_event.json::<AnthropicEvent>() else {
+            return false;
         };
 
         match event {
@@ -207,7 +299,7 @@ impl AnthropicAdapter {
                     ContentBlock::ToolResult { .. } => return false,
                 };
 
-                let _ = ctx.app_handle.emit(
+                let _ = ctx.events.emit(
                     "agent-content-block-start",
                     ContentBlockStartPayload {
                         block_index: global_index,
@@ -218,13 +310,14 @@ impl AnthropicAdapter {
             AnthropicEvent::ContentBlockDelta { index, delta } => {
                 if let ContentDelta::TextDelta { ref text } = delta {
                     let global_index = ctx.block_offset + index;
-                    let _ = ctx.app_handle.emit(
+                    let _ = ctx.events.emit(
                         "agent-chunk",
                         AgentChunkPayload {
                             delta: text.clone(),
                             block_index: global_index,
                         },
                     );
+                    ctx.partial.push_text(global_index, text);
                     is_text_delta = true;
                 }
                 streamed.on_content_delta(index, delta);
@@ -239,12 +332,7 @@ impl AnthropicAdapter {
                 streamed.on_message_delta(delta, usage);
             }
             AnthropicEvent::Error { error } => {
-                let _ = ctx.app_handle.emit(
-                    "agent-error",
-                    AgentErrorPayload {
-                        error: error.message,
-                    },
-                );
+                emit_error(ctx.events, error.message);
             }
             _ => {}
         }
@@ -252,97 +340,11 @@ impl AnthropicAdapter {
         is_text_delta
     }
 
-    async fn execute_tool_loop(
-        &self,
-        initial_messages: Vec<AnthropicMessage>,
-        system_prompt: Option<String>,
-        session: SessionState,
-        app_handle: &AppHandle,
-        cancel_token: &CancellationToken,
-        usage_tracker: &Arc<SessionUsageTracker>,
-    ) -> Result<Option<String>, AgentError> {
-        let executor = create_executor(
-            &self.project_path,
-            &self.execution,
-            session.clone(),
-            cancel_token.clone(),
-            Arc::clone(usage_tracker),
-        );
-        let mut conversation = initial_messages;
-        let max_iterations = self.execution.max_tool_iterations;
-        let mut iteration = 0u32;
-        let mut streaming = StreamingState::new();
-
-        loop {
-            if cancel_token.is_cancelled() {
-                return Err(AgentError::Cancelled);
-            }
-
-            let ctx = streaming.create_context(app_handle, cancel_token);
-            let response = self
-                .stream_response(&conversation, system_prompt.clone(), &ctx)
-                .await?;
-
-            emit_usage(app_handle, usage_tracker, response.usage, UsageSource::Main);
-            streaming.advance(response.block_count());
-
-            if !response.has_tool_use() {
-                return Ok(response.stop_reason);
-            }
-
-            iteration += 1;
-            check_iteration_limit(iteration, max_iterations)?;
-
-            conversation.push(AnthropicMessage {
-                role: "assistant".to_string(),
-                content: MessageContent::Blocks(response.content_blocks.clone()),
-            });
-
-            let tool_calls: Vec<ToolCall> = response
-                .content_blocks
-                .iter()
-                .enumerate()
-                .filter_map(|(index, block)| {
-                    if let ContentBlock::ToolUse { id, name, input } = block {
-                        Some(ToolCall {
-                            id: id.clone(),
-                            name: name.clone(),
-                            input: input.clone(),
-                            block_index: ctx.block_offset + index as u32,
-                        })
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-
-            let results =
-                execute_tool_calls(tool_calls, &executor, &session, app_handle, cancel_token)
-                    .await?;
-
-            let tool_results: Vec<ContentBlock> = results
-                .into_iter()
-                .map(|r| ContentBlock::ToolResult {
-                    tool_use_id: r.id,
-                    content: r.output,
-                    is_error: if r.is_error { Some(true) } else { None },
-                })
-                .collect();
-
-            conversation.push(AnthropicMessage {
-                role: "user".to_string(),
-                content: MessageContent::Blocks(tool_results),
-            });
-
-            emit_status(app_handle, AgentStatus::ToolWaiting, None);
-        }
-    }
-
     async fn call_extraction_api(
         &self,
         prompt: String,
         cancel_token: &CancellationToken,
-    ) -> Result<String, AgentError> {
+    ) -> Result<(String, TokenUsage), AgentError> {
         if cancel_token.is_cancelled() {
             return Err(AgentError::Cancelled);
         }
@@ -355,13 +357,33 @@ impl AnthropicAdapter {
                 content: MessageContent::Text(prompt),
             }],
             stream: false,
+            temperature: None,
+            top_p: None,
+            stop_sequences: Vec::new(),
             system: Some("You are a precise assistant that extracts and summarizes information. Always respond with valid JSON.".to_string()),
             tools: None,
+            tool_choice: None,
         };
 
+        let cache_key = request_cache::cache_key(self.request_cache.as_deref(), &request);
+        if let Some(text) =
+            request_cache::lookup::<String>(self.request_cache.as_deref(), cache_key.as_ref())
+        {
+            // Nothing was sent, so there is no usage to record
+            return Ok((text, TokenUsage::default()));
+        }
+
+        rate_limit::acquire(
+            self.rate_limiter.as_deref(),
+            estimate_request_tokens(&request),
+            cancel_token,
+            |_| {},
+        )
+        .await?;
+
         let response = self
             .client
-            .post(API_URL)
+            .post(&self.api_url)
             .header("x-api-key", &self.api_key)
             .header("anthropic-version", API_VERSION)
             .header("content-type", "application/json")
@@ -391,7 +413,13 @@ impl AnthropicAdapter {
             .unwrap_or("")
             .to_string();
 
-        Ok(text)
+        let usage = TokenUsage {
+            input_tokens: json["usage"]["input_tokens"].as_u64().unwrap_or(0) as u32,
+            output_tokens: json["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32,
+        };
+
+        request_cache::store(self.request_cache.as_deref(), cache_key, &text);
+        Ok((text, usage))
     }
 
     async fn stream_response_headless(
@@ -399,29 +427,76 @@ impl AnthropicAdapter {
         messages: &[AnthropicMessage],
         system: Option<String>,
         tools: &[ToolDefinition],
+        response_schema: Option<&serde_json::Value>,
         cancel_token: &CancellationToken,
     ) -> Result<StreamedResponse, AgentError> {
         if cancel_token.is_cancelled() {
             return Err(AgentError::Cancelled);
         }
 
+        // Structured output: the answer is the input of a tool the model must call
+        let (tools, tool_choice) = match response_schema {
+            Some(schema) => (
+                vec![ToolDefinition {
+                    name: STRUCTURED_OUTPUT_TOOL.to_string(),
+                    description: "Return the final answer.".to_string(),
+                    input_schema: schema.clone(),
+                }],
+                Some(serde_json::json!({"type": "tool", "name": STRUCTURED_OUTPUT_TOOL})),
+            ),
+            None => (tools.to_vec(), None),
+        };
+
         let request = AnthropicRequest {
             model: self.config.model.clone(),
             max_tokens: self.config.max_tokens,
             messages: messages.to_vec(),
             stream: true,
+            temperature: self.config.temperature,
+            top_p: self.top_p(),
+            stop_sequences: self.stop_sequences(),
             system,
-            tools: Some(tools.to_vec()),
+            tools: Some(tools),
+            tool_choice,
         };
 
-        let response = self
-            .client
-            .post(API_URL)
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", API_VERSION)
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
+        let cache_key = request_cache::cache_key(self.request_cache.as_deref(), &request);
+        if let Some(mut cached) = request_cache::lookup::<StreamedResponse>(
+            self.request_cache.as_deref(),
+            cache_key.as_ref(),
+        ) {
+            // Nothing was sent, so there is no usage to record
+            cached.usage = Default::default();
+            return Ok(cached);
+        }
+
+        let estimated_tokens = estimate_request_tokens(&request);
+        rate_limit::acquire(
+            self.rate_limiter.as_deref(),
+            estimated_tokens,
+            cancel_token,
+            |wait| log::info!("Rate limited, waiting {:?}", wait),
+        )
+        .await?;
+
+        let threshold = Duration::from_secs(self.timeouts.slow_response_secs);
+        let watch = SlowResponseWatch::new(threshold, |elapsed| {
+            log::warn!(
+                "Sub-agent response slow, nothing received for {:?}",
+                elapsed
+            )
+        });
+
+        let response = watch
+            .guard(
+                self.client
+                    .post(&self.api_url)
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", API_VERSION)
+                    .header("content-type", "application/json")
+                    .json(&request)
+                    .send(),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -435,59 +510,28 @@ impl AnthropicAdapter {
         }
 
         let mut streamed = StreamedResponse::new();
-        let mut stream = response.bytes_stream();
-        let mut buffer = String::new();
-
-        loop {
-            if cancel_token.is_cancelled() {
-                return Err(AgentError::Cancelled);
-            }
-
-            tokio::select! {
-                _ = cancel_token.cancelled() => {
-                    return Err(AgentError::Cancelled);
-                }
-                chunk_result = stream.next() => {
-                    match chunk_result {
-                        Some(Ok(chunk)) => {
-                            buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                            while let Some(event_end) = buffer.find("\n\n") {
-                                let event_data = buffer[..event_end].to_string();
-                                buffer = buffer[event_end + 2..].to_string();
-                                self.process_headless_sse_event(&event_data, &mut streamed);
-                            }
-                        }
-                        Some(Err(e)) => {
-                            return Err(AgentError::Http(e));
-                        }
-                        None => break,
-                    }
-                }
-            }
-        }
+        let idle_timeout = Duration::from_secs(self.timeouts.stream_idle_secs);
+        sse::read_events(response, cancel_token, &watch, idle_timeout, |event| {
+            self.process_headless_sse_event(event, &mut streamed)
+        })
+        .await?;
 
+        rate_limit::settle(
+            self.rate_limiter.as_deref(),
+            estimated_tokens,
+            streamed.usage,
+        );
+        request_cache::store(self.request_cache.as_deref(), cache_key, &streamed);
         Ok(streamed)
     }
 
-    fn process_headless_sse_event(&self, event_data: &str, streamed: &mut StreamedResponse) {
-        let mut data = None;
-
-        for line in event_data.lines() {
-            if let Some(suffix) = line.strip_prefix("data: ") {
-                data = Some(suffix.to_string());
-            }
-        }
-
-        let Some(data) = data else { return };
-
-        if data == "[DONE]" {
+    fn process_headless_sse_event(&self, sse_event: &SseEvent, streamed: &mut StreamedResponse) {
+        if sse_event.data == "[DONE]" {
             return;
         }
 
-        let event: AnthropicEvent = match serde_json::from_str(&data) {
-            Ok(e) => e,
-            Err(_) => return,
+        let Some(event) = sse_event.json::<AnthropicEvent>() else {
+            return;
         };
 
         match event {
@@ -565,13 +609,37 @@ impl HeadlessStreamer for AnthropicAdapter {
         conversation: &Self::Conversation,
         system_prompt: Option<String>,
         tools: &[ToolDefinition],
+        response_schema: Option<&serde_json::Value>,
         cancel_token: &CancellationToken,
     ) -> Result<HeadlessResponse, AgentError> {
         let response = self
-            .stream_response_headless(conversation, system_prompt, tools, cancel_token)
+            .stream_response_headless(
+                conversation,
+                system_prompt,
+                tools,
+                response_schema,
+                cancel_token,
+            )
             .await?;
+        record_usage(
+            &self.project_path,
+            &self.config.provider,
+            &self.config.model,
+            response.usage,
+        );
 
-        Ok(self.to_headless_response(&response))
+        let mut headless = self.to_headless_response(&response);
+        if response_schema.is_some() {
+            // Hand the forced tool call back as the JSON answer text
+            if let Some(call) = headless
+                .tool_calls
+                .drain(..)
+                .find(|tc| tc.name == STRUCTURED_OUTPUT_TOOL)
+            {
+                headless.text = call.input.to_string();
+            }
+        }
+        Ok(headless)
     }
 
     fn append_assistant_response(
@@ -621,109 +689,107 @@ impl HeadlessStreamer for AnthropicAdapter {
             content: MessageContent::Blocks(blocks),
         });
     }
+
+    fn append_user_text(&self, conversation: &mut Self::Conversation, text: String) {
+        push_user_text(conversation, text);
+    }
 }
 
-#[async_trait]
-impl ProviderAdapter for AnthropicAdapter {
-    async fn send_message(
+impl AnthropicAdapter {
+    /// Same as `ProviderAdapter::send_message`, for any Tauri runtime (the mock one in tests).
+    pub async fn send_message_with_runtime<R: Runtime>(
         &self,
         messages: Vec<ChatMessage>,
         system_prompt: Option<String>,
         memory: Option<String>,
+        options: SendOptions,
         ctx: ExecutionContext,
-        app_handle: AppHandle,
+        events: EventSink<R>,
     ) -> Result<(), AgentError> {
-        // Build system prompt first
-        let base_system = build_system_prompt(
-            self.app_system_prompt,
-            &self.prompts,
-            system_prompt,
-            memory.as_deref(),
-        );
-
-        let message_id = Uuid::new_v4().to_string();
-
-        emit_status(&app_handle, AgentStatus::Sending, None);
+        orchestration::send_message(self, messages, system_prompt, memory, options, ctx, events)
+            .await
+    }
+}
 
-        // Check if compaction is needed
-        let compaction_ctx = CompactionContext {
+#[async_trait]
+impl ProviderRuntime for AnthropicAdapter {
+    fn settings(&self) -> RunSettings<'_> {
+        RunSettings {
+            provider: &self.config.provider,
+            model: &self.config.model,
+            api_key: &self.api_key,
+            project_path: &self.project_path,
+            app_system_prompt: self.app_system_prompt,
+            prompts: &self.prompts,
+            execution: &self.execution,
+            network: &self.network,
+            logging: &self.logging,
+            auto_approve: &self.auto_approve,
+            workspace: &self.workspace,
+            attribution: self.attribution.as_ref(),
             context_limit: self.context_limit,
+            compaction_threshold: self.compaction_threshold,
             extraction_prompt: self.extraction_prompt.as_deref(),
-            session: &ctx.session,
-            app_handle: &app_handle,
-        };
+            compaction_adapter: self.compaction_adapter.as_deref(),
+            tool_descriptions: &self.tool_descriptions,
+        }
+    }
 
-        let compaction_result =
-            maybe_compact(&messages, Some(&base_system), &compaction_ctx, |prompt| {
-                self.call_extraction_api(prompt, &ctx.cancel_token)
-            })
-            .await?;
+    async fn extract(
+        &self,
+        prompt: String,
+        cancel_token: &CancellationToken,
+    ) -> Result<(String, TokenUsage), AgentError> {
+        let (text, usage) = self.call_extraction_api(prompt, cancel_token).await?;
+        record_usage(
+            &self.project_path,
+            &self.config.provider,
+            &self.config.model,
+            usage,
+        );
+        Ok((text, usage))
+    }
 
-        // Prepare messages and system prompt based on compaction result
-        let (final_messages, final_system) = match compaction_result {
-            Some(result) => {
-                let system_with_context = format!("{}\n\n{}", base_system, result.compacted_text);
-                (result.preserved_messages, system_with_context)
-            }
-            None => {
-                let existing = ctx.session.get_compacted().await;
-                if existing.summary.is_some() || !existing.facts.is_empty() {
-                    let compacted_text = format_compacted_context(&existing);
-                    let system_with_context = format!("{}\n\n{}", base_system, compacted_text);
-                    (messages.clone(), system_with_context)
-                } else {
-                    (messages.clone(), base_system)
-                }
-            }
-        };
+    async fn stream_turn<R: Runtime>(
+        &self,
+        conversation: &Self::Conversation,
+        system_prompt: Option<String>,
+        tools: &[ToolDefinition],
+        tool_choice: ToolChoice,
+        ctx: &StreamContext<'_, R>,
+    ) -> Result<StreamedTurn, AgentError> {
+        let response = self
+            .stream_response(conversation, system_prompt, tools, tool_choice, ctx)
+            .await?;
+        let tool_blocks = response
+            .content_blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| matches!(block, ContentBlock::ToolUse { .. }))
+            .map(|(index, _)| index as u32)
+            .collect();
+        Ok(StreamedTurn {
+            response: self.to_headless_response(&response),
+            tool_blocks,
+            block_count: response.block_count(),
+            first_token_at: response.first_token_at,
+        })
+    }
+}
 
-        let anthropic_messages: Vec<AnthropicMessage> =
-            final_messages.iter().map(AnthropicMessage::from).collect();
-
-        let result = self
-            .execute_tool_loop(
-                anthropic_messages,
-                Some(final_system),
-                ctx.session,
-                &app_handle,
-                &ctx.cancel_token,
-                &ctx.usage_tracker,
-            )
-            .await;
-
-        match result {
-            Ok(stop_reason) => {
-                emit_status(&app_handle, AgentStatus::Idle, None);
-                let _ = app_handle.emit(
-                    "agent-complete",
-                    AgentCompletePayload {
-                        message_id,
-                        stop_reason,
-                    },
-                );
-                Ok(())
-            }
-            Err(AgentError::Cancelled) => {
-                emit_status(&app_handle, AgentStatus::Cancelled, None);
-                let _ = app_handle.emit(
-                    "agent-cancelled",
-                    AgentCancelledPayload {
-                        reason: "Cancelled by user".to_string(),
-                    },
-                );
-                Err(AgentError::Cancelled)
-            }
-            Err(e) => {
-                emit_status(&app_handle, AgentStatus::Error, Some(e.to_string()));
-                let _ = app_handle.emit(
-                    "agent-error",
-                    AgentErrorPayload {
-                        error: e.to_string(),
-                    },
-                );
-                Err(e)
-            }
-        }
+#[async_trait]
+impl ProviderAdapter for AnthropicAdapter {
+    async fn send_message(
+        &self,
+        messages: Vec<ChatMessage>,
+        system_prompt: Option<String>,
+        memory: Option<String>,
+        options: SendOptions,
+        ctx: ExecutionContext,
+        events: EventSink,
+    ) -> Result<(), AgentError> {
+        self.send_message_with_runtime(messages, system_prompt, memory, options, ctx, events)
+            .await
     }
 
     async fn run_headless(
@@ -732,38 +798,34 @@ impl ProviderAdapter for AnthropicAdapter {
         system_prompt: Option<String>,
         memory: Option<String>,
         tools: Vec<ToolDefinition>,
+        response_schema: Option<serde_json::Value>,
         ctx: ExecutionContext,
     ) -> Result<HeadlessResult, AgentError> {
-        let system = build_system_prompt(
-            self.app_system_prompt,
-            &self.prompts,
-            system_prompt,
-            memory.as_deref(),
-        );
-        let executor = create_executor(
-            &self.project_path,
-            &self.execution,
-            ctx.session,
-            ctx.cancel_token.clone(),
-            Arc::clone(&ctx.usage_tracker),
-        );
-
-        run_headless_loop(
+        orchestration::run_headless(
             self,
             messages,
-            HeadlessContext {
-                system_prompt: Some(system),
-                tools,
-                executor: &executor,
-                max_iterations: self.execution.max_tool_iterations,
-                cancel_token: &ctx.cancel_token,
-                usage_tracker: ctx.usage_tracker,
-            },
+            system_prompt,
+            memory,
+            tools,
+            response_schema,
+            ctx,
         )
         .await
     }
 
+    async fn complete(
+        &self,
+        prompt: String,
+        cancel_token: &CancellationToken,
+    ) -> Result<(String, TokenUsage), AgentError> {
+        ProviderRuntime::extract(self, prompt, cancel_token).await
+    }
+
     fn model(&self) -> &str {
         &self.config.model
     }
+
+    fn provider(&self) -> &str {
+        &self.config.provider
+    }
 }
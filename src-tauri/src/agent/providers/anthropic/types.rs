@@ -1,3 +1,5 @@
+use std::time::Instant;
+
 use serde::{Deserialize, Serialize};
 
 use crate::agent::types::{ChatMessage, MessageRole, ToolDefinition};
@@ -12,9 +14,17 @@ pub struct AnthropicRequest {
     pub messages: Vec<AnthropicMessage>,
     pub stream: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub system: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<ToolDefinition>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -181,11 +191,13 @@ pub struct UsageData {
 }
 
 /// Accumulates streamed response chunks into complete content blocks.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct StreamedResponse {
     pub content_blocks: Vec<ContentBlock>,
     pub stop_reason: Option<String>,
     pub usage: TokenUsage,
+    /// When the first content block started, for time-to-first-token
+    pub first_token_at: Option<Instant>,
     current_block_index: Option<u32>,
     current_tool_json: String,
 }
@@ -0,0 +1,432 @@
+//! Model listing from each provider's models endpoint, merged with the
+//! metadata registry for limits and pricing the APIs do not report.
+
+use std::env;
+use std::time::Duration;
+
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::agent::error::AgentError;
+use crate::agent::http::build_http_client;
+use crate::agent::models::model_info;
+use crate::config::{NetworkConfig, ProjectConfig};
+
+use super::{anthropic, gemini};
+
+const LIST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// (provider id, default API key env var)
+const PROVIDERS: &[(&str, &str)] = &[
+    ("anthropic", "ANTHROPIC_API_KEY"),
+    ("gemini", "GEMINI_API_KEY"),
+];
+
+/// A model offered by a provider's API.
+#[derive(Debug, Clone, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct ListedModel {
+    pub id: String,
+    pub display_name: Option<String>,
+    /// From the API when reported, else the registry
+    pub context_window: Option<u32>,
+    /// From the API when reported, else the registry
+    pub max_output_tokens: Option<u32>,
+    pub supports_vision: Option<bool>,
+    /// USD per million input tokens
+    pub input_price_per_mtok: Option<f64>,
+    /// USD per million output tokens
+    pub output_price_per_mtok: Option<f64>,
+}
+
+impl ListedModel {
+    fn new(
+        id: String,
+        display_name: Option<String>,
+        context_window: Option<u32>,
+        max_output_tokens: Option<u32>,
+    ) -> Self {
+        let info = model_info(&id);
+        Self {
+            context_window: context_window.or(info.as_ref().map(|i| i.context_window)),
+            max_output_tokens: max_output_tokens.or(info.as_ref().map(|i| i.max_output_tokens)),
+            supports_vision: info.as_ref().map(|i| i.supports_vision),
+            input_price_per_mtok: info.as_ref().map(|i| i.input_price_per_mtok),
+            output_price_per_mtok: info.as_ref().map(|i| i.output_price_per_mtok),
+            id,
+            display_name,
+        }
+    }
+}
+
+/// Models listed by one provider, or why listing failed.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ProviderModels {
+    pub provider: String,
+    pub models: Vec<ListedModel>,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicModelList {
+    data: Vec<AnthropicModel>,
+    #[serde(default)]
+    has_more: bool,
+    last_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicModel {
+    id: String,
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiModelList {
+    #[serde(default)]
+    models: Vec<GeminiModel>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiModel {
+    name: String,
+    display_name: Option<String>,
+    input_token_limit: Option<u32>,
+    output_token_limit: Option<u32>,
+    #[serde(default)]
+    supported_generation_methods: Vec<String>,
+}
+
+fn anthropic_models(list: AnthropicModelList) -> Vec<ListedModel> {
+    list.data
+        .into_iter()
+        .map(|m| ListedModel::new(m.id, m.display_name, None, None))
+        .collect()
+}
+
+/// Keeps chat-capable models, dropping embedding and other endpoints.
+fn gemini_models(list: GeminiModelList) -> Vec<ListedModel> {
+    list.models
+        .into_iter()
+        .filter(|m| {
+            m.supported_generation_methods
+                .iter()
+                .any(|method| method == "generateContent")
+        })
+        .map(|m| {
+            let id = m
+                .name
+                .strip_prefix("models/")
+                .unwrap_or(&m.name)
+                .to_string();
+            ListedModel::new(
+                id,
+                m.display_name,
+                m.input_token_limit,
+                m.output_token_limit,
+            )
+        })
+        .collect()
+}
+
+async fn get_json<T: for<'de> Deserialize<'de>>(
+    request: reqwest::RequestBuilder,
+) -> Result<T, AgentError> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(AgentError::ApiError(format!("{}: {}", status, body)));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| AgentError::ApiError(format!("Invalid models response: {}", e)))
+}
+
+async fn list_anthropic(
+    client: &Client,
+    base_url: &str,
+    api_key: &str,
+) -> Result<Vec<ListedModel>, AgentError> {
+    let url = format!("{}/v1/models", base_url);
+    let mut models = Vec::new();
+    let mut after_id: Option<String> = None;
+    loop {
+        let mut request = client
+            .get(&url)
+            .header("x-api-key", api_key)
+            .header("anthropic-version", anthropic::API_VERSION)
+            .query(&[("limit", "1000")]);
+        if let Some(after) = &after_id {
+            request = request.query(&[("after_id", after)]);
+        }
+
+        let page: AnthropicModelList = get_json(request).await?;
+        let next = page.last_id.clone().filter(|_| page.has_more);
+        models.extend(anthropic_models(page));
+        match next {
+            Some(id) => after_id = Some(id),
+            None => return Ok(models),
+        }
+    }
+}
+
+async fn list_gemini(
+    client: &Client,
+    base_url: &str,
+    api_key: &str,
+) -> Result<Vec<ListedModel>, AgentError> {
+    let url = format!("{}/v1beta/models", base_url);
+    let mut models = Vec::new();
+    let mut page_token: Option<String> = None;
+    loop {
+        // Header rather than `key=` so request errors cannot echo the key
+        let mut request = client
+            .get(&url)
+            .header("x-goog-api-key", api_key)
+            .query(&[("pageSize", "1000")]);
+        if let Some(token) = &page_token {
+            request = request.query(&[("pageToken", token)]);
+        }
+
+        let page: GeminiModelList = get_json(request).await?;
+        let next = page.next_page_token.clone().filter(|t| !t.is_empty());
+        models.extend(gemini_models(page));
+        match next {
+            Some(token) => page_token = Some(token),
+            None => return Ok(models),
+        }
+    }
+}
+
+/// Lists the models of the project's provider and of every other supported
+/// provider whose default API key variable is set.
+pub async fn list_provider_models(config: &ProjectConfig) -> Vec<ProviderModels> {
+    let client = match build_http_client(&config.network, Some(LIST_TIMEOUT)) {
+        Ok(client) => client,
+        Err(e) => {
+            return vec![ProviderModels {
+                provider: config.agent.provider.clone(),
+                models: Vec::new(),
+                error: Some(e.to_string()),
+            }]
+        }
+    };
+
+    let mut results = Vec::new();
+    for (provider, default_key_env) in PROVIDERS {
+        let is_configured = config.agent.provider == *provider;
+        let key_env = if is_configured {
+            config.agent.api_key_env.as_str()
+        } else {
+            default_key_env
+        };
+        let api_key = match env::var(key_env) {
+            Ok(key) => key,
+            Err(_) if is_configured => {
+                results.push(ProviderModels {
+                    provider: provider.to_string(),
+                    models: Vec::new(),
+                    error: Some(AgentError::MissingApiKey(key_env.to_string()).to_string()),
+                });
+                continue;
+            }
+            Err(_) => continue,
+        };
+
+        let base_url = config
+            .agent
+            .base_url
+            .as_deref()
+            .filter(|_| is_configured)
+            .unwrap_or(if *provider == "anthropic" {
+                anthropic::DEFAULT_BASE_URL
+            } else {
+                gemini::DEFAULT_BASE_URL
+            })
+            .trim_end_matches('/');
+
+        let listed = if *provider == "anthropic" {
+            list_anthropic(&client, base_url, &api_key).await
+        } else {
+            list_gemini(&client, base_url, &api_key).await
+        };
+        results.push(match listed {
+            Ok(models) => ProviderModels {
+                provider: provider.to_string(),
+                models,
+                error: None,
+            },
+            Err(e) => ProviderModels {
+                provider: provider.to_string(),
+                models: Vec::new(),
+                error: Some(e.to_string()),
+            },
+        });
+    }
+    results
+}
+
+/// Default API key variable of a supported provider.
+pub fn default_key_env(provider: &str) -> Option<&'static str> {
+    PROVIDERS
+        .iter()
+        .find(|(id, _)| *id == provider)
+        .map(|(_, key_env)| *key_env)
+}
+
+/// Default API key variables of all supported providers.
+pub fn default_key_envs() -> impl Iterator<Item = &'static str> {
+    PROVIDERS.iter().map(|(_, key_env)| *key_env)
+}
+
+/// Why `check_credentials` failed.
+#[derive(Debug, PartialEq)]
+pub enum CredentialsError {
+    /// The provider answered but refused the key
+    Rejected(String),
+    /// No answer: DNS, connect, TLS, proxy or timeout
+    Unreachable(String),
+    /// The `[network]` proxy or CA settings are unusable
+    Network(String),
+    Other(String),
+}
+
+/// Verifies `api_key` with the cheapest authenticated request, listing a
+/// single model, so nothing is billed.
+pub async fn check_credentials(
+    network: &NetworkConfig,
+    provider: &str,
+    base_url: Option<&str>,
+    api_key: &str,
+) -> Result<(), CredentialsError> {
+    let client = build_http_client(network, Some(LIST_TIMEOUT))
+        .map_err(|e| CredentialsError::Network(e.to_string()))?;
+    let request = match provider {
+        "anthropic" => {
+            let base_url = base_url.unwrap_or(anthropic::DEFAULT_BASE_URL);
+            client
+                .get(format!("{}/v1/models", base_url.trim_end_matches('/')))
+                .header("x-api-key", api_key)
+                .header("anthropic-version", anthropic::API_VERSION)
+                .query(&[("limit", "1")])
+        }
+        "gemini" => {
+            let base_url = base_url.unwrap_or(gemini::DEFAULT_BASE_URL);
+            client
+                .get(format!("{}/v1beta/models", base_url.trim_end_matches('/')))
+                .header("x-goog-api-key", api_key)
+                .query(&[("pageSize", "1")])
+        }
+        _ => {
+            return Err(CredentialsError::Other(format!(
+                "Unknown provider '{}'",
+                provider
+            )))
+        }
+    };
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| match AgentError::from(e) {
+            AgentError::Connection(message) => CredentialsError::Unreachable(message),
+            other => CredentialsError::Other(other.to_string()),
+        })?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    classify_credentials_response(status, &body)
+}
+
+fn classify_credentials_response(status: StatusCode, body: &str) -> Result<(), CredentialsError> {
+    if status.is_success() {
+        return Ok(());
+    }
+    let message = format!("{}: {}", status, body.trim());
+    // Gemini answers an unknown key with 400 API_KEY_INVALID rather than 401
+    let rejected = matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)
+        || (status == StatusCode::BAD_REQUEST && body.contains("API_KEY_INVALID"));
+    Err(if rejected {
+        CredentialsError::Rejected(message)
+    } else {
+        CredentialsError::Other(message)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_anthropic_models_use_registry_metadata() {
+        let list: AnthropicModelList = serde_json::from_value(serde_json::json!({
+            "data": [
+                {"id": "claude-sonnet-4-20250514", "display_name": "Claude Sonnet 4", "type": "model"},
+                {"id": "claude-new-model", "display_name": "New", "type": "model"}
+            ],
+            "has_more": false,
+            "last_id": "claude-new-model"
+        }))
+        .unwrap();
+
+        let models = anthropic_models(list);
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].display_name.as_deref(), Some("Claude Sonnet 4"));
+        assert_eq!(models[0].context_window, Some(200_000));
+        assert_eq!(models[0].input_price_per_mtok, Some(3.0));
+        assert_eq!(models[1].context_window, None);
+    }
+
+    #[test]
+    fn test_gemini_models_keep_chat_models() {
+        let list: GeminiModelList = serde_json::from_value(serde_json::json!({
+            "models": [
+                {
+                    "name": "models/gemini-2.0-flash",
+                    "displayName": "Gemini 2.0 Flash",
+                    "inputTokenLimit": 1000000,
+                    "outputTokenLimit": 8192,
+                    "supportedGenerationMethods": ["generateContent", "countTokens"]
+                },
+                {
+                    "name": "models/text-embedding-004",
+                    "supportedGenerationMethods": ["embedContent"]
+                }
+            ]
+        }))
+        .unwrap();
+
+        let models = gemini_models(list);
+        assert_eq!(models.len(), 1);
+        assert_eq!(models[0].id, "gemini-2.0-flash");
+        // API-reported limits take precedence over the registry
+        assert_eq!(models[0].context_window, Some(1_000_000));
+        assert_eq!(models[0].output_price_per_mtok, Some(0.4));
+    }
+
+    #[test]
+    fn test_classify_credentials_response() {
+        assert_eq!(classify_credentials_response(StatusCode::OK, "{}"), Ok(()));
+        assert!(matches!(
+            classify_credentials_response(StatusCode::UNAUTHORIZED, "invalid x-api-key"),
+            Err(CredentialsError::Rejected(_))
+        ));
+        assert!(matches!(
+            classify_credentials_response(
+                StatusCode::BAD_REQUEST,
+                r#"{"error":{"details":[{"reason":"API_KEY_INVALID"}]}}"#
+            ),
+            Err(CredentialsError::Rejected(_))
+        ));
+        assert!(matches!(
+            classify_credentials_response(StatusCode::SERVICE_UNAVAILABLE, "overloaded"),
+            Err(CredentialsError::Other(_))
+        ));
+    }
+}
@@ -1,26 +1,52 @@
 //! Context compaction for long conversations.
 
+use std::collections::{BTreeSet, HashSet};
 use std::future::Future;
+use std::path::Path;
 
 use serde::Deserialize;
-use tauri::{AppHandle, Emitter};
+use tauri::Runtime;
+use tokio_util::sync::CancellationToken;
 
+use crate::agent::conversation::Conversation;
 use crate::agent::error::AgentError;
+use crate::agent::events::EventSink;
+use crate::agent::models;
 use crate::agent::tools::{CompactedContext, SessionState};
 use crate::agent::types::{
-    AgentCompactionPayload, AgentCompactionWarningPayload, AgentStatus, ChatContentBlock,
-    ChatMessage, CompactedFact, FactCategory,
+    AgentCompactionPayload, AgentCompactionPreviewPayload, AgentCompactionWarningPayload,
+    AgentStatus, ChatContentBlock, ChatMessage, CompactedFact, FactCategory, MessageRole,
 };
+use crate::agent::usage::{SessionUsageTracker, TokenUsage, UsageSource};
 
-use super::{emit_status, DEFAULT_EXTRACTION_PROMPT};
+use super::{emit_status, emit_usage, DEFAULT_EXTRACTION_PROMPT};
 
 const DEFAULT_CONTEXT_LIMIT: u32 = 200_000;
-const COMPACTION_THRESHOLD: f64 = 0.8;
+const DEFAULT_COMPACTION_THRESHOLD: f64 = 0.8;
 const PRESERVED_EXCHANGES: usize = 6;
 const AGGRESSIVE_PRESERVED_EXCHANGES: usize = 4;
+/// Largest share of the context limit a single message may take.
+const MAX_MESSAGE_FRACTION: f64 = 0.25;
+/// Room left for the marker in each shortened text.
+const OMITTED_MARKER_CHARS: usize = 80;
+const MAX_FACTS: usize = 20;
+/// Facts of one category at least this similar are merged, keeping the newer.
+const SIMILAR_FACT_THRESHOLD: f64 = 0.9;
+/// Lower for preferences, so a changed preference (tabs, then spaces)
+/// replaces the old one.
+const SIMILAR_PREFERENCE_THRESHOLD: f64 = 0.8;
+
+/// Configured limit if set, else the model's known context window, else a 200k default.
+pub fn get_context_limit(config_limit: Option<u32>, model: &str) -> u32 {
+    config_limit
+        .or_else(|| models::context_window(model))
+        .unwrap_or(DEFAULT_CONTEXT_LIMIT)
+}
 
-pub fn get_context_limit(config_limit: Option<u32>) -> u32 {
-    config_limit.unwrap_or(DEFAULT_CONTEXT_LIMIT)
+pub fn get_compaction_threshold(config_threshold: Option<f32>) -> f64 {
+    config_threshold
+        .map(f64::from)
+        .unwrap_or(DEFAULT_COMPACTION_THRESHOLD)
 }
 
 /// Estimate tokens using chars/4 heuristic.
@@ -77,11 +103,81 @@ pub fn estimate_context_size(
     total
 }
 
-pub fn should_compact(estimated_tokens: u32, context_limit: u32) -> bool {
-    estimated_tokens > (context_limit as f64 * COMPACTION_THRESHOLD) as u32
+pub fn should_compact(estimated_tokens: u32, context_limit: u32, threshold: f64) -> bool {
+    estimated_tokens > (context_limit as f64 * threshold) as u32
 }
 
-/// Returns (to_compact, to_preserve).
+/// Shortens messages estimated above a quarter of `context_limit` (a pasted
+/// log, a huge tool output) by cutting the middle out of their longest texts
+/// and tool outputs. Such a message can sit in the preserved window and never
+/// be compacted. Returns how many messages were shortened.
+pub fn shorten_oversized_messages(messages: &mut [ChatMessage], context_limit: u32) -> usize {
+    let max_tokens = (context_limit as f64 * MAX_MESSAGE_FRACTION) as u32;
+    let mut shortened = 0;
+    for message in messages.iter_mut() {
+        if estimate_message_tokens(message) > max_tokens {
+            shorten_message(message, max_tokens as usize * 4);
+            shortened += 1;
+        }
+    }
+    shortened
+}
+
+/// Cuts the longest texts and tool outputs of `message` to one common
+/// length, so that the whole message fits in `max_chars`.
+fn shorten_message(message: &mut ChatMessage, max_chars: usize) {
+    let tool_calls: usize = message
+        .content_blocks
+        .iter()
+        .map(|block| match block {
+            ChatContentBlock::Text { .. } => 0,
+            ChatContentBlock::ToolUse {
+                tool_name,
+                tool_input,
+                ..
+            } => tool_name.chars().count() + tool_input.to_string().chars().count(),
+        })
+        .sum();
+    let mut texts: Vec<&mut String> = message
+        .content_blocks
+        .iter_mut()
+        .filter_map(|block| match block {
+            ChatContentBlock::Text { text } => Some(text),
+            ChatContentBlock::ToolUse { output, .. } => output.as_mut(),
+        })
+        .collect();
+
+    let mut lengths: Vec<usize> = texts.iter().map(|text| text.chars().count()).collect();
+    lengths.sort_unstable();
+    let mut remaining = max_chars.saturating_sub(tool_calls + texts.len() * OMITTED_MARKER_CHARS);
+    let mut cap = usize::MAX;
+    for (i, &length) in lengths.iter().enumerate() {
+        let left = lengths.len() - i;
+        if length * left > remaining {
+            cap = remaining / left;
+            break;
+        }
+        remaining -= length;
+    }
+
+    for text in texts.iter_mut() {
+        let length = text.chars().count();
+        if length > cap {
+            let head: String = text.chars().take(cap / 2).collect();
+            let tail: String = text.chars().skip(length - (cap - cap / 2)).collect();
+            **text = format!(
+                "{}\n\n[... {} characters omitted to fit the context window ...]\n\n{}",
+                head,
+                length - cap,
+                tail
+            );
+        }
+    }
+}
+
+/// Returns (to_compact, to_preserve). The preserved messages start at a
+/// user turn, so a tool call is never separated from its results; when the
+/// usual split point is inside a tool exchange, more messages are preserved.
 pub fn split_messages_for_compaction(
     messages: &[ChatMessage],
     aggressive: bool,
@@ -96,20 +192,44 @@ pub fn split_messages_for_compaction(
         return (vec![], messages.iter().collect());
     }
 
-    let split_point = messages.len() - preserve_count;
+    let target = messages.len() - preserve_count;
+    let boundary = (1..=target)
+        .rev()
+        .chain(target + 1..messages.len())
+        .find(|&i| starts_exchange(&messages[i - 1], &messages[i]));
+    let Some(split_point) = boundary else {
+        return (vec![], messages.iter().collect());
+    };
+
     let to_compact: Vec<&ChatMessage> = messages[..split_point].iter().collect();
     let to_preserve: Vec<&ChatMessage> = messages[split_point..].iter().collect();
 
     (to_compact, to_preserve)
 }
 
+/// Whether `message` opens a new exchange: a user turn that is not the
+/// results of tool calls, after a message not waiting for any.
+fn starts_exchange(previous: &ChatMessage, message: &ChatMessage) -> bool {
+    let has_tool_blocks = |m: &ChatMessage| {
+        m.content_blocks
+            .iter()
+            .any(|block| matches!(block, ChatContentBlock::ToolUse { .. }))
+    };
+    let awaits_results = previous
+        .content_blocks
+        .iter()
+        .any(|block| matches!(block, ChatContentBlock::ToolUse { output: None, .. }));
+
+    matches!(message.role, MessageRole::User) && !has_tool_blocks(message) && !awaits_results
+}
+
 pub fn format_messages_for_extraction(messages: &[&ChatMessage]) -> String {
     let mut output = String::new();
 
     for msg in messages {
         let role = match msg.role {
-            crate::agent::types::MessageRole::User => "User",
-            crate::agent::types::MessageRole::Assistant => "Assistant",
+            MessageRole::User => "User",
+            MessageRole::Assistant => "Assistant",
         };
 
         output.push_str(&format!("## {}\n", role));
@@ -245,19 +365,75 @@ pub fn merge_compacted_contexts(
 
     let mut facts = existing.facts.clone();
     for fact in new.facts {
-        if !facts.iter().any(|f| f.content == fact.content) {
-            facts.push(fact);
-        }
+        // A restated fact replaces the older wording and counts as new
+        facts.retain(|f| !same_fact(f, &fact));
+        facts.push(fact);
     }
 
-    const MAX_FACTS: usize = 20;
+    // Over the cap, the oldest facts go first
     if facts.len() > MAX_FACTS {
-        facts.truncate(MAX_FACTS);
+        facts.drain(..facts.len() - MAX_FACTS);
     }
 
     CompactedContext { summary, facts }
 }
 
+/// Whether two facts say the same thing: equal once case and punctuation are
+/// ignored, or in the same category and worded almost alike. Facts naming
+/// different numbers or paths (`v1` and `v2`, `Module 1` and `Module 2`) are
+/// never the same.
+fn same_fact(a: &CompactedFact, b: &CompactedFact) -> bool {
+    let (a_text, b_text) = (normalize_fact(&a.content), normalize_fact(&b.content));
+    if a_text == b_text {
+        return true;
+    }
+    if a.category != b.category || specifics(&a.content) != specifics(&b.content) {
+        return false;
+    }
+    let threshold = match a.category {
+        FactCategory::Preference => SIMILAR_PREFERENCE_THRESHOLD,
+        _ => SIMILAR_FACT_THRESHOLD,
+    };
+    similarity(&a_text, &b_text) >= threshold
+}
+
+/// Lowercase words of a fact holding a digit or a path or name separator,
+/// e.g. `src/api/v1/routes.rs`, `DATABASE_URL` or `2`.
+fn specifics(text: &str) -> BTreeSet<String> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| {
+            word.chars()
+                .any(|c| c.is_ascii_digit() || matches!(c, '/' | '\\' | '.' | '_' | ':'))
+        })
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Lowercase words separated by single spaces.
+fn normalize_fact(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Dice coefficient of the character trigrams of two normalized facts, from
+/// 0 (nothing in common) to 1 (same trigrams).
+fn similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (trigrams(a), trigrams(b));
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    2.0 * a.intersection(&b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+    let chars: Vec<char> = format!(" {} ", text).chars().collect();
+    chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
 pub fn format_compacted_context(context: &CompactedContext) -> String {
     let mut output = String::from("[Session Context]\n");
 
@@ -290,24 +466,30 @@ pub struct CompactionResult {
 }
 
 /// Context for compaction operations, reducing function argument count.
-pub struct CompactionContext<'a> {
+pub struct CompactionContext<'a, R: Runtime> {
     pub context_limit: u32,
+    pub threshold: f64,
     pub extraction_prompt: Option<&'a str>,
+    pub project_path: &'a Path,
     pub session: &'a SessionState,
-    pub app_handle: &'a AppHandle,
+    pub events: &'a EventSink<R>,
+    /// Extraction tokens are attributed to `UsageSource::Compaction`
+    pub usage_tracker: &'a SessionUsageTracker,
+    pub cancel_token: &'a CancellationToken,
 }
 
 /// Shared compaction logic for all providers.
-/// The `call_extraction` callback is provider-specific.
-pub async fn maybe_compact<F, Fut>(
+/// The `call_extraction` callback is provider-specific and returns the
+/// response text with its token usage.
+pub async fn maybe_compact<R: Runtime, F, Fut>(
     messages: &[ChatMessage],
     system_prompt: Option<&str>,
-    ctx: &CompactionContext<'_>,
+    ctx: &CompactionContext<'_, R>,
     call_extraction: F,
 ) -> Result<Option<CompactionResult>, AgentError>
 where
     F: FnOnce(String) -> Fut,
-    Fut: Future<Output = Result<String, AgentError>>,
+    Fut: Future<Output = Result<(String, TokenUsage), AgentError>>,
 {
     let existing_compacted = ctx.session.get_compacted().await;
 
@@ -320,11 +502,12 @@ where
 
     let estimated_tokens = estimate_context_size(system_prompt, messages, compacted_ref);
 
-    if !should_compact(estimated_tokens, ctx.context_limit) {
+    let requested = ctx.session.take_compaction_request();
+    if !requested && !should_compact(estimated_tokens, ctx.context_limit, ctx.threshold) {
         return Ok(None);
     }
 
-    emit_status(ctx.app_handle, AgentStatus::Compacting, None);
+    emit_status(ctx.events, AgentStatus::Compacting, None);
 
     let (to_compact, to_preserve) = split_messages_for_compaction(messages, false);
 
@@ -333,7 +516,7 @@ where
             split_messages_for_compaction(messages, true);
 
         if to_compact_aggressive.is_empty() {
-            let _ = ctx.app_handle.emit(
+            let _ = ctx.events.emit(
                 "agent-compaction-warning",
                 AgentCompactionWarningPayload {
                     message: "Unable to compact: not enough messages".to_string(),
@@ -364,17 +547,17 @@ where
     .await
 }
 
-async fn perform_compaction<F, Fut>(
+async fn perform_compaction<R: Runtime, F, Fut>(
     to_compact: &[&ChatMessage],
     to_preserve: &[&ChatMessage],
     existing_compacted: &CompactedContext,
-    ctx: &CompactionContext<'_>,
+    ctx: &CompactionContext<'_, R>,
     call_extraction: F,
     original_tokens: u32,
 ) -> Result<Option<CompactionResult>, AgentError>
 where
     F: FnOnce(String) -> Fut,
-    Fut: Future<Output = Result<String, AgentError>>,
+    Fut: Future<Output = Result<(String, TokenUsage), AgentError>>,
 {
     let formatted = format_messages_for_extraction(to_compact);
 
@@ -390,7 +573,17 @@ where
 
     let extraction_prompt = build_extraction_prompt(&extraction_input, ctx.extraction_prompt);
 
-    let extraction_result = call_extraction(extraction_prompt).await;
+    let extraction_result = call_extraction(extraction_prompt)
+        .await
+        .map(|(text, usage)| {
+            emit_usage(
+                ctx.events,
+                ctx.usage_tracker,
+                usage,
+                UsageSource::Compaction,
+            );
+            text
+        });
 
     match extraction_result {
         Ok(response_text) => match parse_extraction_response(&response_text) {
@@ -398,6 +591,25 @@ where
                 let new_compacted = extraction_to_compacted(extraction);
                 let merged = merge_compacted_contexts(existing_compacted, new_compacted);
 
+                if !confirm_compaction(ctx, &merged, to_compact.len()).await? {
+                    let _ = ctx.events.emit(
+                        "agent-compaction-warning",
+                        AgentCompactionWarningPayload {
+                            message: "Compaction rejected; sending the full history".to_string(),
+                        },
+                    );
+                    return Ok(None);
+                }
+
+                // Kept so `agent_restore_compacted_context` can undo a bad summary
+                if let Err(e) = Conversation::record_compaction(
+                    ctx.project_path,
+                    ctx.session.id(),
+                    existing_compacted,
+                    to_compact,
+                ) {
+                    log::warn!("Failed to keep the messages before compaction: {}", e);
+                }
                 ctx.session.set_compacted(merged.clone()).await;
 
                 let preserved: Vec<ChatMessage> =
@@ -407,7 +619,7 @@ where
                 let compacted_tokens = estimate_context_size(None, &preserved, Some(&merged));
                 let facts_count = merged.facts.len() as u32;
 
-                let _ = ctx.app_handle.emit(
+                let _ = ctx.events.emit(
                     "agent-compaction",
                     AgentCompactionPayload {
                         original_tokens,
@@ -422,7 +634,7 @@ where
                 }))
             }
             Err(e) => {
-                let _ = ctx.app_handle.emit(
+                let _ = ctx.events.emit(
                     "agent-compaction-warning",
                     AgentCompactionWarningPayload {
                         message: format!("Failed to parse extraction: {}", e),
@@ -432,7 +644,7 @@ where
             }
         },
         Err(e) => {
-            let _ = ctx.app_handle.emit(
+            let _ = ctx.events.emit(
                 "agent-compaction-warning",
                 AgentCompactionWarningPayload {
                     message: format!("Extraction API failed: {}", e),
@@ -443,10 +655,49 @@ where
     }
 }
 
+/// Shows the proposed context to the frontend and, if the session confirms
+/// compactions, waits for `agent_respond_compaction`. False if rejected.
+async fn confirm_compaction<R: Runtime>(
+    ctx: &CompactionContext<'_, R>,
+    proposed: &CompactedContext,
+    compacted_messages: usize,
+) -> Result<bool, AgentError> {
+    let confirm = ctx.session.confirms_compaction();
+    let receiver = if confirm {
+        Some(ctx.session.request_compaction_approval().await)
+    } else {
+        None
+    };
+    let _ = ctx.events.emit(
+        "agent-compaction-preview",
+        AgentCompactionPreviewPayload {
+            summary: proposed.summary.clone(),
+            facts: proposed.facts.clone(),
+            compacted_messages: compacted_messages as u32,
+            awaiting_confirmation: confirm,
+        },
+    );
+    let Some(receiver) = receiver else {
+        return Ok(true);
+    };
+
+    emit_status(
+        ctx.events,
+        AgentStatus::Compacting,
+        Some("Awaiting confirmation".to_string()),
+    );
+    tokio::select! {
+        answer = receiver => Ok(answer.unwrap_or(false)),
+        _ = ctx.cancel_token.cancelled() => {
+            ctx.session.cancel_compaction_approval().await;
+            Err(AgentError::Cancelled)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::agent::types::MessageRole;
 
     #[test]
     fn test_estimate_tokens() {
@@ -461,19 +712,30 @@ mod tests {
     #[test]
     fn test_get_context_limit() {
         // With config override
-        assert_eq!(get_context_limit(Some(100_000)), 100_000);
+        assert_eq!(get_context_limit(Some(100_000), "gemini-2.5-pro"), 100_000);
+
+        // Known model window when not configured
+        assert_eq!(get_context_limit(None, "gemini-2.5-pro"), 1_048_576);
 
-        // Default when not configured
-        assert_eq!(get_context_limit(None), 200_000);
+        // Default for unknown models
+        assert_eq!(get_context_limit(None, "custom-model"), 200_000);
     }
 
     #[test]
     fn test_should_compact() {
         // 80% of 200k = 160k
-        assert!(!should_compact(100_000, 200_000));
-        assert!(!should_compact(160_000, 200_000));
-        assert!(should_compact(160_001, 200_000));
-        assert!(should_compact(200_000, 200_000));
+        let threshold = get_compaction_threshold(None);
+        assert!(!should_compact(100_000, 200_000, threshold));
+        assert!(!should_compact(160_000, 200_000, threshold));
+        assert!(should_compact(160_001, 200_000, threshold));
+        assert!(should_compact(200_000, 200_000, threshold));
+
+        // Custom threshold
+        assert!(should_compact(
+            100_001,
+            200_000,
+            get_compaction_threshold(Some(0.5))
+        ));
     }
 
     #[test]
@@ -489,6 +751,7 @@ mod tests {
                 content_blocks: vec![ChatContentBlock::Text {
                     text: format!("Message {}", i),
                 }],
+                interrupted: None,
             })
             .collect();
 
@@ -514,6 +777,7 @@ mod tests {
                 content_blocks: vec![ChatContentBlock::Text {
                     text: format!("Message {}", i),
                 }],
+                interrupted: None,
             })
             .collect();
 
@@ -522,6 +786,91 @@ mod tests {
         assert_eq!(preserve.len(), 10);
     }
 
+    #[test]
+    fn test_shorten_oversized_messages() {
+        let log: String = (0..5000).map(|i| format!("line {}\n", i)).collect();
+        let mut messages = vec![
+            ChatMessage::with_blocks(
+                MessageRole::User,
+                vec![
+                    ChatContentBlock::Text {
+                        text: "Why does the build fail?".to_string(),
+                    },
+                    ChatContentBlock::Text { text: log.clone() },
+                ],
+            ),
+            ChatMessage::new(MessageRole::Assistant, "Let me look.".to_string()),
+        ];
+
+        // A quarter of 10k tokens is 10k characters
+        assert_eq!(shorten_oversized_messages(&mut messages, 10_000), 1);
+        assert!(estimate_message_tokens(&messages[0]) <= 2_500);
+        assert_eq!(messages[0].content_blocks.len(), 2);
+        assert_eq!(messages[1].get_text(), "Let me look.");
+
+        let ChatContentBlock::Text { text } = &messages[0].content_blocks[1] else {
+            panic!("expected text");
+        };
+        assert!(text.starts_with("line 0\n"));
+        assert!(text.ends_with("line 4999\n"));
+        assert!(text.contains("characters omitted to fit the context window"));
+        assert!(messages[0]
+            .get_text()
+            .starts_with("Why does the build fail?"));
+
+        assert_eq!(shorten_oversized_messages(&mut messages, 10_000), 0);
+    }
+
+    fn tool_call(output: Option<&str>) -> ChatContentBlock {
+        ChatContentBlock::ToolUse {
+            tool_use_id: "toolu_1".to_string(),
+            tool_name: "bash".to_string(),
+            tool_input: serde_json::json!({"command": "cargo test"}),
+            output: output.map(str::to_string),
+            is_error: None,
+        }
+    }
+
+    /// `u` user text, `a` assistant text, `c` tool call awaiting its result,
+    /// `r` the tool results.
+    fn conversation(shape: &str) -> Vec<ChatMessage> {
+        shape
+            .chars()
+            .enumerate()
+            .map(|(i, kind)| match kind {
+                'u' => ChatMessage::new(MessageRole::User, format!("Message {}", i)),
+                'a' => ChatMessage::new(MessageRole::Assistant, format!("Message {}", i)),
+                'c' => ChatMessage::with_blocks(MessageRole::Assistant, vec![tool_call(None)]),
+                'r' => ChatMessage::with_blocks(MessageRole::User, vec![tool_call(Some("ok"))]),
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_split_never_separates_tool_results() {
+        // The usual split point (index 4) falls on tool results
+        let messages = conversation("uaucrauauauauaua");
+        let (compact, preserve) = split_messages_for_compaction(&messages, false);
+        assert_eq!(compact.len(), 2);
+        assert_eq!(preserve.len(), 14);
+        assert_eq!(preserve[0].get_text(), "Message 2");
+
+        // Starting on tool results, the split moves forward to the first user turn
+        let messages = conversation("rcrauauauauaua");
+        let (compact, preserve) = split_messages_for_compaction(&messages, false);
+        assert_eq!(compact.len(), 4);
+        assert_eq!(preserve[0].get_text(), "Message 4");
+
+        // One long tool loop ending on tool results has nowhere to split
+        let messages = conversation("ucrcrcrcrcrcrcr");
+        for aggressive in [false, true] {
+            let (compact, preserve) = split_messages_for_compaction(&messages, aggressive);
+            assert!(compact.is_empty());
+            assert_eq!(preserve.len(), messages.len());
+        }
+    }
+
     #[test]
     fn test_extract_json_from_response() {
         // Raw JSON
@@ -625,4 +974,93 @@ mod tests {
         assert!(merged.summary.unwrap().contains("First part"));
         assert_eq!(merged.facts.len(), 2); // Duplicate removed
     }
+
+    #[test]
+    fn test_merge_compacted_contexts_prefers_newer_facts() {
+        let fact = |category, content: &str| CompactedFact {
+            category,
+            content: content.to_string(),
+        };
+        let mut facts: Vec<_> = (1..MAX_FACTS)
+            .map(|i| fact(FactCategory::Context, &format!("Module {} is done", i)))
+            .collect();
+        facts.push(fact(
+            FactCategory::Preference,
+            "User prefers tabs for indentation",
+        ));
+        let existing = CompactedContext {
+            summary: None,
+            facts,
+        };
+
+        let new = CompactedContext {
+            summary: None,
+            facts: vec![
+                fact(
+                    FactCategory::Preference,
+                    "User prefers spaces for indentation.",
+                ),
+                fact(
+                    FactCategory::Decision,
+                    "Config lives in `.devflow/config.toml`",
+                ),
+                fact(
+                    FactCategory::Context,
+                    "config lives in .devflow/config.toml",
+                ),
+                fact(FactCategory::Blocker, "Tests need DATABASE_URL"),
+            ],
+        };
+
+        let merged = merge_compacted_contexts(&existing, new);
+        let contents: Vec<_> = merged.facts.iter().map(|f| f.content.as_str()).collect();
+        assert_eq!(contents.len(), MAX_FACTS);
+        assert!(!contents.contains(&"User prefers tabs for indentation"));
+        assert_eq!(
+            contents[MAX_FACTS - 3..],
+            [
+                "User prefers spaces for indentation.",
+                "config lives in .devflow/config.toml",
+                "Tests need DATABASE_URL",
+            ]
+        );
+        // The two oldest facts made room for the new ones
+        assert_eq!(contents[0], "Module 3 is done");
+    }
+
+    #[test]
+    fn test_similar_facts_with_different_specifics_are_kept() {
+        let fact = |category, content: &str| CompactedFact {
+            category,
+            content: content.to_string(),
+        };
+        for (category, older, newer) in [
+            (
+                FactCategory::Preference,
+                "Do not modify src/api/v1/routes.rs",
+                "Do not modify src/api/v2/routes.rs",
+            ),
+            (
+                FactCategory::Context,
+                "Module 1 is done",
+                "Module 2 is done",
+            ),
+            (
+                FactCategory::Blocker,
+                "Tests in crate api fail",
+                "Tests in crate cli fail",
+            ),
+        ] {
+            let existing = CompactedContext {
+                summary: None,
+                facts: vec![fact(category, older)],
+            };
+            let new = CompactedContext {
+                summary: None,
+                facts: vec![fact(category, newer)],
+            };
+            let merged = merge_compacted_contexts(&existing, new);
+            assert_eq!(merged.facts.len(), 2, "{} / {}", older, newer);
+        }
+    }
 }
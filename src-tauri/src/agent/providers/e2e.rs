@@ -0,0 +1,823 @@
+//! End-to-end tests of the UI `send_message` path: a local HTTP server replays
+//! recorded SSE fixtures from `fixtures/sse/` to the real adapters, pointed at it
+//! through `agent.base_url`, while events go to Tauri's mock runtime.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use tauri::test::{mock_app, MockRuntime};
+use tauri::{AppHandle, Listener};
+use tokio_util::sync::CancellationToken;
+
+use crate::agent::conversation::Conversation;
+use crate::agent::error::AgentError;
+use crate::agent::events::EventSink;
+use crate::agent::provider::{ExecutionContext, ProviderAdapter};
+use crate::agent::tools::{LocalExecutor, SessionState, ToolExecutor, ToolName};
+use crate::agent::types::{
+    ChatContentBlock, ChatMessage, MessageRole, SendOptions, SteeringMessage, ToolChoice,
+};
+use crate::agent::usage::SessionUsageTracker;
+use crate::config::{NetworkConfig, ProjectConfig};
+
+use super::{AnthropicAdapter, GeminiAdapter, DEFAULT_SYSTEM_PROMPT};
+
+const API_KEY_ENV: &str = "DEVFLOW_E2E_API_KEY";
+const API_KEY: &str = "e2e-test-key";
+
+/// One request received by the fake server.
+#[derive(Debug, Clone)]
+struct RecordedRequest {
+    /// Request target, e.g. `/v1/messages`
+    target: String,
+    headers: Vec<(String, String)>,
+    body: serde_json::Value,
+}
+
+impl RecordedRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Serves one fixture per request, in order, then 500s.
+struct FakeSseServer {
+    base_url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl FakeSseServer {
+    fn start(fixtures: &[&str]) -> Self {
+        let bodies: Vec<String> = fixtures
+            .iter()
+            .map(|name| std::fs::read_to_string(fixture_path(name)).expect("fixture exists"))
+            .collect();
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind fake server");
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            let mut bodies = bodies.into_iter();
+            for stream in listener.incoming().flatten() {
+                let body = bodies.next();
+                if let Err(e) = serve(stream, body, &recorded) {
+                    log::warn!("Fake SSE server failed to serve request: {}", e);
+                }
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn fixture_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join("sse")
+        .join(name)
+}
+
+fn serve(
+    stream: TcpStream,
+    body: Option<String>,
+    recorded: &Mutex<Vec<RecordedRequest>>,
+) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let target = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut raw_body = vec![0u8; content_length];
+    reader.read_exact(&mut raw_body)?;
+
+    recorded.lock().unwrap().push(RecordedRequest {
+        target,
+        headers,
+        body: serde_json::from_slice(&raw_body).unwrap_or(serde_json::Value::Null),
+    });
+
+    let (status, content_type, body) = match body {
+        Some(body) => ("200 OK", "text/event-stream", body),
+        None => (
+            "500 Internal Server Error",
+            "application/json",
+            r#"{"error":{"message":"no fixture left"}}"#.to_string(),
+        ),
+    };
+
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Every agent event emitted during a run, as (name, payload).
+type EventLog = Arc<Mutex<Vec<(String, serde_json::Value)>>>;
+
+fn record_events(app_handle: &AppHandle<MockRuntime>) -> EventLog {
+    let events: EventLog = Arc::new(Mutex::new(Vec::new()));
+    for name in [
+        "agent-status",
+        "agent-content-block-start",
+        "agent-chunk",
+        "agent-tool-start",
+        "agent-tool-end",
+        "agent-complete",
+        "agent-error",
+        "agent-steering",
+    ] {
+        let events = Arc::clone(&events);
+        app_handle.listen_any(name, move |event| {
+            let payload = serde_json::from_str(event.payload()).unwrap_or_default();
+            events.lock().unwrap().push((name.to_string(), payload));
+        });
+    }
+    events
+}
+
+fn payloads(events: &EventLog, name: &str) -> Vec<serde_json::Value> {
+    events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(n, _)| n == name)
+        .map(|(_, p)| p.clone())
+        .collect()
+}
+
+fn project_config(provider: &str, model: &str, base_url: &str) -> ProjectConfig {
+    toml::from_str(&config_toml(provider, model, base_url)).expect("valid test config")
+}
+
+fn config_toml(provider: &str, model: &str, base_url: &str) -> String {
+    std::env::set_var(API_KEY_ENV, API_KEY);
+    format!(
+        r#"
+[agent]
+provider = "{provider}"
+model = "{model}"
+api_key_env = "{API_KEY_ENV}"
+max_tokens = 1024
+base_url = "{base_url}"
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 5
+"#
+    )
+}
+
+fn project_with_file() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("hello.txt"), "hello world\n").unwrap();
+    dir
+}
+
+fn execution_context() -> ExecutionContext {
+    ExecutionContext {
+        session: SessionState::new(),
+        cancel_token: CancellationToken::new(),
+        usage_tracker: Arc::new(SessionUsageTracker::new()),
+    }
+}
+
+fn user_message() -> Vec<ChatMessage> {
+    vec![ChatMessage::new(
+        MessageRole::User,
+        "What does hello.txt say?".to_string(),
+    )]
+}
+
+/// Checks the event stream shared by both providers: one tool round trip with
+/// text before the tool call and a final answer in a new block.
+fn assert_tool_round_trip_events(events: &EventLog, final_text: &str) {
+    let statuses: Vec<String> = payloads(events, "agent-status")
+        .iter()
+        .map(|p| p["status"].as_str().unwrap_or_default().to_string())
+        .collect();
+    for expected in ["sending", "thinking", "streaming", "tool_running", "idle"] {
+        assert!(
+            statuses.iter().any(|s| s == expected),
+            "missing status {} in {:?}",
+            expected,
+            statuses
+        );
+    }
+    assert_eq!(statuses.last().map(String::as_str), Some("idle"));
+
+    let tool_start = &payloads(events, "agent-tool-start")[0];
+    assert_eq!(tool_start["tool_name"], "read_file");
+    assert_eq!(tool_start["block_index"], 1);
+
+    let tool_end = &payloads(events, "agent-tool-end")[0];
+    assert_eq!(tool_end["is_error"], false);
+    assert_eq!(tool_end["artifacts"], serde_json::json!([]));
+    assert!(tool_end["output"]
+        .as_str()
+        .unwrap_or_default()
+        .contains("hello world"));
+
+    // Final answer streams into a block after the first response's two blocks
+    let chunks = payloads(events, "agent-chunk");
+    let final_chunks: String = chunks
+        .iter()
+        .filter(|c| c["block_index"] == 2)
+        .map(|c| c["delta"].as_str().unwrap_or_default())
+        .collect();
+    assert_eq!(final_chunks, final_text);
+    assert!(chunks.iter().any(|c| c["block_index"] == 0));
+
+    let complete = payloads(events, "agent-complete");
+    assert_eq!(complete.len(), 1);
+    assert!(payloads(events, "agent-error").is_empty());
+}
+
+#[tokio::test]
+async fn test_anthropic_send_message_tool_loop() {
+    let server = FakeSseServer::start(&["anthropic_tool_use.sse", "anthropic_final_text.sse"]);
+    let project = project_with_file();
+    let adapter = AnthropicAdapter::new(
+        project_config("anthropic", "claude-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let app = mock_app();
+    let events = record_events(app.handle());
+
+    adapter
+        .send_message_with_runtime(
+            user_message(),
+            None,
+            None,
+            SendOptions::default(),
+            execution_context(),
+            EventSink::broadcast(app.handle().clone()),
+        )
+        .await
+        .unwrap();
+
+    assert_tool_round_trip_events(&events, "The file says hello.");
+    assert_eq!(
+        payloads(&events, "agent-complete")[0]["stop_reason"],
+        "end_turn"
+    );
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].target, "/v1/messages");
+    assert_eq!(requests[0].header("x-api-key"), Some(API_KEY));
+    assert_eq!(requests[0].body["model"], "claude-test");
+    assert_eq!(requests[0].body["stream"], true);
+
+    // Second request carries the assistant tool call and its result
+    let messages = requests[1].body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[1]["content"][1]["type"], "tool_use");
+    assert_eq!(messages[1]["content"][1]["input"]["path"], "hello.txt");
+    assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+    assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_01");
+    assert!(messages[2]["content"][0]["content"]
+        .as_str()
+        .unwrap()
+        .contains("hello world"));
+}
+
+#[tokio::test]
+async fn test_gemini_send_message_tool_loop() {
+    let server = FakeSseServer::start(&["gemini_tool_use.sse", "gemini_final_text.sse"]);
+    let project = project_with_file();
+    let adapter = GeminiAdapter::new(
+        project_config("gemini", "gemini-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let app = mock_app();
+    let events = record_events(app.handle());
+
+    adapter
+        .send_message_with_runtime(
+            user_message(),
+            None,
+            None,
+            SendOptions::default(),
+            execution_context(),
+            EventSink::broadcast(app.handle().clone()),
+        )
+        .await
+        .unwrap();
+
+    assert_tool_round_trip_events(&events, "The file says hello.");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(
+        requests[0].target,
+        format!(
+            "/v1beta/models/gemini-test:streamGenerateContent?alt=sse&key={}",
+            API_KEY
+        )
+    );
+
+    let contents = requests[1].body["contents"].as_array().unwrap();
+    assert_eq!(contents.len(), 3);
+    assert_eq!(contents[1]["role"], "model");
+    let function_response = contents[2].to_string();
+    assert!(function_response.contains("read_file"));
+    assert!(function_response.contains("hello world"));
+}
+
+#[tokio::test]
+async fn test_required_tool_choice_applies_to_first_request() {
+    let server = FakeSseServer::start(&["anthropic_tool_use.sse", "anthropic_final_text.sse"]);
+    let project = project_with_file();
+    let adapter = AnthropicAdapter::new(
+        project_config("anthropic", "claude-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let app = mock_app();
+    adapter
+        .send_message_with_runtime(
+            user_message(),
+            None,
+            None,
+            SendOptions {
+                tool_choice: ToolChoice::Required,
+                variables: None,
+            },
+            execution_context(),
+            EventSink::broadcast(app.handle().clone()),
+        )
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].body["tool_choice"]["type"], "any");
+    // Forcing every response would never let the loop end
+    assert!(requests[1].body.get("tool_choice").is_none());
+}
+
+#[tokio::test]
+async fn test_gemini_tool_choice_none_disables_function_calls() {
+    let server = FakeSseServer::start(&["gemini_final_text.sse"]);
+    let project = project_with_file();
+    let adapter = GeminiAdapter::new(
+        project_config("gemini", "gemini-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let app = mock_app();
+    adapter
+        .send_message_with_runtime(
+            user_message(),
+            None,
+            None,
+            SendOptions {
+                tool_choice: ToolChoice::None,
+                variables: None,
+            },
+            execution_context(),
+            EventSink::broadcast(app.handle().clone()),
+        )
+        .await
+        .unwrap();
+
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].body["toolConfig"]["functionCallingConfig"]["mode"],
+        "NONE"
+    );
+}
+
+#[tokio::test]
+async fn test_api_error_is_reported() {
+    let server = FakeSseServer::start(&[]);
+    let project = project_with_file();
+    let adapter = AnthropicAdapter::new(
+        project_config("anthropic", "claude-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let app = mock_app();
+    let events = record_events(app.handle());
+
+    let result = adapter
+        .send_message_with_runtime(
+            user_message(),
+            None,
+            None,
+            SendOptions::default(),
+            execution_context(),
+            EventSink::broadcast(app.handle().clone()),
+        )
+        .await;
+
+    assert!(result.is_err());
+    let errors = payloads(&events, "agent-error");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0]["error"], "API error: no fixture left");
+}
+
+/// Queues a steering message from inside an event listener, as the UI would mid-run.
+fn queue_on(session: &SessionState, content: &str) {
+    futures::executor::block_on(session.queue_message(SteeringMessage {
+        id: content.to_string(),
+        content: content.to_string(),
+    }));
+}
+
+#[tokio::test]
+async fn test_steering_messages_join_the_tool_loop() {
+    let server = FakeSseServer::start(&[
+        "anthropic_tool_use.sse",
+        "anthropic_final_text.sse",
+        "anthropic_final_text.sse",
+    ]);
+    let project = project_with_file();
+    let adapter = AnthropicAdapter::new(
+        project_config("anthropic", "claude-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let app = mock_app();
+    let events = record_events(app.handle());
+    let exec = execution_context();
+    let session_id = exec.session.id().to_string();
+
+    // One message while the tool runs, one during the final answer
+    let session = exec.session.clone();
+    app.handle().listen_any("agent-tool-end", move |_| {
+        queue_on(&session, "Also mention the size.");
+    });
+    let session = exec.session.clone();
+    let queued = AtomicBool::new(false);
+    app.handle().listen_any("agent-chunk", move |event| {
+        let payload: serde_json::Value = serde_json::from_str(event.payload()).unwrap();
+        if payload["block_index"] == 2 && !queued.swap(true, Ordering::SeqCst) {
+            queue_on(&session, "Thanks!");
+        }
+    });
+
+    adapter
+        .send_message_with_runtime(
+            user_message(),
+            None,
+            None,
+            SendOptions::default(),
+            exec,
+            EventSink::broadcast(app.handle().clone()),
+        )
+        .await
+        .unwrap();
+
+    let steering = payloads(&events, "agent-steering");
+    assert_eq!(steering.len(), 2);
+    assert_eq!(
+        steering[0]["messages"][0]["content"],
+        "Also mention the size."
+    );
+    assert_eq!(steering[1]["messages"][0]["content"], "Thanks!");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+
+    // Injected after the tool result, in the same user turn
+    let messages = requests[1].body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+    assert_eq!(messages[2]["content"][1]["text"], "Also mention the size.");
+
+    // A message queued during the final answer continues the run
+    let messages = requests[2].body["messages"].as_array().unwrap();
+    assert_eq!(messages.len(), 5);
+    assert_eq!(messages[3]["role"], "assistant");
+    assert_eq!(messages[4]["role"], "user");
+    assert_eq!(messages[4]["content"], "Thanks!");
+    assert_eq!(payloads(&events, "agent-complete").len(), 1);
+
+    // Recorded as the UI shows it: each steering closes the assistant message so far
+    let recorded = Conversation::load(project.path(), &session_id).messages;
+    let roles: Vec<_> = recorded
+        .iter()
+        .map(|m| serde_json::to_value(&m.role).unwrap())
+        .collect();
+    assert_eq!(
+        roles,
+        [
+            "user",
+            "assistant",
+            "user",
+            "assistant",
+            "user",
+            "assistant"
+        ]
+    );
+    assert_eq!(
+        steering[0]["closed_message_id"].as_str(),
+        Some(recorded[1].id.as_str())
+    );
+    assert!(matches!(
+        recorded[1].content_blocks[1],
+        ChatContentBlock::ToolUse { .. }
+    ));
+    assert_eq!(recorded[2].get_text(), "Also mention the size.");
+    assert_eq!(recorded[3].get_text(), "The file says hello.");
+    assert_eq!(recorded[4].get_text(), "Thanks!");
+    assert_eq!(
+        recorded[5].id,
+        payloads(&events, "agent-complete")[0]["message_id"]
+    );
+}
+
+#[tokio::test]
+async fn test_request_size_is_checked_before_each_request() {
+    let server = FakeSseServer::start(&["anthropic_tool_use.sse", "anthropic_final_text.sse"]);
+    let project = project_with_file();
+    let mut config = project_config("anthropic", "claude-test", &server.base_url);
+    config.execution.max_message_bytes = 100;
+    let adapter =
+        AnthropicAdapter::new(config, project.path().to_path_buf(), DEFAULT_SYSTEM_PROMPT).unwrap();
+
+    let app = mock_app();
+    let events = record_events(app.handle());
+    let exec = execution_context();
+
+    // The first request fits; a steering message over the limit arrives mid-run
+    let session = exec.session.clone();
+    app.handle().listen_any("agent-tool-end", move |_| {
+        queue_on(&session, &"x".repeat(200));
+    });
+
+    let result = adapter
+        .send_message_with_runtime(
+            user_message(),
+            None,
+            None,
+            SendOptions::default(),
+            exec,
+            EventSink::broadcast(app.handle().clone()),
+        )
+        .await;
+
+    assert!(matches!(result, Err(AgentError::RequestTooLarge(_))));
+    assert_eq!(server.requests().len(), 1);
+    let errors = payloads(&events, "agent-error");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]["error"]
+        .as_str()
+        .unwrap()
+        .contains("execution.max_message_bytes"));
+}
+
+#[tokio::test]
+async fn test_pause_holds_before_next_request() {
+    let server = FakeSseServer::start(&["anthropic_tool_use.sse", "anthropic_final_text.sse"]);
+    let project = project_with_file();
+    let adapter = AnthropicAdapter::new(
+        project_config("anthropic", "claude-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let app = mock_app();
+    let events = record_events(app.handle());
+    let exec = execution_context();
+    let session = exec.session.clone();
+
+    let pausing = session.clone();
+    app.handle().listen_any("agent-tool-end", move |_| {
+        pausing.pause();
+    });
+
+    let resume_when_paused = async {
+        loop {
+            let paused = payloads(&events, "agent-status")
+                .iter()
+                .any(|p| p["status"] == "paused");
+            if paused {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        // The tool finished, but the follow-up request is held
+        assert_eq!(server.requests().len(), 1);
+        assert!(session.resume());
+    };
+
+    let (result, ()) = tokio::join!(
+        adapter.send_message_with_runtime(
+            user_message(),
+            None,
+            None,
+            SendOptions::default(),
+            exec,
+            EventSink::broadcast(app.handle().clone()),
+        ),
+        resume_when_paused
+    );
+    result.unwrap();
+
+    assert_eq!(server.requests().len(), 2);
+    assert_tool_round_trip_events(&events, "The file says hello.");
+}
+
+/// Local executor of a session `depth` levels deep, in a project whose config
+/// file points at `server` and allows two levels of sub-agents.
+fn executor_at_depth(
+    project: &std::path::Path,
+    server: &FakeSseServer,
+    depth: u32,
+) -> LocalExecutor {
+    let config = config_toml("anthropic", "claude-test", &server.base_url)
+        .replace("[execution]\n", "[execution]\nmax_agent_depth = 2\n");
+    std::fs::create_dir_all(project.join(".devflow")).unwrap();
+    std::fs::write(project.join(".devflow/config.toml"), config).unwrap();
+
+    let session = SessionState::new();
+    session.set_agent_depth(depth);
+    LocalExecutor::with_session(
+        project.to_path_buf(),
+        30,
+        &NetworkConfig::default(),
+        session,
+        CancellationToken::new(),
+        Arc::new(SessionUsageTracker::new()),
+    )
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_dispatch_agent_respects_max_agent_depth() {
+    let server = FakeSseServer::start(&["anthropic_final_text.sse"]);
+    let project = project_with_file();
+    let dispatch = serde_json::json!({
+        "task": "Read hello.txt",
+        "tools": ["read_file", "dispatch_agent"],
+    });
+
+    // At the limit, dispatching fails without a request
+    let at_limit = executor_at_depth(project.path(), &server, 2);
+    match at_limit
+        .execute(ToolName::DispatchAgent, dispatch.clone())
+        .await
+    {
+        Err(AgentError::ToolExecutionError(msg)) => {
+            assert!(msg.contains("Maximum sub-agent depth (2)"), "{}", msg)
+        }
+        other => panic!("expected depth error, got {:?}", other),
+    }
+    assert!(server.requests().is_empty());
+
+    // One level above, the sub-agent runs at the limit without dispatch_agent
+    let above = executor_at_depth(project.path(), &server, 1);
+    let answer = above
+        .execute(ToolName::DispatchAgent, dispatch)
+        .await
+        .unwrap();
+    assert!(answer.contains("The file says hello."), "{}", answer);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let tools: Vec<&str> = requests[0].body["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert_eq!(tools, ["read_file"]);
+}
+
+fn commit_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {"subject": {"type": "string"}},
+        "required": ["subject"]
+    })
+}
+
+#[tokio::test]
+async fn test_anthropic_structured_output_forces_tool() {
+    let server = FakeSseServer::start(&["anthropic_structured.sse"]);
+    let project = project_with_file();
+    let adapter = AnthropicAdapter::new(
+        project_config("anthropic", "claude-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let result = adapter
+        .run_headless(
+            user_message(),
+            None,
+            None,
+            vec![],
+            Some(commit_schema()),
+            execution_context(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result.structured,
+        Some(serde_json::json!({"subject": "Add greeting file"}))
+    );
+
+    let body = &server.requests()[0].body;
+    assert_eq!(body["tool_choice"]["name"], "structured_output");
+    assert_eq!(body["tools"][0]["name"], "structured_output");
+    assert_eq!(body["tools"][0]["input_schema"], commit_schema());
+}
+
+#[tokio::test]
+async fn test_gemini_structured_output_uses_response_schema() {
+    let server = FakeSseServer::start(&["gemini_structured.sse"]);
+    let project = project_with_file();
+    let adapter = GeminiAdapter::new(
+        project_config("gemini", "gemini-test", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+
+    let result = adapter
+        .run_headless(
+            user_message(),
+            None,
+            None,
+            vec![],
+            Some(commit_schema()),
+            execution_context(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result.structured,
+        Some(serde_json::json!({"subject": "Add greeting file"}))
+    );
+
+    let body = &server.requests()[0].body;
+    assert!(body.get("tools").is_none());
+    assert_eq!(
+        body["generationConfig"]["responseMimeType"],
+        "application/json"
+    );
+    assert_eq!(body["generationConfig"]["responseSchema"], commit_schema());
+}
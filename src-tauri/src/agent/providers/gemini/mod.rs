@@ -3,103 +3,190 @@ mod types;
 use std::env;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
-use futures::StreamExt;
 use reqwest::Client;
-use tauri::{AppHandle, Emitter};
+use tauri::Runtime;
 use tokio_util::sync::CancellationToken;
-use uuid::Uuid;
 
+use crate::agent::attribution::Attribution;
 use crate::agent::error::AgentError;
+use crate::agent::events::EventSink;
+use crate::agent::http::build_provider_client;
+use crate::agent::models;
 use crate::agent::provider::{ExecutionContext, HeadlessResult, ProviderAdapter};
-use crate::agent::tools::{get_tool_definitions, SessionState};
+use crate::agent::rate_limit::{self, estimate_request_tokens, shared_rate_limiter, RateLimiter};
+use crate::agent::request_cache::{self, shared_request_cache, RequestCache};
+use crate::agent::tools::ToolDescriptionOverrides;
 use crate::agent::types::{
-    AgentCancelledPayload, AgentChunkPayload, AgentCompletePayload, AgentErrorPayload, AgentStatus,
-    ChatMessage, ContentBlockStartPayload, ContentBlockType, ToolDefinition,
+    AgentChunkPayload, AgentStatus, ChatMessage, ContentBlockStartPayload, ContentBlockType,
+    SendOptions, ToolChoice, ToolDefinition,
+};
+use crate::agent::usage::TokenUsage;
+use crate::agent::usage_history::record_usage;
+use crate::config::{
+    AgentConfig, AutoApproveConfig, ExecutionConfig, LoggingConfig, NetworkConfig, ProjectConfig,
+    PromptsConfig, ProviderTimeouts, WorkspaceConfig,
 };
-use crate::agent::usage::{SessionUsageTracker, UsageSource};
-use crate::config::{AgentConfig, ExecutionConfig, PromptsConfig};
 
 use super::{
-    build_system_prompt, check_iteration_limit,
-    compaction::{format_compacted_context, get_context_limit, maybe_compact, CompactionContext},
-    create_executor, emit_status, emit_usage, execute_tool_calls,
+    compaction::{get_compaction_threshold, get_context_limit},
+    compaction_adapter, emit_error, emit_rate_limited, emit_slow_response, emit_status,
     headless::{
         HeadlessResponse, HeadlessStreamer, ToolCall as HeadlessToolCall,
         ToolResult as HeadlessToolResult,
     },
-    run_headless_loop, HeadlessContext, StreamContext, StreamingState, ToolCall,
+    orchestration,
+    sse::{self, SseEvent},
+    ProviderRuntime, RunSettings, SlowResponseWatch, StreamContext, StreamedTurn,
 };
 use types::{
     FunctionDeclaration, FunctionResponse, FunctionResponseContent, GeminiContent, GeminiPart,
     GeminiRequest, GeminiResponse, GeminiSystemInstruction, GeminiTool, GenerationConfig,
-    StreamedResponse,
+    SafetySetting, StreamedResponse, ThinkingConfig, ToolConfig,
 };
 
-const API_BASE_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+pub(super) const DEFAULT_BASE_URL: &str = "https://generativelanguage.googleapis.com";
+
+/// Appends user text to the conversation, joining a trailing user turn (e.g.
+/// function responses) so roles keep alternating.
+fn push_user_text(conversation: &mut Vec<GeminiContent>, text: String) {
+    let part = GeminiPart::Text { text };
+    match conversation.last_mut().filter(|c| c.role == "user") {
+        Some(last) => last.parts.push(part),
+        None => conversation.push(GeminiContent {
+            role: "user".to_string(),
+            parts: vec![part],
+        }),
+    }
+}
 
 pub struct GeminiAdapter {
     client: Client,
     config: AgentConfig,
     prompts: PromptsConfig,
     execution: ExecutionConfig,
+    network: NetworkConfig,
+    attribution: Option<Attribution>,
+    logging: LoggingConfig,
+    auto_approve: AutoApproveConfig,
+    workspace: WorkspaceConfig,
+    timeouts: ProviderTimeouts,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    request_cache: Option<Arc<RequestCache>>,
     api_key: String,
+    models_url: String,
     project_path: PathBuf,
     app_system_prompt: &'static str,
     context_limit: u32,
+    compaction_threshold: f64,
     extraction_prompt: Option<String>,
+    compaction_adapter: Option<Arc<dyn ProviderAdapter>>,
+    tool_descriptions: ToolDescriptionOverrides,
 }
 
 impl GeminiAdapter {
     pub fn new(
-        config: AgentConfig,
-        prompts: PromptsConfig,
-        execution: ExecutionConfig,
+        project_config: ProjectConfig,
         project_path: PathBuf,
         app_system_prompt: &'static str,
-        extraction_prompt: Option<String>,
     ) -> Result<Self, AgentError> {
+        let timeouts = project_config.provider_timeouts();
+        let tool_descriptions = ToolDescriptionOverrides::from_config(&project_config);
+        let rate_limiter = shared_rate_limiter(
+            &project_config.agent.provider,
+            &project_config.provider_rate_limit(),
+        );
+        let request_cache = shared_request_cache(
+            &project_config.agent.provider,
+            &project_config.request_cache,
+        );
+        let compaction_adapter = compaction_adapter(&project_config, &project_path)?;
+        let mut config = project_config.agent;
+        config.max_tokens = models::clamp_max_tokens(&config.model, config.max_tokens);
         let api_key = env::var(&config.api_key_env)
             .map_err(|_| AgentError::MissingApiKey(config.api_key_env.clone()))?;
 
-        let context_limit = get_context_limit(config.context_limit);
+        let context_limit = get_context_limit(config.context_limit, &config.model);
+        let compaction_threshold = get_compaction_threshold(config.compaction_threshold);
+        let attribution = Attribution::from_config(&project_config.attribution, &config.model);
+        let models_url = format!(
+            "{}/v1beta/models",
+            config
+                .base_url
+                .as_deref()
+                .unwrap_or(DEFAULT_BASE_URL)
+                .trim_end_matches('/')
+        );
 
         Ok(Self {
-            client: Client::new(),
+            client: build_provider_client(&project_config.network, &timeouts)?,
             config,
-            prompts,
-            execution,
+            prompts: project_config.prompts,
+            execution: project_config.execution,
+            network: project_config.network,
+            attribution,
+            logging: project_config.logging,
+            auto_approve: project_config.auto_approve,
+            workspace: project_config.workspace,
+            timeouts,
+            rate_limiter,
+            request_cache,
             api_key,
+            models_url,
             project_path,
             app_system_prompt,
             context_limit,
-            extraction_prompt,
+            compaction_threshold,
+            extraction_prompt: project_config.extraction_prompt,
+            compaction_adapter,
+            tool_descriptions,
         })
     }
 
-    fn build_tools(&self) -> Vec<GeminiTool> {
-        let tool_defs = get_tool_definitions();
-        let declarations: Vec<FunctionDeclaration> =
-            tool_defs.iter().map(FunctionDeclaration::from).collect();
+    /// Generation settings from `[agent]` and `[agent.gemini]`, optionally
+    /// constraining the output to a JSON schema.
+    fn generation_config(&self, response_schema: Option<&serde_json::Value>) -> GenerationConfig {
+        let options = self.config.gemini.as_ref();
+        GenerationConfig {
+            max_output_tokens: Some(self.config.max_tokens),
+            temperature: self.config.temperature,
+            top_p: options.and_then(|o| o.top_p),
+            thinking_config: self.thinking_config(),
+            response_mime_type: response_schema.map(|_| "application/json".to_string()),
+            response_schema: response_schema.cloned(),
+        }
+    }
 
-        vec![GeminiTool {
-            function_declarations: declarations,
-        }]
+    fn thinking_config(&self) -> Option<ThinkingConfig> {
+        let thinking_budget = self.config.gemini.as_ref()?.thinking_budget?;
+        Some(ThinkingConfig { thinking_budget })
+    }
+
+    fn safety_settings(&self) -> Vec<SafetySetting> {
+        self.config
+            .gemini
+            .iter()
+            .flat_map(|o| &o.safety_settings)
+            .map(SafetySetting::from)
+            .collect()
     }
 
     fn api_url(&self) -> String {
         format!(
             "{}/{}:streamGenerateContent?alt=sse&key={}",
-            API_BASE_URL, self.config.model, self.api_key
+            self.models_url, self.config.model, self.api_key
         )
     }
 
-    async fn stream_response(
+    async fn stream_response<R: Runtime>(
         &self,
         contents: &[GeminiContent],
         system: Option<String>,
-        ctx: &StreamContext<'_>,
+        tools: &[ToolDefinition],
+        tool_choice: ToolChoice,
+        ctx: &StreamContext<'_, R>,
     ) -> Result<StreamedResponse, AgentError> {
         if ctx.cancel_token.is_cancelled() {
             return Err(AgentError::Cancelled);
@@ -112,20 +199,36 @@ impl GeminiAdapter {
         let request = GeminiRequest {
             contents: contents.to_vec(),
             system_instruction,
-            tools: Some(self.build_tools()),
-            generation_config: Some(GenerationConfig {
-                max_output_tokens: Some(self.config.max_tokens),
-            }),
+            tools: Some(self.build_tools_from_definitions(tools)),
+            tool_config: ToolConfig::for_choice(tool_choice),
+            generation_config: Some(self.generation_config(None)),
+            safety_settings: self.safety_settings(),
         };
 
-        emit_status(ctx.app_handle, AgentStatus::Thinking, None);
+        let estimated_tokens = estimate_request_tokens(&request);
+        rate_limit::acquire(
+            self.rate_limiter.as_deref(),
+            estimated_tokens,
+            ctx.cancel_token,
+            |wait| emit_rate_limited(ctx.events, wait),
+        )
+        .await?;
 
-        let response = self
-            .client
-            .post(self.api_url())
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
+        emit_status(ctx.events, AgentStatus::Thinking, None);
+
+        let threshold = Duration::from_secs(self.timeouts.slow_response_secs);
+        let watch = SlowResponseWatch::new(threshold, |elapsed| {
+            emit_slow_response(ctx.events, elapsed, threshold)
+        });
+
+        let response = watch
+            .guard(
+                self.client
+                    .post(self.api_url())
+                    .header("content-type", "application/json")
+                    .json(&request)
+                    .send(),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -135,82 +238,42 @@ impl GeminiAdapter {
         }
 
         let mut streamed = StreamedResponse::new();
-        let mut stream = response.bytes_stream();
-        let mut buffer = String::new();
         let mut first_text_chunk = true;
-
-        loop {
-            if ctx.cancel_token.is_cancelled() {
-                return Err(AgentError::Cancelled);
+        let idle_timeout = Duration::from_secs(self.timeouts.stream_idle_secs);
+        sse::read_events(response, ctx.cancel_token, &watch, idle_timeout, |event| {
+            let is_text = self.process_sse_event(event, ctx, &mut streamed);
+            if streamed.first_token_at.is_none() && streamed.block_count() > 0 {
+                streamed.first_token_at = Some(Instant::now());
             }
-
-            tokio::select! {
-                _ = ctx.cancel_token.cancelled() => {
-                    return Err(AgentError::Cancelled);
-                }
-                chunk_result = stream.next() => {
-                    match chunk_result {
-                        Some(Ok(chunk)) => {
-                            buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                            // Handle both \r\n\r\n (Gemini) and \n\n delimiters
-                            while let Some((event_end, delim_len)) = buffer
-                                .find("\r\n\r\n")
-                                .map(|i| (i, 4))
-                                .or_else(|| buffer.find("\n\n").map(|i| (i, 2)))
-                            {
-                                let event_data = buffer[..event_end].to_string();
-                                buffer = buffer[event_end + delim_len..].to_string();
-
-                                let is_text = self.process_sse_event(&event_data, ctx, &mut streamed);
-
-                                if is_text && first_text_chunk {
-                                    emit_status(ctx.app_handle, AgentStatus::Streaming, None);
-                                    first_text_chunk = false;
-                                }
-                            }
-                        }
-                        Some(Err(e)) => {
-                            return Err(AgentError::Http(e));
-                        }
-                        None => break,
-                    }
-                }
+            if is_text && first_text_chunk {
+                emit_status(ctx.events, AgentStatus::Streaming, None);
+                first_text_chunk = false;
             }
-        }
+        })
+        .await?;
 
+        rate_limit::settle(
+            self.rate_limiter.as_deref(),
+            estimated_tokens,
+            streamed.usage,
+        );
         Ok(streamed)
     }
 
-    fn process_sse_event(
+    fn process_sse_event<R: Runtime>(
         &self,
-        event_data: &str,
-        ctx: &StreamContext<'_>,
+        event: &SseEvent,
+        ctx: &StreamContext<'_, R>,
         streamed: &mut StreamedResponse,
     ) -> bool {
-        let mut data = None;
         let mut is_text = false;
 
-        for line in event_data.lines() {
-            if let Some(suffix) = line.strip_prefix("data: ") {
-                data = Some(suffix.to_string());
-            }
-        }
-
-        let Some(data) = data else { return false };
-
-        let response: GeminiResponse = match serde_json::from_str(&data) {
-            Ok(r) => r,
-            Err(_) => return false,
+        let Some(response) = event.json::<GeminiResponse>() else {
+            return false;
         };
 
         if let Some(error) = response.error {
-            let _ = ctx.app_handle.emit(
-                "agent-error",
-                AgentErrorPayload {
-                    error: error.message,
-                },
-            );
+            emit_error(ctx.events, error.message);
             return false;
         }
 
@@ -230,7 +293,7 @@ impl GeminiAdapter {
                             match part {
                                 types::ResponsePart::Text { text } => {
                                     if !streamed.has_text_block {
-                                        let _ = ctx.app_handle.emit(
+                                        let _ = ctx.events.emit(
                                             "agent-content-block-start",
                                             ContentBlockStartPayload {
                                                 block_index: ctx.block_offset,
@@ -239,12 +302,14 @@ impl GeminiAdapter {
                                         );
                                     }
 
-                                    let local_index = streamed.append_text(&text);
-                                    let _ = ctx.app_handle.emit(
+                                    let block_index =
+                                        ctx.block_offset + streamed.append_text(&text);
+                                    ctx.partial.push_text(block_index, &text);
+                                    let _ = ctx.events.emit(
                                         "agent-chunk",
                                         AgentChunkPayload {
                                             delta: text.clone(),
-                                            block_index: ctx.block_offset + local_index,
+                                            block_index,
                                         },
                                     );
                                     is_text = true;
@@ -252,12 +317,14 @@ impl GeminiAdapter {
                                 types::ResponsePart::FunctionCall { function_call } => {
                                     let local_index =
                                         streamed.add_function_call(function_call.clone());
-                                    let _ = ctx.app_handle.emit(
+                                    let tool_use_id =
+                                        streamed.call_ids.last().cloned().unwrap_or_default();
+                                    let _ = ctx.events.emit(
                                         "agent-content-block-start",
                                         ContentBlockStartPayload {
                                             block_index: ctx.block_offset + local_index,
                                             block_type: ContentBlockType::ToolUse {
-                                                tool_use_id: Uuid::new_v4().to_string(),
+                                                tool_use_id,
                                                 tool_name: function_call.name,
                                             },
                                         },
@@ -274,117 +341,11 @@ impl GeminiAdapter {
         is_text
     }
 
-    async fn execute_tool_loop(
-        &self,
-        initial_contents: Vec<GeminiContent>,
-        system_prompt: Option<String>,
-        session: SessionState,
-        app_handle: &AppHandle,
-        cancel_token: &CancellationToken,
-        usage_tracker: &Arc<SessionUsageTracker>,
-    ) -> Result<Option<String>, AgentError> {
-        let executor = create_executor(
-            &self.project_path,
-            &self.execution,
-            session.clone(),
-            cancel_token.clone(),
-            Arc::clone(usage_tracker),
-        );
-        let mut conversation = initial_contents;
-        let max_iterations = self.execution.max_tool_iterations;
-        let mut iteration = 0u32;
-        let mut streaming = StreamingState::new();
-
-        loop {
-            if cancel_token.is_cancelled() {
-                return Err(AgentError::Cancelled);
-            }
-
-            let ctx = streaming.create_context(app_handle, cancel_token);
-            let response = self
-                .stream_response(&conversation, system_prompt.clone(), &ctx)
-                .await?;
-
-            emit_usage(app_handle, usage_tracker, response.usage, UsageSource::Main);
-            streaming.advance(response.block_count());
-
-            if !response.has_function_calls() {
-                return Ok(response.finish_reason);
-            }
-
-            iteration += 1;
-            check_iteration_limit(iteration, max_iterations)?;
-
-            // Add model's response with function calls to conversation
-            let model_parts: Vec<GeminiPart> = response
-                .function_calls
-                .iter()
-                .map(|fc| GeminiPart::FunctionCall {
-                    function_call: fc.clone(),
-                })
-                .collect();
-
-            conversation.push(GeminiContent {
-                role: "model".to_string(),
-                parts: model_parts,
-            });
-
-            // Execute tools with global block indices
-            let text_offset = if response.has_text_block { 1 } else { 0 };
-            let tool_calls: Vec<ToolCall> = response
-                .function_calls
-                .iter()
-                .enumerate()
-                .map(|(i, fc)| ToolCall {
-                    id: Uuid::new_v4().to_string(),
-                    name: fc.name.clone(),
-                    input: fc.args.clone(),
-                    block_index: ctx.block_offset + text_offset + i as u32,
-                })
-                .collect();
-
-            let results =
-                execute_tool_calls(tool_calls, &executor, &session, app_handle, cancel_token)
-                    .await?;
-
-            let function_responses: Vec<GeminiPart> = results
-                .into_iter()
-                .map(|r| {
-                    let response_content = if r.is_error {
-                        FunctionResponseContent {
-                            result: None,
-                            error: Some(r.output),
-                        }
-                    } else {
-                        FunctionResponseContent {
-                            result: Some(r.output),
-                            error: None,
-                        }
-                    };
-                    GeminiPart::FunctionResponse {
-                        function_response: FunctionResponse {
-                            name: r.name,
-                            response: response_content,
-                        },
-                    }
-                })
-                .collect();
-
-            // Add function responses as user message
-            conversation.push(GeminiContent {
-                role: "user".to_string(),
-                parts: function_responses,
-            });
-
-            emit_status(app_handle, AgentStatus::ToolWaiting, None);
-        }
-    }
-
     async fn call_extraction_api(
         &self,
         prompt: String,
         cancel_token: &CancellationToken,
-    ) -> Result<String, AgentError> {
+    ) -> Result<(String, TokenUsage), AgentError> {
         if cancel_token.is_cancelled() {
             return Err(AgentError::Cancelled);
         }
@@ -402,15 +363,38 @@ impl GeminiAdapter {
             }],
             system_instruction: Some(system_instruction),
             tools: None,
+            tool_config: None,
             generation_config: Some(GenerationConfig {
                 max_output_tokens: Some(2048),
+                temperature: None,
+                top_p: None,
+                thinking_config: self.thinking_config(),
+                response_mime_type: None,
+                response_schema: None,
             }),
+            safety_settings: self.safety_settings(),
         };
 
+        let cache_key = request_cache::cache_key(self.request_cache.as_deref(), &request);
+        if let Some(text) =
+            request_cache::lookup::<String>(self.request_cache.as_deref(), cache_key.as_ref())
+        {
+            // Nothing was sent, so there is no usage to record
+            return Ok((text, TokenUsage::default()));
+        }
+
+        rate_limit::acquire(
+            self.rate_limiter.as_deref(),
+            estimate_request_tokens(&request),
+            cancel_token,
+            |_| {},
+        )
+        .await?;
+
         // Use non-streaming endpoint
         let url = format!(
             "{}/{}:generateContent?key={}",
-            API_BASE_URL, self.config.model, self.api_key
+            self.models_url, self.config.model, self.api_key
         );
 
         let response = self
@@ -445,7 +429,17 @@ impl GeminiAdapter {
             .unwrap_or("")
             .to_string();
 
-        Ok(text)
+        let usage = TokenUsage {
+            input_tokens: json["usageMetadata"]["promptTokenCount"]
+                .as_u64()
+                .unwrap_or(0) as u32,
+            output_tokens: json["usageMetadata"]["candidatesTokenCount"]
+                .as_u64()
+                .unwrap_or(0) as u32,
+        };
+
+        request_cache::store(self.request_cache.as_deref(), cache_key, &text);
+        Ok((text, usage))
     }
 
     fn build_tools_from_definitions(&self, tools: &[ToolDefinition]) -> Vec<GeminiTool> {
@@ -462,6 +456,7 @@ impl GeminiAdapter {
         contents: &[GeminiContent],
         system: Option<String>,
         tools: &[ToolDefinition],
+        response_schema: Option<&serde_json::Value>,
         cancel_token: &CancellationToken,
     ) -> Result<StreamedResponse, AgentError> {
         if cancel_token.is_cancelled() {
@@ -472,21 +467,53 @@ impl GeminiAdapter {
             parts: vec![GeminiPart::Text { text }],
         });
 
+        // Gemini does not combine function calling with a response schema
         let request = GeminiRequest {
             contents: contents.to_vec(),
             system_instruction,
-            tools: Some(self.build_tools_from_definitions(tools)),
-            generation_config: Some(GenerationConfig {
-                max_output_tokens: Some(self.config.max_tokens),
-            }),
+            tools: response_schema
+                .is_none()
+                .then(|| self.build_tools_from_definitions(tools)),
+            tool_config: None,
+            generation_config: Some(self.generation_config(response_schema)),
+            safety_settings: self.safety_settings(),
         };
 
-        let response = self
-            .client
-            .post(self.api_url())
-            .header("content-type", "application/json")
-            .json(&request)
-            .send()
+        let cache_key = request_cache::cache_key(self.request_cache.as_deref(), &request);
+        if let Some(mut cached) = request_cache::lookup::<StreamedResponse>(
+            self.request_cache.as_deref(),
+            cache_key.as_ref(),
+        ) {
+            // Nothing was sent, so there is no usage to record
+            cached.usage = Default::default();
+            return Ok(cached);
+        }
+
+        let estimated_tokens = estimate_request_tokens(&request);
+        rate_limit::acquire(
+            self.rate_limiter.as_deref(),
+            estimated_tokens,
+            cancel_token,
+            |wait| log::info!("Rate limited, waiting {:?}", wait),
+        )
+        .await?;
+
+        let threshold = Duration::from_secs(self.timeouts.slow_response_secs);
+        let watch = SlowResponseWatch::new(threshold, |elapsed| {
+            log::warn!(
+                "Sub-agent response slow, nothing received for {:?}",
+                elapsed
+            )
+        });
+
+        let response = watch
+            .guard(
+                self.client
+                    .post(self.api_url())
+                    .header("content-type", "application/json")
+                    .json(&request)
+                    .send(),
+            )
             .await?;
 
         if !response.status().is_success() {
@@ -496,59 +523,24 @@ impl GeminiAdapter {
         }
 
         let mut streamed = StreamedResponse::new();
-        let mut stream = response.bytes_stream();
-        let mut buffer = String::new();
-
-        loop {
-            if cancel_token.is_cancelled() {
-                return Err(AgentError::Cancelled);
-            }
-
-            tokio::select! {
-                _ = cancel_token.cancelled() => {
-                    return Err(AgentError::Cancelled);
-                }
-                chunk_result = stream.next() => {
-                    match chunk_result {
-                        Some(Ok(chunk)) => {
-                            buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-                            while let Some((event_end, delim_len)) = buffer
-                                .find("\r\n\r\n")
-                                .map(|i| (i, 4))
-                                .or_else(|| buffer.find("\n\n").map(|i| (i, 2)))
-                            {
-                                let event_data = buffer[..event_end].to_string();
-                                buffer = buffer[event_end + delim_len..].to_string();
-                                self.process_sse_event_headless(&event_data, &mut streamed);
-                            }
-                        }
-                        Some(Err(e)) => {
-                            return Err(AgentError::Http(e));
-                        }
-                        None => break,
-                    }
-                }
-            }
-        }
+        let idle_timeout = Duration::from_secs(self.timeouts.stream_idle_secs);
+        sse::read_events(response, cancel_token, &watch, idle_timeout, |event| {
+            self.process_sse_event_headless(event, &mut streamed)
+        })
+        .await?;
 
+        rate_limit::settle(
+            self.rate_limiter.as_deref(),
+            estimated_tokens,
+            streamed.usage,
+        );
+        request_cache::store(self.request_cache.as_deref(), cache_key, &streamed);
         Ok(streamed)
     }
 
-    fn process_sse_event_headless(&self, event_data: &str, streamed: &mut StreamedResponse) {
-        let mut data = None;
-
-        for line in event_data.lines() {
-            if let Some(suffix) = line.strip_prefix("data: ") {
-                data = Some(suffix.to_string());
-            }
-        }
-
-        let Some(data) = data else { return };
-
-        let response: GeminiResponse = match serde_json::from_str(&data) {
-            Ok(r) => r,
-            Err(_) => return,
+    fn process_sse_event_headless(&self, event: &SseEvent, streamed: &mut StreamedResponse) {
+        let Some(response) = event.json::<GeminiResponse>() else {
+            return;
         };
 
         if let Some(usage_metadata) = &response.usage_metadata {
@@ -584,8 +576,9 @@ impl GeminiAdapter {
         let tool_calls = response
             .function_calls
             .iter()
-            .map(|fc| HeadlessToolCall {
-                id: fc.name.clone(), // Gemini uses name as ID
+            .zip(&response.call_ids)
+            .map(|(fc, id)| HeadlessToolCall {
+                id: id.clone(),
                 name: fc.name.clone(),
                 input: fc.args.clone(),
             })
@@ -613,11 +606,24 @@ impl HeadlessStreamer for GeminiAdapter {
         conversation: &Self::Conversation,
         system_prompt: Option<String>,
         tools: &[ToolDefinition],
+        response_schema: Option<&serde_json::Value>,
         cancel_token: &CancellationToken,
     ) -> Result<HeadlessResponse, AgentError> {
         let response = self
-            .stream_response_headless(conversation, system_prompt, tools, cancel_token)
+            .stream_response_headless(
+                conversation,
+                system_prompt,
+                tools,
+                response_schema,
+                cancel_token,
+            )
             .await?;
+        record_usage(
+            &self.project_path,
+            &self.config.provider,
+            &self.config.model,
+            response.usage,
+        );
 
         Ok(self.to_headless_response(&response))
     }
@@ -684,109 +690,109 @@ impl HeadlessStreamer for GeminiAdapter {
             parts,
         });
     }
+
+    fn append_user_text(&self, conversation: &mut Self::Conversation, text: String) {
+        push_user_text(conversation, text);
+    }
 }
 
-#[async_trait]
-impl ProviderAdapter for GeminiAdapter {
-    async fn send_message(
+impl GeminiAdapter {
+    /// Same as `ProviderAdapter::send_message`, for any Tauri runtime (the mock one in tests).
+    pub async fn send_message_with_runtime<R: Runtime>(
         &self,
         messages: Vec<ChatMessage>,
         system_prompt: Option<String>,
         memory: Option<String>,
+        options: SendOptions,
         ctx: ExecutionContext,
-        app_handle: AppHandle,
+        events: EventSink<R>,
     ) -> Result<(), AgentError> {
-        // Build system prompt first
-        let base_system = build_system_prompt(
-            self.app_system_prompt,
-            &self.prompts,
-            system_prompt,
-            memory.as_deref(),
-        );
-
-        let message_id = Uuid::new_v4().to_string();
-
-        emit_status(&app_handle, AgentStatus::Sending, None);
+        orchestration::send_message(self, messages, system_prompt, memory, options, ctx, events)
+            .await
+    }
+}
 
-        // Check if compaction is needed
-        let compaction_ctx = CompactionContext {
+#[async_trait]
+impl ProviderRuntime for GeminiAdapter {
+    fn settings(&self) -> RunSettings<'_> {
+        RunSettings {
+            provider: &self.config.provider,
+            model: &self.config.model,
+            api_key: &self.api_key,
+            project_path: &self.project_path,
+            app_system_prompt: self.app_system_prompt,
+            prompts: &self.prompts,
+            execution: &self.execution,
+            network: &self.network,
+            logging: &self.logging,
+            auto_approve: &self.auto_approve,
+            workspace: &self.workspace,
+            attribution: self.attribution.as_ref(),
             context_limit: self.context_limit,
+            compaction_threshold: self.compaction_threshold,
             extraction_prompt: self.extraction_prompt.as_deref(),
-            session: &ctx.session,
-            app_handle: &app_handle,
-        };
+            compaction_adapter: self.compaction_adapter.as_deref(),
+            tool_descriptions: &self.tool_descriptions,
+        }
+    }
 
-        let compaction_result =
-            maybe_compact(&messages, Some(&base_system), &compaction_ctx, |prompt| {
-                self.call_extraction_api(prompt, &ctx.cancel_token)
-            })
-            .await?;
+    fn supports_tool(&self, tool: &ToolDefinition) -> bool {
+        types::schema_supported(&tool.input_schema)
+    }
 
-        // Prepare messages and system prompt based on compaction result
-        let (final_messages, final_system) = match compaction_result {
-            Some(result) => {
-                let system_with_context = format!("{}\n\n{}", base_system, result.compacted_text);
-                (result.preserved_messages, system_with_context)
-            }
-            None => {
-                let existing = ctx.session.get_compacted().await;
-                if existing.summary.is_some() || !existing.facts.is_empty() {
-                    let compacted_text = format_compacted_context(&existing);
-                    let system_with_context = format!("{}\n\n{}", base_system, compacted_text);
-                    (messages.clone(), system_with_context)
-                } else {
-                    (messages.clone(), base_system)
-                }
-            }
-        };
+    async fn extract(
+        &self,
+        prompt: String,
+        cancel_token: &CancellationToken,
+    ) -> Result<(String, TokenUsage), AgentError> {
+        let (text, usage) = self.call_extraction_api(prompt, cancel_token).await?;
+        record_usage(
+            &self.project_path,
+            &self.config.provider,
+            &self.config.model,
+            usage,
+        );
+        Ok((text, usage))
+    }
 
-        let gemini_contents: Vec<GeminiContent> =
-            final_messages.iter().map(GeminiContent::from).collect();
-
-        let result = self
-            .execute_tool_loop(
-                gemini_contents,
-                Some(final_system),
-                ctx.session,
-                &app_handle,
-                &ctx.cancel_token,
-                &ctx.usage_tracker,
-            )
-            .await;
-
-        match result {
-            Ok(finish_reason) => {
-                emit_status(&app_handle, AgentStatus::Idle, None);
-                let _ = app_handle.emit(
-                    "agent-complete",
-                    AgentCompletePayload {
-                        message_id,
-                        stop_reason: finish_reason,
-                    },
-                );
-                Ok(())
-            }
-            Err(AgentError::Cancelled) => {
-                emit_status(&app_handle, AgentStatus::Cancelled, None);
-                let _ = app_handle.emit(
-                    "agent-cancelled",
-                    AgentCancelledPayload {
-                        reason: "Cancelled by user".to_string(),
-                    },
-                );
-                Err(AgentError::Cancelled)
-            }
-            Err(e) => {
-                emit_status(&app_handle, AgentStatus::Error, Some(e.to_string()));
-                let _ = app_handle.emit(
-                    "agent-error",
-                    AgentErrorPayload {
-                        error: e.to_string(),
-                    },
-                );
-                Err(e)
-            }
-        }
+    async fn stream_turn<R: Runtime>(
+        &self,
+        conversation: &Self::Conversation,
+        system_prompt: Option<String>,
+        tools: &[ToolDefinition],
+        tool_choice: ToolChoice,
+        ctx: &StreamContext<'_, R>,
+    ) -> Result<StreamedTurn, AgentError> {
+        let response = self
+            .stream_response(conversation, system_prompt, tools, tool_choice, ctx)
+            .await?;
+        // The text block, if any, comes first and function calls follow it
+        let first_call = u32::from(response.has_text_block);
+        let tool_blocks = (0..response.function_calls.len() as u32)
+            .map(|i| first_call + i)
+            .collect();
+        Ok(StreamedTurn {
+            response: self.to_headless_response(&response),
+            tool_blocks,
+            block_count: response.block_count(),
+            first_token_at: response.first_token_at,
+        })
+    }
+}
+
+#[async_trait]
+impl ProviderAdapter for GeminiAdapter {
+    async fn send_message(
+        &self,
+        messages: Vec<ChatMessage>,
+        system_prompt: Option<String>,
+        memory: Option<String>,
+        options: SendOptions,
+        ctx: ExecutionContext,
+        events: EventSink,
+    ) -> Result<(), AgentError> {
+        self.send_message_with_runtime(messages, system_prompt, memory, options, ctx, events)
+            .await
     }
 
     async fn run_headless(
@@ -795,38 +801,34 @@ impl ProviderAdapter for GeminiAdapter {
         system_prompt: Option<String>,
         memory: Option<String>,
         tools: Vec<ToolDefinition>,
+        response_schema: Option<serde_json::Value>,
         ctx: ExecutionContext,
     ) -> Result<HeadlessResult, AgentError> {
-        let system = build_system_prompt(
-            self.app_system_prompt,
-            &self.prompts,
-            system_prompt,
-            memory.as_deref(),
-        );
-        let executor = create_executor(
-            &self.project_path,
-            &self.execution,
-            ctx.session,
-            ctx.cancel_token.clone(),
-            Arc::clone(&ctx.usage_tracker),
-        );
-
-        run_headless_loop(
+        orchestration::run_headless(
             self,
             messages,
-            HeadlessContext {
-                system_prompt: Some(system),
-                tools,
-                executor: &executor,
-                max_iterations: self.execution.max_tool_iterations,
-                cancel_token: &ctx.cancel_token,
-                usage_tracker: ctx.usage_tracker,
-            },
+            system_prompt,
+            memory,
+            tools,
+            response_schema,
+            ctx,
         )
         .await
     }
 
+    async fn complete(
+        &self,
+        prompt: String,
+        cancel_token: &CancellationToken,
+    ) -> Result<(String, TokenUsage), AgentError> {
+        ProviderRuntime::extract(self, prompt, cancel_token).await
+    }
+
     fn model(&self) -> &str {
         &self.config.model
     }
+
+    fn provider(&self) -> &str {
+        &self.config.provider
+    }
 }
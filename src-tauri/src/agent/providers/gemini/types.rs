@@ -1,7 +1,11 @@
+use std::time::Instant;
+
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::agent::types::{ChatMessage, MessageRole, ToolDefinition};
+use crate::agent::types::{ChatMessage, MessageRole, ToolChoice, ToolDefinition};
 use crate::agent::usage::TokenUsage;
+use crate::config::GeminiSafetySetting;
 
 // === Request Types ===
 
@@ -14,7 +18,11 @@ pub struct GeminiRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<GeminiTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_config: Option<ToolConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub generation_config: Option<GenerationConfig>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub safety_settings: Vec<SafetySetting>,
 }
 
 #[derive(Debug, Serialize)]
@@ -71,6 +79,32 @@ pub struct GeminiTool {
     pub function_declarations: Vec<FunctionDeclaration>,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolConfig {
+    pub function_calling_config: FunctionCallingConfig,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FunctionCallingConfig {
+    /// AUTO, ANY or NONE
+    pub mode: &'static str,
+}
+
+impl ToolConfig {
+    /// Config forcing or forbidding function calls; None leaves the API default (AUTO).
+    pub fn for_choice(choice: ToolChoice) -> Option<Self> {
+        let mode = match choice {
+            ToolChoice::Auto => return None,
+            ToolChoice::Required => "ANY",
+            ToolChoice::None => "NONE",
+        };
+        Some(Self {
+            function_calling_config: FunctionCallingConfig { mode },
+        })
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct FunctionDeclaration {
     pub name: String,
@@ -83,6 +117,67 @@ pub struct FunctionDeclaration {
 pub struct GenerationConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_output_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_config: Option<ThinkingConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ThinkingConfig {
+    pub thinking_budget: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SafetySetting {
+    pub category: String,
+    pub threshold: String,
+}
+
+impl From<&GeminiSafetySetting> for SafetySetting {
+    fn from(setting: &GeminiSafetySetting) -> Self {
+        Self {
+            category: setting.category.clone(),
+            threshold: setting.threshold.clone(),
+        }
+    }
+}
+
+/// JSON Schema keywords outside the OpenAPI subset Gemini accepts for
+/// function parameters
+const UNSUPPORTED_SCHEMA_KEYWORDS: &[&str] = &[
+    "$ref",
+    "$defs",
+    "additionalProperties",
+    "allOf",
+    "const",
+    "oneOf",
+    "patternProperties",
+];
+
+/// Whether Gemini accepts `schema` as function parameters.
+pub fn schema_supported(schema: &serde_json::Value) -> bool {
+    let Some(object) = schema.as_object() else {
+        return true;
+    };
+    object.iter().all(|(key, value)| match key.as_str() {
+        key if UNSUPPORTED_SCHEMA_KEYWORDS.contains(&key) => false,
+        "properties" => value
+            .as_object()
+            .is_none_or(|properties| properties.values().all(schema_supported)),
+        "items" => schema_supported(value),
+        "anyOf" => value
+            .as_array()
+            .is_none_or(|schemas| schemas.iter().all(schema_supported)),
+        _ => true,
+    })
 }
 
 impl From<&ToolDefinition> for FunctionDeclaration {
@@ -213,13 +308,17 @@ pub struct GeminiError {
 // === Streaming State ===
 
 /// Accumulates streamed response chunks into complete content blocks.
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct StreamedResponse {
     pub text_content: String,
     pub function_calls: Vec<FunctionCall>,
+    /// Ids for `function_calls`; Gemini does not assign any, so they are made up locally
+    pub call_ids: Vec<String>,
     pub finish_reason: Option<String>,
     pub has_text_block: bool,
     pub usage: TokenUsage,
+    /// When the first content block started, for time-to-first-token
+    pub first_token_at: Option<Instant>,
 }
 
 impl StreamedResponse {
@@ -239,6 +338,7 @@ impl StreamedResponse {
         let block_index =
             if self.has_text_block { 1 } else { 0 } + self.function_calls.len() as u32;
         self.function_calls.push(call);
+        self.call_ids.push(Uuid::new_v4().to_string());
         block_index
     }
 
@@ -259,3 +359,42 @@ impl StreamedResponse {
         (if self.has_text_block { 1 } else { 0 }) + self.function_calls.len() as u32
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::tools::get_tool_definitions;
+    use serde_json::json;
+
+    #[test]
+    fn test_builtin_tool_schemas_are_supported() {
+        for definition in get_tool_definitions() {
+            assert!(
+                schema_supported(&definition.input_schema),
+                "{} has a schema Gemini rejects",
+                definition.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_unsupported_keywords_are_found_when_nested() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "items": {
+                    "type": "array",
+                    "items": { "type": "object", "additionalProperties": false }
+                }
+            }
+        });
+        assert!(!schema_supported(&schema));
+
+        // Property names are not keywords
+        let schema = json!({
+            "type": "object",
+            "properties": { "const": { "type": "string" } }
+        });
+        assert!(schema_supported(&schema));
+    }
+}
@@ -5,9 +5,13 @@ use tokio_util::sync::CancellationToken;
 
 use crate::agent::error::AgentError;
 use crate::agent::provider::HeadlessResult;
+use crate::agent::structured::{self, STRUCTURED_OUTPUT_PROMPT};
 use crate::agent::tools::{ToolExecutor, ToolName};
+use crate::agent::transcript::{TranscriptEvent, TranscriptLogger};
 use crate::agent::types::ToolDefinition;
-use crate::agent::usage::{SessionUsageTracker, TokenUsage};
+use crate::agent::usage::{SessionUsageTracker, TokenUsage, UsageSource};
+
+use super::reconnect::{with_reconnect, ReconnectPolicy};
 
 #[derive(Debug, Clone)]
 pub struct ToolCall {
@@ -37,10 +41,17 @@ pub struct HeadlessResponse {
 pub struct HeadlessContext<'a> {
     pub system_prompt: Option<String>,
     pub tools: Vec<ToolDefinition>,
+    /// JSON schema the final answer must match
+    pub response_schema: Option<serde_json::Value>,
     pub executor: &'a dyn ToolExecutor,
     pub max_iterations: u32,
     pub cancel_token: &'a CancellationToken,
     pub usage_tracker: Arc<SessionUsageTracker>,
+    /// Sub-agent type the usage is attributed to
+    pub agent_type: Option<String>,
+    pub transcript: Option<TranscriptLogger>,
+    /// Seed for the reconnect jitter (None = `DEVFLOW_SEED` or random)
+    pub seed: Option<u64>,
 }
 
 impl HeadlessResponse {
@@ -58,11 +69,14 @@ pub trait HeadlessStreamer: Send + Sync {
         messages: Vec<crate::agent::types::ChatMessage>,
     ) -> Self::Conversation;
 
+    /// With `response_schema`, `tools` is empty and the response text must be
+    /// the JSON answer.
     async fn stream_response(
         &self,
         conversation: &Self::Conversation,
         system_prompt: Option<String>,
         tools: &[ToolDefinition],
+        response_schema: Option<&serde_json::Value>,
         cancel_token: &CancellationToken,
     ) -> Result<HeadlessResponse, AgentError>;
 
@@ -73,6 +87,8 @@ pub trait HeadlessStreamer: Send + Sync {
     );
 
     fn append_tool_results(&self, conversation: &mut Self::Conversation, results: Vec<ToolResult>);
+
+    fn append_user_text(&self, conversation: &mut Self::Conversation, text: String);
 }
 
 pub async fn run_headless_loop<S: HeadlessStreamer>(
@@ -83,34 +99,92 @@ pub async fn run_headless_loop<S: HeadlessStreamer>(
     let mut conversation = streamer.initial_conversation(messages);
     let mut iteration = 0u32;
     let mut final_text = String::new();
+    // The schema is enforced on a request without tools: right away if the run
+    // has none, otherwise once the tool loop has produced its final answer
+    let mut structured_turn = ctx.response_schema.is_some() && ctx.tools.is_empty();
 
     loop {
         if ctx.cancel_token.is_cancelled() {
             return Err(AgentError::Cancelled);
         }
 
-        let response = streamer
-            .stream_response(
-                &conversation,
-                ctx.system_prompt.clone(),
-                &ctx.tools,
-                ctx.cancel_token,
-            )
-            .await?;
+        if let Some(t) = &ctx.transcript {
+            t.log(TranscriptEvent::Request { turn: iteration });
+        }
+
+        let (conversation_ref, tools) = if structured_turn {
+            (&conversation, &[][..])
+        } else {
+            (&conversation, &ctx.tools[..])
+        };
+        let schema = ctx.response_schema.as_ref().filter(|_| structured_turn);
+        let (system_prompt, cancel_token) = (&ctx.system_prompt, ctx.cancel_token);
+        let response = with_reconnect(
+            ReconnectPolicy {
+                seed: ctx.seed,
+                ..ReconnectPolicy::default()
+            },
+            ctx.cancel_token,
+            |attempt| {
+                log::warn!(
+                    "Sub-agent connection lost ({}), retry {}/{} in {:?}",
+                    attempt.error,
+                    attempt.attempt,
+                    attempt.max_retries,
+                    attempt.delay
+                )
+            },
+            move || {
+                streamer.stream_response(
+                    conversation_ref,
+                    system_prompt.clone(),
+                    tools,
+                    schema,
+                    cancel_token,
+                )
+            },
+        )
+        .await?;
+
+        if let Some(t) = &ctx.transcript {
+            t.log_response(iteration, &response);
+        }
 
         let usage = response.usage;
         if usage.input_tokens > 0 || usage.output_tokens > 0 {
-            ctx.usage_tracker
-                .add_tokens(usage.input_tokens, usage.output_tokens);
+            ctx.usage_tracker.add_source_tokens(
+                UsageSource::SubAgent,
+                ctx.agent_type.as_deref(),
+                usage,
+            );
         }
 
         final_text.push_str(&response.text);
 
+        if let Some(schema) = schema {
+            let value = structured::parse_json_text(&response.text)
+                .and_then(|value| structured::validate(&value, schema).map(|_| value))
+                .map_err(AgentError::InvalidStructuredOutput)?;
+            return Ok(HeadlessResult {
+                text: final_text,
+                tool_calls_made: iteration,
+                stop_reason: response.stop_reason,
+                structured: Some(value),
+            });
+        }
+
         if !response.has_tool_calls() {
+            if ctx.response_schema.is_some() {
+                streamer.append_assistant_response(&mut conversation, &response);
+                streamer.append_user_text(&mut conversation, STRUCTURED_OUTPUT_PROMPT.to_string());
+                structured_turn = true;
+                continue;
+            }
             return Ok(HeadlessResult {
                 text: final_text,
                 tool_calls_made: iteration,
                 stop_reason: response.stop_reason,
+                structured: None,
             });
         }
 
@@ -142,6 +216,28 @@ pub async fn run_headless_loop<S: HeadlessStreamer>(
             });
         }
 
+        if let Some(note) = ctx.executor.after_batch().await {
+            let last_edit = results.iter_mut().rev().find(|r| {
+                !r.is_error && ToolName::parse(&r.name).is_some_and(|tool| tool.is_file_edit())
+            });
+            if let Some(result) = last_edit {
+                result.output.push_str("\n\n");
+                result.output.push_str(&note);
+            }
+        }
+
+        if let Some(t) = &ctx.transcript {
+            for result in &results {
+                t.log(TranscriptEvent::ToolResult {
+                    id: result.id.clone(),
+                    name: result.name.clone(),
+                    output: result.output.clone(),
+                    is_error: result.is_error,
+                    artifacts: Vec::new(),
+                });
+            }
+        }
+
         streamer.append_tool_results(&mut conversation, results);
     }
 }
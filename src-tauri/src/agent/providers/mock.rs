@@ -8,7 +8,8 @@ use tokio_util::sync::CancellationToken;
 
 use super::headless::{HeadlessResponse, HeadlessStreamer, ToolCall, ToolResult};
 use crate::agent::error::AgentError;
-use crate::agent::types::{ChatMessage, ToolDefinition};
+use crate::agent::rng::SeededRng;
+use crate::agent::types::{ChatMessage, MessageRole, ToolDefinition};
 use crate::agent::usage::TokenUsage;
 
 /// A scripted response for the mock streamer
@@ -89,6 +90,43 @@ impl ScriptedResponse {
             stop_reason: Some(stop_reason.to_string()),
         }
     }
+
+    /// Generate a script of up to `max_tool_turns` read-only tool calls followed by
+    /// a final text response. The same seed always yields the same script.
+    pub fn generate(seed: u64, max_tool_turns: usize) -> Vec<Self> {
+        const TOOLS: [(&str, &str); 3] = [
+            ("read_file", "path"),
+            ("glob", "pattern"),
+            ("grep", "pattern"),
+        ];
+
+        let mut rng = SeededRng::new(seed);
+        let turns = rng.below(max_tool_turns + 1);
+        let mut script: Vec<Self> = (0..turns)
+            .map(|turn| {
+                let (name, arg) = TOOLS[rng.below(TOOLS.len())];
+                let mut response = Self::with_tool_call(
+                    name,
+                    &format!("tool_{}", turn + 1),
+                    serde_json::json!({ arg: format!("fixture_{}", rng.below(100)) }),
+                );
+                response.usage.output_tokens = 10 + rng.below(90) as u32;
+                response
+            })
+            .collect();
+
+        script.push(Self::text(&format!(
+            "Finished after {} tool calls (seed {})",
+            turns, seed
+        )));
+        script
+    }
+}
+
+/// Seeded injection of connection failures into mock responses.
+struct FailureInjection {
+    rate: f64,
+    rng: std::sync::Mutex<SeededRng>,
 }
 
 /// Mock streamer that returns scripted responses
@@ -97,6 +135,7 @@ pub struct MockStreamer {
     call_count: AtomicUsize,
     /// Collected tool results from append_tool_results calls
     tool_results: Arc<Mutex<Vec<Vec<ToolResult>>>>,
+    failures: Option<FailureInjection>,
 }
 
 impl MockStreamer {
@@ -106,9 +145,20 @@ impl MockStreamer {
             responses: Mutex::new(responses),
             call_count: AtomicUsize::new(0),
             tool_results: Arc::new(Mutex::new(Vec::new())),
+            failures: None,
         }
     }
 
+    /// Fail each call with a connection error at `rate`, deterministically for a given seed.
+    /// Failed calls do not consume a scripted response.
+    pub fn with_failure_rate(mut self, rate: f64, seed: u64) -> Self {
+        self.failures = Some(FailureInjection {
+            rate,
+            rng: std::sync::Mutex::new(SeededRng::new(seed)),
+        });
+        self
+    }
+
     /// Get the number of times stream_response was called
     pub fn call_count(&self) -> usize {
         self.call_count.load(Ordering::SeqCst)
@@ -144,12 +194,24 @@ impl HeadlessStreamer for MockStreamer {
         _conversation: &Self::Conversation,
         _system_prompt: Option<String>,
         _tools: &[ToolDefinition],
+        _response_schema: Option<&serde_json::Value>,
         cancel_token: &CancellationToken,
     ) -> Result<HeadlessResponse, AgentError> {
         if cancel_token.is_cancelled() {
             return Err(AgentError::Cancelled);
         }
 
+        if let Some(failures) = &self.failures {
+            let fail = failures
+                .rng
+                .lock()
+                .map(|mut rng| rng.chance(failures.rate))
+                .unwrap_or(false);
+            if fail {
+                return Err(AgentError::Connection("Injected mock failure".to_string()));
+            }
+        }
+
         let count = self.call_count.fetch_add(1, Ordering::SeqCst);
         let responses = self.responses.lock().await;
 
@@ -186,6 +248,12 @@ impl HeadlessStreamer for MockStreamer {
             tool_results.lock().await.push(results);
         });
     }
+
+    fn append_user_text(&self, conversation: &mut Self::Conversation, text: String) {
+        conversation
+            .messages
+            .push(ChatMessage::new(MessageRole::User, text));
+    }
 }
 
 /// Mock executor that returns scripted tool outputs
@@ -234,7 +302,6 @@ impl crate::agent::tools::ToolExecutor for MockExecutor {
 mod tests {
     use super::*;
     use crate::agent::providers::headless::{run_headless_loop, HeadlessContext};
-    use crate::agent::types::MessageRole;
     use crate::agent::usage::SessionUsageTracker;
 
     fn create_test_context<'a>(
@@ -245,10 +312,14 @@ mod tests {
         HeadlessContext {
             system_prompt: Some("Test system prompt".to_string()),
             tools: vec![],
+            response_schema: None,
             executor,
             max_iterations: 10,
             cancel_token,
             usage_tracker,
+            agent_type: None,
+            transcript: None,
+            seed: None,
         }
     }
 
@@ -365,10 +436,14 @@ mod tests {
         let ctx = HeadlessContext {
             system_prompt: None,
             tools: vec![],
+            response_schema: None,
             executor: &executor,
             max_iterations: 5,
             cancel_token: &cancel_token,
             usage_tracker,
+            agent_type: None,
+            transcript: None,
+            seed: None,
         };
 
         let messages = vec![ChatMessage::new(MessageRole::User, "Loop".to_string())];
@@ -380,6 +455,91 @@ mod tests {
         assert!(err.to_string().contains("maximum tool iterations"));
     }
 
+    fn verdict_schema() -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {"approved": {"type": "boolean"}},
+            "required": ["approved"]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_structured_output_without_tools() {
+        let streamer = MockStreamer::new(vec![ScriptedResponse::text(r#"{"approved": true}"#)]);
+        let executor = MockExecutor::new();
+        let cancel_token = CancellationToken::new();
+        let mut ctx = create_test_context(
+            &executor,
+            &cancel_token,
+            Arc::new(SessionUsageTracker::new()),
+        );
+        ctx.response_schema = Some(verdict_schema());
+
+        let messages = vec![ChatMessage::new(MessageRole::User, "Review".to_string())];
+        let result = run_headless_loop(&streamer, messages, ctx).await.unwrap();
+
+        assert_eq!(
+            result.structured,
+            Some(serde_json::json!({"approved": true}))
+        );
+        assert_eq!(streamer.call_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_structured_output_follows_tool_loop() {
+        let streamer = MockStreamer::new(vec![
+            ScriptedResponse::with_tool_call("bash", "t1", serde_json::json!({"command": "ls"})),
+            ScriptedResponse::text("Looks fine."),
+            ScriptedResponse::text("```json\n{\"approved\": false}\n```"),
+        ]);
+        let executor = MockExecutor::new();
+        executor.expect_tool("bash", Ok("a.rs".to_string())).await;
+        let cancel_token = CancellationToken::new();
+        let mut ctx = create_test_context(
+            &executor,
+            &cancel_token,
+            Arc::new(SessionUsageTracker::new()),
+        );
+        ctx.tools = vec![ToolDefinition {
+            name: "bash".to_string(),
+            description: "Run a command".to_string(),
+            input_schema: serde_json::json!({"type": "object"}),
+        }];
+        ctx.response_schema = Some(verdict_schema());
+
+        let messages = vec![ChatMessage::new(MessageRole::User, "Review".to_string())];
+        let result = run_headless_loop(&streamer, messages, ctx).await.unwrap();
+
+        // One extra request turns the final answer into JSON
+        assert_eq!(streamer.call_count(), 3);
+        assert_eq!(result.tool_calls_made, 1);
+        assert_eq!(
+            result.structured,
+            Some(serde_json::json!({"approved": false}))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_structured_output_must_match_schema() {
+        let streamer = MockStreamer::new(vec![ScriptedResponse::text(r#"{"approved": "yes"}"#)]);
+        let executor = MockExecutor::new();
+        let cancel_token = CancellationToken::new();
+        let mut ctx = create_test_context(
+            &executor,
+            &cancel_token,
+            Arc::new(SessionUsageTracker::new()),
+        );
+        ctx.response_schema = Some(verdict_schema());
+
+        let messages = vec![ChatMessage::new(MessageRole::User, "Review".to_string())];
+        let err = run_headless_loop(&streamer, messages, ctx)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, AgentError::InvalidStructuredOutput(_)));
+        assert!(err.to_string().contains("$.approved"));
+    }
+
     #[tokio::test]
     async fn test_cancellation() {
         let streamer = MockStreamer::new(vec![ScriptedResponse::text("Hello")]);
@@ -792,10 +952,14 @@ mod tests {
         let ctx = HeadlessContext {
             system_prompt: None,
             tools: vec![],
+            response_schema: None,
             executor: &executor,
             max_iterations: 3, // Would fail if counting individual tools
             cancel_token: &cancel_token,
             usage_tracker,
+            agent_type: None,
+            transcript: None,
+            seed: None,
         };
 
         let messages = vec![ChatMessage::new(MessageRole::User, "Run".to_string())];
@@ -927,4 +1091,54 @@ mod tests {
         // Final response has end_turn, not tool_use
         assert_eq!(result.stop_reason, Some("end_turn".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_generated_script_is_reproducible() {
+        let first = ScriptedResponse::generate(1234, 5);
+        let second = ScriptedResponse::generate(1234, 5);
+
+        let inputs = |script: &[ScriptedResponse]| -> Vec<String> {
+            script
+                .iter()
+                .flat_map(|r| r.tool_calls.iter().map(|c| c.input.to_string()))
+                .collect()
+        };
+        assert_eq!(inputs(&first), inputs(&second));
+        assert_eq!(first.last().unwrap().text, second.last().unwrap().text);
+
+        let turns = first.len() - 1;
+        let streamer = MockStreamer::new(first);
+        let executor = MockExecutor::new();
+        let cancel_token = CancellationToken::new();
+        let usage_tracker = Arc::new(SessionUsageTracker::new());
+
+        let ctx = create_test_context(&executor, &cancel_token, usage_tracker.clone());
+        let messages = vec![ChatMessage::new(MessageRole::User, "Go".to_string())];
+        let result = run_headless_loop(&streamer, messages, ctx).await.unwrap();
+
+        assert_eq!(result.tool_calls_made as usize, turns);
+        assert!(result.text.contains("seed 1234"));
+    }
+
+    #[tokio::test]
+    async fn test_failure_injection_is_reproducible() {
+        async fn failure_pattern(seed: u64) -> Vec<bool> {
+            let streamer = MockStreamer::new(vec![]).with_failure_rate(0.5, seed);
+            let conversation = streamer.initial_conversation(vec![]);
+            let cancel_token = CancellationToken::new();
+            let mut pattern = Vec::new();
+            for _ in 0..16 {
+                let result = streamer
+                    .stream_response(&conversation, None, &[], &cancel_token)
+                    .await;
+                pattern.push(matches!(result, Err(AgentError::Connection(_))));
+            }
+            pattern
+        }
+
+        let pattern = failure_pattern(99).await;
+        assert_eq!(pattern, failure_pattern(99).await);
+        assert!(pattern.contains(&true));
+        assert!(pattern.contains(&false));
+    }
 }
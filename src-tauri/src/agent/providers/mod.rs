@@ -1,7 +1,19 @@
 pub mod anthropic;
+pub mod catalog;
 pub mod compaction;
+#[cfg(test)]
+mod e2e;
 pub mod gemini;
 pub mod headless;
+mod orchestration;
+mod partial;
+mod reconnect;
+pub mod replay;
+mod slow_response;
+mod sse;
+mod tool_loop;
+#[cfg(test)]
+mod vcr;
 
 #[cfg(test)]
 pub mod mock;
@@ -11,18 +23,37 @@ pub use headless::{
     run_headless_loop, HeadlessContext, HeadlessResponse, HeadlessStreamer,
     ToolResult as HeadlessToolResult,
 };
+pub(crate) use orchestration::{offered_tools, ProviderRuntime, RunSettings};
+pub(crate) use partial::PartialResponse;
+pub(crate) use reconnect::{with_reconnect, ReconnectAttempt, ReconnectPolicy};
+pub(crate) use slow_response::SlowResponseWatch;
+pub(crate) use tool_loop::StreamedTurn;
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
-use tauri::{AppHandle, Emitter};
+use tauri::Runtime;
 
-use crate::config::{ConfigService, ExecutionConfig, PromptsConfig};
+use crate::config::{
+    AutoApproveConfig, ConfigService, ExecutionConfig, NetworkConfig, ProjectConfig, PromptsConfig,
+    WorkspaceConfig,
+};
 
+use super::approvals::{needs_approval, ApprovalCategory};
+use super::attribution::Attribution;
 use super::error::AgentError;
+use super::events::EventSink;
+use super::prompts::{interpolate_variables, AgentPromptOverrides};
 use super::provider::ProviderAdapter;
+use super::run_options::RunOptions;
 use super::tools::LocalExecutor;
-use super::types::{AgentStatus, AgentStatusPayload};
+use super::transcript::{TranscriptEvent, TranscriptLogger};
+use super::types::{
+    AgentErrorPayload, AgentReconnectingPayload, AgentSlowResponsePayload, AgentStatus,
+    AgentStatusPayload, AgentSteeringPayload, SteeringMessage,
+};
 
 pub use anthropic::AnthropicAdapter;
 pub use gemini::GeminiAdapter;
@@ -33,10 +64,11 @@ pub const DEFAULT_SYSTEM_PROMPT: &str = include_str!("../default_system_prompt.m
 pub const DEFAULT_EXTRACTION_PROMPT: &str = include_str!("../extraction_prompt.md");
 
 /// Context for streaming responses, reducing parameter passing.
-pub(crate) struct StreamContext<'a> {
-    pub app_handle: &'a AppHandle,
+pub(crate) struct StreamContext<'a, R: Runtime> {
+    pub events: &'a EventSink<R>,
     pub cancel_token: &'a CancellationToken,
     pub block_offset: u32,
+    pub partial: &'a PartialResponse,
 }
 
 /// Tracks global block indices across multiple streaming responses in a tool loop.
@@ -51,15 +83,17 @@ impl StreamingState {
         }
     }
 
-    pub fn create_context<'a>(
+    pub fn create_context<'a, R: Runtime>(
         &self,
-        app_handle: &'a AppHandle,
+        events: &'a EventSink<R>,
         cancel_token: &'a CancellationToken,
-    ) -> StreamContext<'a> {
+        partial: &'a PartialResponse,
+    ) -> StreamContext<'a, R> {
         StreamContext {
-            app_handle,
+            events,
             cancel_token,
             block_offset: self.global_block_counter,
+            partial,
         }
     }
 
@@ -73,52 +107,182 @@ impl StreamingState {
     }
 }
 
-pub(crate) fn emit_status(app_handle: &AppHandle, status: AgentStatus, detail: Option<String>) {
-    let _ = app_handle.emit("agent-status", AgentStatusPayload::new(status, detail));
+pub(crate) fn emit_status<R: Runtime>(
+    events: &EventSink<R>,
+    status: AgentStatus,
+    detail: Option<String>,
+) {
+    let _ = events.emit("agent-status", AgentStatusPayload::new(status, detail));
+}
+
+/// Emitted while a request queues for the shared provider rate limit.
+pub(crate) fn emit_rate_limited<R: Runtime>(events: &EventSink<R>, wait: Duration) {
+    emit_status(
+        events,
+        AgentStatus::RateLimited,
+        Some(format!("{}s", wait.as_secs_f64().ceil() as u64)),
+    );
+}
+
+pub(crate) fn emit_reconnecting<R: Runtime>(
+    events: &EventSink<R>,
+    block_offset: u32,
+    attempt: &ReconnectAttempt,
+) {
+    emit_status(
+        events,
+        AgentStatus::Reconnecting,
+        Some(format!("{}/{}", attempt.attempt, attempt.max_retries)),
+    );
+    let _ = events.emit(
+        "agent-reconnecting",
+        AgentReconnectingPayload {
+            attempt: attempt.attempt,
+            max_attempts: attempt.max_retries,
+            delay_ms: attempt.delay.as_millis() as u64,
+            error: attempt.error.clone(),
+            block_offset,
+        },
+    );
+}
+
+/// Emits `agent-error` and keeps the error for diagnostics bundles.
+pub(crate) fn emit_error<R: Runtime>(events: &EventSink<R>, error: String) {
+    crate::diagnostics::record_error(&error);
+    let _ = events.emit("agent-error", AgentErrorPayload { error });
+}
+
+pub(crate) fn emit_slow_response<R: Runtime>(
+    events: &EventSink<R>,
+    elapsed: Duration,
+    threshold: Duration,
+) {
+    let _ = events.emit(
+        "agent-slow-response",
+        AgentSlowResponsePayload {
+            elapsed_ms: elapsed.as_millis() as u64,
+            threshold_ms: threshold.as_millis() as u64,
+        },
+    );
 }
 
+/// Holds the tool loop while the user has paused the run.
+pub(crate) async fn wait_if_paused<R: Runtime>(
+    session: &SessionState,
+    events: &EventSink<R>,
+    cancel_token: &CancellationToken,
+) -> Result<(), AgentError> {
+    if !session.is_paused() {
+        return Ok(());
+    }
+
+    emit_status(events, AgentStatus::Paused, None);
+    tokio::select! {
+        _ = session.wait_until_resumed() => Ok(()),
+        _ = cancel_token.cancelled() => Err(AgentError::Cancelled),
+    }
+}
+
+/// Takes the messages the user queued since the last request and announces them.
+/// The caller appends them to its provider-specific conversation.
+pub(crate) async fn take_steering<R: Runtime>(
+    session: &SessionState,
+    events: &EventSink<R>,
+    transcript: Option<&TranscriptLogger>,
+    partial: &PartialResponse,
+) -> Vec<SteeringMessage> {
+    let messages = session.take_queued_messages().await;
+    if messages.is_empty() {
+        return messages;
+    }
+
+    if let Some(t) = transcript {
+        for message in &messages {
+            t.log(TranscriptEvent::Steering {
+                content: message.content.clone(),
+            });
+        }
+    }
+
+    // The UI closes the current assistant message here, so a later cancel
+    // only needs to report what follows; both are recorded with the reply
+    let closed_message_id = partial.close_segment(&messages);
+
+    let _ = events.emit(
+        "agent-steering",
+        AgentSteeringPayload {
+            messages: messages.clone(),
+            closed_message_id,
+        },
+    );
+    messages
+}
+
+/// Joins the system prompt parts, resolving `{VAR:name}` placeholders in all
+/// but the memory content from `variables` and then `[prompts.variables]`.
 pub(crate) fn build_system_prompt(
     app_system_prompt: &str,
     prompts: &PromptsConfig,
     custom: Option<String>,
     memory: Option<&str>,
-) -> String {
-    let mut parts = Vec::new();
+    variables: &HashMap<String, String>,
+) -> Result<String, AgentError> {
+    let sections = system_prompt_sections(app_system_prompt, prompts, custom, memory, variables)?;
+    Ok(sections
+        .into_iter()
+        .map(|(_, text)| text)
+        .collect::<Vec<_>>()
+        .join("\n\n"))
+}
+
+/// The parts `build_system_prompt` joins, in order and named for display.
+pub(crate) fn system_prompt_sections(
+    app_system_prompt: &str,
+    prompts: &PromptsConfig,
+    custom: Option<String>,
+    memory: Option<&str>,
+    variables: &HashMap<String, String>,
+) -> Result<Vec<(&'static str, String)>, AgentError> {
+    let mut all_variables = prompts.variables.clone();
+    all_variables.extend(variables.iter().map(|(k, v)| (k.clone(), v.clone())));
+    let resolve = |text: &str| interpolate_variables(text, &all_variables);
+
+    let mut sections = Vec::new();
 
-    parts.push(app_system_prompt.to_string());
+    sections.push(("app", resolve(app_system_prompt)?));
 
     // Memory content (AGENTS.md) comes right after base prompt
     if let Some(memory_content) = memory {
-        parts.push(memory_content.to_string());
+        sections.push(("memory", memory_content.to_string()));
     }
 
     if !prompts.pre.is_empty() {
-        parts.push(prompts.pre.clone());
+        sections.push(("pre", resolve(&prompts.pre)?));
     }
 
     if let Some(custom) = custom {
-        parts.push(custom);
+        sections.push(("custom", resolve(&custom)?));
     }
 
     if !prompts.post.is_empty() {
-        parts.push(prompts.post.clone());
+        sections.push(("post", resolve(&prompts.post)?));
     }
 
-    parts.join("\n\n")
+    Ok(sections)
 }
 
 use super::tools::SessionState;
 use super::usage::{AgentUsagePayload, SessionUsageTracker, TokenUsage, UsageSource};
 
-pub(crate) fn emit_usage(
-    app_handle: &AppHandle,
+pub(crate) fn emit_usage<R: Runtime>(
+    events: &EventSink<R>,
     tracker: &SessionUsageTracker,
     usage: TokenUsage,
     source: UsageSource,
 ) {
     if usage.input_tokens > 0 || usage.output_tokens > 0 {
-        let totals = tracker.add_tokens(usage.input_tokens, usage.output_tokens);
-        let _ = app_handle.emit(
+        let totals = tracker.add_source_tokens(source, None, usage);
+        let _ = events.emit(
             "agent-usage",
             AgentUsagePayload {
                 input_tokens: totals.input_tokens,
@@ -132,20 +296,36 @@ pub(crate) fn emit_usage(
 pub(crate) fn create_executor(
     project_path: &Path,
     execution: &ExecutionConfig,
+    workspace: &WorkspaceConfig,
+    network: &NetworkConfig,
+    attribution: Option<Attribution>,
     session: SessionState,
     cancel_token: CancellationToken,
     usage_tracker: Arc<SessionUsageTracker>,
-) -> LocalExecutor {
-    LocalExecutor::with_session(
+) -> Result<LocalExecutor, AgentError> {
+    Ok(LocalExecutor::with_session(
         project_path.to_path_buf(),
         execution.timeout_secs,
+        network,
         session,
         cancel_token,
         usage_tracker,
-    )
+    )?
+    .with_attribution(attribution)
+    .with_max_output_bytes(execution.max_tool_result_bytes as usize)
+    .with_max_read_bytes(execution.max_read_bytes as usize)
+    .with_max_timeout(execution.max_timeout_secs)
+    .with_disabled_tools(&execution.disabled_tools)
+    .with_dry_run(execution.dry_run)
+    .with_shell(execution.shell)
+    .with_allowed_paths(&execution.allowed_paths)
+    .with_workspace_roots(&workspace.roots))
 }
 
-use super::types::{PlanReadyPayload, ToolEndPayload, ToolStartPayload};
+use super::types::{
+    PlanReadyPayload, PlanWithdrawnPayload, ToolApprovalRequestPayload, ToolArtifact,
+    ToolEndPayload, ToolStartPayload,
+};
 
 pub(crate) struct ToolCall {
     pub id: String,
@@ -159,22 +339,80 @@ pub(crate) struct ToolResult {
     pub name: String,
     pub output: String,
     pub is_error: bool,
+    /// Shown by the UI and kept in transcripts; providers only get `output`
+    pub artifacts: Vec<ToolArtifact>,
+}
+
+/// Asks the user about a call whose category is not auto-approved. Returns
+/// the tool result to use instead of running it, or None once approved.
+async fn await_tool_approval<R: Runtime>(
+    events: &EventSink<R>,
+    session: &SessionState,
+    call_id: &str,
+    call_name: &str,
+    input: &serde_json::Value,
+    category: ApprovalCategory,
+) -> Option<String> {
+    use super::tools::PlanApproval;
+
+    let receiver = session.request_tool_approval(call_id).await;
+    let _ = events.emit(
+        "agent-tool-approval",
+        ToolApprovalRequestPayload {
+            tool_use_id: call_id.to_string(),
+            tool_name: call_name.to_string(),
+            tool_input: input.clone(),
+            category,
+        },
+    );
+    emit_status(
+        events,
+        AgentStatus::ToolWaiting,
+        Some(format!("Awaiting approval for {}", call_name)),
+    );
+
+    let rejection = match receiver.await {
+        Ok(PlanApproval::Approved) => None,
+        Ok(PlanApproval::Rejected(Some(reason))) => {
+            Some(format!("Tool call rejected by user: {}", reason))
+        }
+        Ok(PlanApproval::Rejected(None)) => Some("Tool call rejected by user.".to_string()),
+        Err(_) => Some("Tool call approval was abandoned".to_string()),
+    };
+    if rejection.is_none() {
+        emit_status(
+            events,
+            AgentStatus::ToolRunning,
+            Some(call_name.to_string()),
+        );
+    }
+    rejection
 }
 
-pub(crate) async fn execute_tool_calls(
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn execute_tool_calls<R: Runtime>(
     tool_calls: Vec<ToolCall>,
     executor: &LocalExecutor,
     session: &SessionState,
-    app_handle: &AppHandle,
+    default_auto_approve: &AutoApproveConfig,
+    events: &EventSink<R>,
     cancel_token: &CancellationToken,
+    partial: &PartialResponse,
+    plan_timeout: Option<Duration>,
 ) -> Result<Vec<ToolResult>, AgentError> {
-    use super::tools::{ToolExecutor, ToolName};
+    use super::tools::{ToolExecutor, ToolName, ToolOutput};
     use futures::future::join_all;
 
     if cancel_token.is_cancelled() {
         return Err(AgentError::Cancelled);
     }
 
+    // Read per batch, so a change made during the run applies to the next tool calls
+    let auto_approve = session
+        .get_auto_approve()
+        .await
+        .unwrap_or_else(|| default_auto_approve.clone());
+
     let (sequential_calls, parallel_calls): (Vec<_>, Vec<_>) = tool_calls
         .into_iter()
         .partition(|call| call.name == "submit_plan");
@@ -188,10 +426,10 @@ pub(crate) async fn execute_tool_calls(
         } else {
             parallel_calls[0].name.clone()
         };
-        emit_status(app_handle, AgentStatus::ToolRunning, Some(status_msg));
+        emit_status(events, AgentStatus::ToolRunning, Some(status_msg));
 
         for call in &parallel_calls {
-            let _ = app_handle.emit(
+            let _ = events.emit(
                 "agent-tool-start",
                 ToolStartPayload {
                     tool_use_id: call.id.clone(),
@@ -221,34 +459,82 @@ pub(crate) async fn execute_tool_calls(
                 let name = call.name.clone();
                 let block_index = call.block_index;
                 let cancel = cancel_token.clone();
+                let category = needs_approval(&auto_approve, tool_name);
 
                 async move {
-                    let (output, is_error) = tokio::select! {
+                    let run = async {
+                        if let Some(category) = category {
+                            if let Some(rejection) =
+                                await_tool_approval(events, session, &id, &name, &input, category)
+                                    .await
+                            {
+                                return Err(rejection);
+                            }
+                        }
+                        executor
+                            .execute_output(tool_name, input.clone())
+                            .await
+                            .map_err(|e| e.to_string())
+                    };
+                    let (output, is_error, completed) = tokio::select! {
                         _ = cancel.cancelled() => {
-                            ("Cancelled by user".to_string(), true)
+                            session.cancel_tool_approval(&id).await;
+                            (ToolOutput::from("Cancelled by user".to_string()), true, false)
                         }
-                        result = executor.execute(tool_name, input) => {
+                        result = run => {
                             match result {
-                                Ok(result) => (result, false),
-                                Err(e) => (e.to_string(), true),
+                                Ok(result) => (result, false, true),
+                                Err(e) => (ToolOutput::from(e), true, true),
                             }
                         }
                     };
-                    (id, name, output, is_error, block_index)
+                    (id, name, output, is_error, completed, block_index)
                 }
             })
             .collect();
 
-        let parallel_results = join_all(futures).await;
+        let mut parallel_results = join_all(futures).await;
 
-        for (id, name, output, is_error, block_index) in parallel_results {
-            let _ = app_handle.emit(
+        // Post-edit checks are reported on the batch's last successful edit
+        let diagnostics = tokio::select! {
+            _ = cancel_token.cancelled() => None,
+            note = executor.after_batch() => note,
+        };
+        if let Some(note) = diagnostics {
+            let last_edit =
+                parallel_results
+                    .iter_mut()
+                    .rev()
+                    .find(|(_, name, _, is_error, completed, _)| {
+                        *completed
+                            && !*is_error
+                            && ToolName::parse(name).is_some_and(|tool| tool.is_file_edit())
+                    });
+            if let Some((_, _, output, ..)) = last_edit {
+                output.text.push_str("\n\n");
+                output.text.push_str(&note);
+            }
+        }
+
+        for (call, (id, name, output, is_error, completed, block_index)) in
+            parallel_calls.iter().zip(parallel_results)
+        {
+            let ToolOutput {
+                text: output,
+                artifacts,
+            } = output;
+            if completed {
+                partial.push_tool_result(block_index, &id, &name, &call.input, &output, is_error);
+            }
+
+            let _ = events.emit(
                 "agent-tool-end",
                 ToolEndPayload {
                     tool_use_id: id.clone(),
                     output: output.clone(),
                     is_error,
                     block_index,
+                    artifacts: artifacts.clone(),
                 },
             );
 
@@ -257,6 +543,7 @@ pub(crate) async fn execute_tool_calls(
                 name,
                 output,
                 is_error,
+                artifacts,
             });
         }
     }
@@ -270,13 +557,9 @@ pub(crate) async fn execute_tool_calls(
             return Err(AgentError::Cancelled);
         }
 
-        emit_status(
-            app_handle,
-            AgentStatus::ToolRunning,
-            Some(call.name.clone()),
-        );
+        emit_status(events, AgentStatus::ToolRunning, Some(call.name.clone()));
 
-        let _ = app_handle.emit(
+        let _ = events.emit(
             "agent-tool-start",
             ToolStartPayload {
                 tool_use_id: call.id.clone(),
@@ -302,73 +585,138 @@ pub(crate) async fn execute_tool_calls(
         };
 
         if cancel_token.is_cancelled() {
-            let _ = app_handle.emit(
+            let _ = events.emit(
                 "agent-tool-end",
                 ToolEndPayload {
                     tool_use_id: call.id.clone(),
                     output: "Cancelled by user".to_string(),
                     is_error: true,
                     block_index: call.block_index,
+                    artifacts: Vec::new(),
                 },
             );
             return Err(AgentError::Cancelled);
         }
 
-        let _ = app_handle.emit(
+        let _ = events.emit(
             "agent-tool-end",
             ToolEndPayload {
                 tool_use_id: call.id.clone(),
                 output: output.clone(),
                 is_error,
                 block_index: call.block_index,
+                artifacts: Vec::new(),
             },
         );
+        partial.push_tool_result(
+            call.block_index,
+            &call.id,
+            &call.name,
+            &call.input,
+            &output,
+            is_error,
+        );
 
         // Handle submit_plan special case
         if tool_name == ToolName::SubmitPlan && !is_error {
-            if let Some(plan) = session.get_plan().await {
-                let _ =
-                    app_handle.emit("agent-plan-ready", PlanReadyPayload { plan: plan.clone() });
-
-                emit_status(
-                    app_handle,
-                    AgentStatus::ToolWaiting,
-                    Some("Awaiting plan approval".to_string()),
-                );
-
-                if let Some(approval) = session.wait_for_plan_approval().await {
-                    use super::tools::PlanApproval;
-
-                    match approval {
-                        PlanApproval::Approved => {
-                            results.push(ToolResult {
-                                id: call.id,
-                                name: call.name,
-                                output: "Plan approved by user. Proceed with implementation."
-                                    .to_string(),
-                                is_error: false,
-                            });
+            if let Some((plan_id, plan)) = session.latest_plan().await {
+                use super::tools::{PlanApproval, PlanStatus};
+
+                let approval = if auto_approve.plans {
+                    session.remove_plan(plan_id).await;
+                    Some(PlanApproval::Approved)
+                } else {
+                    let _ = events.emit(
+                        "agent-plan-ready",
+                        PlanReadyPayload {
+                            plan: plan.clone(),
+                            plan_id,
+                        },
+                    );
+
+                    emit_status(
+                        events,
+                        AgentStatus::ToolWaiting,
+                        Some("Awaiting plan approval".to_string()),
+                    );
+
+                    tokio::select! {
+                        _ = cancel_token.cancelled() => {
+                            session.remove_plan(plan_id).await;
+                            let _ = events.emit(
+                                "agent-plan-withdrawn",
+                                PlanWithdrawnPayload { plan_id },
+                            );
+                            return Err(AgentError::Cancelled);
+                        }
+                        approval = session.wait_for_plan_approval(plan_id, plan_timeout) => approval,
+                    }
+                };
+
+                match approval {
+                    Some(PlanApproval::Approved) => {
+                        let status = if auto_approve.plans {
+                            PlanStatus::AutoApproved
+                        } else {
+                            PlanStatus::Approved
+                        };
+                        let archived = session.archive_plan(plan, status, None).await;
+                        let mut output = if auto_approve.plans {
+                            "Plan approved automatically. Proceed with implementation."
+                        } else {
+                            "Plan approved by user. Proceed with implementation."
                         }
-                        PlanApproval::Rejected(reason) => {
-                            let rejection_msg = match reason {
-                                Some(r) => format!("Plan rejected by user: {}", r),
-                                None => "Plan rejected by user.".to_string(),
-                            };
-                            results.push(ToolResult {
-                                id: call.id,
-                                name: call.name,
-                                output: rejection_msg,
-                                is_error: true,
-                            });
+                        .to_string();
+                        if !archived.todo_ids.is_empty() {
+                            output.push_str(&format!(
+                                " Its steps were added to the todo list ({}); \
+                                 keep them current with todo_write as you work.",
+                                archived.todo_ids.join(", ")
+                            ));
                         }
+                        results.push(ToolResult {
+                            id: call.id,
+                            name: call.name,
+                            output,
+                            is_error: false,
+                            artifacts: Vec::new(),
+                        });
+                    }
+                    Some(PlanApproval::Rejected(reason)) => {
+                        session
+                            .archive_plan(plan, PlanStatus::Rejected, reason.clone())
+                            .await;
+                        let rejection_msg = match reason {
+                            Some(r) => format!("Plan rejected by user: {}", r),
+                            None => "Plan rejected by user.".to_string(),
+                        };
+                        results.push(ToolResult {
+                            id: call.id,
+                            name: call.name,
+                            output: rejection_msg,
+                            is_error: true,
+                            artifacts: Vec::new(),
+                        });
+                    }
+                    None => {
+                        let _ =
+                            events.emit("agent-plan-withdrawn", PlanWithdrawnPayload { plan_id });
+                        let waited = plan_timeout
+                            .map(|timeout| format!(" within {}s", timeout.as_secs()))
+                            .unwrap_or_default();
+                        results.push(ToolResult {
+                            id: call.id,
+                            name: call.name,
+                            output: format!(
+                                "The user has not responded to the plan{}, so it was withdrawn. \
+                                 Do not start implementing it: end your turn, tell the user the \
+                                 plan still needs their review and submit it again when they ask.",
+                                waited
+                            ),
+                            is_error: true,
+                            artifacts: Vec::new(),
+                        });
                     }
-                } else {
-                    results.push(ToolResult {
-                        id: call.id,
-                        name: call.name,
-                        output,
-                        is_error,
-                    });
                 }
                 continue;
             }
@@ -379,6 +727,7 @@ pub(crate) async fn execute_tool_calls(
             name: call.name,
             output,
             is_error,
+            artifacts: Vec::new(),
         });
     }
 
@@ -395,34 +744,58 @@ pub(crate) fn check_iteration_limit(iteration: u32, max_iterations: u32) -> Resu
     Ok(())
 }
 
+/// Creates the adapter from the project config, with session `overrides` applied on top.
 pub fn create_provider_adapter(
     project_path: &Path,
+    overrides: Option<&RunOptions>,
 ) -> Result<Arc<dyn ProviderAdapter>, AgentError> {
-    let project_config = ConfigService::load_project_config(project_path)
+    let mut project_config = ConfigService::load_project_config(project_path)
         .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+    if let Some(options) = overrides {
+        options.apply_to(&mut project_config);
+    }
+    // Sub-agents load these per run; checking here reports mistakes when the session starts
+    AgentPromptOverrides::load(project_path, &project_config)?;
+
+    adapter_for_config(project_config, project_path)
+}
 
+/// The adapter that extracts facts when compacting, if `[agent_models.compaction]`
+/// picks another model than `[agent]`.
+pub(crate) fn compaction_adapter(
+    project_config: &ProjectConfig,
+    project_path: &Path,
+) -> Result<Option<Arc<dyn ProviderAdapter>>, AgentError> {
+    if !project_config.agent_models.contains_key("compaction") {
+        return Ok(None);
+    }
+    let mut config = project_config.clone();
+    config.apply_agent_model("compaction");
+    // Its own compactions never run, and it must not build another adapter
+    config.agent_models.clear();
+    adapter_for_config(config, project_path).map(Some)
+}
+
+fn adapter_for_config(
+    project_config: ProjectConfig,
+    project_path: &Path,
+) -> Result<Arc<dyn ProviderAdapter>, AgentError> {
     let provider = project_config.agent.provider.to_lowercase();
 
     match provider.as_str() {
         "anthropic" => {
             let adapter = AnthropicAdapter::new(
-                project_config.agent,
-                project_config.prompts,
-                project_config.execution,
+                project_config,
                 project_path.to_path_buf(),
                 DEFAULT_SYSTEM_PROMPT,
-                project_config.extraction_prompt,
             )?;
             Ok(Arc::new(adapter))
         }
         "gemini" => {
             let adapter = GeminiAdapter::new(
-                project_config.agent,
-                project_config.prompts,
-                project_config.execution,
+                project_config,
                 project_path.to_path_buf(),
                 DEFAULT_SYSTEM_PROMPT,
-                project_config.extraction_prompt,
             )?;
             Ok(Arc::new(adapter))
         }
@@ -437,7 +810,8 @@ mod tests {
     #[test]
     fn test_build_system_prompt_includes_app_prompt() {
         let prompts = PromptsConfig::default();
-        let result = build_system_prompt("App prompt", &prompts, None, None);
+        let result =
+            build_system_prompt("App prompt", &prompts, None, None, &HashMap::new()).unwrap();
         assert_eq!(result, "App prompt");
     }
 
@@ -446,13 +820,16 @@ mod tests {
         let prompts = PromptsConfig {
             pre: "Pre prompt".to_string(),
             post: "Post prompt".to_string(),
+            ..PromptsConfig::default()
         };
         let result = build_system_prompt(
             "App prompt",
             &prompts,
             Some("Custom prompt".to_string()),
             None,
-        );
+            &HashMap::new(),
+        )
+        .unwrap();
         assert_eq!(
             result,
             "App prompt\n\nPre prompt\n\nCustom prompt\n\nPost prompt"
@@ -464,9 +841,12 @@ mod tests {
         let prompts = PromptsConfig {
             pre: "Pre prompt".to_string(),
             post: "Post prompt".to_string(),
+            ..PromptsConfig::default()
         };
         let memory = "<project-memory source=\"AGENTS.md\">\nTest memory\n</project-memory>";
-        let result = build_system_prompt("App prompt", &prompts, None, Some(memory));
+        let result =
+            build_system_prompt("App prompt", &prompts, None, Some(memory), &HashMap::new())
+                .unwrap();
         assert_eq!(
             result,
             "App prompt\n\n<project-memory source=\"AGENTS.md\">\nTest memory\n</project-memory>\n\nPre prompt\n\nPost prompt"
@@ -478,8 +858,10 @@ mod tests {
         let prompts = PromptsConfig {
             pre: "".to_string(),
             post: "Post prompt".to_string(),
+            ..PromptsConfig::default()
         };
-        let result = build_system_prompt("App prompt", &prompts, None, None);
+        let result =
+            build_system_prompt("App prompt", &prompts, None, None, &HashMap::new()).unwrap();
         assert_eq!(result, "App prompt\n\nPost prompt");
     }
 
@@ -488,12 +870,36 @@ mod tests {
         let prompts = PromptsConfig {
             pre: "Pre".to_string(),
             post: "".to_string(),
+            ..PromptsConfig::default()
         };
-        let result = build_system_prompt("App", &prompts, None, None);
+        let result = build_system_prompt("App", &prompts, None, None, &HashMap::new()).unwrap();
         assert!(result.contains("\n\n"));
         assert_eq!(result, "App\n\nPre");
     }
 
+    #[test]
+    fn test_build_system_prompt_resolves_variables() {
+        let prompts = PromptsConfig {
+            pre: "Branch {VAR:branch}, ticket {VAR:ticket}".to_string(),
+            post: "".to_string(),
+            variables: HashMap::from([
+                ("branch".to_string(), "main".to_string()),
+                ("ticket".to_string(), "none".to_string()),
+            ]),
+        };
+        let runtime = HashMap::from([("ticket".to_string(), "DF-12".to_string())]);
+        let result = build_system_prompt("App", &prompts, None, None, &runtime).unwrap();
+        assert_eq!(result, "App\n\nBranch main, ticket DF-12");
+
+        // Memory is the project's own text and is left as written
+        let memory = "Use {VAR:unknown} literally";
+        let result = build_system_prompt("App", &prompts, None, Some(memory), &runtime).unwrap();
+        assert!(result.contains(memory));
+
+        let custom = Some("See {VAR:missing}".to_string());
+        assert!(build_system_prompt("App", &prompts, custom, None, &runtime).is_err());
+    }
+
     #[test]
     fn test_default_system_prompt_is_not_empty() {
         assert!(!DEFAULT_SYSTEM_PROMPT.is_empty());
@@ -536,6 +942,99 @@ mod tests {
         assert_eq!(state.current_offset(), 2);
     }
 
+    fn plan_call() -> ToolCall {
+        ToolCall {
+            id: "plan_1".to_string(),
+            name: "submit_plan".to_string(),
+            input: serde_json::json!({ "plan": "### Steps\n1. Add the parser" }),
+            block_index: 0,
+        }
+    }
+
+    fn plan_executor(
+        project: &Path,
+        session: &SessionState,
+        cancel_token: &CancellationToken,
+    ) -> LocalExecutor {
+        LocalExecutor::with_session(
+            project.to_path_buf(),
+            30,
+            &NetworkConfig::default(),
+            session.clone(),
+            cancel_token.clone(),
+            Arc::new(SessionUsageTracker::new()),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cancel_while_awaiting_plan_approval() {
+        let app = tauri::test::mock_app();
+        let events = EventSink::broadcast(app.handle().clone());
+        let project = tempfile::tempdir().unwrap();
+        let session = SessionState::new();
+        let cancel_token = CancellationToken::new();
+        let executor = plan_executor(project.path(), &session, &cancel_token);
+
+        let cancel_when_waiting = async {
+            while !session.has_pending_plan().await {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            cancel_token.cancel();
+        };
+        let run = async {
+            tokio::join!(
+                execute_tool_calls(
+                    vec![plan_call()],
+                    &executor,
+                    &session,
+                    &AutoApproveConfig::default(),
+                    &events,
+                    &cancel_token,
+                    &PartialResponse::new(),
+                    None,
+                ),
+                cancel_when_waiting
+            )
+        };
+        let (result, ()) = tokio::time::timeout(Duration::from_secs(5), run)
+            .await
+            .expect("cancellation ends the wait");
+
+        assert!(matches!(result, Err(AgentError::Cancelled)));
+        assert!(!session.has_pending_plan().await);
+        assert!(!session.approve_plan(None).await);
+        assert!(session.plan_history().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_plan_times_out() {
+        let app = tauri::test::mock_app();
+        let events = EventSink::broadcast(app.handle().clone());
+        let project = tempfile::tempdir().unwrap();
+        let session = SessionState::new();
+        let cancel_token = CancellationToken::new();
+        let executor = plan_executor(project.path(), &session, &cancel_token);
+
+        let results = execute_tool_calls(
+            vec![plan_call()],
+            &executor,
+            &session,
+            &AutoApproveConfig::default(),
+            &events,
+            &cancel_token,
+            &PartialResponse::new(),
+            Some(Duration::from_millis(20)),
+        )
+        .await
+        .unwrap();
+
+        assert!(results[0].is_error);
+        assert!(results[0].output.contains("has not responded"));
+        assert!(!session.has_pending_plan().await);
+        assert!(session.get_todos().await.is_empty());
+    }
+
     #[test]
     fn test_streaming_state_simulates_tool_loop() {
         let mut state = StreamingState::new();
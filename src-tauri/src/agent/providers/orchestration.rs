@@ -0,0 +1,439 @@
+//! Provider-agnostic parts of a run: system prompt and memory assembly,
+//! compaction, request guardrails, transcripts, the tool loop and the events
+//! that end a run.
+//! Adapters supply only what differs per API through `ProviderRuntime`, so
+//! every provider gets these features without reimplementing them.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tauri::Runtime;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::agent::approvals::tools_needing_approval;
+use crate::agent::attribution::Attribution;
+use crate::agent::conversation::Conversation;
+use crate::agent::error::AgentError;
+use crate::agent::events::EventSink;
+use crate::agent::guardrails::check_request_size;
+use crate::agent::provider::{ExecutionContext, HeadlessResult, ProviderAdapter};
+use crate::agent::run_journal::RunJournal;
+use crate::agent::tools::{
+    without_disabled, LocalExecutor, SessionState, ToolDescriptionOverrides,
+};
+use crate::agent::transcript::{TranscriptEvent, TranscriptLogger};
+use crate::agent::turn_summary::{summarize_turn, TurnUsage};
+use crate::agent::types::{
+    AgentCancelledPayload, AgentCompactionWarningPayload, AgentCompletePayload, AgentStatus,
+    ChatMessage, SendOptions, ToolChoice, ToolDefinition,
+};
+use crate::agent::usage::{SessionUsageTracker, TokenUsage};
+use crate::config::{
+    AutoApproveConfig, ExecutionConfig, LoggingConfig, NetworkConfig, PromptsConfig,
+    WorkspaceConfig,
+};
+
+use super::compaction::{
+    format_compacted_context, maybe_compact, shorten_oversized_messages, CompactionContext,
+};
+use super::headless::{run_headless_loop, HeadlessContext, HeadlessStreamer};
+use super::tool_loop::{run_tool_loop, StreamedTurn};
+use super::{
+    build_system_prompt, create_executor, emit_error, emit_status, PartialResponse, StreamContext,
+};
+
+/// Adapter settings the shared orchestration reads.
+pub(crate) struct RunSettings<'a> {
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub api_key: &'a str,
+    pub project_path: &'a Path,
+    pub app_system_prompt: &'a str,
+    pub prompts: &'a PromptsConfig,
+    pub execution: &'a ExecutionConfig,
+    pub network: &'a NetworkConfig,
+    pub logging: &'a LoggingConfig,
+    /// Config default; the session setting from `agent_set_auto_approve` wins
+    pub auto_approve: &'a AutoApproveConfig,
+    pub workspace: &'a WorkspaceConfig,
+    pub attribution: Option<&'a Attribution>,
+    pub context_limit: u32,
+    pub compaction_threshold: f64,
+    pub extraction_prompt: Option<&'a str>,
+    /// Set by `[agent_models.compaction]`; None extracts with this adapter
+    pub compaction_adapter: Option<&'a dyn ProviderAdapter>,
+    pub tool_descriptions: &'a ToolDescriptionOverrides,
+}
+
+impl RunSettings<'_> {
+    /// Transcript logger for the session when transcripts are enabled.
+    pub fn transcript(&self, session: &SessionState) -> Option<TranscriptLogger> {
+        TranscriptLogger::from_config(
+            self.logging,
+            self.project_path,
+            session.id(),
+            &[self.api_key],
+        )
+    }
+
+    pub fn executor(
+        &self,
+        session: SessionState,
+        cancel_token: CancellationToken,
+        usage_tracker: Arc<SessionUsageTracker>,
+    ) -> Result<LocalExecutor, AgentError> {
+        create_executor(
+            self.project_path,
+            self.execution,
+            self.workspace,
+            self.network,
+            self.attribution.cloned(),
+            session,
+            cancel_token,
+            usage_tracker,
+        )
+    }
+}
+
+/// What an adapter provides on top of headless streaming for interactive runs.
+#[async_trait]
+pub(crate) trait ProviderRuntime: HeadlessStreamer {
+    fn settings(&self) -> RunSettings<'_>;
+
+    /// Whether the provider's API can be offered `tool`.
+    fn supports_tool(&self, _tool: &ToolDefinition) -> bool {
+        true
+    }
+
+    /// One-shot completion used to extract facts when compacting, with its
+    /// token usage.
+    async fn extract(
+        &self,
+        prompt: String,
+        cancel_token: &CancellationToken,
+    ) -> Result<(String, TokenUsage), AgentError>;
+
+    /// Streams one response of the interactive tool loop, emitting its
+    /// content blocks to the UI through `ctx`.
+    async fn stream_turn<R: Runtime>(
+        &self,
+        conversation: &Self::Conversation,
+        system_prompt: Option<String>,
+        tools: &[ToolDefinition],
+        tool_choice: ToolChoice,
+        ctx: &StreamContext<'_, R>,
+    ) -> Result<StreamedTurn, AgentError>;
+}
+
+/// The subset of `tools` offered to the model, with the project's description
+/// overrides: disabled tools (from config or the agent mode) and tools the
+/// provider cannot express are left out. The executor refuses disabled tools
+/// on its own, so both filters line up.
+pub(crate) fn offered_tools<P: ProviderRuntime>(
+    provider: &P,
+    tools: Vec<ToolDefinition>,
+) -> Vec<ToolDefinition> {
+    let settings = provider.settings();
+    let tools = without_disabled(tools, &settings.execution.disabled_tools);
+    settings
+        .tool_descriptions
+        .apply(tools)
+        .into_iter()
+        .filter(|tool| {
+            let supported = provider.supports_tool(tool);
+            if !supported {
+                log::warn!(
+                    "Not offering tool '{}': its schema is not supported by {}",
+                    tool.name,
+                    settings.provider
+                );
+            }
+            supported
+        })
+        .collect()
+}
+
+/// Interactive run with UI events: builds the system prompt (with memory),
+/// compacts the history when it nears the context limit, runs the provider's
+/// tool loop and reports how the run ended.
+pub(crate) async fn send_message<P: ProviderRuntime, R: Runtime>(
+    provider: &P,
+    mut messages: Vec<ChatMessage>,
+    system_prompt: Option<String>,
+    memory: Option<String>,
+    options: SendOptions,
+    ctx: ExecutionContext,
+    events: EventSink<R>,
+) -> Result<(), AgentError> {
+    let settings = provider.settings();
+    let variables = options.variables.unwrap_or_default();
+    let base_system = build_system_prompt(
+        settings.app_system_prompt,
+        settings.prompts,
+        system_prompt,
+        memory.as_deref(),
+        &variables,
+    )?;
+    // Sub-agents started in this run resolve their prompts with the same variables
+    ctx.session.set_prompt_variables(variables).await;
+
+    let message_id = Uuid::new_v4().to_string();
+    let session_id = ctx.session.id().to_string();
+    if let Err(e) = Conversation::record_request(settings.project_path, &session_id, &messages) {
+        log::warn!("Failed to record conversation: {}", e);
+    }
+
+    emit_status(&events, AgentStatus::Sending, None);
+
+    // A single huge message would stay in the preserved window and never be
+    // compacted, so it is shortened on its own first
+    let shortened = shorten_oversized_messages(&mut messages, settings.context_limit);
+    if shortened > 0 {
+        let _ = events.emit(
+            "agent-compaction-warning",
+            AgentCompactionWarningPayload {
+                message: format!(
+                    "Shortened {} oversized message(s) to fit the context window",
+                    shortened
+                ),
+            },
+        );
+    }
+
+    let compaction_ctx = CompactionContext {
+        context_limit: settings.context_limit,
+        threshold: settings.compaction_threshold,
+        extraction_prompt: settings.extraction_prompt,
+        project_path: settings.project_path,
+        session: &ctx.session,
+        events: &events,
+        usage_tracker: &ctx.usage_tracker,
+        cancel_token: &ctx.cancel_token,
+    };
+
+    let (compaction_adapter, cancel_token) = (settings.compaction_adapter, &ctx.cancel_token);
+    let compaction_result = maybe_compact(
+        &messages,
+        Some(&base_system),
+        &compaction_ctx,
+        |prompt| async move {
+            match compaction_adapter {
+                Some(adapter) => adapter.complete(prompt, cancel_token).await,
+                None => provider.extract(prompt, cancel_token).await,
+            }
+        },
+    )
+    .await?;
+
+    // Compacted context, new or from an earlier run, goes after the system prompt
+    let (final_messages, final_system) = match compaction_result {
+        Some(result) => (
+            result.preserved_messages,
+            format!("{}\n\n{}", base_system, result.compacted_text),
+        ),
+        None => {
+            let existing = ctx.session.get_compacted().await;
+            if existing.summary.is_some() || !existing.facts.is_empty() {
+                let compacted_text = format_compacted_context(&existing);
+                (messages, format!("{}\n\n{}", base_system, compacted_text))
+            } else {
+                (messages, base_system)
+            }
+        }
+    };
+
+    let transcript = settings.transcript(&ctx.session);
+    if let Some(t) = &transcript {
+        t.log(TranscriptEvent::RunStart {
+            provider: settings.provider.to_string(),
+            model: settings.model.to_string(),
+            headless: false,
+            system_prompt: Some(final_system.clone()),
+            messages: final_messages.clone(),
+        });
+    }
+
+    let usage_before = ctx.usage_tracker.get_totals();
+    let partial = PartialResponse::new();
+    let result = match check_request_size(&final_messages, &final_system, settings.execution) {
+        Ok(()) => {
+            let journal = RunJournal::start(
+                settings.project_path,
+                &session_id,
+                settings.provider,
+                settings.model,
+            );
+            ctx.session.set_run_journal(Some(journal.clone())).await;
+            let result = run_tool_loop(
+                provider,
+                final_messages,
+                final_system,
+                options.tool_choice,
+                &ctx,
+                &events,
+                &partial,
+            )
+            .await;
+            ctx.session.set_run_journal(None).await;
+            journal.finish();
+            result
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Some(t) = &transcript {
+        t.log_run_end(result.as_ref().map(|outcome| outcome.stop_reason.clone()));
+    }
+
+    // Todos and plans are saved with the conversation, so they survive a restart
+    let todos = ctx.session.tracked_todos().await;
+    let plans = ctx.session.plan_history().await;
+    if let Err(e) =
+        Conversation::record_progress(settings.project_path, &session_id, &todos, &plans)
+    {
+        log::warn!("Failed to record todos and plans: {}", e);
+    }
+
+    // Kept so the conversation can later be edited or regenerated from this run,
+    // with the assistant text and user messages of any steering before the reply
+    let closed = partial.take_closed();
+    let record_reply = |reply: Option<ChatMessage>| {
+        let messages: Vec<ChatMessage> = closed.into_iter().chain(reply).collect();
+        if messages.is_empty() {
+            return;
+        }
+        if let Err(e) = Conversation::record_reply(settings.project_path, &session_id, messages) {
+            log::warn!("Failed to record conversation: {}", e);
+        }
+    };
+
+    match result {
+        Ok(outcome) => {
+            let reply = partial.into_reply(message_id.clone());
+            let summary = summarize_turn(
+                message_id.clone(),
+                reply.as_ref(),
+                ctx.session.turn_snapshots().await,
+                TurnUsage {
+                    model: settings.model,
+                    before: usage_before,
+                    after: ctx.usage_tracker.get_totals(),
+                },
+                settings.execution.dry_run,
+            )
+            .await;
+            record_reply(reply);
+            emit_status(&events, AgentStatus::Idle, None);
+            let _ = events.emit(
+                "agent-complete",
+                AgentCompletePayload {
+                    message_id,
+                    stop_reason: outcome.stop_reason,
+                    timings: outcome.timings,
+                },
+            );
+            let _ = events.emit("agent-turn-summary", summary);
+            Ok(())
+        }
+        Err(AgentError::Cancelled) => {
+            let partial = partial.into_message();
+            record_reply(partial.clone());
+            emit_status(&events, AgentStatus::Cancelled, None);
+            let _ = events.emit(
+                "agent-cancelled",
+                AgentCancelledPayload {
+                    reason: "Cancelled by user".to_string(),
+                    partial,
+                },
+            );
+            Err(AgentError::Cancelled)
+        }
+        Err(e) => {
+            emit_status(&events, AgentStatus::Error, Some(e.to_string()));
+            emit_error(&events, e.to_string());
+            Err(e)
+        }
+    }
+}
+
+/// Headless run for sub-agents and automations, with the same system prompt
+/// assembly, guardrails and transcripts as interactive runs.
+pub(crate) async fn run_headless<P: ProviderRuntime>(
+    provider: &P,
+    messages: Vec<ChatMessage>,
+    system_prompt: Option<String>,
+    memory: Option<String>,
+    tools: Vec<ToolDefinition>,
+    response_schema: Option<serde_json::Value>,
+    ctx: ExecutionContext,
+) -> Result<HeadlessResult, AgentError> {
+    let settings = provider.settings();
+    let system = build_system_prompt(
+        settings.app_system_prompt,
+        settings.prompts,
+        system_prompt,
+        memory.as_deref(),
+        &ctx.session.get_prompt_variables().await,
+    )?;
+    check_request_size(&messages, &system, settings.execution)?;
+
+    let transcript = settings.transcript(&ctx.session);
+    if let Some(t) = &transcript {
+        t.log(TranscriptEvent::RunStart {
+            provider: settings.provider.to_string(),
+            model: settings.model.to_string(),
+            headless: true,
+            system_prompt: Some(system.clone()),
+            messages: messages.clone(),
+        });
+    }
+
+    // Nobody can answer an approval request here, so those tools are left out
+    let auto_approve = ctx
+        .session
+        .get_auto_approve()
+        .await
+        .unwrap_or_else(|| settings.auto_approve.clone());
+    let disabled: Vec<String> = settings
+        .execution
+        .disabled_tools
+        .iter()
+        .cloned()
+        .chain(tools_needing_approval(&auto_approve))
+        .collect();
+
+    let agent_type = ctx.session.get_agent_type().await;
+    let executor = settings
+        .executor(
+            ctx.session,
+            ctx.cancel_token.clone(),
+            Arc::clone(&ctx.usage_tracker),
+        )?
+        .with_disabled_tools(&disabled);
+
+    let result = run_headless_loop(
+        provider,
+        messages,
+        HeadlessContext {
+            system_prompt: Some(system),
+            tools: without_disabled(offered_tools(provider, tools), &disabled),
+            response_schema,
+            executor: &executor,
+            max_iterations: settings.execution.max_tool_iterations,
+            cancel_token: &ctx.cancel_token,
+            usage_tracker: ctx.usage_tracker,
+            agent_type,
+            transcript: transcript.clone(),
+            seed: None,
+        },
+    )
+    .await;
+
+    if let Some(t) = &transcript {
+        t.log_run_end(result.as_ref().map(|r| r.stop_reason.clone()));
+    }
+
+    result
+}
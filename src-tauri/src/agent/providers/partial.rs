@@ -0,0 +1,230 @@
+//! Content produced by a run: kept so the user can continue from a cancelled
+//! run, and recorded as the reply of a completed one. Steering closes the
+//! assistant message so far, which is kept with the steering messages.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use crate::agent::types::{ChatContentBlock, ChatMessage, MessageRole, SteeringMessage};
+
+/// Collects streamed text and completed tool results by global block index.
+#[derive(Default)]
+pub(crate) struct PartialResponse {
+    blocks: Mutex<BTreeMap<u32, ChatContentBlock>>,
+    /// Assistant messages closed by steering and the steering messages, in order
+    closed: Mutex<Vec<ChatMessage>>,
+}
+
+impl PartialResponse {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push_text(&self, block_index: u32, delta: &str) {
+        let Ok(mut blocks) = self.blocks.lock() else {
+            return;
+        };
+        match blocks.entry(block_index).or_insert(ChatContentBlock::Text {
+            text: String::new(),
+        }) {
+            ChatContentBlock::Text { text } => text.push_str(delta),
+            ChatContentBlock::ToolUse { .. } => {}
+        }
+    }
+
+    pub fn push_tool_result(
+        &self,
+        block_index: u32,
+        tool_use_id: &str,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+        output: &str,
+        is_error: bool,
+    ) {
+        if let Ok(mut blocks) = self.blocks.lock() {
+            blocks.insert(
+                block_index,
+                ChatContentBlock::ToolUse {
+                    tool_use_id: tool_use_id.to_string(),
+                    tool_name: tool_name.to_string(),
+                    tool_input: tool_input.clone(),
+                    output: Some(output.to_string()),
+                    is_error: Some(is_error),
+                },
+            );
+        }
+    }
+
+    /// Drops blocks from a response that is about to be re-streamed after a reconnect.
+    pub fn discard_from(&self, block_index: u32) {
+        if let Ok(mut blocks) = self.blocks.lock() {
+            blocks.split_off(&block_index);
+        }
+    }
+
+    /// Closes the assistant message so far, as the UI does when `steering`
+    /// joins the run, and keeps it and the steering messages. Returns the
+    /// closed message's id, or None if nothing was produced yet.
+    pub fn close_segment(&self, steering: &[SteeringMessage]) -> Option<String> {
+        let blocks = self
+            .blocks
+            .lock()
+            .map(|mut blocks| std::mem::take(&mut *blocks))
+            .unwrap_or_default();
+        let Ok(mut closed) = self.closed.lock() else {
+            return None;
+        };
+        let segment = kept_blocks(blocks)
+            .map(|blocks| ChatMessage::with_blocks(MessageRole::Assistant, blocks));
+        let id = segment.as_ref().map(|message| message.id.clone());
+        closed.extend(segment);
+        closed.extend(steering.iter().map(|message| {
+            let mut user = ChatMessage::new(MessageRole::User, message.content.clone());
+            user.id = message.id.clone();
+            user
+        }));
+        id
+    }
+
+    /// Takes the messages closed by steering, to record before the reply.
+    pub fn take_closed(&self) -> Vec<ChatMessage> {
+        self.closed
+            .lock()
+            .map(|mut closed| std::mem::take(&mut *closed))
+            .unwrap_or_default()
+    }
+
+    /// Builds an interrupted assistant message, or None if nothing was produced.
+    pub fn into_message(self) -> Option<ChatMessage> {
+        let mut message = ChatMessage::with_blocks(MessageRole::Assistant, self.into_blocks()?);
+        message.interrupted = Some(true);
+        Some(message)
+    }
+
+    /// Builds the assistant message of a completed run with the id sent in
+    /// `agent-complete`, or None if nothing was produced.
+    pub fn into_reply(self, id: String) -> Option<ChatMessage> {
+        let mut message = ChatMessage::with_blocks(MessageRole::Assistant, self.into_blocks()?);
+        message.id = id;
+        Some(message)
+    }
+
+    fn into_blocks(self) -> Option<Vec<ChatContentBlock>> {
+        kept_blocks(self.blocks.into_inner().ok()?)
+    }
+}
+
+/// The blocks in order without blank text, or None if none are left.
+fn kept_blocks(blocks: BTreeMap<u32, ChatContentBlock>) -> Option<Vec<ChatContentBlock>> {
+    let blocks: Vec<ChatContentBlock> = blocks
+        .into_values()
+        .filter(|block| match block {
+            ChatContentBlock::Text { text } => !text.trim().is_empty(),
+            ChatContentBlock::ToolUse { .. } => true,
+        })
+        .collect();
+
+    (!blocks.is_empty()).then_some(blocks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_partial_yields_none() {
+        assert!(PartialResponse::new().into_message().is_none());
+
+        let partial = PartialResponse::new();
+        partial.push_text(0, "  ");
+        assert!(partial.into_message().is_none());
+    }
+
+    #[test]
+    fn test_collects_text_and_tool_results_in_block_order() {
+        let partial = PartialResponse::new();
+        partial.push_text(2, "After tool");
+        partial.push_tool_result(
+            1,
+            "t1",
+            "read_file",
+            &serde_json::json!({"path": "a.txt"}),
+            "contents",
+            false,
+        );
+        partial.push_text(0, "Let me ");
+        partial.push_text(0, "check.");
+
+        let message = partial.into_message().unwrap();
+        assert_eq!(message.interrupted, Some(true));
+        assert!(matches!(message.role, MessageRole::Assistant));
+        assert_eq!(message.content_blocks.len(), 3);
+        assert_eq!(message.get_text(), "Let me check.After tool");
+        match &message.content_blocks[1] {
+            ChatContentBlock::ToolUse { output, .. } => {
+                assert_eq!(output.as_deref(), Some("contents"));
+            }
+            other => panic!("unexpected block: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reply_keeps_id_and_is_not_interrupted() {
+        let partial = PartialResponse::new();
+        partial.push_text(0, "Done.");
+
+        let message = partial.into_reply("m1".to_string()).unwrap();
+        assert_eq!(message.id, "m1");
+        assert_eq!(message.interrupted, None);
+        assert_eq!(message.get_text(), "Done.");
+    }
+
+    #[test]
+    fn test_discard_from_drops_restreamed_blocks() {
+        let partial = PartialResponse::new();
+        partial.push_text(0, "kept");
+        partial.push_text(1, "duplicated");
+        partial.discard_from(1);
+        partial.push_text(1, "restreamed");
+
+        let message = partial.into_message().unwrap();
+        assert_eq!(message.get_text(), "keptrestreamed");
+    }
+
+    #[test]
+    fn test_steering_closes_the_segment_before_the_reply() {
+        let partial = PartialResponse::new();
+        partial.push_text(0, "Reading the file.");
+        let steering = [SteeringMessage {
+            id: "u1".to_string(),
+            content: "Also mention the size.".to_string(),
+        }];
+        let closed_id = partial.close_segment(&steering).unwrap();
+        partial.push_text(1, "It is 12 bytes.");
+
+        let closed = partial.take_closed();
+        assert_eq!(closed.len(), 2);
+        assert_eq!(closed[0].id, closed_id);
+        assert!(matches!(closed[0].role, MessageRole::Assistant));
+        assert_eq!(closed[0].get_text(), "Reading the file.");
+        assert_eq!(closed[1].id, "u1");
+        assert!(matches!(closed[1].role, MessageRole::User));
+        assert_eq!(closed[1].get_text(), "Also mention the size.");
+
+        let reply = partial.into_reply("m1".to_string()).unwrap();
+        assert_eq!(reply.get_text(), "It is 12 bytes.");
+    }
+
+    #[test]
+    fn test_steering_before_any_output_keeps_only_the_messages() {
+        let partial = PartialResponse::new();
+        let steering = [SteeringMessage {
+            id: "u1".to_string(),
+            content: "Use tabs.".to_string(),
+        }];
+        assert!(partial.close_segment(&steering).is_none());
+        let closed = partial.take_closed();
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].id, "u1");
+    }
+}
@@ -0,0 +1,215 @@
+//! Retry of provider requests that fail on connection errors (network drop, DNS, reset).
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::agent::error::AgentError;
+use crate::agent::rng::{resolve_seed, SeededRng};
+
+/// Backoff schedule for reconnect attempts: base, 2x base, 4x base, ...
+/// each spread by up to `jitter` (a fraction of the delay) in either direction.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReconnectPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: f64,
+    /// Seed for the jitter (None = `DEVFLOW_SEED` or random)
+    pub seed: Option<u64>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.2,
+            seed: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    pub fn delay_for(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    pub fn jittered_delay(&self, retry: u32, rng: &mut SeededRng) -> Duration {
+        let delay = self.delay_for(retry);
+        if self.jitter <= 0.0 {
+            return delay;
+        }
+        let spread = (rng.next_f64() * 2.0 - 1.0) * self.jitter;
+        delay.mul_f64((1.0 + spread).max(0.0)).min(self.max_delay)
+    }
+}
+
+/// Details of an upcoming retry, passed to the `on_retry` callback.
+#[derive(Debug, Clone)]
+pub(crate) struct ReconnectAttempt {
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub delay: Duration,
+    pub error: String,
+}
+
+/// Runs `request`, retrying with backoff while it fails with `AgentError::Connection`.
+/// API errors and cancellation are returned immediately.
+pub(crate) async fn with_reconnect<T, F, Fut, R>(
+    policy: ReconnectPolicy,
+    cancel_token: &CancellationToken,
+    mut on_retry: R,
+    mut request: F,
+) -> Result<T, AgentError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AgentError>>,
+    R: FnMut(&ReconnectAttempt),
+{
+    let mut retries = 0u32;
+    let mut rng = SeededRng::new(resolve_seed(policy.seed));
+
+    loop {
+        match request().await {
+            Err(AgentError::Connection(message)) if retries < policy.max_retries => {
+                retries += 1;
+                let delay = policy.jittered_delay(retries, &mut rng);
+                on_retry(&ReconnectAttempt {
+                    attempt: retries,
+                    max_retries: policy.max_retries,
+                    delay,
+                    error: message,
+                });
+
+                tokio::select! {
+                    _ = cancel_token.cancelled() => return Err(AgentError::Cancelled),
+                    _ = tokio::time::sleep(delay) => {}
+                }
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy(max_retries: u32) -> ReconnectPolicy {
+        ReconnectPolicy {
+            max_retries,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: 0.0,
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn test_delay_doubles_and_caps() {
+        let policy = ReconnectPolicy::default();
+        assert_eq!(policy.delay_for(1), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(2), Duration::from_secs(2));
+        assert_eq!(policy.delay_for(3), Duration::from_secs(4));
+        assert_eq!(policy.delay_for(10), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_jitter_is_bounded_and_reproducible() {
+        let policy = ReconnectPolicy::default();
+        let mut a = SeededRng::new(5);
+        let mut b = SeededRng::new(5);
+
+        for retry in 1..=4 {
+            let delay = policy.jittered_delay(retry, &mut a);
+            assert_eq!(delay, policy.jittered_delay(retry, &mut b));
+
+            let base = policy.delay_for(retry);
+            assert!(delay >= base.mul_f64(0.8));
+            assert!(delay <= base.mul_f64(1.2));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_connection_errors_until_success() {
+        let calls = &AtomicU32::new(0);
+        let mut attempts = Vec::new();
+
+        let result = with_reconnect(
+            fast_policy(3),
+            &CancellationToken::new(),
+            |a| attempts.push(a.attempt),
+            move || async move {
+                if calls.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(AgentError::Connection("reset".to_string()))
+                } else {
+                    Ok("done")
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), "done");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(attempts, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let calls = &AtomicU32::new(0);
+
+        let result: Result<(), _> = with_reconnect(
+            fast_policy(2),
+            &CancellationToken::new(),
+            |_| {},
+            move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(AgentError::Connection("offline".to_string()))
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(AgentError::Connection(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_api_errors_are_not_retried() {
+        let calls = &AtomicU32::new(0);
+
+        let result: Result<(), _> = with_reconnect(
+            fast_policy(3),
+            &CancellationToken::new(),
+            |_| {},
+            move || async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(AgentError::ApiError("invalid request".to_string()))
+            },
+        )
+        .await;
+
+        assert!(matches!(result, Err(AgentError::ApiError(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_during_backoff() {
+        let cancel_token = CancellationToken::new();
+        cancel_token.cancel();
+
+        let result: Result<(), _> = with_reconnect(
+            ReconnectPolicy::default(),
+            &cancel_token,
+            |_| {},
+            || async { Err(AgentError::Connection("offline".to_string())) },
+        )
+        .await;
+
+        assert!(matches!(result, Err(AgentError::Cancelled)));
+    }
+}
@@ -0,0 +1,446 @@
+//! Replay of recorded transcripts through the headless tool loop, without provider calls.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use ts_rs::TS;
+
+use crate::agent::error::AgentError;
+use crate::agent::tools::{get_tool_definitions, SessionState, ToolExecutor, ToolName};
+use crate::agent::transcript::{load_transcript, TranscriptEntry, TranscriptEvent};
+use crate::agent::types::{ChatMessage, ToolDefinition};
+use crate::agent::usage::{SessionUsageTracker, TokenUsage};
+use crate::config::ConfigService;
+
+use super::create_executor;
+use super::headless::{
+    run_headless_loop, HeadlessContext, HeadlessResponse, HeadlessStreamer, ToolCall, ToolResult,
+};
+
+/// Outcome of a replay, alongside what the recorded run ended with.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ReplaySummary {
+    pub text: String,
+    pub tool_calls_made: u32,
+    pub stop_reason: Option<String>,
+    pub recorded_stop_reason: Option<String>,
+    pub recorded_error: Option<String>,
+    pub responses_replayed: u32,
+    pub responses_recorded: u32,
+}
+
+/// A single run (RunStart to RunEnd) extracted from a transcript.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayRun {
+    pub system_prompt: Option<String>,
+    pub messages: Vec<ChatMessage>,
+    pub responses: Vec<HeadlessResponse>,
+    pub tool_results: Vec<ToolResult>,
+    pub stop_reason: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ReplayRun {
+    /// Extracts the `index`-th run (0-based) from transcript entries.
+    pub fn from_entries(entries: &[TranscriptEntry], index: usize) -> Result<Self, AgentError> {
+        let mut runs = entries
+            .split_inclusive(|e| matches!(e.event, TranscriptEvent::RunEnd { .. }))
+            .filter(|events| {
+                events
+                    .iter()
+                    .any(|e| matches!(e.event, TranscriptEvent::RunStart { .. }))
+            });
+        let events = runs.nth(index).ok_or_else(|| {
+            AgentError::ConfigError(format!("Transcript has no run at index {}", index))
+        })?;
+
+        let mut run = ReplayRun::default();
+        for entry in events {
+            match &entry.event {
+                TranscriptEvent::RunStart {
+                    system_prompt,
+                    messages,
+                    ..
+                } => {
+                    run.system_prompt = system_prompt.clone();
+                    run.messages = messages.clone();
+                }
+                TranscriptEvent::Request { .. }
+                | TranscriptEvent::Steering { .. }
+                | TranscriptEvent::Rating { .. } => {}
+                TranscriptEvent::Response {
+                    text,
+                    tool_calls,
+                    stop_reason,
+                    input_tokens,
+                    output_tokens,
+                    ..
+                } => run.responses.push(HeadlessResponse {
+                    text: text.clone(),
+                    tool_calls: tool_calls
+                        .iter()
+                        .map(|tc| ToolCall {
+                            id: tc.id.clone(),
+                            name: tc.name.clone(),
+                            input: tc.input.clone(),
+                        })
+                        .collect(),
+                    usage: TokenUsage {
+                        input_tokens: *input_tokens,
+                        output_tokens: *output_tokens,
+                    },
+                    stop_reason: stop_reason.clone(),
+                }),
+                TranscriptEvent::ToolResult {
+                    id,
+                    name,
+                    output,
+                    is_error,
+                    ..
+                } => run.tool_results.push(ToolResult {
+                    id: id.clone(),
+                    name: name.clone(),
+                    output: output.clone(),
+                    is_error: *is_error,
+                }),
+                TranscriptEvent::RunEnd { stop_reason, error } => {
+                    run.stop_reason = stop_reason.clone();
+                    run.error = error.clone();
+                }
+            }
+        }
+
+        Ok(run)
+    }
+}
+
+/// Returns recorded responses in order instead of calling a provider.
+pub struct ReplayStreamer {
+    responses: Vec<HeadlessResponse>,
+    next: AtomicUsize,
+}
+
+impl ReplayStreamer {
+    pub fn new(responses: Vec<HeadlessResponse>) -> Self {
+        Self {
+            responses,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of recorded responses handed out so far.
+    pub fn replayed(&self) -> usize {
+        self.next.load(Ordering::SeqCst).min(self.responses.len())
+    }
+}
+
+#[async_trait]
+impl HeadlessStreamer for ReplayStreamer {
+    type Conversation = ();
+
+    fn initial_conversation(&self, _messages: Vec<ChatMessage>) -> Self::Conversation {}
+
+    async fn stream_response(
+        &self,
+        _conversation: &Self::Conversation,
+        _system_prompt: Option<String>,
+        _tools: &[ToolDefinition],
+        _response_schema: Option<&serde_json::Value>,
+        cancel_token: &CancellationToken,
+    ) -> Result<HeadlessResponse, AgentError> {
+        if cancel_token.is_cancelled() {
+            return Err(AgentError::Cancelled);
+        }
+
+        let index = self.next.fetch_add(1, Ordering::SeqCst);
+        self.responses.get(index).cloned().ok_or_else(|| {
+            AgentError::ApiError(format!(
+                "Replay diverged: transcript has only {} recorded responses",
+                self.responses.len()
+            ))
+        })
+    }
+
+    fn append_assistant_response(
+        &self,
+        _conversation: &mut Self::Conversation,
+        _response: &HeadlessResponse,
+    ) {
+    }
+
+    fn append_tool_results(
+        &self,
+        _conversation: &mut Self::Conversation,
+        _results: Vec<ToolResult>,
+    ) {
+    }
+
+    fn append_user_text(&self, _conversation: &mut Self::Conversation, _text: String) {}
+}
+
+/// Returns recorded tool outputs, matched by tool name in recording order.
+pub struct ReplayExecutor {
+    results: Mutex<Vec<ToolResult>>,
+}
+
+impl ReplayExecutor {
+    pub fn new(results: Vec<ToolResult>) -> Self {
+        Self {
+            results: Mutex::new(results),
+        }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for ReplayExecutor {
+    async fn execute(
+        &self,
+        tool: ToolName,
+        _input: serde_json::Value,
+    ) -> Result<String, AgentError> {
+        let mut results = self.results.lock().await;
+        let position = results
+            .iter()
+            .position(|r| r.name == tool.as_str())
+            .ok_or_else(|| {
+                AgentError::ToolExecutionError(format!(
+                    "No recorded result left for tool '{}'",
+                    tool.as_str()
+                ))
+            })?;
+
+        let result = results.remove(position);
+        if result.is_error {
+            Err(AgentError::ToolExecutionError(result.output))
+        } else {
+            Ok(result.output)
+        }
+    }
+}
+
+/// Replays run `run_index` of a session transcript. With `mock_tools`, recorded
+/// tool outputs are returned; otherwise tools run for real against the project.
+pub async fn replay_transcript(
+    project_path: &Path,
+    session_id: &str,
+    run_index: usize,
+    mock_tools: bool,
+    cancel_token: CancellationToken,
+) -> Result<ReplaySummary, AgentError> {
+    if session_id.is_empty() || session_id.contains(['/', '\\']) || session_id.contains("..") {
+        return Err(AgentError::ConfigError(format!(
+            "Invalid session id '{}'",
+            session_id
+        )));
+    }
+
+    let config = ConfigService::load_project_config(project_path)
+        .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+    let path = ConfigService::transcript_dir(project_path).join(format!("{}.jsonl", session_id));
+    let entries = load_transcript(&path)?;
+    let run = ReplayRun::from_entries(&entries, run_index)?;
+
+    let responses_recorded = run.responses.len() as u32;
+    let streamer = ReplayStreamer::new(run.responses);
+    let usage_tracker = Arc::new(SessionUsageTracker::new());
+
+    let replay_executor;
+    let local_executor;
+    let executor: &dyn ToolExecutor = if mock_tools {
+        replay_executor = ReplayExecutor::new(run.tool_results);
+        &replay_executor
+    } else {
+        local_executor = create_executor(
+            project_path,
+            &config.execution,
+            &config.workspace,
+            &config.network,
+            None,
+            SessionState::new(),
+            cancel_token.clone(),
+            Arc::clone(&usage_tracker),
+        )?;
+        &local_executor
+    };
+
+    let result = run_headless_loop(
+        &streamer,
+        run.messages,
+        HeadlessContext {
+            system_prompt: run.system_prompt,
+            tools: get_tool_definitions(),
+            response_schema: None,
+            executor,
+            max_iterations: config.execution.max_tool_iterations,
+            cancel_token: &cancel_token,
+            usage_tracker,
+            agent_type: None,
+            transcript: None,
+            seed: None,
+        },
+    )
+    .await?;
+
+    Ok(ReplaySummary {
+        text: result.text,
+        tool_calls_made: result.tool_calls_made,
+        stop_reason: result.stop_reason,
+        recorded_stop_reason: run.stop_reason,
+        recorded_error: run.error,
+        responses_replayed: streamer.replayed() as u32,
+        responses_recorded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::transcript::{TranscriptLogger, TranscriptToolCall};
+    use crate::agent::types::MessageRole;
+
+    fn record_run(logger: &TranscriptLogger) {
+        logger.log(TranscriptEvent::RunStart {
+            provider: "anthropic".to_string(),
+            model: "claude-sonnet-4".to_string(),
+            headless: false,
+            system_prompt: Some("system".to_string()),
+            messages: vec![ChatMessage::new(
+                MessageRole::User,
+                "Read a.txt".to_string(),
+            )],
+        });
+        logger.log(TranscriptEvent::Response {
+            turn: 0,
+            text: "Reading".to_string(),
+            tool_calls: vec![TranscriptToolCall {
+                id: "t1".to_string(),
+                name: "read_file".to_string(),
+                input: serde_json::json!({"path": "a.txt"}),
+            }],
+            stop_reason: Some("tool_use".to_string()),
+            input_tokens: 10,
+            output_tokens: 5,
+        });
+        logger.log(TranscriptEvent::ToolResult {
+            id: "t1".to_string(),
+            name: "read_file".to_string(),
+            output: "hello".to_string(),
+            is_error: false,
+            artifacts: Vec::new(),
+        });
+        logger.log(TranscriptEvent::Response {
+            turn: 1,
+            text: " done".to_string(),
+            tool_calls: vec![],
+            stop_reason: Some("end_turn".to_string()),
+            input_tokens: 20,
+            output_tokens: 2,
+        });
+        logger.log(TranscriptEvent::RunEnd {
+            stop_reason: Some("end_turn".to_string()),
+            error: None,
+        });
+    }
+
+    fn recorded_entries() -> Vec<TranscriptEntry> {
+        let temp = tempfile::tempdir().unwrap();
+        let logger = TranscriptLogger::new(temp.path(), "s1", &[]);
+        record_run(&logger);
+        record_run(&logger);
+        load_transcript(logger.path()).unwrap()
+    }
+
+    #[test]
+    fn test_from_entries_extracts_run() {
+        let entries = recorded_entries();
+
+        let run = ReplayRun::from_entries(&entries, 1).unwrap();
+        assert_eq!(run.system_prompt.as_deref(), Some("system"));
+        assert_eq!(run.messages.len(), 1);
+        assert_eq!(run.responses.len(), 2);
+        assert_eq!(run.responses[0].tool_calls[0].name, "read_file");
+        assert_eq!(run.tool_results[0].output, "hello");
+        assert_eq!(run.stop_reason.as_deref(), Some("end_turn"));
+
+        assert!(ReplayRun::from_entries(&entries, 2).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_through_headless_loop_with_mocked_tools() {
+        let run = ReplayRun::from_entries(&recorded_entries(), 0).unwrap();
+        let streamer = ReplayStreamer::new(run.responses);
+        let executor = ReplayExecutor::new(run.tool_results);
+        let cancel_token = CancellationToken::new();
+
+        let result = run_headless_loop(
+            &streamer,
+            run.messages,
+            HeadlessContext {
+                system_prompt: run.system_prompt,
+                tools: vec![],
+                response_schema: None,
+                executor: &executor,
+                max_iterations: 5,
+                cancel_token: &cancel_token,
+                usage_tracker: Arc::new(SessionUsageTracker::new()),
+                agent_type: None,
+                transcript: None,
+                seed: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.text, "Reading done");
+        assert_eq!(result.tool_calls_made, 1);
+        assert_eq!(streamer.replayed(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_replay_errors_when_responses_run_out() {
+        let streamer = ReplayStreamer::new(vec![]);
+        let result = streamer
+            .stream_response(&(), None, &[], &CancellationToken::new())
+            .await;
+        assert!(matches!(result, Err(AgentError::ApiError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_replay_executor_matches_by_name() {
+        let executor = ReplayExecutor::new(vec![
+            ToolResult {
+                id: "a".to_string(),
+                name: "bash".to_string(),
+                output: "exit 1".to_string(),
+                is_error: true,
+            },
+            ToolResult {
+                id: "b".to_string(),
+                name: "read_file".to_string(),
+                output: "content".to_string(),
+                is_error: false,
+            },
+        ]);
+
+        let read = executor
+            .execute(ToolName::ReadFile, serde_json::json!({}))
+            .await;
+        assert_eq!(read.unwrap(), "content");
+
+        let bash = executor
+            .execute(ToolName::Bash, serde_json::json!({}))
+            .await;
+        assert!(matches!(bash, Err(AgentError::ToolExecutionError(_))));
+
+        let missing = executor
+            .execute(ToolName::Bash, serde_json::json!({}))
+            .await;
+        assert!(missing.is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! Soft warning for provider requests that go quiet longer than expected.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+/// Calls `on_slow` once per request when no data has arrived within `threshold`.
+/// Unlike the read timeout, this never fails the request.
+pub(crate) struct SlowResponseWatch<F: Fn(Duration) + Sync> {
+    threshold: Duration,
+    started: Instant,
+    last_activity: Mutex<Instant>,
+    warned: AtomicBool,
+    on_slow: F,
+}
+
+impl<F: Fn(Duration) + Sync> SlowResponseWatch<F> {
+    pub fn new(threshold: Duration, on_slow: F) -> Self {
+        let now = Instant::now();
+        Self {
+            threshold,
+            started: now,
+            last_activity: Mutex::new(now),
+            warned: AtomicBool::new(false),
+            on_slow,
+        }
+    }
+
+    pub fn on_activity(&self) {
+        if let Ok(mut last) = self.last_activity.lock() {
+            *last = Instant::now();
+        }
+    }
+
+    /// Completes when the threshold passes without activity; pending forever
+    /// once the warning has fired. Meant to be raced against the next chunk.
+    pub async fn wait(&self) {
+        if self.warned.load(Ordering::SeqCst) || self.threshold.is_zero() {
+            return std::future::pending().await;
+        }
+
+        let deadline = self
+            .last_activity
+            .lock()
+            .map(|last| *last + self.threshold)
+            .unwrap_or_else(|_| Instant::now() + self.threshold);
+        tokio::time::sleep_until(deadline).await;
+
+        if !self.warned.swap(true, Ordering::SeqCst) {
+            (self.on_slow)(self.started.elapsed());
+        }
+    }
+
+    /// Awaits `future`, firing the warning if it takes longer than the threshold.
+    pub async fn guard<T>(&self, future: impl Future<Output = T>) -> T {
+        tokio::pin!(future);
+        loop {
+            tokio::select! {
+                output = &mut future => return output,
+                _ = self.wait() => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[tokio::test]
+    async fn test_guard_warns_once_for_slow_future() {
+        let warnings = &AtomicU32::new(0);
+        let watch = SlowResponseWatch::new(Duration::from_millis(10), |_| {
+            warnings.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let output = watch
+            .guard(async {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                "done"
+            })
+            .await;
+
+        assert_eq!(output, "done");
+        assert_eq!(warnings.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fast_future_does_not_warn() {
+        let warnings = &AtomicU32::new(0);
+        let watch = SlowResponseWatch::new(Duration::from_secs(5), |_| {
+            warnings.fetch_add(1, Ordering::SeqCst);
+        });
+
+        watch.guard(async {}).await;
+        assert_eq!(warnings.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_zero_threshold_disables_warning() {
+        let warnings = &AtomicU32::new(0);
+        let watch = SlowResponseWatch::new(Duration::ZERO, |_| {
+            warnings.fetch_add(1, Ordering::SeqCst);
+        });
+
+        watch
+            .guard(tokio::time::sleep(Duration::from_millis(10)))
+            .await;
+        assert_eq!(warnings.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,332 @@
+//! Server-sent event decoding shared by the streaming providers.
+
+use std::time::Duration;
+
+use futures::{Stream, StreamExt};
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::agent::error::AgentError;
+
+use super::SlowResponseWatch;
+
+/// Longest payload excerpt included in parse-error logs
+const LOGGED_DATA_CHARS: usize = 200;
+
+/// One dispatched event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SseEvent {
+    /// `event:` name; None for the default message type
+    pub event: Option<String>,
+    /// `data:` lines joined with newlines
+    pub data: String,
+    /// Last `id:` seen on the stream so far
+    pub id: Option<String>,
+}
+
+impl SseEvent {
+    /// Data parsed as JSON. Malformed payloads are logged and skipped rather
+    /// than failing the stream.
+    pub fn json<T: DeserializeOwned>(&self) -> Option<T> {
+        match serde_json::from_str(&self.data) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                let excerpt: String = self.data.chars().take(LOGGED_DATA_CHARS).collect();
+                log::warn!(
+                    "Skipping malformed SSE event (event: {}, id: {}): {}: {}",
+                    self.event.as_deref().unwrap_or("message"),
+                    self.id.as_deref().unwrap_or("-"),
+                    e,
+                    excerpt
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Incremental decoder for the `text/event-stream` format. Accepts LF, CRLF
+/// and CR line endings, ignores comment lines (used as keep-alives) and
+/// buffers raw bytes so UTF-8 sequences split across chunks decode intact.
+#[derive(Debug, Default)]
+pub(crate) struct SseDecoder {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+    last_id: Option<String>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a chunk of the body, returning the events it completed.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(line) = self.next_line(false) {
+            events.extend(self.process_line(&line));
+        }
+        events
+    }
+
+    /// Ends the body, returning the events still pending, including one left
+    /// without its closing blank line.
+    pub fn finish(&mut self) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+        while let Some(line) = self.next_line(true) {
+            events.extend(self.process_line(&line));
+        }
+        if !self.buffer.is_empty() {
+            let line = String::from_utf8_lossy(&std::mem::take(&mut self.buffer)).into_owned();
+            events.extend(self.process_line(&line));
+        }
+        events.extend(self.dispatch());
+        events
+    }
+
+    /// Takes the next complete line. A trailing CR might be the first half of
+    /// a CRLF, so it waits for more input unless the body has ended.
+    fn next_line(&mut self, at_end: bool) -> Option<String> {
+        let end = self.buffer.iter().position(|&b| b == b'\n' || b == b'\r')?;
+        let mut consumed = end + 1;
+        if self.buffer[end] == b'\r' {
+            match self.buffer.get(end + 1) {
+                Some(b'\n') => consumed += 1,
+                None if !at_end => return None,
+                _ => {}
+            }
+        }
+        let line = String::from_utf8_lossy(&self.buffer[..end]).into_owned();
+        self.buffer.drain(..consumed);
+        Some(line)
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+            // Reconnect timing is left to the provider's reconnect policy
+            "retry" => {}
+            _ => log::debug!("Ignoring SSE field {:?}", field),
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        if self.data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            event,
+            data: std::mem::take(&mut self.data).join("\n"),
+            id: self.last_id.clone(),
+        })
+    }
+}
+
+/// Reads an event stream body until it ends, passing each event to
+/// `on_event`. Fails with `Cancelled` as soon as `cancel_token` fires, and
+/// keeps `watch` informed so a stalled stream still gets its slow warning.
+/// A stream that sends nothing for `idle_timeout` (zero = no limit) fails
+/// with a connection error so the reconnect policy retries it.
+pub(crate) async fn read_events<F: Fn(Duration) + Sync>(
+    response: Response,
+    cancel_token: &CancellationToken,
+    watch: &SlowResponseWatch<F>,
+    idle_timeout: Duration,
+    on_event: impl FnMut(&SseEvent),
+) -> Result<(), AgentError> {
+    read_stream(
+        response.bytes_stream(),
+        cancel_token,
+        watch,
+        idle_timeout,
+        on_event,
+    )
+    .await
+}
+
+async fn read_stream<F, S, B>(
+    mut stream: S,
+    cancel_token: &CancellationToken,
+    watch: &SlowResponseWatch<F>,
+    idle_timeout: Duration,
+    mut on_event: impl FnMut(&SseEvent),
+) -> Result<(), AgentError>
+where
+    F: Fn(Duration) + Sync,
+    S: Stream<Item = Result<B, reqwest::Error>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    let mut decoder = SseDecoder::new();
+    let mut idle_deadline = Instant::now() + idle_timeout;
+
+    loop {
+        if cancel_token.is_cancelled() {
+            return Err(AgentError::Cancelled);
+        }
+
+        tokio::select! {
+            _ = cancel_token.cancelled() => {
+                return Err(AgentError::Cancelled);
+            }
+            _ = watch.wait() => {}
+            _ = tokio::time::sleep_until(idle_deadline), if !idle_timeout.is_zero() => {
+                log::warn!("Response stream stalled for {:?}", idle_timeout);
+                return Err(AgentError::Connection(format!(
+                    "stream stalled, no data for {}s",
+                    idle_timeout.as_secs()
+                )));
+            }
+            chunk_result = stream.next() => {
+                watch.on_activity();
+                idle_deadline = Instant::now() + idle_timeout;
+                match chunk_result {
+                    Some(Ok(chunk)) => {
+                        for event in decoder.push(chunk.as_ref()) {
+                            on_event(&event);
+                        }
+                    }
+                    Some(Err(e)) => return Err(e.into()),
+                    None => {
+                        for event in decoder.finish() {
+                            on_event(&event);
+                        }
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(chunks: &[&[u8]]) -> Vec<SseEvent> {
+        let mut decoder = SseDecoder::new();
+        let mut events: Vec<SseEvent> = chunks.iter().flat_map(|c| decoder.push(c)).collect();
+        events.extend(decoder.finish());
+        events
+    }
+
+    fn data(events: &[SseEvent]) -> Vec<&str> {
+        events.iter().map(|e| e.data.as_str()).collect()
+    }
+
+    #[test]
+    fn test_line_endings() {
+        let events = decode(&[b"data: a\n\ndata: b\r\n\r\ndata: c\r\rdata: d\n\n"]);
+        assert_eq!(data(&events), vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_crlf_split_across_chunks() {
+        let events = decode(&[b"data: a\r", b"\n\r", b"\ndata: b\r\n\r\n"]);
+        assert_eq!(data(&events), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_comments_and_fields() {
+        let events = decode(&[
+            b": keep-alive\n\nevent: message_start\nid: 7\ndata: {}\nretry: 100\n\n",
+            b"data:no-space\n\n",
+        ]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event.as_deref(), Some("message_start"));
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+        assert_eq!(events[1].data, "no-space");
+        assert_eq!(events[1].event, None);
+        // The last event id carries over
+        assert_eq!(events[1].id.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn test_multi_line_data() {
+        let events = decode(&[b"data: {\"a\":\ndata: 1}\n\n"]);
+        assert_eq!(events[0].data, "{\"a\":\n1}");
+        assert_eq!(
+            events[0].json::<serde_json::Value>(),
+            Some(serde_json::json!({"a": 1}))
+        );
+    }
+
+    #[test]
+    fn test_utf8_split_across_chunks() {
+        let text = "data: héllo\n\n".as_bytes();
+        // Split inside the two-byte 'é'
+        let events = decode(&[&text[..8], &text[8..]]);
+        assert_eq!(events[0].data, "héllo");
+    }
+
+    #[test]
+    fn test_unterminated_event_is_flushed_at_end() {
+        let events = decode(&[b"data: a\n\ndata: tail"]);
+        assert_eq!(data(&events), vec!["a", "tail"]);
+    }
+
+    #[test]
+    fn test_malformed_json_is_skipped() {
+        let events = decode(&[b"data: {not json\n\n"]);
+        assert_eq!(events[0].json::<serde_json::Value>(), None);
+    }
+
+    fn no_warning() -> SlowResponseWatch<impl Fn(Duration) + Sync> {
+        SlowResponseWatch::new(Duration::ZERO, |_| {})
+    }
+
+    #[tokio::test]
+    async fn test_stalled_stream_is_a_connection_error() {
+        let chunks: Vec<Result<&[u8], reqwest::Error>> = vec![Ok(b"data: a\n\n")];
+        let stream = futures::stream::iter(chunks).chain(futures::stream::pending());
+        let mut received = Vec::new();
+
+        let result = read_stream(
+            stream,
+            &CancellationToken::new(),
+            &no_warning(),
+            Duration::from_millis(20),
+            |event| received.push(event.data.clone()),
+        )
+        .await;
+
+        assert!(matches!(result, Err(AgentError::Connection(_))));
+        assert_eq!(received, vec!["a"]);
+    }
+
+    #[tokio::test]
+    async fn test_zero_idle_timeout_waits_for_the_end() {
+        let chunks: Vec<Result<&[u8], reqwest::Error>> = vec![Ok(b"data: a\n\n"), Ok(b"data: b")];
+        let mut received = Vec::new();
+
+        read_stream(
+            futures::stream::iter(chunks),
+            &CancellationToken::new(),
+            &no_warning(),
+            Duration::ZERO,
+            |event| received.push(event.data.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(received, vec!["a", "b"]);
+    }
+}
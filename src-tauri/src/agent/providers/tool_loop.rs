@@ -0,0 +1,287 @@
+//! Interactive tool loop shared by all providers: stream a response, run the
+//! tools it calls, feed the results back and repeat until the model stops.
+//! Pausing, steering, reconnects, transcripts, usage and request size checks
+//! live here once;
+//! providers only stream a turn and shape their own conversation.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tauri::Runtime;
+
+use crate::agent::error::AgentError;
+use crate::agent::events::EventSink;
+use crate::agent::guardrails::check_request_size;
+use crate::agent::provider::ExecutionContext;
+use crate::agent::run_journal::JournalToolCall;
+use crate::agent::tools::get_tool_definitions;
+use crate::agent::transcript::TranscriptEvent;
+use crate::agent::types::{AgentStatus, ChatContentBlock, ChatMessage, MessageRole, ToolChoice};
+use crate::agent::usage::{IterationTiming, RunTimings, UsageSource};
+use crate::agent::usage_history::record_usage;
+
+use super::headless::{HeadlessResponse, ToolResult as HeadlessToolResult};
+use super::orchestration::offered_tools;
+use super::{
+    check_iteration_limit, emit_reconnecting, emit_status, emit_usage, execute_tool_calls,
+    take_steering, wait_if_paused, with_reconnect, PartialResponse, ProviderRuntime,
+    ReconnectPolicy, StreamingState, ToolCall,
+};
+
+/// A streamed response in provider-neutral form.
+pub(crate) struct StreamedTurn {
+    pub response: HeadlessResponse,
+    /// Block index of each of `response.tool_calls`, relative to this response
+    pub tool_blocks: Vec<u32>,
+    /// Content blocks emitted to the UI for this response
+    pub block_count: u32,
+    /// When the first content block arrived
+    pub first_token_at: Option<Instant>,
+}
+
+/// How a tool loop ended.
+pub(crate) struct ToolLoopOutcome {
+    pub stop_reason: Option<String>,
+    pub timings: RunTimings,
+}
+
+/// Runs the tool loop from `messages` until the model stops. A `Required`
+/// tool choice applies to the first response only, so the loop can end.
+pub(crate) async fn run_tool_loop<P: ProviderRuntime, R: Runtime>(
+    provider: &P,
+    messages: Vec<ChatMessage>,
+    system_prompt: String,
+    mut tool_choice: ToolChoice,
+    exec: &ExecutionContext,
+    events: &EventSink<R>,
+    partial: &PartialResponse,
+) -> Result<ToolLoopOutcome, AgentError> {
+    let ExecutionContext {
+        session,
+        cancel_token,
+        usage_tracker,
+    } = exec;
+    let settings = provider.settings();
+    let executor = settings.executor(
+        session.clone(),
+        cancel_token.clone(),
+        Arc::clone(usage_tracker),
+    )?;
+    let transcript = settings.transcript(session);
+    let plan_timeout = match settings.execution.plan_approval_timeout_secs {
+        0 => None,
+        secs => Some(Duration::from_secs(secs)),
+    };
+    let tools = offered_tools(provider, get_tool_definitions());
+    let system_prompt = Some(system_prompt);
+    // What the provider conversation holds, in neutral form for the size check
+    let mut sent = messages.clone();
+    let mut conversation = provider.initial_conversation(messages);
+    let mut iteration = 0u32;
+    let mut streaming = StreamingState::new();
+    let run_started = Instant::now();
+    let mut timings = Vec::new();
+
+    loop {
+        if cancel_token.is_cancelled() {
+            return Err(AgentError::Cancelled);
+        }
+
+        wait_if_paused(session, events, cancel_token).await?;
+
+        for message in take_steering(session, events, transcript.as_ref(), partial).await {
+            sent.push(ChatMessage::new(MessageRole::User, message.content.clone()));
+            provider.append_user_text(&mut conversation, message.content);
+        }
+
+        // Tool results and steering grow the conversation after the run's first check
+        check_request_size(
+            &sent,
+            system_prompt.as_deref().unwrap_or_default(),
+            settings.execution,
+        )?;
+
+        if let Some(t) = &transcript {
+            t.log(TranscriptEvent::Request { turn: iteration });
+        }
+
+        let ctx = streaming.create_context(events, cancel_token, partial);
+        let request_started = Instant::now();
+        let (conversation_ref, system_ref, tools_ref, ctx_ref) =
+            (&conversation, &system_prompt, tools.as_slice(), &ctx);
+        let turn = with_reconnect(
+            ReconnectPolicy::default(),
+            cancel_token,
+            |attempt| {
+                partial.discard_from(ctx.block_offset);
+                emit_reconnecting(events, ctx.block_offset, attempt)
+            },
+            move || {
+                provider.stream_turn(
+                    conversation_ref,
+                    system_ref.clone(),
+                    tools_ref,
+                    tool_choice,
+                    ctx_ref,
+                )
+            },
+        )
+        .await?;
+        if tool_choice == ToolChoice::Required {
+            tool_choice = ToolChoice::Auto;
+        }
+        let response = turn.response;
+        let mut timing = IterationTiming::new(
+            turn.first_token_at
+                .map(|at| at.saturating_duration_since(request_started)),
+            request_started.elapsed(),
+            response.usage.output_tokens,
+        );
+        usage_tracker.record_response(&timing);
+
+        if let Some(t) = &transcript {
+            t.log_response(iteration, &response);
+        }
+
+        emit_usage(events, usage_tracker, response.usage, UsageSource::Main);
+        record_usage(
+            settings.project_path,
+            settings.provider,
+            settings.model,
+            response.usage,
+        );
+        streaming.advance(turn.block_count);
+
+        if !response.has_tool_calls() {
+            timings.push(timing);
+            if !session.has_queued_messages().await {
+                return Ok(ToolLoopOutcome {
+                    stop_reason: response.stop_reason,
+                    timings: RunTimings::new(run_started.elapsed(), timings),
+                });
+            }
+            // Answer messages queued during the final response in this run
+            sent.push(assistant_message(&response));
+            provider.append_assistant_response(&mut conversation, &response);
+            continue;
+        }
+
+        iteration += 1;
+        check_iteration_limit(iteration, settings.execution.max_tool_iterations)?;
+
+        sent.push(assistant_message(&response));
+        provider.append_assistant_response(&mut conversation, &response);
+
+        let tool_calls: Vec<ToolCall> = response
+            .tool_calls
+            .into_iter()
+            .zip(turn.tool_blocks)
+            .map(|(call, block)| ToolCall {
+                id: call.id,
+                name: call.name,
+                input: call.input,
+                block_index: ctx.block_offset + block,
+            })
+            .collect();
+
+        let journal = session.run_journal().await;
+        if let Some(journal) = &journal {
+            journal.set_pending(
+                tool_calls
+                    .iter()
+                    .map(|call| JournalToolCall {
+                        id: call.id.clone(),
+                        name: call.name.clone(),
+                        input: call.input.clone(),
+                    })
+                    .collect(),
+            );
+        }
+
+        let tools_started = Instant::now();
+        let results = execute_tool_calls(
+            tool_calls,
+            &executor,
+            session,
+            settings.auto_approve,
+            events,
+            cancel_token,
+            partial,
+            plan_timeout,
+        )
+        .await?;
+        timing.tool_ms = tools_started.elapsed().as_millis() as u64;
+        if let Some(journal) = &journal {
+            journal.set_pending(Vec::new());
+        }
+        timings.push(timing);
+
+        if let Some(t) = &transcript {
+            for r in &results {
+                t.log(TranscriptEvent::ToolResult {
+                    id: r.id.clone(),
+                    name: r.name.clone(),
+                    output: r.output.clone(),
+                    is_error: r.is_error,
+                    artifacts: r.artifacts.clone(),
+                });
+            }
+        }
+
+        let results = results
+            .into_iter()
+            .map(|r| HeadlessToolResult {
+                id: r.id,
+                name: r.name,
+                output: r.output,
+                is_error: r.is_error,
+            })
+            .collect::<Vec<_>>();
+        attach_results(&mut sent, &results);
+        provider.append_tool_results(&mut conversation, results);
+
+        emit_status(events, AgentStatus::ToolWaiting, None);
+    }
+}
+
+/// `response` as a chat message: its text and tool calls, results still unset.
+fn assistant_message(response: &HeadlessResponse) -> ChatMessage {
+    let text = (!response.text.is_empty()).then(|| ChatContentBlock::Text {
+        text: response.text.clone(),
+    });
+    let calls = response
+        .tool_calls
+        .iter()
+        .map(|call| ChatContentBlock::ToolUse {
+            tool_use_id: call.id.clone(),
+            tool_name: call.name.clone(),
+            tool_input: call.input.clone(),
+            output: None,
+            is_error: None,
+        });
+    ChatMessage::with_blocks(
+        MessageRole::Assistant,
+        text.into_iter().chain(calls).collect(),
+    )
+}
+
+/// Fills in the outputs of the last message's tool calls from `results`.
+fn attach_results(messages: &mut [ChatMessage], results: &[HeadlessToolResult]) {
+    let Some(message) = messages.last_mut() else {
+        return;
+    };
+    for block in &mut message.content_blocks {
+        if let ChatContentBlock::ToolUse {
+            tool_use_id,
+            output,
+            is_error,
+            ..
+        } = block
+        {
+            if let Some(result) = results.iter().find(|r| r.id == *tool_use_id) {
+                *output = Some(result.output.clone());
+                *is_error = Some(result.is_error);
+            }
+        }
+    }
+}
@@ -0,0 +1,483 @@
+//! Record-and-replay tests of the headless adapter path against real provider
+//! SSE streams.
+//!
+//! Each test talks to a local cassette server through `agent.base_url`. By
+//! default the server replays `fixtures/cassettes/<name>.json` offline, writing
+//! each response in small pieces so events straddle network reads, and checks
+//! every request against the recorded one. With `DEVFLOW_VCR_RECORD=1` it
+//! forwards requests to the real API using the provider's default API key
+//! variable and rewrites the cassette from the responses.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_util::sync::CancellationToken;
+
+use crate::agent::provider::{ExecutionContext, ProviderAdapter};
+use crate::agent::tools::{get_tool_definitions, SessionState};
+use crate::agent::types::{ChatMessage, MessageRole, ToolDefinition};
+use crate::agent::usage::SessionUsageTracker;
+use crate::config::commands::config_get_providers;
+use crate::config::ProjectConfig;
+
+use super::{AnthropicAdapter, GeminiAdapter, DEFAULT_SYSTEM_PROMPT};
+
+const RECORD_ENV: &str = "DEVFLOW_VCR_RECORD";
+const API_KEY_ENV: &str = "DEVFLOW_VCR_API_KEY";
+/// Replayed responses are written this many bytes at a time
+const REPLAY_CHUNK_BYTES: usize = 16;
+
+/// One request and the response it got.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    /// Request target with the API key query parameter redacted
+    target: String,
+    request: Value,
+    status: u16,
+    content_type: String,
+    body: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+enum Mode {
+    Replay(Vec<Interaction>),
+    Record { upstream: String },
+}
+
+#[derive(Default)]
+struct ServerState {
+    served: usize,
+    recorded: Vec<Interaction>,
+    mismatches: Vec<String>,
+}
+
+struct VcrServer {
+    name: String,
+    base_url: String,
+    recording: bool,
+    expected: usize,
+    state: Arc<Mutex<ServerState>>,
+}
+
+fn recording() -> bool {
+    std::env::var(RECORD_ENV).is_ok_and(|v| v == "1")
+}
+
+fn cassette_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join("cassettes")
+        .join(format!("{}.json", name))
+}
+
+impl VcrServer {
+    async fn start(name: &str, upstream: &str) -> Self {
+        let mode = if recording() {
+            Mode::Record {
+                upstream: upstream.trim_end_matches('/').to_string(),
+            }
+        } else {
+            let content = std::fs::read_to_string(cassette_path(name))
+                .unwrap_or_else(|e| panic!("cassette '{}': {}", name, e));
+            let cassette: Cassette = serde_json::from_str(&content).expect("valid cassette");
+            Mode::Replay(cassette.interactions)
+        };
+        let recording = matches!(mode, Mode::Record { .. });
+        let expected = match &mode {
+            Mode::Replay(interactions) => interactions.len(),
+            Mode::Record { .. } => 0,
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(ServerState::default()));
+
+        let mode = Arc::new(mode);
+        let server_state = Arc::clone(&state);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let mode = Arc::clone(&mode);
+                let state = Arc::clone(&server_state);
+                tokio::spawn(async move {
+                    if let Err(e) = serve(stream, &mode, &state).await {
+                        log::warn!("Cassette server failed to serve request: {}", e);
+                    }
+                });
+            }
+        });
+
+        Self {
+            name: name.to_string(),
+            base_url,
+            recording,
+            expected,
+            state,
+        }
+    }
+
+    /// Writes the cassette when recording; otherwise asserts that every
+    /// recorded interaction was replayed and matched.
+    fn finish(self) {
+        let state = self.state.lock().unwrap();
+        if self.recording {
+            let cassette = Cassette {
+                interactions: state.recorded.clone(),
+            };
+            let path = cassette_path(&self.name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(
+                path,
+                serde_json::to_string_pretty(&cassette).unwrap() + "\n",
+            )
+            .unwrap();
+            return;
+        }
+
+        assert!(
+            state.mismatches.is_empty(),
+            "requests differ from cassette '{}': {:#?}",
+            self.name,
+            state.mismatches
+        );
+        assert_eq!(
+            state.served, self.expected,
+            "cassette '{}' was not fully replayed",
+            self.name
+        );
+    }
+}
+
+struct IncomingRequest {
+    target: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+async fn read_request(stream: &mut TcpStream) -> std::io::Result<IncomingRequest> {
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let target = request_line
+        .split_whitespace()
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).await?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+
+    Ok(IncomingRequest {
+        target,
+        headers,
+        body,
+    })
+}
+
+async fn serve(
+    mut stream: TcpStream,
+    mode: &Mode,
+    state: &Mutex<ServerState>,
+) -> std::io::Result<()> {
+    let request = read_request(&mut stream).await?;
+    let target = redact_target(&request.target);
+    let body: Value = serde_json::from_slice(&request.body).unwrap_or(Value::Null);
+
+    let interaction = match mode {
+        Mode::Replay(interactions) => {
+            let mut state = state.lock().unwrap();
+            let index = state.served;
+            state.served += 1;
+            match interactions.get(index) {
+                Some(recorded) => {
+                    if let Err(mismatch) = match_request(recorded, &target, &body) {
+                        state
+                            .mismatches
+                            .push(format!("request {}: {}", index, mismatch));
+                    }
+                    recorded.clone()
+                }
+                None => {
+                    state
+                        .mismatches
+                        .push(format!("unexpected request {} to {}", index, target));
+                    Interaction {
+                        target,
+                        request: body,
+                        status: 500,
+                        content_type: "application/json".to_string(),
+                        body: r#"{"error":{"message":"cassette exhausted"}}"#.to_string(),
+                    }
+                }
+            }
+        }
+        Mode::Record { upstream } => {
+            let interaction = forward(upstream, &request, target, body).await;
+            state.lock().unwrap().recorded.push(interaction.clone());
+            interaction
+        }
+    };
+
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {} Status\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                interaction.status,
+                interaction.content_type,
+                interaction.body.len()
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+    // Small writes with yields in between, so SSE events arrive split
+    for chunk in interaction.body.as_bytes().chunks(REPLAY_CHUNK_BYTES) {
+        stream.write_all(chunk).await?;
+        stream.flush().await?;
+        tokio::task::yield_now().await;
+    }
+    stream.shutdown().await
+}
+
+async fn forward(
+    upstream: &str,
+    request: &IncomingRequest,
+    target: String,
+    body: Value,
+) -> Interaction {
+    let mut builder = reqwest::Client::new()
+        .post(format!("{}{}", upstream, request.target))
+        .body(request.body.clone());
+    for (name, value) in &request.headers {
+        let skipped = ["host", "content-length", "connection", "accept-encoding"];
+        if !skipped.iter().any(|s| name.eq_ignore_ascii_case(s)) {
+            builder = builder.header(name, value);
+        }
+    }
+
+    let (status, content_type, response_body) = match builder.send().await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("text/event-stream")
+                .to_string();
+            (
+                status,
+                content_type,
+                response.text().await.unwrap_or_default(),
+            )
+        }
+        Err(e) => (
+            502,
+            "application/json".to_string(),
+            serde_json::json!({"error": {"message": e.to_string()}}).to_string(),
+        ),
+    };
+
+    Interaction {
+        target,
+        request: body,
+        status,
+        content_type,
+        body: response_body,
+    }
+}
+
+/// Replaces the value of a `key=` query parameter, as sent by the Gemini adapter.
+fn redact_target(target: &str) -> String {
+    let Some((path, query)) = target.split_once('?') else {
+        return target.to_string();
+    };
+    let query: Vec<String> = query
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some(("key", _)) => "key=REDACTED".to_string(),
+            _ => pair.to_string(),
+        })
+        .collect();
+    format!("{}?{}", path, query.join("&"))
+}
+
+/// Role of each conversation turn in an Anthropic or Gemini request body.
+fn conversation_roles(body: &Value) -> Vec<String> {
+    body.get("messages")
+        .or_else(|| body.get("contents"))
+        .and_then(Value::as_array)
+        .map(|turns| {
+            turns
+                .iter()
+                .map(|t| t["role"].as_str().unwrap_or_default().to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A replayed request must go to the same endpoint with the same conversation
+/// shape. Prompt text and tool results can differ between machines, so only
+/// the turn roles are compared.
+fn match_request(recorded: &Interaction, target: &str, body: &Value) -> Result<(), String> {
+    if recorded.target != target {
+        return Err(format!("target {} != recorded {}", target, recorded.target));
+    }
+    let (roles, recorded_roles) = (
+        conversation_roles(body),
+        conversation_roles(&recorded.request),
+    );
+    if roles != recorded_roles {
+        return Err(format!(
+            "roles {:?} != recorded {:?}",
+            roles, recorded_roles
+        ));
+    }
+    Ok(())
+}
+
+/// Config pointed at the cassette server. Replays use a dummy key; recordings
+/// use the provider's default key variable.
+fn project_config(provider: &str, model: &str, base_url: &str) -> ProjectConfig {
+    let api_key_env = if recording() {
+        config_get_providers()
+            .into_iter()
+            .find(|p| p.id == provider)
+            .map(|p| p.default_api_key_env)
+            .unwrap()
+    } else {
+        std::env::set_var(API_KEY_ENV, "vcr-test-key");
+        API_KEY_ENV.to_string()
+    };
+
+    toml::from_str(&format!(
+        r#"
+[agent]
+provider = "{provider}"
+model = "{model}"
+api_key_env = "{api_key_env}"
+max_tokens = 1024
+base_url = "{base_url}"
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 5
+"#
+    ))
+    .expect("valid test config")
+}
+
+fn project_with_file() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("hello.txt"), "hello world\n").unwrap();
+    dir
+}
+
+fn execution_context() -> ExecutionContext {
+    ExecutionContext {
+        session: SessionState::new(),
+        cancel_token: CancellationToken::new(),
+        usage_tracker: Arc::new(SessionUsageTracker::new()),
+    }
+}
+
+fn prompt() -> Vec<ChatMessage> {
+    vec![ChatMessage::new(
+        MessageRole::User,
+        "Use read_file to read hello.txt, then tell me what it says in one sentence.".to_string(),
+    )]
+}
+
+fn read_file_tool() -> Vec<ToolDefinition> {
+    get_tool_definitions()
+        .into_iter()
+        .filter(|t| t.name == "read_file")
+        .collect()
+}
+
+#[tokio::test]
+async fn test_anthropic_headless_tool_loop_cassette() {
+    let server = VcrServer::start("anthropic_read_file", "https://api.anthropic.com").await;
+    let project = project_with_file();
+    let adapter = AnthropicAdapter::new(
+        project_config("anthropic", "claude-3-5-haiku-20241022", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+    let ctx = execution_context();
+    let usage_tracker = Arc::clone(&ctx.usage_tracker);
+
+    let result = adapter
+        .run_headless(prompt(), None, None, read_file_tool(), None, ctx)
+        .await
+        .unwrap();
+
+    assert_eq!(result.tool_calls_made, 1);
+    assert_eq!(result.stop_reason.as_deref(), Some("end_turn"));
+    assert!(result.text.to_lowercase().contains("hello world"));
+    assert!(usage_tracker.get_totals().input_tokens > 0);
+    server.finish();
+}
+
+#[tokio::test]
+async fn test_gemini_headless_tool_loop_cassette() {
+    let server = VcrServer::start(
+        "gemini_read_file",
+        "https://generativelanguage.googleapis.com",
+    )
+    .await;
+    let project = project_with_file();
+    let adapter = GeminiAdapter::new(
+        project_config("gemini", "gemini-2.0-flash", &server.base_url),
+        project.path().to_path_buf(),
+        DEFAULT_SYSTEM_PROMPT,
+    )
+    .unwrap();
+    let ctx = execution_context();
+    let usage_tracker = Arc::clone(&ctx.usage_tracker);
+
+    let result = adapter
+        .run_headless(prompt(), None, None, read_file_tool(), None, ctx)
+        .await
+        .unwrap();
+
+    assert_eq!(result.tool_calls_made, 1);
+    assert!(result.text.to_lowercase().contains("hello world"));
+    assert!(usage_tracker.get_totals().input_tokens > 0);
+    server.finish();
+}
+
+#[test]
+fn test_redact_target() {
+    assert_eq!(
+        redact_target("/v1beta/models/m:streamGenerateContent?alt=sse&key=secret"),
+        "/v1beta/models/m:streamGenerateContent?alt=sse&key=REDACTED"
+    );
+    assert_eq!(redact_target("/v1/messages"), "/v1/messages");
+}
@@ -0,0 +1,302 @@
+//! Process-wide token-bucket rate limiting of provider requests.
+//!
+//! Every session talking to the same provider shares one limiter, so parallel
+//! sessions and sub-agents draw from a single requests/minute and
+//! tokens/minute budget. Token counts are not known until a response arrives:
+//! the request is admitted against an estimate, and `settle` corrects the
+//! bucket with the real usage afterwards, which may leave it in debt.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::agent::error::AgentError;
+use crate::agent::usage::TokenUsage;
+use crate::config::RateLimitConfig;
+
+static LIMITERS: Lazy<Mutex<HashMap<String, Arc<RateLimiter>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the limiter shared by all requests to `provider`, or None when
+/// `config` sets no limits. A changed config updates the shared limiter.
+pub(crate) fn shared_rate_limiter(
+    provider: &str,
+    config: &RateLimitConfig,
+) -> Option<Arc<RateLimiter>> {
+    let mut limiters = LIMITERS.lock().unwrap_or_else(|e| e.into_inner());
+    if config.requests_per_minute.is_none() && config.tokens_per_minute.is_none() {
+        limiters.remove(provider);
+        return None;
+    }
+
+    let limiter = limiters
+        .entry(provider.to_string())
+        .or_insert_with(|| Arc::new(RateLimiter::new(config)));
+    limiter.update(config);
+    Some(Arc::clone(limiter))
+}
+
+/// Waits for `limiter` if there is one; see `RateLimiter::acquire`.
+pub(crate) async fn acquire(
+    limiter: Option<&RateLimiter>,
+    estimated_tokens: u32,
+    cancel_token: &CancellationToken,
+    on_wait: impl FnMut(Duration),
+) -> Result<(), AgentError> {
+    match limiter {
+        Some(limiter) => {
+            limiter
+                .acquire(estimated_tokens, cancel_token, on_wait)
+                .await
+        }
+        None => Ok(()),
+    }
+}
+
+/// Charges a finished request's real usage to `limiter`, if there is one.
+pub(crate) fn settle(limiter: Option<&RateLimiter>, estimated_tokens: u32, usage: TokenUsage) {
+    if let Some(limiter) = limiter {
+        limiter.settle(estimated_tokens, usage.input_tokens + usage.output_tokens);
+    }
+}
+
+/// Rough token count of a request: four bytes of JSON per token.
+pub(crate) fn estimate_request_tokens<T: Serialize>(request: &T) -> u32 {
+    serde_json::to_vec(request)
+        .map(|body| (body.len() / 4) as u32)
+        .unwrap_or(0)
+}
+
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    available: f64,
+    per_sec: f64,
+}
+
+impl Bucket {
+    fn new(per_minute: u32) -> Self {
+        Self {
+            capacity: per_minute as f64,
+            available: per_minute as f64,
+            per_sec: per_minute as f64 / 60.0,
+        }
+    }
+
+    fn refill(&mut self, elapsed: Duration) {
+        self.available = (self.available + elapsed.as_secs_f64() * self.per_sec).min(self.capacity);
+    }
+
+    /// Time until `amount` is available; zero if it already is. Amounts above
+    /// the capacity wait for a full bucket rather than forever.
+    fn wait_for(&self, amount: f64) -> Duration {
+        let missing = amount.min(self.capacity) - self.available;
+        if missing <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(missing / self.per_sec)
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+    refilled_at: Instant,
+    config: RateLimitConfig,
+}
+
+impl Buckets {
+    fn new(config: &RateLimitConfig, now: Instant) -> Self {
+        Self {
+            requests: config
+                .requests_per_minute
+                .filter(|&n| n > 0)
+                .map(Bucket::new),
+            tokens: config.tokens_per_minute.filter(|&n| n > 0).map(Bucket::new),
+            refilled_at: now,
+            config: config.clone(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled_at);
+        self.refilled_at = now;
+        for bucket in [&mut self.requests, &mut self.tokens].into_iter().flatten() {
+            bucket.refill(elapsed);
+        }
+    }
+
+    /// Takes one request and `tokens` if both are available, otherwise
+    /// returns how long to wait before trying again.
+    fn try_take(&mut self, tokens: u32, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        let wait = [
+            self.requests.as_ref().map(|b| b.wait_for(1.0)),
+            self.tokens.as_ref().map(|b| b.wait_for(tokens as f64)),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+        .unwrap_or(Duration::ZERO);
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        if let Some(bucket) = &mut self.requests {
+            bucket.available -= 1.0;
+        }
+        if let Some(bucket) = &mut self.tokens {
+            bucket.available -= (tokens as f64).min(bucket.capacity);
+        }
+        Ok(())
+    }
+}
+
+pub struct RateLimiter {
+    buckets: Mutex<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(config: &RateLimitConfig) -> Self {
+        Self {
+            buckets: Mutex::new(Buckets::new(config, Instant::now())),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Buckets> {
+        self.buckets.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, config: &RateLimitConfig) {
+        let mut buckets = self.lock();
+        if buckets.config != *config {
+            *buckets = Buckets::new(config, Instant::now());
+        }
+    }
+
+    /// Waits until a request estimated at `estimated_tokens` fits in the
+    /// budget, calling `on_wait` with the delay each time it has to queue.
+    pub async fn acquire(
+        &self,
+        estimated_tokens: u32,
+        cancel_token: &CancellationToken,
+        mut on_wait: impl FnMut(Duration),
+    ) -> Result<(), AgentError> {
+        loop {
+            let result = self.lock().try_take(estimated_tokens, Instant::now());
+            let Err(wait) = result else {
+                return Ok(());
+            };
+
+            on_wait(wait);
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = cancel_token.cancelled() => return Err(AgentError::Cancelled),
+            }
+        }
+    }
+
+    /// Corrects the token bucket once the real usage of a request admitted
+    /// with `estimated_tokens` is known.
+    pub fn settle(&self, estimated_tokens: u32, actual_tokens: u32) {
+        let mut buckets = self.lock();
+        if let Some(bucket) = &mut buckets.tokens {
+            let correction = actual_tokens as f64 - estimated_tokens as f64;
+            bucket.available = (bucket.available - correction).min(bucket.capacity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(requests: Option<u32>, tokens: Option<u32>) -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_minute: requests,
+            tokens_per_minute: tokens,
+        }
+    }
+
+    #[test]
+    fn test_request_bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut buckets = Buckets::new(&config(Some(2), None), start);
+
+        assert!(buckets.try_take(0, start).is_ok());
+        assert!(buckets.try_take(0, start).is_ok());
+        let wait = buckets.try_take(0, start).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(30));
+
+        assert!(buckets
+            .try_take(0, start + Duration::from_secs(29))
+            .is_err());
+        assert!(buckets.try_take(0, start + Duration::from_secs(31)).is_ok());
+    }
+
+    #[test]
+    fn test_token_bucket_waits_for_largest_shortfall() {
+        let start = Instant::now();
+        let mut buckets = Buckets::new(&config(Some(100), Some(600)), start);
+
+        assert!(buckets.try_take(500, start).is_ok());
+        // 100 tokens left, 400 more needed at 10 per second
+        assert_eq!(
+            buckets.try_take(500, start).unwrap_err(),
+            Duration::from_secs(40)
+        );
+
+        // A request larger than the whole budget waits for a full bucket
+        let later = start + Duration::from_secs(60);
+        assert!(buckets.try_take(10_000, later).is_ok());
+    }
+
+    #[test]
+    fn test_settle_charges_actual_usage() {
+        let limiter = RateLimiter::new(&config(None, Some(600)));
+        assert!(limiter.lock().try_take(100, Instant::now()).is_ok());
+
+        // Response used 400 tokens more than estimated
+        limiter.settle(100, 500);
+        let available = limiter.lock().tokens.as_ref().unwrap().available;
+        assert!((available - 100.0).abs() < 1.0);
+
+        // Overestimates are refunded, never beyond capacity
+        limiter.settle(10_000, 0);
+        assert_eq!(limiter.lock().tokens.as_ref().unwrap().available, 600.0);
+    }
+
+    #[test]
+    fn test_shared_limiter_per_provider() {
+        let limits = config(Some(60), None);
+        let a = shared_rate_limiter("test-provider-a", &limits).unwrap();
+        let b = shared_rate_limiter("test-provider-a", &limits).unwrap();
+        assert!(Arc::ptr_eq(&a, &b));
+
+        let other = shared_rate_limiter("test-provider-b", &limits).unwrap();
+        assert!(!Arc::ptr_eq(&a, &other));
+
+        assert!(shared_rate_limiter("test-provider-a", &config(None, None)).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_queues_and_cancels() {
+        let limiter = RateLimiter::new(&config(Some(1), None));
+        let token = CancellationToken::new();
+        let mut waits = Vec::new();
+
+        limiter.acquire(0, &token, |w| waits.push(w)).await.unwrap();
+        assert!(waits.is_empty());
+
+        token.cancel();
+        let result = limiter.acquire(0, &token, |w| waits.push(w)).await;
+        assert!(matches!(result, Err(AgentError::Cancelled)));
+        assert_eq!(waits.len(), 1);
+    }
+}
@@ -0,0 +1,260 @@
+//! Process-wide cache of headless provider responses.
+//!
+//! Background calls such as session titles, compaction extraction and
+//! sub-agent steps are often repeated with exactly the same request. When
+//! `[request_cache]` is enabled, their responses are kept for a while, keyed
+//! by a hash of the canonical request JSON, and a repeated request is
+//! answered from the cache without reaching the API. Interactive turns never
+//! use it.
+
+use std::any::Any;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::config::RequestCacheConfig;
+
+static CACHES: Lazy<Mutex<HashMap<String, Arc<RequestCache>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Returns the cache shared by all requests to `provider`, or None when
+/// `config` leaves caching off. A changed config updates the shared cache.
+pub(crate) fn shared_request_cache(
+    provider: &str,
+    config: &RequestCacheConfig,
+) -> Option<Arc<RequestCache>> {
+    let mut caches = CACHES.lock().unwrap_or_else(|e| e.into_inner());
+    if !config.enabled || config.ttl_secs == 0 || config.max_entries == 0 {
+        caches.remove(provider);
+        return None;
+    }
+
+    let cache = caches
+        .entry(provider.to_string())
+        .or_insert_with(|| Arc::new(RequestCache::new(config)));
+    cache.update(config);
+    Some(Arc::clone(cache))
+}
+
+/// Key of `request` in `cache`; None when there is no cache.
+pub(crate) fn cache_key<T: Serialize>(
+    cache: Option<&RequestCache>,
+    request: &T,
+) -> Option<[u8; 32]> {
+    cache.and_then(|_| request_key(request))
+}
+
+/// The SHA-256 of a request's JSON with object keys sorted, so maps that
+/// serialize in a different order still hit. None if the request cannot be
+/// serialized.
+fn request_key<T: Serialize>(request: &T) -> Option<[u8; 32]> {
+    let value = serde_json::to_value(request).ok()?;
+    let body = serde_json::to_vec(&canonical(value)).ok()?;
+    Some(Sha256::digest(&body).into())
+}
+
+fn canonical(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, canonical(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonical).collect()),
+        other => other,
+    }
+}
+
+struct Entry {
+    stored_at: Instant,
+    response: Arc<dyn Any + Send + Sync>,
+}
+
+struct Entries {
+    ttl: Duration,
+    max_entries: usize,
+    config: RequestCacheConfig,
+    map: HashMap<[u8; 32], Entry>,
+}
+
+impl Entries {
+    fn new(config: &RequestCacheConfig) -> Self {
+        Self {
+            ttl: Duration::from_secs(config.ttl_secs),
+            max_entries: config.max_entries as usize,
+            config: config.clone(),
+            map: HashMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &[u8; 32], now: Instant) -> Option<Arc<dyn Any + Send + Sync>> {
+        let entry = self.map.get(key)?;
+        if now.saturating_duration_since(entry.stored_at) >= self.ttl {
+            self.map.remove(key);
+            return None;
+        }
+        Some(Arc::clone(&entry.response))
+    }
+
+    fn insert(&mut self, key: [u8; 32], response: Arc<dyn Any + Send + Sync>, now: Instant) {
+        let ttl = self.ttl;
+        self.map
+            .retain(|_, entry| now.saturating_duration_since(entry.stored_at) < ttl);
+        while self.map.len() >= self.max_entries && !self.map.contains_key(&key) {
+            let Some(oldest) = self
+                .map
+                .iter()
+                .min_by_key(|(_, entry)| entry.stored_at)
+                .map(|(key, _)| *key)
+            else {
+                break;
+            };
+            self.map.remove(&oldest);
+        }
+        self.map.insert(
+            key,
+            Entry {
+                stored_at: now,
+                response,
+            },
+        );
+    }
+}
+
+pub struct RequestCache {
+    entries: Mutex<Entries>,
+}
+
+impl RequestCache {
+    pub fn new(config: &RequestCacheConfig) -> Self {
+        Self {
+            entries: Mutex::new(Entries::new(config)),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, config: &RequestCacheConfig) {
+        let mut entries = self.lock();
+        if entries.config != *config {
+            *entries = Entries::new(config);
+        }
+    }
+
+    /// The response stored for `key`, if it is still fresh and of type `T`.
+    pub fn get<T: Clone + 'static>(&self, key: &[u8; 32]) -> Option<T> {
+        let response = self.lock().get(key, Instant::now())?;
+        response.downcast_ref::<T>().cloned()
+    }
+
+    /// Stores `response` for `key`, making room by dropping expired entries
+    /// and then the oldest ones.
+    pub fn insert<T: Send + Sync + 'static>(&self, key: [u8; 32], response: T) {
+        self.lock().insert(key, Arc::new(response), Instant::now());
+    }
+}
+
+/// Looks `key` up in `cache`, if there is one.
+pub(crate) fn lookup<T: Clone + 'static>(
+    cache: Option<&RequestCache>,
+    key: Option<&[u8; 32]>,
+) -> Option<T> {
+    cache?.get(key?)
+}
+
+/// Stores `response` under `key` in `cache`, if there is one.
+pub(crate) fn store<T: Clone + Send + Sync + 'static>(
+    cache: Option<&RequestCache>,
+    key: Option<[u8; 32]>,
+    response: &T,
+) {
+    if let (Some(cache), Some(key)) = (cache, key) {
+        cache.insert(key, response.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(ttl_secs: u64, max_entries: u32) -> RequestCacheConfig {
+        RequestCacheConfig {
+            enabled: true,
+            ttl_secs,
+            max_entries,
+        }
+    }
+
+    fn response(text: &str) -> Arc<dyn Any + Send + Sync> {
+        Arc::new(text.to_string())
+    }
+
+    #[test]
+    fn test_request_key_ignores_key_order() {
+        let a = json!({"model": "m", "tools": [{"schema": {"a": 1, "b": 2}}]});
+        let b = json!({"tools": [{"schema": {"b": 2, "a": 1}}], "model": "m"});
+        assert_eq!(request_key(&a), request_key(&b));
+
+        let other = json!({"model": "m", "tools": [{"schema": {"a": 1, "b": 3}}]});
+        assert_ne!(request_key(&a), request_key(&other));
+    }
+
+    #[test]
+    fn test_entries_expire_after_ttl() {
+        let start = Instant::now();
+        let mut entries = Entries::new(&config(60, 10));
+        entries.insert([1; 32], response("title"), start);
+
+        let hit = entries
+            .get(&[1; 32], start + Duration::from_secs(59))
+            .unwrap();
+        assert_eq!(hit.downcast_ref::<String>().unwrap(), "title");
+        assert!(entries
+            .get(&[1; 32], start + Duration::from_secs(60))
+            .is_none());
+        assert!(entries.map.is_empty());
+    }
+
+    #[test]
+    fn test_insert_evicts_oldest_entry() {
+        let start = Instant::now();
+        let mut entries = Entries::new(&config(600, 2));
+        entries.insert([1; 32], response("a"), start);
+        entries.insert([2; 32], response("b"), start + Duration::from_secs(1));
+        entries.insert([3; 32], response("c"), start + Duration::from_secs(2));
+
+        let now = start + Duration::from_secs(3);
+        assert!(entries.get(&[1; 32], now).is_none());
+        assert!(entries.get(&[2; 32], now).is_some());
+        assert!(entries.get(&[3; 32], now).is_some());
+    }
+
+    #[test]
+    fn test_shared_cache_per_provider() {
+        let cache = shared_request_cache("test-cache-a", &config(60, 10)).unwrap();
+        cache.insert([7; 32], "summary".to_string());
+
+        let same = shared_request_cache("test-cache-a", &config(60, 10)).unwrap();
+        assert_eq!(same.get::<String>(&[7; 32]).as_deref(), Some("summary"));
+        assert_eq!(same.get::<u32>(&[7; 32]), None);
+
+        let disabled = RequestCacheConfig {
+            enabled: false,
+            ..config(60, 10)
+        };
+        assert!(shared_request_cache("test-cache-a", &disabled).is_none());
+    }
+}
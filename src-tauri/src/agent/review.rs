@@ -0,0 +1,454 @@
+//! Headless review pipelines: run a review agent over a git diff and return
+//! structured results the diff viewer can render as annotations.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+use ts_rs::TS;
+
+use crate::agent::error::AgentError;
+use crate::agent::tools::{run_subagent, SubagentParams};
+use crate::agent::usage::SessionUsageTracker;
+use crate::git::{GitError, GitService};
+
+/// Diffs larger than this are cut off before being sent to the model.
+const MAX_DIFF_CHARS: usize = 200_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum FindingSeverity {
+    Critical,
+    High,
+    Medium,
+    Low,
+}
+
+/// A single issue reported by the security review agent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SecurityFinding {
+    pub severity: FindingSeverity,
+    /// Path relative to the repository root
+    pub file: String,
+    /// Line in the new version of the file, when the finding has one
+    #[serde(default)]
+    pub line: Option<u32>,
+    pub description: String,
+    #[serde(default)]
+    pub remediation: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SecurityReport {
+    findings: Vec<SecurityFinding>,
+}
+
+/// A draft inline comment from the PR review agent, anchored to a line in the
+/// new version of a file and ready to post as a review comment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ReviewCommentDraft {
+    /// Path relative to the repository root
+    pub file: String,
+    pub line: u32,
+    /// First line when the comment spans a range ending at `line`
+    #[serde(default)]
+    pub start_line: Option<u32>,
+    pub body: String,
+}
+
+#[derive(Deserialize)]
+struct PrReviewReport {
+    comments: Vec<ReviewCommentDraft>,
+}
+
+fn findings_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "findings": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "severity": {
+                            "type": "string",
+                            "enum": ["critical", "high", "medium", "low"]
+                        },
+                        "file": {"type": "string"},
+                        "line": {"type": "integer"},
+                        "description": {"type": "string"},
+                        "remediation": {"type": "string"}
+                    },
+                    "required": ["severity", "file", "description"]
+                }
+            }
+        },
+        "required": ["findings"]
+    })
+}
+
+fn comments_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "comments": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "file": {"type": "string"},
+                        "line": {"type": "integer"},
+                        "start_line": {"type": "integer"},
+                        "body": {"type": "string"}
+                    },
+                    "required": ["file", "line", "body"]
+                }
+            }
+        },
+        "required": ["comments"]
+    })
+}
+
+/// Wraps the diff in review instructions, truncating it if needed.
+fn build_review_task(instructions: &str, diff: &str) -> String {
+    let (diff, truncated) = match diff.char_indices().nth(MAX_DIFF_CHARS) {
+        Some((cut, _)) => (&diff[..cut], true),
+        None => (diff, false),
+    };
+
+    let mut task = format!("{}\n\n```diff\n{}", instructions, diff);
+    if !task.ends_with('\n') {
+        task.push('\n');
+    }
+    task.push_str("```\n");
+    if truncated {
+        task.push_str(
+            "\nThe diff was truncated. Use read_file to inspect files beyond this point.\n",
+        );
+    }
+    task
+}
+
+fn parse_findings(value: Value) -> Result<Vec<SecurityFinding>, AgentError> {
+    let report: SecurityReport = serde_json::from_value(value)
+        .map_err(|e| AgentError::InvalidStructuredOutput(e.to_string()))?;
+    let mut findings = report.findings;
+    findings.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then_with(|| a.file.cmp(&b.file))
+            .then_with(|| a.line.cmp(&b.line))
+    });
+    Ok(findings)
+}
+
+fn parse_comments(value: Value) -> Result<Vec<ReviewCommentDraft>, AgentError> {
+    let report: PrReviewReport = serde_json::from_value(value)
+        .map_err(|e| AgentError::InvalidStructuredOutput(e.to_string()))?;
+    let mut comments: Vec<ReviewCommentDraft> = report
+        .comments
+        .into_iter()
+        .filter(|c| c.line > 0 && !c.body.trim().is_empty())
+        .map(|mut c| {
+            c.start_line = c.start_line.filter(|&start| start > 0 && start < c.line);
+            c
+        })
+        .collect();
+    comments.sort_by(|a, b| a.file.cmp(&b.file).then_with(|| a.line.cmp(&b.line)));
+    Ok(comments)
+}
+
+/// Runs a review agent over `diff` and returns its structured answer, or None
+/// without calling the agent when there are no changes.
+async fn run_review(
+    project_path: &Path,
+    diff: &str,
+    agent_type_id: &str,
+    instructions: &str,
+    schema: Value,
+    cancel_token: &CancellationToken,
+    usage_tracker: Arc<SessionUsageTracker>,
+) -> Result<Option<Value>, AgentError> {
+    if diff.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let task = build_review_task(instructions, diff);
+    let result = run_subagent(SubagentParams {
+        project_path,
+        task: &task,
+        agent_type_id: Some(agent_type_id),
+        allowed_tools: None,
+        max_depth: 1,
+        current_depth: 0,
+        parent_token: cancel_token,
+        usage_tracker,
+        response_schema: Some(schema),
+        variables: HashMap::new(),
+        dry_run: false,
+        auto_approve: None,
+    })
+    .await?;
+
+    result.structured.map(Some).ok_or_else(|| {
+        AgentError::InvalidStructuredOutput(format!("'{}' returned no result", agent_type_id))
+    })
+}
+
+/// Runs the `security-review` agent over the working tree changes, or over
+/// `range` (e.g. `main...HEAD`) when given. Returns findings ordered by severity.
+pub async fn run_security_review(
+    project_path: &Path,
+    range: Option<&str>,
+    cancel_token: &CancellationToken,
+    usage_tracker: Arc<SessionUsageTracker>,
+) -> Result<Vec<SecurityFinding>, AgentError> {
+    let diff = GitService::open(project_path)?.diff_text(range, &[])?;
+    let structured = run_review(
+        project_path,
+        &diff,
+        "security-review",
+        "Review the following changes for security vulnerabilities. Report each \
+         finding with the file path relative to the repository root and, where it \
+         applies, the line number in the new version of the file.",
+        findings_schema(),
+        cancel_token,
+        usage_tracker,
+    )
+    .await?;
+
+    match structured {
+        Some(value) => parse_findings(value),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Review instructions for the pr-review agent, noting when the review covers
+/// only part of the branch.
+fn pr_review_instructions(range: &str, files: &[String]) -> String {
+    let mut instructions = format!(
+        "Review the following pull request changes ({}). Instead of a written report, \
+         return inline review comments: each one names the file path relative to the \
+         repository root and the line in the new version of the file it refers to \
+         (plus start_line for multi-line ranges). Only comment on lines the diff \
+         adds or modifies.",
+        range
+    );
+    if !files.is_empty() {
+        instructions.push_str(&format!(
+            " This review covers only {}; the rest of the pull request is reviewed \
+             separately, so do not comment on other files.",
+            files.join(", ")
+        ));
+    }
+    instructions
+}
+
+/// Runs the `pr-review` agent over `range` when given (e.g. `abc123..def456`),
+/// otherwise over `base_ref...HEAD`, limited to `files` when any are given so
+/// large pull requests can be reviewed piecewise. Returns draft inline comments
+/// ordered by file and line.
+pub async fn run_pr_review(
+    project_path: &Path,
+    base_ref: Option<&str>,
+    range: Option<&str>,
+    files: &[String],
+    cancel_token: &CancellationToken,
+    usage_tracker: Arc<SessionUsageTracker>,
+) -> Result<Vec<ReviewCommentDraft>, AgentError> {
+    let range = match (range.map(str::trim), base_ref.map(str::trim)) {
+        (Some(range), _) if !range.is_empty() => range.to_string(),
+        (_, Some(base_ref)) if !base_ref.is_empty() => format!("{}...HEAD", base_ref),
+        _ => return Err(GitError::InvalidRevision(String::new()).into()),
+    };
+
+    let diff = GitService::open(project_path)?.diff_text(Some(&range), files)?;
+    let structured = run_review(
+        project_path,
+        &diff,
+        "pr-review",
+        &pr_review_instructions(&range, files),
+        comments_schema(),
+        cancel_token,
+        usage_tracker,
+    )
+    .await?;
+
+    match structured {
+        Some(value) => parse_comments(value),
+        None => Ok(Vec::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::structured::validate;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_findings_orders_by_severity() {
+        let value = json!({
+            "findings": [
+                {"severity": "low", "file": "b.rs", "description": "Verbose error"},
+                {"severity": "critical", "file": "a.rs", "line": 12,
+                 "description": "Command injection", "remediation": "Quote the argument"},
+                {"severity": "low", "file": "a.rs", "line": 3, "description": "Debug log"}
+            ]
+        });
+        assert!(validate(&value, &findings_schema()).is_ok());
+
+        let findings = parse_findings(value).unwrap();
+        assert_eq!(findings[0].severity, FindingSeverity::Critical);
+        assert_eq!(findings[0].line, Some(12));
+        assert_eq!(
+            findings[0].remediation.as_deref(),
+            Some("Quote the argument")
+        );
+        assert_eq!(findings[1].file, "a.rs");
+        assert_eq!(findings[2].file, "b.rs");
+        assert_eq!(findings[2].line, None);
+    }
+
+    #[test]
+    fn test_parse_findings_rejects_unknown_severity() {
+        let value = json!({
+            "findings": [{"severity": "urgent", "file": "a.rs", "description": "x"}]
+        });
+        assert!(validate(&value, &findings_schema()).is_err());
+        assert!(matches!(
+            parse_findings(value),
+            Err(AgentError::InvalidStructuredOutput(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_comments_orders_and_drops_invalid() {
+        let value = json!({
+            "comments": [
+                {"file": "src/b.rs", "line": 4, "body": "Handle the error"},
+                {"file": "src/a.rs", "line": 20, "start_line": 18, "body": "Extract a helper"},
+                {"file": "src/a.rs", "line": 7, "start_line": 9, "body": "Typo"},
+                {"file": "src/a.rs", "line": 0, "body": "Whole file"},
+                {"file": "src/c.rs", "line": 1, "body": "  "}
+            ]
+        });
+        assert!(validate(&value, &comments_schema()).is_ok());
+
+        let comments = parse_comments(value).unwrap();
+        assert_eq!(comments.len(), 3);
+        assert_eq!(
+            (comments[0].file.as_str(), comments[0].line),
+            ("src/a.rs", 7)
+        );
+        // start_line after line is not a range
+        assert_eq!(comments[0].start_line, None);
+        assert_eq!(comments[1].start_line, Some(18));
+        assert_eq!(comments[2].file, "src/b.rs");
+    }
+
+    #[tokio::test]
+    async fn test_pr_review_rejects_empty_base_ref() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let result = run_pr_review(
+            temp_dir.path(),
+            Some("  "),
+            None,
+            &[],
+            &CancellationToken::new(),
+            Arc::new(SessionUsageTracker::new()),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(AgentError::Git(GitError::InvalidRevision(_)))
+        ));
+    }
+
+    #[test]
+    fn test_pr_review_instructions_note_partial_scope() {
+        let whole = pr_review_instructions("main...HEAD", &[]);
+        assert!(whole.contains("(main...HEAD)"));
+        assert!(!whole.contains("covers only"));
+
+        let files = ["src/a.rs".to_string(), "src/b.rs".to_string()];
+        let partial = pr_review_instructions("abc..def", &files);
+        assert!(partial.contains("covers only src/a.rs, src/b.rs"));
+    }
+
+    #[test]
+    fn test_build_review_task_fences_diff() {
+        let task = build_review_task("Review this.", "+let x = 1;");
+        assert_eq!(task, "Review this.\n\n```diff\n+let x = 1;\n```\n");
+
+        let long = "+".repeat(MAX_DIFF_CHARS + 10);
+        let task = build_review_task("Review this.", &long);
+        assert!(task.contains("The diff was truncated"));
+        assert!(task.len() < long.len() + 200);
+    }
+
+    #[tokio::test]
+    async fn test_no_changes_skip_the_agent() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
+        git(dir, &["init"]);
+        git(dir, &["config", "user.email", "test@test.com"]);
+        git(dir, &["config", "user.name", "Test"]);
+        std::fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-m", "Initial"]);
+
+        // No config and no API key: reaching the agent would fail
+        let findings = run_security_review(
+            dir,
+            None,
+            &CancellationToken::new(),
+            Arc::new(SessionUsageTracker::new()),
+        )
+        .await
+        .unwrap();
+        assert!(findings.is_empty());
+
+        let comments = run_pr_review(
+            dir,
+            Some("HEAD"),
+            None,
+            &[],
+            &CancellationToken::new(),
+            Arc::new(SessionUsageTracker::new()),
+        )
+        .await
+        .unwrap();
+        assert!(comments.is_empty());
+
+        // A range and file list with no changes in them skip the agent too
+        std::fs::write(dir.join("lib.rs"), "pub fn lib() {}\n").unwrap();
+        git(dir, &["add", "."]);
+        git(dir, &["commit", "-m", "Add lib"]);
+        let comments = run_pr_review(
+            dir,
+            None,
+            Some("HEAD~1..HEAD"),
+            &["main.rs".to_string()],
+            &CancellationToken::new(),
+            Arc::new(SessionUsageTracker::new()),
+        )
+        .await
+        .unwrap();
+        assert!(comments.is_empty());
+    }
+}
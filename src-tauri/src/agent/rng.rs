@@ -0,0 +1,131 @@
+//! Seedable randomness so jittered backoff, mock runs and eval sampling can be
+//! reproduced exactly from a logged seed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Environment variable that pins the seed for every run in the process.
+pub const SEED_ENV: &str = "DEVFLOW_SEED";
+
+/// Explicit seed if given, else `DEVFLOW_SEED`, else one derived from the clock.
+pub fn resolve_seed(explicit: Option<u64>) -> u64 {
+    if let Some(seed) = explicit {
+        return seed;
+    }
+
+    if let Some(seed) = std::env::var(SEED_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+    {
+        return seed;
+    }
+
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default();
+    let seed = SeededRng::new(nanos).next_u64();
+    log::debug!("Using random seed {} (set {} to reproduce)", seed, SEED_ENV);
+    seed
+}
+
+/// SplitMix64 generator. Not cryptographic; chosen because it is tiny and gives
+/// the same sequence on every platform and toolchain.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform in `[0, bound)`; returns 0 when `bound` is 0.
+    pub fn below(&mut self, bound: usize) -> usize {
+        if bound == 0 {
+            return 0;
+        }
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.next_f64() < probability
+    }
+
+    /// Fisher-Yates shuffle.
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = SeededRng::new(42);
+        let mut b = SeededRng::new(42);
+        let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        let second: Vec<u64> = (0..5).map(|_| b.next_u64()).collect();
+        assert_eq!(first, second);
+
+        let mut c = SeededRng::new(43);
+        assert_ne!(first[0], c.next_u64());
+    }
+
+    #[test]
+    fn test_known_sequence_is_stable() {
+        // Reference values for SplitMix64 seeded with 0
+        let mut rng = SeededRng::new(0);
+        assert_eq!(rng.next_u64(), 0xE220_A839_7B1D_CDAF);
+        assert_eq!(rng.next_u64(), 0x6E78_9E6A_A1B9_65F4);
+    }
+
+    #[test]
+    fn test_ranges() {
+        let mut rng = SeededRng::new(7);
+        for _ in 0..1000 {
+            let f = rng.next_f64();
+            assert!((0.0..1.0).contains(&f));
+            assert!(rng.below(3) < 3);
+        }
+        assert_eq!(rng.below(0), 0);
+        assert!(!rng.chance(0.0));
+        assert!(rng.chance(1.0));
+    }
+
+    #[test]
+    fn test_shuffle_is_deterministic_permutation() {
+        let mut a: Vec<u32> = (0..10).collect();
+        let mut b = a.clone();
+        SeededRng::new(1).shuffle(&mut a);
+        SeededRng::new(1).shuffle(&mut b);
+        assert_eq!(a, b);
+
+        let mut sorted = a.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..10).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_explicit_seed_wins() {
+        assert_eq!(resolve_seed(Some(99)), 99);
+    }
+}
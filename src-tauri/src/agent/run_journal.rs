@@ -0,0 +1,418 @@
+//! Journal of the interactive run in progress, kept under
+//! `.devflow/runs/<session>.json` while the tool loop runs and removed when
+//! it ends. The original of every file is saved before the agent first edits
+//! it, so a journal left behind by a crash can put the working tree back
+//! (`recover_last_run` with "restore") or hand the session's conversation
+//! back to continue from ("resume"). Changes made through bash are not tracked.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::conversation::Conversation;
+use super::tools::{resolve_in_dir, ArchivedPlan, FileSnapshot, TrackedTodo};
+use super::types::ChatMessage;
+
+const RUNS_DIR: &str = ".devflow/runs";
+
+/// A tool call the run was executing when the journal was last written.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalToolCall {
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+}
+
+/// What the journal file holds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalRecord {
+    pub session_id: String,
+    /// RFC 3339 UTC timestamp
+    pub started_at: String,
+    /// Process that wrote the journal; a journal of the running app belongs to a live run
+    pub pid: u32,
+    pub provider: String,
+    pub model: String,
+    /// Tool calls of the batch being executed
+    #[serde(default)]
+    pub pending_tool_calls: Vec<JournalToolCall>,
+    /// Project-relative paths of completed file edits, in order
+    #[serde(default)]
+    pub applied_edits: Vec<String>,
+    /// Originals of the files edited during the run
+    #[serde(default)]
+    pub checkpoints: Vec<FileSnapshot>,
+}
+
+/// Handle to the journal of a running run; updates are written through.
+#[derive(Clone)]
+pub struct RunJournal {
+    path: PathBuf,
+    record: Arc<Mutex<JournalRecord>>,
+}
+
+impl RunJournal {
+    /// Creates the journal for a run of `session_id`.
+    pub fn start(project_path: &Path, session_id: &str, provider: &str, model: &str) -> Self {
+        let journal = Self {
+            path: journal_path(project_path, session_id),
+            record: Arc::new(Mutex::new(JournalRecord {
+                session_id: session_id.to_string(),
+                started_at: Utc::now().to_rfc3339(),
+                pid: std::process::id(),
+                provider: provider.to_string(),
+                model: model.to_string(),
+                pending_tool_calls: Vec::new(),
+                applied_edits: Vec::new(),
+                checkpoints: Vec::new(),
+            })),
+        };
+        journal.update(|_| {});
+        journal
+    }
+
+    pub fn set_pending(&self, calls: Vec<JournalToolCall>) {
+        self.update(|record| record.pending_tool_calls = calls);
+    }
+
+    /// Saves a file's original; only the first snapshot of each file is kept.
+    pub fn record_checkpoint(&self, snapshot: &FileSnapshot) {
+        self.update(|record| {
+            if !record
+                .checkpoints
+                .iter()
+                .any(|checkpoint| checkpoint.absolute == snapshot.absolute)
+            {
+                record.checkpoints.push(snapshot.clone());
+            }
+        });
+    }
+
+    pub fn record_edit(&self, path: &str) {
+        self.update(|record| record.applied_edits.push(path.to_string()));
+    }
+
+    /// Removes the journal; the run ended without the app going down.
+    pub fn finish(self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                log::warn!("Failed to remove run journal: {}", e);
+            }
+        }
+    }
+
+    /// Best-effort; a journal that can't be written must never fail a run.
+    fn update(&self, change: impl FnOnce(&mut JournalRecord)) {
+        let Ok(mut record) = self.record.lock() else {
+            return;
+        };
+        change(&mut record);
+        if let Err(e) = write_record(&self.path, &record) {
+            log::warn!("Failed to write run journal: {}", e);
+        }
+    }
+}
+
+fn journal_path(project_path: &Path, session_id: &str) -> PathBuf {
+    project_path
+        .join(RUNS_DIR)
+        .join(format!("{}.json", session_id))
+}
+
+fn write_record(path: &Path, record: &JournalRecord) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content = serde_json::to_string_pretty(record)?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, content)?;
+    fs::rename(&temp, path)
+}
+
+/// The most recent journal left behind by an earlier app process.
+pub fn find_interrupted(project_path: &Path) -> Option<JournalRecord> {
+    let entries = fs::read_dir(project_path.join(RUNS_DIR)).ok()?;
+    entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .filter_map(|content| serde_json::from_str::<JournalRecord>(&content).ok())
+        .filter(|record| record.pid != std::process::id())
+        .max_by(|a, b| a.started_at.cmp(&b.started_at))
+}
+
+/// A run the app did not finish, offered for recovery on startup.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct InterruptedRun {
+    pub session_id: String,
+    /// RFC 3339 UTC timestamp
+    pub started_at: String,
+    pub provider: String,
+    pub model: String,
+    /// Tools that were running when the app stopped
+    pub pending_tools: Vec<String>,
+    /// Files the agent changed, which "restore" puts back
+    pub changed_files: Vec<String>,
+}
+
+impl From<&JournalRecord> for InterruptedRun {
+    fn from(record: &JournalRecord) -> Self {
+        Self {
+            session_id: record.session_id.clone(),
+            started_at: record.started_at.clone(),
+            provider: record.provider.clone(),
+            model: record.model.clone(),
+            pending_tools: record
+                .pending_tool_calls
+                .iter()
+                .map(|call| call.name.clone())
+                .collect(),
+            changed_files: record
+                .checkpoints
+                .iter()
+                .map(|checkpoint| checkpoint.path.clone())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// Put the files the run changed back to their originals
+    Restore,
+    /// Keep the files and return the session's conversation to continue from
+    Resume,
+    /// Keep the files and forget the run
+    Discard,
+}
+
+#[derive(Debug, Clone, Default, Serialize, TS)]
+#[ts(export)]
+pub struct RecoveryResult {
+    /// Files put back by "restore"
+    pub restored_files: Vec<String>,
+    /// The session's conversation as last sent, for "resume"
+    pub messages: Vec<ChatMessage>,
+    /// The session's todo list, for "resume"; the current session takes it over
+    pub todos: Vec<TrackedTodo>,
+    /// The session's plan history, for "resume"; the current session takes it over
+    pub plans: Vec<ArchivedPlan>,
+}
+
+/// Applies `action` to the most recent interrupted run and removes its
+/// journal. Errors if there is none.
+pub fn recover(project_path: &Path, action: RecoveryAction) -> Result<RecoveryResult, String> {
+    let record = find_interrupted(project_path)
+        .ok_or_else(|| "No interrupted run to recover".to_string())?;
+
+    let mut result = RecoveryResult::default();
+    match action {
+        RecoveryAction::Restore => {
+            // The journal is a file in the project, so `absolute` is not
+            // trusted: targets are resolved again and all must be inside it
+            let targets = record
+                .checkpoints
+                .iter()
+                .map(|checkpoint| {
+                    resolve_in_dir(project_path, &checkpoint.path)
+                        .map(|target| (checkpoint, target))
+                        .map_err(|e| format!("Not restoring {}: {}", checkpoint.path, e))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for (checkpoint, target) in targets {
+                restore_checkpoint(&target, checkpoint.original.as_deref())
+                    .map_err(|e| format!("Failed to restore {}: {}", checkpoint.path, e))?;
+                result.restored_files.push(checkpoint.path.clone());
+            }
+        }
+        RecoveryAction::Resume => {
+            let conversation = Conversation::load(project_path, &record.session_id);
+            result.messages = conversation.messages;
+            result.todos = conversation.todos;
+            result.plans = conversation.plans;
+        }
+        RecoveryAction::Discard => {}
+    }
+
+    let path = journal_path(project_path, &record.session_id);
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove run journal: {}", e))?;
+    Ok(result)
+}
+
+fn restore_checkpoint(target: &Path, original: Option<&str>) -> io::Result<()> {
+    match original {
+        Some(content) => {
+            if let Some(dir) = target.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(target, content)
+        }
+        // The run created the file
+        None => match fs::remove_file(target) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::tools::TodoItem;
+    use crate::agent::types::MessageRole;
+    use tempfile::TempDir;
+
+    /// Rewrites the journal as if an earlier app process had left it.
+    fn crash(project: &Path, session_id: &str) {
+        let path = journal_path(project, session_id);
+        let mut record: JournalRecord =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        record.pid = record.pid.wrapping_add(1);
+        write_record(&path, &record).unwrap();
+    }
+
+    fn snapshot(project: &Path, path: &str, original: Option<&str>) -> FileSnapshot {
+        FileSnapshot {
+            path: path.to_string(),
+            absolute: project.join(path),
+            original: original.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_live_and_finished_runs_are_not_interrupted() {
+        let temp = TempDir::new().unwrap();
+        let journal = RunJournal::start(temp.path(), "s1", "anthropic", "model");
+        assert!(find_interrupted(temp.path()).is_none());
+
+        journal.finish();
+        assert!(!journal_path(temp.path(), "s1").exists());
+        assert!(recover(temp.path(), RecoveryAction::Discard).is_err());
+    }
+
+    #[test]
+    fn test_restore_puts_back_originals() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path();
+        fs::write(project.join("main.rs"), "half edited").unwrap();
+        fs::write(project.join("new.rs"), "created").unwrap();
+
+        let journal = RunJournal::start(project, "s1", "anthropic", "model");
+        journal.record_checkpoint(&snapshot(project, "main.rs", Some("original")));
+        journal.record_checkpoint(&snapshot(project, "main.rs", Some("later")));
+        journal.record_checkpoint(&snapshot(project, "new.rs", None));
+        journal.record_edit("main.rs");
+        journal.set_pending(vec![JournalToolCall {
+            id: "t1".to_string(),
+            name: "bash".to_string(),
+            input: serde_json::json!({ "command": "cargo test" }),
+        }]);
+        crash(project, "s1");
+
+        let interrupted = InterruptedRun::from(&find_interrupted(project).unwrap());
+        assert_eq!(interrupted.pending_tools, vec!["bash"]);
+        assert_eq!(interrupted.changed_files, vec!["main.rs", "new.rs"]);
+
+        let result = recover(project, RecoveryAction::Restore).unwrap();
+        assert_eq!(result.restored_files.len(), 2);
+        assert_eq!(
+            fs::read_to_string(project.join("main.rs")).unwrap(),
+            "original"
+        );
+        assert!(!project.join("new.rs").exists());
+        assert!(find_interrupted(project).is_none());
+    }
+
+    #[test]
+    fn test_restore_refuses_targets_outside_the_project() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path().join("project");
+        fs::create_dir(&project).unwrap();
+        let outside = temp.path().join("bashrc");
+        fs::write(&outside, "mine").unwrap();
+
+        // The recorded absolute path is ignored
+        let journal = RunJournal::start(&project, "s1", "anthropic", "model");
+        journal.record_checkpoint(&FileSnapshot {
+            path: "main.rs".to_string(),
+            absolute: outside.clone(),
+            original: Some("original".to_string()),
+        });
+        crash(&project, "s1");
+        recover(&project, RecoveryAction::Restore).unwrap();
+        assert_eq!(fs::read_to_string(&outside).unwrap(), "mine");
+        assert_eq!(
+            fs::read_to_string(project.join("main.rs")).unwrap(),
+            "original"
+        );
+
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(temp.path(), project.join("link")).unwrap();
+        let mut escapes = vec![
+            "../bashrc".to_string(),
+            outside.to_string_lossy().into_owned(),
+        ];
+        if cfg!(unix) {
+            escapes.push("link/bashrc".to_string());
+        }
+        for path in escapes {
+            let journal = RunJournal::start(&project, "s2", "anthropic", "model");
+            journal.record_checkpoint(&FileSnapshot {
+                path: path.clone(),
+                absolute: outside.clone(),
+                original: Some("overwritten".to_string()),
+            });
+            crash(&project, "s2");
+
+            let err = recover(&project, RecoveryAction::Restore).unwrap_err();
+            assert!(err.contains("Not restoring"), "{}: {}", path, err);
+            assert_eq!(fs::read_to_string(&outside).unwrap(), "mine");
+            recover(&project, RecoveryAction::Discard).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_resume_returns_conversation() {
+        let temp = TempDir::new().unwrap();
+        let project = temp.path();
+        let messages = vec![ChatMessage::new(MessageRole::User, "fix it".to_string())];
+        Conversation::record_request(project, "s1", &messages).unwrap();
+        let todo = TrackedTodo {
+            item: TodoItem {
+                id: "1".to_string(),
+                content: "Fix the parser".to_string(),
+                status: "in_progress".to_string(),
+                priority: "high".to_string(),
+            },
+            updated_at: Utc::now().to_rfc3339(),
+            updated_turn: 2,
+        };
+        Conversation::record_progress(project, "s1", &[todo], &[]).unwrap();
+        fs::write(project.join("main.rs"), "half edited").unwrap();
+
+        let journal = RunJournal::start(project, "s1", "gemini", "model");
+        journal.record_checkpoint(&snapshot(project, "main.rs", Some("original")));
+        crash(project, "s1");
+
+        let result = recover(project, RecoveryAction::Resume).unwrap();
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].get_text(), "fix it");
+        assert_eq!(result.todos.len(), 1);
+        assert_eq!(result.todos[0].item.content, "Fix the parser");
+        assert!(result.plans.is_empty());
+        assert!(result.restored_files.is_empty());
+        assert_eq!(
+            fs::read_to_string(project.join("main.rs")).unwrap(),
+            "half edited"
+        );
+    }
+}
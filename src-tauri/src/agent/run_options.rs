@@ -0,0 +1,120 @@
+//! Session-level overrides of model and loop parameters, applied on top of the project config.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::config::ProjectConfig;
+
+use super::models::max_temperature;
+use super::providers::compaction::{get_compaction_threshold, get_context_limit};
+use super::tools::ToolName;
+
+/// Whether the main agent may change files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum AgentMode {
+    #[default]
+    Code,
+    /// Read-only: file editing tools are not offered and calls to them are refused
+    Plan,
+    /// All tools are offered, but bash, file edits and docker exec only report
+    /// what they would have done (`execution.dry_run`), for sub-agents too
+    DryRun,
+}
+
+/// Tools disabled in plan mode
+const PLAN_MODE_DISABLED_TOOLS: &[ToolName] = &[
+    ToolName::WriteFile,
+    ToolName::EditFile,
+    ToolName::MultiEdit,
+    ToolName::NotebookEdit,
+    ToolName::Scaffold,
+];
+
+/// Effective parameters for the main agent. Sub-agents keep using the project config.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RunOptions {
+    pub model: String,
+    /// Sampling temperature (None = provider default)
+    pub temperature: Option<f32>,
+    pub max_tokens: u32,
+    pub max_tool_iterations: u32,
+    pub context_limit: u32,
+    /// Fraction of the context limit at which compaction starts
+    pub compaction_threshold: f32,
+    #[serde(default)]
+    pub mode: AgentMode,
+}
+
+impl RunOptions {
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        Self {
+            model: config.agent.model.clone(),
+            temperature: config.agent.temperature,
+            max_tokens: config.agent.max_tokens,
+            max_tool_iterations: config.execution.max_tool_iterations,
+            context_limit: get_context_limit(config.agent.context_limit, &config.agent.model),
+            compaction_threshold: get_compaction_threshold(config.agent.compaction_threshold)
+                as f32,
+            mode: AgentMode::Code,
+        }
+    }
+
+    pub fn apply_to(&self, config: &mut ProjectConfig) {
+        config.agent.model = self.model.clone();
+        config.agent.temperature = self.temperature;
+        config.agent.max_tokens = self.max_tokens;
+        config.execution.max_tool_iterations = self.max_tool_iterations;
+        config.agent.context_limit = Some(self.context_limit);
+        config.agent.compaction_threshold = Some(self.compaction_threshold);
+        match self.mode {
+            AgentMode::Code => {}
+            AgentMode::Plan => {
+                let disabled = &mut config.execution.disabled_tools;
+                for tool in PLAN_MODE_DISABLED_TOOLS {
+                    if !disabled.iter().any(|name| name == tool.as_str()) {
+                        disabled.push(tool.as_str().to_string());
+                    }
+                }
+            }
+            AgentMode::DryRun => config.execution.dry_run = true,
+        }
+    }
+
+    /// Checks the options for a project using `provider`.
+    pub fn validate(&self, provider: &str) -> Result<(), String> {
+        if self.model.trim().is_empty() {
+            return Err("Model must not be empty".to_string());
+        }
+        if let Some(temperature) = self.temperature {
+            let max = max_temperature(provider);
+            if !(0.0..=max).contains(&temperature) {
+                return Err(format!(
+                    "Temperature must be between 0 and {} for {}, got {}",
+                    max, provider, temperature
+                ));
+            }
+        }
+        if self.max_tokens == 0 {
+            return Err("Max tokens must be greater than 0".to_string());
+        }
+        if self.max_tool_iterations == 0 {
+            return Err("Max tool iterations must be greater than 0".to_string());
+        }
+        if self.context_limit == 0 {
+            return Err("Context limit must be greater than 0".to_string());
+        }
+        if self.compaction_threshold.is_nan()
+            || self.compaction_threshold <= 0.0
+            || self.compaction_threshold > 1.0
+        {
+            return Err(format!(
+                "Compaction threshold must be in (0, 1], got {}",
+                self.compaction_threshold
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,483 @@
+//! Semantic index: embeddings of the project's text files in fixed-size line
+//! chunks, stored in `.devflow/index` and searched by cosine similarity.
+//! Updates are incremental: only files whose content hash changed are
+//! re-embedded, so keeping the index fresh is cheap after the first build.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::embeddings::{Embedder, EmbeddingTask};
+use super::error::AgentError;
+use super::project_map::{is_under, project_files, project_files_in};
+use crate::config::IndexConfig;
+
+const INDEX_DIR: &str = ".devflow/index";
+const INDEX_FILE: &str = "embeddings.json";
+/// Chunks embedded per request round; the index is saved after each, so an
+/// interrupted build keeps its progress
+const EMBED_BATCH_CHUNKS: usize = 64;
+/// Chunk text sent to the model is cut to this size
+const MAX_CHUNK_CHARS: usize = 6000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Chunk {
+    start_line: usize,
+    end_line: usize,
+    vector: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedFile {
+    hash: u64,
+    chunks: Vec<Chunk>,
+}
+
+/// One search result, lines 1-based and inclusive.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SearchHit {
+    pub path: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub score: f32,
+}
+
+/// What an update changed.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct UpdateStats {
+    pub embedded_files: usize,
+    pub removed_files: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct SemanticIndex {
+    model: String,
+    chunk_lines: usize,
+    /// Keyed by project-relative path with `/` separators
+    files: BTreeMap<String, IndexedFile>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// A file waiting to be embedded.
+struct PendingFile {
+    path: String,
+    hash: u64,
+    /// (start_line, end_line, text)
+    chunks: Vec<(usize, usize, String)>,
+}
+
+impl SemanticIndex {
+    /// Loads the project's index, starting empty if there is none yet.
+    pub fn load(project_path: &Path) -> Self {
+        let path = project_path.join(INDEX_DIR).join(INDEX_FILE);
+        let loaded = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<SemanticIndex>(&content).ok());
+        match loaded {
+            Some(index) => Self { path, ..index },
+            None => Self {
+                model: String::new(),
+                chunk_lines: 0,
+                files: BTreeMap::new(),
+                path,
+            },
+        }
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn chunk_count(&self) -> usize {
+        self.files.values().map(|file| file.chunks.len()).sum()
+    }
+
+    /// Where the index is stored.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn save(&self) -> Result<(), AgentError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string(self).map_err(|e| {
+            AgentError::ToolExecutionError(format!("Failed to encode index: {}", e))
+        })?;
+        // Written aside and renamed, so a concurrent reader never sees half an index
+        let temp = self.path.with_extension("json.tmp");
+        fs::write(&temp, content)?;
+        fs::rename(&temp, &self.path)?;
+        Ok(())
+    }
+
+    /// Brings the index up to date with the project's files, embedding new
+    /// and changed files and dropping deleted ones. A different model or
+    /// chunk size discards the whole index.
+    pub async fn update(
+        &mut self,
+        project_path: &Path,
+        config: &IndexConfig,
+        embedder: &dyn Embedder,
+    ) -> Result<UpdateStats, AgentError> {
+        let dirty = self.reset_if_incompatible(config, embedder);
+        let files = indexable(project_files(project_path));
+        let listed: HashSet<&str> = files.iter().map(String::as_str).collect();
+        let before = self.files.len();
+        self.files.retain(|path, _| listed.contains(path.as_str()));
+        let removed = before - self.files.len();
+        self.sync(project_path, config, embedder, &files, removed, dirty)
+            .await
+    }
+
+    /// Like `update`, for just the files at or below `paths` (relative), e.g.
+    /// paths the file watcher reported changed. An index that has to be
+    /// rebuilt anyway gets a full update.
+    pub async fn update_paths(
+        &mut self,
+        project_path: &Path,
+        config: &IndexConfig,
+        embedder: &dyn Embedder,
+        paths: &[String],
+    ) -> Result<UpdateStats, AgentError> {
+        if self.reset_if_incompatible(config, embedder) {
+            return self.update(project_path, config, embedder).await;
+        }
+        let files = indexable(project_files_in(project_path, paths));
+        let listed: HashSet<&str> = files.iter().map(String::as_str).collect();
+        let before = self.files.len();
+        self.files.retain(|file, _| {
+            listed.contains(file.as_str()) || !paths.iter().any(|path| is_under(file, path))
+        });
+        let removed = before - self.files.len();
+        self.sync(project_path, config, embedder, &files, removed, false)
+            .await
+    }
+
+    /// Clears the index if it was built with another model or chunk size,
+    /// returning whether it did.
+    fn reset_if_incompatible(&mut self, config: &IndexConfig, embedder: &dyn Embedder) -> bool {
+        let model = embedder.model_id();
+        let chunk_lines = config.chunk_lines.max(1) as usize;
+        if self.model == model && self.chunk_lines == chunk_lines {
+            return false;
+        }
+        self.files.clear();
+        self.model = model;
+        self.chunk_lines = chunk_lines;
+        true
+    }
+
+    /// Embeds those of `files` whose content changed since they were indexed,
+    /// saving after each round and once more if only removals happened.
+    async fn sync(
+        &mut self,
+        project_path: &Path,
+        config: &IndexConfig,
+        embedder: &dyn Embedder,
+        files: &[String],
+        removed_files: usize,
+        mut dirty: bool,
+    ) -> Result<UpdateStats, AgentError> {
+        let mut stats = UpdateStats {
+            removed_files,
+            ..Default::default()
+        };
+        let mut pending = Vec::new();
+        let mut pending_chunks = 0;
+        for file in files {
+            let Some(content) = read_text(&project_path.join(file), config.max_file_bytes) else {
+                // Binary, too large or gone: whatever was indexed is stale
+                dirty |= self.files.remove(file).is_some();
+                continue;
+            };
+            let hash = fnv1a(content.as_bytes());
+            if self
+                .files
+                .get(file)
+                .is_some_and(|indexed| indexed.hash == hash)
+            {
+                continue;
+            }
+            let chunks = chunk_lines_of(&content, self.chunk_lines);
+            pending_chunks += chunks.len();
+            pending.push(PendingFile {
+                path: file.clone(),
+                hash,
+                chunks,
+            });
+            if pending_chunks >= EMBED_BATCH_CHUNKS {
+                stats.embedded_files += self.embed_files(&mut pending, embedder).await?;
+                pending_chunks = 0;
+            }
+        }
+        if !pending.is_empty() {
+            stats.embedded_files += self.embed_files(&mut pending, embedder).await?;
+        } else if dirty || stats.removed_files > 0 {
+            self.save()?;
+        }
+
+        Ok(stats)
+    }
+
+    /// Embeds the pending files' chunks in one round, adds them and saves.
+    async fn embed_files(
+        &mut self,
+        pending: &mut Vec<PendingFile>,
+        embedder: &dyn Embedder,
+    ) -> Result<usize, AgentError> {
+        let texts: Vec<String> = pending
+            .iter()
+            .flat_map(|file| {
+                file.chunks
+                    .iter()
+                    .map(move |(_, _, text)| format!("{}\n{}", file.path, text))
+            })
+            .collect();
+        let mut vectors = if texts.is_empty() {
+            Vec::new()
+        } else {
+            embedder.embed(&texts, EmbeddingTask::Document).await?
+        }
+        .into_iter();
+
+        let count = pending.len();
+        for file in pending.drain(..) {
+            let chunks = file
+                .chunks
+                .iter()
+                .map(|(start_line, end_line, _)| Chunk {
+                    start_line: *start_line,
+                    end_line: *end_line,
+                    vector: vectors.next().unwrap_or_default(),
+                })
+                .collect();
+            self.files.insert(
+                file.path,
+                IndexedFile {
+                    hash: file.hash,
+                    chunks,
+                },
+            );
+        }
+        self.save()?;
+        Ok(count)
+    }
+
+    /// The `limit` chunks most similar to `query`, best first.
+    pub async fn search(
+        &self,
+        query: &str,
+        limit: usize,
+        embedder: &dyn Embedder,
+    ) -> Result<Vec<SearchHit>, AgentError> {
+        let query = embedder
+            .embed(&[query.to_string()], EmbeddingTask::Query)
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        Ok(self.nearest(&query, limit))
+    }
+
+    fn nearest(&self, query: &[f32], limit: usize) -> Vec<SearchHit> {
+        let mut hits: Vec<SearchHit> = self
+            .files
+            .iter()
+            .flat_map(|(path, file)| {
+                file.chunks.iter().map(move |chunk| SearchHit {
+                    path: path.clone(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    score: cosine(query, &chunk.vector),
+                })
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Project files minus devflow's own, which include the index itself.
+fn indexable(files: Vec<String>) -> Vec<String> {
+    files
+        .into_iter()
+        .filter(|file| !file.starts_with(".devflow/"))
+        .collect()
+}
+
+/// File content if it is UTF-8 text within the size limit.
+fn read_text(path: &Path, max_bytes: u64) -> Option<String> {
+    if fs::metadata(path).ok()?.len() > max_bytes {
+        return None;
+    }
+    let content = String::from_utf8(fs::read(path).ok()?).ok()?;
+    (!content.contains('\0')).then_some(content)
+}
+
+/// Non-overlapping chunks of `size` lines as (start_line, end_line, text),
+/// skipping chunks that are only whitespace.
+fn chunk_lines_of(content: &str, size: usize) -> Vec<(usize, usize, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .chunks(size)
+        .enumerate()
+        .filter_map(|(i, chunk)| {
+            let text = chunk.join("\n");
+            if text.trim().is_empty() {
+                return None;
+            }
+            let text = match text.char_indices().nth(MAX_CHUNK_CHARS) {
+                Some((end, _)) => text[..end].to_string(),
+                None => text,
+            };
+            let start_line = i * size + 1;
+            Some((start_line, start_line + chunk.len() - 1, text))
+        })
+        .collect()
+}
+
+/// FNV-1a, enough to tell whether a file changed since it was embedded.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Embeds texts by counting a few keywords, and counts the texts it saw.
+    struct KeywordEmbedder {
+        embedded: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Embedder for KeywordEmbedder {
+        fn model_id(&self) -> String {
+            "test/keywords".to_string()
+        }
+
+        async fn embed(
+            &self,
+            texts: &[String],
+            _task: EmbeddingTask,
+        ) -> Result<Vec<Vec<f32>>, AgentError> {
+            self.embedded.fetch_add(texts.len(), Ordering::SeqCst);
+            Ok(texts
+                .iter()
+                .map(|text| {
+                    ["parse", "network", "render"]
+                        .iter()
+                        .map(|word| text.matches(word).count() as f32)
+                        .collect()
+                })
+                .collect())
+        }
+    }
+
+    fn config() -> IndexConfig {
+        IndexConfig {
+            enabled: true,
+            chunk_lines: 2,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_chunk_lines_of() {
+        let chunks = chunk_lines_of("a\nb\n\n\nc", 2);
+        assert_eq!(
+            chunks,
+            vec![(1, 2, "a\nb".to_string()), (5, 5, "c".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_cosine() {
+        assert!((cosine(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+        assert_eq!(cosine(&[1.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(cosine(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_update_is_incremental_and_searchable() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::write(root.join("parser.rs"), "fn parse() {}\n// parse tokens").unwrap();
+        fs::write(root.join("net.rs"), "fn network() {}").unwrap();
+        fs::write(root.join("image.bin"), [0u8, 1, 2]).unwrap();
+        let embedder = KeywordEmbedder {
+            embedded: AtomicUsize::new(0),
+        };
+
+        let mut index = SemanticIndex::load(root);
+        let stats = index.update(root, &config(), &embedder).await.unwrap();
+        assert_eq!(stats.embedded_files, 2);
+        assert_eq!(embedder.embedded.load(Ordering::SeqCst), 2);
+
+        fs::write(root.join("net.rs"), "fn network() {}\nfn render() {}").unwrap();
+        fs::remove_file(root.join("parser.rs")).unwrap();
+        let mut index = SemanticIndex::load(root);
+        assert_eq!(index.file_count(), 2);
+        let stats = index.update(root, &config(), &embedder).await.unwrap();
+        assert_eq!(
+            stats,
+            UpdateStats {
+                embedded_files: 1,
+                removed_files: 1
+            }
+        );
+        assert_eq!(embedder.embedded.load(Ordering::SeqCst), 3);
+
+        // Only the reported paths are looked at
+        fs::write(root.join("net.rs"), "fn parse() {}").unwrap();
+        fs::write(root.join("ui.rs"), "fn render() {}").unwrap();
+        let stats = index
+            .update_paths(root, &config(), &embedder, &["ui.rs".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(stats.embedded_files, 1);
+        assert_eq!(index.file_count(), 2);
+        fs::remove_file(root.join("ui.rs")).unwrap();
+        let stats = index
+            .update_paths(root, &config(), &embedder, &["ui.rs".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(stats.removed_files, 1);
+        assert_eq!(embedder.embedded.load(Ordering::SeqCst), 4);
+
+        let hits = index.search("render", 5, &embedder).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            (hits[0].path.as_str(), hits[0].start_line, hits[0].end_line),
+            ("net.rs", 1, 2)
+        );
+    }
+}
@@ -1,22 +1,35 @@
+use std::collections::HashMap;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
 
 use tokio_util::sync::CancellationToken;
 
 use super::error::AgentError;
+use super::indexes::{embeddings_status, IndexStatus, ProjectIndexes};
 use super::memory::{LoadResult, MemoryState};
+use super::project_map::ProjectMap;
 use super::provider::ProviderAdapter;
 use super::providers::create_provider_adapter;
+use super::run_options::RunOptions;
 use super::tools::SessionState;
+use super::usage::SessionUsageTracker;
+use crate::config::ConfigService;
 
 pub struct AgentState {
     pub adapter: Option<Arc<dyn ProviderAdapter>>,
     pub project_path: Option<String>,
     pub cancel_token: Option<CancellationToken>,
+    /// Cancelled once the current run has unwound, even after `cancel`
+    pub run_finished: Option<CancellationToken>,
     pub is_running: bool,
     pub config_stale: bool,
     pub session: SessionState,
     pub memory: MemoryState,
+    /// Project map and watcher for the current project, started with its session
+    pub indexes: Option<ProjectIndexes>,
+    /// Session overrides of the project config, keyed by project path
+    pub run_options: HashMap<String, RunOptions>,
 }
 
 impl AgentState {
@@ -25,10 +38,13 @@ impl AgentState {
             adapter: None,
             project_path: None,
             cancel_token: None,
+            run_finished: None,
             is_running: false,
             config_stale: false,
             session: SessionState::new(),
             memory: MemoryState::new(),
+            indexes: None,
+            run_options: HashMap::new(),
         }
     }
 
@@ -48,8 +64,12 @@ impl AgentState {
 
     pub fn initialize(&mut self, project_path: &str) -> Result<LoadResult, AgentError> {
         let path = Path::new(project_path);
-        let adapter = create_provider_adapter(path)?;
+        let adapter = create_provider_adapter(path, self.run_options.get(project_path))?;
         self.adapter = Some(adapter);
+        // Config reloads keep the indexes; a new project gets its own
+        if self.indexes.is_none() || self.project_path.as_deref() != Some(project_path) {
+            self.indexes = Some(ProjectIndexes::start(path));
+        }
         self.project_path = Some(project_path.to_string());
         self.config_stale = false;
 
@@ -65,8 +85,79 @@ impl AgentState {
             .and_then(|path| self.memory.reload_if_changed(path))
     }
 
+    /// AGENTS.md memory and the project map, whichever are available.
     pub fn get_memory_for_injection(&self) -> Option<String> {
-        self.memory.format_for_injection()
+        let parts: Vec<String> = self
+            .memory
+            .format_for_injection()
+            .into_iter()
+            .chain(self.indexes.as_ref().map(ProjectIndexes::map_for_injection))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n\n"))
+    }
+
+    /// Regenerates the map from a full listing and returns it.
+    pub fn refresh_project_map(&self, project_path: &str) -> String {
+        match self.current_indexes(project_path) {
+            Some(indexes) => indexes.refresh_map(),
+            None => ProjectMap::generate(Path::new(project_path))
+                .text()
+                .to_string(),
+        }
+    }
+
+    /// Freshness and size of the project's indexes; only the current project
+    /// has a project map and a watcher.
+    pub fn index_status(&self, project_path: &str) -> IndexStatus {
+        match self.current_indexes(project_path) {
+            Some(indexes) => indexes.status(),
+            None => IndexStatus {
+                watching: false,
+                project_map: None,
+                embeddings: embeddings_status(Path::new(project_path), 0),
+            },
+        }
+    }
+
+    fn current_indexes(&self, project_path: &str) -> Option<&ProjectIndexes> {
+        self.indexes
+            .as_ref()
+            .filter(|_| self.project_path.as_deref() == Some(project_path))
+    }
+
+    /// Effective options: the session overrides if set, otherwise the project config.
+    pub fn get_run_options(&self, project_path: &str) -> Result<RunOptions, AgentError> {
+        if let Some(options) = self.run_options.get(project_path) {
+            return Ok(options.clone());
+        }
+
+        let config = ConfigService::load_project_config(Path::new(project_path))
+            .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+        Ok(RunOptions::from_config(&config))
+    }
+
+    /// Stores overrides (None resets to the project config). The adapter is
+    /// rebuilt before the next message.
+    pub fn set_run_options(
+        &mut self,
+        project_path: &str,
+        options: Option<RunOptions>,
+    ) -> Result<(), AgentError> {
+        match options {
+            Some(options) => {
+                let config = ConfigService::load_project_config(Path::new(project_path))
+                    .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+                options
+                    .validate(&config.agent.provider)
+                    .map_err(AgentError::ConfigError)?;
+                self.run_options.insert(project_path.to_string(), options);
+            }
+            None => {
+                self.run_options.remove(project_path);
+            }
+        }
+        self.config_stale = true;
+        Ok(())
     }
 
     pub fn get_adapter(&self) -> Option<Arc<dyn ProviderAdapter>> {
@@ -76,6 +167,7 @@ impl AgentState {
     pub fn start_run(&mut self) -> CancellationToken {
         let token = CancellationToken::new();
         self.cancel_token = Some(token.clone());
+        self.run_finished = Some(CancellationToken::new());
         self.is_running = true;
         token
     }
@@ -89,16 +181,22 @@ impl AgentState {
 
     pub fn finish_run(&mut self) {
         self.cancel_token = None;
+        if let Some(finished) = self.run_finished.take() {
+            finished.cancel();
+        }
         self.is_running = false;
     }
 
     pub fn clear(&mut self) {
         self.cancel();
+        self.session.kill_processes();
         self.adapter = None;
         self.project_path = None;
         self.config_stale = false;
         self.session = SessionState::new();
         self.memory = MemoryState::new();
+        self.indexes = None;
+        self.run_options.clear();
     }
 }
 
@@ -108,6 +206,113 @@ impl Default for AgentState {
     }
 }
 
+/// Agent state and session usage of one window
+#[derive(Clone)]
+pub struct WindowState {
+    pub agent: Arc<RwLock<AgentState>>,
+    pub usage: Arc<SessionUsageTracker>,
+}
+
+/// State of every window, keyed by webview label, so each window is an
+/// independent project workspace. A window's state is created by its first command.
+#[derive(Default)]
+pub struct WindowStates {
+    windows: Mutex<HashMap<String, WindowState>>,
+    /// `run_finished` of closed windows' runs that may still be unwinding
+    closing_runs: Mutex<Vec<CancellationToken>>,
+    shutting_down: AtomicBool,
+}
+
+impl WindowStates {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, label: &str) -> WindowState {
+        self.lock()
+            .entry(label.to_string())
+            .or_insert_with(|| WindowState {
+                agent: Arc::new(RwLock::new(AgentState::new())),
+                usage: Arc::new(SessionUsageTracker::new()),
+            })
+            .clone()
+    }
+
+    pub fn agent(&self, label: &str) -> Arc<RwLock<AgentState>> {
+        self.get(label).agent
+    }
+
+    pub fn usage(&self, label: &str) -> Arc<SessionUsageTracker> {
+        self.get(label).usage
+    }
+
+    /// Drops a closed window's state, cancelling its run and killing what
+    /// its bash commands left running.
+    pub fn remove(&self, label: &str) {
+        if let Some(window) = self.lock().remove(label) {
+            if let Ok(mut agent) = window.agent.write() {
+                let mut closing = self
+                    .closing_runs
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner);
+                closing.retain(|finished| !finished.is_cancelled());
+                closing.extend(agent.run_finished.clone());
+                agent.cancel();
+                agent.session.kill_processes();
+            }
+        }
+    }
+
+    /// Cancels every run, those of closed windows included, kills leftover
+    /// bash processes and returns a token per run that is cancelled once it
+    /// has unwound. Empty after the first call, so the exit that follows goes through.
+    pub fn shutdown(&self) -> Vec<CancellationToken> {
+        if self.shutting_down.swap(true, Ordering::SeqCst) {
+            return Vec::new();
+        }
+        let mut finished: Vec<CancellationToken> = self
+            .closing_runs
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .drain(..)
+            .collect();
+        for window in self.lock().values() {
+            if let Ok(mut agent) = window.agent.write() {
+                finished.extend(agent.run_finished.clone());
+                agent.cancel();
+                agent.session.kill_processes();
+            }
+        }
+        finished.retain(|finished| !finished.is_cancelled());
+        finished
+    }
+
+    /// Config changes can affect any window's project.
+    pub fn mark_config_stale(&self) {
+        for window in self.lock().values() {
+            if let Ok(mut agent) = window.agent.write() {
+                agent.mark_config_stale();
+            }
+        }
+    }
+
+    /// A project config change only affects the windows that have that project open.
+    pub fn mark_project_config_stale(&self, project_path: &Path) {
+        for window in self.lock().values() {
+            if let Ok(mut agent) = window.agent.write() {
+                if agent.project_path.as_deref().map(Path::new) == Some(project_path) {
+                    agent.mark_config_stale();
+                }
+            }
+        }
+    }
+
+    // Only holds Arcs, so a panic while locked leaves nothing half-updated
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, WindowState>> {
+        self.windows.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +358,139 @@ mod tests {
         assert!(state.project_path.is_none());
     }
 
+    #[test]
+    fn test_windows_have_independent_state() {
+        let windows = WindowStates::new();
+        let main = windows.agent("main");
+        let token = main.write().unwrap().start_run();
+        windows.agent("other").write().unwrap().mark_config_stale();
+
+        assert!(windows.agent("main").read().unwrap().is_running);
+        assert!(!windows.agent("main").read().unwrap().config_stale);
+        assert!(!windows.agent("other").read().unwrap().is_running);
+        assert!(!Arc::ptr_eq(
+            &windows.usage("main"),
+            &windows.usage("other")
+        ));
+
+        windows.mark_config_stale();
+        assert!(main.read().unwrap().config_stale);
+
+        windows.remove("main");
+        assert!(token.is_cancelled());
+        assert!(!windows.agent("main").read().unwrap().is_running);
+    }
+
+    #[test]
+    fn test_project_config_change_marks_only_its_windows() {
+        let windows = WindowStates::new();
+        windows.agent("main").write().unwrap().project_path = Some("/work/app".to_string());
+        windows.agent("other").write().unwrap().project_path = Some("/work/lib".to_string());
+
+        windows.mark_project_config_stale(Path::new("/work/app/"));
+        assert!(windows.agent("main").read().unwrap().config_stale);
+        assert!(!windows.agent("other").read().unwrap().config_stale);
+        assert!(!windows.agent("empty").read().unwrap().config_stale);
+    }
+
+    #[test]
+    fn test_shutdown_waits_for_closed_and_open_windows() {
+        let windows = WindowStates::new();
+        let closed = windows.agent("closed");
+        closed.write().unwrap().start_run();
+        windows.remove("closed");
+        let open = windows.agent("open");
+        let token = open.write().unwrap().start_run();
+        windows.agent("idle");
+
+        let pending = windows.shutdown();
+        assert_eq!(pending.len(), 2);
+        assert!(token.is_cancelled());
+        assert!(pending.iter().all(|finished| !finished.is_cancelled()));
+
+        closed.write().unwrap().finish_run();
+        open.write().unwrap().finish_run();
+        assert!(pending.iter().all(CancellationToken::is_cancelled));
+        assert!(windows.shutdown().is_empty());
+    }
+
+    fn write_project_config(dir: &Path) {
+        let config_dir = dir.join(".devflow");
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(
+            config_dir.join("config.toml"),
+            r#"
+[agent]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+api_key_env = "ANTHROPIC_API_KEY"
+max_tokens = 4096
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_run_options_default_to_project_config() {
+        let temp = tempfile::tempdir().unwrap();
+        write_project_config(temp.path());
+        let project = temp.path().to_string_lossy().to_string();
+
+        let state = AgentState::new();
+        let options = state.get_run_options(&project).unwrap();
+        assert_eq!(options.model, "claude-sonnet-4-20250514");
+        assert_eq!(options.max_tokens, 4096);
+        assert_eq!(options.max_tool_iterations, 50);
+        assert_eq!(options.context_limit, 200_000);
+        assert_eq!(options.temperature, None);
+    }
+
+    #[test]
+    fn test_set_run_options_overrides_and_resets() {
+        let temp = tempfile::tempdir().unwrap();
+        write_project_config(temp.path());
+        let project = temp.path().to_string_lossy().to_string();
+
+        let mut state = AgentState::new();
+        let mut options = state.get_run_options(&project).unwrap();
+        options.temperature = Some(0.2);
+        options.max_tool_iterations = 10;
+
+        state
+            .set_run_options(&project, Some(options.clone()))
+            .unwrap();
+        assert!(state.config_stale);
+        assert_eq!(state.get_run_options(&project).unwrap(), options);
+
+        state.set_run_options(&project, None).unwrap();
+        assert_eq!(state.get_run_options(&project).unwrap().temperature, None);
+    }
+
+    #[test]
+    fn test_set_run_options_rejects_invalid_values() {
+        let temp = tempfile::tempdir().unwrap();
+        write_project_config(temp.path());
+        let project = temp.path().to_string_lossy().to_string();
+
+        let mut state = AgentState::new();
+        let mut options = state.get_run_options(&project).unwrap();
+        options.compaction_threshold = 1.5;
+
+        assert!(state.set_run_options(&project, Some(options)).is_err());
+        assert!(state.run_options.is_empty());
+
+        // Anthropic's maximum is 1.0
+        let mut options = state.get_run_options(&project).unwrap();
+        options.temperature = Some(1.5);
+        let err = state.set_run_options(&project, Some(options)).unwrap_err();
+        assert!(err.to_string().contains("between 0 and 1"));
+        assert!(state.run_options.is_empty());
+    }
+
     #[test]
     fn test_default_state_is_not_running() {
         let state = AgentState::new();
@@ -0,0 +1,175 @@
+//! Structured (JSON) output for headless runs: parsing the model's answer and
+//! checking it against the requested schema.
+//!
+//! Validation covers the JSON Schema subset providers accept for structured
+//! output: `type`, `properties`, `required`, `additionalProperties: false`,
+//! `items` and `enum`. Other keywords are ignored.
+
+use serde_json::Value;
+
+/// Tool the Anthropic adapter forces the model to call; its input is the result.
+pub const STRUCTURED_OUTPUT_TOOL: &str = "structured_output";
+
+/// Follow-up prompt asking for the final answer once the tool loop is done.
+pub const STRUCTURED_OUTPUT_PROMPT: &str =
+    "Now give your final answer as JSON matching the requested schema.";
+
+/// Parses a JSON answer, tolerating a surrounding Markdown code fence.
+pub fn parse_json_text(text: &str) -> Result<Value, String> {
+    let trimmed = text.trim();
+    let body = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.strip_suffix("```"))
+        .unwrap_or(trimmed);
+
+    serde_json::from_str(body.trim()).map_err(|e| format!("response is not valid JSON: {}", e))
+}
+
+/// Checks `value` against `schema`, returning the first mismatch.
+pub fn validate(value: &Value, schema: &Value) -> Result<(), String> {
+    validate_at(value, schema, "$")
+}
+
+fn validate_at(value: &Value, schema: &Value, path: &str) -> Result<(), String> {
+    if let Some(expected) = schema.get("type") {
+        let matches = match expected {
+            Value::String(name) => type_matches(value, name),
+            Value::Array(names) => names
+                .iter()
+                .filter_map(Value::as_str)
+                .any(|name| type_matches(value, name)),
+            _ => true,
+        };
+        if !matches {
+            return Err(format!("{}: expected {}, got {}", path, expected, value));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return Err(format!("{}: {} is not one of {:?}", path, value, allowed));
+        }
+    }
+
+    if let Value::Object(fields) = value {
+        for name in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !fields.contains_key(name) {
+                return Err(format!("{}: missing required field '{}'", path, name));
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+        for (name, field) in fields {
+            match properties.and_then(|p| p.get(name)) {
+                Some(field_schema) => {
+                    validate_at(field, field_schema, &format!("{}.{}", path, name))?
+                }
+                None if closed => {
+                    return Err(format!("{}: unexpected field '{}'", path, name));
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let (Value::Array(items), Some(item_schema)) = (value, schema.get("items")) {
+        for (i, item) in items.iter().enumerate() {
+            validate_at(item, item_schema, &format!("{}[{}]", path, i))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn type_matches(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn commit_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "subject": {"type": "string"},
+                "kind": {"type": "string", "enum": ["feat", "fix", "chore"]},
+                "files": {"type": "array", "items": {"type": "string"}},
+                "breaking": {"type": "boolean"}
+            },
+            "required": ["subject", "kind"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn test_parse_json_text_strips_fences() {
+        assert_eq!(parse_json_text(r#" {"a": 1} "#).unwrap(), json!({"a": 1}));
+        assert_eq!(
+            parse_json_text("```json\n{\"a\": 1}\n```").unwrap(),
+            json!({"a": 1})
+        );
+        assert_eq!(parse_json_text("```\n[1, 2]\n```").unwrap(), json!([1, 2]));
+        assert!(parse_json_text("Here you go: {}").is_err());
+    }
+
+    #[test]
+    fn test_valid_value_passes() {
+        let value = json!({
+            "subject": "Fix parser",
+            "kind": "fix",
+            "files": ["src/parser.rs"],
+            "breaking": false
+        });
+        assert!(validate(&value, &commit_schema()).is_ok());
+    }
+
+    #[test]
+    fn test_reports_first_mismatch_with_path() {
+        let schema = commit_schema();
+
+        let err = validate(&json!({"kind": "fix"}), &schema).unwrap_err();
+        assert_eq!(err, "$: missing required field 'subject'");
+
+        let err = validate(&json!({"subject": "x", "kind": "docs"}), &schema).unwrap_err();
+        assert!(err.starts_with("$.kind: \"docs\" is not one of"));
+
+        let err = validate(
+            &json!({"subject": "x", "kind": "fix", "files": ["a", 2]}),
+            &schema,
+        )
+        .unwrap_err();
+        assert_eq!(err, "$.files[1]: expected \"string\", got 2");
+
+        let err =
+            validate(&json!({"subject": "x", "kind": "fix", "extra": 1}), &schema).unwrap_err();
+        assert_eq!(err, "$: unexpected field 'extra'");
+    }
+
+    #[test]
+    fn test_type_unions_and_integers() {
+        let schema = json!({"type": ["integer", "null"]});
+        assert!(validate(&json!(3), &schema).is_ok());
+        assert!(validate(&Value::Null, &schema).is_ok());
+        assert!(validate(&json!(3.5), &schema).is_err());
+    }
+}
@@ -2,9 +2,41 @@ use async_trait::async_trait;
 
 use super::types::ToolName;
 use crate::agent::error::AgentError;
+use crate::agent::types::ToolArtifact;
+
+/// A tool's result: the text the model gets, and artifacts for the UI.
+#[derive(Debug, Clone, Default)]
+pub struct ToolOutput {
+    pub text: String,
+    pub artifacts: Vec<ToolArtifact>,
+}
+
+impl From<String> for ToolOutput {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            artifacts: Vec::new(),
+        }
+    }
+}
 
 #[async_trait]
 pub trait ToolExecutor: Send + Sync {
     async fn execute(&self, tool: ToolName, input: serde_json::Value)
         -> Result<String, AgentError>;
+
+    /// Like `execute`, with the artifacts the tool produced.
+    async fn execute_output(
+        &self,
+        tool: ToolName,
+        input: serde_json::Value,
+    ) -> Result<ToolOutput, AgentError> {
+        self.execute(tool, input).await.map(ToolOutput::from)
+    }
+
+    /// Runs once after each batch of tool calls. A returned note is appended to
+    /// the batch's last successful edit result.
+    async fn after_batch(&self) -> Option<String> {
+        None
+    }
 }
@@ -1,34 +1,136 @@
+use std::ffi::OsString;
 use std::future::Future;
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use reqwest::Client;
+use tokio::fs;
+use tokio::process::Command;
 use tokio::time::timeout;
 
+use crate::agent::attribution::Attribution;
 use crate::agent::error::AgentError;
+use crate::agent::http::build_http_client;
+use crate::config::{AllowedPath, NetworkConfig, PathAccess, WorkspaceRoot};
+use crate::git::wsl::{parse_wsl_path, write_file_via_wsl, WslPath};
 
 pub const MAX_OUTPUT_SIZE: usize = 1024 * 1024; // 1MB
+/// Default of `execution.max_read_bytes`
+pub const DEFAULT_MAX_READ_BYTES: usize = 100 * 1024;
 
 #[derive(Clone)]
 pub struct ExecutionContext {
     pub working_dir: PathBuf,
     pub timeout: Duration,
     pub http_client: Client,
+    /// Trailer appended to files created by write_file (None = disabled)
+    pub attribution: Option<Attribution>,
+    /// Set for projects inside a WSL distro (`\\wsl$\...`): file writes and
+    /// commands then run through wsl.exe
+    pub wsl_path: Option<WslPath>,
+    /// Canonical directories outside the project the tools may use (`execution.allowed_paths`)
+    pub allowed_dirs: Vec<(PathBuf, PathAccess)>,
+    /// Canonical workspace repos by name: `name/...` paths resolve inside them
+    pub workspace_roots: Vec<(String, PathBuf)>,
+    /// Most of a file read_file returns at once (`execution.max_read_bytes`)
+    pub max_read_bytes: usize,
 }
 
 impl ExecutionContext {
-    pub fn new(working_dir: PathBuf, timeout_secs: u64) -> Self {
+    /// Fails with a config error if the proxy or CA settings in `network`
+    /// are invalid, rather than fetching without them.
+    pub fn new(
+        working_dir: PathBuf,
+        timeout_secs: u64,
+        network: &NetworkConfig,
+    ) -> Result<Self, AgentError> {
         let timeout = Duration::from_secs(timeout_secs);
-        let http_client = Client::builder()
-            .timeout(timeout)
-            .build()
-            .expect("Failed to create HTTP client");
+        let http_client = build_http_client(network, Some(timeout))?;
 
-        Self {
+        Ok(Self {
+            wsl_path: parse_wsl_path(&working_dir),
             working_dir,
             timeout,
             http_client,
+            attribution: None,
+            allowed_dirs: Vec::new(),
+            workspace_roots: Vec::new(),
+            max_read_bytes: DEFAULT_MAX_READ_BYTES,
+        })
+    }
+
+    /// Resolves `allowed` against the working directory. Directories that do
+    /// not exist are skipped, since they could not be canonicalized.
+    pub fn set_allowed_paths(&mut self, allowed: &[AllowedPath]) {
+        self.allowed_dirs = allowed
+            .iter()
+            .filter_map(
+                |entry| match self.working_dir.join(&entry.path).canonicalize() {
+                    Ok(dir) => Some((dir, entry.access)),
+                    Err(e) => {
+                        log::warn!("Ignoring allowed path '{}': {}", entry.path, e);
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Resolves the workspace `roots` against the working directory, skipping
+    /// ones that do not exist like `set_allowed_paths`.
+    pub fn set_workspace_roots(&mut self, roots: &[WorkspaceRoot]) {
+        self.workspace_roots = roots
+            .iter()
+            .filter_map(
+                |root| match self.working_dir.join(&root.path).canonicalize() {
+                    Ok(dir) => Some((root.name.clone(), dir)),
+                    Err(e) => {
+                        log::warn!("Ignoring workspace root '{}': {}", root.name, e);
+                        None
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// Writes `content` to `path`, a resolved project path, creating parent directories.
+    pub async fn write(&self, path: &Path, content: &str) -> std::io::Result<()> {
+        if let Some(wsl) = &self.wsl_path {
+            return write_file_via_wsl(wsl, &self.linux_path(wsl, path), content.as_bytes()).await;
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
         }
+        fs::write(path, content).await
+    }
+
+    /// Command running `program` in the working directory, inside the distro
+    /// for WSL projects (a `program` path in the project is translated too).
+    pub fn command(&self, program: &str) -> Command {
+        match &self.wsl_path {
+            Some(wsl) => {
+                let program = self.command_path(Path::new(program));
+                wsl.command(&wsl.linux_path, &program.to_string_lossy())
+            }
+            None => {
+                let mut cmd = Command::new(program);
+                cmd.current_dir(&self.working_dir);
+                cmd
+            }
+        }
+    }
+
+    /// `path` as commands from `command` see it: the Linux path for project
+    /// paths of WSL projects, unchanged otherwise.
+    pub fn command_path(&self, path: &Path) -> OsString {
+        match &self.wsl_path {
+            Some(wsl) if path.starts_with(&self.working_dir) => self.linux_path(wsl, path).into(),
+            _ => path.as_os_str().to_owned(),
+        }
+    }
+
+    fn linux_path(&self, wsl: &WslPath, path: &Path) -> String {
+        wsl.join(path.strip_prefix(&self.working_dir).unwrap_or(path))
     }
 
     pub async fn with_timeout<T, E, F>(&self, operation: &str, fut: F) -> Result<T, AgentError>
@@ -46,56 +148,167 @@ impl ExecutionContext {
         }
     }
 
-    /// Rejects absolute paths and path traversal attempts.
+    /// Resolves a path for reading. Relative paths must stay in the working
+    /// directory and absolute ones in an allowed directory; traversal and
+    /// symlinks (including symlinked parents of files that do not exist yet)
+    /// that lead anywhere else are rejected.
     pub fn resolve_path(&self, path: &str) -> Result<PathBuf, AgentError> {
+        self.resolve(path, PathAccess::Read)
+    }
+
+    /// Like `resolve_path`, but refuses read-only allowed directories.
+    pub fn resolve_write_path(&self, path: &str) -> Result<PathBuf, AgentError> {
+        self.resolve(path, PathAccess::ReadWrite)
+    }
+
+    fn resolve(&self, path: &str, access: PathAccess) -> Result<PathBuf, AgentError> {
         let path = Path::new(path);
 
-        if path.is_absolute() {
+        if path.is_absolute() && self.allowed_dirs.is_empty() {
             return Err(AgentError::InvalidToolInput(
                 "Absolute paths are not allowed".to_string(),
             ));
         }
 
-        for component in path.components() {
-            match component {
-                std::path::Component::ParentDir => {
-                    return Err(AgentError::InvalidToolInput(
-                        "Path traversal ('..') is not allowed".to_string(),
-                    ));
-                }
-                std::path::Component::Prefix(_) => {
-                    return Err(AgentError::InvalidToolInput(
-                        "Invalid path component".to_string(),
-                    ));
-                }
-                _ => {}
-            }
+        check_components(path)?;
+
+        if let Some(resolved) = self.resolve_in_workspace_root(path)? {
+            return Ok(resolved);
         }
 
         let resolved = self.working_dir.join(path);
+        let canonical = canonicalize_existing_prefix(&resolved)?;
+        let canonical_working = self.working_dir.canonicalize()?;
 
-        if resolved.exists() {
-            let canonical = resolved.canonicalize()?;
-            let canonical_working = self.working_dir.canonicalize()?;
+        if canonical.starts_with(&canonical_working) && !path.is_absolute() {
+            return Ok(resolved);
+        }
 
-            if !canonical.starts_with(&canonical_working) {
-                return Err(AgentError::InvalidToolInput(format!(
-                    "Path '{}' resolves outside working directory",
+        match self
+            .allowed_dirs
+            .iter()
+            .find(|(dir, _)| canonical.starts_with(dir))
+        {
+            Some((_, PathAccess::Read)) if access == PathAccess::ReadWrite => {
+                Err(AgentError::InvalidToolInput(format!(
+                    "Path '{}' is in a read-only allowed directory",
                     path.display()
-                )));
+                )))
             }
+            Some(_) => Ok(resolved),
+            None if path.is_absolute() => Err(AgentError::InvalidToolInput(format!(
+                "Absolute paths must be inside an allowed directory ({})",
+                self.describe_allowed_dirs()
+            ))),
+            None => Err(AgentError::InvalidToolInput(format!(
+                "Path '{}' resolves outside working directory",
+                path.display()
+            ))),
+        }
+    }
+
+    /// Resolves `name/rest` inside the workspace root `name`; None if the
+    /// path does not start with a root name.
+    fn resolve_in_workspace_root(&self, path: &Path) -> Result<Option<PathBuf>, AgentError> {
+        let Some(std::path::Component::Normal(first)) = path.components().next() else {
+            return Ok(None);
+        };
+        let Some((name, root)) = self
+            .workspace_roots
+            .iter()
+            .find(|(name, _)| first == name.as_str())
+        else {
+            return Ok(None);
+        };
+
+        let resolved = root.join(path.strip_prefix(name).unwrap_or(path));
+        if !canonicalize_existing_prefix(&resolved)?.starts_with(root) {
+            return Err(AgentError::InvalidToolInput(format!(
+                "Path '{}' resolves outside workspace root '{}'",
+                path.display(),
+                name
+            )));
         }
+        Ok(Some(resolved))
+    }
+
+    fn describe_allowed_dirs(&self) -> String {
+        self.allowed_dirs
+            .iter()
+            .map(|(dir, access)| match access {
+                PathAccess::Read => format!("{} (read-only)", dir.display()),
+                PathAccess::ReadWrite => dir.display().to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Resolves the relative `path` inside `dir` like a tool path inside the
+/// project: absolute paths, `..` and symlinks leading out of `dir` are refused.
+pub(crate) fn resolve_in_dir(dir: &Path, path: &str) -> Result<PathBuf, AgentError> {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return Err(AgentError::InvalidToolInput(
+            "Absolute paths are not allowed".to_string(),
+        ));
+    }
+    check_components(path)?;
+
+    let resolved = dir.join(path);
+    if !canonicalize_existing_prefix(&resolved)?.starts_with(dir.canonicalize()?) {
+        return Err(AgentError::InvalidToolInput(format!(
+            "Path '{}' resolves outside working directory",
+            path.display()
+        )));
+    }
+    Ok(resolved)
+}
 
-        Ok(resolved)
+fn check_components(path: &Path) -> Result<(), AgentError> {
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                return Err(AgentError::InvalidToolInput(
+                    "Path traversal ('..') is not allowed".to_string(),
+                ));
+            }
+            std::path::Component::Prefix(_) if !path.is_absolute() => {
+                return Err(AgentError::InvalidToolInput(
+                    "Invalid path component".to_string(),
+                ));
+            }
+            _ => {}
+        }
     }
+    Ok(())
+}
 
-    pub fn truncate_output(mut output: String) -> String {
-        if output.len() > MAX_OUTPUT_SIZE {
-            output.truncate(MAX_OUTPUT_SIZE);
-            output.push_str("\n... (output truncated)");
+/// Canonical form of `path` when only a prefix of it may exist: the deepest
+/// existing ancestor is canonicalized (resolving symlinks) and the missing
+/// components are appended unchanged.
+fn canonicalize_existing_prefix(path: &Path) -> std::io::Result<PathBuf> {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        // symlink_metadata so a dangling symlink counts as existing and is rejected below
+        if existing.symlink_metadata().is_ok() {
+            break;
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name.to_owned());
+                existing = parent;
+            }
+            _ => return Ok(path.to_path_buf()),
         }
-        output
     }
+
+    let mut canonical = existing.canonicalize()?;
+    for name in missing.iter().rev() {
+        canonical.push(name);
+    }
+    Ok(canonical)
 }
 
 #[cfg(test)]
@@ -103,37 +316,159 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_invalid_network_config_is_config_error() {
+        let temp = tempdir().unwrap();
+        let network = NetworkConfig {
+            proxy: Some("not a url".to_string()),
+            ..Default::default()
+        };
+        let result = ExecutionContext::new(temp.path().to_path_buf(), 30, &network);
+        assert!(matches!(result, Err(AgentError::ConfigError(_))));
+    }
+
     #[test]
     fn test_resolve_path_rejects_absolute() {
         let temp = tempdir().unwrap();
-        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30);
+        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+            .unwrap();
         assert!(ctx.resolve_path("/etc/passwd").is_err());
     }
 
     #[test]
     fn test_resolve_path_rejects_traversal() {
         let temp = tempdir().unwrap();
-        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30);
+        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+            .unwrap();
         assert!(ctx.resolve_path("../../../etc/passwd").is_err());
     }
 
     #[test]
     fn test_resolve_path_accepts_relative() {
         let temp = tempdir().unwrap();
-        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30);
+        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+            .unwrap();
         let result = ctx.resolve_path("foo/bar.txt");
         assert!(result.is_ok());
         assert!(result.unwrap().starts_with(temp.path()));
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_rejects_symlink_escape() {
+        let temp = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(outside.path(), temp.path().join("link")).unwrap();
+        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+            .unwrap();
+
+        // Existing file behind the symlink
+        assert!(ctx.resolve_path("link/secret.txt").is_err());
+        // New file (and new directories) behind the symlink
+        assert!(ctx.resolve_path("link/new.txt").is_err());
+        assert!(ctx.resolve_path("link/a/b/new.txt").is_err());
+    }
+
+    #[cfg(unix)]
     #[test]
-    fn test_truncate_output() {
-        let short = "hello".to_string();
-        assert_eq!(ExecutionContext::truncate_output(short.clone()), short);
-
-        let long = "x".repeat(MAX_OUTPUT_SIZE + 100);
-        let truncated = ExecutionContext::truncate_output(long);
-        assert!(truncated.len() <= MAX_OUTPUT_SIZE + 50);
-        assert!(truncated.ends_with("(output truncated)"));
+    fn test_resolve_path_rejects_dangling_symlink_escape() {
+        let temp = tempdir().unwrap();
+        let outside = tempdir().unwrap();
+        std::os::unix::fs::symlink(
+            outside.path().join("missing.txt"),
+            temp.path().join("dangling"),
+        )
+        .unwrap();
+        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+            .unwrap();
+
+        assert!(ctx.resolve_path("dangling").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_allows_symlink_inside() {
+        let temp = tempdir().unwrap();
+        std::fs::create_dir(temp.path().join("real")).unwrap();
+        std::os::unix::fs::symlink(temp.path().join("real"), temp.path().join("alias")).unwrap();
+        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+            .unwrap();
+
+        let resolved = ctx.resolve_path("alias/new.txt").unwrap();
+        assert_eq!(resolved, temp.path().join("alias/new.txt"));
+    }
+
+    #[test]
+    fn test_resolve_path_allowed_dirs() {
+        let temp = tempdir().unwrap();
+        let shared = tempdir().unwrap();
+        let scratch = tempdir().unwrap();
+        let mut ctx =
+            ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+                .unwrap();
+        ctx.set_allowed_paths(&[
+            AllowedPath {
+                path: shared.path().to_string_lossy().into_owned(),
+                access: PathAccess::Read,
+            },
+            AllowedPath {
+                path: scratch.path().to_string_lossy().into_owned(),
+                access: PathAccess::ReadWrite,
+            },
+        ]);
+
+        let shared_file = shared.path().join("lib.ts").to_string_lossy().into_owned();
+        assert!(ctx.resolve_path(&shared_file).is_ok());
+        let err = ctx.resolve_write_path(&shared_file).unwrap_err();
+        assert!(err.to_string().contains("read-only"));
+
+        let scratch_file = scratch.path().join("out/notes.md");
+        assert_eq!(
+            ctx.resolve_write_path(&scratch_file.to_string_lossy())
+                .unwrap(),
+            scratch_file
+        );
+
+        // Other absolute paths are still rejected, and relative paths still work
+        let err = ctx.resolve_path("/etc/passwd").unwrap_err();
+        assert!(err.to_string().contains("allowed directory"));
+        assert!(ctx.resolve_write_path("src/main.rs").is_ok());
+    }
+
+    #[test]
+    fn test_resolve_path_workspace_roots() {
+        let workspace = tempdir().unwrap();
+        let frontend = workspace.path().join("frontend");
+        let backend = workspace.path().join("backend");
+        std::fs::create_dir_all(&frontend).unwrap();
+        std::fs::create_dir_all(backend.join("src")).unwrap();
+        let mut ctx =
+            ExecutionContext::new(frontend.clone(), 30, &NetworkConfig::default()).unwrap();
+        ctx.set_workspace_roots(&[
+            WorkspaceRoot {
+                name: "backend".to_string(),
+                path: "../backend".to_string(),
+            },
+            WorkspaceRoot {
+                name: "infra".to_string(),
+                path: "../missing".to_string(),
+            },
+        ]);
+
+        let resolved = ctx.resolve_write_path("backend/src/main.rs").unwrap();
+        assert_eq!(
+            resolved,
+            backend.canonicalize().unwrap().join("src/main.rs")
+        );
+        // Other paths stay in the project; roots that do not exist are ignored
+        assert_eq!(
+            ctx.resolve_path("src/App.tsx").unwrap(),
+            frontend.join("src/App.tsx")
+        );
+        assert_eq!(
+            ctx.resolve_path("infra/main.tf").unwrap(),
+            frontend.join("infra/main.tf")
+        );
     }
 }
@@ -0,0 +1,132 @@
+//! Check commands (e.g. `cargo check`) run after a batch of edits, so compile
+//! errors reach the model with the edit results instead of at the end of a run.
+
+use std::path::PathBuf;
+
+use super::shell::ShellExecutor;
+use crate::agent::error::AgentError;
+use crate::config::DiagnosticsConfig;
+
+/// Distinct check commands for the extensions of `paths`, in edit order.
+fn commands_for<'a>(config: &'a DiagnosticsConfig, paths: &[PathBuf]) -> Vec<&'a str> {
+    let mut commands = Vec::new();
+    for path in paths {
+        let Some(extension) = path.extension().and_then(|ext| ext.to_str()) else {
+            continue;
+        };
+        if let Some(command) = config.commands.get(&extension.to_lowercase()) {
+            if !commands.contains(&command.as_str()) {
+                commands.push(command.as_str());
+            }
+        }
+    }
+    commands
+}
+
+fn truncate_lines(output: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    if lines.len() <= max_lines {
+        return output.trim_end().to_string();
+    }
+    format!(
+        "{}\n... ({} more lines)",
+        lines[..max_lines].join("\n"),
+        lines.len() - max_lines
+    )
+}
+
+/// Runs the checks for the edited `paths` and describes the failures, None if all pass.
+pub(super) async fn run_checks(
+    shell: &ShellExecutor,
+    config: &DiagnosticsConfig,
+    paths: &[PathBuf],
+) -> Option<String> {
+    let mut failures = Vec::new();
+    for command in commands_for(config, paths) {
+        let input = serde_json::json!({ "command": command, "timeout": config.timeout_secs });
+        match shell.execute(input, None).await {
+            Ok(_) => {}
+            Err(AgentError::CommandTimeout { secs, .. }) => {
+                failures.push(format!("`{}` timed out after {}s", command, secs))
+            }
+            Err(e) => failures.push(format!(
+                "`{}` failed:\n{}",
+                command,
+                truncate_lines(&e.to_string(), config.max_lines as usize)
+            )),
+        }
+    }
+
+    if failures.is_empty() {
+        None
+    } else {
+        Some(format!(
+            "Diagnostics after these edits:\n{}",
+            failures.join("\n\n")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn config(commands: &[(&str, &str)]) -> DiagnosticsConfig {
+        DiagnosticsConfig {
+            commands: commands
+                .iter()
+                .map(|(ext, command)| (ext.to_string(), command.to_string()))
+                .collect::<HashMap<_, _>>(),
+            ..DiagnosticsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_commands_for_dedupes_by_command() {
+        let config = config(&[
+            ("ts", "npx tsc --noEmit"),
+            ("tsx", "npx tsc --noEmit"),
+            ("rs", "cargo check"),
+        ]);
+        let paths = [
+            PathBuf::from("src/App.tsx"),
+            PathBuf::from("src/api.ts"),
+            PathBuf::from("README.md"),
+            PathBuf::from("src-tauri/src/lib.RS"),
+        ];
+        assert_eq!(
+            commands_for(&config, &paths),
+            vec!["npx tsc --noEmit", "cargo check"]
+        );
+    }
+
+    #[test]
+    fn test_truncate_lines() {
+        assert_eq!(truncate_lines("a\nb\n", 5), "a\nb");
+        assert_eq!(truncate_lines("a\nb\nc\nd", 2), "a\nb\n... (2 more lines)");
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_run_checks_reports_failures_only() {
+        let temp = tempfile::tempdir().unwrap();
+        let shell = ShellExecutor::new(
+            temp.path().to_path_buf(),
+            std::time::Duration::from_secs(30),
+        );
+        let paths = [PathBuf::from("main.rs"), PathBuf::from("index.ts")];
+
+        let passing = config(&[("rs", "true")]);
+        assert!(run_checks(&shell, &passing, &paths).await.is_none());
+
+        let failing = config(&[
+            ("rs", "true"),
+            ("ts", "echo 'error TS2322: bad type'; exit 2"),
+        ]);
+        let note = run_checks(&shell, &failing, &paths).await.unwrap();
+        assert!(note.starts_with("Diagnostics after these edits:"));
+        assert!(note.contains("error TS2322: bad type"));
+        assert!(!note.contains("`true`"));
+    }
+}
@@ -0,0 +1,233 @@
+//! Compose service access through the docker CLI: listing containers, running
+//! commands in a service and tailing its logs. Disabled unless the project's
+//! `[docker]` section enables it, and limited to its allowed_services.
+
+use std::io::ErrorKind;
+
+use tokio::process::Command;
+use tokio::time::timeout;
+
+use super::context::{ExecutionContext, MAX_OUTPUT_SIZE};
+use super::output;
+use crate::agent::error::AgentError;
+use crate::agent::tools::types::{DockerAction, DockerInput};
+use crate::config::{ConfigService, DockerConfig};
+
+const DEFAULT_LOG_LINES: u32 = 200;
+const MAX_LOG_LINES: u32 = 2000;
+
+pub async fn execute(
+    ctx: &ExecutionContext,
+    input: serde_json::Value,
+) -> Result<String, AgentError> {
+    let input: DockerInput = serde_json::from_value(input)
+        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
+    let config = ConfigService::load_project_config(&ctx.working_dir)
+        .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+
+    let mut command = Command::new("docker");
+    command
+        .args(compose_args(&config.docker, &input)?)
+        .current_dir(&ctx.working_dir)
+        .kill_on_drop(true);
+
+    let output = match timeout(ctx.timeout, command.output()).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) if e.kind() == ErrorKind::NotFound => {
+            return Err(AgentError::ToolExecutionError(
+                "docker is not installed or not on PATH".to_string(),
+            ))
+        }
+        Ok(Err(e)) => {
+            return Err(AgentError::ToolExecutionError(format!(
+                "Failed to run docker: {}",
+                e
+            )))
+        }
+        Err(_) => return Err(AgentError::ToolTimeout),
+    };
+
+    // compose writes logs and exec output to both streams
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let combined = match (stdout.is_empty(), stderr.is_empty()) {
+        (_, true) => stdout.to_string(),
+        (true, false) => stderr.to_string(),
+        (false, false) => format!("{}\n{}", stdout, stderr),
+    };
+    let combined = output::clean(&combined, MAX_OUTPUT_SIZE);
+
+    if output.status.success() {
+        Ok(combined)
+    } else {
+        Err(AgentError::ToolExecutionError(format!(
+            "docker exited with code {}: {}",
+            output.status.code().unwrap_or(-1),
+            combined
+        )))
+    }
+}
+
+/// Arguments for `docker`, after checking the tool is enabled and the service allowed.
+fn compose_args(config: &DockerConfig, input: &DockerInput) -> Result<Vec<String>, AgentError> {
+    if !config.enabled {
+        return Err(AgentError::InvalidToolInput(
+            "The docker tool is disabled. Set enabled = true in the [docker] section of .devflow/config.toml"
+                .to_string(),
+        ));
+    }
+
+    let mut args = vec!["compose".to_string()];
+    if let Some(file) = &config.compose_file {
+        args.push("-f".to_string());
+        args.push(file.clone());
+    }
+
+    match input.action {
+        DockerAction::Ps => {
+            args.push("ps".to_string());
+            args.push("--all".to_string());
+        }
+        DockerAction::Exec => {
+            let service = allowed_service(config, input)?;
+            let command = input
+                .command
+                .as_deref()
+                .filter(|c| !c.trim().is_empty())
+                .ok_or_else(|| {
+                    AgentError::InvalidToolInput("exec requires a command".to_string())
+                })?;
+            args.extend(["exec", "-T", service, "sh", "-c", command].map(String::from));
+        }
+        DockerAction::Logs => {
+            let service = allowed_service(config, input)?;
+            let tail = input
+                .tail
+                .unwrap_or(DEFAULT_LOG_LINES)
+                .min(MAX_LOG_LINES)
+                .to_string();
+            args.extend(["logs", "--no-color", "--tail", tail.as_str(), service].map(String::from));
+        }
+    }
+    Ok(args)
+}
+
+fn allowed_service<'a>(
+    config: &DockerConfig,
+    input: &'a DockerInput,
+) -> Result<&'a str, AgentError> {
+    let service = input
+        .service
+        .as_deref()
+        .ok_or_else(|| AgentError::InvalidToolInput("A service is required".to_string()))?;
+    if config
+        .allowed_services
+        .iter()
+        .any(|allowed| allowed == service)
+    {
+        return Ok(service);
+    }
+
+    let allowed = if config.allowed_services.is_empty() {
+        "none (add them to allowed_services in [docker])".to_string()
+    } else {
+        config.allowed_services.join(", ")
+    };
+    Err(AgentError::InvalidToolInput(format!(
+        "Service '{}' is not allowed. Allowed services: {}",
+        service, allowed
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DockerConfig {
+        DockerConfig {
+            enabled: true,
+            compose_file: None,
+            allowed_services: vec!["app".to_string()],
+        }
+    }
+
+    fn input(action: DockerAction, service: Option<&str>, command: Option<&str>) -> DockerInput {
+        DockerInput {
+            action,
+            service: service.map(String::from),
+            command: command.map(String::from),
+            tail: None,
+        }
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let err = compose_args(
+            &DockerConfig::default(),
+            &input(DockerAction::Ps, None, None),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("disabled"));
+    }
+
+    #[test]
+    fn test_exec_args() {
+        let config = DockerConfig {
+            compose_file: Some("docker/compose.dev.yml".to_string()),
+            ..config()
+        };
+        let args = compose_args(
+            &config,
+            &input(DockerAction::Exec, Some("app"), Some("cargo test")),
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            [
+                "compose",
+                "-f",
+                "docker/compose.dev.yml",
+                "exec",
+                "-T",
+                "app",
+                "sh",
+                "-c",
+                "cargo test"
+            ]
+        );
+
+        let err = compose_args(&config, &input(DockerAction::Exec, Some("app"), None)).unwrap_err();
+        assert!(err.to_string().contains("requires a command"));
+    }
+
+    #[test]
+    fn test_service_allowlist() {
+        let err = compose_args(
+            &config(),
+            &input(DockerAction::Exec, Some("db"), Some("psql")),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Service 'db' is not allowed. Allowed services: app"));
+
+        let err = compose_args(&config(), &input(DockerAction::Logs, None, None)).unwrap_err();
+        assert!(err.to_string().contains("service is required"));
+
+        // Listing containers needs no service
+        assert!(compose_args(&config(), &input(DockerAction::Ps, None, None)).is_ok());
+    }
+
+    #[test]
+    fn test_logs_tail_is_capped() {
+        let logs = DockerInput {
+            tail: Some(1_000_000),
+            ..input(DockerAction::Logs, Some("app"), None)
+        };
+        let args = compose_args(&config(), &logs).unwrap();
+        assert_eq!(
+            args,
+            ["compose", "logs", "--no-color", "--tail", "2000", "app"]
+        );
+    }
+}
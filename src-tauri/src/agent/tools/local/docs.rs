@@ -0,0 +1,264 @@
+//! Package documentation lookup: docs.rs for crates, the npm and PyPI
+//! registries for READMEs, and MDN for web platform APIs.
+
+use reqwest::Url;
+use scraper::{Html, Selector};
+
+use super::context::ExecutionContext;
+use super::state::SessionState;
+use super::web;
+use crate::agent::error::AgentError;
+use crate::agent::tools::types::{DocsEcosystem, LookupDocsInput};
+
+const DOCS_RS: &str = "https://docs.rs";
+const NPM_REGISTRY: &str = "https://registry.npmjs.org";
+const PYPI: &str = "https://pypi.org/pypi";
+const MDN: &str = "https://developer.mozilla.org";
+
+/// Returned docs are cut to this size
+const MAX_DOC_BYTES: usize = 40 * 1024;
+
+pub async fn lookup(
+    ctx: &ExecutionContext,
+    session: &SessionState,
+    input: serde_json::Value,
+) -> Result<String, AgentError> {
+    let input: LookupDocsInput = serde_json::from_value(input)
+        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
+    validate_package(&input.package)?;
+    let symbol = input.symbol.as_deref().filter(|s| !s.trim().is_empty());
+    let version = input.version.as_deref().unwrap_or("latest");
+
+    let (url, docs) = match input.ecosystem {
+        DocsEcosystem::Crate => {
+            let crate_root = format!(
+                "{}/{}/{}/{}/",
+                DOCS_RS,
+                input.package,
+                version,
+                input.package.replace('-', "_")
+            );
+            let url = match symbol {
+                Some(symbol) => {
+                    let index =
+                        cached_fetch(ctx, session, &format!("{}all.html", crate_root)).await?;
+                    match find_rustdoc_item(&index, symbol) {
+                        Some(path) => format!("{}{}", crate_root, path),
+                        None => {
+                            return Ok(format!(
+                                "No item named '{}' in {} {}. Check the name, or look it up without a symbol for the crate overview.",
+                                symbol, input.package, version
+                            ))
+                        }
+                    }
+                }
+                None => crate_root,
+            };
+            let page = cached_fetch(ctx, session, &url).await?;
+            (url, web::html_to_markdown(&page))
+        }
+        DocsEcosystem::Npm => {
+            let url = format!("{}/{}", NPM_REGISTRY, input.package.replace('/', "%2F"));
+            let readme = registry_readme(ctx, session, &url, &["readme"]).await?;
+            (url, section_or_all(&readme, symbol))
+        }
+        DocsEcosystem::Pypi => {
+            let url = match input.version.as_deref() {
+                Some(version) => format!("{}/{}/{}/json", PYPI, input.package, version),
+                None => format!("{}/{}/json", PYPI, input.package),
+            };
+            let readme = registry_readme(ctx, session, &url, &["info", "description"]).await?;
+            (url, section_or_all(&readme, symbol))
+        }
+        DocsEcosystem::Mdn => {
+            let query = symbol.unwrap_or(&input.package);
+            let url = mdn_page_url(ctx, query).await?;
+            let page = cached_fetch(ctx, session, &url).await?;
+            (url, web::html_to_markdown(&page))
+        }
+    };
+
+    if docs.trim().is_empty() {
+        return Ok(format!("No documentation found at {}", url));
+    }
+    Ok(web::truncate_page(
+        format!("Source: {}\n\n{}", url, docs),
+        MAX_DOC_BYTES,
+    ))
+}
+
+/// Package names go into URL paths, so only registry-valid characters are allowed.
+fn validate_package(package: &str) -> Result<(), AgentError> {
+    let valid = !package.is_empty()
+        && !package.contains("..")
+        && package
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '@' | '/'));
+    if valid {
+        Ok(())
+    } else {
+        Err(AgentError::InvalidToolInput(format!(
+            "Invalid package name: '{}'",
+            package
+        )))
+    }
+}
+
+/// Fetches the raw body of `url` through the session's web cache. Keys are
+/// prefixed so they never collide with web_fetch's converted pages.
+async fn cached_fetch(
+    ctx: &ExecutionContext,
+    session: &SessionState,
+    url: &str,
+) -> Result<String, AgentError> {
+    let key = format!("docs:{}", url);
+    if let Some(raw) = session.get_cached_page(&key).await {
+        return Ok(raw);
+    }
+    let raw = web::fetch_raw(ctx, url).await?;
+    session.cache_page(key, raw.clone()).await;
+    Ok(raw)
+}
+
+/// README markdown at `pointer` (a JSON key path) in a registry's package metadata.
+async fn registry_readme(
+    ctx: &ExecutionContext,
+    session: &SessionState,
+    url: &str,
+    pointer: &[&str],
+) -> Result<String, AgentError> {
+    let body = cached_fetch(ctx, session, url).await?;
+    let metadata: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| AgentError::ToolExecutionError(format!("Invalid registry response: {}", e)))?;
+    Ok(pointer
+        .iter()
+        .try_fold(&metadata, |value, key| value.get(key))
+        .and_then(|value| value.as_str())
+        .unwrap_or_default()
+        .to_string())
+}
+
+async fn mdn_page_url(ctx: &ExecutionContext, query: &str) -> Result<String, AgentError> {
+    let search = Url::parse_with_params(
+        &format!("{}/api/v1/search", MDN),
+        &[("q", query), ("locale", "en-US")],
+    )
+    .map_err(|e| AgentError::InvalidToolInput(format!("Invalid query: {}", e)))?;
+
+    let body = web::fetch_raw(ctx, search.as_str()).await?;
+    let results: serde_json::Value = serde_json::from_str(&body)
+        .map_err(|e| AgentError::ToolExecutionError(format!("Invalid MDN response: {}", e)))?;
+    results["documents"][0]["mdn_url"]
+        .as_str()
+        .map(|path| format!("{}{}", MDN, path))
+        .ok_or_else(|| AgentError::ToolExecutionError(format!("No MDN page found for '{}'", query)))
+}
+
+/// Relative path of the rustdoc page for `symbol`, looked up in the crate's
+/// `all.html` index. Matches the full path (`de::Deserialize`) or the item name.
+fn find_rustdoc_item(all_html: &str, symbol: &str) -> Option<String> {
+    let document = Html::parse_document(all_html);
+    let selector = Selector::parse("a[href]").ok()?;
+    let symbol = symbol.trim_start_matches("::");
+
+    let mut by_name = None;
+    for link in document.select(&selector) {
+        let href = link.value().attr("href")?;
+        if !href.ends_with(".html") || href.contains("://") {
+            continue;
+        }
+        let text: String = link.text().collect();
+        if text == symbol {
+            return Some(href.to_string());
+        }
+        if by_name.is_none() && text.rsplit("::").next() == Some(symbol) {
+            by_name = Some(href.to_string());
+        }
+    }
+    by_name
+}
+
+fn section_or_all(markdown: &str, symbol: Option<&str>) -> String {
+    symbol
+        .and_then(|symbol| extract_section(markdown, symbol))
+        .unwrap_or_else(|| markdown.to_string())
+}
+
+/// The markdown section whose heading mentions `symbol`, up to the next
+/// heading of the same or a higher level.
+fn extract_section(markdown: &str, symbol: &str) -> Option<String> {
+    let symbol = symbol.to_lowercase();
+    let lines: Vec<&str> = markdown.lines().collect();
+    let heading_level = |line: &str| {
+        let level = line.chars().take_while(|&c| c == '#').count();
+        (level > 0 && line[level..].starts_with(' ')).then_some(level)
+    };
+
+    let start = lines
+        .iter()
+        .position(|line| heading_level(line).is_some() && line.to_lowercase().contains(&symbol))?;
+    let level = heading_level(lines[start])?;
+    let end = lines[start + 1..]
+        .iter()
+        .position(|line| heading_level(line).is_some_and(|l| l <= level))
+        .map(|offset| start + 1 + offset)
+        .unwrap_or(lines.len());
+
+    Some(lines[start..end].join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_rustdoc_item() {
+        let all_html = r#"<html><body>
+            <h3>Structs</h3>
+            <ul>
+              <li><a href="struct.Client.html">Client</a></li>
+              <li><a href="blocking/struct.Client.html">blocking::Client</a></li>
+            </ul>
+            <h3>Traits</h3>
+            <ul><li><a href="de/trait.Deserialize.html">de::Deserialize</a></li></ul>
+            <a href="https://example.com/x.html">Client</a>
+        </body></html>"#;
+
+        assert_eq!(
+            find_rustdoc_item(all_html, "Client").as_deref(),
+            Some("struct.Client.html")
+        );
+        assert_eq!(
+            find_rustdoc_item(all_html, "blocking::Client").as_deref(),
+            Some("blocking/struct.Client.html")
+        );
+        assert_eq!(
+            find_rustdoc_item(all_html, "Deserialize").as_deref(),
+            Some("de/trait.Deserialize.html")
+        );
+        assert!(find_rustdoc_item(all_html, "Missing").is_none());
+    }
+
+    #[test]
+    fn test_extract_section() {
+        let readme = "# lib\n\nIntro\n\n## API\n\n### parse(input)\n\nParses input.\n\n#### Options\n\nstrict\n\n### format(value)\n\nFormats.\n\n## License\n\nMIT";
+
+        let section = extract_section(readme, "parse").unwrap();
+        assert!(section.starts_with("### parse(input)"));
+        assert!(section.contains("#### Options"));
+        assert!(!section.contains("format(value)"));
+
+        assert!(extract_section(readme, "missing").is_none());
+        assert_eq!(section_or_all(readme, Some("missing")), readme);
+    }
+
+    #[test]
+    fn test_validate_package() {
+        assert!(validate_package("serde_json").is_ok());
+        assert!(validate_package("@types/node").is_ok());
+        assert!(validate_package("requests").is_ok());
+        assert!(validate_package("../etc").is_err());
+        assert!(validate_package("a?b=c").is_err());
+        assert!(validate_package("").is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Dry-run mode (`execution.dry_run`): calls that would change files or run
+//! commands are answered with what they would have done, a diff or the
+//! command, and nothing is executed. Inputs are still validated, so a call
+//! that would fail fails the same way.
+
+use std::path::Path;
+
+use tokio::fs;
+
+use super::context::ExecutionContext;
+use super::{file, scaffold};
+use crate::agent::error::AgentError;
+use crate::agent::tools::types::{BashInput, DockerInput, NotebookEditInput, ToolName};
+use crate::git::{diff_texts, unified_diff, FileDiff, FileStatus};
+
+/// Whether dry-run mode intercepts this call. Docker only runs commands with exec.
+pub(super) fn intercepts(tool: ToolName, input: &serde_json::Value) -> bool {
+    match tool {
+        ToolName::Bash
+        | ToolName::WriteFile
+        | ToolName::EditFile
+        | ToolName::MultiEdit
+        | ToolName::NotebookEdit => true,
+        ToolName::Docker => input.get("action").and_then(|a| a.as_str()) == Some("exec"),
+        // Listing the templates changes nothing
+        ToolName::Scaffold => input.get("template").is_some_and(|t| !t.is_null()),
+        _ => false,
+    }
+}
+
+/// The "would have executed" result for an intercepted call.
+pub(super) async fn preview(
+    ctx: &ExecutionContext,
+    tool: ToolName,
+    input: serde_json::Value,
+) -> Result<String, AgentError> {
+    let invalid =
+        |e: serde_json::Error| AgentError::InvalidToolInput(format!("Invalid input: {}", e));
+    match tool {
+        ToolName::Bash => {
+            let cwd = match input.get("cwd").and_then(|cwd| cwd.as_str()) {
+                Some(cwd) => ctx.resolve_write_path(cwd)?,
+                None => ctx.working_dir.clone(),
+            };
+            let input: BashInput = serde_json::from_value(input).map_err(invalid)?;
+            Ok(format!(
+                "Dry run, nothing was executed. Would have run in {}:\n{}",
+                cwd.display(),
+                input.command
+            ))
+        }
+        ToolName::NotebookEdit => {
+            let edit: NotebookEditInput = serde_json::from_value(input.clone()).map_err(invalid)?;
+            let path = ctx.resolve_write_path(&edit.path)?;
+            Ok(format!(
+                "Dry run, nothing was changed. Would have edited notebook {} with:\n{}",
+                path.display(),
+                serde_json::to_string_pretty(&input).unwrap_or_default()
+            ))
+        }
+        ToolName::Scaffold => {
+            let input = scaffold::parse(input)?;
+            let template = input.template.as_deref().unwrap_or_default();
+            let planned = scaffold::plan(ctx, template, &input)?;
+            Ok(format!(
+                "Dry run, nothing was changed. {}",
+                scaffold::describe(&planned, "Would have created")
+            ))
+        }
+        ToolName::Docker => {
+            let input: DockerInput = serde_json::from_value(input).map_err(invalid)?;
+            Ok(format!(
+                "Dry run, nothing was executed. Would have run in service {}:\n{}",
+                input.service.as_deref().unwrap_or("(none)"),
+                input.command.as_deref().unwrap_or_default()
+            ))
+        }
+        _ => {
+            let display = input
+                .get("path")
+                .and_then(|path| path.as_str())
+                .unwrap_or_default()
+                .to_string();
+            let (path, content) = file::planned_write(ctx, tool, input).await?;
+            let original = fs::read_to_string(&path).await.ok();
+            let diff = diff_texts(&display, original.as_deref(), Some(&content));
+            Ok(describe_write(&path, &diff))
+        }
+    }
+}
+
+fn describe_write(path: &Path, diff: &FileDiff) -> String {
+    if diff.hunks.is_empty() {
+        return format!(
+            "Dry run, nothing was changed. {} would be left unchanged",
+            path.display()
+        );
+    }
+    let verb = match diff.status {
+        FileStatus::Added => "created",
+        _ => "changed",
+    };
+    format!(
+        "Dry run, nothing was changed. {} would have been {}:\n{}",
+        path.display(),
+        verb,
+        unified_diff(diff)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NetworkConfig;
+
+    fn context(dir: &Path) -> ExecutionContext {
+        ExecutionContext::new(dir.to_path_buf(), 30, &NetworkConfig::default()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_edit_is_previewed_as_diff_without_writing() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "one\ntwo\nthree\n").unwrap();
+        let ctx = context(temp.path());
+
+        let output = preview(
+            &ctx,
+            ToolName::EditFile,
+            serde_json::json!({ "path": "a.txt", "old_text": "two", "new_text": "TWO" }),
+        )
+        .await
+        .unwrap();
+
+        assert!(output.contains("--- a/a.txt\n+++ b/a.txt\n"), "{}", output);
+        assert!(output.contains("\n-two\n+TWO\n"), "{}", output);
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("a.txt")).unwrap(),
+            "one\ntwo\nthree\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_new_file_and_command_previews() {
+        let temp = tempfile::tempdir().unwrap();
+        let ctx = context(temp.path());
+
+        let output = preview(
+            &ctx,
+            ToolName::WriteFile,
+            serde_json::json!({ "path": "new.txt", "content": "hello\n" }),
+        )
+        .await
+        .unwrap();
+        assert!(output.contains("would have been created"), "{}", output);
+        assert!(
+            output.contains("--- /dev/null\n+++ b/new.txt\n"),
+            "{}",
+            output
+        );
+        assert!(!temp.path().join("new.txt").exists());
+
+        let output = preview(
+            &ctx,
+            ToolName::Bash,
+            serde_json::json!({ "command": "rm -rf build" }),
+        )
+        .await
+        .unwrap();
+        assert!(output.ends_with("\nrm -rf build"), "{}", output);
+    }
+
+    #[tokio::test]
+    async fn test_failing_edit_fails_the_same_way() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::write(temp.path().join("a.txt"), "one\n").unwrap();
+
+        let result = preview(
+            &context(temp.path()),
+            ToolName::EditFile,
+            serde_json::json!({ "path": "a.txt", "old_text": "missing", "new_text": "x" }),
+        )
+        .await;
+        assert!(result.is_err());
+        assert!(!intercepts(
+            ToolName::Docker,
+            &serde_json::json!({ "action": "logs" })
+        ));
+    }
+}
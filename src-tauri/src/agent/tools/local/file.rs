@@ -1,22 +1,30 @@
-use std::collections::BinaryHeap;
-use std::path::PathBuf;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::path::{Component, Path, PathBuf};
 use std::time::SystemTime;
 
 use glob::glob as glob_match;
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 use tokio::fs;
 use walkdir::WalkDir;
 
 use super::context::ExecutionContext;
+use super::outline::file_index;
+use super::output::{floor_char_boundary, truncate_middle};
 use crate::agent::error::AgentError;
 use crate::agent::tools::types::{
-    EditFileInput, GlobInput, GrepInput, ListDirectoryInput, MultiEditInput, ReadFileInput,
-    WriteFileInput,
+    EditFileInput, GlobInput, GrepInput, GrepOutputMode, ListDirectoryInput, MultiEditInput,
+    ReadFileInput, ToolName, WriteFileInput,
 };
+use crate::git::{unified_diff, FileDiff, FileStatus};
 
 const MAX_GLOB_RESULTS: usize = 1000;
 const MAX_GREP_RESULTS: usize = 100;
+/// Files listed by grep in files_with_matches and count mode
+const MAX_GREP_FILES: usize = 1000;
 const BINARY_CHECK_SIZE: usize = 1024;
+/// Diffs returned by the edit tools are cut in the middle past this size
+const MAX_DIFF_BYTES: usize = 20_000;
 
 #[derive(Eq, PartialEq)]
 struct GlobEntry {
@@ -25,8 +33,11 @@ struct GlobEntry {
 }
 
 impl Ord for GlobEntry {
+    /// By mtime; of files modified at the same time, the first path is the greater
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.mtime.cmp(&other.mtime)
+        self.mtime
+            .cmp(&other.mtime)
+            .then_with(|| other.path.cmp(&self.path))
     }
 }
 
@@ -49,48 +60,226 @@ pub async fn read_file(
         .with_timeout("read file", fs::read_to_string(&path))
         .await?;
 
+    let (mut result, cut) = number_lines(
+        &input.path,
+        &content,
+        input.offset,
+        input.limit,
+        ctx.max_read_bytes,
+    );
+    if cut {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_lowercase)
+            .unwrap_or_default();
+        if let Some(index) = file_index(&content, &extension) {
+            result.push_str(&format!("\n\nIndex of {}:\n{}", input.path, index));
+        }
+    }
+    Ok(result)
+}
+
+/// `cat -n` style view of `content` from line `offset` (0-based), under a
+/// header with the file's size, its line count and, when only part of the
+/// file is shown, the range and where to read on. Lines past `max_bytes`
+/// are left out; the flag is set when that cut the view short.
+fn number_lines(
+    path: &str,
+    content: &str,
+    offset: Option<u32>,
+    limit: Option<u32>,
+    max_bytes: usize,
+) -> (String, bool) {
     let lines: Vec<&str> = content.lines().collect();
-    let offset = input.offset.unwrap_or(0) as usize;
-    let limit = input.limit.map(|l| l as usize).unwrap_or(lines.len());
+    let total = lines.len();
+    if total == 0 {
+        let empty = format!("[{}: empty, 0 lines, {} bytes]", path, content.len());
+        return (empty, false);
+    }
 
-    let result: String = lines
-        .into_iter()
-        .skip(offset)
-        .take(limit)
-        .collect::<Vec<_>>()
-        .join("\n");
+    let start = (offset.unwrap_or(0) as usize).min(total);
+    let end = limit.map_or(total, |limit| {
+        start.saturating_add(limit as usize).min(total)
+    });
+    if start == end {
+        let past_end = format!(
+            "[{}: no lines at offset {}; the file has {} lines, {} bytes]",
+            path,
+            offset.unwrap_or(0),
+            total,
+            content.len()
+        );
+        return (past_end, false);
+    }
 
-    Ok(result)
+    let mut body = String::new();
+    let mut shown = start;
+    let mut cut = false;
+    while shown < end {
+        let numbered = format!("\n{:>6}\t{}", shown + 1, lines[shown]);
+        if body.len() + numbered.len() > max_bytes {
+            if shown == start {
+                // A single line over the limit, e.g. minified code: keep its start
+                let cut = floor_char_boundary(&numbered, max_bytes);
+                body = format!(
+                    "{} ... ({} more bytes on this line)",
+                    &numbered[..cut],
+                    numbered.len() - cut
+                );
+                shown += 1;
+            }
+            cut = true;
+            break;
+        }
+        body.push_str(&numbered);
+        shown += 1;
+    }
+    let mut requested = Vec::new();
+    if let Some(offset) = offset {
+        requested.push(format!("offset {}", offset));
+    }
+    if let Some(limit) = limit {
+        requested.push(format!("limit {}", limit));
+    }
+    let requested = if requested.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", requested.join(", "))
+    };
+
+    let header = if start == 0 && shown == total && !cut {
+        format!("[{}: {} lines, {} bytes]", path, total, content.len())
+    } else {
+        let read_limit = if cut {
+            format!(
+                "; cut at the {} byte read limit (execution.max_read_bytes)",
+                max_bytes
+            )
+        } else {
+            String::new()
+        };
+        let more = match (shown < total, cut) {
+            (true, true) => format!(
+                "; {} more, read on in chunks with offset {} and limit {}",
+                total - shown,
+                shown,
+                shown - start
+            ),
+            (true, false) => format!("; {} more, read on with offset {}", total - shown, shown),
+            (false, _) => String::new(),
+        };
+        format!(
+            "[{}: lines {}-{} of {}{}, {} bytes{}{}]",
+            path,
+            start + 1,
+            shown,
+            total,
+            requested,
+            content.len(),
+            read_limit,
+            more
+        )
+    };
+
+    (header + &body, cut)
 }
 
 pub async fn write_file(
     ctx: &ExecutionContext,
     input: serde_json::Value,
-) -> Result<String, AgentError> {
+) -> Result<(), AgentError> {
     let input: WriteFileInput = serde_json::from_value(input)
         .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
 
-    let path = ctx.resolve_path(&input.path)?;
+    let (path, content) = plan_write(ctx, input)?;
 
-    if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent).await?;
-        }
-    }
+    ctx.with_timeout("write file", ctx.write(&path, &content))
+        .await
+}
 
-    ctx.with_timeout("write file", fs::write(&path, &input.content))
-        .await?;
-    Ok(format!("Successfully wrote to {}", path.display()))
+pub async fn edit_file(ctx: &ExecutionContext, input: serde_json::Value) -> Result<(), AgentError> {
+    let input: EditFileInput = serde_json::from_value(input)
+        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
+
+    let (path, new_content) = plan_edit(ctx, &input).await?;
+
+    ctx.with_timeout("write file", ctx.write(&path, &new_content))
+        .await
 }
 
-pub async fn edit_file(
+pub async fn multi_edit(
     ctx: &ExecutionContext,
     input: serde_json::Value,
-) -> Result<String, AgentError> {
-    let input: EditFileInput = serde_json::from_value(input)
+) -> Result<(), AgentError> {
+    let input: MultiEditInput = serde_json::from_value(input)
         .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
 
-    let path = ctx.resolve_path(&input.path)?;
+    let (path, result) = plan_multi_edit(ctx, &input).await?;
+
+    ctx.with_timeout("write file", ctx.write(&path, &result))
+        .await
+}
+
+/// The result of a write or edit: what changed, as a unified diff.
+pub fn describe_change(diff: &FileDiff) -> String {
+    if diff.hunks.is_empty() {
+        return format!("{} is unchanged", diff.path);
+    }
+    let verb = match diff.status {
+        FileStatus::Added => "Created",
+        _ => "Edited",
+    };
+    format!(
+        "{} {}:\n{}",
+        verb,
+        diff.path,
+        truncate_middle(&unified_diff(diff), MAX_DIFF_BYTES)
+    )
+}
+
+/// The file a write_file, edit_file or multi_edit call would write and the
+/// content it would write, without writing it. Fails where the call would.
+pub async fn planned_write(
+    ctx: &ExecutionContext,
+    tool: ToolName,
+    input: serde_json::Value,
+) -> Result<(PathBuf, String), AgentError> {
+    let invalid =
+        |e: serde_json::Error| AgentError::InvalidToolInput(format!("Invalid input: {}", e));
+    match tool {
+        ToolName::WriteFile => plan_write(ctx, serde_json::from_value(input).map_err(invalid)?),
+        ToolName::EditFile => {
+            plan_edit(ctx, &serde_json::from_value(input).map_err(invalid)?).await
+        }
+        ToolName::MultiEdit => {
+            plan_multi_edit(ctx, &serde_json::from_value(input).map_err(invalid)?).await
+        }
+        _ => Err(AgentError::ToolExecutionError(format!(
+            "{} does not write files",
+            tool.as_str()
+        ))),
+    }
+}
+
+fn plan_write(
+    ctx: &ExecutionContext,
+    input: WriteFileInput,
+) -> Result<(PathBuf, String), AgentError> {
+    let path = ctx.resolve_write_path(&input.path)?;
+
+    let content = match &ctx.attribution {
+        Some(attribution) => attribution.apply_to_file(&path, &input.content),
+        None => input.content,
+    };
+    Ok((path, content))
+}
+
+async fn plan_edit(
+    ctx: &ExecutionContext,
+    input: &EditFileInput,
+) -> Result<(PathBuf, String), AgentError> {
+    let path = ctx.resolve_write_path(&input.path)?;
 
     let content = ctx
         .with_timeout("read file", fs::read_to_string(&path))
@@ -107,20 +296,14 @@ pub async fn edit_file(
     } else {
         content.replacen(&input.old_text, &input.new_text, 1)
     };
-
-    ctx.with_timeout("write file", fs::write(&path, &new_content))
-        .await?;
-    Ok(format!("Successfully edited {}", path.display()))
+    Ok((path, new_content))
 }
 
-pub async fn multi_edit(
+async fn plan_multi_edit(
     ctx: &ExecutionContext,
-    input: serde_json::Value,
-) -> Result<String, AgentError> {
-    let input: MultiEditInput = serde_json::from_value(input)
-        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
-
-    let path = ctx.resolve_path(&input.path)?;
+    input: &MultiEditInput,
+) -> Result<(PathBuf, String), AgentError> {
+    let path = ctx.resolve_write_path(&input.path)?;
 
     let content = ctx
         .with_timeout("read file", fs::read_to_string(&path))
@@ -136,14 +319,7 @@ pub async fn multi_edit(
         }
         result = result.replacen(&edit.old_text, &edit.new_text, 1);
     }
-
-    ctx.with_timeout("write file", fs::write(&path, &result))
-        .await?;
-    Ok(format!(
-        "Successfully applied {} edits to {}",
-        input.edits.len(),
-        path.display()
-    ))
+    Ok((path, result))
 }
 
 pub async fn list_directory(
@@ -185,54 +361,169 @@ pub async fn glob(ctx: &ExecutionContext, input: serde_json::Value) -> Result<St
         ctx.working_dir.clone()
     };
 
-    let pattern = base_path.join(&input.pattern);
-    let pattern_str = pattern.to_string_lossy();
+    let excludes = input
+        .exclude
+        .iter()
+        .flat_map(|pattern| expand_braces(pattern.strip_prefix('!').unwrap_or(pattern)))
+        .map(|pattern| glob::Pattern::new(&pattern))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid exclude pattern: {}", e)))?;
+    let exclude_options = glob::MatchOptions {
+        require_literal_separator: true,
+        ..Default::default()
+    };
 
-    // Use bounded heap to keep only the newest MAX_GLOB_RESULTS files
-    let mut heap: BinaryHeap<GlobEntry> = BinaryHeap::new();
+    // Min-heap on mtime, so the oldest entry is dropped once it holds more
+    // than MAX_GLOB_RESULTS files
+    let mut heap: BinaryHeap<Reverse<GlobEntry>> = BinaryHeap::new();
+    let mut seen = HashSet::new();
+    let mut matches = 0;
+    let canonical_working = ctx.working_dir.canonicalize()?;
+
+    for pattern in expand_braces(&input.pattern) {
+        check_glob_pattern(&pattern)?;
+        let pattern = base_path.join(&pattern);
+        let paths = glob_match(&pattern.to_string_lossy())
+            .map_err(|e| AgentError::InvalidToolInput(format!("Invalid glob pattern: {}", e)))?;
+        for path in paths.flatten() {
+            if !path.is_file() || seen.contains(&path) {
+                continue;
+            }
+            // Symlinks can still lead out of the search path
+            let inside = path.canonicalize().is_ok_and(|canonical| {
+                canonical.starts_with(&canonical_working)
+                    || ctx
+                        .allowed_dirs
+                        .iter()
+                        .any(|(dir, _)| canonical.starts_with(dir))
+                    || ctx
+                        .workspace_roots
+                        .iter()
+                        .any(|(_, root)| canonical.starts_with(root))
+            });
+            if !inside {
+                continue;
+            }
+            let relative = path.strip_prefix(&base_path).unwrap_or(&path);
+            if excludes
+                .iter()
+                .any(|exclude| exclude.matches_path_with(relative, exclude_options))
+            {
+                continue;
+            }
 
-    for entry in glob_match(&pattern_str)
-        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid glob pattern: {}", e)))?
-    {
-        match entry {
-            Ok(path) if path.is_file() => {
-                let mtime = path
-                    .metadata()
-                    .and_then(|m| m.modified())
-                    .unwrap_or(SystemTime::UNIX_EPOCH);
-
-                heap.push(GlobEntry { path, mtime });
-
-                // Keep heap bounded
-                if heap.len() > MAX_GLOB_RESULTS {
-                    // Remove oldest entry by popping and keeping newest
-                    let entries: Vec<_> = heap.drain().collect();
-                    for entry in entries.into_iter().take(MAX_GLOB_RESULTS) {
-                        heap.push(entry);
-                    }
-                }
+            let mtime = path
+                .metadata()
+                .and_then(|m| m.modified())
+                .unwrap_or(SystemTime::UNIX_EPOCH);
+            seen.insert(path.clone());
+            matches += 1;
+            heap.push(Reverse(GlobEntry { path, mtime }));
+            if heap.len() > MAX_GLOB_RESULTS {
+                heap.pop();
             }
-            Ok(_) => {}
-            Err(_) => {}
         }
     }
 
-    let results: Vec<String> = heap
+    // Ascending in Reverse is newest first
+    let mut results: Vec<String> = heap
         .into_sorted_vec()
         .into_iter()
-        .rev() // Newest first
-        .filter_map(|entry| {
+        .map(|Reverse(entry)| {
             entry
                 .path
                 .strip_prefix(&ctx.working_dir)
-                .ok()
-                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or(&entry.path)
+                .to_string_lossy()
+                .to_string()
         })
         .collect();
+    if matches > results.len() {
+        results.push(format!(
+            "... ({} newest of {} matches shown; narrow the pattern or add exclude patterns)",
+            results.len(),
+            matches
+        ));
+    }
 
     Ok(results.join("\n"))
 }
 
+/// Patterns are relative to the search path and may not climb out of it;
+/// other directories are searched by passing them as `path`.
+fn check_glob_pattern(pattern: &str) -> Result<(), AgentError> {
+    let path = Path::new(pattern);
+    if path.has_root() || pattern.starts_with(['/', '\\']) {
+        return Err(AgentError::InvalidToolInput(format!(
+            "Glob pattern '{}' must be relative; pass another directory as `path`",
+            pattern
+        )));
+    }
+    if path
+        .components()
+        .any(|component| matches!(component, Component::ParentDir | Component::Prefix(_)))
+    {
+        return Err(AgentError::InvalidToolInput(format!(
+            "Glob pattern '{}' may not contain '..'",
+            pattern
+        )));
+    }
+    Ok(())
+}
+
+/// Expands `{a,b}` alternatives, which the glob crate does not support, into
+/// one pattern per alternative, nested braces included. Braces without a
+/// comma or without a closing brace are kept as written.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let mut search_from = 0;
+    while let Some(open) = pattern[search_from..].find('{').map(|i| search_from + i) {
+        let mut depth = 0;
+        let mut close = None;
+        let mut commas = Vec::new();
+        for (i, c) in pattern[open..].char_indices() {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        close = Some(open + i);
+                        break;
+                    }
+                }
+                ',' if depth == 1 => commas.push(open + i),
+                _ => {}
+            }
+        }
+        let Some(close) = close else {
+            break;
+        };
+        if commas.is_empty() {
+            search_from = open + 1;
+            continue;
+        }
+
+        let mut bounds = vec![open];
+        bounds.extend(commas);
+        bounds.push(close);
+        let mut expanded = Vec::new();
+        for pair in bounds.windows(2) {
+            let alternative = format!(
+                "{}{}{}",
+                &pattern[..open],
+                &pattern[pair[0] + 1..pair[1]],
+                &pattern[close + 1..]
+            );
+            for pattern in expand_braces(&alternative) {
+                if !expanded.contains(&pattern) {
+                    expanded.push(pattern);
+                }
+            }
+        }
+        return expanded;
+    }
+    vec![pattern.to_string()]
+}
+
 fn is_binary(path: &std::path::Path) -> bool {
     if let Ok(mut file) = std::fs::File::open(path) {
         use std::io::Read;
@@ -254,15 +545,21 @@ pub async fn grep(ctx: &ExecutionContext, input: serde_json::Value) -> Result<St
         ctx.working_dir.clone()
     };
 
-    let regex = Regex::new(&input.pattern)
+    let regex = RegexBuilder::new(&input.pattern)
+        .case_insensitive(input.case_insensitive)
+        .multi_line(input.multiline)
+        .dot_matches_new_line(input.multiline)
+        .build()
         .map_err(|e| AgentError::InvalidToolInput(format!("Invalid regex: {}", e)))?;
 
-    let include_pattern = input.include.clone();
+    let before = input.before_context.or(input.context).unwrap_or(0);
+    let after = input.after_context.or(input.context).unwrap_or(0);
     let working_dir = ctx.working_dir.clone();
 
     // Use spawn_blocking for file I/O intensive operation
     let results = tokio::task::spawn_blocking(move || {
         let mut results = Vec::new();
+        let mut matches = 0;
 
         for entry in WalkDir::new(&base_path)
             .into_iter()
@@ -272,7 +569,7 @@ pub async fn grep(ctx: &ExecutionContext, input: serde_json::Value) -> Result<St
             let path = entry.path();
 
             // Apply include filter
-            if let Some(ref pattern) = include_pattern {
+            if let Some(ref pattern) = input.include {
                 let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
                 if !glob::Pattern::new(pattern)
                     .map(|p| p.matches(file_name))
@@ -287,23 +584,70 @@ pub async fn grep(ctx: &ExecutionContext, input: serde_json::Value) -> Result<St
                 continue;
             }
 
-            if let Ok(content) = std::fs::read_to_string(path) {
-                for (line_num, line) in content.lines().enumerate() {
-                    if regex.is_match(line) {
-                        let relative_path = path
-                            .strip_prefix(&working_dir)
-                            .unwrap_or(path)
-                            .to_string_lossy();
-                        results.push(format!("{}:{}:{}", relative_path, line_num + 1, line));
+            let Ok(content) = std::fs::read_to_string(path) else {
+                continue;
+            };
+            let matched = matched_lines(&regex, &content, input.multiline);
+            if matched.is_empty() {
+                continue;
+            }
+            let relative_path = path
+                .strip_prefix(&working_dir)
+                .unwrap_or(path)
+                .to_string_lossy();
+
+            match input.output_mode {
+                GrepOutputMode::FilesWithMatches => results.push(relative_path.to_string()),
+                GrepOutputMode::Count => {
+                    results.push(format!("{}:{}", relative_path, matched.len()));
+                }
+                GrepOutputMode::Content => {
+                    let lines: Vec<&str> = content.lines().collect();
+                    // Last line printed in this file, to merge overlapping context
+                    let mut printed: Option<usize> = None;
+                    for &line in &matched {
+                        let from = line.saturating_sub(before);
+                        let from = match printed {
+                            Some(last) if from <= last + 1 => last + 1,
+                            _ => {
+                                if (before > 0 || after > 0) && !results.is_empty() {
+                                    results.push("--".to_string());
+                                }
+                                from
+                            }
+                        };
+                        let to = (line + after).min(lines.len().saturating_sub(1));
+                        for n in from..=to {
+                            let separator = if matched.binary_search(&n).is_ok() {
+                                ':'
+                            } else {
+                                '-'
+                            };
+                            results.push(format!(
+                                "{}{}{}{}{}",
+                                relative_path,
+                                separator,
+                                n + 1,
+                                separator,
+                                lines.get(n).unwrap_or(&"")
+                            ));
+                        }
+                        printed = Some(printed.map_or(to, |last| last.max(to)));
 
-                        if results.len() >= MAX_GREP_RESULTS {
+                        matches += 1;
+                        if matches >= MAX_GREP_RESULTS {
                             results
                                 .push(format!("... (truncated at {} results)", MAX_GREP_RESULTS));
                             return results;
                         }
                     }
+                    continue;
                 }
             }
+            if results.len() >= MAX_GREP_FILES {
+                results.push(format!("... (truncated at {} files)", MAX_GREP_FILES));
+                return results;
+            }
         }
 
         results
@@ -317,3 +661,56 @@ pub async fn grep(ctx: &ExecutionContext, input: serde_json::Value) -> Result<St
         Ok(results.join("\n"))
     }
 }
+
+/// 0-based numbers of the lines `regex` matches in `content`, in order. In
+/// multiline mode a match covers every line from its start to its end.
+fn matched_lines(regex: &Regex, content: &str, multiline: bool) -> Vec<usize> {
+    if !multiline {
+        return content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| regex.is_match(line))
+            .map(|(n, _)| n)
+            .collect();
+    }
+
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_of = |offset: usize| line_starts.partition_point(|&start| start <= offset) - 1;
+    let mut matched: Vec<usize> = Vec::new();
+    for m in regex.find_iter(content) {
+        let last = line_of(m.end().saturating_sub(1).max(m.start()));
+        for line in line_of(m.start())..=last {
+            if matched.last().map_or(true, |&prev| line > prev) {
+                matched.push(line);
+            }
+        }
+    }
+    matched
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_braces() {
+        assert_eq!(
+            expand_braces("{src,tests}/**/*.{ts,tsx}"),
+            vec![
+                "src/**/*.ts",
+                "src/**/*.tsx",
+                "tests/**/*.ts",
+                "tests/**/*.tsx"
+            ]
+        );
+        assert_eq!(
+            expand_braces("a/{b,{c,d}/e}"),
+            vec!["a/b", "a/c/e", "a/d/e"]
+        );
+        assert_eq!(expand_braces("{x}/{y,z}"), vec!["{x}/y", "{x}/z"]);
+        assert_eq!(expand_braces("src/{lib"), vec!["src/{lib"]);
+        assert_eq!(expand_braces("*.{rs,rs}"), vec!["*.rs"]);
+    }
+}
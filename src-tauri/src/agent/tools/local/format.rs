@@ -0,0 +1,186 @@
+//! Runs the project's formatter on files the agent changed, so its edits pass
+//! format checks without a separate formatting step.
+
+use std::path::Path;
+
+use tokio::fs;
+use tokio::time::timeout;
+
+use super::context::ExecutionContext;
+use crate::config::FormattingConfig;
+
+/// Formatter stderr lines kept in the tool result
+const MAX_ERROR_LINES: usize = 20;
+
+const PRETTIER_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "mjs", "cjs", "ts", "tsx", "json", "css", "scss", "html", "md", "yaml", "yml",
+    "vue",
+];
+
+/// prettier's launcher: the .cmd shim on native Windows, the script elsewhere and inside WSL.
+fn prettier_bin(wsl: bool) -> &'static str {
+    if cfg!(windows) && !wsl {
+        "node_modules/.bin/prettier.cmd"
+    } else {
+        "node_modules/.bin/prettier"
+    }
+}
+
+/// Formatter for `extension` that the project visibly uses: rustfmt for Rust,
+/// a locally installed prettier, or black/ruff when pyproject.toml configures them.
+fn detect_formatter(working_dir: &Path, extension: &str, wsl: bool) -> Option<Vec<String>> {
+    let command: &[&str] = match extension {
+        "rs" => &["rustfmt", "--edition", "2021"],
+        ext if PRETTIER_EXTENSIONS.contains(&ext) => {
+            let prettier = working_dir.join(prettier_bin(wsl));
+            if !prettier.is_file() {
+                return None;
+            }
+            return Some(vec![
+                prettier.to_string_lossy().into_owned(),
+                "--write".to_string(),
+            ]);
+        }
+        "py" | "pyi" => {
+            let pyproject = std::fs::read_to_string(working_dir.join("pyproject.toml")).ok()?;
+            if pyproject.contains("[tool.black]") {
+                &["black", "--quiet"]
+            } else if pyproject.contains("[tool.ruff") {
+                &["ruff", "format", "--quiet"]
+            } else {
+                return None;
+            }
+        }
+        _ => return None,
+    };
+    Some(command.iter().map(|arg| arg.to_string()).collect())
+}
+
+/// Formats `path` and returns a note for the tool result when the formatter
+/// changed the file or failed. None when no formatter applies or nothing changed.
+pub(super) async fn format_file(
+    ctx: &ExecutionContext,
+    config: &FormattingConfig,
+    path: &Path,
+) -> Option<String> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let command = match config.commands.get(&extension) {
+        Some(command) => command.split_whitespace().map(String::from).collect(),
+        None => detect_formatter(&ctx.working_dir, &extension, ctx.wsl_path.is_some())?,
+    };
+    let (program, args) = command.split_first()?;
+    let name = Path::new(program)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| program.clone());
+
+    let before = fs::read(path).await.ok()?;
+    let mut cmd = ctx.command(program);
+    cmd.args(args)
+        .arg(ctx.command_path(path))
+        .kill_on_drop(true);
+    let run = cmd.output();
+
+    match timeout(ctx.timeout, run).await {
+        Ok(Ok(output)) if output.status.success() => {}
+        Ok(Ok(output)) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let error: Vec<&str> = stderr.lines().take(MAX_ERROR_LINES).collect();
+            return Some(format!(
+                "Formatter {} failed, file left as written:\n{}",
+                name,
+                error.join("\n")
+            ));
+        }
+        Ok(Err(e)) => return Some(format!("Formatter {} could not run: {}", name, e)),
+        Err(_) => return Some(format!("Formatter {} timed out", name)),
+    }
+
+    let after = fs::read(path).await.ok()?;
+    (before != after).then(|| {
+        format!(
+            "Formatted with {}, which changed the file; read it again before editing text it may have moved",
+            name
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NetworkConfig;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_detect_formatter() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+
+        assert_eq!(
+            detect_formatter(dir, "rs", false),
+            Some(vec![
+                "rustfmt".to_string(),
+                "--edition".to_string(),
+                "2021".to_string()
+            ])
+        );
+        // prettier and Python formatters only when the project sets them up
+        assert!(detect_formatter(dir, "ts", false).is_none());
+        assert!(detect_formatter(dir, "py", false).is_none());
+        assert!(detect_formatter(dir, "txt", false).is_none());
+
+        std::fs::write(
+            dir.join("pyproject.toml"),
+            "[tool.ruff.lint]\nselect = [\"E\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            detect_formatter(dir, "py", false).unwrap()[..2],
+            ["ruff".to_string(), "format".to_string()]
+        );
+
+        std::fs::create_dir_all(dir.join("node_modules/.bin")).unwrap();
+        std::fs::write(dir.join(prettier_bin(false)), "").unwrap();
+        let prettier = detect_formatter(dir, "tsx", false).unwrap();
+        assert!(prettier[0].ends_with(prettier_bin(false)));
+        assert_eq!(prettier[1], "--write");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_format_file_reports_changes() {
+        let temp = tempfile::tempdir().unwrap();
+        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+            .unwrap();
+        let config = FormattingConfig {
+            on_edit: true,
+            commands: HashMap::from([("txt".to_string(), "sed -i.bak s/messy/tidy/".to_string())]),
+        };
+
+        let path = temp.path().join("notes.txt");
+        std::fs::write(&path, "messy\n").unwrap();
+        let note = format_file(&ctx, &config, &path).await.unwrap();
+        assert!(note.contains("Formatted with sed"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "tidy\n");
+
+        // Already formatted: nothing to report
+        assert!(format_file(&ctx, &config, &path).await.is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_format_file_reports_failure() {
+        let temp = tempfile::tempdir().unwrap();
+        let ctx = ExecutionContext::new(temp.path().to_path_buf(), 30, &NetworkConfig::default())
+            .unwrap();
+        let config = FormattingConfig {
+            on_edit: true,
+            commands: HashMap::from([("txt".to_string(), "false".to_string())]),
+        };
+
+        let path = temp.path().join("notes.txt");
+        std::fs::write(&path, "text\n").unwrap();
+        let note = format_file(&ctx, &config, &path).await.unwrap();
+        assert!(note.starts_with("Formatter false failed"));
+    }
+}
@@ -0,0 +1,364 @@
+//! The bash tool has no terminal and closes stdin, so commands that ask the
+//! user something can only hang. Known interactive commands are refused
+//! before they run, with the non-interactive spelling; commands that stop
+//! at a prompt anyway are caught by the stall check in the shell.
+
+/// A command that waits for the user unless told otherwise.
+struct InteractiveCommand {
+    /// Leading words, e.g. `git rebase`
+    words: &'static [&'static str],
+    /// Interactive only with one of these flags; empty = always
+    with_any: &'static [&'static str],
+    /// Not interactive with one of these flags
+    unless_any: &'static [&'static str],
+    /// Short options that take a value, which fills the rest of their word
+    /// (`-Xtheirs`), so the letters after them are not flags
+    value_flags: &'static str,
+    /// Waits in git's editor, which setting an `*EDITOR` variable replaces;
+    /// otherwise it asks on stdin, which a pipe or redirect can answer
+    editor: bool,
+    hint: &'static str,
+}
+
+const INTERACTIVE_COMMANDS: &[InteractiveCommand] = &[
+    InteractiveCommand {
+        words: &["git", "rebase"],
+        with_any: &["-i", "--interactive"],
+        unless_any: &[],
+        value_flags: "CSXsx",
+        editor: true,
+        hint: "run a plain `git rebase <base>`, or set `GIT_SEQUENCE_EDITOR` to a script that edits the todo list",
+    },
+    InteractiveCommand {
+        words: &["git", "add"],
+        with_any: &["-p", "-i", "--patch", "--interactive"],
+        unless_any: &[],
+        value_flags: "",
+        editor: false,
+        hint: "stage whole files by path",
+    },
+    InteractiveCommand {
+        words: &["git", "commit"],
+        with_any: &[],
+        unless_any: &[
+            "-m",
+            "-F",
+            "-C",
+            "--message",
+            "--file",
+            "--reuse-message",
+            "--fixup",
+            "--no-edit",
+        ],
+        value_flags: "CFScmtu",
+        editor: true,
+        hint: "pass the message with `-m \"...\"`, or `--no-edit` when amending",
+    },
+    InteractiveCommand {
+        words: &["npm", "init"],
+        with_any: &[],
+        unless_any: &["-y", "--yes"],
+        value_flags: "w",
+        editor: false,
+        hint: "use `npm init -y`",
+    },
+    InteractiveCommand {
+        words: &["yarn", "init"],
+        with_any: &[],
+        unless_any: &["-y", "--yes"],
+        value_flags: "",
+        editor: false,
+        hint: "use `yarn init -y`",
+    },
+    InteractiveCommand {
+        words: &["apt-get", "install"],
+        with_any: &[],
+        unless_any: &["-y", "--yes", "--assume-yes"],
+        value_flags: "cot",
+        editor: false,
+        hint: "use `apt-get install -y`",
+    },
+    InteractiveCommand {
+        words: &["apt", "install"],
+        with_any: &[],
+        unless_any: &["-y", "--yes", "--assume-yes"],
+        value_flags: "cot",
+        editor: false,
+        hint: "use `apt install -y`",
+    },
+];
+
+/// Full-screen programs that need a terminal whatever the flags.
+const TERMINAL_PROGRAMS: &[(&str, &str)] = &[
+    ("vi", "use read_file and edit_file"),
+    ("vim", "use read_file and edit_file"),
+    ("nvim", "use read_file and edit_file"),
+    ("nano", "use read_file and edit_file"),
+    ("emacs", "use read_file and edit_file"),
+    ("less", "use read_file"),
+    ("more", "use read_file"),
+    ("top", "use `ps aux`"),
+    ("htop", "use `ps aux`"),
+];
+
+/// Error output of programs that wanted a terminal and did not get one.
+const NO_TERMINAL_ERRORS: &[&str] = &[
+    "/dev/tty",
+    "not a terminal",
+    "not a tty",
+    "Inappropriate ioctl for device",
+    "Input is not from a terminal",
+];
+
+/// Why `command` would wait for input, with how to run it non-interactively.
+pub(super) fn check(command: &str) -> Option<String> {
+    for segment in segments(command) {
+        let words = command_words(segment.text);
+        let sets_editor = segment
+            .text
+            .split_whitespace()
+            .take_while(|word| is_assignment(word))
+            .any(|word| word.contains("EDITOR="));
+        let Some(program) = words.first().map(|word| program_name(word)) else {
+            continue;
+        };
+
+        if let Some((_, hint)) = TERMINAL_PROGRAMS.iter().find(|(name, _)| *name == program) {
+            return Some(refusal(program, hint));
+        }
+
+        for rule in INTERACTIVE_COMMANDS {
+            let matches_words = words.len() >= rule.words.len()
+                && program == rule.words[0]
+                && words[1..rule.words.len()] == rule.words[1..];
+            let answered = if rule.editor {
+                sets_editor
+            } else {
+                segment.piped || reads_file(&words)
+            };
+            if !matches_words || answered {
+                continue;
+            }
+            let flags = &words[rule.words.len()..];
+            let has = |flag: &&str| has_flag(flags, flag, rule.value_flags);
+            let interactive = (rule.with_any.is_empty() || rule.with_any.iter().any(has))
+                && !rule.unless_any.iter().any(has);
+            if interactive {
+                return Some(refusal(&rule.words.join(" "), rule.hint));
+            }
+        }
+    }
+    None
+}
+
+fn refusal(command: &str, hint: &str) -> String {
+    format!(
+        "`{}` waits for input, and the bash tool has no terminal to answer it from; {}.",
+        command, hint
+    )
+}
+
+/// Whether output ending in `text` looks like a question waiting for an
+/// answer. A line merely ending in `:` is not enough, since progress headers
+/// such as `Running migrations:` do too.
+pub(super) fn looks_like_prompt(text: &str) -> bool {
+    let line = text.rsplit(['\n', '\r']).next().unwrap_or("").trim_end();
+    if line.is_empty() {
+        return false;
+    }
+    let lower = line.to_lowercase();
+    line.ends_with('?')
+        // npm init style, with the default in parentheses
+        || (line.ends_with(')') && line.contains(": ("))
+        || (line.ends_with(':')
+            && (lower.contains("password")
+                || lower.contains("passphrase")
+                || lower.starts_with("enter ")))
+        || lower.contains("[y/n]")
+        || lower.contains("(y/n)")
+        || lower.contains("yes/no")
+        || lower.contains("press enter")
+        || lower.contains("press any key")
+}
+
+/// Note for failure output of a program that wanted a terminal.
+pub(super) fn terminal_hint(output: &str) -> Option<&'static str> {
+    NO_TERMINAL_ERRORS
+        .iter()
+        .any(|error| output.contains(error))
+        .then_some(
+            "the bash tool has no terminal, so commands cannot prompt; \
+             pass answers as flags (e.g. `--yes`) or environment variables",
+        )
+}
+
+/// A simple command of a command line.
+pub(super) struct Segment<'a> {
+    pub(super) text: &'a str,
+    /// Stdin comes from the previous command, e.g. `yes | apt-get install x`
+    piped: bool,
+}
+
+/// The simple commands of a command line, split at `;`, `&&`, `||`, `|` and
+/// newlines outside quotes.
+pub(super) fn segments(command: &str) -> Vec<Segment<'_>> {
+    let bytes = command.as_bytes();
+    let mut segments = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    let mut piped = false;
+    for (i, c) in command.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '\n' | ';' | '|' | '&') => {
+                let text = command[start..i].trim();
+                if !text.is_empty() {
+                    segments.push(Segment { text, piped });
+                    piped = false;
+                }
+                // A lone `|` (or `|&`), not `||`
+                if c == '|' && bytes.get(i + 1) != Some(&b'|') && (i == 0 || bytes[i - 1] != b'|') {
+                    piped = true;
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let text = command[start..].trim();
+    if !text.is_empty() {
+        segments.push(Segment { text, piped });
+    }
+    segments
+}
+
+/// Whether a simple command redirects its stdin (`< answers.txt`, `<<EOF`).
+/// Process substitution (`<(...)`) is not stdin.
+fn reads_file(words: &[&str]) -> bool {
+    words.iter().any(|word| {
+        word.trim_start_matches('0')
+            .strip_prefix('<')
+            .is_some_and(|rest| !rest.starts_with('('))
+    })
+}
+
+/// Words of a simple command, without leading `VAR=value` assignments and `sudo`.
+pub(super) fn command_words(segment: &str) -> Vec<&str> {
+    segment
+        .split_whitespace()
+        .skip_while(|word| is_assignment(word) || *word == "sudo")
+        .collect()
+}
+
+fn is_assignment(word: &str) -> bool {
+    word.contains('=') && !word.starts_with('-')
+}
+
+/// `/usr/bin/vim` is `vim`.
+pub(super) fn program_name(word: &str) -> &str {
+    word.rsplit('/').next().unwrap_or(word)
+}
+
+/// Whether `flag` is among `words`, in a cluster of short flags (`-am`) or
+/// with a value (`--message=...`). A cluster ends at the first of
+/// `value_flags`, whose value is the rest of the word.
+fn has_flag(words: &[&str], flag: &str, value_flags: &str) -> bool {
+    words.iter().any(|word| {
+        if let Some(long) = flag.strip_prefix("--") {
+            word.strip_prefix("--").is_some_and(|w| {
+                w == long
+                    || w.strip_prefix(long)
+                        .is_some_and(|rest| rest.starts_with('='))
+            })
+        } else {
+            let short = flag.trim_start_matches('-');
+            let Some(cluster) = word.strip_prefix('-').filter(|w| {
+                !w.starts_with('-') && w.starts_with(|c: char| c.is_ascii_alphabetic())
+            }) else {
+                return false;
+            };
+            for c in cluster.chars() {
+                if short.starts_with(c) {
+                    return true;
+                }
+                if value_flags.contains(c) {
+                    return false;
+                }
+            }
+            false
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_refuses_interactive_commands() {
+        for command in [
+            "git rebase -i HEAD~3",
+            "git add -p",
+            "cargo fmt && git commit",
+            "npm init",
+            "cd web; sudo apt-get install curl",
+            "EDITOR=nano vim src/main.rs",
+            "git rebase -Xtheirs -i main",
+            "echo fix | git commit",
+            "true || apt-get install curl",
+        ] {
+            assert!(check(command).is_some(), "{}", command);
+        }
+
+        let refusal = check("git commit --amend").unwrap();
+        assert!(refusal.contains("--no-edit"));
+    }
+
+    #[test]
+    fn test_check_allows_non_interactive_spellings() {
+        for command in [
+            "git rebase main",
+            "git add -A src",
+            "git commit -am \"fix: handle empty input\"",
+            "git commit -m 'docs: fewer pages; less noise'",
+            "git commit --amend --no-edit",
+            "git commit --message=wip",
+            "GIT_EDITOR=true git rebase --continue",
+            "GIT_SEQUENCE_EDITOR=true git rebase -i --autosquash main",
+            "npm init -y",
+            "cargo test | less-than-helpful-name",
+            "grep -r more src",
+            "git rebase -Xignore-space-change main",
+            "git rebase -Xtheirs main",
+            "yes | apt-get install x",
+            "yes | sudo apt-get install curl && apt-get install -y jq",
+            "npm init < answers.txt",
+            "git add -p <<EOF\ny\nEOF",
+        ] {
+            assert!(check(command).is_none(), "{}", command);
+        }
+    }
+
+    #[test]
+    fn test_looks_like_prompt() {
+        assert!(looks_like_prompt("Installing...\nProceed? "));
+        assert!(looks_like_prompt("package name: (app) "));
+        assert!(looks_like_prompt("Overwrite [Y/n] "));
+        assert!(looks_like_prompt("Continue? "));
+        assert!(looks_like_prompt("Password: "));
+        assert!(looks_like_prompt("[sudo] password for dev: "));
+        assert!(looks_like_prompt(
+            "Enter passphrase for key '/home/dev/.ssh/id_ed25519': "
+        ));
+        assert!(looks_like_prompt("Do you accept? (yes/no) "));
+        assert!(!looks_like_prompt("Compiling app\n"));
+        assert!(!looks_like_prompt("Downloading 45%\r"));
+
+        // Progress headers
+        assert!(!looks_like_prompt("Running migrations:"));
+        assert!(!looks_like_prompt("Applying database changes:\n"));
+        assert!(!looks_like_prompt("Step 2/5 : RUN cargo build:"));
+    }
+}
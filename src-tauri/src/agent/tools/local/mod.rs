@@ -1,28 +1,52 @@
 mod context;
+mod database;
+mod diagnostics;
+mod docker;
+mod docs;
+mod dry_run;
 mod file;
+mod format;
+mod interactive;
 mod notebook;
+mod outline;
+mod output;
+mod plan;
+mod process;
+mod scaffold;
 mod search;
+mod semantic;
 mod shell;
 mod state;
 mod subagent;
+mod trailer;
 mod web;
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
+use tokio::sync::Mutex;
 use tokio_util::sync::CancellationToken;
 
+pub(crate) use context::resolve_in_dir;
 pub use context::ExecutionContext;
-pub use state::{CompactedContext, PlanApproval, SessionState};
+use context::MAX_OUTPUT_SIZE;
+pub use plan::{ArchivedPlan, PlanStatus};
+pub(crate) use shell::probe_shell;
+pub use state::{
+    CompactedContext, FileSnapshot, PlanApproval, SessionState, TodoList, TrackedTodo,
+};
+pub(crate) use subagent::{agent_system_prompt, run_subagent, SubagentParams};
 
-use super::executor::ToolExecutor;
+use super::executor::{ToolExecutor, ToolOutput};
 use super::types::ToolName;
+use crate::agent::attribution::Attribution;
 use crate::agent::error::AgentError;
+use crate::agent::types::ToolArtifact;
 use crate::agent::usage::SessionUsageTracker;
-
-#[cfg(windows)]
-use crate::git::wsl::{is_wsl_path, parse_wsl_path};
+use crate::config::{AllowedPath, NetworkConfig, ShellKind, WorkspaceRoot};
+use crate::git::diff_texts;
 
 /// Clone is cheap: session/cancel_token/usage_tracker are Arc-wrapped, others are small structs.
 #[derive(Clone)]
@@ -32,6 +56,13 @@ pub struct LocalExecutor {
     shell: shell::ShellExecutor,
     cancel_token: CancellationToken,
     usage_tracker: Arc<SessionUsageTracker>,
+    max_output_bytes: Option<usize>,
+    /// Tools refused even if the model calls them
+    disabled_tools: Arc<[String]>,
+    /// Report what changing tools would do instead of running them
+    dry_run: bool,
+    /// Files changed since the last batch, checked by `after_batch`
+    edited_paths: Arc<Mutex<Vec<PathBuf>>>,
 }
 
 impl LocalExecutor {
@@ -40,40 +71,113 @@ impl LocalExecutor {
         Self::with_session(
             working_dir,
             timeout_secs,
+            &NetworkConfig::default(),
             SessionState::new(),
             CancellationToken::new(),
             Arc::new(SessionUsageTracker::new()),
         )
+        .expect("default network config builds a client")
     }
 
     pub fn with_session(
         working_dir: PathBuf,
         timeout_secs: u64,
+        network: &NetworkConfig,
         session: SessionState,
         cancel_token: CancellationToken,
         usage_tracker: Arc<SessionUsageTracker>,
-    ) -> Self {
-        let ctx = ExecutionContext::new(working_dir.clone(), timeout_secs);
+    ) -> Result<Self, AgentError> {
+        let ctx = ExecutionContext::new(working_dir.clone(), timeout_secs, network)?;
 
         #[cfg(windows)]
-        let shell = {
-            let wsl_path = if is_wsl_path(&working_dir) {
-                parse_wsl_path(&working_dir)
-            } else {
-                None
-            };
-            shell::ShellExecutor::new(working_dir, ctx.timeout, wsl_path)
-        };
+        let shell = shell::ShellExecutor::new(working_dir, ctx.timeout, ctx.wsl_path.clone());
 
         #[cfg(not(windows))]
         let shell = shell::ShellExecutor::new(working_dir, ctx.timeout);
 
-        Self {
+        let shell = shell.with_processes(session.processes());
+
+        Ok(Self {
             ctx,
             session,
             shell,
             cancel_token,
             usage_tracker,
+            max_output_bytes: None,
+            disabled_tools: Arc::from([]),
+            dry_run: false,
+            edited_paths: Arc::new(Mutex::new(Vec::new())),
+        })
+    }
+
+    pub fn with_attribution(mut self, attribution: Option<Attribution>) -> Self {
+        self.shell = self
+            .shell
+            .with_commit_trailer(attribution.as_ref().map(Attribution::trailer));
+        self.ctx.attribution = attribution;
+        self
+    }
+
+    /// Rejects tool outputs larger than `max_bytes` instead of returning them
+    /// to the model; bash output is truncated to fit instead.
+    pub fn with_max_output_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_output_bytes = Some(max_bytes);
+        self.shell = self.shell.with_max_output(max_bytes.min(MAX_OUTPUT_SIZE));
+        self
+    }
+
+    /// Cuts read_file results past `max_bytes` (`execution.max_read_bytes`).
+    pub fn with_max_read_bytes(mut self, max_bytes: usize) -> Self {
+        self.ctx.max_read_bytes = max_bytes;
+        self
+    }
+
+    /// Caps the timeout a bash call may ask for (`execution.max_timeout_secs`).
+    pub fn with_max_timeout(mut self, secs: u64) -> Self {
+        self.shell = self.shell.with_max_timeout(Duration::from_secs(secs));
+        self
+    }
+
+    /// Runs bash tool commands and diagnostics checks with `kind` (`execution.shell`).
+    pub fn with_shell(mut self, kind: ShellKind) -> Self {
+        self.shell = self.shell.with_kind(kind);
+        self
+    }
+
+    /// Lets the file tools and bash use these directories outside the project.
+    pub fn with_allowed_paths(mut self, allowed: &[AllowedPath]) -> Self {
+        self.ctx.set_allowed_paths(allowed);
+        self
+    }
+
+    /// Maps `name/...` paths of the file tools to these workspace repos (`[workspace] roots`).
+    pub fn with_workspace_roots(mut self, roots: &[WorkspaceRoot]) -> Self {
+        self.ctx.set_workspace_roots(roots);
+        self
+    }
+
+    /// Refuses calls to these tools, e.g. ones not offered for the run.
+    pub fn with_disabled_tools(mut self, tools: &[String]) -> Self {
+        self.disabled_tools = Arc::from(tools);
+        self
+    }
+
+    /// Answers bash, file edit and docker exec calls with the command or diff
+    /// they would have run or applied (`execution.dry_run`).
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    fn check_output_size(&self, output: ToolOutput) -> Result<ToolOutput, AgentError> {
+        match self.max_output_bytes {
+            Some(max) if output.text.len() > max => Err(AgentError::ToolExecutionError(format!(
+                "Output is {} bytes, over the {} byte limit (execution.max_tool_result_bytes). \
+                 Narrow the request, e.g. read a line range or use a more specific pattern",
+                output.text.len(),
+                max
+            ))),
+            _ => Ok(output),
         }
     }
 
@@ -110,9 +214,16 @@ impl LocalExecutor {
         let input: SubmitPlanInput = serde_json::from_value(input)
             .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
 
-        self.session.set_plan(input.plan.clone()).await;
+        let ahead = self.session.pending_plan_count().await;
+        self.session.queue_plan(input.plan).await;
 
-        Ok("Plan submitted for review. Awaiting user approval.".to_string())
+        Ok(match ahead {
+            0 => "Plan submitted for review. Awaiting user approval.".to_string(),
+            n => format!(
+                "Plan submitted for review. It is queued behind {} earlier plan(s) awaiting user approval.",
+                n
+            ),
+        })
     }
 
     async fn execute_dispatch_agent(&self, input: serde_json::Value) -> Result<String, AgentError> {
@@ -127,47 +238,298 @@ impl LocalExecutor {
             .map_err(|e| AgentError::ConfigError(e.to_string()))?;
 
         let max_depth = config.execution.max_agent_depth;
+        let task = subagent::task_with_context(&self.ctx, &input.task, &input.context).await?;
 
         // Execute the sub-agent with a child cancellation token
         subagent::execute_subagent(subagent::SubagentParams {
             project_path: &self.ctx.working_dir,
-            task: &input.task,
+            task: &task,
             agent_type_id: input.agent_type.as_deref(),
             allowed_tools: input.tools,
             max_depth,
-            current_depth: 0,
+            current_depth: self.session.agent_depth(),
             parent_token: &self.cancel_token,
             usage_tracker: Arc::clone(&self.usage_tracker),
+            response_schema: None,
+            variables: self.session.get_prompt_variables().await,
+            dry_run: self.dry_run,
+            auto_approve: self.session.get_auto_approve().await,
         })
         .await
     }
-}
 
-#[async_trait]
-impl ToolExecutor for LocalExecutor {
-    async fn execute(
+    /// Fetches the page (cached per session by URL) and, given a prompt,
+    /// answers it with the web-extract agent instead of returning the page.
+    async fn execute_web_fetch(&self, input: serde_json::Value) -> Result<String, AgentError> {
+        use super::types::WebFetchInput;
+        use crate::config::ConfigService;
+
+        let input: WebFetchInput = serde_json::from_value(input)
+            .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
+
+        let page = match self.session.get_cached_page(&input.url).await {
+            Some(page) => page,
+            None => {
+                let page = web::fetch_page(&self.ctx, &input.url).await?;
+                self.session
+                    .cache_page(input.url.clone(), page.clone())
+                    .await;
+                page
+            }
+        };
+
+        let Some(prompt) = input.prompt.as_deref().filter(|p| !p.trim().is_empty()) else {
+            return Ok(page);
+        };
+
+        let max_depth = match ConfigService::load_project_config(&self.ctx.working_dir) {
+            Ok(config) => config.execution.max_agent_depth,
+            Err(e) => {
+                log::warn!("web_fetch extraction skipped, returning page: {}", e);
+                return Ok(page);
+            }
+        };
+
+        let task = web::extraction_task(&input.url, prompt, &page);
+        let extracted = subagent::execute_subagent(subagent::SubagentParams {
+            project_path: &self.ctx.working_dir,
+            task: &task,
+            agent_type_id: Some("web-extract"),
+            allowed_tools: None,
+            max_depth,
+            current_depth: self.session.agent_depth(),
+            parent_token: &self.cancel_token,
+            usage_tracker: Arc::clone(&self.usage_tracker),
+            response_schema: None,
+            variables: self.session.get_prompt_variables().await,
+            dry_run: self.dry_run,
+            auto_approve: self.session.get_auto_approve().await,
+        })
+        .await;
+
+        match extracted {
+            Ok(answer) => Ok(answer),
+            Err(AgentError::Cancelled) => Err(AgentError::Cancelled),
+            Err(e) => {
+                log::warn!("web_fetch extraction failed, returning page: {}", e);
+                Ok(page)
+            }
+        }
+    }
+
+    /// Records the file's current content the first time the turn touches it,
+    /// so the turn's changes can be diffed against it later.
+    async fn snapshot_before_edit(&self, path: &str) {
+        let Ok(absolute) = self.ctx.resolve_path(path) else {
+            return;
+        };
+        if self.session.has_snapshot(&absolute).await {
+            return;
+        }
+        let original = match tokio::fs::read_to_string(&absolute).await {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            // Binary or unreadable: nothing to diff against
+            Err(_) => return,
+        };
+        let snapshot = FileSnapshot {
+            path: path.to_string(),
+            absolute,
+            original,
+        };
+        // Saved before the edit lands, so a crash mid-run can be undone
+        if let Some(journal) = self.session.run_journal().await {
+            journal.record_checkpoint(&snapshot);
+        }
+        self.session.record_snapshot(snapshot).await;
+    }
+
+    async fn journal_edit(&self, path: &str) {
+        if let Some(journal) = self.session.run_journal().await {
+            journal.record_edit(path);
+        }
+    }
+
+    /// Applies a write or edit, then formats the file when `[formatting] on_edit` is set.
+    /// The change, formatting included, comes back as a unified diff and a diff artifact.
+    async fn execute_file_edit(
         &self,
         tool: ToolName,
         input: serde_json::Value,
-    ) -> Result<String, AgentError> {
+    ) -> Result<ToolOutput, AgentError> {
+        use crate::config::ConfigService;
+
+        let path = input
+            .get("path")
+            .and_then(|path| path.as_str())
+            .map(str::to_string);
+        let mut before = None;
+        if let Some(path) = &path {
+            self.snapshot_before_edit(path).await;
+            if let Ok(absolute) = self.ctx.resolve_path(path) {
+                before = tokio::fs::read_to_string(absolute).await.ok();
+            }
+        }
         match tool {
-            ToolName::Bash => self.shell.execute(input).await,
+            ToolName::WriteFile => file::write_file(&self.ctx, input).await?,
+            ToolName::EditFile => file::edit_file(&self.ctx, input).await?,
+            _ => file::multi_edit(&self.ctx, input).await?,
+        }
+
+        // The input was checked for a path before anything was written
+        let Some(display) = path else {
+            return Ok("File written".to_string().into());
+        };
+        self.journal_edit(&display).await;
+        let path = self.ctx.resolve_path(&display)?;
+        self.edited_paths.lock().await.push(path.clone());
+
+        let mut format_note = None;
+        if let Ok(config) = ConfigService::load_project_config(&self.ctx.working_dir) {
+            if config.formatting.on_edit {
+                format_note = format::format_file(&self.ctx, &config.formatting, &path).await;
+            }
+        }
+
+        let (mut text, artifacts) = match tokio::fs::read_to_string(&path).await {
+            Ok(after) => {
+                let diff = diff_texts(&display, before.as_deref(), Some(&after));
+                (
+                    file::describe_change(&diff),
+                    vec![ToolArtifact::Diff { diff }],
+                )
+            }
+            Err(_) => (format!("Wrote {}", display), Vec::new()),
+        };
+        if let Some(note) = format_note {
+            text = format!("{}\n{}", text.trim_end(), note);
+        }
+        Ok(ToolOutput { text, artifacts })
+    }
+
+    /// Lists the scaffolds, or creates the files of one. Created files are
+    /// snapshotted and checked after the batch like other edits.
+    async fn execute_scaffold(&self, input: serde_json::Value) -> Result<ToolOutput, AgentError> {
+        let input = scaffold::parse(input)?;
+        let Some(template) = input.template.as_deref() else {
+            return scaffold::list(&self.ctx).map(ToolOutput::from);
+        };
+        let planned = scaffold::plan(&self.ctx, template, &input)?;
+
+        let mut artifacts = Vec::with_capacity(planned.files.len());
+        for (file, path) in &planned.files {
+            self.snapshot_before_edit(&file.path).await;
+            self.ctx
+                .with_timeout("write file", self.ctx.write(path, &file.content))
+                .await?;
+            self.journal_edit(&file.path).await;
+            self.edited_paths.lock().await.push(path.clone());
+            artifacts.push(ToolArtifact::Diff {
+                diff: diff_texts(&file.path, None, Some(&file.content)),
+            });
+        }
+        Ok(ToolOutput {
+            text: scaffold::describe(&planned, "Created"),
+            artifacts,
+        })
+    }
+
+    async fn execute_tool(
+        &self,
+        tool: ToolName,
+        input: serde_json::Value,
+    ) -> Result<ToolOutput, AgentError> {
+        let text = match tool {
+            ToolName::Bash => {
+                // Commands can write anywhere below their directory, so they need write access
+                let cwd = match input.get("cwd").and_then(|cwd| cwd.as_str()) {
+                    Some(cwd) => Some(self.ctx.resolve_write_path(cwd)?),
+                    None => None,
+                };
+                self.shell.execute(input, cwd).await
+            }
             ToolName::ReadFile => file::read_file(&self.ctx, input).await,
-            ToolName::WriteFile => file::write_file(&self.ctx, input).await,
-            ToolName::EditFile => file::edit_file(&self.ctx, input).await,
-            ToolName::MultiEdit => file::multi_edit(&self.ctx, input).await,
+            ToolName::WriteFile | ToolName::EditFile | ToolName::MultiEdit => {
+                return self.execute_file_edit(tool, input).await;
+            }
             ToolName::ListDirectory => file::list_directory(&self.ctx, input).await,
             ToolName::Glob => file::glob(&self.ctx, input).await,
             ToolName::Grep => file::grep(&self.ctx, input).await,
+            ToolName::OutlineFile => outline::outline_file(&self.ctx, input).await,
+            ToolName::SemanticSearch => semantic::search(&self.ctx, input).await,
             ToolName::NotebookRead => notebook::read(&self.ctx, input).await,
             ToolName::NotebookEdit => notebook::edit(&self.ctx, input).await,
+            ToolName::Scaffold => return self.execute_scaffold(input).await,
             ToolName::TodoRead => self.execute_todo_read().await,
             ToolName::TodoWrite => self.execute_todo_write(input).await,
-            ToolName::WebFetch => web::fetch(&self.ctx, input).await,
-            ToolName::SearchWeb => search::search(&self.ctx, input).await,
+            ToolName::WebFetch => self.execute_web_fetch(input).await,
+            ToolName::SearchWeb => return search::search(&self.ctx, input).await,
+            ToolName::LookupDocs => docs::lookup(&self.ctx, &self.session, input).await,
+            ToolName::QueryDatabase => return database::query(&self.ctx, input).await,
+            ToolName::Docker => docker::execute(&self.ctx, input).await,
             ToolName::SubmitPlan => self.execute_submit_plan(input).await,
             ToolName::DispatchAgent => self.execute_dispatch_agent(input).await,
+        };
+        text.map(ToolOutput::from)
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for LocalExecutor {
+    async fn execute(
+        &self,
+        tool: ToolName,
+        input: serde_json::Value,
+    ) -> Result<String, AgentError> {
+        self.execute_output(tool, input)
+            .await
+            .map(|output| output.text)
+    }
+
+    async fn execute_output(
+        &self,
+        tool: ToolName,
+        input: serde_json::Value,
+    ) -> Result<ToolOutput, AgentError> {
+        let started = Instant::now();
+        let result = if self.disabled_tools.iter().any(|name| name == tool.as_str()) {
+            Err(AgentError::ToolExecutionError(format!(
+                "Tool '{}' is disabled for this run",
+                tool.as_str()
+            )))
+        } else if self.dry_run && dry_run::intercepts(tool, &input) {
+            dry_run::preview(&self.ctx, tool, input)
+                .await
+                .and_then(|output| self.check_output_size(output.into()))
+        } else {
+            self.execute_tool(tool, input)
+                .await
+                .and_then(|output| self.check_output_size(output))
+        };
+
+        let (is_error, output_bytes) = match &result {
+            Ok(output) => (false, output.text.len()),
+            Err(e) => (true, e.to_string().len()),
+        };
+        self.usage_tracker.record_tool_call(
+            tool.as_str(),
+            started.elapsed(),
+            is_error,
+            output_bytes,
+        );
+
+        result
+    }
+
+    async fn after_batch(&self) -> Option<String> {
+        use crate::config::ConfigService;
+
+        let paths = std::mem::take(&mut *self.edited_paths.lock().await);
+        if paths.is_empty() {
+            return None;
         }
+        let config = ConfigService::load_project_config(&self.ctx.working_dir).ok()?;
+        diagnostics::run_checks(&self.shell, &config.diagnostics, &paths).await
     }
 }
 
@@ -216,6 +578,108 @@ max_tool_iterations = 50
         assert_eq!(result.unwrap().trim(), "hello");
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_bash_commits_get_the_attribution_trailer() {
+        let (executor, _dir) = create_executor();
+        let attribution = Attribution::new("devflow", "claude-sonnet-4", "2025-01-31");
+        let executor = executor.with_attribution(Some(attribution.clone()));
+        let bash = |command: String| {
+            executor.execute(ToolName::Bash, serde_json::json!({ "command": command }))
+        };
+
+        let git = "git -c user.name=test -c user.email=test@example.com";
+        bash(format!(
+            "git init -q && git add -A && {} commit -qm 'fix: no trailer'",
+            git
+        ))
+        .await
+        .unwrap();
+        let message = bash("git log -1 --format=%B".to_string()).await.unwrap();
+        assert_eq!(
+            message.trim(),
+            format!("fix: no trailer\n\n{}", attribution.trailer())
+        );
+
+        // A message that already ends with the trailer keeps a single one
+        bash(format!(
+            "{} commit -q --allow-empty -m 'fix: with trailer' -m '{}'",
+            git,
+            attribution.trailer()
+        ))
+        .await
+        .unwrap();
+        let message = bash("git log -1 --format=%B".to_string()).await.unwrap();
+        assert_eq!(message.matches("Generated-by:").count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_output_is_rejected() {
+        let (executor, _dir) = create_executor();
+        let executor = executor.with_max_output_bytes(16);
+
+        let small = executor
+            .execute(ToolName::Bash, serde_json::json!({ "command": "echo hi" }))
+            .await;
+        assert!(small.is_ok());
+
+        let large = executor
+            .execute(
+                ToolName::Bash,
+                serde_json::json!({ "command": "printf '%040d' 0" }),
+            )
+            .await;
+        match large {
+            Err(AgentError::ToolExecutionError(msg)) => {
+                assert!(msg.contains("over the 16 byte limit"));
+                assert!(msg.contains("max_tool_result_bytes"));
+            }
+            other => panic!("expected size error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_disabled_tool_is_refused() {
+        let (executor, dir) = create_executor();
+        let executor = executor.with_disabled_tools(&["write_file".to_string()]);
+
+        let result = executor
+            .execute(
+                ToolName::WriteFile,
+                serde_json::json!({ "path": "new.txt", "content": "x" }),
+            )
+            .await;
+
+        match result {
+            Err(AgentError::ToolExecutionError(msg)) => assert!(msg.contains("disabled")),
+            other => panic!("expected disabled error, got {:?}", other),
+        }
+        assert!(!dir.path().join("new.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_are_recorded() {
+        let (executor, _dir) = create_executor();
+        executor
+            .execute(ToolName::Bash, serde_json::json!({ "command": "echo hi" }))
+            .await
+            .unwrap();
+        let missing = executor
+            .execute(
+                ToolName::ReadFile,
+                serde_json::json!({ "path": "missing.txt" }),
+            )
+            .await;
+        assert!(missing.is_err());
+
+        let stats = executor.usage_tracker.get_tool_stats();
+        let bash = stats.iter().find(|s| s.tool == "bash").unwrap();
+        assert_eq!((bash.calls, bash.errors), (1, 0));
+        assert!(bash.total_output_bytes > 0);
+        let read = stats.iter().find(|s| s.tool == "read_file").unwrap();
+        assert_eq!((read.calls, read.errors), (1, 1));
+    }
+
     #[tokio::test]
     async fn test_bash_failure() {
         let (executor, _dir) = create_executor();
@@ -244,7 +708,235 @@ max_tool_iterations = 50
             )
             .await;
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "hello world");
+        assert_eq!(
+            result.unwrap(),
+            "[test.txt: 1 lines, 11 bytes]\n     1\thello world"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_file_range_is_numbered() {
+        let (executor, dir) = create_executor();
+        let content: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(dir.path().join("lines.txt"), &content).unwrap();
+
+        let result = executor
+            .execute(
+                ToolName::ReadFile,
+                serde_json::json!({ "path": "lines.txt", "offset": 3, "limit": 2 }),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            format!(
+                "[lines.txt: lines 4-5 of 10 (offset 3, limit 2), {} bytes; \
+                 5 more, read on with offset 5]\n     4\tline 4\n     5\tline 5",
+                content.len()
+            )
+        );
+
+        let past_end = executor
+            .execute(
+                ToolName::ReadFile,
+                serde_json::json!({ "path": "lines.txt", "offset": 20 }),
+            )
+            .await
+            .unwrap();
+        assert!(past_end.contains("no lines at offset 20; the file has 10 lines"));
+    }
+
+    #[tokio::test]
+    async fn test_read_file_cuts_large_file_with_index() {
+        let (executor, dir) = create_executor();
+        let source: String = (0..100_000)
+            .map(|i| format!("pub fn handler_{}() -> u32 {{\n    {}\n}}\n", i, i))
+            .collect();
+        assert!(source.len() > 4_000_000);
+        std::fs::write(dir.path().join("big.rs"), &source).unwrap();
+
+        let result = executor
+            .execute(ToolName::ReadFile, serde_json::json!({ "path": "big.rs" }))
+            .await
+            .unwrap();
+        let (view, index) = result.split_once("\n\nIndex of big.rs:\n").unwrap();
+        assert!(view.len() <= 100 * 1024 + 200);
+        assert!(view.starts_with("[big.rs: lines 1-"));
+        assert!(view.contains("of 300000, "));
+        assert!(view.contains("cut at the 102400 byte read limit (execution.max_read_bytes)"));
+        assert!(view.contains("read on in chunks with offset "));
+        assert!(view.contains("     1\tpub fn handler_0() -> u32 {"));
+        assert!(index.starts_with("     1: pub fn handler_0() -> u32"));
+        assert!(index.ends_with("... 99800 more entries"));
+
+        let shown = view.lines().count() - 1;
+        let next = executor
+            .execute(
+                ToolName::ReadFile,
+                serde_json::json!({ "path": "big.rs", "offset": shown, "limit": 3 }),
+            )
+            .await
+            .unwrap();
+        assert!(next.contains(&format!("\n{:>6}\t", shown + 1)));
+        assert!(!next.contains("Index of"));
+    }
+
+    #[tokio::test]
+    async fn test_read_file_cuts_long_line() {
+        let (executor, dir) = create_executor();
+        let executor = executor.with_max_read_bytes(1000);
+        std::fs::write(dir.path().join("bundle.txt"), "x".repeat(5000)).unwrap();
+
+        let result = executor
+            .execute(
+                ToolName::ReadFile,
+                serde_json::json!({ "path": "bundle.txt" }),
+            )
+            .await
+            .unwrap();
+        assert!(result.starts_with(
+            "[bundle.txt: lines 1-1 of 1, 5000 bytes; cut at the 1000 byte read limit"
+        ));
+        assert!(result.ends_with(" ... (4008 more bytes on this line)"));
+    }
+
+    #[tokio::test]
+    async fn test_edit_file_returns_diff_artifact() {
+        let (executor, dir) = create_executor();
+        std::fs::write(dir.path().join("notes.txt"), "one\ntwo\n").unwrap();
+
+        let output = executor
+            .execute_output(
+                ToolName::EditFile,
+                serde_json::json!({ "path": "notes.txt", "old_text": "two", "new_text": "2" }),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            output.text,
+            "Edited notes.txt:\n--- a/notes.txt\n+++ b/notes.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n"
+        );
+        let [ToolArtifact::Diff { diff }] = output.artifacts.as_slice() else {
+            panic!("expected one diff, got {:?}", output.artifacts);
+        };
+        assert_eq!(diff.path, "notes.txt");
+        assert_eq!(diff.status, crate::git::FileStatus::Modified);
+        assert_eq!(diff.hunks.len(), 1);
+
+        let read = executor
+            .execute_output(
+                ToolName::ReadFile,
+                serde_json::json!({ "path": "notes.txt" }),
+            )
+            .await
+            .unwrap();
+        assert!(read.artifacts.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_edit_runs_formatter_when_enabled() {
+        let (executor, dir) = create_executor();
+        let config_path = dir.path().join(".devflow").join("config.toml");
+        let mut config = std::fs::read_to_string(&config_path).unwrap();
+        config.push_str(
+            "\n[formatting]\non_edit = true\ncommands = { txt = \"sed -i.bak s/messy/tidy/\" }\n",
+        );
+        std::fs::write(&config_path, config).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "first\n").unwrap();
+
+        let result = executor
+            .execute(
+                ToolName::EditFile,
+                serde_json::json!({ "path": "notes.txt", "old_text": "first", "new_text": "messy" }),
+            )
+            .await
+            .unwrap();
+
+        assert!(result.contains("Formatted with sed"));
+        let edited = std::fs::read_to_string(dir.path().join("notes.txt")).unwrap();
+        assert_eq!(edited, "tidy\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_after_batch_checks_edited_files_once() {
+        let (executor, dir) = create_executor();
+        let config_path = dir.path().join(".devflow").join("config.toml");
+        let mut config = std::fs::read_to_string(&config_path).unwrap();
+        config
+            .push_str("\n[diagnostics]\ncommands = { txt = \"echo 'notes.txt: bad'; exit 1\" }\n");
+        std::fs::write(&config_path, config).unwrap();
+
+        assert!(executor.after_batch().await.is_none());
+
+        executor
+            .execute(
+                ToolName::WriteFile,
+                serde_json::json!({ "path": "notes.txt", "content": "text" }),
+            )
+            .await
+            .unwrap();
+        let note = executor.after_batch().await.unwrap();
+        assert!(note.contains("notes.txt: bad"));
+
+        // Paths are cleared once checked
+        assert!(executor.after_batch().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_edits_snapshot_original_content_once() {
+        let (executor, dir) = create_executor();
+        std::fs::write(dir.path().join("existing.txt"), "before").unwrap();
+
+        for (path, content) in [
+            ("existing.txt", "first"),
+            ("existing.txt", "second"),
+            ("new.txt", "created"),
+        ] {
+            executor
+                .execute(
+                    ToolName::WriteFile,
+                    serde_json::json!({ "path": path, "content": content }),
+                )
+                .await
+                .unwrap();
+        }
+
+        let snapshots = executor.session.turn_snapshots().await;
+        let originals: Vec<(&str, Option<&str>)> = snapshots
+            .iter()
+            .map(|s| (s.path.as_str(), s.original.as_deref()))
+            .collect();
+        assert_eq!(
+            originals,
+            vec![("existing.txt", Some("before")), ("new.txt", None)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_file_appends_attribution() {
+        let (executor, dir) = create_executor();
+        let executor = executor.with_attribution(Some(Attribution::new(
+            "devflow",
+            "test-model",
+            "2025-01-31",
+        )));
+
+        executor
+            .execute(
+                ToolName::WriteFile,
+                serde_json::json!({ "path": "main.py", "content": "print(1)\n" }),
+            )
+            .await
+            .unwrap();
+
+        let written = std::fs::read_to_string(dir.path().join("main.py")).unwrap();
+        assert_eq!(
+            written,
+            "print(1)\n# Generated-by: devflow (test-model) 2025-01-31\n"
+        );
     }
 
     #[tokio::test]
@@ -277,7 +969,7 @@ max_tool_iterations = 50
                 serde_json::json!({ "path": "test.txt" }),
             )
             .await;
-        assert_eq!(result.unwrap(), "hello rust");
+        assert!(result.unwrap().ends_with("     1\thello rust"));
     }
 
     #[tokio::test]
@@ -369,10 +1061,12 @@ max_tool_iterations = 50
         let executor = LocalExecutor::with_session(
             temp_dir.path().to_path_buf(),
             30,
+            &NetworkConfig::default(),
             session.clone(),
             CancellationToken::new(),
             Arc::new(SessionUsageTracker::new()),
-        );
+        )
+        .unwrap();
 
         executor
             .execute(
@@ -430,6 +1124,144 @@ max_tool_iterations = 50
         assert!(result.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_glob_braces_and_excludes() {
+        let (executor, dir) = create_executor();
+        for path in [
+            "src/app.ts",
+            "src/view.tsx",
+            "src/style.css",
+            "tests/app.test.ts",
+            "web/dist/bundle.ts",
+        ] {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let result = executor
+            .execute(
+                ToolName::Glob,
+                serde_json::json!({
+                    "pattern": "{src,tests,web}/**/*.{ts,tsx}",
+                    "exclude": ["!**/dist/**", "**/*.test.ts"]
+                }),
+            )
+            .await
+            .unwrap();
+        let mut paths: Vec<&str> = result.lines().collect();
+        paths.sort();
+        assert_eq!(paths, vec!["src/app.ts", "src/view.tsx"]);
+    }
+
+    #[tokio::test]
+    async fn test_glob_stays_inside_the_project() {
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::write(outside.path().join("secret.txt"), "").unwrap();
+        let (executor, dir) = create_executor();
+        let project = dir.path().file_name().unwrap().to_string_lossy();
+
+        for pattern in [
+            format!("{}/*", outside.path().display()),
+            "../**/*".to_string(),
+            format!("{{*.txt,../{}/*}}", project),
+        ] {
+            let result = executor
+                .execute(ToolName::Glob, serde_json::json!({ "pattern": pattern }))
+                .await;
+            assert!(
+                matches!(result, Err(AgentError::InvalidToolInput(_))),
+                "{}: {:?}",
+                pattern,
+                result
+            );
+        }
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(outside.path(), dir.path().join("link")).unwrap();
+            let result = executor
+                .execute(ToolName::Glob, serde_json::json!({ "pattern": "link/*" }))
+                .await
+                .unwrap();
+            assert!(!result.contains("secret.txt"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_glob_reports_capped_count() {
+        let (executor, dir) = create_executor();
+        for i in 0..1005 {
+            std::fs::write(dir.path().join(format!("file{}.txt", i)), "").unwrap();
+        }
+
+        let result = executor
+            .execute(ToolName::Glob, serde_json::json!({ "pattern": "*.txt" }))
+            .await
+            .unwrap();
+        let lines: Vec<&str> = result.lines().collect();
+        assert_eq!(lines.len(), 1001);
+        assert_eq!(
+            lines[1000],
+            "... (1000 newest of 1005 matches shown; narrow the pattern or add exclude patterns)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grep_output_modes_and_context() {
+        let (executor, dir) = create_executor();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(
+            dir.path().join("src/a.rs"),
+            "fn one() {}\n// TODO: tidy\nfn two() {}\nfn three() {}\n// todo: test\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("src/b.rs"), "struct B;\n").unwrap();
+
+        let grep = |input: serde_json::Value| {
+            let executor = &executor;
+            async move { executor.execute(ToolName::Grep, input).await.unwrap() }
+        };
+
+        assert_eq!(
+            grep(serde_json::json!({ "pattern": "todo", "path": "src", "-i": true })).await,
+            "src/a.rs:2:// TODO: tidy\nsrc/a.rs:5:// todo: test"
+        );
+        assert_eq!(
+            grep(serde_json::json!({ "pattern": "TODO", "path": "src", "-A": 1 })).await,
+            "src/a.rs:2:// TODO: tidy\nsrc/a.rs-3-fn two() {}"
+        );
+        assert_eq!(
+            grep(serde_json::json!({ "pattern": "(?i)todo", "path": "src", "-B": 1 })).await,
+            "src/a.rs-1-fn one() {}\nsrc/a.rs:2:// TODO: tidy\n--\n\
+             src/a.rs-4-fn three() {}\nsrc/a.rs:5:// todo: test"
+        );
+        assert_eq!(
+            grep(serde_json::json!({
+                "pattern": "fn",
+                "path": "src",
+                "output_mode": "count"
+            }))
+            .await,
+            "src/a.rs:3"
+        );
+        assert_eq!(
+            grep(serde_json::json!({
+                "pattern": "tidy.fn two",
+                "path": "src",
+                "multiline": true,
+                "output_mode": "files_with_matches"
+            }))
+            .await,
+            "src/a.rs"
+        );
+        assert_eq!(
+            grep(serde_json::json!({ "pattern": "tidy\\nfn", "path": "src", "multiline": true }))
+                .await,
+            "src/a.rs:2:// TODO: tidy\nsrc/a.rs:3:fn two() {}"
+        );
+    }
+
     #[tokio::test]
     async fn test_grep_invalid_regex() {
         let (executor, _dir) = create_executor();
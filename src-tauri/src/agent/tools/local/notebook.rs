@@ -1,9 +1,123 @@
 use tokio::fs;
 
 use super::context::ExecutionContext;
+use super::output::clean;
 use crate::agent::error::AgentError;
 use crate::agent::tools::types::{NotebookEditInput, NotebookReadInput};
 
+/// Each rendered output is cut to this size, keeping its start and end;
+/// ANSI color codes, which tracebacks are full of, are stripped first
+const MAX_OUTPUT_BYTES: usize = 2000;
+
+/// Joins a notebook text field, stored either as a string or a list of lines.
+fn join_text(value: Option<&serde_json::Value>) -> String {
+    match value {
+        Some(serde_json::Value::Array(lines)) => lines
+            .iter()
+            .filter_map(|v| v.as_str())
+            .collect::<Vec<_>>()
+            .join(""),
+        Some(serde_json::Value::String(s)) => s.clone(),
+        _ => String::new(),
+    }
+}
+
+fn truncate_output(text: &str) -> String {
+    clean(text.trim_end(), MAX_OUTPUT_BYTES)
+}
+
+/// Renders one output as text. Rich data without a text/plain form becomes a
+/// placeholder naming its MIME types.
+fn render_output(output: &serde_json::Value) -> String {
+    match output.get("output_type").and_then(|t| t.as_str()) {
+        Some("stream") => {
+            let name = output
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("stdout");
+            format!(
+                "[{}]\n{}",
+                name,
+                truncate_output(&join_text(output.get("text")))
+            )
+        }
+        Some("execute_result" | "display_data") => {
+            let Some(data) = output.get("data").and_then(|d| d.as_object()) else {
+                return "[empty output]".to_string();
+            };
+            let mut parts = Vec::new();
+            if let Some(text) = data.get("text/plain") {
+                parts.push(truncate_output(&join_text(Some(text))));
+            }
+            let omitted: Vec<&str> = data
+                .keys()
+                .map(String::as_str)
+                .filter(|mime| *mime != "text/plain")
+                .filter(|mime| parts.is_empty() || mime.starts_with("image/"))
+                .collect();
+            if !omitted.is_empty() {
+                parts.push(format!("[{} output omitted]", omitted.join(", ")));
+            }
+            parts.join("\n")
+        }
+        Some("error") => {
+            let name = output.get("ename").and_then(|n| n.as_str()).unwrap_or("");
+            let value = output.get("evalue").and_then(|v| v.as_str()).unwrap_or("");
+            let traceback = output
+                .get("traceback")
+                .and_then(|t| t.as_array())
+                .map(|lines| {
+                    lines
+                        .iter()
+                        .filter_map(|l| l.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                })
+                .unwrap_or_default();
+            format!(
+                "[error] {}: {}\n{}",
+                name,
+                value,
+                truncate_output(&traceback)
+            )
+        }
+        other => format!("[{} output]", other.unwrap_or("unknown")),
+    }
+}
+
+fn render_cell(index: usize, cell: &serde_json::Value, include_outputs: bool) -> String {
+    let cell_type = cell
+        .get("cell_type")
+        .and_then(|t| t.as_str())
+        .unwrap_or("unknown");
+    let source = join_text(cell.get("source"));
+    let mut rendered = format!("--- Cell {} ({}) ---\n{}", index, cell_type, source);
+
+    if !include_outputs {
+        return rendered;
+    }
+    let outputs = cell
+        .get("outputs")
+        .and_then(|o| o.as_array())
+        .filter(|o| !o.is_empty());
+    if let Some(outputs) = outputs {
+        let count = cell
+            .get("execution_count")
+            .and_then(|c| c.as_u64())
+            .map(|c| format!(" [{}]", c))
+            .unwrap_or_default();
+        rendered.push_str(&format!("\n--- Output{} ---\n", count));
+        rendered.push_str(
+            &outputs
+                .iter()
+                .map(render_output)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+    rendered
+}
+
 pub async fn read(ctx: &ExecutionContext, input: serde_json::Value) -> Result<String, AgentError> {
     let input: NotebookReadInput = serde_json::from_value(input)
         .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
@@ -25,40 +139,37 @@ pub async fn read(ctx: &ExecutionContext, input: serde_json::Value) -> Result<St
     let output: Vec<String> = cells
         .iter()
         .enumerate()
-        .map(|(i, cell)| {
-            let cell_type = cell
-                .get("cell_type")
-                .and_then(|t| t.as_str())
-                .unwrap_or("unknown");
-
-            let source = cell
-                .get("source")
-                .and_then(|s| {
-                    if let Some(arr) = s.as_array() {
-                        Some(
-                            arr.iter()
-                                .filter_map(|v| v.as_str())
-                                .collect::<Vec<_>>()
-                                .join(""),
-                        )
-                    } else {
-                        s.as_str().map(|s| s.to_string())
-                    }
-                })
-                .unwrap_or_default();
-
-            format!("--- Cell {} ({}) ---\n{}", i, cell_type, source)
-        })
+        .map(|(i, cell)| render_cell(i, cell, input.include_outputs))
         .collect();
 
     Ok(output.join("\n\n"))
 }
 
+/// Empties outputs and execution counts of all code cells, returning how many had outputs.
+fn clear_outputs(cells: &mut [serde_json::Value]) -> usize {
+    let mut cleared = 0;
+    for cell in cells
+        .iter_mut()
+        .filter(|c| c.get("cell_type").and_then(|t| t.as_str()) == Some("code"))
+    {
+        if cell
+            .get("outputs")
+            .and_then(|o| o.as_array())
+            .is_some_and(|o| !o.is_empty())
+        {
+            cleared += 1;
+        }
+        cell["outputs"] = serde_json::json!([]);
+        cell["execution_count"] = serde_json::Value::Null;
+    }
+    cleared
+}
+
 pub async fn edit(ctx: &ExecutionContext, input: serde_json::Value) -> Result<String, AgentError> {
     let input: NotebookEditInput = serde_json::from_value(input)
         .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
 
-    let path = ctx.resolve_path(&input.path)?;
+    let path = ctx.resolve_write_path(&input.path)?;
 
     let content = ctx
         .with_timeout("read notebook", fs::read_to_string(&path))
@@ -72,72 +183,212 @@ pub async fn edit(ctx: &ExecutionContext, input: serde_json::Value) -> Result<St
         .and_then(|c| c.as_array_mut())
         .ok_or_else(|| AgentError::ToolExecutionError("Notebook has no cells array".to_string()))?;
 
-    let edit_mode = input.edit_mode.as_deref().unwrap_or("replace");
-    let cell_idx = input.cell_number as usize;
-
-    match edit_mode {
-        "replace" => {
-            if cell_idx >= cells.len() {
-                return Err(AgentError::ToolExecutionError(format!(
-                    "Cell {} does not exist (notebook has {} cells)",
-                    cell_idx,
-                    cells.len()
-                )));
+    let only_clearing = input.clear_outputs
+        && input.edit_mode.is_none()
+        && input.cell_number.is_none()
+        && input.new_source.is_none();
+
+    let mut messages = Vec::new();
+    if !only_clearing {
+        let edit_mode = input.edit_mode.as_deref().unwrap_or("replace");
+        let cell_idx = input.cell_number.ok_or_else(|| {
+            AgentError::InvalidToolInput(format!("cell_number is required for {}", edit_mode))
+        })? as usize;
+        let new_source = || {
+            input.new_source.as_deref().ok_or_else(|| {
+                AgentError::InvalidToolInput(format!("new_source is required for {}", edit_mode))
+            })
+        };
+
+        match edit_mode {
+            "replace" => {
+                if cell_idx >= cells.len() {
+                    return Err(AgentError::ToolExecutionError(format!(
+                        "Cell {} does not exist (notebook has {} cells)",
+                        cell_idx,
+                        cells.len()
+                    )));
+                }
+                let source_lines: Vec<serde_json::Value> = new_source()?
+                    .lines()
+                    .map(|l| serde_json::Value::String(format!("{}\n", l)))
+                    .collect();
+                cells[cell_idx]["source"] = serde_json::Value::Array(source_lines);
             }
-            let source_lines: Vec<serde_json::Value> = input
-                .new_source
-                .lines()
-                .map(|l| serde_json::Value::String(format!("{}\n", l)))
-                .collect();
-            cells[cell_idx]["source"] = serde_json::Value::Array(source_lines);
-        }
-        "insert" => {
-            let cell_type = input.cell_type.as_deref().unwrap_or("code");
-            let source_lines: Vec<serde_json::Value> = input
-                .new_source
-                .lines()
-                .map(|l| serde_json::Value::String(format!("{}\n", l)))
-                .collect();
-            let new_cell = serde_json::json!({
-                "cell_type": cell_type,
-                "source": source_lines,
-                "metadata": {},
-                "outputs": []
-            });
-            if cell_idx > cells.len() {
-                cells.push(new_cell);
-            } else {
-                cells.insert(cell_idx, new_cell);
+            "insert" => {
+                let cell_type = input.cell_type.as_deref().unwrap_or("code");
+                let source_lines: Vec<serde_json::Value> = new_source()?
+                    .lines()
+                    .map(|l| serde_json::Value::String(format!("{}\n", l)))
+                    .collect();
+                let new_cell = serde_json::json!({
+                    "cell_type": cell_type,
+                    "source": source_lines,
+                    "metadata": {},
+                    "outputs": []
+                });
+                if cell_idx > cells.len() {
+                    cells.push(new_cell);
+                } else {
+                    cells.insert(cell_idx, new_cell);
+                }
             }
-        }
-        "delete" => {
-            if cell_idx >= cells.len() {
-                return Err(AgentError::ToolExecutionError(format!(
-                    "Cell {} does not exist (notebook has {} cells)",
-                    cell_idx,
-                    cells.len()
-                )));
+            "delete" => {
+                if cell_idx >= cells.len() {
+                    return Err(AgentError::ToolExecutionError(format!(
+                        "Cell {} does not exist (notebook has {} cells)",
+                        cell_idx,
+                        cells.len()
+                    )));
+                }
+                cells.remove(cell_idx);
+            }
+            _ => {
+                return Err(AgentError::InvalidToolInput(format!(
+                    "Unknown edit_mode: {}",
+                    edit_mode
+                )))
             }
-            cells.remove(cell_idx);
-        }
-        _ => {
-            return Err(AgentError::InvalidToolInput(format!(
-                "Unknown edit_mode: {}",
-                edit_mode
-            )))
         }
+        messages.push(format!(
+            "Successfully {} cell {} in {}",
+            edit_mode,
+            cell_idx,
+            path.display()
+        ));
+    }
+
+    if input.clear_outputs {
+        let cleared = clear_outputs(cells);
+        messages.push(format!(
+            "Cleared outputs of {} cells in {}",
+            cleared,
+            path.display()
+        ));
     }
 
     let new_content = serde_json::to_string_pretty(&notebook)
         .map_err(|e| AgentError::ToolExecutionError(format!("Failed to serialize: {}", e)))?;
 
-    ctx.with_timeout("write notebook", fs::write(&path, &new_content))
+    ctx.with_timeout("write notebook", ctx.write(&path, &new_content))
         .await?;
 
-    Ok(format!(
-        "Successfully {} cell {} in {}",
-        edit_mode,
-        cell_idx,
-        path.display()
-    ))
+    Ok(messages.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::NetworkConfig;
+
+    fn notebook() -> serde_json::Value {
+        serde_json::json!({
+            "cells": [
+                {"cell_type": "markdown", "source": ["# Title\n"], "metadata": {}},
+                {
+                    "cell_type": "code",
+                    "execution_count": 3,
+                    "source": ["df.head()\n"],
+                    "metadata": {},
+                    "outputs": [
+                        {"output_type": "stream", "name": "stdout", "text": ["loading\n"]},
+                        {
+                            "output_type": "execute_result",
+                            "data": {"text/plain": ["   a  b\n0  1  2"], "text/html": ["<table/>"]}
+                        },
+                        {"output_type": "display_data", "data": {"image/png": "iVBORw0KGgo="}},
+                        {
+                            "output_type": "error",
+                            "ename": "KeyError",
+                            "evalue": "'c'",
+                            "traceback": ["\u{1b}[0;31mKeyError\u{1b}[0m: 'c'"]
+                        }
+                    ]
+                }
+            ],
+            "metadata": {},
+            "nbformat": 4,
+            "nbformat_minor": 5
+        })
+    }
+
+    fn setup() -> (ExecutionContext, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("nb.ipynb"),
+            serde_json::to_string(&notebook()).unwrap(),
+        )
+        .unwrap();
+        let ctx =
+            ExecutionContext::new(dir.path().to_path_buf(), 30, &NetworkConfig::default()).unwrap();
+        (ctx, dir)
+    }
+
+    #[tokio::test]
+    async fn test_read_with_outputs() {
+        let (ctx, _dir) = setup();
+
+        let without = read(&ctx, serde_json::json!({"path": "nb.ipynb"}))
+            .await
+            .unwrap();
+        assert!(without.contains("df.head()"));
+        assert!(!without.contains("Output"));
+
+        let with = read(
+            &ctx,
+            serde_json::json!({"path": "nb.ipynb", "include_outputs": true}),
+        )
+        .await
+        .unwrap();
+        assert!(with.contains("--- Output [3] ---"));
+        assert!(with.contains("[stdout]\nloading"));
+        assert!(with.contains("0  1  2"));
+        assert!(!with.contains("<table/>"));
+        assert!(with.contains("[image/png output omitted]"));
+        assert!(with.contains("[error] KeyError: 'c'\nKeyError: 'c'"));
+    }
+
+    #[test]
+    fn test_long_output_is_truncated() {
+        let output = serde_json::json!({
+            "output_type": "stream",
+            "name": "stdout",
+            "text": "x".repeat(MAX_OUTPUT_BYTES + 10)
+        });
+        let rendered = render_output(&output);
+        assert!(rendered.contains("(10 of 2010 bytes omitted)"));
+    }
+
+    #[tokio::test]
+    async fn test_edit_clears_outputs() {
+        let (ctx, dir) = setup();
+
+        let result = edit(
+            &ctx,
+            serde_json::json!({"path": "nb.ipynb", "clear_outputs": true}),
+        )
+        .await
+        .unwrap();
+        assert!(result.starts_with("Cleared outputs of 1 cells"));
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join("nb.ipynb")).unwrap())
+                .unwrap();
+        assert_eq!(saved["cells"][1]["outputs"], serde_json::json!([]));
+        assert!(saved["cells"][1]["execution_count"].is_null());
+        assert_eq!(saved["cells"][1]["source"][0], "df.head()\n");
+    }
+
+    #[tokio::test]
+    async fn test_edit_requires_source_for_replace() {
+        let (ctx, _dir) = setup();
+        let result = edit(
+            &ctx,
+            serde_json::json!({"path": "nb.ipynb", "cell_number": 1}),
+        )
+        .await;
+        assert!(
+            matches!(result, Err(AgentError::InvalidToolInput(msg)) if msg.contains("new_source"))
+        );
+    }
 }
@@ -0,0 +1,372 @@
+//! File outlines: the declarations of a source file with their line numbers,
+//! parsed with tree-sitter, so the agent can find its way around a large file
+//! without reading all of it.
+
+use tokio::fs;
+use tree_sitter::{Language, Node, Parser};
+
+use super::context::ExecutionContext;
+use crate::agent::error::AgentError;
+use crate::agent::tools::types::OutlineFileInput;
+
+/// Longest signature shown; longer ones are cut with an ellipsis
+const MAX_SIGNATURE_CHARS: usize = 160;
+/// Most entries in the index of a file too large to read at once
+const MAX_INDEX_ENTRIES: usize = 200;
+
+/// Node kinds listed in an outline, per grammar.
+struct OutlineSpec {
+    /// Declarations shown in the outline
+    symbols: &'static [&'static str],
+    /// Symbols whose members are outlined one level deeper
+    containers: &'static [&'static str],
+}
+
+const RUST: OutlineSpec = OutlineSpec {
+    symbols: &[
+        "function_item",
+        "function_signature_item",
+        "struct_item",
+        "enum_item",
+        "union_item",
+        "trait_item",
+        "impl_item",
+        "mod_item",
+        "type_item",
+        "const_item",
+        "static_item",
+        "macro_definition",
+    ],
+    containers: &["impl_item", "trait_item", "mod_item"],
+};
+
+const PYTHON: OutlineSpec = OutlineSpec {
+    symbols: &["class_definition", "function_definition"],
+    containers: &["class_definition"],
+};
+
+const TYPESCRIPT: OutlineSpec = OutlineSpec {
+    symbols: &[
+        "function_declaration",
+        "generator_function_declaration",
+        "class_declaration",
+        "abstract_class_declaration",
+        "method_definition",
+        "abstract_method_signature",
+        "interface_declaration",
+        "type_alias_declaration",
+        "enum_declaration",
+        "internal_module",
+    ],
+    containers: &[
+        "class_declaration",
+        "abstract_class_declaration",
+        "internal_module",
+    ],
+};
+
+const GO: OutlineSpec = OutlineSpec {
+    symbols: &[
+        "function_declaration",
+        "method_declaration",
+        "type_spec",
+        "const_declaration",
+    ],
+    containers: &[],
+};
+
+/// Grammar and outline spec for a file extension.
+fn language_for(extension: &str) -> Option<(Language, &'static OutlineSpec)> {
+    let language = match extension {
+        "rs" => (tree_sitter_rust::LANGUAGE.into(), &RUST),
+        "py" | "pyi" => (tree_sitter_python::LANGUAGE.into(), &PYTHON),
+        "ts" | "mts" | "cts" => (
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            &TYPESCRIPT,
+        ),
+        "tsx" => (tree_sitter_typescript::LANGUAGE_TSX.into(), &TYPESCRIPT),
+        "js" | "jsx" | "mjs" | "cjs" => (tree_sitter_javascript::LANGUAGE.into(), &TYPESCRIPT),
+        "go" => (tree_sitter_go::LANGUAGE.into(), &GO),
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// One outline entry.
+#[derive(Debug, PartialEq)]
+struct Symbol {
+    /// 1-based line of the declaration
+    line: usize,
+    depth: usize,
+    signature: String,
+}
+
+/// Declarations in `source`, or None if the extension has no grammar.
+fn outline(source: &str, extension: &str) -> Option<Vec<Symbol>> {
+    let (language, spec) = language_for(extension)?;
+    let mut parser = Parser::new();
+    parser.set_language(&language).ok()?;
+    let tree = parser.parse(source, None)?;
+
+    let mut symbols = Vec::new();
+    collect(tree.root_node(), source, spec, 0, &mut symbols);
+    Some(symbols)
+}
+
+fn collect(node: Node, source: &str, spec: &OutlineSpec, depth: usize, out: &mut Vec<Symbol>) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if spec.symbols.contains(&child.kind()) {
+            out.push(Symbol {
+                line: child.start_position().row + 1,
+                depth,
+                signature: signature(child, source),
+            });
+            // Function bodies are left out; container members go one level deeper
+            if spec.containers.contains(&child.kind()) {
+                collect(child, source, spec, depth + 1, out);
+            }
+        } else {
+            collect(child, source, spec, depth, out);
+        }
+    }
+}
+
+/// The declaration up to its body, on one line.
+fn signature(node: Node, source: &str) -> String {
+    let end = node
+        .child_by_field_name("body")
+        .map(|body| body.start_byte())
+        .unwrap_or_else(|| node.end_byte());
+    let text = &source[node.start_byte()..end];
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = text.trim_end_matches(['{', ' ']).to_string();
+
+    if text.chars().count() > MAX_SIGNATURE_CHARS {
+        let cut: String = text.chars().take(MAX_SIGNATURE_CHARS).collect();
+        format!("{}...", cut)
+    } else {
+        text
+    }
+}
+
+fn format_outline(symbols: &[Symbol], total_lines: usize) -> String {
+    if symbols.is_empty() {
+        return format!("No declarations found ({} lines)", total_lines);
+    }
+    let width = total_lines.to_string().len();
+    let mut output = format!("{} lines, {} declarations\n", total_lines, symbols.len());
+    for symbol in symbols {
+        output.push_str(&format!(
+            "{:>width$}: {}{}\n",
+            symbol.line,
+            "  ".repeat(symbol.depth),
+            symbol.signature,
+            width = width
+        ));
+    }
+    output.pop();
+    output
+}
+
+/// Headings of a Markdown document, skipping fenced code blocks.
+fn markdown_headings(source: &str) -> Vec<Symbol> {
+    let mut headings = Vec::new();
+    let mut in_fence = false;
+    for (i, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if in_fence || level == 0 || level > 6 || !line[level..].starts_with(' ') {
+            continue;
+        }
+        headings.push(Symbol {
+            line: i + 1,
+            depth: level - 1,
+            signature: line.trim_end().to_string(),
+        });
+    }
+    headings
+}
+
+/// Index for read_file results cut at the read limit: the declarations of
+/// source files outline_file supports, or the headings of Markdown files.
+/// None for other files, or when there is nothing to list.
+pub(super) fn file_index(source: &str, extension: &str) -> Option<String> {
+    let symbols = match extension {
+        "md" | "markdown" => markdown_headings(source),
+        _ => outline(source, extension)?,
+    };
+    if symbols.is_empty() {
+        return None;
+    }
+
+    let width = source.lines().count().to_string().len();
+    let mut index = String::new();
+    for symbol in symbols.iter().take(MAX_INDEX_ENTRIES) {
+        index.push_str(&format!(
+            "{:>width$}: {}{}\n",
+            symbol.line,
+            "  ".repeat(symbol.depth),
+            symbol.signature,
+            width = width
+        ));
+    }
+    if symbols.len() > MAX_INDEX_ENTRIES {
+        index.push_str(&format!(
+            "... {} more entries\n",
+            symbols.len() - MAX_INDEX_ENTRIES
+        ));
+    }
+    index.pop();
+    Some(index)
+}
+
+pub async fn outline_file(
+    ctx: &ExecutionContext,
+    input: serde_json::Value,
+) -> Result<String, AgentError> {
+    let input: OutlineFileInput = serde_json::from_value(input)
+        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
+
+    let path = ctx.resolve_path(&input.path)?;
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_lowercase)
+        .unwrap_or_default();
+
+    let source = ctx
+        .with_timeout("read file", fs::read_to_string(&path))
+        .await?;
+
+    let symbols = outline(&source, &extension).ok_or_else(|| {
+        AgentError::InvalidToolInput(format!(
+            "No outline support for '.{}' files (supported: rs, py, ts, tsx, js, jsx, go); use read_file",
+            extension
+        ))
+    })?;
+
+    Ok(format_outline(&symbols, source.lines().count()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signatures(source: &str, extension: &str) -> Vec<(usize, usize, String)> {
+        outline(source, extension)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.line, s.depth, s.signature))
+            .collect()
+    }
+
+    #[test]
+    fn test_rust_outline() {
+        let source = r#"
+use std::fmt;
+
+pub struct Point {
+    x: i32,
+}
+
+impl Point {
+    pub fn new(x: i32) -> Self {
+        fn helper() {}
+        Self { x }
+    }
+}
+
+pub(crate) fn distance(
+    a: &Point,
+    b: &Point,
+) -> i32 {
+    a.x - b.x
+}
+"#;
+        assert_eq!(
+            signatures(source, "rs"),
+            vec![
+                (4, 0, "pub struct Point".to_string()),
+                (8, 0, "impl Point".to_string()),
+                (9, 1, "pub fn new(x: i32) -> Self".to_string()),
+                (
+                    15,
+                    0,
+                    "pub(crate) fn distance( a: &Point, b: &Point, ) -> i32".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_python_outline() {
+        let source = "class Greeter:\n    @staticmethod\n    def hello(name):\n        return name\n\ndef main():\n    pass\n";
+        assert_eq!(
+            signatures(source, "py"),
+            vec![
+                (1, 0, "class Greeter:".to_string()),
+                (3, 1, "def hello(name):".to_string()),
+                (6, 0, "def main():".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typescript_outline() {
+        let source = "export interface Props { name: string }\n\nexport class Store {\n  load(id: string): Promise<void> {\n    return fetch(id);\n  }\n}\n\nexport function App(props: Props) {\n  return null;\n}\n";
+        assert_eq!(
+            signatures(source, "tsx"),
+            vec![
+                (1, 0, "interface Props".to_string()),
+                (3, 0, "class Store".to_string()),
+                (4, 1, "load(id: string): Promise<void>".to_string()),
+                (9, 0, "function App(props: Props)".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file_index_lists_markdown_headings() {
+        let source =
+            "# Guide\n\nIntro\n\n## Setup\n```sh\n# not a heading\n```\n#hashtag\n### Details\n";
+        assert_eq!(
+            file_index(source, "md").unwrap(),
+            " 1: # Guide\n 5:   ## Setup\n10:     ### Details"
+        );
+        assert!(file_index("plain text", "txt").is_none());
+        assert!(file_index("fn main() {}", "rs")
+            .unwrap()
+            .ends_with("1: fn main()"));
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        assert!(outline("hello", "txt").is_none());
+    }
+
+    #[test]
+    fn test_format_outline() {
+        let symbols = vec![
+            Symbol {
+                line: 8,
+                depth: 0,
+                signature: "impl Point".to_string(),
+            },
+            Symbol {
+                line: 120,
+                depth: 1,
+                signature: "fn new()".to_string(),
+            },
+        ];
+        assert_eq!(
+            format_outline(&symbols, 300),
+            "300 lines, 2 declarations\n  8: impl Point\n120:   fn new()"
+        );
+        assert_eq!(format_outline(&[], 3), "No declarations found (3 lines)");
+    }
+}
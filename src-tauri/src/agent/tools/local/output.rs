@@ -0,0 +1,155 @@
+//! Command output as the model gets it: terminal escape codes removed,
+//! progress lines redrawn with `\r` collapsed to their final state, and long
+//! output cut in the middle, keeping the start and the end, where errors and
+//! summaries usually are.
+
+/// Long output keeps this fraction of the limit from its start, the rest from its end.
+const HEAD_DIVISOR: usize = 4;
+
+/// Cleans up raw output and truncates it to `max_bytes`.
+pub(super) fn clean(text: &str, max_bytes: usize) -> String {
+    truncate_middle(&strip_ansi(text), max_bytes)
+}
+
+/// Removes ANSI escape sequences (colors, cursor movement, window titles)
+/// and keeps only what is left visible of lines redrawn with `\r`.
+pub(super) fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\u{1b}' => match chars.next() {
+                // CSI: parameters up to a final byte in @..~
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, DCS and the other string sequences: up to BEL or ESC \
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\u{7}' || (c == '\u{1b}' && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+                // Character set selection takes one more character
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            // C1 form of CSI
+            '\u{9b}' => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            _ => stripped.push(c),
+        }
+    }
+
+    if !stripped.contains('\r') {
+        return stripped;
+    }
+    stripped
+        .split('\n')
+        .map(|line| {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            line.rsplit('\r')
+                .find(|redraw| !redraw.is_empty())
+                .unwrap_or("")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Cuts `text` to about `max_bytes`, dropping the middle and noting how many
+/// bytes were left out. Cuts at line breaks when one is close.
+pub(super) fn truncate_middle(text: &str, max_bytes: usize) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+
+    let head_bytes = max_bytes / HEAD_DIVISOR;
+    let tail_bytes = max_bytes - head_bytes;
+
+    let mut head_end = floor_char_boundary(text, head_bytes);
+    if let Some(newline) = text[..head_end].rfind('\n') {
+        if newline >= head_end / 2 {
+            head_end = newline;
+        }
+    }
+    let mut tail_start = ceil_char_boundary(text, text.len() - tail_bytes);
+    if let Some(newline) = text[tail_start..].find('\n') {
+        if newline < tail_bytes / 2 {
+            tail_start += newline + 1;
+        }
+    }
+
+    format!(
+        "{}\n... ({} of {} bytes omitted) ...\n{}",
+        &text[..head_end],
+        tail_start - head_end,
+        text.len(),
+        &text[tail_start..]
+    )
+}
+
+pub(super) fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            strip_ansi("\u{1b}[1m\u{1b}[31merror\u{1b}[0m: expected `;`"),
+            "error: expected `;`"
+        );
+        assert_eq!(strip_ansi("\u{1b}]0;cargo build\u{7}done"), "done");
+        assert_eq!(strip_ansi("\u{1b}(Bplain"), "plain");
+        assert_eq!(
+            strip_ansi("Downloading 10%\rDownloading 60%\rDownloading 100%\r\nDone\r\n"),
+            "Downloading 100%\nDone\n"
+        );
+    }
+
+    #[test]
+    fn test_truncate_middle_keeps_both_ends() {
+        let text: String = (0..1000).map(|i| format!("line {}\n", i)).collect();
+        let truncated = truncate_middle(&text, 400);
+
+        assert!(truncated.len() < 500);
+        assert!(truncated.starts_with("line 0\n"));
+        assert!(truncated.ends_with("line 999\n"));
+        assert!(truncated.contains("bytes omitted) ...\nline"));
+        assert!(truncated.contains(&format!("of {} bytes", text.len())));
+    }
+
+    #[test]
+    fn test_truncate_middle_respects_char_boundaries() {
+        let text = "é".repeat(100);
+        let truncated = truncate_middle(&text, 51);
+        assert!(truncated.starts_with("éééééé"));
+        assert!(truncated.ends_with('é'));
+        assert_eq!(truncate_middle("short", 10), "short");
+    }
+}
@@ -0,0 +1,111 @@
+//! Plans submitted with submit_plan, kept once the user decides on them, and
+//! the steps an approved plan adds to the todo list.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Most steps taken from one plan into the todo list
+const MAX_PLAN_STEPS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    Approved,
+    /// Approved by `[auto_approve] plans` without asking the user
+    AutoApproved,
+    Rejected,
+}
+
+/// A plan the user approved or rejected.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchivedPlan {
+    /// 1 for the session's first plan, 2 for the next, and so on
+    pub number: u32,
+    /// The plan as submitted, in markdown
+    pub plan: String,
+    pub status: PlanStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rejection_reason: Option<String>,
+    /// RFC 3339 UTC timestamp of the decision
+    pub archived_at: String,
+    /// Todo items added for the plan's steps
+    #[serde(default)]
+    pub todo_ids: Vec<String>,
+}
+
+/// Steps of a markdown plan: the top-level numbered items of its section
+/// with "step" in the heading, or of the whole plan when there is no such
+/// section.
+pub(super) fn plan_steps(plan: &str) -> Vec<String> {
+    let mut steps_section = Vec::new();
+    let mut all = Vec::new();
+    // Heading level of the steps section while inside it
+    let mut in_steps: Option<usize> = None;
+
+    for line in plan.lines() {
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if level > 0 && line[level..].starts_with(' ') {
+            in_steps = match in_steps {
+                Some(steps_level) if level > steps_level => Some(steps_level),
+                _ if line.to_lowercase().contains("step") => Some(level),
+                _ => None,
+            };
+            continue;
+        }
+
+        let Some(step) = numbered_item(line) else {
+            continue;
+        };
+        if in_steps.is_some() {
+            steps_section.push(step.clone());
+        }
+        all.push(step);
+    }
+
+    let mut steps = if steps_section.is_empty() {
+        all
+    } else {
+        steps_section
+    };
+    steps.truncate(MAX_PLAN_STEPS);
+    steps
+}
+
+/// The text of an unindented `1. text` or `1) text` line, without bold markers.
+fn numbered_item(line: &str) -> Option<String> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let text = line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "))?
+        .trim()
+        .replace("**", "");
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_steps_prefers_steps_section() {
+        let plan = "## Add auth\n\n### Summary\n1. Not a step\n\n### Steps\n1. Create **User** model\n   - with hashing\n2) Add login endpoint\n#### Details\n3. Add middleware\n\n### Considerations\n1. Tokens expire\n";
+        assert_eq!(
+            plan_steps(plan),
+            vec!["Create User model", "Add login endpoint", "Add middleware"]
+        );
+    }
+
+    #[test]
+    fn test_plan_steps_without_steps_section() {
+        let plan =
+            "Refactor the parser:\n\n1. Split the lexer\n2. Add tests\n  3. nested\n10.no space\n";
+        assert_eq!(plan_steps(plan), vec!["Split the lexer", "Add tests"]);
+        assert!(plan_steps("Just a paragraph").is_empty());
+    }
+}
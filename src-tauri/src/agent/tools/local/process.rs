@@ -0,0 +1,143 @@
+//! Bash tool commands run in their own process group (a new process group on
+//! Windows, killed as a tree), so a timeout or a cancelled run kills what
+//! they started too, e.g. a dev server, and not just the shell.
+//!
+//! Windows has no job object here yet: `taskkill /T` only finds the tree
+//! through a live shell, so background jobs left behind by a command that
+//! exited are not tracked and are not killed with the session.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::process::Command;
+
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+
+/// Process groups started by a session's commands, by leader pid. A group
+/// that outlives its command (a background job) stays tracked until the
+/// session is cleared, its window closes or the app exits.
+#[derive(Clone, Default)]
+pub struct ProcessRegistry {
+    groups: Arc<Mutex<HashSet<u32>>>,
+}
+
+impl ProcessRegistry {
+    fn track(&self, pid: u32) {
+        self.lock().insert(pid);
+    }
+
+    fn untrack(&self, pid: u32) {
+        self.lock().remove(&pid);
+    }
+
+    #[cfg(test)]
+    pub fn tracked(&self) -> Vec<u32> {
+        self.lock().iter().copied().collect()
+    }
+
+    /// Kills every tracked group that is still running. Groups that have
+    /// since emptied are dropped unsignalled, as their id may have been reused.
+    pub fn kill_all(&self) {
+        for pid in self.lock().drain() {
+            // Only commands still running are tracked on Windows, see `group_alive`
+            if cfg!(windows) || group_alive(pid) {
+                kill_group(pid);
+            }
+        }
+    }
+
+    // Only holds pids, so a panic while locked leaves nothing half-updated
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashSet<u32>> {
+        self.groups.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Kills a command's process group if the command is dropped before it
+/// finished, on a timeout or when the run is cancelled.
+pub(super) struct GroupGuard {
+    pid: u32,
+    registry: ProcessRegistry,
+    finished: bool,
+}
+
+impl GroupGuard {
+    pub fn new(pid: u32, registry: &ProcessRegistry) -> Self {
+        registry.track(pid);
+        Self {
+            pid,
+            registry: registry.clone(),
+            finished: false,
+        }
+    }
+
+    /// The command exited; its group stays tracked while background jobs remain.
+    pub fn finish(mut self) {
+        self.finished = true;
+        if !group_alive(self.pid) {
+            self.registry.untrack(self.pid);
+        }
+    }
+}
+
+impl Drop for GroupGuard {
+    fn drop(&mut self) {
+        if !self.finished {
+            kill_group(self.pid);
+            self.registry.untrack(self.pid);
+        }
+    }
+}
+
+/// Starts `cmd` as the leader of a new process group. On Unix that is a
+/// new session too, without a controlling terminal, so a program that opens
+/// `/dev/tty` to prompt fails at once instead of stopping in the background.
+pub(super) fn new_group(cmd: &mut Command) {
+    #[cfg(unix)]
+    // SAFETY: setsid is async-signal-safe and touches no memory of the parent
+    unsafe {
+        cmd.pre_exec(|| {
+            if libc::setsid() == -1 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW | CREATE_NEW_PROCESS_GROUP);
+}
+
+#[cfg(unix)]
+fn kill_group(pid: u32) {
+    // SAFETY: kill has no memory effects; a negative pid addresses the group
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+fn kill_group(pid: u32) {
+    use std::os::windows::process::CommandExt;
+    use std::process::Stdio;
+
+    let _ = std::process::Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &pid.to_string()])
+        .creation_flags(CREATE_NO_WINDOW)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+}
+
+#[cfg(unix)]
+fn group_alive(pid: u32) -> bool {
+    // SAFETY: signal 0 only checks that the group exists
+    unsafe { libc::kill(-(pid as libc::pid_t), 0) == 0 }
+}
+
+/// Windows kills by process tree, which is lost once the shell has exited.
+#[cfg(windows)]
+fn group_alive(_pid: u32) -> bool {
+    false
+}
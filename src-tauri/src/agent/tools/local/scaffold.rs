@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use super::context::ExecutionContext;
+use crate::agent::error::AgentError;
+use crate::agent::tools::types::ScaffoldInput;
+use crate::template::{ScaffoldFile, TemplateService, TemplateVariable, TemplateVariableType};
+
+/// A scaffold rendered and checked against the project, ready to write.
+pub(super) struct PlannedScaffold {
+    pub template: String,
+    /// Each file with the absolute path it will be created at
+    pub files: Vec<(ScaffoldFile, PathBuf)>,
+}
+
+fn service() -> Result<TemplateService, AgentError> {
+    TemplateService::new().map_err(|e| AgentError::ToolExecutionError(e.to_string()))
+}
+
+pub(super) fn parse(input: serde_json::Value) -> Result<ScaffoldInput, AgentError> {
+    serde_json::from_value(input)
+        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))
+}
+
+/// The scaffolds the project can use, for calls without a template.
+pub(super) fn list(ctx: &ExecutionContext) -> Result<String, AgentError> {
+    let scaffolds = service()?.list_scaffolds(&ctx.working_dir);
+    if scaffolds.is_empty() {
+        return Ok(
+            "No scaffold templates. Add them to .devflow/templates/<name>/ in the project"
+                .to_string(),
+        );
+    }
+    let lines: Vec<String> = scaffolds
+        .iter()
+        .map(|s| {
+            let mut line = format!("{} ({} files)", s.name, s.files);
+            for variable in &s.variables {
+                line.push_str(&format!("\n  - {}", describe_variable(variable)));
+            }
+            line
+        })
+        .collect();
+    Ok(format!("Available templates:\n{}", lines.join("\n")))
+}
+
+/// `name (boolean, default false): help` for the template list.
+fn describe_variable(variable: &TemplateVariable) -> String {
+    let mut details = vec![match variable.kind {
+        TemplateVariableType::String => "string".to_string(),
+        TemplateVariableType::Number => "number".to_string(),
+        TemplateVariableType::Boolean => "true/false".to_string(),
+        TemplateVariableType::Choice => format!("one of {}", variable.options.join(", ")),
+    }];
+    if let Some(pattern) = &variable.pattern {
+        details.push(format!("matching {}", pattern));
+    }
+    match &variable.default {
+        Some(default) => details.push(format!("default {}", default)),
+        None => details.push("required".to_string()),
+    }
+    let mut line = format!("{} ({})", variable.name, details.join(", "));
+    if let Some(description) = &variable.description {
+        line.push_str(&format!(": {}", description));
+    }
+    line
+}
+
+/// Renders the template and resolves its files like write_file does. Fails
+/// before anything is written if a variable is missing or a file exists.
+pub(super) fn plan(
+    ctx: &ExecutionContext,
+    template: &str,
+    input: &ScaffoldInput,
+) -> Result<PlannedScaffold, AgentError> {
+    let variables: HashMap<String, String> = input
+        .variables
+        .iter()
+        .map(|v| (v.name.clone(), v.value.clone()))
+        .collect();
+    let files = service()?
+        .render_scaffold(&ctx.working_dir, template, &variables)
+        .map_err(|e| AgentError::ToolExecutionError(e.to_string()))?;
+
+    let mut planned = Vec::with_capacity(files.len());
+    for file in files {
+        let path = ctx.resolve_write_path(&file.path)?;
+        if path.exists() {
+            return Err(AgentError::ToolExecutionError(format!(
+                "{} already exists; scaffolds never overwrite files",
+                file.path
+            )));
+        }
+        planned.push((file, path));
+    }
+    Ok(PlannedScaffold {
+        template: template.to_string(),
+        files: planned,
+    })
+}
+
+/// The tool result listing what was (or would be) created.
+pub(super) fn describe(planned: &PlannedScaffold, verb: &str) -> String {
+    let lines: Vec<String> = planned
+        .files
+        .iter()
+        .map(|(file, _)| format!("{} ({} lines)", file.path, file.content.lines().count()))
+        .collect();
+    format!(
+        "{} {} files from template '{}':\n{}",
+        verb,
+        planned.files.len(),
+        planned.template,
+        lines.join("\n")
+    )
+}
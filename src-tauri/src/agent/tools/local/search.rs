@@ -1,8 +1,11 @@
 use scraper::{Html, Selector};
 
 use super::context::{ExecutionContext, MAX_OUTPUT_SIZE};
+use super::output::clean;
 use crate::agent::error::AgentError;
 use crate::agent::tools::types::WebSearchInput;
+use crate::agent::tools::ToolOutput;
+use crate::agent::types::ToolArtifact;
 use crate::config::ConfigService;
 
 const DEFAULT_MAX_RESULTS: usize = 10;
@@ -20,7 +23,7 @@ struct SearchResult {
 pub async fn search(
     ctx: &ExecutionContext,
     input: serde_json::Value,
-) -> Result<String, AgentError> {
+) -> Result<ToolOutput, AgentError> {
     let input: WebSearchInput = serde_json::from_value(input)
         .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
 
@@ -38,19 +41,21 @@ pub async fn search(
         .collect();
 
     if filtered.is_empty() {
-        return Ok("No results found.".to_string());
+        return Ok("No results found.".to_string().into());
     }
 
     let output = format_results(&filtered);
+    let links = filtered
+        .into_iter()
+        .map(|r| ToolArtifact::Link {
+            url: r.url,
+            title: Some(r.title).filter(|title| !title.is_empty()),
+        })
+        .collect();
 
-    Ok(if output.len() > MAX_OUTPUT_SIZE {
-        format!(
-            "{}...\n(truncated, {} bytes total)",
-            &output[..MAX_OUTPUT_SIZE],
-            output.len()
-        )
-    } else {
-        output
+    Ok(ToolOutput {
+        text: clean(&output, MAX_OUTPUT_SIZE),
+        artifacts: links,
     })
 }
 
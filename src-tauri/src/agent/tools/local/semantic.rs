@@ -0,0 +1,88 @@
+//! Semantic code search over the project's embeddings index. The index is
+//! brought up to date before each search, so only files changed since the
+//! last one are re-embedded. Disabled unless the project's `[index]` section
+//! enables it.
+
+use super::context::ExecutionContext;
+use crate::agent::embeddings::EmbeddingClient;
+use crate::agent::error::AgentError;
+use crate::agent::semantic_index::{SearchHit, SemanticIndex};
+use crate::agent::tools::types::SemanticSearchInput;
+use crate::config::ConfigService;
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 50;
+/// Lines of each hit shown under its location
+const PREVIEW_LINES: usize = 3;
+
+pub async fn search(
+    ctx: &ExecutionContext,
+    input: serde_json::Value,
+) -> Result<String, AgentError> {
+    let input: SemanticSearchInput = serde_json::from_value(input)
+        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
+    if input.query.trim().is_empty() {
+        return Err(AgentError::InvalidToolInput(
+            "query must not be empty".to_string(),
+        ));
+    }
+
+    let config = ConfigService::load_project_config(&ctx.working_dir)
+        .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+    if !config.index.enabled {
+        return Err(AgentError::ToolExecutionError(
+            "The semantic index is disabled. Set enabled = true in the [index] section of .devflow/config.toml, or use grep"
+                .to_string(),
+        ));
+    }
+    let client = EmbeddingClient::from_config(&config.index, &config.network)?;
+
+    let mut index = SemanticIndex::load(&ctx.working_dir);
+    let stats = index
+        .update(&ctx.working_dir, &config.index, &client)
+        .await?;
+    if stats.embedded_files > 0 || stats.removed_files > 0 {
+        log::info!(
+            "Semantic index updated: {} files embedded, {} removed",
+            stats.embedded_files,
+            stats.removed_files
+        );
+    }
+
+    let limit = input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+    let hits = index.search(&input.query, limit, &client).await?;
+    if hits.is_empty() {
+        return Ok("No indexed files".to_string());
+    }
+    Ok(hits
+        .iter()
+        .map(|hit| format_hit(ctx, hit))
+        .collect::<Vec<_>>()
+        .join("\n\n"))
+}
+
+/// `path:start-end (score)` followed by the first lines of the chunk.
+fn format_hit(ctx: &ExecutionContext, hit: &SearchHit) -> String {
+    let header = format!(
+        "{}:{}-{} ({:.2})",
+        hit.path, hit.start_line, hit.end_line, hit.score
+    );
+    let preview: Vec<String> = std::fs::read_to_string(ctx.working_dir.join(&hit.path))
+        .map(|content| {
+            content
+                .lines()
+                .skip(hit.start_line - 1)
+                .take(hit.end_line + 1 - hit.start_line)
+                .filter(|line| !line.trim().is_empty())
+                .take(PREVIEW_LINES)
+                .map(|line| format!("  {}", line.trim_end()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if preview.is_empty() {
+        header
+    } else {
+        format!("{}\n{}", header, preview.join("\n"))
+    }
+}
@@ -1,12 +1,20 @@
-use std::path::PathBuf;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command;
 use tokio::time::timeout;
 
-use super::context::ExecutionContext;
+use super::context::MAX_OUTPUT_SIZE;
+use super::interactive;
+use super::output;
+use super::process::{self, ProcessRegistry};
+use super::trailer;
 use crate::agent::error::AgentError;
 use crate::agent::tools::types::BashInput;
+use crate::config::ShellKind;
 
 #[cfg(windows)]
 use crate::git::wsl::WslPath;
@@ -14,10 +22,154 @@ use crate::git::wsl::WslPath;
 #[cfg(windows)]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
+/// PowerShell binary: Windows PowerShell on Windows, PowerShell 7 elsewhere.
+const POWERSHELL: &str = if cfg!(windows) { "powershell" } else { "pwsh" };
+
+/// A POSIX shell construct and how to write it for PowerShell and cmd (None = works there).
+struct BashIsm {
+    pattern: &'static str,
+    powershell: Option<&'static str>,
+    cmd: Option<&'static str>,
+}
+
+const BASH_ISMS: &[BashIsm] = &[
+    BashIsm {
+        pattern: "&&",
+        powershell: Some("Windows PowerShell has no `&&`; use `; if ($?) { ... }`"),
+        cmd: None,
+    },
+    BashIsm {
+        pattern: "||",
+        powershell: Some("Windows PowerShell has no `||`; use `; if (-not $?) { ... }`"),
+        cmd: None,
+    },
+    BashIsm {
+        pattern: "export ",
+        powershell: Some("set variables with `$env:NAME = \"value\"`, not `export`"),
+        cmd: Some("set variables with `set NAME=value`, not `export`"),
+    },
+    BashIsm {
+        pattern: "/dev/null",
+        powershell: Some("discard output with `$null`, not `/dev/null`"),
+        cmd: Some("discard output with `NUL`, not `/dev/null`"),
+    },
+    BashIsm {
+        pattern: "rm -rf",
+        powershell: Some("delete with `Remove-Item -Recurse -Force`, not `rm -rf`"),
+        cmd: Some("delete with `rmdir /s /q` or `del /q`, not `rm -rf`"),
+    },
+    BashIsm {
+        pattern: "which ",
+        powershell: Some("locate programs with `Get-Command`, not `which`"),
+        cmd: Some("locate programs with `where`, not `which`"),
+    },
+    BashIsm {
+        pattern: "<<",
+        powershell: Some("there are no heredocs; use a here-string `@' ... '@`"),
+        cmd: Some("there are no heredocs; write the file with write_file instead"),
+    },
+];
+
+/// Shell that `kind` runs commands with on this platform.
+fn resolve(kind: ShellKind) -> ShellKind {
+    match kind {
+        ShellKind::Auto if cfg!(windows) => ShellKind::Powershell,
+        ShellKind::Auto => ShellKind::Sh,
+        kind => kind,
+    }
+}
+
+/// Program and arguments that run `command` with a resolved `shell`.
+fn invocation(shell: ShellKind, command: &str) -> (&'static str, Vec<&str>) {
+    match shell {
+        ShellKind::Powershell => (
+            POWERSHELL,
+            vec!["-NoProfile", "-NonInteractive", "-Command", command],
+        ),
+        ShellKind::Cmd => ("cmd", vec!["/C", command]),
+        ShellKind::Auto | ShellKind::Sh => ("sh", vec!["-c", command]),
+    }
+}
+
+/// Bash constructs in `command` that `shell` does not understand, with the
+/// native spelling, so the model can rewrite the command after a failure.
+fn translation_warnings(shell: ShellKind, command: &str) -> Vec<&'static str> {
+    BASH_ISMS
+        .iter()
+        .filter(|ism| command.contains(ism.pattern))
+        .filter_map(|ism| match shell {
+            ShellKind::Powershell => ism.powershell,
+            ShellKind::Cmd => ism.cmd,
+            ShellKind::Auto | ShellKind::Sh => None,
+        })
+        .collect()
+}
+
+fn shell_name(shell: ShellKind) -> &'static str {
+    match shell {
+        ShellKind::Powershell => "PowerShell",
+        ShellKind::Cmd => "cmd",
+        ShellKind::Auto | ShellKind::Sh => "sh",
+    }
+}
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// `execution.max_timeout_secs` when not configured
+const DEFAULT_MAX_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// How long output may sit at a prompt before the command counts as waiting for input
+const PROMPT_STALL: Duration = Duration::from_secs(5);
+const STALL_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+/// Output checked for a prompt, enough for its last line
+const PROMPT_TAIL_BYTES: usize = 512;
+/// Output limit left for notes added after truncating
+const OUTPUT_NOTE_BYTES: usize = 1024;
+
+/// Runs a trivial command with `kind`, for the setup check. Returns the name
+/// of the shell that ran it, or why it could not.
+pub(crate) async fn probe_shell(kind: ShellKind) -> Result<&'static str, String> {
+    let shell = resolve(kind);
+    let (program, args) = invocation(shell, "echo devflow");
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+
+    match timeout(PROBE_TIMEOUT, cmd.output()).await {
+        Ok(Ok(output))
+            if output.status.success()
+                && String::from_utf8_lossy(&output.stdout).contains("devflow") =>
+        {
+            Ok(shell_name(shell))
+        }
+        Ok(Ok(output)) => Err(format!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Ok(Err(e)) => Err(format!("Could not run {}: {}", program, e)),
+        Err(_) => Err(format!(
+            "{} did not respond within {}s",
+            program,
+            PROBE_TIMEOUT.as_secs()
+        )),
+    }
+}
+
 #[derive(Clone)]
 pub struct ShellExecutor {
     working_dir: PathBuf,
     default_timeout: Duration,
+    /// Longest timeout a call may ask for
+    max_timeout: Duration,
+    /// Output is cut in the middle to fit
+    max_output: usize,
+    kind: ShellKind,
+    processes: ProcessRegistry,
+    /// Added to every `git commit` the commands make (None = attribution off)
+    commit_trailer: Option<String>,
     #[cfg(windows)]
     wsl_path: Option<WslPath>,
 }
@@ -28,6 +180,11 @@ impl ShellExecutor {
         Self {
             working_dir,
             default_timeout,
+            max_timeout: DEFAULT_MAX_TIMEOUT,
+            max_output: MAX_OUTPUT_SIZE,
+            kind: ShellKind::Auto,
+            processes: ProcessRegistry::default(),
+            commit_trailer: None,
             wsl_path,
         }
     }
@@ -37,81 +194,463 @@ impl ShellExecutor {
         Self {
             working_dir,
             default_timeout,
+            max_timeout: DEFAULT_MAX_TIMEOUT,
+            max_output: MAX_OUTPUT_SIZE,
+            kind: ShellKind::Auto,
+            processes: ProcessRegistry::default(),
+            commit_trailer: None,
         }
     }
 
-    async fn run_command(&self, command: &str) -> std::io::Result<std::process::Output> {
-        #[cfg(target_os = "windows")]
-        {
-            if let Some(ref wsl) = self.wsl_path {
-                Command::new("wsl.exe")
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .args(["-d", &wsl.distro, "sh", "-c"])
-                    .arg(format!("cd '{}' && {}", wsl.linux_path, command))
-                    .output()
-                    .await
-            } else {
-                Command::new("cmd")
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .args(["/C", command])
-                    .current_dir(&self.working_dir)
-                    .output()
-                    .await
+    /// Runs commands with `kind` instead of the platform default. Ignored for
+    /// WSL projects, whose commands always run in the distro's sh.
+    pub fn with_kind(mut self, kind: ShellKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_max_timeout(mut self, max_timeout: Duration) -> Self {
+        self.max_timeout = max_timeout;
+        self
+    }
+
+    pub fn with_max_output(mut self, max_bytes: usize) -> Self {
+        self.max_output = max_bytes;
+        self
+    }
+
+    /// Tracks the process groups of commands in `processes`, usually the session's.
+    pub fn with_processes(mut self, processes: ProcessRegistry) -> Self {
+        self.processes = processes;
+        self
+    }
+
+    pub fn with_commit_trailer(mut self, trailer: Option<String>) -> Self {
+        self.commit_trailer = trailer;
+        self
+    }
+
+    /// The shell commands actually run in.
+    fn shell(&self) -> ShellKind {
+        #[cfg(windows)]
+        if self.wsl_path.is_some() {
+            return ShellKind::Sh;
+        }
+        resolve(self.kind)
+    }
+
+    /// Runs the command for at most `limit`, killing its process group when
+    /// it runs over or sits at a prompt; the output read until then is kept.
+    async fn run_command(
+        &self,
+        command: &str,
+        cwd: &Path,
+        limit: Duration,
+    ) -> std::io::Result<CommandOutput> {
+        let mut cmd = self.command(command, cwd);
+        // Its own group, so the guard can kill whatever the command started;
+        // a cancelled run drops the future, and with it the guard
+        process::new_group(&mut cmd);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = cmd.spawn()?;
+        let guard = child
+            .id()
+            .map(|pid| process::GroupGuard::new(pid, &self.processes));
+        let mut stdout_pipe = child.stdout.take();
+        let mut stderr_pipe = child.stderr.take();
+        let captured = Mutex::new(Captured::default());
+
+        let run = async {
+            let (status, _, _) = tokio::try_join!(
+                child.wait(),
+                read_pipe(stdout_pipe.as_mut(), &captured, false),
+                read_pipe(stderr_pipe.as_mut(), &captured, true),
+            )?;
+            Ok::<_, std::io::Error>(Ended::Exited(status))
+        };
+        let watched = async {
+            tokio::select! {
+                ended = run => ended,
+                _ = stalled_at_prompt(&captured) => Ok(Ended::WaitingForInput),
             }
+        };
+        let ended = match timeout(limit, watched).await {
+            Ok(ended) => ended?,
+            Err(_) => Ended::TimedOut,
+        };
+
+        // Unless the command exited, dropping the guard kills the group
+        if let (Ended::Exited(_), Some(guard)) = (ended, guard) {
+            guard.finish();
         }
+        let captured = captured
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        Ok(CommandOutput {
+            ended,
+            stdout: captured.stdout,
+            stderr: captured.stderr,
+        })
+    }
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            Command::new("sh")
-                .arg("-c")
-                .arg(command)
-                .current_dir(&self.working_dir)
-                .output()
-                .await
+    fn command(&self, command: &str, cwd: &Path) -> Command {
+        #[cfg(windows)]
+        if let Some(ref wsl) = self.wsl_path {
+            let linux_dir = match cwd.strip_prefix(&self.working_dir) {
+                Ok(relative) => wsl.join(relative),
+                Err(_) => cwd.to_string_lossy().into_owned(),
+            };
+            let mut cmd = Command::new("wsl.exe");
+            cmd.args(["-d", &wsl.distro, "sh", "-c"])
+                .arg(format!("cd '{}' && {}", linux_dir, command));
+            return cmd;
         }
+
+        let (program, args) = invocation(self.shell(), command);
+        let mut cmd = Command::new(program);
+        cmd.args(args).current_dir(cwd);
+        cmd
     }
 
-    pub async fn execute(&self, input: serde_json::Value) -> Result<String, AgentError> {
+    /// Runs the command in `cwd`, an already resolved directory, or the working directory.
+    pub async fn execute(
+        &self,
+        input: serde_json::Value,
+        cwd: Option<PathBuf>,
+    ) -> Result<String, AgentError> {
         let input: BashInput = serde_json::from_value(input)
             .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
 
-        let cmd_timeout = input
+        let requested = input
             .timeout
             .map(Duration::from_secs)
             .unwrap_or(self.default_timeout);
+        let cmd_timeout = requested.min(self.max_timeout);
+
+        if let Some(refusal) = interactive::check(&input.command) {
+            return Err(AgentError::ToolExecutionError(refusal));
+        }
+
+        let command = match &self.commit_trailer {
+            Some(trailer) => trailer::add_commit_trailer(&input.command, trailer, self.shell()),
+            None => input.command.clone(),
+        };
 
-        let result = timeout(cmd_timeout, self.run_command(&input.command)).await;
-
-        match result {
-            Ok(Ok(output)) => {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let stderr = String::from_utf8_lossy(&output.stderr);
-
-                let combined = if stderr.is_empty() {
-                    stdout.to_string()
-                } else if stdout.is_empty() {
-                    stderr.to_string()
-                } else {
-                    format!("{}\n{}", stdout, stderr)
-                };
-
-                let combined = ExecutionContext::truncate_output(combined);
-
-                if output.status.success() {
-                    Ok(combined)
-                } else {
-                    Err(AgentError::ToolExecutionError(format!(
-                        "Command failed with exit code {}: {}",
-                        output.status.code().unwrap_or(-1),
-                        combined
-                    )))
+        let cwd = cwd.unwrap_or_else(|| self.working_dir.clone());
+        let output = self
+            .run_command(&command, &cwd, cmd_timeout)
+            .await
+            .map_err(|e| {
+                AgentError::ToolExecutionError(format!("Failed to execute command: {}", e))
+            })?;
+
+        // Room for the notes appended below
+        let max_bytes = self.max_output - OUTPUT_NOTE_BYTES.min(self.max_output / 2);
+        let mut combined = output::clean(&output.combined(), max_bytes);
+
+        let status = match output.ended {
+            Ended::Exited(status) => status,
+            Ended::TimedOut => {
+                if combined.is_empty() {
+                    combined.push_str("(none)");
+                }
+                if requested > cmd_timeout {
+                    combined.push_str(&format!(
+                        "\n\nNote: the requested {}s is over the {}s limit (execution.max_timeout_secs)",
+                        requested.as_secs(),
+                        cmd_timeout.as_secs()
+                    ));
                 }
+                return Err(AgentError::CommandTimeout {
+                    secs: cmd_timeout.as_secs(),
+                    output: combined,
+                });
             }
-            Ok(Err(e)) => Err(AgentError::ToolExecutionError(format!(
-                "Failed to execute command: {}",
-                e
-            ))),
-            Err(_) => Err(AgentError::ToolTimeout),
+            Ended::WaitingForInput => {
+                return Err(AgentError::ToolExecutionError(format!(
+                    "Command stopped at a prompt and was killed: the bash tool has no terminal \
+                     and cannot answer. Pass the answer as a flag (e.g. `--yes`) or pipe it in \
+                     (e.g. `yes | ...`). Output so far:\n{}",
+                    combined
+                )));
+            }
+        };
+
+        let shell = self.shell();
+        let warnings = translation_warnings(shell, &input.command);
+        if !warnings.is_empty() {
+            combined.push_str(&format!(
+                "\n\nNote: this command ran in {}, not bash:\n- {}",
+                shell_name(shell),
+                warnings.join("\n- ")
+            ));
         }
+
+        if status.success() {
+            Ok(combined)
+        } else {
+            if let Some(hint) = interactive::terminal_hint(&combined) {
+                combined.push_str(&format!("\n\nNote: {}", hint));
+            }
+            Err(AgentError::ToolExecutionError(format!(
+                "Command failed with exit code {}: {}",
+                status.code().unwrap_or(-1),
+                combined
+            )))
+        }
+    }
+}
+
+/// How a command ended.
+#[derive(Clone, Copy)]
+enum Ended {
+    Exited(ExitStatus),
+    TimedOut,
+    /// Sat at a prompt for `PROMPT_STALL`
+    WaitingForInput,
+}
+
+/// What a command wrote, and how it ended.
+struct CommandOutput {
+    ended: Ended,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+impl CommandOutput {
+    fn combined(&self) -> String {
+        let stdout = String::from_utf8_lossy(&self.stdout);
+        let stderr = String::from_utf8_lossy(&self.stderr);
+        if stderr.is_empty() {
+            stdout.into_owned()
+        } else if stdout.is_empty() {
+            stderr.into_owned()
+        } else {
+            format!("{}\n{}", stdout, stderr)
+        }
+    }
+}
+
+/// Output read so far, shared by the pipe readers and the stall check.
+#[derive(Default)]
+struct Captured {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    /// When output last arrived, if it ended in what looks like a prompt
+    at_prompt: Option<Instant>,
+}
+
+impl Captured {
+    fn push(&mut self, chunk: &[u8], from_stderr: bool) {
+        let buf = if from_stderr {
+            &mut self.stderr
+        } else {
+            &mut self.stdout
+        };
+        buf.extend_from_slice(chunk);
+        let tail = &buf[buf.len().saturating_sub(PROMPT_TAIL_BYTES)..];
+        let tail = output::strip_ansi(&String::from_utf8_lossy(tail));
+        self.at_prompt = interactive::looks_like_prompt(&tail).then(Instant::now);
+    }
+}
+
+/// Reads `pipe` to the end into `captured`, which keeps what was read if the read is dropped.
+async fn read_pipe(
+    pipe: Option<&mut (impl AsyncRead + Unpin)>,
+    captured: &Mutex<Captured>,
+    from_stderr: bool,
+) -> std::io::Result<()> {
+    let Some(pipe) = pipe else {
+        return Ok(());
+    };
+    let mut chunk = vec![0; 8192];
+    loop {
+        let read = pipe.read(&mut chunk).await?;
+        if read == 0 {
+            return Ok(());
+        }
+        captured
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(&chunk[..read], from_stderr);
+    }
+}
+
+/// Resolves once the output has ended in a prompt, with nothing new, for `PROMPT_STALL`.
+async fn stalled_at_prompt(captured: &Mutex<Captured>) {
+    loop {
+        tokio::time::sleep(STALL_CHECK_INTERVAL).await;
+        let at_prompt = captured
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .at_prompt;
+        if at_prompt.is_some_and(|since| since.elapsed() >= PROMPT_STALL) {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_resolves_per_platform() {
+        let expected = if cfg!(windows) {
+            ShellKind::Powershell
+        } else {
+            ShellKind::Sh
+        };
+        assert_eq!(resolve(ShellKind::Auto), expected);
+        assert_eq!(resolve(ShellKind::Cmd), ShellKind::Cmd);
+    }
+
+    #[test]
+    fn test_invocation() {
+        assert_eq!(
+            invocation(ShellKind::Cmd, "dir"),
+            ("cmd", vec!["/C", "dir"])
+        );
+        assert_eq!(invocation(ShellKind::Sh, "ls"), ("sh", vec!["-c", "ls"]));
+        let (_, args) = invocation(ShellKind::Powershell, "Get-ChildItem");
+        assert_eq!(args.last(), Some(&"Get-ChildItem"));
+    }
+
+    #[test]
+    fn test_translation_warnings() {
+        let command = "export FOO=1 && cargo build 2>/dev/null";
+        assert!(translation_warnings(ShellKind::Sh, command).is_empty());
+
+        let powershell = translation_warnings(ShellKind::Powershell, command);
+        assert_eq!(powershell.len(), 3);
+        assert!(powershell.iter().any(|w| w.contains("$env:NAME")));
+
+        // cmd understands `&&`
+        let cmd = translation_warnings(ShellKind::Cmd, command);
+        assert_eq!(cmd.len(), 2);
+        assert!(cmd.iter().any(|w| w.contains("NUL")));
+    }
+
+    /// Whether the process whose pid is in `pid_file` is gone within a second;
+    /// the container may never reap it, so zombies count.
+    #[cfg(target_os = "linux")]
+    async fn exited(dir: &Path, pid_file: &str) -> bool {
+        let pid = std::fs::read_to_string(dir.join(pid_file)).unwrap();
+        for _ in 0..20 {
+            match std::fs::read_to_string(format!("/proc/{}/stat", pid.trim())) {
+                Ok(stat) if !stat.contains(") Z ") => {
+                    tokio::time::sleep(Duration::from_millis(50)).await
+                }
+                _ => return true,
+            }
+        }
+        false
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_timeout_kills_process_group() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let processes = ProcessRegistry::default();
+        let shell = ShellExecutor::new(temp.path().to_path_buf(), Duration::from_secs(30))
+            .with_processes(processes.clone());
+
+        let result = shell
+            .execute(
+                serde_json::json!({ "command": "sleep 30 & echo $! > bg; wait", "timeout": 1 }),
+                None,
+            )
+            .await;
+        assert!(matches!(
+            result,
+            Err(AgentError::CommandTimeout { secs: 1, .. })
+        ));
+        assert!(exited(temp.path(), "bg").await);
+        assert!(processes.tracked().is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_background_jobs_stay_tracked() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let processes = ProcessRegistry::default();
+        let shell = ShellExecutor::new(temp.path().to_path_buf(), Duration::from_secs(30))
+            .with_processes(processes.clone());
+
+        shell
+            .execute(serde_json::json!({ "command": "echo done" }), None)
+            .await
+            .unwrap();
+        assert!(processes.tracked().is_empty());
+
+        shell
+            .execute(
+                serde_json::json!({ "command": "sleep 30 > /dev/null 2>&1 & echo $! > bg" }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(processes.tracked().len(), 1);
+        assert!(!exited(temp.path(), "bg").await);
+
+        processes.kill_all();
+        assert!(processes.tracked().is_empty());
+        assert!(exited(temp.path(), "bg").await);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timeout_returns_partial_output() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let shell = ShellExecutor::new(temp.path().to_path_buf(), Duration::from_secs(30))
+            .with_max_timeout(Duration::from_secs(1));
+
+        let result = shell
+            .execute(
+                serde_json::json!({ "command": "echo started; sleep 30", "timeout": 60 }),
+                None,
+            )
+            .await;
+        let Err(AgentError::CommandTimeout { secs, output }) = result else {
+            panic!("expected a timeout, got {:?}", result);
+        };
+        assert_eq!(secs, 1);
+        assert!(output.starts_with("started"));
+        assert!(output.contains("the requested 60s is over the 1s limit"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_interactive_commands_fail_fast() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let shell = ShellExecutor::new(temp.path().to_path_buf(), Duration::from_secs(30));
+
+        let refused = shell
+            .execute(
+                serde_json::json!({ "command": "git rebase -i HEAD~2" }),
+                None,
+            )
+            .await;
+        assert!(
+            matches!(refused, Err(AgentError::ToolExecutionError(msg)) if msg.contains("no terminal"))
+        );
+
+        let started = Instant::now();
+        let stalled = shell
+            .execute(
+                serde_json::json!({ "command": "printf 'Overwrite existing files? [y/N] '; sleep 30" }),
+                None,
+            )
+            .await;
+        let Err(AgentError::ToolExecutionError(msg)) = stalled else {
+            panic!("expected a prompt stall, got {:?}", stalled);
+        };
+        assert!(msg.contains("stopped at a prompt"));
+        assert!(msg.contains("Overwrite existing files?"));
+        assert!(started.elapsed() < Duration::from_secs(10));
     }
 }
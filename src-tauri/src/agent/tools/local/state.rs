@@ -1,34 +1,120 @@
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
 use std::sync::Arc;
-use tokio::sync::{oneshot, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, watch, RwLock};
+use ts_rs::TS;
 
+use super::plan::{plan_steps, ArchivedPlan, PlanStatus};
+use super::process::ProcessRegistry;
+use crate::agent::run_journal::RunJournal;
 use crate::agent::tools::types::TodoItem;
-use crate::agent::types::CompactedFact;
+use crate::agent::types::{CompactedFact, SteeringMessage};
+use crate::config::AutoApproveConfig;
 
-/// Result of plan approval
+/// Result of a plan or tool call approval
 #[derive(Debug, Clone)]
 pub enum PlanApproval {
     Approved,
     Rejected(Option<String>),
 }
 
-/// Inner state for plan approval channel
-struct PlanApprovalState {
-    plan: Option<String>,
+/// A submitted plan and its approval channel. The sender is taken by the
+/// answer, the receiver by the tool loop waiting for it; the plan leaves the
+/// queue once that wait ends.
+struct PendingPlan {
+    id: u32,
+    plan: String,
     sender: Option<oneshot::Sender<PlanApproval>>,
     receiver: Option<oneshot::Receiver<PlanApproval>>,
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CompactedContext {
     pub summary: Option<String>,
     pub facts: Vec<CompactedFact>,
 }
 
+/// A file's content from before the agent first changed it in the current turn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSnapshot {
+    /// Project-relative path as given to the tool
+    pub path: String,
+    pub absolute: PathBuf,
+    /// None when the file did not exist yet
+    pub original: Option<String>,
+}
+
+/// How long a fetched page is reused by web_fetch
+const WEB_CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+/// Unfinished todos unchanged for this many turns are stale
+pub const STALE_TODO_TURNS: u32 = 3;
+
+/// A todo item with when it last changed.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TrackedTodo {
+    pub item: TodoItem,
+    /// RFC 3339 UTC timestamp of the last change to the item
+    pub updated_at: String,
+    /// Turn of the session the item last changed in; each user message starts a turn
+    pub updated_turn: u32,
+}
+
+/// The session's todos and how stale they are, for the task panel and the
+/// stale todo list reminder.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TodoList {
+    pub items: Vec<TrackedTodo>,
+    /// Current turn of the session
+    pub turn: u32,
+    /// Ids of unfinished items unchanged for `STALE_TODO_TURNS` turns or more
+    pub stale: Vec<String>,
+}
+
 #[derive(Clone)]
 pub struct SessionState {
-    todos: Arc<RwLock<Vec<TodoItem>>>,
-    plan_approval: Arc<RwLock<PlanApprovalState>>,
+    id: Arc<str>,
+    todos: Arc<RwLock<Vec<TrackedTodo>>>,
+    /// Turns started in this session, see `begin_turn`
+    turn: Arc<AtomicU32>,
+    /// Submitted plans, oldest first
+    plans: Arc<RwLock<VecDeque<PendingPlan>>>,
+    /// Id of the last plan submitted in this session
+    last_plan_id: Arc<AtomicU32>,
+    /// Plans decided on in this session, oldest first
+    plan_history: Arc<RwLock<Vec<ArchivedPlan>>>,
     compacted: Arc<RwLock<CompactedContext>>,
+    /// Compact before the next request even if the history is below the threshold
+    compaction_requested: Arc<AtomicBool>,
+    /// Compaction waits for `agent_respond_compaction` before it is applied
+    confirm_compaction: Arc<AtomicBool>,
+    compaction_approval: Arc<RwLock<Option<oneshot::Sender<bool>>>>,
+    steering: Arc<RwLock<Vec<SteeringMessage>>>,
+    paused: Arc<watch::Sender<bool>>,
+    /// Converted page content by URL, with fetch time
+    web_cache: Arc<RwLock<HashMap<String, (Instant, String)>>>,
+    /// Originals of files edited since the turn started, in edit order
+    turn_snapshots: Arc<RwLock<Vec<FileSnapshot>>>,
+    /// `{VAR:name}` values sent with the current message
+    prompt_variables: Arc<RwLock<HashMap<String, String>>>,
+    /// Type of the sub-agent this session runs, None for the user's session
+    agent_type: Arc<RwLock<Option<String>>>,
+    /// How deeply this session's agent is nested: 0 for the user's session,
+    /// 1 for its sub-agents, and so on
+    agent_depth: Arc<AtomicU32>,
+    /// Set with `agent_set_auto_approve`; None follows the project config
+    auto_approve: Arc<RwLock<Option<AutoApproveConfig>>>,
+    /// Tool calls waiting for the user, by tool use id
+    tool_approvals: Arc<RwLock<HashMap<String, oneshot::Sender<PlanApproval>>>>,
+    /// Journal of the interactive run in progress, for crash recovery
+    run_journal: Arc<RwLock<Option<RunJournal>>>,
+    /// Process groups of bash commands, including background jobs they left running
+    processes: ProcessRegistry,
 }
 
 impl Default for SessionState {
@@ -40,93 +126,306 @@ impl Default for SessionState {
 impl SessionState {
     pub fn new() -> Self {
         Self {
+            id: Arc::from(uuid::Uuid::new_v4().to_string()),
             todos: Arc::new(RwLock::new(Vec::new())),
-            plan_approval: Arc::new(RwLock::new(PlanApprovalState {
-                plan: None,
-                sender: None,
-                receiver: None,
-            })),
+            turn: Arc::new(AtomicU32::new(0)),
+            plans: Arc::new(RwLock::new(VecDeque::new())),
+            last_plan_id: Arc::new(AtomicU32::new(0)),
+            plan_history: Arc::new(RwLock::new(Vec::new())),
             compacted: Arc::new(RwLock::new(CompactedContext::default())),
+            compaction_requested: Arc::new(AtomicBool::new(false)),
+            confirm_compaction: Arc::new(AtomicBool::new(false)),
+            compaction_approval: Arc::new(RwLock::new(None)),
+            steering: Arc::new(RwLock::new(Vec::new())),
+            paused: Arc::new(watch::channel(false).0),
+            web_cache: Arc::new(RwLock::new(HashMap::new())),
+            turn_snapshots: Arc::new(RwLock::new(Vec::new())),
+            prompt_variables: Arc::new(RwLock::new(HashMap::new())),
+            agent_type: Arc::new(RwLock::new(None)),
+            agent_depth: Arc::new(AtomicU32::new(0)),
+            auto_approve: Arc::new(RwLock::new(None)),
+            tool_approvals: Arc::new(RwLock::new(HashMap::new())),
+            run_journal: Arc::new(RwLock::new(None)),
+            processes: ProcessRegistry::default(),
+        }
+    }
+
+    /// Unique id of this session, stable across clones.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub async fn get_prompt_variables(&self) -> HashMap<String, String> {
+        self.prompt_variables.read().await.clone()
+    }
+
+    pub async fn set_prompt_variables(&self, variables: HashMap<String, String>) {
+        *self.prompt_variables.write().await = variables;
+    }
+
+    pub async fn get_agent_type(&self) -> Option<String> {
+        self.agent_type.read().await.clone()
+    }
+
+    pub async fn set_agent_type(&self, agent_type: &str) {
+        *self.agent_type.write().await = Some(agent_type.to_string());
+    }
+
+    pub fn agent_depth(&self) -> u32 {
+        self.agent_depth.load(Ordering::SeqCst)
+    }
+
+    pub fn set_agent_depth(&self, depth: u32) {
+        self.agent_depth.store(depth, Ordering::SeqCst);
+    }
+
+    pub async fn get_auto_approve(&self) -> Option<AutoApproveConfig> {
+        self.auto_approve.read().await.clone()
+    }
+
+    /// Takes effect from the next tool call, including in a running turn.
+    pub async fn set_auto_approve(&self, settings: Option<AutoApproveConfig>) {
+        *self.auto_approve.write().await = settings;
+    }
+
+    /// Registers a tool call as waiting; the receiver gets the user's answer.
+    pub async fn request_tool_approval(
+        &self,
+        tool_use_id: &str,
+    ) -> oneshot::Receiver<PlanApproval> {
+        let (tx, rx) = oneshot::channel();
+        self.tool_approvals
+            .write()
+            .await
+            .insert(tool_use_id.to_string(), tx);
+        rx
+    }
+
+    /// Answers a waiting tool call (called by Tauri command). False if none is waiting.
+    pub async fn respond_tool_approval(&self, tool_use_id: &str, approval: PlanApproval) -> bool {
+        match self.tool_approvals.write().await.remove(tool_use_id) {
+            Some(sender) => sender.send(approval).is_ok(),
+            None => false,
         }
     }
 
+    /// Drops a request that is no longer waited on, e.g. after cancellation.
+    pub async fn cancel_tool_approval(&self, tool_use_id: &str) {
+        self.tool_approvals.write().await.remove(tool_use_id);
+    }
+
     pub async fn get_todos(&self) -> Vec<TodoItem> {
-        self.todos.read().await.clone()
+        self.todos
+            .read()
+            .await
+            .iter()
+            .map(|todo| todo.item.clone())
+            .collect()
     }
 
+    /// Replaces the todo list. Items the list already had unchanged keep
+    /// their update time and turn; new and changed ones get the current ones.
     pub async fn set_todos(&self, todos: Vec<TodoItem>) {
+        let turn = self.turn();
+        let now = Utc::now().to_rfc3339();
+        let mut current = self.todos.write().await;
+        let updated = todos
+            .into_iter()
+            .map(|item| match current.iter().find(|todo| todo.item == item) {
+                Some(unchanged) => unchanged.clone(),
+                None => TrackedTodo {
+                    item,
+                    updated_at: now.clone(),
+                    updated_turn: turn,
+                },
+            })
+            .collect();
+        *current = updated;
+    }
+
+    pub async fn tracked_todos(&self) -> Vec<TrackedTodo> {
+        self.todos.read().await.clone()
+    }
+
+    /// Puts back todos persisted by an earlier session, moving the turn
+    /// count up to theirs so their staleness carries over.
+    pub async fn restore_todos(&self, todos: Vec<TrackedTodo>) {
+        let last_turn = todos.iter().map(|todo| todo.updated_turn).max();
+        if let Some(last_turn) = last_turn {
+            self.turn.fetch_max(last_turn, Ordering::SeqCst);
+        }
         *self.todos.write().await = todos;
     }
 
-    /// Set a plan and create approval channel
-    pub async fn set_plan(&self, plan: String) {
+    pub async fn todo_list(&self) -> TodoList {
+        let turn = self.turn();
+        let items = self.tracked_todos().await;
+        let stale = items
+            .iter()
+            .filter(|todo| {
+                todo.item.status != "completed"
+                    && turn.saturating_sub(todo.updated_turn) >= STALE_TODO_TURNS
+            })
+            .map(|todo| todo.item.id.clone())
+            .collect();
+        TodoList { items, turn, stale }
+    }
+
+    /// Turns started in this session, 0 before the first user message.
+    pub fn turn(&self) -> u32 {
+        self.turn.load(Ordering::SeqCst)
+    }
+
+    /// Queues a plan for the user to answer and returns its id. Plans
+    /// submitted while others are waiting are answered after them.
+    pub async fn queue_plan(&self, plan: String) -> u32 {
+        let id = self.last_plan_id.fetch_add(1, Ordering::SeqCst) + 1;
         let (tx, rx) = oneshot::channel();
-        let mut state = self.plan_approval.write().await;
-        state.plan = Some(plan);
-        state.sender = Some(tx);
-        state.receiver = Some(rx);
+        self.plans.write().await.push_back(PendingPlan {
+            id,
+            plan,
+            sender: Some(tx),
+            receiver: Some(rx),
+        });
+        id
     }
 
-    pub async fn get_plan(&self) -> Option<String> {
-        self.plan_approval.read().await.plan.clone()
+    /// The plan submitted last, with its id.
+    pub async fn latest_plan(&self) -> Option<(u32, String)> {
+        let plans = self.plans.read().await;
+        plans
+            .back()
+            .map(|pending| (pending.id, pending.plan.clone()))
     }
 
-    pub async fn clear_plan(&self) {
-        let mut state = self.plan_approval.write().await;
-        state.plan = None;
-        state.sender = None;
-        state.receiver = None;
+    /// Plans still waiting for an answer.
+    pub async fn pending_plan_count(&self) -> usize {
+        let plans = self.plans.read().await;
+        plans
+            .iter()
+            .filter(|pending| pending.sender.is_some())
+            .count()
     }
 
-    /// Wait for plan approval from user
-    /// Returns None if no plan is pending, otherwise waits for approval/rejection
-    pub async fn wait_for_plan_approval(&self) -> Option<PlanApproval> {
-        // Take the receiver out of the state
-        let receiver = {
-            let mut state = self.plan_approval.write().await;
-            state.receiver.take()
+    /// Withdraws a plan, waking whoever waits for it without an answer.
+    /// False if it was already answered or is not queued.
+    pub async fn remove_plan(&self, id: u32) -> bool {
+        let mut plans = self.plans.write().await;
+        match plans.iter().position(|pending| pending.id == id) {
+            Some(index) => plans
+                .remove(index)
+                .is_some_and(|pending| pending.sender.is_some()),
+            None => false,
+        }
+    }
+
+    /// Withdraws every plan.
+    pub async fn clear_plans(&self) {
+        self.plans.write().await.clear();
+    }
+
+    /// Waits for the answer to plan `id`, for at most `timeout` (None waits
+    /// until it comes). The plan leaves the queue either way. None if the
+    /// plan is not queued, is already waited on, was withdrawn or the time
+    /// ran out.
+    pub async fn wait_for_plan_approval(
+        &self,
+        id: u32,
+        timeout: Option<Duration>,
+    ) -> Option<PlanApproval> {
+        let mut receiver = {
+            let mut plans = self.plans.write().await;
+            let pending = plans.iter_mut().find(|pending| pending.id == id)?;
+            pending.receiver.take()?
         };
 
-        match receiver {
-            Some(rx) => {
-                // Wait for approval signal
-                match rx.await {
-                    Ok(approval) => Some(approval),
-                    Err(_) => Some(PlanApproval::Rejected(Some("Channel closed".to_string()))),
+        let answer = match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, &mut receiver)
+                .await
+                .ok()
+                .and_then(Result::ok),
+            None => (&mut receiver).await.ok(),
+        };
+        self.remove_plan(id).await;
+        // An answer can arrive between the timeout and the removal
+        answer.or_else(|| receiver.try_recv().ok())
+    }
+
+    /// Approve a waiting plan, the oldest unless `id` is given (called by Tauri command)
+    pub async fn approve_plan(&self, id: Option<u32>) -> bool {
+        self.answer_plan(id, PlanApproval::Approved).await
+    }
+
+    /// Reject a waiting plan, the oldest unless `id` is given (called by Tauri command)
+    pub async fn reject_plan(&self, id: Option<u32>, reason: Option<String>) -> bool {
+        self.answer_plan(id, PlanApproval::Rejected(reason)).await
+    }
+
+    async fn answer_plan(&self, id: Option<u32>, approval: PlanApproval) -> bool {
+        let mut plans = self.plans.write().await;
+        let sender = plans
+            .iter_mut()
+            .filter(|pending| id.is_none_or(|id| pending.id == id))
+            .find_map(|pending| pending.sender.take());
+        match sender {
+            Some(sender) => sender.send(approval).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Keeps a plan the user decided on. The steps of an approved plan are
+    /// added to the todo list as pending items `plan-<number>-<step>`,
+    /// skipping steps the list already has.
+    pub async fn archive_plan(
+        &self,
+        plan: String,
+        status: PlanStatus,
+        rejection_reason: Option<String>,
+    ) -> ArchivedPlan {
+        let number = self.plan_history.read().await.len() as u32 + 1;
+        let mut todo_ids = Vec::new();
+        if status != PlanStatus::Rejected {
+            let mut todos = self.get_todos().await;
+            for (i, step) in plan_steps(&plan).into_iter().enumerate() {
+                if todos.iter().any(|todo| todo.content == step) {
+                    continue;
                 }
+                let id = format!("plan-{}-{}", number, i + 1);
+                todos.push(TodoItem {
+                    id: id.clone(),
+                    content: step,
+                    status: "pending".to_string(),
+                    priority: "medium".to_string(),
+                });
+                todo_ids.push(id);
             }
-            None => None,
+            self.set_todos(todos).await;
         }
+
+        let archived = ArchivedPlan {
+            number,
+            plan,
+            status,
+            rejection_reason,
+            archived_at: Utc::now().to_rfc3339(),
+            todo_ids,
+        };
+        self.plan_history.write().await.push(archived.clone());
+        archived
     }
 
-    /// Approve the pending plan (called by Tauri command)
-    pub async fn approve_plan(&self) -> bool {
-        let mut state = self.plan_approval.write().await;
-        if let Some(sender) = state.sender.take() {
-            state.plan = None;
-            let _ = sender.send(PlanApproval::Approved);
-            true
-        } else {
-            false
-        }
+    pub async fn plan_history(&self) -> Vec<ArchivedPlan> {
+        self.plan_history.read().await.clone()
     }
 
-    /// Reject the pending plan (called by Tauri command)
-    pub async fn reject_plan(&self, reason: Option<String>) -> bool {
-        let mut state = self.plan_approval.write().await;
-        if let Some(sender) = state.sender.take() {
-            state.plan = None;
-            let _ = sender.send(PlanApproval::Rejected(reason));
-            true
-        } else {
-            false
-        }
+    /// Puts back plans persisted by an earlier session; later plans number on from them.
+    pub async fn restore_plan_history(&self, plans: Vec<ArchivedPlan>) {
+        *self.plan_history.write().await = plans;
     }
 
     /// Check if a plan is pending approval
     pub async fn has_pending_plan(&self) -> bool {
-        let state = self.plan_approval.read().await;
-        state.plan.is_some() && state.sender.is_some()
+        self.pending_plan_count().await > 0
     }
 
     pub async fn get_compacted(&self) -> CompactedContext {
@@ -137,6 +436,45 @@ impl SessionState {
         *self.compacted.write().await = context;
     }
 
+    /// Compact the history when the next message is sent, whatever its size.
+    pub fn request_compaction(&self) {
+        self.compaction_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether compaction was requested, clearing the request.
+    pub fn take_compaction_request(&self) -> bool {
+        self.compaction_requested.swap(false, Ordering::SeqCst)
+    }
+
+    /// Whether compaction waits for the user to accept the proposed context.
+    pub fn confirms_compaction(&self) -> bool {
+        self.confirm_compaction.load(Ordering::SeqCst)
+    }
+
+    pub fn set_confirm_compaction(&self, confirm: bool) {
+        self.confirm_compaction.store(confirm, Ordering::SeqCst);
+    }
+
+    /// Registers a compaction as waiting; the receiver gets whether it was accepted.
+    pub async fn request_compaction_approval(&self) -> oneshot::Receiver<bool> {
+        let (tx, rx) = oneshot::channel();
+        *self.compaction_approval.write().await = Some(tx);
+        rx
+    }
+
+    /// Answers a waiting compaction (called by Tauri command). False if none is waiting.
+    pub async fn respond_compaction_approval(&self, approved: bool) -> bool {
+        match self.compaction_approval.write().await.take() {
+            Some(sender) => sender.send(approved).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Drops a compaction that is no longer waited on, e.g. after cancellation.
+    pub async fn cancel_compaction_approval(&self) {
+        self.compaction_approval.write().await.take();
+    }
+
     pub async fn has_compacted(&self) -> bool {
         let ctx = self.compacted.read().await;
         ctx.summary.is_some() || !ctx.facts.is_empty()
@@ -146,6 +484,100 @@ impl SessionState {
         *self.compacted.write().await = CompactedContext::default();
     }
 
+    /// Queue a user message for the running tool loop (called by Tauri command)
+    pub async fn queue_message(&self, message: SteeringMessage) {
+        self.steering.write().await.push(message);
+    }
+
+    pub async fn has_queued_messages(&self) -> bool {
+        !self.steering.read().await.is_empty()
+    }
+
+    /// Take all queued messages, oldest first
+    pub async fn take_queued_messages(&self) -> Vec<SteeringMessage> {
+        std::mem::take(&mut *self.steering.write().await)
+    }
+
+    /// Hold the tool loop before its next request. Returns false if already paused.
+    pub fn pause(&self) -> bool {
+        !self.paused.send_replace(true)
+    }
+
+    /// Release a paused tool loop. Returns false if it was not paused.
+    pub fn resume(&self) -> bool {
+        self.paused.send_replace(false)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.borrow()
+    }
+
+    pub async fn wait_until_resumed(&self) {
+        let mut receiver = self.paused.subscribe();
+        let _ = receiver.wait_for(|paused| !paused).await;
+    }
+
+    /// Page content fetched for `url` within the cache TTL.
+    pub async fn get_cached_page(&self, url: &str) -> Option<String> {
+        self.web_cache
+            .read()
+            .await
+            .get(url)
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < WEB_CACHE_TTL)
+            .map(|(_, content)| content.clone())
+    }
+
+    /// Caches page content for `url`, dropping expired entries.
+    pub async fn cache_page(&self, url: String, content: String) {
+        let mut cache = self.web_cache.write().await;
+        cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < WEB_CACHE_TTL);
+        cache.insert(url, (Instant::now(), content));
+    }
+
+    /// Counts a new turn and forgets the previous turn's snapshots (called
+    /// when a user message starts a run)
+    pub async fn begin_turn(&self) {
+        self.turn.fetch_add(1, Ordering::SeqCst);
+        self.turn_snapshots.write().await.clear();
+    }
+
+    pub async fn has_snapshot(&self, absolute: &Path) -> bool {
+        self.turn_snapshots
+            .read()
+            .await
+            .iter()
+            .any(|snapshot| snapshot.absolute == absolute)
+    }
+
+    /// Keeps the first snapshot of each file; later edits in the turn don't replace it.
+    pub async fn record_snapshot(&self, snapshot: FileSnapshot) {
+        let mut snapshots = self.turn_snapshots.write().await;
+        if !snapshots.iter().any(|s| s.absolute == snapshot.absolute) {
+            snapshots.push(snapshot);
+        }
+    }
+
+    pub async fn turn_snapshots(&self) -> Vec<FileSnapshot> {
+        self.turn_snapshots.read().await.clone()
+    }
+
+    pub async fn run_journal(&self) -> Option<RunJournal> {
+        self.run_journal.read().await.clone()
+    }
+
+    pub async fn set_run_journal(&self, journal: Option<RunJournal>) {
+        *self.run_journal.write().await = journal;
+    }
+
+    pub fn processes(&self) -> ProcessRegistry {
+        self.processes.clone()
+    }
+
+    /// Kills everything the session's bash commands left running.
+    pub fn kill_processes(&self) {
+        self.processes.kill_all();
+    }
+
     #[cfg(test)]
     pub async fn todos_count(&self) -> usize {
         self.todos.read().await.len()
@@ -172,14 +604,137 @@ mod tests {
         assert_eq!(state.todos_count().await, 1);
     }
 
+    #[tokio::test]
+    async fn test_todo_staleness_across_turns() {
+        let state = SessionState::new();
+        let todo = |id: &str, status: &str| TodoItem {
+            id: id.to_string(),
+            content: format!("Task {}", id),
+            status: status.to_string(),
+            priority: "medium".to_string(),
+        };
+
+        state.begin_turn().await;
+        state
+            .set_todos(vec![todo("1", "pending"), todo("2", "pending")])
+            .await;
+        for _ in 0..STALE_TODO_TURNS {
+            state.begin_turn().await;
+        }
+        state
+            .set_todos(vec![todo("1", "pending"), todo("2", "in_progress")])
+            .await;
+
+        let list = state.todo_list().await;
+        assert_eq!(list.turn, 1 + STALE_TODO_TURNS);
+        assert_eq!(list.items[0].updated_turn, 1);
+        assert_eq!(list.items[1].updated_turn, 1 + STALE_TODO_TURNS);
+        assert_eq!(list.stale, vec!["1".to_string()]);
+
+        let restored = SessionState::new();
+        restored.restore_todos(list.items).await;
+        assert_eq!(restored.turn(), 1 + STALE_TODO_TURNS);
+        assert_eq!(restored.todo_list().await.stale, vec!["1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_approved_plan_becomes_todos() {
+        let state = SessionState::new();
+        state
+            .set_todos(vec![TodoItem {
+                id: "1".to_string(),
+                content: "Add tests".to_string(),
+                status: "pending".to_string(),
+                priority: "high".to_string(),
+            }])
+            .await;
+
+        let rejected = state
+            .archive_plan(
+                "1. Rewrite everything".to_string(),
+                PlanStatus::Rejected,
+                Some("Too broad".to_string()),
+            )
+            .await;
+        assert!(rejected.todo_ids.is_empty());
+
+        let approved = state
+            .archive_plan(
+                "### Steps\n1. Split the lexer\n2. Add tests".to_string(),
+                PlanStatus::Approved,
+                None,
+            )
+            .await;
+        assert_eq!(approved.number, 2);
+        assert_eq!(approved.todo_ids, vec!["plan-2-1"]);
+
+        let todos = state.get_todos().await;
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[1].content, "Split the lexer");
+        assert_eq!(todos[1].status, "pending");
+
+        let history = state.plan_history().await;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].status, PlanStatus::Rejected);
+        assert_eq!(history[0].rejection_reason.as_deref(), Some("Too broad"));
+    }
+
+    #[tokio::test]
+    async fn test_web_cache_by_url() {
+        let state = SessionState::new();
+        assert!(state.get_cached_page("https://example.com").await.is_none());
+
+        state
+            .cache_page("https://example.com".to_string(), "# Example".to_string())
+            .await;
+        assert_eq!(
+            state
+                .get_cached_page("https://example.com")
+                .await
+                .as_deref(),
+            Some("# Example")
+        );
+        assert!(state.get_cached_page("https://example.org").await.is_none());
+
+        // Clones share the cache
+        assert!(state
+            .clone()
+            .get_cached_page("https://example.com")
+            .await
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_turn_snapshots_keep_first_original() {
+        let state = SessionState::new();
+        let snapshot = |original: &str| FileSnapshot {
+            path: "src/main.rs".to_string(),
+            absolute: PathBuf::from("/project/src/main.rs"),
+            original: Some(original.to_string()),
+        };
+
+        state.record_snapshot(snapshot("first")).await;
+        state.record_snapshot(snapshot("second")).await;
+        assert!(state.has_snapshot(Path::new("/project/src/main.rs")).await);
+        let snapshots = state.clone().turn_snapshots().await;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].original.as_deref(), Some("first"));
+
+        state.begin_turn().await;
+        assert!(state.turn_snapshots().await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_plan_approval() {
         let state = SessionState::new();
 
         // Set a plan
-        state.set_plan("Test plan".to_string()).await;
+        let id = state.queue_plan("Test plan".to_string()).await;
         assert!(state.has_pending_plan().await);
-        assert_eq!(state.get_plan().await, Some("Test plan".to_string()));
+        assert_eq!(
+            state.latest_plan().await,
+            Some((id, "Test plan".to_string()))
+        );
 
         // Clone for the approval task
         let state2 = state.clone();
@@ -187,34 +742,182 @@ mod tests {
         // Spawn approval in background
         let handle = tokio::spawn(async move {
             tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
-            state2.approve_plan().await
+            state2.approve_plan(None).await
         });
 
         // Wait for approval
-        let result = state.wait_for_plan_approval().await;
+        let result = state.wait_for_plan_approval(id, None).await;
         assert!(matches!(result, Some(PlanApproval::Approved)));
 
         let approved = handle.await.unwrap();
         assert!(approved);
+        assert!(!state.has_pending_plan().await);
+        assert_eq!(state.latest_plan().await, None);
     }
 
     #[tokio::test]
     async fn test_plan_rejection() {
         let state = SessionState::new();
 
-        state.set_plan("Test plan".to_string()).await;
+        let id = state.queue_plan("Test plan".to_string()).await;
 
         let state2 = state.clone();
 
         let handle = tokio::spawn(async move {
             tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
-            state2.reject_plan(Some("Not good".to_string())).await
+            state2.reject_plan(None, Some("Not good".to_string())).await
         });
 
-        let result = state.wait_for_plan_approval().await;
+        let result = state.wait_for_plan_approval(id, None).await;
         assert!(matches!(result, Some(PlanApproval::Rejected(Some(ref r))) if r == "Not good"));
 
         let rejected = handle.await.unwrap();
         assert!(rejected);
     }
+
+    #[tokio::test]
+    async fn test_queued_plans_are_answered_in_order() {
+        let state = SessionState::new();
+        let first = state.queue_plan("First plan".to_string()).await;
+        let second = state.queue_plan("Second plan".to_string()).await;
+        assert_eq!(state.pending_plan_count().await, 2);
+        assert_eq!(
+            state.latest_plan().await,
+            Some((second, "Second plan".to_string()))
+        );
+
+        // Answered before anyone waits: the answer is kept for the waiter
+        assert!(state.reject_plan(Some(second), None).await);
+        assert!(state.approve_plan(None).await);
+        assert!(!state.approve_plan(None).await);
+        assert!(!state.has_pending_plan().await);
+
+        assert!(matches!(
+            state.wait_for_plan_approval(first, None).await,
+            Some(PlanApproval::Approved)
+        ));
+        assert!(matches!(
+            state.wait_for_plan_approval(second, None).await,
+            Some(PlanApproval::Rejected(None))
+        ));
+        assert_eq!(state.latest_plan().await, None);
+    }
+
+    #[tokio::test]
+    async fn test_plan_wait_times_out() {
+        let state = SessionState::new();
+        let id = state.queue_plan("Test plan".to_string()).await;
+
+        let result = state
+            .wait_for_plan_approval(id, Some(Duration::from_millis(10)))
+            .await;
+        assert!(result.is_none());
+        assert!(!state.has_pending_plan().await);
+        // Too late to answer
+        assert!(!state.approve_plan(Some(id)).await);
+    }
+
+    #[tokio::test]
+    async fn test_removing_plan_wakes_waiter() {
+        let state = SessionState::new();
+        let id = state.queue_plan("Test plan".to_string()).await;
+
+        let state2 = state.clone();
+        let waiter = tokio::spawn(async move { state2.wait_for_plan_approval(id, None).await });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert!(state.remove_plan(id).await);
+        let result = tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .expect("waiter woke up")
+            .unwrap();
+        assert!(result.is_none());
+        assert!(!state.reject_plan(Some(id), None).await);
+    }
+
+    #[tokio::test]
+    async fn test_tool_approvals_are_answered_by_id() {
+        let state = SessionState::new();
+        let first = state.request_tool_approval("tool_1").await;
+        let second = state.request_tool_approval("tool_2").await;
+
+        assert!(
+            state
+                .respond_tool_approval("tool_2", PlanApproval::Rejected(None))
+                .await
+        );
+        assert!(
+            !state
+                .respond_tool_approval("tool_3", PlanApproval::Approved)
+                .await
+        );
+        assert!(matches!(second.await, Ok(PlanApproval::Rejected(None))));
+
+        state.cancel_tool_approval("tool_1").await;
+        assert!(first.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_queued_messages_are_taken_in_order() {
+        let state = SessionState::new();
+        assert!(!state.has_queued_messages().await);
+
+        for (id, content) in [("a", "first"), ("b", "second")] {
+            state
+                .queue_message(SteeringMessage {
+                    id: id.to_string(),
+                    content: content.to_string(),
+                })
+                .await;
+        }
+        assert!(state.clone().has_queued_messages().await);
+
+        let taken = state.take_queued_messages().await;
+        assert_eq!(
+            taken.iter().map(|m| m.id.as_str()).collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert!(!state.has_queued_messages().await);
+    }
+
+    #[test]
+    fn test_compaction_request_is_taken_once() {
+        let state = SessionState::new();
+        assert!(!state.take_compaction_request());
+        state.clone().request_compaction();
+        assert!(state.take_compaction_request());
+        assert!(!state.take_compaction_request());
+    }
+
+    #[tokio::test]
+    async fn test_compaction_approval() {
+        let state = SessionState::new();
+        assert!(!state.respond_compaction_approval(true).await);
+
+        let receiver = state.request_compaction_approval().await;
+        assert!(state.respond_compaction_approval(false).await);
+        assert!(!receiver.await.unwrap());
+        assert!(!state.respond_compaction_approval(true).await);
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume() {
+        let state = SessionState::new();
+        assert!(!state.is_paused());
+        assert!(!state.resume());
+
+        assert!(state.pause());
+        assert!(!state.pause());
+        assert!(state.is_paused());
+
+        let state2 = state.clone();
+        let handle = tokio::spawn(async move { state2.wait_until_resumed().await });
+
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        assert!(!handle.is_finished());
+
+        assert!(state.resume());
+        handle.await.unwrap();
+        assert!(!state.is_paused());
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -5,19 +6,24 @@ use tokio_util::sync::CancellationToken;
 
 use crate::agent::error::AgentError;
 use crate::agent::prompts::{
-    get_agent_type, get_default_agent_type, interpolate_prompt, AgentType,
+    get_default_agent_type, interpolate_prompt, resolve_agent_type, AgentPromptOverrides, AgentType,
 };
-use crate::agent::provider::ProviderAdapter;
+use crate::agent::provider::{HeadlessResult, ProviderAdapter};
 use crate::agent::tools::get_tool_definitions;
+use crate::agent::tools::types::AgentContext;
 use crate::agent::types::{ChatMessage, MessageRole, ToolDefinition};
 use crate::agent::usage::SessionUsageTracker;
-use crate::config::ConfigService;
+use crate::config::{AutoApproveConfig, ConfigService};
 
+use super::context::ExecutionContext as ToolContext;
+use super::output::truncate_middle;
 use super::state::SessionState;
 
 /// Default tools for the explore agent (backward compatibility)
 const DEFAULT_SUBAGENT_TOOLS: &[&str] = &[
     "read_file",
+    "outline_file",
+    "semantic_search",
     "list_directory",
     "glob",
     "grep",
@@ -26,6 +32,9 @@ const DEFAULT_SUBAGENT_TOOLS: &[&str] = &[
     "todo_read",
 ];
 
+/// Context files longer than this are cut in the middle.
+const MAX_CONTEXT_FILE_BYTES: usize = 50_000;
+
 /// Parameters for executing a sub-agent
 pub struct SubagentParams<'a> {
     pub project_path: &'a Path,
@@ -36,10 +45,23 @@ pub struct SubagentParams<'a> {
     pub current_depth: u32,
     pub parent_token: &'a CancellationToken,
     pub usage_tracker: Arc<SessionUsageTracker>,
+    /// JSON schema the final answer must match (see `HeadlessResult::structured`)
+    pub response_schema: Option<serde_json::Value>,
+    /// `{VAR:name}` values sent with the parent's message
+    pub variables: HashMap<String, String>,
+    /// The parent runs in dry-run mode; the sub-agent does too
+    pub dry_run: bool,
+    /// The parent session's `agent_set_auto_approve` setting (None = project config)
+    pub auto_approve: Option<AutoApproveConfig>,
 }
 
 /// Execute a sub-agent with the specified agent type.
 pub async fn execute_subagent(params: SubagentParams<'_>) -> Result<String, AgentError> {
+    Ok(run_subagent(params).await?.text)
+}
+
+/// Like `execute_subagent`, but returns the full headless result.
+pub async fn run_subagent(params: SubagentParams<'_>) -> Result<HeadlessResult, AgentError> {
     let SubagentParams {
         project_path,
         task,
@@ -49,6 +71,10 @@ pub async fn execute_subagent(params: SubagentParams<'_>) -> Result<String, Agen
         current_depth,
         parent_token,
         usage_tracker,
+        response_schema,
+        variables,
+        dry_run,
+        auto_approve,
     } = params;
 
     // Check for cancellation before starting
@@ -58,27 +84,39 @@ pub async fn execute_subagent(params: SubagentParams<'_>) -> Result<String, Agen
 
     if current_depth >= max_depth {
         return Err(AgentError::ToolExecutionError(format!(
-            "Maximum sub-agent depth ({}) exceeded",
-            max_depth
+            "Maximum sub-agent depth ({}) reached: this agent is nested {} deep and cannot \
+             start another agent; do the task with its own tools",
+            max_depth, current_depth
         )));
     }
+    let depth = current_depth + 1;
 
-    // Get the agent type from registry
+    // Load project config, which may define more agent types
+    let mut config = ConfigService::load_project_config(project_path).map_err(|e| {
+        AgentError::ConfigError(format!(
+            "Failed to load config for '{}' agent: {}",
+            agent_type_id.unwrap_or("explore"),
+            e
+        ))
+    })?;
+
+    // Get the agent type from the registry or the project's custom agents
+    let custom_agents = config.custom_agents.clone();
     let agent_type = match agent_type_id {
-        Some(id) => get_agent_type(id).unwrap_or_else(|| {
+        Some(id) => resolve_agent_type(id, &custom_agents).unwrap_or_else(|| {
             log::warn!("Unknown agent type '{}', falling back to explore", id);
-            get_default_agent_type()
+            get_default_agent_type().clone()
         }),
-        None => get_default_agent_type(),
+        None => get_default_agent_type().clone(),
     };
+    let agent_type = &agent_type;
 
-    // Load project config
-    let config = ConfigService::load_project_config(project_path).map_err(|e| {
-        AgentError::ConfigError(format!(
-            "Failed to load config for '{}' agent: {}",
-            agent_type.id, e
-        ))
-    })?;
+    // This agent type's model and sampling settings
+    config.apply_agent_model(agent_type.id);
+    config.apply_agent_sampling(agent_type.id);
+    config.execution.max_agent_depth = max_depth;
+    config.execution.restrict_to_depth(depth);
+    config.execution.dry_run |= dry_run;
 
     // Create provider adapter
     let provider = create_subagent_provider(project_path, &config).map_err(|e| {
@@ -101,11 +139,16 @@ pub async fn execute_subagent(params: SubagentParams<'_>) -> Result<String, Agen
         })?
     };
 
+    // Build system prompt for sub-agent (with the project's prompt overrides)
+    let system_prompt =
+        build_agent_system_prompt(agent_type, &tools, project_path, &config, &variables)?;
+
     // Create fresh session for sub-agent
     let session = SessionState::new();
-
-    // Build system prompt for sub-agent (use custom prompt if configured)
-    let system_prompt = build_agent_system_prompt(agent_type, &tools, project_path, &config);
+    session.set_prompt_variables(variables).await;
+    session.set_agent_type(agent_type.id).await;
+    session.set_agent_depth(depth);
+    session.set_auto_approve(auto_approve).await;
 
     // Create initial message
     let messages = vec![ChatMessage::new(MessageRole::User, task.to_string())];
@@ -120,17 +163,58 @@ pub async fn execute_subagent(params: SubagentParams<'_>) -> Result<String, Agen
         usage_tracker,
     };
 
-    let result = provider
+    provider
         .run_headless(
             messages,
             Some(system_prompt),
             None, // Sub-agents don't use project memory
             tools,
+            response_schema,
             ctx,
         )
-        .await?;
+        .await
+}
+
+/// `task` preceded by the files and snippets of `context`. Files are read
+/// like read_file reads them, and long ones are cut in the middle.
+pub(super) async fn task_with_context(
+    ctx: &ToolContext,
+    task: &str,
+    context: &[AgentContext],
+) -> Result<String, AgentError> {
+    if context.is_empty() {
+        return Ok(task.to_string());
+    }
+
+    let mut sections = Vec::with_capacity(context.len());
+    for item in context {
+        sections.push(match item {
+            AgentContext::File { path } => {
+                let resolved = ctx.resolve_path(path)?;
+                let content = ctx
+                    .with_timeout(
+                        &format!("read context file {}", path),
+                        tokio::fs::read_to_string(&resolved),
+                    )
+                    .await?;
+                format!(
+                    "### {}\n```\n{}\n```",
+                    path,
+                    truncate_middle(content.trim_end(), MAX_CONTEXT_FILE_BYTES)
+                )
+            }
+            AgentContext::Snippet { text, label } => match label {
+                Some(label) => format!("### {}\n{}", label, text.trim()),
+                None => text.trim().to_string(),
+            },
+        });
+    }
 
-    Ok(result.text)
+    Ok(format!(
+        "## Context\n\n{}\n\n## Task\n\n{}",
+        sections.join("\n\n"),
+        task
+    ))
 }
 
 fn create_subagent_provider(
@@ -144,23 +228,17 @@ fn create_subagent_provider(
     match provider.as_str() {
         "anthropic" => {
             let adapter = AnthropicAdapter::new(
-                config.agent.clone(),
-                config.prompts.clone(),
-                config.execution.clone(),
+                config.clone(),
                 project_path.to_path_buf(),
                 DEFAULT_SYSTEM_PROMPT,
-                config.extraction_prompt.clone(),
             )?;
             Ok(Box::new(adapter))
         }
         "gemini" => {
             let adapter = GeminiAdapter::new(
-                config.agent.clone(),
-                config.prompts.clone(),
-                config.execution.clone(),
+                config.clone(),
                 project_path.to_path_buf(),
                 DEFAULT_SYSTEM_PROMPT,
-                config.extraction_prompt.clone(),
             )?;
             Ok(Box::new(adapter))
         }
@@ -203,29 +281,48 @@ fn filter_tools(
     Ok(filtered)
 }
 
+/// The system prompt `agent_type` runs with when dispatched with its default tools.
+pub(crate) fn agent_system_prompt(
+    agent_type: &AgentType,
+    project_path: &Path,
+    config: &crate::config::ProjectConfig,
+    variables: &HashMap<String, String>,
+) -> Result<String, AgentError> {
+    let tools = if agent_type.flags.no_tools {
+        vec![]
+    } else {
+        filter_tools(None, agent_type)?
+    };
+    build_agent_system_prompt(agent_type, &tools, project_path, config, variables)
+}
+
 fn build_agent_system_prompt(
     agent_type: &AgentType,
     tools: &[ToolDefinition],
     project_path: &Path,
     config: &crate::config::ProjectConfig,
-) -> String {
+    variables: &HashMap<String, String>,
+) -> Result<String, AgentError> {
     let tool_names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
     let project_path_str = project_path.to_string_lossy();
 
-    // Use custom prompt if configured, otherwise use default
-    let prompt = config
-        .agent_prompts
-        .as_ref()
-        .and_then(|prompts| prompts.get(agent_type.id))
-        .map(|s| s.as_str())
-        .unwrap_or(agent_type.prompt);
-
-    interpolate_prompt(prompt, &tool_names, Some(&project_path_str))
+    // Project overrides from config or .devflow/agents/overrides/, otherwise the default
+    let prompt = AgentPromptOverrides::load(project_path, config)?.prompt_for(agent_type);
+
+    let mut all_variables = config.prompts.variables.clone();
+    all_variables.extend(variables.iter().map(|(k, v)| (k.clone(), v.clone())));
+    interpolate_prompt(
+        &prompt,
+        &tool_names,
+        Some(&project_path_str),
+        &all_variables,
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::agent::prompts::get_agent_type;
 
     #[test]
     fn test_get_agent_type_explore() {
@@ -323,6 +420,47 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_task_with_context() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("auth.rs"), "fn login() {}\n").unwrap();
+        let ctx = ToolContext::new(
+            temp_dir.path().to_path_buf(),
+            30,
+            &crate::config::NetworkConfig::default(),
+        );
+
+        let task = task_with_context(
+            &ctx,
+            "Check the login flow",
+            &[
+                AgentContext::File {
+                    path: "auth.rs".to_string(),
+                },
+                AgentContext::Snippet {
+                    text: "Sessions expire after 1h".to_string(),
+                    label: Some("Finding".to_string()),
+                },
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            task,
+            "## Context\n\n### auth.rs\n```\nfn login() {}\n```\n\n\
+             ### Finding\nSessions expire after 1h\n\n## Task\n\nCheck the login flow"
+        );
+
+        assert_eq!(
+            task_with_context(&ctx, "Plain task", &[]).await.unwrap(),
+            "Plain task"
+        );
+        let missing = [AgentContext::File {
+            path: "missing.rs".to_string(),
+        }];
+        assert!(task_with_context(&ctx, "Task", &missing).await.is_err());
+    }
+
     #[tokio::test]
     async fn test_depth_limit_exceeded() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -353,6 +491,10 @@ max_tokens = 4096
             current_depth: 3, // Already at max
             parent_token: &cancel_token,
             usage_tracker,
+            response_schema: None,
+            variables: HashMap::new(),
+            dry_run: false,
+            auto_approve: None,
         })
         .await;
 
@@ -397,6 +539,10 @@ max_tokens = 4096
             current_depth: 0,
             parent_token: &cancel_token,
             usage_tracker,
+            response_schema: None,
+            variables: HashMap::new(),
+            dry_run: false,
+            auto_approve: None,
         })
         .await;
 
@@ -0,0 +1,103 @@
+//! Attribution trailer for commits the agent makes through the bash tool.
+//! Asking the model to add the trailer is not enough, so every `git commit`
+//! in a command line gets a `--trailer` argument before it runs. Git does not
+//! repeat a trailer the message already ends with.
+
+use super::interactive::{command_words, program_name, segments};
+use crate::config::ShellKind;
+
+/// `command` with `--trailer <trailer>` after each `git commit`, quoted for `shell`.
+pub(super) fn add_commit_trailer(command: &str, trailer: &str, shell: ShellKind) -> String {
+    let argument = format!(" --trailer {}", quote(trailer, shell));
+    let mut result = command.to_string();
+    // From the end, so earlier offsets stay valid
+    for end in commit_word_ends(command).into_iter().rev() {
+        result.insert_str(end, &argument);
+    }
+    result
+}
+
+/// Byte offsets just past the `commit` of each `git commit` in `command`.
+fn commit_word_ends(command: &str) -> Vec<usize> {
+    let mut ends = Vec::new();
+    for segment in segments(command) {
+        let words = command_words(segment.text);
+        let Some((git, rest)) = words.split_first() else {
+            continue;
+        };
+        if program_name(git) != "git" {
+            continue;
+        }
+        if let Some(commit) = subcommand(rest).filter(|word| *word == "commit") {
+            // Words are slices of `command`
+            let start = commit.as_ptr() as usize - command.as_ptr() as usize;
+            ends.push(start + commit.len());
+        }
+    }
+    ends
+}
+
+/// The git subcommand, after global options such as `-c user.name=x` or `-C dir`.
+fn subcommand<'a>(words: &[&'a str]) -> Option<&'a str> {
+    let mut words = words.iter();
+    while let Some(word) = words.next() {
+        match *word {
+            "-c" | "-C" | "--git-dir" | "--work-tree" | "--namespace" => {
+                words.next();
+            }
+            word if word.starts_with('-') => {}
+            word => return Some(word),
+        }
+    }
+    None
+}
+
+fn quote(text: &str, shell: ShellKind) -> String {
+    match shell {
+        ShellKind::Powershell => format!("'{}'", text.replace('\'', "''")),
+        ShellKind::Cmd => format!("\"{}\"", text.replace('"', "")),
+        ShellKind::Auto | ShellKind::Sh => format!("'{}'", text.replace('\'', "'\\''")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRAILER: &str = "Generated-by: devflow (claude-sonnet-4) 2025-01-31";
+
+    #[test]
+    fn test_add_commit_trailer_to_each_commit() {
+        assert_eq!(
+            add_commit_trailer("git add -A && git commit -m 'fix: x'", TRAILER, ShellKind::Sh),
+            "git add -A && git commit --trailer 'Generated-by: devflow (claude-sonnet-4) 2025-01-31' -m 'fix: x'"
+        );
+        assert_eq!(
+            add_commit_trailer(
+                "git -c user.name=a commit -qm one; git commit --amend --no-edit",
+                "A: b",
+                ShellKind::Sh
+            ),
+            "git -c user.name=a commit --trailer 'A: b' -qm one; git commit --trailer 'A: b' --amend --no-edit"
+        );
+    }
+
+    #[test]
+    fn test_add_commit_trailer_leaves_other_commands() {
+        for command in [
+            "git status",
+            "git log --grep commit",
+            "echo 'git commit -m x'",
+            "cargo commit",
+        ] {
+            assert_eq!(add_commit_trailer(command, TRAILER, ShellKind::Sh), command);
+        }
+    }
+
+    #[test]
+    fn test_quote_per_shell() {
+        assert_eq!(quote("it's", ShellKind::Sh), "'it'\\''s'");
+        assert_eq!(quote("it's", ShellKind::Powershell), "'it''s'");
+        assert_eq!(quote("a \"b\"", ShellKind::Cmd), "\"a b\"");
+    }
+}
@@ -1,18 +1,36 @@
 use scraper::{Html, Selector};
 
-use super::context::{ExecutionContext, MAX_OUTPUT_SIZE};
+use super::context::ExecutionContext;
 use crate::agent::error::AgentError;
-use crate::agent::tools::types::WebFetchInput;
 
 const USER_AGENT: &str = "Mozilla/5.0 (compatible; DevflowBot/1.0)";
 
-pub async fn fetch(ctx: &ExecutionContext, input: serde_json::Value) -> Result<String, AgentError> {
-    let input: WebFetchInput = serde_json::from_value(input)
-        .map_err(|e| AgentError::InvalidToolInput(format!("Invalid input: {}", e)))?;
+/// Converted pages are cut to this size, about 25k tokens
+const MAX_PAGE_BYTES: usize = 100 * 1024;
 
+/// Fetches `url`, converting HTML to markdown and truncating to the page budget.
+pub async fn fetch_page(ctx: &ExecutionContext, url: &str) -> Result<String, AgentError> {
+    let (content_type, content) = get(ctx, url).await?;
+
+    let content = if content_type.contains("text/html") || content.trim_start().starts_with("<!") {
+        html_to_markdown(&content)
+    } else {
+        content
+    };
+
+    Ok(truncate_page(content, MAX_PAGE_BYTES))
+}
+
+/// Fetches the body of `url` unconverted.
+pub async fn fetch_raw(ctx: &ExecutionContext, url: &str) -> Result<String, AgentError> {
+    Ok(get(ctx, url).await?.1)
+}
+
+/// Returns the content type and body of a successful GET.
+async fn get(ctx: &ExecutionContext, url: &str) -> Result<(String, String), AgentError> {
     let response = ctx
         .http_client
-        .get(&input.url)
+        .get(url)
         .header("User-Agent", USER_AGENT)
         .send()
         .await
@@ -38,26 +56,45 @@ pub async fn fetch(ctx: &ExecutionContext, input: serde_json::Value) -> Result<S
         .await
         .map_err(|e| AgentError::ToolExecutionError(format!("Failed to read response: {}", e)))?;
 
-    let content = if content_type.contains("text/html") || content.trim_start().starts_with("<!") {
-        html_to_markdown(&content)
-    } else {
-        content
-    };
+    Ok((content_type, content))
+}
 
-    let content = if content.len() > MAX_OUTPUT_SIZE {
-        format!(
-            "{}...\n(truncated, {} bytes total)",
-            &content[..MAX_OUTPUT_SIZE],
-            content.len()
-        )
-    } else {
-        content
-    };
+/// Task for the web-extract agent: answer `prompt` from the fetched page.
+pub fn extraction_task(url: &str, prompt: &str, content: &str) -> String {
+    format!(
+        "URL: {}\n\nQuestion: {}\n\n<page>\n{}\n</page>",
+        url, prompt, content
+    )
+}
+
+pub(super) fn truncate_page(content: String, max_bytes: usize) -> String {
+    if content.len() <= max_bytes {
+        return content;
+    }
 
-    Ok(content)
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}...\n(truncated, {} bytes total)",
+        &content[..end],
+        content.len()
+    )
 }
 
-fn html_to_markdown(html: &str) -> String {
+/// Elements hidden from readers or used for site chrome rather than content.
+fn is_non_content(el: &scraper::ElementRef) -> bool {
+    let value = el.value();
+    value.attr("hidden").is_some()
+        || value.attr("aria-hidden") == Some("true")
+        || matches!(
+            value.attr("role"),
+            Some("navigation" | "banner" | "contentinfo" | "search")
+        )
+}
+
+pub(super) fn html_to_markdown(html: &str) -> String {
     let document = Html::parse_document(html);
     let mut output = String::new();
 
@@ -102,6 +139,9 @@ fn element_to_markdown(element: &scraper::ElementRef) -> String {
                 output.push(' ');
             }
         } else if let Some(el) = scraper::ElementRef::wrap(child) {
+            if is_non_content(&el) {
+                continue;
+            }
             let tag = el.value().name();
             match tag {
                 "h1" => output.push_str(&format!("\n# {}\n\n", get_text(&el))),
@@ -163,7 +203,8 @@ fn element_to_markdown(element: &scraper::ElementRef) -> String {
                     }
                     output.push('\n');
                 }
-                "script" | "style" | "nav" | "footer" | "header" | "aside" | "noscript" => {
+                "script" | "style" | "nav" | "footer" | "header" | "aside" | "noscript"
+                | "form" | "button" | "svg" | "template" | "iframe" | "dialog" => {
                     // Skip non-content elements
                 }
                 "div" | "section" | "article" | "main" | "span" | "figure" | "figcaption" => {
@@ -179,13 +220,8 @@ fn element_to_markdown(element: &scraper::ElementRef) -> String {
                 "table" => {
                     output.push_str(&table_to_markdown(&el));
                 }
-                _ => {
-                    let text = get_text(&el);
-                    if !text.is_empty() {
-                        output.push_str(&text);
-                        output.push(' ');
-                    }
-                }
+                // Recurse rather than take all text, so nested scripts and nav are still skipped
+                _ => output.push_str(&element_to_markdown(&el)),
             }
         }
     }
@@ -254,3 +290,44 @@ fn clean_markdown(text: &str) -> String {
 
     result.trim().to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_markdown_strips_chrome() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Docs</title><script>var x = 1;</script></head>
+<body>
+  <nav><a href="/">Home</a></nav>
+  <div role="navigation">Breadcrumbs</div>
+  <div class="wrapper">
+    <h2>Install</h2>
+    <p>Run <code>cargo add serde</code>.</p>
+    <dl><dt>Note</dt><dd>Works offline<script>track()</script></dd></dl>
+    <div aria-hidden="true">Decorative</div>
+  </div>
+  <footer>Copyright</footer>
+</body></html>"#;
+
+        let md = html_to_markdown(html);
+        assert!(md.starts_with("# Docs"));
+        assert!(md.contains("## Install"));
+        assert!(md.contains("`cargo add serde`"));
+        assert!(md.contains("Works offline"));
+        for chrome in ["Home", "Breadcrumbs", "track()", "Decorative", "Copyright"] {
+            assert!(!md.contains(chrome), "{} should be stripped", chrome);
+        }
+    }
+
+    #[test]
+    fn test_truncate_page_respects_char_boundaries() {
+        let content = "é".repeat(10);
+        let truncated = truncate_page(content.clone(), 5);
+        assert!(truncated.starts_with("éé..."));
+        assert!(truncated.contains("20 bytes total"));
+
+        assert_eq!(truncate_page(content.clone(), 100), content);
+    }
+}
@@ -2,14 +2,27 @@ mod executor;
 mod local;
 mod types;
 
-pub use executor::ToolExecutor;
+pub use executor::{ToolExecutor, ToolOutput};
+pub use local::ArchivedPlan;
 pub use local::CompactedContext;
+pub use local::FileSnapshot;
 pub use local::LocalExecutor;
 pub use local::PlanApproval;
+pub use local::PlanStatus;
 pub use local::SessionState;
+pub use local::TodoList;
+pub use local::TrackedTodo;
+pub(crate) use local::{
+    agent_system_prompt, probe_shell, resolve_in_dir, run_subagent, SubagentParams,
+};
+pub use types::TodoItem;
 pub use types::ToolName;
 
+use std::collections::HashMap;
+
+use crate::agent::prompts::get_agent_type;
 use crate::agent::types::ToolDefinition;
+use crate::config::ProjectConfig;
 use once_cell::sync::Lazy;
 use serde_json::json;
 
@@ -22,10 +35,16 @@ mod descriptions {
     pub const LIST_DIRECTORY: &str = include_str!("descriptions/list_directory.md");
     pub const GLOB: &str = include_str!("descriptions/glob.md");
     pub const GREP: &str = include_str!("descriptions/grep.md");
+    pub const OUTLINE_FILE: &str = include_str!("descriptions/outline_file.md");
+    pub const SEMANTIC_SEARCH: &str = include_str!("descriptions/semantic_search.md");
     pub const NOTEBOOK_READ: &str = include_str!("descriptions/notebook_read.md");
     pub const NOTEBOOK_EDIT: &str = include_str!("descriptions/notebook_edit.md");
+    pub const SCAFFOLD: &str = include_str!("descriptions/scaffold.md");
     pub const WEB_FETCH: &str = include_str!("descriptions/web_fetch.md");
     pub const SEARCH_WEB: &str = include_str!("descriptions/search_web.md");
+    pub const LOOKUP_DOCS: &str = include_str!("descriptions/lookup_docs.md");
+    pub const QUERY_DATABASE: &str = include_str!("descriptions/query_database.md");
+    pub const DOCKER: &str = include_str!("descriptions/docker.md");
     pub const TODO_READ: &str = include_str!("descriptions/todo_read.md");
     pub const TODO_WRITE: &str = include_str!("descriptions/todo_write.md");
     pub const DISPATCH_AGENT: &str = include_str!("descriptions/dispatch_agent.md");
@@ -48,6 +67,10 @@ static TOOL_DEFINITIONS: Lazy<Vec<ToolDefinition>> = Lazy::new(|| {
                     "timeout": {
                         "type": "integer",
                         "description": "Timeout in seconds (optional)"
+                    },
+                    "cwd": {
+                        "type": "string",
+                        "description": "Directory to run in: a project subdirectory or an absolute path in a writable allowed directory (optional, default project root)"
                     }
                 },
                 "required": ["command"]
@@ -167,11 +190,16 @@ static TOOL_DEFINITIONS: Lazy<Vec<ToolDefinition>> = Lazy::new(|| {
                 "properties": {
                     "pattern": {
                         "type": "string",
-                        "description": "Glob pattern (e.g., **/*.rs)"
+                        "description": "Glob pattern (e.g., **/*.rs or {src,tests}/**/*.{ts,tsx})"
                     },
                     "path": {
                         "type": "string",
                         "description": "Base directory (optional, defaults to project root)"
+                    },
+                    "exclude": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Patterns of paths to leave out, relative to the base directory (e.g., !**/dist/**)"
                     }
                 },
                 "required": ["pattern"]
@@ -194,11 +222,68 @@ static TOOL_DEFINITIONS: Lazy<Vec<ToolDefinition>> = Lazy::new(|| {
                     "include": {
                         "type": "string",
                         "description": "File pattern filter (e.g., *.rs)"
+                    },
+                    "output_mode": {
+                        "type": "string",
+                        "enum": ["content", "files_with_matches", "count"],
+                        "description": "content: matching lines (default); files_with_matches: file paths only; count: matching lines per file"
+                    },
+                    "-B": {
+                        "type": "integer",
+                        "description": "Lines to show before each match (content mode)"
+                    },
+                    "-A": {
+                        "type": "integer",
+                        "description": "Lines to show after each match (content mode)"
+                    },
+                    "-C": {
+                        "type": "integer",
+                        "description": "Lines to show before and after each match (content mode)"
+                    },
+                    "-i": {
+                        "type": "boolean",
+                        "description": "Case-insensitive search"
+                    },
+                    "multiline": {
+                        "type": "boolean",
+                        "description": "Let the pattern match across lines, with . matching newlines"
                     }
                 },
                 "required": ["pattern"]
             }),
         },
+        ToolDefinition {
+            name: "outline_file".to_string(),
+            description: descriptions::OUTLINE_FILE.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Relative path to the source file"
+                    }
+                },
+                "required": ["path"]
+            }),
+        },
+        ToolDefinition {
+            name: "semantic_search".to_string(),
+            description: descriptions::SEMANTIC_SEARCH.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "query": {
+                        "type": "string",
+                        "description": "What the code you are looking for does, in natural language"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum results (default: 10, max: 50)"
+                    }
+                },
+                "required": ["query"]
+            }),
+        },
         // Notebook Tools
         ToolDefinition {
             name: "notebook_read".to_string(),
@@ -209,6 +294,10 @@ static TOOL_DEFINITIONS: Lazy<Vec<ToolDefinition>> = Lazy::new(|| {
                     "path": {
                         "type": "string",
                         "description": "Relative path to .ipynb file"
+                    },
+                    "include_outputs": {
+                        "type": "boolean",
+                        "description": "Include cell outputs, truncated, with images as placeholders (default: false)"
                     }
                 },
                 "required": ["path"]
@@ -226,11 +315,11 @@ static TOOL_DEFINITIONS: Lazy<Vec<ToolDefinition>> = Lazy::new(|| {
                     },
                     "cell_number": {
                         "type": "integer",
-                        "description": "Zero-indexed cell position"
+                        "description": "Zero-indexed cell position (not needed when only clearing outputs)"
                     },
                     "new_source": {
                         "type": "string",
-                        "description": "New cell content"
+                        "description": "New cell content (required for replace and insert)"
                     },
                     "cell_type": {
                         "type": "string",
@@ -241,9 +330,40 @@ static TOOL_DEFINITIONS: Lazy<Vec<ToolDefinition>> = Lazy::new(|| {
                         "type": "string",
                         "enum": ["replace", "insert", "delete"],
                         "description": "Edit mode (default: replace)"
+                    },
+                    "clear_outputs": {
+                        "type": "boolean",
+                        "description": "Clear outputs and execution counts of all code cells (default: false)"
+                    }
+                },
+                "required": ["path"]
+            }),
+        },
+        // Template Tools
+        ToolDefinition {
+            name: "scaffold".to_string(),
+            description: descriptions::SCAFFOLD.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "template": {
+                        "type": "string",
+                        "description": "Name of the scaffold template (omit to list the available templates)"
+                    },
+                    "variables": {
+                        "type": "array",
+                        "description": "Values for the template's variables",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "name": { "type": "string" },
+                                "value": { "type": "string" }
+                            },
+                            "required": ["name", "value"]
+                        }
                     }
                 },
-                "required": ["path", "cell_number", "new_source"]
+                "required": []
             }),
         },
         // Web Tools
@@ -289,6 +409,80 @@ static TOOL_DEFINITIONS: Lazy<Vec<ToolDefinition>> = Lazy::new(|| {
                 "required": ["query"]
             }),
         },
+        ToolDefinition {
+            name: "lookup_docs".to_string(),
+            description: descriptions::LOOKUP_DOCS.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "ecosystem": {
+                        "type": "string",
+                        "enum": ["crate", "npm", "pypi", "mdn"],
+                        "description": "Package registry or documentation source"
+                    },
+                    "package": {
+                        "type": "string",
+                        "description": "Package name (e.g. serde, @types/node, requests)"
+                    },
+                    "symbol": {
+                        "type": "string",
+                        "description": "Item or section to look up (e.g. Deserialize, useEffect)"
+                    },
+                    "version": {
+                        "type": "string",
+                        "description": "Package version (default: latest)"
+                    }
+                },
+                "required": ["ecosystem", "package"]
+            }),
+        },
+        // Database Tools
+        ToolDefinition {
+            name: "query_database".to_string(),
+            description: descriptions::QUERY_DATABASE.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "database": {
+                        "type": "string",
+                        "description": "Name of a database configured in .devflow/config.toml"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "SQL to run; omit to get the schema"
+                    }
+                },
+                "required": ["database"]
+            }),
+        },
+        // Container Tools
+        ToolDefinition {
+            name: "docker".to_string(),
+            description: descriptions::DOCKER.to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "action": {
+                        "type": "string",
+                        "enum": ["ps", "exec", "logs"],
+                        "description": "List containers, run a command in a service, or read a service's logs"
+                    },
+                    "service": {
+                        "type": "string",
+                        "description": "Compose service name (required for exec and logs)"
+                    },
+                    "command": {
+                        "type": "string",
+                        "description": "Shell command to run in the service (exec)"
+                    },
+                    "tail": {
+                        "type": "integer",
+                        "description": "Number of log lines to return (logs, default 200, max 2000)"
+                    }
+                },
+                "required": ["action"]
+            }),
+        },
         // Task Management Tools
         ToolDefinition {
             name: "todo_read".to_string(),
@@ -348,6 +542,27 @@ static TOOL_DEFINITIONS: Lazy<Vec<ToolDefinition>> = Lazy::new(|| {
                         "type": "array",
                         "items": { "type": "string" },
                         "description": "Override allowed tools for the agent (optional)"
+                    },
+                    "context": {
+                        "type": "array",
+                        "description": "Files and snippets the agent gets before the task, so it does not have to find them again (optional)",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "path": {
+                                    "type": "string",
+                                    "description": "Project file to include whole"
+                                },
+                                "text": {
+                                    "type": "string",
+                                    "description": "Snippet to include as is, e.g. an excerpt or an earlier finding"
+                                },
+                                "label": {
+                                    "type": "string",
+                                    "description": "Heading for the snippet"
+                                }
+                            }
+                        }
                     }
                 },
                 "required": ["task"]
@@ -374,6 +589,84 @@ pub fn get_tool_definitions() -> Vec<ToolDefinition> {
     TOOL_DEFINITIONS.clone()
 }
 
+/// Project changes to the built-in tool descriptions.
+#[derive(Debug, Clone, Default)]
+pub struct ToolDescriptionOverrides {
+    /// Replacement descriptions by tool name (`tool_descriptions`)
+    replace: HashMap<String, String>,
+    /// Text appended to descriptions by tool name (`tool_notes`)
+    append: HashMap<String, String>,
+    /// Ids and descriptions of the project's agent types (`custom_agents`),
+    /// offered by dispatch_agent next to the built-in ones
+    custom_agents: Vec<(String, String)>,
+}
+
+impl ToolDescriptionOverrides {
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        let mut custom_agents: Vec<(String, String)> = config
+            .custom_agents
+            .iter()
+            .filter(|(id, _)| get_agent_type(id).is_none())
+            .map(|(id, agent)| (id.clone(), agent.description.clone()))
+            .collect();
+        custom_agents.sort();
+        Self {
+            replace: config.tool_descriptions.clone().unwrap_or_default(),
+            append: config.tool_notes.clone(),
+            custom_agents,
+        }
+    }
+
+    /// Replaces, then appends to, the descriptions of `tools`. Blank entries
+    /// are ignored so a cleared field falls back to the default. The
+    /// project's agent types are added to dispatch_agent first.
+    pub fn apply(&self, mut tools: Vec<ToolDefinition>) -> Vec<ToolDefinition> {
+        for tool in &mut tools {
+            if tool.name == "dispatch_agent" {
+                self.add_custom_agents(tool);
+            }
+            if let Some(description) = non_blank(self.replace.get(&tool.name)) {
+                tool.description = description.to_string();
+            }
+            if let Some(note) = non_blank(self.append.get(&tool.name)) {
+                tool.description = format!("{}\n\n{}", tool.description.trim_end(), note.trim());
+            }
+        }
+        tools
+    }
+
+    fn add_custom_agents(&self, tool: &mut ToolDefinition) {
+        if self.custom_agents.is_empty() {
+            return;
+        }
+        if let Some(ids) = tool.input_schema["properties"]["agent_type"]["enum"].as_array_mut() {
+            ids.extend(self.custom_agents.iter().map(|(id, _)| json!(id)));
+        }
+        let rows: Vec<String> = self
+            .custom_agents
+            .iter()
+            .map(|(id, description)| format!("| `{}` | {} |", id, description.trim()))
+            .collect();
+        tool.description = format!(
+            "{}\n\n## Project Agent Types\n\n| Type | Purpose |\n|------|---------|\n{}",
+            tool.description.trim_end(),
+            rows.join("\n")
+        );
+    }
+}
+
+fn non_blank(value: Option<&String>) -> Option<&str> {
+    value.map(|v| v.as_str()).filter(|v| !v.trim().is_empty())
+}
+
+/// `tools` without the ones named in `disabled` (`execution.disabled_tools`).
+pub fn without_disabled(tools: Vec<ToolDefinition>, disabled: &[String]) -> Vec<ToolDefinition> {
+    tools
+        .into_iter()
+        .filter(|tool| !disabled.contains(&tool.name))
+        .collect()
+}
+
 /// Returns a HashMap of tool names to their default descriptions (for settings UI)
 pub fn get_tool_descriptions() -> std::collections::HashMap<String, String> {
     let mut map = std::collections::HashMap::new();
@@ -394,6 +687,14 @@ pub fn get_tool_descriptions() -> std::collections::HashMap<String, String> {
     );
     map.insert("glob".to_string(), descriptions::GLOB.to_string());
     map.insert("grep".to_string(), descriptions::GREP.to_string());
+    map.insert(
+        "outline_file".to_string(),
+        descriptions::OUTLINE_FILE.to_string(),
+    );
+    map.insert(
+        "semantic_search".to_string(),
+        descriptions::SEMANTIC_SEARCH.to_string(),
+    );
     map.insert(
         "notebook_read".to_string(),
         descriptions::NOTEBOOK_READ.to_string(),
@@ -402,11 +703,21 @@ pub fn get_tool_descriptions() -> std::collections::HashMap<String, String> {
         "notebook_edit".to_string(),
         descriptions::NOTEBOOK_EDIT.to_string(),
     );
+    map.insert("scaffold".to_string(), descriptions::SCAFFOLD.to_string());
     map.insert("web_fetch".to_string(), descriptions::WEB_FETCH.to_string());
     map.insert(
         "search_web".to_string(),
         descriptions::SEARCH_WEB.to_string(),
     );
+    map.insert(
+        "lookup_docs".to_string(),
+        descriptions::LOOKUP_DOCS.to_string(),
+    );
+    map.insert(
+        "query_database".to_string(),
+        descriptions::QUERY_DATABASE.to_string(),
+    );
+    map.insert("docker".to_string(), descriptions::DOCKER.to_string());
     map.insert("todo_read".to_string(), descriptions::TODO_READ.to_string());
     map.insert(
         "todo_write".to_string(),
@@ -437,10 +748,16 @@ mod tests {
         "list_directory",
         "glob",
         "grep",
+        "outline_file",
+        "semantic_search",
         "notebook_read",
         "notebook_edit",
+        "scaffold",
         "web_fetch",
         "search_web",
+        "lookup_docs",
+        "query_database",
+        "docker",
         "todo_read",
         "todo_write",
         "dispatch_agent",
@@ -614,4 +931,72 @@ mod tests {
         assert!(values.contains(&"explore"), "Missing 'explore' agent type");
         assert!(values.contains(&"plan"), "Missing 'plan' agent type");
     }
+
+    #[test]
+    fn test_without_disabled() {
+        let disabled = vec!["docker".to_string(), "write_file".to_string()];
+        let names: Vec<String> = without_disabled(get_tool_definitions(), &disabled)
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+
+        assert_eq!(names.len(), EXPECTED_TOOLS.len() - 2);
+        assert!(!names.contains(&"docker".to_string()));
+        assert!(!names.contains(&"write_file".to_string()));
+        assert!(names.contains(&"read_file".to_string()));
+    }
+
+    #[test]
+    fn test_description_overrides() {
+        let overrides = ToolDescriptionOverrides {
+            replace: HashMap::from([
+                ("grep".to_string(), "Project grep.".to_string()),
+                ("glob".to_string(), "  ".to_string()),
+            ]),
+            append: HashMap::from([(
+                "bash".to_string(),
+                "Use `make test`, never cargo directly.".to_string(),
+            )]),
+            custom_agents: Vec::new(),
+        };
+
+        let tools = overrides.apply(get_tool_definitions());
+        let description = |name: &str| {
+            tools
+                .iter()
+                .find(|t| t.name == name)
+                .unwrap()
+                .description
+                .clone()
+        };
+
+        assert_eq!(description("grep"), "Project grep.");
+        // Blank replacements keep the default
+        assert_eq!(description("glob"), descriptions::GLOB);
+        let bash = description("bash");
+        assert!(bash.starts_with(descriptions::BASH.trim_end()));
+        assert!(bash.ends_with("\n\nUse `make test`, never cargo directly."));
+    }
+
+    #[test]
+    fn test_custom_agents_offered_by_dispatch_agent() {
+        let overrides = ToolDescriptionOverrides {
+            custom_agents: vec![(
+                "migration-check".to_string(),
+                "Checks database migrations".to_string(),
+            )],
+            ..Default::default()
+        };
+
+        let tools = overrides.apply(get_tool_definitions());
+        let dispatch = tools.iter().find(|t| t.name == "dispatch_agent").unwrap();
+        let ids = dispatch.input_schema["properties"]["agent_type"]["enum"]
+            .as_array()
+            .unwrap();
+        assert!(ids.contains(&json!("explore")));
+        assert!(ids.contains(&json!("migration-check")));
+        assert!(dispatch
+            .description
+            .ends_with("| `migration-check` | Checks database migrations |"));
+    }
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -12,12 +13,21 @@ pub enum ToolName {
     ListDirectory,
     Glob,
     Grep,
+    OutlineFile,
+    SemanticSearch,
     // Notebook Tools
     NotebookRead,
     NotebookEdit,
+    // Template Tools
+    Scaffold,
     // Web Tools
     WebFetch,
     SearchWeb,
+    LookupDocs,
+    // Database Tools
+    QueryDatabase,
+    // Container Tools
+    Docker,
     // Task Management Tools
     TodoRead,
     TodoWrite,
@@ -36,10 +46,16 @@ impl ToolName {
             "list_directory" => Some(ToolName::ListDirectory),
             "glob" => Some(ToolName::Glob),
             "grep" => Some(ToolName::Grep),
+            "outline_file" => Some(ToolName::OutlineFile),
+            "semantic_search" => Some(ToolName::SemanticSearch),
             "notebook_read" => Some(ToolName::NotebookRead),
             "notebook_edit" => Some(ToolName::NotebookEdit),
+            "scaffold" => Some(ToolName::Scaffold),
             "web_fetch" => Some(ToolName::WebFetch),
             "search_web" => Some(ToolName::SearchWeb),
+            "lookup_docs" => Some(ToolName::LookupDocs),
+            "query_database" => Some(ToolName::QueryDatabase),
+            "docker" => Some(ToolName::Docker),
             "todo_read" => Some(ToolName::TodoRead),
             "todo_write" => Some(ToolName::TodoWrite),
             "dispatch_agent" => Some(ToolName::DispatchAgent),
@@ -48,6 +64,14 @@ impl ToolName {
         }
     }
 
+    /// Tools that change file contents
+    pub fn is_file_edit(&self) -> bool {
+        matches!(
+            self,
+            ToolName::WriteFile | ToolName::EditFile | ToolName::MultiEdit | ToolName::Scaffold
+        )
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             ToolName::Bash => "bash",
@@ -58,10 +82,16 @@ impl ToolName {
             ToolName::ListDirectory => "list_directory",
             ToolName::Glob => "glob",
             ToolName::Grep => "grep",
+            ToolName::OutlineFile => "outline_file",
+            ToolName::SemanticSearch => "semantic_search",
             ToolName::NotebookRead => "notebook_read",
             ToolName::NotebookEdit => "notebook_edit",
+            ToolName::Scaffold => "scaffold",
             ToolName::WebFetch => "web_fetch",
             ToolName::SearchWeb => "search_web",
+            ToolName::LookupDocs => "lookup_docs",
+            ToolName::QueryDatabase => "query_database",
+            ToolName::Docker => "docker",
             ToolName::TodoRead => "todo_read",
             ToolName::TodoWrite => "todo_write",
             ToolName::DispatchAgent => "dispatch_agent",
@@ -120,6 +150,9 @@ pub struct ListDirectoryInput {
 pub struct GlobInput {
     pub pattern: String,
     pub path: Option<String>,
+    /// Patterns of paths to leave out, relative to `path`; a leading `!` is allowed
+    #[serde(default)]
+    pub exclude: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -127,6 +160,46 @@ pub struct GrepInput {
     pub pattern: String,
     pub path: Option<String>,
     pub include: Option<String>,
+    #[serde(default)]
+    pub output_mode: GrepOutputMode,
+    /// Lines shown before each match in content mode
+    #[serde(rename = "-B")]
+    pub before_context: Option<usize>,
+    /// Lines shown after each match in content mode
+    #[serde(rename = "-A")]
+    pub after_context: Option<usize>,
+    /// Lines shown before and after each match, unless `-B`/`-A` say otherwise
+    #[serde(rename = "-C")]
+    pub context: Option<usize>,
+    #[serde(rename = "-i", default)]
+    pub case_insensitive: bool,
+    /// Let the pattern span lines, with `.` matching newlines
+    #[serde(default)]
+    pub multiline: bool,
+}
+
+/// What grep lists for the files it searched.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GrepOutputMode {
+    /// Matching lines as `path:line:text`
+    #[default]
+    Content,
+    /// Paths of files with at least one match
+    FilesWithMatches,
+    /// Matching lines per file as `path:count`
+    Count,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutlineFileInput {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SemanticSearchInput {
+    pub query: String,
+    pub limit: Option<u32>,
 }
 
 // Notebook Tool Inputs
@@ -134,15 +207,38 @@ pub struct GrepInput {
 #[derive(Debug, Clone, Deserialize)]
 pub struct NotebookReadInput {
     pub path: String,
+    #[serde(default)]
+    pub include_outputs: bool,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct NotebookEditInput {
     pub path: String,
-    pub cell_number: u32,
-    pub new_source: String,
+    /// Required unless only clearing outputs
+    pub cell_number: Option<u32>,
+    /// Required for replace and insert
+    pub new_source: Option<String>,
     pub cell_type: Option<String>,
     pub edit_mode: Option<String>,
+    /// Clear outputs and execution counts of all code cells
+    #[serde(default)]
+    pub clear_outputs: bool,
+}
+
+// Template Tool Inputs
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScaffoldVariable {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScaffoldInput {
+    /// Scaffold to instantiate; None lists the available ones
+    pub template: Option<String>,
+    #[serde(default)]
+    pub variables: Vec<ScaffoldVariable>,
 }
 
 // Web Tool Inputs
@@ -150,8 +246,8 @@ pub struct NotebookEditInput {
 #[derive(Debug, Clone, Deserialize)]
 pub struct WebFetchInput {
     pub url: String,
-    #[serde(rename = "prompt")]
-    pub _prompt: Option<String>, // Not yet implemented
+    /// What to extract from the page; None returns the converted page
+    pub prompt: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -161,6 +257,25 @@ pub struct WebSearchInput {
     pub blocked_domains: Option<Vec<String>>,
 }
 
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DocsEcosystem {
+    Crate,
+    Npm,
+    Pypi,
+    Mdn,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LookupDocsInput {
+    pub ecosystem: DocsEcosystem,
+    pub package: String,
+    /// Item or section to narrow the docs to
+    pub symbol: Option<String>,
+    /// Defaults to the latest release
+    pub version: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct SubmitPlanInput {
     pub plan: String,
@@ -175,11 +290,60 @@ pub struct DispatchAgentInput {
     /// Override allowed tools for the agent
     #[serde(default)]
     pub tools: Option<Vec<String>>,
+    /// Files and snippets put before the task
+    #[serde(default)]
+    pub context: Vec<AgentContext>,
+}
+
+/// Something the parent already has that a sub-agent should not have to find again.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum AgentContext {
+    /// A project file, included whole
+    File { path: String },
+    /// Text passed as is, e.g. an excerpt or an earlier finding
+    Snippet {
+        text: String,
+        #[serde(default)]
+        label: Option<String>,
+    },
+}
+
+// Database Tool Inputs
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct QueryDatabaseInput {
+    /// Name of a `[databases.<name>]` entry in the project config
+    pub database: String,
+    /// SQL to run; None returns the schema
+    pub query: Option<String>,
+}
+
+// Container Tool Inputs
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DockerAction {
+    Ps,
+    Exec,
+    Logs,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DockerInput {
+    pub action: DockerAction,
+    /// Compose service, required for exec and logs
+    pub service: Option<String>,
+    /// Shell command for exec
+    pub command: Option<String>,
+    /// Log lines to return for logs
+    pub tail: Option<u32>,
 }
 
 // Task Management Tool Inputs
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct TodoItem {
     pub id: String,
     pub content: String,
@@ -280,6 +444,7 @@ mod tests {
         .unwrap();
         assert_eq!(input.pattern, "**/*.rs");
         assert!(input.path.is_none());
+        assert!(input.exclude.is_empty());
     }
 
     #[test]
@@ -293,6 +458,18 @@ mod tests {
         assert_eq!(input.pattern, "fn main");
         assert_eq!(input.path, Some("src".to_string()));
         assert_eq!(input.include, Some("*.rs".to_string()));
+        assert_eq!(input.output_mode, GrepOutputMode::Content);
+
+        let input: GrepInput = serde_json::from_value(json!({
+            "pattern": "todo",
+            "output_mode": "files_with_matches",
+            "-C": 2,
+            "-i": true
+        }))
+        .unwrap();
+        assert_eq!(input.output_mode, GrepOutputMode::FilesWithMatches);
+        assert_eq!(input.context, Some(2));
+        assert!(input.case_insensitive && !input.multiline);
     }
 
     #[test]
@@ -305,6 +482,7 @@ mod tests {
         assert_eq!(input.task, "Find auth files");
         assert!(input.agent_type.is_none());
         assert!(input.tools.is_none());
+        assert!(input.context.is_empty());
 
         // Full input
         let input_full: DispatchAgentInput = serde_json::from_value(json!({
@@ -318,6 +496,23 @@ mod tests {
             input_full.tools,
             Some(vec!["read_file".to_string(), "grep".to_string()])
         );
+
+        let with_context: DispatchAgentInput = serde_json::from_value(json!({
+            "task": "Check the login flow",
+            "context": [
+                { "path": "src/auth.rs" },
+                { "text": "Sessions expire after 1h", "label": "Finding" }
+            ]
+        }))
+        .unwrap();
+        assert!(matches!(
+            &with_context.context[0],
+            AgentContext::File { path } if path == "src/auth.rs"
+        ));
+        assert!(matches!(
+            &with_context.context[1],
+            AgentContext::Snippet { label: Some(label), .. } if label == "Finding"
+        ));
     }
 
     #[test]
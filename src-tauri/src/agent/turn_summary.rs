@@ -0,0 +1,279 @@
+//! What a completed turn did, for the `agent-turn-summary` event: files
+//! changed (from the turn's snapshots), bash commands and test runs (from
+//! the reply's tool results), and the tokens the turn used.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use super::tools::FileSnapshot;
+use super::types::{ChatContentBlock, ChatMessage};
+use super::usage::UsageTotals;
+use super::usage_history::estimate_cost_usd;
+use crate::git::{diff_texts, FileStatus, LineKind};
+
+/// Commands recognized as test runs, matched at the start of a shell segment
+const TEST_COMMANDS: &[&str] = &[
+    "cargo test",
+    "cargo nextest",
+    "npm test",
+    "npm run test",
+    "pnpm test",
+    "pnpm run test",
+    "yarn test",
+    "bun test",
+    "npx jest",
+    "npx vitest",
+    "jest",
+    "vitest",
+    "pytest",
+    "python -m pytest",
+    "python3 -m pytest",
+    "tox",
+    "go test",
+    "mvn test",
+    "gradle test",
+    "./gradlew test",
+    "dotnet test",
+    "rspec",
+    "bundle exec rspec",
+    "phpunit",
+    "make test",
+];
+
+/// Emitted as `agent-turn-summary` right after `agent-complete`
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TurnSummaryPayload {
+    /// Same id as the `agent-complete` event
+    pub message_id: String,
+    /// Files written or edited through the file tools; bash changes are not tracked
+    pub files: Vec<FileChangeSummary>,
+    pub commands: Vec<CommandSummary>,
+    /// Commands from `commands` that ran tests
+    pub tests: Vec<TestRunSummary>,
+    /// Tokens used by the turn, sub-agents included
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// Estimated at the main model's prices; None for models without known pricing
+    pub cost_usd: Option<f64>,
+    /// Tools ran in dry-run mode, so nothing was changed or executed
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct FileChangeSummary {
+    pub path: String,
+    /// added, modified or deleted
+    pub status: FileStatus,
+    pub additions: u32,
+    pub deletions: u32,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct CommandSummary {
+    pub command: String,
+    /// Failed or was refused, e.g. a non-zero exit code
+    pub is_error: bool,
+}
+
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct TestRunSummary {
+    pub command: String,
+    pub passed: bool,
+}
+
+/// Token usage at the start of the turn, to report the turn's share.
+pub(crate) struct TurnUsage<'a> {
+    pub model: &'a str,
+    pub before: UsageTotals,
+    pub after: UsageTotals,
+}
+
+pub(crate) async fn summarize_turn(
+    message_id: String,
+    reply: Option<&ChatMessage>,
+    snapshots: Vec<FileSnapshot>,
+    usage: TurnUsage<'_>,
+    dry_run: bool,
+) -> TurnSummaryPayload {
+    let mut files = Vec::new();
+    for snapshot in snapshots {
+        let current = tokio::fs::read_to_string(&snapshot.absolute).await.ok();
+        if let Some(change) = file_change(&snapshot, current.as_deref()) {
+            files.push(change);
+        }
+    }
+
+    let commands = reply.map(commands_run).unwrap_or_default();
+    let tests = commands
+        .iter()
+        .filter(|command| is_test_command(&command.command))
+        .map(|command| TestRunSummary {
+            command: command.command.clone(),
+            passed: !command.is_error,
+        })
+        .collect();
+
+    let input_tokens = usage
+        .after
+        .input_tokens
+        .saturating_sub(usage.before.input_tokens);
+    let output_tokens = usage
+        .after
+        .output_tokens
+        .saturating_sub(usage.before.output_tokens);
+
+    TurnSummaryPayload {
+        message_id,
+        files,
+        commands,
+        tests,
+        input_tokens,
+        output_tokens,
+        cost_usd: estimate_cost_usd(usage.model, input_tokens.into(), output_tokens.into()),
+        dry_run,
+    }
+}
+
+/// Line counts of the file's change since its snapshot, None if it ended up unchanged.
+fn file_change(snapshot: &FileSnapshot, current: Option<&str>) -> Option<FileChangeSummary> {
+    let diff = diff_texts(&snapshot.path, snapshot.original.as_deref(), current);
+    if diff.hunks.is_empty() {
+        return None;
+    }
+    let lines = diff.hunks.iter().flat_map(|hunk| &hunk.lines);
+    let (mut additions, mut deletions) = (0, 0);
+    for line in lines {
+        match line.kind {
+            LineKind::Addition => additions += 1,
+            LineKind::Deletion => deletions += 1,
+            LineKind::Context => {}
+        }
+    }
+    Some(FileChangeSummary {
+        path: diff.path,
+        status: diff.status,
+        additions,
+        deletions,
+    })
+}
+
+fn commands_run(reply: &ChatMessage) -> Vec<CommandSummary> {
+    reply
+        .content_blocks
+        .iter()
+        .filter_map(|block| match block {
+            ChatContentBlock::ToolUse {
+                tool_name,
+                tool_input,
+                is_error,
+                ..
+            } if tool_name == "bash" => Some(CommandSummary {
+                command: tool_input.get("command")?.as_str()?.to_string(),
+                is_error: is_error.unwrap_or(false),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Whether any segment of a shell command (split on `&&`, `||`, `;` and `|`)
+/// starts with a known test runner, after any `VAR=value` assignments.
+fn is_test_command(command: &str) -> bool {
+    command.split(['&', '|', ';', '\n']).any(|segment| {
+        let words: Vec<&str> = segment
+            .split_whitespace()
+            .skip_while(|word| word.contains('=') && !word.starts_with('-'))
+            .collect();
+        let segment = words.join(" ");
+        TEST_COMMANDS.iter().any(|test| {
+            segment
+                .strip_prefix(test)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::types::MessageRole;
+
+    fn bash(command: &str, is_error: bool) -> ChatContentBlock {
+        ChatContentBlock::ToolUse {
+            tool_use_id: "t".to_string(),
+            tool_name: "bash".to_string(),
+            tool_input: serde_json::json!({ "command": command }),
+            output: Some(String::new()),
+            is_error: Some(is_error),
+        }
+    }
+
+    #[test]
+    fn test_recognizes_test_commands() {
+        assert!(is_test_command("cargo test -p core"));
+        assert!(is_test_command("cd web && CI=1 npm run test"));
+        assert!(is_test_command("python -m pytest tests/ | tail -5"));
+        assert!(!is_test_command("cargo testify"));
+        assert!(!is_test_command("echo go test"));
+        assert!(!is_test_command("git status"));
+    }
+
+    #[tokio::test]
+    async fn test_summary_counts_changes_commands_and_tokens() {
+        let temp = tempfile::tempdir().unwrap();
+        let absolute = temp.path().join("lib.rs");
+        std::fs::write(&absolute, "fn a() {}\nfn b() {}\nfn c() {}\n").unwrap();
+        let snapshots = vec![
+            FileSnapshot {
+                path: "lib.rs".to_string(),
+                absolute,
+                original: Some("fn a() {}\nfn x() {}\n".to_string()),
+            },
+            FileSnapshot {
+                path: "gone.rs".to_string(),
+                absolute: temp.path().join("gone.rs"),
+                original: None,
+            },
+        ];
+        let reply = ChatMessage::with_blocks(
+            MessageRole::Assistant,
+            vec![
+                bash("cargo build", false),
+                bash("cargo test", true),
+                ChatContentBlock::Text {
+                    text: "Done".to_string(),
+                },
+            ],
+        );
+        let usage = TurnUsage {
+            model: "claude-sonnet-4-20250514",
+            before: UsageTotals {
+                input_tokens: 1_000,
+                output_tokens: 100,
+            },
+            after: UsageTotals {
+                input_tokens: 1_001_000,
+                output_tokens: 100,
+            },
+        };
+
+        let summary =
+            summarize_turn("msg".to_string(), Some(&reply), snapshots, usage, false).await;
+
+        assert_eq!(summary.files.len(), 1);
+        assert_eq!(summary.files[0].status, FileStatus::Modified);
+        assert_eq!(
+            (summary.files[0].additions, summary.files[0].deletions),
+            (2, 1)
+        );
+        assert_eq!(summary.commands.len(), 2);
+        assert_eq!(summary.tests.len(), 1);
+        assert!(!summary.tests[0].passed);
+        assert_eq!(summary.input_tokens, 1_000_000);
+        assert_eq!(summary.cost_usd, Some(3.0));
+    }
+}
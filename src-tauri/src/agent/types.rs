@@ -1,7 +1,13 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
 use uuid::Uuid;
 
+use crate::agent::approvals::ApprovalCategory;
+use crate::agent::usage::RunTimings;
+use crate::git::FileDiff;
+
 /// Tool definition for Anthropic API requests
 #[derive(Debug, Serialize, Clone)]
 pub struct ToolDefinition {
@@ -34,6 +40,10 @@ pub struct ChatMessage {
     pub id: String,
     pub role: MessageRole,
     pub content_blocks: Vec<ChatContentBlock>,
+    /// Set on assistant messages kept from a cancelled run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub interrupted: Option<bool>,
 }
 
 impl ChatMessage {
@@ -42,6 +52,7 @@ impl ChatMessage {
             id: Uuid::new_v4().to_string(),
             role,
             content_blocks: vec![ChatContentBlock::Text { text: content }],
+            interrupted: None,
         }
     }
 
@@ -50,6 +61,7 @@ impl ChatMessage {
             id: Uuid::new_v4().to_string(),
             role,
             content_blocks: blocks,
+            interrupted: None,
         }
     }
 
@@ -86,6 +98,7 @@ pub struct AgentChunkPayload {
 pub struct AgentCompletePayload {
     pub message_id: String,
     pub stop_reason: Option<String>,
+    pub timings: RunTimings,
 }
 
 #[derive(Debug, Clone, Serialize, TS)]
@@ -108,9 +121,31 @@ pub struct ToolStartPayload {
 #[ts(export)]
 pub struct ToolEndPayload {
     pub tool_use_id: String,
+    /// What the model gets
     pub output: String,
     pub is_error: bool,
     pub block_index: u32,
+    /// Structured results for the UI to render in place of `output`
+    pub artifacts: Vec<ToolArtifact>,
+}
+
+/// Something a tool produced besides its text. The model only sees the
+/// text; the UI can show these instead.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToolArtifact {
+    /// A file change, e.g. from edit_file
+    Diff { diff: FileDiff },
+    /// Query results
+    Table {
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+    /// Base64-encoded image
+    Image { media_type: String, data: String },
+    /// A page the result refers to, e.g. a search hit
+    Link { url: String, title: Option<String> },
 }
 
 #[derive(Debug, Clone, Serialize, TS)]
@@ -161,6 +196,9 @@ pub enum AgentStatus {
     ToolRunning,
     ToolWaiting,
     Compacting,
+    Reconnecting,
+    RateLimited,
+    Paused,
     Cancelled,
     Error,
 }
@@ -181,6 +219,21 @@ impl AgentStatus {
             }
             AgentStatus::ToolWaiting => "Waiting for response...".to_string(),
             AgentStatus::Compacting => "Compacting context...".to_string(),
+            AgentStatus::Reconnecting => {
+                if let Some(attempt) = detail {
+                    format!("Connection lost, reconnecting ({})...", attempt)
+                } else {
+                    "Connection lost, reconnecting...".to_string()
+                }
+            }
+            AgentStatus::RateLimited => {
+                if let Some(wait) = detail {
+                    format!("Rate limited, waiting {}...", wait)
+                } else {
+                    "Rate limited, waiting...".to_string()
+                }
+            }
+            AgentStatus::Paused => "Paused".to_string(),
             AgentStatus::Cancelled => "Cancelled".to_string(),
             AgentStatus::Error => "Error".to_string(),
         }
@@ -191,12 +244,115 @@ impl AgentStatus {
 #[ts(export)]
 pub struct AgentCancelledPayload {
     pub reason: String,
+    /// Text and completed tool results streamed before cancellation
+    pub partial: Option<ChatMessage>,
+}
+
+/// Emitted when an edit or regeneration truncates the conversation, before
+/// the agent runs again.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ConversationRewoundPayload {
+    /// The conversation the new run continues from
+    pub messages: Vec<ChatMessage>,
+}
+
+/// Emitted before retrying a request that failed on a connection error.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AgentReconnectingPayload {
+    pub attempt: u32,
+    pub max_attempts: u32,
+    #[ts(type = "number")]
+    pub delay_ms: u64,
+    pub error: String,
+    /// Streamed blocks from this index onward belong to the failed attempt and will be re-sent
+    pub block_offset: u32,
+}
+
+/// Emitted once per request when the provider has sent nothing for longer than
+/// the configured slow-response threshold.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AgentSlowResponsePayload {
+    #[ts(type = "number")]
+    pub elapsed_ms: u64,
+    #[ts(type = "number")]
+    pub threshold_ms: u64,
+}
+
+/// How the model may use tools while answering a message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoice {
+    /// The model decides
+    #[default]
+    Auto,
+    /// The first response must call a tool; later ones are free
+    Required,
+    /// Tools stay declared but may not be called
+    None,
+}
+
+/// Per-message options for `agent_send_message`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SendOptions {
+    #[serde(default)]
+    pub tool_choice: ToolChoice,
+    /// Values for `{VAR:name}` prompt placeholders, overriding `[prompts.variables]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub variables: Option<HashMap<String, String>>,
+}
+
+/// A user message queued while the agent is running, injected before its next request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SteeringMessage {
+    /// Client-side id of the queued message
+    pub id: String,
+    pub content: String,
+}
+
+/// Emitted when queued messages are added to the conversation mid-run.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AgentSteeringPayload {
+    pub messages: Vec<SteeringMessage>,
+    /// Id of the assistant message the steering closed, if it had content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub closed_message_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, TS)]
 #[ts(export)]
 pub struct PlanReadyPayload {
     pub plan: String,
+    /// Id to answer with `agent_approve_plan` / `agent_reject_plan`
+    pub plan_id: u32,
+}
+
+/// A plan that stopped waiting for the user: it timed out or the run was
+/// cancelled. Answering it is no longer possible.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct PlanWithdrawnPayload {
+    pub plan_id: u32,
+}
+
+/// A tool call waiting for `agent_respond_tool_approval` because its category
+/// is not auto-approved.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ToolApprovalRequestPayload {
+    pub tool_use_id: String,
+    pub tool_name: String,
+    #[ts(type = "unknown")]
+    pub tool_input: serde_json::Value,
+    pub category: ApprovalCategory,
 }
 
 #[derive(Debug, Clone, Serialize, TS)]
@@ -238,6 +394,18 @@ pub struct AgentCompactionPayload {
     pub facts_count: u32,
 }
 
+/// Session context a compaction is about to apply. With confirmation on for
+/// the session, the run waits for `agent_respond_compaction`.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct AgentCompactionPreviewPayload {
+    pub summary: Option<String>,
+    pub facts: Vec<CompactedFact>,
+    /// Messages the summary and facts replace
+    pub compacted_messages: u32,
+    pub awaiting_confirmation: bool,
+}
+
 #[derive(Debug, Clone, Serialize, TS)]
 #[ts(export)]
 pub struct AgentCompactionWarningPayload {
@@ -1,5 +1,8 @@
 use serde::Serialize;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 use ts_rs::TS;
 
 /// Token counts from a single API response.
@@ -9,11 +12,36 @@ pub struct TokenUsage {
     pub output_tokens: u32,
 }
 
-/// Thread-safe token usage tracker using atomics for lock-free updates.
+/// Running totals for one tool.
+#[derive(Debug, Default, Clone, Copy)]
+struct ToolCounters {
+    calls: u32,
+    errors: u32,
+    duration_ms: u64,
+    output_bytes: u64,
+}
+
+/// Running totals for model responses.
+#[derive(Debug, Default, Clone, Copy)]
+struct ResponseCounters {
+    responses: u32,
+    first_token_ms: u64,
+    /// Responses with a first token, the denominator for `first_token_ms`
+    first_tokens: u32,
+    generating_ms: u64,
+    output_tokens: u64,
+}
+
+/// Thread-safe usage tracker. Token counts use atomics for lock-free updates;
+/// per-tool and response counters sit behind mutexes.
 #[derive(Default)]
 pub struct SessionUsageTracker {
     input_tokens: AtomicU32,
     output_tokens: AtomicU32,
+    tools: Mutex<HashMap<&'static str, ToolCounters>>,
+    responses: Mutex<ResponseCounters>,
+    /// Tokens by source, sub-agents by agent type
+    sources: Mutex<HashMap<(UsageSource, Option<String>), UsageTotals>>,
 }
 
 impl SessionUsageTracker {
@@ -21,6 +49,71 @@ impl SessionUsageTracker {
         Self {
             input_tokens: AtomicU32::new(0),
             output_tokens: AtomicU32::new(0),
+            tools: Mutex::new(HashMap::new()),
+            responses: Mutex::new(ResponseCounters::default()),
+            sources: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record one tool invocation. `output_bytes` is the size of the output
+    /// or error message returned to the model.
+    pub fn record_tool_call(
+        &self,
+        tool: &'static str,
+        duration: Duration,
+        is_error: bool,
+        output_bytes: usize,
+    ) {
+        let mut tools = self.tools.lock().unwrap_or_else(|e| e.into_inner());
+        let counters = tools.entry(tool).or_default();
+        counters.calls += 1;
+        if is_error {
+            counters.errors += 1;
+        }
+        counters.duration_ms += duration.as_millis() as u64;
+        counters.output_bytes += output_bytes as u64;
+    }
+
+    /// Per-tool statistics, most called first.
+    pub fn get_tool_stats(&self) -> Vec<ToolStats> {
+        let tools = self.tools.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stats: Vec<ToolStats> = tools
+            .iter()
+            .map(|(&tool, c)| ToolStats {
+                tool: tool.to_string(),
+                calls: c.calls,
+                errors: c.errors,
+                error_rate: c.errors as f64 / c.calls as f64,
+                total_duration_ms: c.duration_ms,
+                avg_duration_ms: c.duration_ms / c.calls as u64,
+                total_output_bytes: c.output_bytes,
+                avg_output_bytes: c.output_bytes / c.calls as u64,
+            })
+            .collect();
+        stats.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.tool.cmp(&b.tool)));
+        stats
+    }
+
+    /// Record the timing of one streamed model response.
+    pub fn record_response(&self, timing: &IterationTiming) {
+        let mut counters = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        counters.responses += 1;
+        if let Some(first_token_ms) = timing.first_token_ms {
+            counters.first_token_ms += first_token_ms;
+            counters.first_tokens += 1;
+        }
+        counters.generating_ms += timing.generating_ms();
+        counters.output_tokens += u64::from(timing.output_tokens);
+    }
+
+    /// Average latency and throughput of the session's model responses.
+    pub fn get_latency_stats(&self) -> LatencyStats {
+        let counters = *self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        LatencyStats {
+            responses: counters.responses,
+            avg_first_token_ms: (counters.first_tokens > 0)
+                .then(|| counters.first_token_ms / u64::from(counters.first_tokens)),
+            tokens_per_sec: tokens_per_sec(counters.output_tokens, counters.generating_ms),
         }
     }
 
@@ -35,6 +128,53 @@ impl SessionUsageTracker {
         }
     }
 
+    /// Add tokens spent by `source` (for a sub-agent, of type `agent_type`)
+    /// and return the session's new cumulative totals.
+    pub fn add_source_tokens(
+        &self,
+        source: UsageSource,
+        agent_type: Option<&str>,
+        usage: TokenUsage,
+    ) -> UsageTotals {
+        {
+            let mut sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+            let totals = sources
+                .entry((source, agent_type.map(str::to_string)))
+                .or_insert(UsageTotals {
+                    input_tokens: 0,
+                    output_tokens: 0,
+                });
+            totals.input_tokens += usage.input_tokens;
+            totals.output_tokens += usage.output_tokens;
+        }
+        self.add_tokens(usage.input_tokens, usage.output_tokens)
+    }
+
+    /// Session totals with their breakdown by source, largest first.
+    pub fn get_session_usage(&self) -> SessionUsage {
+        let sources = self.sources.lock().unwrap_or_else(|e| e.into_inner());
+        let mut by_source: Vec<SourceUsage> = sources
+            .iter()
+            .map(|((source, agent_type), totals)| SourceUsage {
+                source: *source,
+                agent_type: agent_type.clone(),
+                input_tokens: totals.input_tokens,
+                output_tokens: totals.output_tokens,
+            })
+            .collect();
+        by_source.sort_by(|a, b| {
+            (b.input_tokens + b.output_tokens)
+                .cmp(&(a.input_tokens + a.output_tokens))
+                .then_with(|| a.agent_type.cmp(&b.agent_type))
+        });
+        let totals = self.get_totals();
+        SessionUsage {
+            input_tokens: totals.input_tokens,
+            output_tokens: totals.output_tokens,
+            by_source,
+        }
+    }
+
     /// Get current cumulative totals.
     pub fn get_totals(&self) -> UsageTotals {
         UsageTotals {
@@ -47,6 +187,12 @@ impl SessionUsageTracker {
     pub fn reset(&self) {
         self.input_tokens.store(0, Ordering::SeqCst);
         self.output_tokens.store(0, Ordering::SeqCst);
+        self.tools.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        *self.responses.lock().unwrap_or_else(|e| e.into_inner()) = ResponseCounters::default();
+        self.sources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
     }
 }
 
@@ -58,6 +204,133 @@ pub struct UsageTotals {
     pub output_tokens: u32,
 }
 
+/// Session token usage with its breakdown by source.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SessionUsage {
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub by_source: Vec<SourceUsage>,
+}
+
+/// Tokens spent by one source of the session.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SourceUsage {
+    pub source: UsageSource,
+    /// Sub-agent type, for sub-agent usage
+    pub agent_type: Option<String>,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+/// Invocation statistics for one tool in the current session.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ToolStats {
+    pub tool: String,
+    pub calls: u32,
+    pub errors: u32,
+    /// Fraction of calls that returned an error, 0.0 to 1.0
+    pub error_rate: f64,
+    #[ts(type = "number")]
+    pub total_duration_ms: u64,
+    #[ts(type = "number")]
+    pub avg_duration_ms: u64,
+    /// Bytes of output returned to the model
+    #[ts(type = "number")]
+    pub total_output_bytes: u64,
+    #[ts(type = "number")]
+    pub avg_output_bytes: u64,
+}
+
+/// Output tokens per second over `generating_ms`, None when there is nothing to measure.
+fn tokens_per_sec(output_tokens: u64, generating_ms: u64) -> Option<f64> {
+    (output_tokens > 0 && generating_ms > 0)
+        .then(|| output_tokens as f64 * 1000.0 / generating_ms as f64)
+}
+
+/// Timing of one model response and the tools it called.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct IterationTiming {
+    /// Milliseconds from sending the request to the first content (None if the response had none)
+    #[ts(type = "number | null")]
+    pub first_token_ms: Option<u64>,
+    /// Milliseconds the response took, including any reconnects
+    #[ts(type = "number")]
+    pub model_ms: u64,
+    /// Milliseconds spent running the response's tool calls
+    #[ts(type = "number")]
+    pub tool_ms: u64,
+    pub output_tokens: u32,
+    /// Output tokens per second once content started arriving
+    pub tokens_per_sec: Option<f64>,
+}
+
+impl IterationTiming {
+    pub fn new(first_token: Option<Duration>, model: Duration, output_tokens: u32) -> Self {
+        let mut timing = Self {
+            first_token_ms: first_token.map(|d| d.as_millis() as u64),
+            model_ms: model.as_millis() as u64,
+            tool_ms: 0,
+            output_tokens,
+            tokens_per_sec: None,
+        };
+        timing.tokens_per_sec = tokens_per_sec(u64::from(output_tokens), timing.generating_ms());
+        timing
+    }
+
+    /// Milliseconds between the first content and the end of the response.
+    fn generating_ms(&self) -> u64 {
+        self.first_token_ms
+            .map_or(0, |first| self.model_ms.saturating_sub(first))
+    }
+}
+
+/// Where the time of an agent run went: waiting for the model, generating, or running tools.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct RunTimings {
+    #[ts(type = "number")]
+    pub total_ms: u64,
+    /// Time to first content of the run's first response
+    #[ts(type = "number | null")]
+    pub first_token_ms: Option<u64>,
+    #[ts(type = "number")]
+    pub model_ms: u64,
+    #[ts(type = "number")]
+    pub tool_ms: u64,
+    /// Output tokens per second across all responses
+    pub tokens_per_sec: Option<f64>,
+    pub iterations: Vec<IterationTiming>,
+}
+
+impl RunTimings {
+    pub fn new(total: Duration, iterations: Vec<IterationTiming>) -> Self {
+        let output_tokens = iterations.iter().map(|i| u64::from(i.output_tokens)).sum();
+        let generating_ms = iterations.iter().map(IterationTiming::generating_ms).sum();
+        Self {
+            total_ms: total.as_millis() as u64,
+            first_token_ms: iterations.first().and_then(|i| i.first_token_ms),
+            model_ms: iterations.iter().map(|i| i.model_ms).sum(),
+            tool_ms: iterations.iter().map(|i| i.tool_ms).sum(),
+            tokens_per_sec: tokens_per_sec(output_tokens, generating_ms),
+            iterations,
+        }
+    }
+}
+
+/// Average model latency and throughput for the session.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct LatencyStats {
+    pub responses: u32,
+    #[ts(type = "number | null")]
+    pub avg_first_token_ms: Option<u64>,
+    pub tokens_per_sec: Option<f64>,
+}
+
 /// Payload for agent-usage events.
 #[derive(Debug, Clone, Serialize, TS)]
 #[ts(export)]
@@ -68,12 +341,14 @@ pub struct AgentUsagePayload {
 }
 
 /// Source of the token usage.
-#[derive(Debug, Clone, Copy, Serialize, TS)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, TS)]
 #[ts(export)]
 #[serde(rename_all = "snake_case")]
 pub enum UsageSource {
     Main,
     SubAgent,
+    /// Fact extraction when compacting the history
+    Compaction,
 }
 
 #[cfg(test)]
@@ -149,6 +424,67 @@ mod tests {
         assert_eq!(totals.output_tokens, 500u32);
     }
 
+    #[test]
+    fn test_tool_stats() {
+        let tracker = SessionUsageTracker::new();
+        for _ in 0..3 {
+            tracker.record_tool_call("grep", Duration::from_millis(20), false, 100);
+        }
+        tracker.record_tool_call("bash", Duration::from_millis(500), true, 40);
+        tracker.record_tool_call("bash", Duration::from_millis(300), false, 60);
+
+        let stats = tracker.get_tool_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].tool, "grep");
+        assert_eq!(stats[0].calls, 3);
+        assert_eq!(stats[0].total_output_bytes, 300);
+        assert_eq!(stats[0].error_rate, 0.0);
+
+        assert_eq!(stats[1].tool, "bash");
+        assert_eq!(stats[1].errors, 1);
+        assert_eq!(stats[1].error_rate, 0.5);
+        assert_eq!(stats[1].avg_duration_ms, 400);
+        assert_eq!(stats[1].avg_output_bytes, 50);
+
+        tracker.reset();
+        assert!(tracker.get_tool_stats().is_empty());
+    }
+
+    #[test]
+    fn test_session_usage_by_source() {
+        let tracker = SessionUsageTracker::new();
+        let usage = |input_tokens, output_tokens| TokenUsage {
+            input_tokens,
+            output_tokens,
+        };
+        tracker.add_source_tokens(UsageSource::Main, None, usage(1000, 200));
+        tracker.add_source_tokens(UsageSource::SubAgent, Some("explore"), usage(300, 50));
+        tracker.add_source_tokens(UsageSource::SubAgent, Some("explore"), usage(300, 50));
+        tracker.add_source_tokens(UsageSource::SubAgent, Some("plan"), usage(100, 20));
+        let totals = tracker.add_source_tokens(UsageSource::Compaction, None, usage(400, 100));
+        assert_eq!(totals.input_tokens, 2100);
+
+        let session = tracker.get_session_usage();
+        assert_eq!(session.output_tokens, 420);
+        let sources: Vec<_> = session
+            .by_source
+            .iter()
+            .map(|s| (s.source, s.agent_type.as_deref(), s.input_tokens))
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                (UsageSource::Main, None, 1000),
+                (UsageSource::SubAgent, Some("explore"), 600),
+                (UsageSource::Compaction, None, 400),
+                (UsageSource::SubAgent, Some("plan"), 100),
+            ]
+        );
+
+        tracker.reset();
+        assert!(tracker.get_session_usage().by_source.is_empty());
+    }
+
     #[test]
     fn test_default_impl() {
         let tracker = SessionUsageTracker::default();
@@ -156,4 +492,64 @@ mod tests {
         assert_eq!(totals.input_tokens, 0);
         assert_eq!(totals.output_tokens, 0);
     }
+
+    #[test]
+    fn test_iteration_throughput_excludes_time_to_first_token() {
+        let timing = IterationTiming::new(
+            Some(Duration::from_millis(500)),
+            Duration::from_millis(2500),
+            100,
+        );
+        assert_eq!(timing.first_token_ms, Some(500));
+        assert_eq!(timing.tokens_per_sec, Some(50.0));
+
+        let empty = IterationTiming::new(None, Duration::from_millis(300), 0);
+        assert_eq!(empty.tokens_per_sec, None);
+    }
+
+    #[test]
+    fn test_run_timings_totals() {
+        let mut first = IterationTiming::new(
+            Some(Duration::from_millis(200)),
+            Duration::from_millis(1200),
+            50,
+        );
+        first.tool_ms = 300;
+        let second = IterationTiming::new(
+            Some(Duration::from_millis(400)),
+            Duration::from_millis(1400),
+            150,
+        );
+
+        let timings = RunTimings::new(Duration::from_millis(3000), vec![first, second]);
+        assert_eq!(timings.first_token_ms, Some(200));
+        assert_eq!(timings.model_ms, 2600);
+        assert_eq!(timings.tool_ms, 300);
+        assert_eq!(timings.tokens_per_sec, Some(100.0));
+    }
+
+    #[test]
+    fn test_latency_stats_average_responses() {
+        let tracker = SessionUsageTracker::new();
+        assert_eq!(tracker.get_latency_stats().avg_first_token_ms, None);
+
+        tracker.record_response(&IterationTiming::new(
+            Some(Duration::from_millis(100)),
+            Duration::from_millis(1100),
+            40,
+        ));
+        tracker.record_response(&IterationTiming::new(
+            Some(Duration::from_millis(300)),
+            Duration::from_millis(1300),
+            60,
+        ));
+
+        let stats = tracker.get_latency_stats();
+        assert_eq!(stats.responses, 2);
+        assert_eq!(stats.avg_first_token_ms, Some(200));
+        assert_eq!(stats.tokens_per_sec, Some(50.0));
+
+        tracker.reset();
+        assert_eq!(tracker.get_latency_stats().responses, 0);
+    }
 }
@@ -0,0 +1,364 @@
+//! Persisted per-response token usage, appended as JSONL in the app data directory.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::config::ConfigService;
+
+use super::models;
+use super::usage::TokenUsage;
+
+const HISTORY_FILENAME: &str = "usage-history.jsonl";
+
+/// USD per million tokens (input, output), matched by model id prefix.
+/// A single API response's token usage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// RFC 3339 UTC timestamp
+    pub timestamp: String,
+    pub project: String,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+}
+
+/// Inclusive UTC date range (YYYY-MM-DD). Missing bounds are open.
+#[derive(Debug, Clone, Default, Deserialize, TS)]
+#[ts(export)]
+pub struct UsageExportRange {
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+/// Aggregated usage for one day, project and model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyUsage {
+    pub date: NaiveDate,
+    pub project: String,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl DailyUsage {
+    /// Estimated cost in USD, None for models without known pricing.
+    pub fn cost_usd(&self) -> Option<f64> {
+        estimate_cost_usd(&self.model, self.input_tokens, self.output_tokens)
+    }
+}
+
+pub struct UsageHistory {
+    path: PathBuf,
+}
+
+impl UsageHistory {
+    pub fn new() -> io::Result<Self> {
+        let config = ConfigService::new().map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(Self::with_dir(config.app_data_dir()))
+    }
+
+    pub fn with_dir(dir: &Path) -> Self {
+        Self {
+            path: dir.join(HISTORY_FILENAME),
+        }
+    }
+
+    pub fn append(&self, record: &UsageRecord) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    /// Loads all records, skipping lines that fail to parse.
+    pub fn load(&self) -> io::Result<Vec<UsageRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)?;
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+/// Best-effort append; usage accounting must never fail a run.
+pub fn record_usage(project_path: &Path, provider: &str, model: &str, usage: TokenUsage) {
+    if usage.input_tokens == 0 && usage.output_tokens == 0 {
+        return;
+    }
+
+    let record = UsageRecord {
+        timestamp: Utc::now().to_rfc3339(),
+        project: project_path.to_string_lossy().to_string(),
+        provider: provider.to_string(),
+        model: model.to_string(),
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+    };
+
+    if let Err(e) = UsageHistory::new().and_then(|history| history.append(&record)) {
+        log::warn!("Failed to record usage history: {}", e);
+    }
+}
+
+/// Estimated cost in USD of the given token counts, None for models without
+/// known pricing.
+pub fn estimate_cost_usd(model: &str, input_tokens: u64, output_tokens: u64) -> Option<f64> {
+    let (input_price, output_price) = models::pricing(model)?;
+    Some((input_tokens as f64 * input_price + output_tokens as f64 * output_price) / 1_000_000.0)
+}
+
+fn parse_date(value: Option<&str>) -> Result<Option<NaiveDate>, String> {
+    value
+        .filter(|v| !v.trim().is_empty())
+        .map(|v| {
+            NaiveDate::parse_from_str(v.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", v))
+        })
+        .transpose()
+}
+
+/// (date, project, provider, model)
+type DailyKey = (NaiveDate, String, String, String);
+
+/// Groups records by day, project and model within the range, sorted by date.
+pub fn aggregate_daily(
+    records: &[UsageRecord],
+    range: &UsageExportRange,
+) -> Result<Vec<DailyUsage>, String> {
+    let start = parse_date(range.start.as_deref())?;
+    let end = parse_date(range.end.as_deref())?;
+
+    let mut groups: BTreeMap<DailyKey, (u64, u64)> = BTreeMap::new();
+
+    for record in records {
+        let Ok(timestamp) = DateTime::parse_from_rfc3339(&record.timestamp) else {
+            continue;
+        };
+        let date = timestamp.with_timezone(&Utc).date_naive();
+
+        if start.is_some_and(|s| date < s) || end.is_some_and(|e| date > e) {
+            continue;
+        }
+
+        let entry = groups
+            .entry((
+                date,
+                record.project.clone(),
+                record.provider.clone(),
+                record.model.clone(),
+            ))
+            .or_default();
+        entry.0 += record.input_tokens as u64;
+        entry.1 += record.output_tokens as u64;
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(
+            |((date, project, provider, model), (input_tokens, output_tokens))| DailyUsage {
+                date,
+                project,
+                provider,
+                model,
+                input_tokens,
+                output_tokens,
+            },
+        )
+        .collect())
+}
+
+pub fn format_csv(rows: &[DailyUsage]) -> String {
+    let mut output = String::from(
+        "date,project,provider,model,input_tokens,output_tokens,total_tokens,cost_usd\n",
+    );
+
+    for row in rows {
+        let cost = row
+            .cost_usd()
+            .map(|c| format!("{:.4}", c))
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            row.date.format("%Y-%m-%d"),
+            escape_csv(&row.project),
+            escape_csv(&row.provider),
+            escape_csv(&row.model),
+            row.input_tokens,
+            row.output_tokens,
+            row.input_tokens + row.output_tokens,
+            cost
+        ));
+    }
+
+    output
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: &str, project: &str, model: &str, input: u32, output: u32) -> UsageRecord {
+        UsageRecord {
+            timestamp: timestamp.to_string(),
+            project: project.to_string(),
+            provider: "anthropic".to_string(),
+            model: model.to_string(),
+            input_tokens: input,
+            output_tokens: output,
+        }
+    }
+
+    #[test]
+    fn test_append_and_load_roundtrip() {
+        let temp = tempfile::tempdir().unwrap();
+        let history = UsageHistory::with_dir(temp.path());
+
+        assert!(history.load().unwrap().is_empty());
+
+        history
+            .append(&record("2025-01-01T10:00:00+00:00", "/p", "m", 1, 2))
+            .unwrap();
+        history
+            .append(&record("2025-01-02T10:00:00+00:00", "/p", "m", 3, 4))
+            .unwrap();
+
+        let loaded = history.load().unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[1].input_tokens, 3);
+    }
+
+    #[test]
+    fn test_aggregate_groups_by_day_project_and_model() {
+        let records = vec![
+            record(
+                "2025-01-01T08:00:00+00:00",
+                "/a",
+                "claude-sonnet-4-20250514",
+                100,
+                10,
+            ),
+            record(
+                "2025-01-01T20:00:00+00:00",
+                "/a",
+                "claude-sonnet-4-20250514",
+                200,
+                20,
+            ),
+            record(
+                "2025-01-01T09:00:00+00:00",
+                "/b",
+                "claude-sonnet-4-20250514",
+                50,
+                5,
+            ),
+            record(
+                "2025-01-02T09:00:00+00:00",
+                "/a",
+                "claude-sonnet-4-20250514",
+                1,
+                1,
+            ),
+        ];
+
+        let rows = aggregate_daily(&records, &UsageExportRange::default()).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].project, "/a");
+        assert_eq!(rows[0].input_tokens, 300);
+        assert_eq!(rows[0].output_tokens, 30);
+        assert_eq!(rows[2].date, NaiveDate::from_ymd_opt(2025, 1, 2).unwrap());
+    }
+
+    #[test]
+    fn test_aggregate_respects_inclusive_range() {
+        let records = vec![
+            record("2025-01-01T08:00:00+00:00", "/a", "m", 1, 1),
+            record("2025-01-02T08:00:00+00:00", "/a", "m", 2, 2),
+            record("2025-01-03T08:00:00+00:00", "/a", "m", 3, 3),
+        ];
+        let range = UsageExportRange {
+            start: Some("2025-01-02".to_string()),
+            end: Some("2025-01-03".to_string()),
+        };
+
+        let rows = aggregate_daily(&records, &range).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].input_tokens, 2);
+    }
+
+    #[test]
+    fn test_aggregate_rejects_invalid_date() {
+        let range = UsageExportRange {
+            start: Some("01/02/2025".to_string()),
+            end: None,
+        };
+        assert!(aggregate_daily(&[], &range).is_err());
+    }
+
+    #[test]
+    fn test_cost_uses_model_pricing() {
+        let row = DailyUsage {
+            date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            project: "/a".to_string(),
+            provider: "anthropic".to_string(),
+            model: "claude-sonnet-4-20250514".to_string(),
+            input_tokens: 1_000_000,
+            output_tokens: 1_000_000,
+        };
+        assert_eq!(row.cost_usd(), Some(18.0));
+
+        let unknown = DailyUsage {
+            model: "custom-model".to_string(),
+            ..row
+        };
+        assert_eq!(unknown.cost_usd(), None);
+    }
+
+    #[test]
+    fn test_format_csv_escapes_fields() {
+        let rows = vec![DailyUsage {
+            date: NaiveDate::from_ymd_opt(2025, 1, 1).unwrap(),
+            project: "/work/a,b".to_string(),
+            provider: "gemini".to_string(),
+            model: "custom-model".to_string(),
+            input_tokens: 10,
+            output_tokens: 5,
+        }];
+
+        let csv = format_csv(&rows);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines[0].starts_with("date,project,provider,model"));
+        assert_eq!(
+            lines[1],
+            "2025-01-01,\"/work/a,b\",gemini,custom-model,10,5,15,"
+        );
+    }
+}
@@ -0,0 +1,91 @@
+//! Headless command-line entry point: `devflow-cli run "fix the failing tests"`.
+
+use std::process::ExitCode;
+
+use devflow_lib::cli::{self, CliCommand, EvalArgs, ExitStatus, USAGE};
+use devflow_lib::evals::compare::format_comparison;
+use devflow_lib::evals::format_summary;
+use tokio_util::sync::CancellationToken;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(CliCommand::Run(args)) => args,
+        Ok(CliCommand::Evals(args)) => return run_evals(args).await,
+        Ok(CliCommand::Help) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, USAGE);
+            return ExitCode::from(ExitStatus::UsageError.code());
+        }
+    };
+
+    let cancel_token = CancellationToken::new();
+    let on_interrupt = cancel_token.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            on_interrupt.cancel();
+        }
+    });
+
+    let json = args.json;
+    let report = cli::run(args, cancel_token).await;
+
+    if json {
+        match serde_json::to_string(&report) {
+            Ok(line) => println!("{}", line),
+            Err(e) => eprintln!("error: failed to serialize report: {}", e),
+        }
+    } else if let Some(error) = &report.error {
+        eprintln!("error: {}", error);
+    }
+
+    ExitCode::from(report.exit_code)
+}
+
+async fn run_evals(mut args: EvalArgs) -> ExitCode {
+    let cases = match cli::select_eval_cases(args.category.as_deref(), &args.cases) {
+        Ok(cases) => cli::sample_eval_cases(&mut args, cases),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            return ExitCode::from(ExitStatus::UsageError.code());
+        }
+    };
+
+    let out_dir = args.out_dir.clone();
+    if !args.models.is_empty() {
+        return match cli::run_eval_comparison(args, cases).await {
+            Ok(runs) => {
+                println!("{}", format_comparison(&runs));
+                eprintln!("Reports written to {}", out_dir.display());
+                if runs.iter().all(|run| run.summary.failed == 0) {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::from(ExitStatus::TaskFailed.code())
+                }
+            }
+            Err(e) => {
+                eprintln!("error: {}", e);
+                ExitCode::from(ExitStatus::TaskFailed.code())
+            }
+        };
+    }
+
+    match cli::run_evals(args, cases).await {
+        Ok(summary) => {
+            println!("{}", format_summary(&summary));
+            eprintln!("Reports written to {}", out_dir.display());
+            if summary.failed == 0 {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(ExitStatus::TaskFailed.code())
+            }
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::from(ExitStatus::TaskFailed.code())
+        }
+    }
+}
@@ -0,0 +1,1050 @@
+//! Terminal front end for the agent core, used by the `devflow-cli` binary.
+//!
+//! Runs the same headless tool loop as sub-agents, with the tools and executor
+//! limits from the project's config, and prints each response and tool call as
+//! it happens.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::agent::error::AgentError;
+use crate::agent::provider::HeadlessResult;
+use crate::agent::providers::{
+    build_system_prompt, offered_tools, run_headless_loop, AnthropicAdapter, GeminiAdapter,
+    HeadlessContext, HeadlessResponse, HeadlessStreamer, HeadlessToolResult, ProviderRuntime,
+    DEFAULT_SYSTEM_PROMPT,
+};
+use crate::agent::rng::resolve_seed;
+use crate::agent::tools::{
+    get_tool_definitions, without_disabled, LocalExecutor, SessionState, ToolExecutor, ToolName,
+};
+use crate::agent::types::{ChatMessage, MessageRole, ToolDefinition};
+use crate::agent::usage_history::estimate_cost_usd;
+use crate::agent::{tools_needing_approval, MemoryState, SessionUsageTracker, UsageTotals};
+use crate::config::{ConfigService, ProjectConfig};
+use crate::evals::cases;
+use crate::evals::compare::{format_comparison, ModelRun, ModelTarget};
+use crate::evals::history::{diff_runs, format_regressions, EvalHistory, EvalRunRecord};
+use crate::evals::real_eval::run_real_suite;
+use crate::evals::report::{format_junit, format_markdown};
+use crate::evals::{EvalCase, EvalSummary};
+
+pub const USAGE: &str = "\
+Usage: devflow-cli run [options] <prompt>
+       devflow-cli evals [options]
+
+Runs the agent on <prompt> in the project directory using its
+.devflow/config.toml. Responses go to stdout, tool activity and usage to
+stderr.
+
+Options:
+  --project <dir>        Project directory (default: current directory)
+  --max-iterations <n>   Override execution.max_tool_iterations
+  --max-cost <usd>       Stop before the next request once the estimated cost
+                         exceeds this amount
+  --json                 Print a single JSON report instead of the responses
+
+Exit status: 0 success, 1 task failed, 2 usage error, 3 budget exceeded,
+4 cancelled.
+
+`evals` runs the behavioral eval suite against the configured provider and
+writes eval-report.md and eval-junit.xml. Each run is appended to
+.devflow/eval-history.jsonl, and eval-regressions.md lists cases whose outcome
+changed since the previous run of the same model. Exits 1 if any case fails.
+
+Options:
+  --project <dir>        Project to run the cases in (default: current directory)
+  --case <id>            Run only this case (repeatable)
+  --category <name>      Run only cases in this category (review, search,
+                         complex, safety, write)
+  --parallel <n>         Cases to run at once (default: 1)
+  --out <dir>            Report directory (default: eval-results)
+  --model <p:model>      Run the suite against this provider and model instead
+                         of the configured one (repeatable). Each run's reports
+                         go in a subdirectory, plus eval-comparison.md
+  --sample <n>           Run n of the selected cases, picked by the seed
+  --seed <n>             Seed for sampling and reconnect jitter (default:
+                         DEVFLOW_SEED, else random); the report shows the one
+                         used so a run can be repeated";
+
+/// Longest tool input echoed to the terminal.
+const MAX_INPUT_PREVIEW: usize = 120;
+
+#[derive(Debug, PartialEq)]
+pub enum CliCommand {
+    Run(RunArgs),
+    Evals(EvalArgs),
+    Help,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct EvalArgs {
+    pub project: PathBuf,
+    pub cases: Vec<String>,
+    pub category: Option<String>,
+    pub parallel: usize,
+    pub out_dir: PathBuf,
+    /// Targets to compare; empty runs the configured provider once
+    pub models: Vec<ModelTarget>,
+    /// Number of the selected cases to run (None = all)
+    pub sample: Option<usize>,
+    /// Seed for sampling and reconnect jitter (None = `DEVFLOW_SEED` or random)
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunArgs {
+    pub prompt: String,
+    pub project: PathBuf,
+    /// Overrides `execution.max_tool_iterations`
+    pub max_iterations: Option<u32>,
+    /// Estimated spend in USD after which no further request is made
+    pub max_cost: Option<f64>,
+    pub json: bool,
+}
+
+/// Outcome of a CLI run, also used as the process exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitStatus {
+    Success,
+    TaskFailed,
+    UsageError,
+    BudgetExceeded,
+    Cancelled,
+}
+
+impl ExitStatus {
+    pub fn code(self) -> u8 {
+        match self {
+            ExitStatus::Success => 0,
+            ExitStatus::TaskFailed => 1,
+            ExitStatus::UsageError => 2,
+            ExitStatus::BudgetExceeded => 3,
+            ExitStatus::Cancelled => 4,
+        }
+    }
+
+    fn from_result(result: &Result<HeadlessResult, AgentError>) -> Self {
+        match result {
+            Ok(_) => ExitStatus::Success,
+            Err(AgentError::BudgetExceeded(_)) => ExitStatus::BudgetExceeded,
+            Err(AgentError::Cancelled) => ExitStatus::Cancelled,
+            Err(_) => ExitStatus::TaskFailed,
+        }
+    }
+}
+
+/// Machine-readable summary of a run, printed with `--json`.
+#[derive(Debug, Serialize)]
+pub struct RunReport {
+    pub status: ExitStatus,
+    pub exit_code: u8,
+    /// Final response text, None when the run failed
+    pub text: Option<String>,
+    pub error: Option<String>,
+    pub tool_calls: u32,
+    pub stop_reason: Option<String>,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// None for models without known pricing
+    pub cost_usd: Option<f64>,
+}
+
+impl RunReport {
+    fn new(
+        result: Result<HeadlessResult, AgentError>,
+        usage: UsageTotals,
+        model: Option<&str>,
+    ) -> Self {
+        let status = ExitStatus::from_result(&result);
+        let cost_usd = model.and_then(|m| {
+            estimate_cost_usd(m, usage.input_tokens.into(), usage.output_tokens.into())
+        });
+        let (text, error, tool_calls, stop_reason) = match result {
+            Ok(r) => (Some(r.text), None, r.tool_calls_made, r.stop_reason),
+            Err(e) => (None, Some(e.to_string()), 0, None),
+        };
+
+        Self {
+            status,
+            exit_code: status.code(),
+            text,
+            error,
+            tool_calls,
+            stop_reason,
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cost_usd,
+        }
+    }
+}
+
+/// Parses the arguments after the program name.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<CliCommand, String> {
+    let mut args = args.into_iter();
+
+    match args.next().as_deref() {
+        Some("run") => parse_run_args(args),
+        Some("evals") => parse_eval_args(args),
+        Some("help" | "-h" | "--help") | None => Ok(CliCommand::Help),
+        Some(other) => Err(format!("unknown command '{}'", other)),
+    }
+}
+
+fn parse_run_args(mut args: impl Iterator<Item = String>) -> Result<CliCommand, String> {
+    let mut project = None;
+    let mut max_iterations = None;
+    let mut max_cost = None;
+    let mut json = false;
+    let mut prompt: Vec<String> = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "--project" | "-C" => {
+                let dir = args.next().ok_or("--project needs a directory")?;
+                project = Some(PathBuf::from(dir));
+            }
+            "--max-iterations" => {
+                let value = args.next().ok_or("--max-iterations needs a number")?;
+                let n = value
+                    .parse()
+                    .map_err(|_| format!("invalid --max-iterations '{}'", value))?;
+                max_iterations = Some(n);
+            }
+            "--max-cost" => {
+                let value = args.next().ok_or("--max-cost needs an amount in USD")?;
+                let amount: f64 = value
+                    .parse()
+                    .ok()
+                    .filter(|v: &f64| v.is_finite() && *v > 0.0)
+                    .ok_or_else(|| format!("invalid --max-cost '{}'", value))?;
+                max_cost = Some(amount);
+            }
+            "--json" => json = true,
+            "--" => prompt.extend(args.by_ref()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option '{}'", flag)),
+            _ => prompt.push(arg),
+        }
+    }
+
+    let prompt = prompt.join(" ");
+    if prompt.trim().is_empty() {
+        return Err("missing prompt".to_string());
+    }
+
+    Ok(CliCommand::Run(RunArgs {
+        prompt,
+        project: project.unwrap_or_else(|| PathBuf::from(".")),
+        max_iterations,
+        max_cost,
+        json,
+    }))
+}
+
+fn parse_eval_args(mut args: impl Iterator<Item = String>) -> Result<CliCommand, String> {
+    let mut eval_args = EvalArgs {
+        project: PathBuf::from("."),
+        cases: Vec::new(),
+        category: None,
+        parallel: 1,
+        out_dir: PathBuf::from("eval-results"),
+        models: Vec::new(),
+        sample: None,
+        seed: None,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} needs a value", name));
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "--project" | "-C" => eval_args.project = PathBuf::from(value("--project")?),
+            "--case" => eval_args.cases.push(value("--case")?),
+            "--category" => {
+                let category = value("--category")?;
+                if !cases::CATEGORIES.contains(&category.as_str()) {
+                    return Err(format!("unknown category '{}'", category));
+                }
+                eval_args.category = Some(category);
+            }
+            "--parallel" => {
+                let n = value("--parallel")?;
+                eval_args.parallel = n
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid --parallel '{}'", n))?;
+            }
+            "--out" => eval_args.out_dir = PathBuf::from(value("--out")?),
+            "--model" => {
+                let target = ModelTarget::parse(&value("--model")?)?;
+                if !eval_args.models.contains(&target) {
+                    eval_args.models.push(target);
+                }
+            }
+            "--sample" => {
+                let n = value("--sample")?;
+                eval_args.sample = Some(
+                    n.parse()
+                        .ok()
+                        .filter(|&n| n > 0)
+                        .ok_or_else(|| format!("invalid --sample '{}'", n))?,
+                );
+            }
+            "--seed" => {
+                let seed = value("--seed")?;
+                eval_args.seed = Some(
+                    seed.parse()
+                        .map_err(|_| format!("invalid --seed '{}'", seed))?,
+                );
+            }
+            other => return Err(format!("unknown option '{}'", other)),
+        }
+    }
+
+    Ok(CliCommand::Evals(eval_args))
+}
+
+/// Runs the agent to completion, printing as it goes. Failures, including
+/// config errors, are reported in the returned report rather than as `Err`.
+pub async fn run(args: RunArgs, cancel_token: CancellationToken) -> RunReport {
+    let usage_tracker = Arc::new(SessionUsageTracker::new());
+
+    let (result, model) = match load_project(&args.project) {
+        Ok((project_path, config)) => {
+            let model = config.agent.model.clone();
+            let result = run_agent(
+                args,
+                project_path,
+                config,
+                &cancel_token,
+                Arc::clone(&usage_tracker),
+            )
+            .await;
+            (result, Some(model))
+        }
+        Err(e) => (Err(e), None),
+    };
+
+    let report = RunReport::new(result, usage_tracker.get_totals(), model.as_deref());
+    match report.cost_usd {
+        Some(cost) => eprintln!(
+            "[usage] {} input / {} output tokens (~${:.4})",
+            report.input_tokens, report.output_tokens, cost
+        ),
+        None => eprintln!(
+            "[usage] {} input / {} output tokens",
+            report.input_tokens, report.output_tokens
+        ),
+    }
+    report
+}
+
+/// Resolves `args.seed` (so the reports can show it) and, with `--sample`,
+/// picks that many of `cases` in the seed's order.
+pub fn sample_eval_cases(args: &mut EvalArgs, cases: Vec<EvalCase>) -> Vec<EvalCase> {
+    let seed = resolve_seed(args.seed);
+    args.seed = Some(seed);
+    match args.sample {
+        Some(count) => cases::sample_cases(cases, count, seed),
+        None => cases,
+    }
+}
+
+/// Selects cases by category and id, erroring on ids that do not exist.
+pub fn select_eval_cases(category: Option<&str>, ids: &[String]) -> Result<Vec<EvalCase>, String> {
+    let candidates = match category {
+        Some(category) => cases::get_cases_by_category(category),
+        None => cases::get_all_cases(),
+    };
+    if ids.is_empty() {
+        return Ok(candidates);
+    }
+
+    ids.iter()
+        .map(|id| {
+            candidates
+                .iter()
+                .find(|c| &c.id == id)
+                .cloned()
+                .ok_or_else(|| format!("unknown eval case '{}'", id))
+        })
+        .collect()
+}
+
+/// Runs the selected eval cases and writes the Markdown and JUnit reports.
+pub async fn run_evals(args: EvalArgs, cases: Vec<EvalCase>) -> std::io::Result<EvalSummary> {
+    let project_path = std::fs::canonicalize(&args.project)?;
+    let model = ConfigService::load_project_config(&project_path)
+        .map(|config| {
+            format!(
+                "{}:{}",
+                config.agent.provider.to_lowercase(),
+                config.agent.model
+            )
+        })
+        .unwrap_or_else(|_| "unconfigured".to_string());
+    let summary = run_real_suite(&project_path, cases, args.parallel, None, args.seed).await;
+    write_eval_reports(&args.out_dir, &summary)?;
+    record_eval_run(&project_path, &args.out_dir, &model, &summary)?;
+    Ok(summary)
+}
+
+/// Runs the selected cases once per `args.models` target, writing each run's
+/// reports to a subdirectory and the side-by-side table to eval-comparison.md.
+pub async fn run_eval_comparison(
+    args: EvalArgs,
+    cases: Vec<EvalCase>,
+) -> std::io::Result<Vec<ModelRun>> {
+    let project_path = std::fs::canonicalize(&args.project)?;
+    let mut runs = Vec::with_capacity(args.models.len());
+
+    for target in args.models {
+        eprintln!("[evals] {}", target.label());
+        let summary = run_real_suite(
+            &project_path,
+            cases.clone(),
+            args.parallel,
+            Some(&target),
+            args.seed,
+        )
+        .await;
+        let run_dir = args.out_dir.join(report_dir_name(&target));
+        write_eval_reports(&run_dir, &summary)?;
+        record_eval_run(&project_path, &run_dir, &target.label(), &summary)?;
+        runs.push(ModelRun { target, summary });
+    }
+
+    std::fs::create_dir_all(&args.out_dir)?;
+    std::fs::write(
+        args.out_dir.join("eval-comparison.md"),
+        format_comparison(&runs),
+    )?;
+    Ok(runs)
+}
+
+/// Appends the run to the project's eval history and, when the same model has
+/// run before, writes eval-regressions.md comparing against that run.
+fn record_eval_run(
+    project_path: &Path,
+    out_dir: &Path,
+    model: &str,
+    summary: &EvalSummary,
+) -> std::io::Result<()> {
+    let history = EvalHistory::for_project(project_path);
+    let record = EvalRunRecord::for_project(summary, model, project_path);
+
+    if let Some(previous) = history.latest(model)? {
+        let diff = diff_runs(&previous, &record);
+        if diff.has_regressions() {
+            eprintln!(
+                "[evals] {} newly failing since {}: {}",
+                model,
+                previous.timestamp,
+                diff.regressions.join(", ")
+            );
+        }
+        std::fs::create_dir_all(out_dir)?;
+        std::fs::write(
+            out_dir.join("eval-regressions.md"),
+            format_regressions(&previous, &record),
+        )?;
+    }
+
+    history.append(&record)
+}
+
+fn write_eval_reports(out_dir: &Path, summary: &EvalSummary) -> std::io::Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    std::fs::write(out_dir.join("eval-report.md"), format_markdown(summary))?;
+    std::fs::write(out_dir.join("eval-junit.xml"), format_junit(summary))
+}
+
+/// Directory name for a target's reports, e.g. `gemini-gemini-2.0-flash`.
+fn report_dir_name(target: &ModelTarget) -> String {
+    target
+        .label()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn load_project(project: &Path) -> Result<(PathBuf, ProjectConfig), AgentError> {
+    let project_path = std::fs::canonicalize(project)?;
+    let config = ConfigService::load_project_config(&project_path)
+        .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+    Ok((project_path, config))
+}
+
+async fn run_agent(
+    args: RunArgs,
+    project_path: PathBuf,
+    config: ProjectConfig,
+    cancel_token: &CancellationToken,
+    usage_tracker: Arc<SessionUsageTracker>,
+) -> Result<HeadlessResult, AgentError> {
+    let budget = match args.max_cost {
+        Some(max_cost_usd) => {
+            if estimate_cost_usd(&config.agent.model, 0, 0).is_none() {
+                return Err(AgentError::ConfigError(format!(
+                    "No known pricing for model '{}', --max-cost cannot be enforced",
+                    config.agent.model
+                )));
+            }
+            Some(Budget {
+                model: config.agent.model.clone(),
+                max_cost_usd,
+                tracker: Arc::clone(&usage_tracker),
+            })
+        }
+        None => None,
+    };
+
+    let (memory, _) = MemoryState::load(&project_path);
+    let system_prompt = build_system_prompt(
+        DEFAULT_SYSTEM_PROMPT,
+        &config.prompts,
+        None,
+        memory.format_for_injection().as_deref(),
+        &HashMap::new(),
+    )?;
+
+    let run = ConsoleRun {
+        system_prompt,
+        max_iterations: args
+            .max_iterations
+            .unwrap_or(config.execution.max_tool_iterations),
+        cancel_token,
+        usage_tracker,
+    };
+    let messages = vec![ChatMessage::new(MessageRole::User, args.prompt)];
+    let output = ConsoleOutput {
+        print_text: !args.json,
+        budget,
+    };
+
+    run_with_provider(config, project_path, messages, run, output).await
+}
+
+/// Per-run settings that do not come from the provider.
+struct ConsoleRun<'a> {
+    system_prompt: String,
+    max_iterations: u32,
+    cancel_token: &'a CancellationToken,
+    usage_tracker: Arc<SessionUsageTracker>,
+}
+
+async fn run_with_provider(
+    config: ProjectConfig,
+    project_path: PathBuf,
+    messages: Vec<ChatMessage>,
+    run: ConsoleRun<'_>,
+    output: ConsoleOutput,
+) -> Result<HeadlessResult, AgentError> {
+    let provider = config.agent.provider.to_lowercase();
+    match provider.as_str() {
+        "anthropic" => {
+            let adapter = AnthropicAdapter::new(config, project_path, DEFAULT_SYSTEM_PROMPT)?;
+            run_console(&adapter, messages, run, output).await
+        }
+        "gemini" => {
+            let adapter = GeminiAdapter::new(config, project_path, DEFAULT_SYSTEM_PROMPT)?;
+            run_console(&adapter, messages, run, output).await
+        }
+        _ => Err(AgentError::UnsupportedProvider(provider)),
+    }
+}
+
+async fn run_console<P>(
+    provider: &P,
+    messages: Vec<ChatMessage>,
+    run: ConsoleRun<'_>,
+    output: ConsoleOutput,
+) -> Result<HeadlessResult, AgentError>
+where
+    P: ProviderRuntime,
+    P::Conversation: Sync,
+{
+    let (tools, executor) =
+        console_tools(provider, run.cancel_token, Arc::clone(&run.usage_tracker))?;
+    let executor = ConsoleExecutor { inner: executor };
+    let streamer = ConsoleStreamer {
+        inner: provider,
+        output,
+    };
+    let ctx = HeadlessContext {
+        system_prompt: Some(run.system_prompt),
+        tools,
+        response_schema: None,
+        executor: &executor,
+        max_iterations: run.max_iterations,
+        cancel_token: run.cancel_token,
+        usage_tracker: run.usage_tracker,
+        agent_type: None,
+        transcript: None,
+        seed: None,
+    };
+    run_headless_loop(&streamer, messages, ctx).await
+}
+
+/// Tools offered to the model and the executor that runs them, set up from
+/// the project config like a sub-agent run. Nobody can answer an approval
+/// request from the terminal, so tools that need one are left out along with
+/// `execution.disabled_tools`.
+fn console_tools<P: ProviderRuntime>(
+    provider: &P,
+    cancel_token: &CancellationToken,
+    usage_tracker: Arc<SessionUsageTracker>,
+) -> Result<(Vec<ToolDefinition>, LocalExecutor), AgentError> {
+    let settings = provider.settings();
+    let disabled: Vec<String> = settings
+        .execution
+        .disabled_tools
+        .iter()
+        .cloned()
+        .chain(tools_needing_approval(settings.auto_approve))
+        .collect();
+    let executor = settings
+        .executor(SessionState::new(), cancel_token.clone(), usage_tracker)?
+        .with_disabled_tools(&disabled);
+    let tools = without_disabled(offered_tools(provider, get_tool_definitions()), &disabled);
+    Ok((tools, executor))
+}
+
+/// Spending cap checked before each request.
+struct Budget {
+    model: String,
+    max_cost_usd: f64,
+    tracker: Arc<SessionUsageTracker>,
+}
+
+impl Budget {
+    fn check(&self) -> Result<(), AgentError> {
+        let totals = self.tracker.get_totals();
+        let spent = estimate_cost_usd(
+            &self.model,
+            totals.input_tokens.into(),
+            totals.output_tokens.into(),
+        )
+        .unwrap_or_default();
+        if spent > self.max_cost_usd {
+            return Err(AgentError::BudgetExceeded(format!(
+                "spent ~${:.4} of ${:.4}",
+                spent, self.max_cost_usd
+            )));
+        }
+        Ok(())
+    }
+}
+
+struct ConsoleOutput {
+    print_text: bool,
+    budget: Option<Budget>,
+}
+
+/// Enforces the budget and prints each response's text to stdout once it has
+/// been received.
+struct ConsoleStreamer<'a, S> {
+    inner: &'a S,
+    output: ConsoleOutput,
+}
+
+#[async_trait]
+impl<S> HeadlessStreamer for ConsoleStreamer<'_, S>
+where
+    S: HeadlessStreamer,
+    S::Conversation: Sync,
+{
+    type Conversation = S::Conversation;
+
+    fn initial_conversation(&self, messages: Vec<ChatMessage>) -> Self::Conversation {
+        self.inner.initial_conversation(messages)
+    }
+
+    async fn stream_response(
+        &self,
+        conversation: &Self::Conversation,
+        system_prompt: Option<String>,
+        tools: &[ToolDefinition],
+        response_schema: Option<&serde_json::Value>,
+        cancel_token: &CancellationToken,
+    ) -> Result<HeadlessResponse, AgentError> {
+        if let Some(budget) = &self.output.budget {
+            budget.check()?;
+        }
+
+        let response = self
+            .inner
+            .stream_response(
+                conversation,
+                system_prompt,
+                tools,
+                response_schema,
+                cancel_token,
+            )
+            .await?;
+
+        if self.output.print_text && !response.text.trim().is_empty() {
+            let mut stdout = std::io::stdout().lock();
+            let _ = writeln!(stdout, "{}", response.text.trim_end());
+            let _ = stdout.flush();
+        }
+        Ok(response)
+    }
+
+    fn append_assistant_response(
+        &self,
+        conversation: &mut Self::Conversation,
+        response: &HeadlessResponse,
+    ) {
+        self.inner.append_assistant_response(conversation, response)
+    }
+
+    fn append_tool_results(
+        &self,
+        conversation: &mut Self::Conversation,
+        results: Vec<HeadlessToolResult>,
+    ) {
+        self.inner.append_tool_results(conversation, results)
+    }
+
+    fn append_user_text(&self, conversation: &mut Self::Conversation, text: String) {
+        self.inner.append_user_text(conversation, text)
+    }
+}
+
+/// Reports each tool call and its failures on stderr.
+struct ConsoleExecutor<E> {
+    inner: E,
+}
+
+#[async_trait]
+impl<E: ToolExecutor> ToolExecutor for ConsoleExecutor<E> {
+    async fn execute(
+        &self,
+        tool: ToolName,
+        input: serde_json::Value,
+    ) -> Result<String, AgentError> {
+        eprintln!("[tool] {} {}", tool.as_str(), preview_input(&input));
+        let result = self.inner.execute(tool, input).await;
+        if let Err(e) = &result {
+            eprintln!("[tool] {} failed: {}", tool.as_str(), e);
+        }
+        result
+    }
+}
+
+/// Single-line preview of a tool input, truncated on a char boundary.
+fn preview_input(input: &serde_json::Value) -> String {
+    let text = input.to_string();
+    match text.char_indices().nth(MAX_INPUT_PREVIEW) {
+        Some((cut, _)) => format!("{}...", &text[..cut]),
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_run() {
+        let parsed = parse_args(args(&["run", "fix", "the", "failing", "tests"])).unwrap();
+        assert_eq!(
+            parsed,
+            CliCommand::Run(RunArgs {
+                prompt: "fix the failing tests".to_string(),
+                project: PathBuf::from("."),
+                max_iterations: None,
+                max_cost: None,
+                json: false,
+            })
+        );
+
+        let parsed = parse_args(args(&[
+            "run",
+            "--project",
+            "/tmp/repo",
+            "--max-iterations",
+            "5",
+            "--max-cost",
+            "0.5",
+            "--json",
+            "--",
+            "--not-a-flag",
+        ]))
+        .unwrap();
+        assert_eq!(
+            parsed,
+            CliCommand::Run(RunArgs {
+                prompt: "--not-a-flag".to_string(),
+                project: PathBuf::from("/tmp/repo"),
+                max_iterations: Some(5),
+                max_cost: Some(0.5),
+                json: true,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_and_help() {
+        assert_eq!(parse_args(args(&[])).unwrap(), CliCommand::Help);
+        assert_eq!(
+            parse_args(args(&["run", "--help"])).unwrap(),
+            CliCommand::Help
+        );
+        assert!(parse_args(args(&["exec", "x"])).is_err());
+        assert!(parse_args(args(&["run"])).is_err());
+        assert!(parse_args(args(&["run", "--project"])).is_err());
+        assert!(parse_args(args(&["run", "--max-iterations", "many", "x"])).is_err());
+        assert!(parse_args(args(&["run", "--verbose", "x"])).is_err());
+        assert!(parse_args(args(&["run", "--max-cost", "-1", "x"])).is_err());
+        assert!(parse_args(args(&["run", "--max-cost", "NaN", "x"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_evals() {
+        let parsed = parse_args(args(&[
+            "evals",
+            "--case",
+            "search_before_read",
+            "--case",
+            "find_files_uses_glob",
+            "--category",
+            "search",
+            "--parallel",
+            "3",
+            "--out",
+            "reports",
+        ]))
+        .unwrap();
+        assert_eq!(
+            parsed,
+            CliCommand::Evals(EvalArgs {
+                project: PathBuf::from("."),
+                cases: vec![
+                    "search_before_read".to_string(),
+                    "find_files_uses_glob".to_string()
+                ],
+                category: Some("search".to_string()),
+                parallel: 3,
+                out_dir: PathBuf::from("reports"),
+                models: vec![],
+                sample: None,
+                seed: None,
+            })
+        );
+
+        let parsed = parse_args(args(&["evals", "--sample", "2", "--seed", "42"])).unwrap();
+        let CliCommand::Evals(mut eval_args) = parsed else {
+            panic!("expected evals command");
+        };
+        assert_eq!((eval_args.sample, eval_args.seed), (Some(2), Some(42)));
+        let sampled = sample_eval_cases(&mut eval_args, cases::get_all_cases());
+        let ids = |cases: Vec<EvalCase>| cases.into_iter().map(|c| c.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(sampled),
+            ids(cases::sample_cases(cases::get_all_cases(), 2, 42))
+        );
+        assert!(parse_args(args(&["evals", "--sample", "0"])).is_err());
+        assert!(parse_args(args(&["evals", "--seed", "-1"])).is_err());
+
+        let parsed = parse_args(args(&[
+            "evals",
+            "--model",
+            "anthropic:claude-sonnet-4-20250514",
+            "--model",
+            "gemini:gemini-2.0-flash",
+            "--model",
+            "gemini:gemini-2.0-flash",
+        ]))
+        .unwrap();
+        let CliCommand::Evals(eval_args) = parsed else {
+            panic!("expected evals command");
+        };
+        assert_eq!(eval_args.models.len(), 2);
+        assert_eq!(
+            report_dir_name(&eval_args.models[1]),
+            "gemini-gemini-2.0-flash"
+        );
+        assert!(parse_args(args(&["evals", "--model", "gpt-4o"])).is_err());
+
+        assert!(parse_args(args(&["evals", "--category", "speed"])).is_err());
+        assert!(parse_args(args(&["evals", "--parallel", "0"])).is_err());
+        assert!(parse_args(args(&["evals", "--case"])).is_err());
+    }
+
+    #[test]
+    fn test_select_eval_cases() {
+        let all = select_eval_cases(None, &[]).unwrap();
+        assert_eq!(all.len(), cases::get_all_cases().len());
+
+        let search = select_eval_cases(Some("search"), &[]).unwrap();
+        assert_eq!(search.len(), 2);
+
+        let picked = select_eval_cases(None, &["search_before_read".to_string()]).unwrap();
+        assert_eq!(picked.len(), 1);
+        assert_eq!(picked[0].id, "search_before_read");
+
+        // Case outside the category
+        assert!(select_eval_cases(Some("safety"), &["search_before_read".to_string()]).is_err());
+        assert!(select_eval_cases(None, &["nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_exit_status_from_result() {
+        let ok = Ok(HeadlessResult {
+            text: "done".to_string(),
+            tool_calls_made: 2,
+            stop_reason: Some("end_turn".to_string()),
+            structured: None,
+        });
+        assert_eq!(ExitStatus::from_result(&ok), ExitStatus::Success);
+        assert_eq!(
+            ExitStatus::from_result(&Err(AgentError::BudgetExceeded("x".into()))),
+            ExitStatus::BudgetExceeded
+        );
+        assert_eq!(
+            ExitStatus::from_result(&Err(AgentError::Cancelled)),
+            ExitStatus::Cancelled
+        );
+        assert_eq!(
+            ExitStatus::from_result(&Err(AgentError::ToolTimeout)),
+            ExitStatus::TaskFailed
+        );
+
+        let codes: Vec<u8> = [
+            ExitStatus::Success,
+            ExitStatus::TaskFailed,
+            ExitStatus::UsageError,
+            ExitStatus::BudgetExceeded,
+            ExitStatus::Cancelled,
+        ]
+        .iter()
+        .map(|s| s.code())
+        .collect();
+        assert_eq!(codes, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_report_json() {
+        let usage = UsageTotals {
+            input_tokens: 1_000_000,
+            output_tokens: 0,
+        };
+        let report = RunReport::new(
+            Err(AgentError::BudgetExceeded("spent".into())),
+            usage,
+            Some("claude-sonnet-4-20250514"),
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["status"], "budget_exceeded");
+        assert_eq!(json["exit_code"], 3);
+        assert_eq!(json["text"], serde_json::Value::Null);
+        assert_eq!(json["cost_usd"], 3.0);
+
+        let report = RunReport::new(Err(AgentError::Cancelled), usage, Some("custom"));
+        assert_eq!(report.cost_usd, None);
+    }
+
+    #[test]
+    fn test_budget_check() {
+        let tracker = Arc::new(SessionUsageTracker::new());
+        let budget = Budget {
+            model: "claude-sonnet-4-20250514".to_string(),
+            max_cost_usd: 0.01,
+            tracker: Arc::clone(&tracker),
+        };
+        assert!(budget.check().is_ok());
+
+        // 1000 output tokens at $15/M = $0.015
+        tracker.add_tokens(0, 1000);
+        assert!(matches!(budget.check(), Err(AgentError::BudgetExceeded(_))));
+    }
+
+    #[tokio::test]
+    async fn test_missing_project_fails_the_task() {
+        let report = run(
+            RunArgs {
+                prompt: "hi".to_string(),
+                project: PathBuf::from("/nonexistent/devflow-cli-test"),
+                max_iterations: None,
+                max_cost: None,
+                json: true,
+            },
+            CancellationToken::new(),
+        )
+        .await;
+        assert_eq!(report.status, ExitStatus::TaskFailed);
+        assert!(report.error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_tool_is_not_offered_or_run() {
+        std::env::set_var("DEVFLOW_CLI_TEST_API_KEY", "cli-test-key");
+        let config: ProjectConfig = toml::from_str(
+            r#"
+[agent]
+provider = "anthropic"
+model = "claude-test"
+api_key_env = "DEVFLOW_CLI_TEST_API_KEY"
+max_tokens = 1024
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 5
+disabled_tools = ["bash"]
+"#,
+        )
+        .unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let adapter =
+            AnthropicAdapter::new(config, project.path().to_path_buf(), DEFAULT_SYSTEM_PROMPT)
+                .unwrap();
+
+        let (tools, executor) = console_tools(
+            &adapter,
+            &CancellationToken::new(),
+            Arc::new(SessionUsageTracker::new()),
+        )
+        .unwrap();
+        assert!(tools.iter().all(|tool| tool.name != "bash"));
+        assert!(tools.iter().any(|tool| tool.name == "read_file"));
+
+        let result = executor
+            .execute(ToolName::Bash, serde_json::json!({"command": "echo hi"}))
+            .await;
+        match result {
+            Err(AgentError::ToolExecutionError(msg)) => assert!(msg.contains("disabled")),
+            other => panic!("expected disabled error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_preview_input_truncates() {
+        let short = serde_json::json!({"path": "src/main.rs"});
+        assert_eq!(preview_input(&short), r#"{"path":"src/main.rs"}"#);
+
+        let long = serde_json::json!({"content": "é".repeat(500)});
+        let preview = preview_input(&long);
+        assert!(preview.ends_with("..."));
+        assert_eq!(preview.chars().count(), MAX_INPUT_PREVIEW + 3);
+    }
+}
@@ -1,10 +1,11 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, State};
 
 use super::service::ConfigService;
-use super::types::{ConfigChangedPayload, ProjectConfig, ProviderInfo};
+use super::types::{ConfigChangedPayload, ProjectConfig, ProviderInfo, RecentProject};
+use super::watcher::ConfigWatchers;
 use crate::agent::{
     get_agent_prompts, get_agent_type_infos, get_tool_descriptions, AgentTypeInfo,
     DEFAULT_EXTRACTION_PROMPT, DEFAULT_SYSTEM_PROMPT,
@@ -17,14 +18,49 @@ pub fn config_get_last_project() -> Result<Option<String>, String> {
     Ok(config.state.last_project)
 }
 
+/// Called whenever a window opens a project, which also starts watching its
+/// config for edits made outside the app.
 #[tauri::command]
-pub fn config_set_last_project(project_path: Option<String>) -> Result<(), String> {
+pub fn config_set_last_project(
+    app_handle: AppHandle,
+    watchers: State<'_, ConfigWatchers>,
+    project_path: Option<String>,
+) -> Result<(), String> {
     let service = ConfigService::new().map_err(|e| e.to_string())?;
     let mut config = service.load_app_config().map_err(|e| e.to_string())?;
+    if let Some(path) = &project_path {
+        config.state.record_opened(path);
+        watchers.watch(&app_handle, Path::new(path));
+    }
     config.state.last_project = project_path;
     service.save_app_config(&config).map_err(|e| e.to_string())
 }
 
+/// Recently opened projects, pinned ones first.
+#[tauri::command]
+pub fn config_get_recent_projects() -> Result<Vec<RecentProject>, String> {
+    let service = ConfigService::new().map_err(|e| e.to_string())?;
+    let config = service.load_app_config().map_err(|e| e.to_string())?;
+    Ok(config.state.recent_projects())
+}
+
+/// Pin or unpin a recent project. Returns the updated list.
+#[tauri::command]
+pub fn config_pin_project(
+    project_path: String,
+    pinned: bool,
+) -> Result<Vec<RecentProject>, String> {
+    let service = ConfigService::new().map_err(|e| e.to_string())?;
+    let mut config = service.load_app_config().map_err(|e| e.to_string())?;
+    if !config.state.set_pinned(&project_path, pinned) {
+        return Err(format!("Not a recent project: {}", project_path));
+    }
+    service
+        .save_app_config(&config)
+        .map_err(|e| e.to_string())?;
+    Ok(config.state.recent_projects())
+}
+
 #[tauri::command]
 pub fn config_project_exists(project_path: String) -> bool {
     ConfigService::project_config_exists(Path::new(&project_path))
@@ -66,11 +102,14 @@ pub fn config_get_providers() -> Vec<ProviderInfo> {
 #[tauri::command]
 pub fn config_save_project(
     app_handle: AppHandle,
+    watchers: State<'_, ConfigWatchers>,
     project_path: String,
     config: ProjectConfig,
 ) -> Result<(), String> {
     ConfigService::save_project_config(Path::new(&project_path), &config)
         .map_err(|e| e.to_string())?;
+    // The first save creates `.devflow/`, which can only be watched from then on
+    watchers.watch(&app_handle, Path::new(&project_path));
 
     // Emit event for listeners (agent marks itself stale, frontend refreshes)
     let _ = app_handle.emit(
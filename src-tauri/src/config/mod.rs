@@ -1,8 +1,11 @@
 pub mod commands;
 mod error;
+mod recent;
 mod service;
 mod types;
+mod watcher;
 
 pub use error::ConfigError;
 pub use service::ConfigService;
 pub use types::*;
+pub use watcher::ConfigWatchers;
@@ -0,0 +1,144 @@
+//! The recent projects list kept in the app config, for the project switcher.
+
+use chrono::Utc;
+
+use super::types::{AppState, RecentProject};
+
+/// Unpinned projects kept in the list; pinned ones do not count
+const MAX_RECENT_PROJECTS: usize = 15;
+
+/// Longest session title kept, in characters
+const MAX_TITLE_CHARS: usize = 80;
+
+impl AppState {
+    /// Pinned projects first, each group most recently opened first.
+    pub fn recent_projects(&self) -> Vec<RecentProject> {
+        let (mut pinned, unpinned): (Vec<_>, Vec<_>) = self
+            .recent_projects
+            .iter()
+            .cloned()
+            .partition(|project| project.pinned);
+        pinned.extend(unpinned);
+        pinned
+    }
+
+    /// Moves the project to the front of the list, adding it if new.
+    pub fn record_opened(&mut self, path: &str) {
+        let mut project = match self.recent_projects.iter().position(|p| p.path == path) {
+            Some(index) => self.recent_projects.remove(index),
+            None => RecentProject {
+                path: path.to_string(),
+                last_opened: String::new(),
+                pinned: false,
+                provider: None,
+                model: None,
+                last_session_title: None,
+            },
+        };
+        project.last_opened = Utc::now().to_rfc3339();
+        self.recent_projects.insert(0, project);
+
+        let mut unpinned = 0;
+        self.recent_projects.retain(|project| {
+            if project.pinned {
+                return true;
+            }
+            unpinned += 1;
+            unpinned <= MAX_RECENT_PROJECTS
+        });
+    }
+
+    /// Remembers what the project's latest session used. The title is taken
+    /// from the session's first message.
+    pub fn record_session(&mut self, path: &str, provider: &str, model: &str, first_message: &str) {
+        if !self.recent_projects.iter().any(|p| p.path == path) {
+            self.record_opened(path);
+        }
+        if let Some(project) = self.recent_projects.iter_mut().find(|p| p.path == path) {
+            project.provider = Some(provider.to_string());
+            project.model = Some(model.to_string());
+            project.last_session_title = session_title(first_message);
+        }
+    }
+
+    /// Returns false if the project is not in the list.
+    pub fn set_pinned(&mut self, path: &str, pinned: bool) -> bool {
+        match self.recent_projects.iter_mut().find(|p| p.path == path) {
+            Some(project) => {
+                project.pinned = pinned;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// First non-empty line of the message, shortened to `MAX_TITLE_CHARS`.
+fn session_title(first_message: &str) -> Option<String> {
+    let line = first_message
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())?;
+    if line.chars().count() <= MAX_TITLE_CHARS {
+        return Some(line.to_string());
+    }
+    let truncated: String = line.chars().take(MAX_TITLE_CHARS - 3).collect();
+    Some(format!("{}...", truncated.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(state: &AppState) -> Vec<String> {
+        state
+            .recent_projects()
+            .into_iter()
+            .map(|project| project.path)
+            .collect()
+    }
+
+    #[test]
+    fn test_reopening_moves_project_to_front_and_pinned_stay() {
+        let mut state = AppState::default();
+        state.record_opened("/a");
+        state.record_opened("/b");
+        assert!(state.set_pinned("/a", true));
+        assert!(!state.set_pinned("/missing", true));
+
+        for i in 0..MAX_RECENT_PROJECTS {
+            state.record_opened(&format!("/p{}", i));
+        }
+        state.record_opened("/p0");
+
+        let listed = paths(&state);
+        assert_eq!(listed[0], "/a");
+        assert_eq!(listed[1], "/p0");
+        assert!(!listed.contains(&"/b".to_string()));
+        assert_eq!(listed.len(), MAX_RECENT_PROJECTS + 1);
+    }
+
+    #[test]
+    fn test_session_metadata_and_title() {
+        let mut state = AppState::default();
+        state.record_session(
+            "/a",
+            "anthropic",
+            "claude-sonnet-4-20250514",
+            "\n  Fix the flaky login test  \nIt fails on CI",
+        );
+
+        let project = &state.recent_projects()[0];
+        assert_eq!(project.provider.as_deref(), Some("anthropic"));
+        assert_eq!(
+            project.last_session_title.as_deref(),
+            Some("Fix the flaky login test")
+        );
+
+        let long = "word ".repeat(40);
+        let title = session_title(&long).unwrap();
+        assert!(title.ends_with("..."));
+        assert!(title.chars().count() <= MAX_TITLE_CHARS);
+        assert_eq!(session_title("  \n "), None);
+    }
+}
@@ -10,6 +10,8 @@ const APP_CONFIG_FILENAME: &str = "app.toml";
 const PROJECT_CONFIG_DIR: &str = ".devflow";
 const PROJECT_CONFIG_FILENAME: &str = "config.toml";
 const AGENTS_MD_FILENAME: &str = "AGENTS.md";
+const TRANSCRIPT_DIR: &str = "logs";
+const EVALS_DIR: &str = "evals";
 
 pub struct ConfigService {
     app_data_dir: PathBuf,
@@ -29,16 +31,30 @@ impl ConfigService {
         Self { app_data_dir }
     }
 
+    pub fn app_data_dir(&self) -> &Path {
+        &self.app_data_dir
+    }
+
     fn app_config_path(&self) -> PathBuf {
         self.app_data_dir.join(APP_CONFIG_FILENAME)
     }
 
-    fn project_config_path(project_path: &Path) -> PathBuf {
+    pub fn project_config_path(project_path: &Path) -> PathBuf {
         project_path
             .join(PROJECT_CONFIG_DIR)
             .join(PROJECT_CONFIG_FILENAME)
     }
 
+    /// Directory holding per-session run transcripts.
+    pub fn transcript_dir(project_path: &Path) -> PathBuf {
+        project_path.join(PROJECT_CONFIG_DIR).join(TRANSCRIPT_DIR)
+    }
+
+    /// Directory holding the evals dataset built from rated replies.
+    pub fn evals_dir(project_path: &Path) -> PathBuf {
+        project_path.join(PROJECT_CONFIG_DIR).join(EVALS_DIR)
+    }
+
     // App Config Methods
 
     pub fn load_app_config(&self) -> Result<AppConfig, ConfigError> {
@@ -167,19 +183,54 @@ mod tests {
                 api_key_env: "ANTHROPIC_API_KEY".to_string(),
                 max_tokens: 8192,
                 context_limit: None,
+                temperature: None,
+                compaction_threshold: None,
+                base_url: None,
+                anthropic: None,
+                gemini: None,
             },
             prompts: PromptsConfig::default(),
+            network: NetworkConfig::default(),
+            attribution: AttributionConfig::default(),
+            logging: LoggingConfig::default(),
+            docker: DockerConfig::default(),
+            formatting: FormattingConfig::default(),
+            diagnostics: DiagnosticsConfig::default(),
+            index: IndexConfig::default(),
+            auto_approve: Default::default(),
+            workspace: Default::default(),
+            templates: Default::default(),
+            request_cache: Default::default(),
+            timeouts: Default::default(),
+            rate_limits: Default::default(),
+            databases: Default::default(),
+            agent_sampling: Default::default(),
+            agent_models: Default::default(),
+            custom_agents: Default::default(),
             execution: ExecutionConfig {
                 timeout_secs: 30,
+                max_timeout_secs: 600,
+                plan_approval_timeout_secs: 1800,
                 max_tool_iterations: 50,
                 max_agent_depth: 3,
+                max_message_bytes: 512 * 1024,
+                max_conversation_bytes: 4 * 1024 * 1024,
+                max_tool_result_bytes: 512 * 1024,
+                max_read_bytes: 100 * 1024,
+                disabled_tools: Vec::new(),
+                depth_disabled_tools: Vec::new(),
+                shell: ShellKind::Auto,
+                allowed_paths: Vec::new(),
+                dry_run: false,
             },
             notifications: NotificationsConfig::default(),
             search: SearchConfig::default(),
             system_prompt: None,
             tool_descriptions: None,
+            tool_notes: Default::default(),
             extraction_prompt: None,
             agent_prompts: None,
+            agent_prompt_notes: Default::default(),
         }
     }
 
@@ -200,6 +251,7 @@ mod tests {
         let config = AppConfig {
             state: AppState {
                 last_project: Some("/path/to/project".to_string()),
+                recent_projects: Vec::new(),
             },
         };
 
@@ -212,6 +264,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_recent_projects_round_trip() {
+        let temp_dir = create_temp_dir();
+        let service = ConfigService::with_app_data_dir(temp_dir.path().to_path_buf());
+
+        let mut config = AppConfig::default();
+        config.state.last_project = Some("/b".to_string());
+        config.state.record_opened("/a");
+        config
+            .state
+            .record_session("/b", "gemini", "gemini-2.0-flash", "Add tests");
+        config.state.set_pinned("/a", true);
+        service.save_app_config(&config).unwrap();
+
+        let loaded = service.load_app_config().unwrap();
+        assert_eq!(
+            loaded.state.recent_projects(),
+            config.state.recent_projects()
+        );
+        assert!(loaded.state.recent_projects()[0].pinned);
+    }
+
     #[test]
     fn test_load_project_config_not_found() {
         let temp_dir = create_temp_dir();
@@ -231,15 +305,49 @@ mod tests {
                 api_key_env: "ANTHROPIC_API_KEY".to_string(),
                 max_tokens: 8192,
                 context_limit: None,
+                temperature: None,
+                compaction_threshold: None,
+                base_url: None,
+                anthropic: None,
+                gemini: None,
             },
             prompts: PromptsConfig {
                 pre: "You are a helpful assistant.".to_string(),
                 post: "Be concise.".to_string(),
+                ..PromptsConfig::default()
             },
+            network: NetworkConfig::default(),
+            attribution: AttributionConfig::default(),
+            logging: LoggingConfig::default(),
+            docker: DockerConfig::default(),
+            formatting: FormattingConfig::default(),
+            diagnostics: DiagnosticsConfig::default(),
+            index: IndexConfig::default(),
+            auto_approve: Default::default(),
+            workspace: Default::default(),
+            templates: Default::default(),
+            request_cache: Default::default(),
+            timeouts: Default::default(),
+            rate_limits: Default::default(),
+            databases: Default::default(),
+            agent_sampling: Default::default(),
+            agent_models: Default::default(),
+            custom_agents: Default::default(),
             execution: ExecutionConfig {
                 timeout_secs: 60,
+                max_timeout_secs: 600,
+                plan_approval_timeout_secs: 1800,
                 max_tool_iterations: 50,
                 max_agent_depth: 3,
+                max_message_bytes: 512 * 1024,
+                max_conversation_bytes: 4 * 1024 * 1024,
+                max_tool_result_bytes: 512 * 1024,
+                max_read_bytes: 100 * 1024,
+                disabled_tools: Vec::new(),
+                depth_disabled_tools: Vec::new(),
+                shell: ShellKind::Auto,
+                allowed_paths: Vec::new(),
+                dry_run: false,
             },
             notifications: NotificationsConfig {
                 on_complete: vec![NotificationAction::Window],
@@ -248,8 +356,10 @@ mod tests {
             search: SearchConfig::default(),
             system_prompt: None,
             tool_descriptions: None,
+            tool_notes: Default::default(),
             extraction_prompt: None,
             agent_prompts: None,
+            agent_prompt_notes: Default::default(),
         };
 
         ConfigService::save_project_config(temp_dir.path(), &config).unwrap();
@@ -358,11 +468,27 @@ provider = "anthropic"
                 api_key_env: "GEMINI_API_KEY".to_string(),
                 max_tokens: 4096,
                 context_limit: None,
+                temperature: None,
+                compaction_threshold: None,
+                base_url: None,
+                anthropic: None,
+                gemini: None,
             },
             execution: ExecutionConfig {
                 timeout_secs: 60,
+                max_timeout_secs: 600,
+                plan_approval_timeout_secs: 1800,
                 max_tool_iterations: 100,
                 max_agent_depth: 5,
+                max_message_bytes: 512 * 1024,
+                max_conversation_bytes: 4 * 1024 * 1024,
+                max_tool_result_bytes: 512 * 1024,
+                max_read_bytes: 100 * 1024,
+                disabled_tools: Vec::new(),
+                depth_disabled_tools: Vec::new(),
+                shell: ShellKind::Auto,
+                allowed_paths: Vec::new(),
+                dry_run: false,
             },
             ..test_project_config()
         };
@@ -383,6 +509,7 @@ provider = "anthropic"
         let config = AppConfig {
             state: AppState {
                 last_project: Some("/first/project".to_string()),
+                recent_projects: Vec::new(),
             },
         };
         service.save_app_config(&config).unwrap();
@@ -390,6 +517,7 @@ provider = "anthropic"
         let updated_config = AppConfig {
             state: AppState {
                 last_project: Some("/second/project".to_string()),
+                recent_projects: Vec::new(),
             },
         };
         service.save_app_config(&updated_config).unwrap();
@@ -409,12 +537,13 @@ provider = "anthropic"
         let config = AppConfig {
             state: AppState {
                 last_project: Some("/some/project".to_string()),
+                recent_projects: Vec::new(),
             },
         };
         service.save_app_config(&config).unwrap();
 
         let cleared_config = AppConfig {
-            state: AppState { last_project: None },
+            state: AppState::default(),
         };
         service.save_app_config(&cleared_config).unwrap();
 
@@ -502,6 +631,347 @@ max_tool_iterations = 50
         assert_eq!(loaded.execution.max_agent_depth, 3);
     }
 
+    #[test]
+    fn test_depth_disabled_tools() {
+        let temp_dir = create_temp_dir();
+        let config_dir = temp_dir.path().join(".devflow");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let config = r#"
+[agent]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+api_key_env = "ANTHROPIC_API_KEY"
+max_tokens = 4096
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+max_agent_depth = 2
+disabled_tools = ["bash"]
+
+[[execution.depth_disabled_tools]]
+depth = 1
+tools = ["write_file", "bash"]
+
+[[execution.depth_disabled_tools]]
+depth = 2
+tools = ["web_fetch"]
+"#;
+
+        fs::write(config_dir.join("config.toml"), config).unwrap();
+
+        let loaded = ConfigService::load_project_config(temp_dir.path()).unwrap();
+
+        let mut first = loaded.execution.clone();
+        first.restrict_to_depth(1);
+        assert_eq!(first.disabled_tools, vec!["bash", "write_file"]);
+
+        let mut second = loaded.execution;
+        second.restrict_to_depth(2);
+        assert_eq!(
+            second.disabled_tools,
+            vec!["bash", "write_file", "web_fetch", "dispatch_agent"]
+        );
+    }
+
+    #[test]
+    fn test_network_config_parsing() {
+        let temp_dir = create_temp_dir();
+        let config_dir = temp_dir.path().join(".devflow");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let config_with_network = r#"
+[agent]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+api_key_env = "ANTHROPIC_API_KEY"
+max_tokens = 4096
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+
+[network]
+proxy = "http://proxy.corp:3128"
+no_proxy = "localhost,.corp"
+ca_cert_path = "/etc/ssl/corp-ca.pem"
+"#;
+
+        fs::write(config_dir.join("config.toml"), config_with_network).unwrap();
+
+        let loaded = ConfigService::load_project_config(temp_dir.path()).unwrap();
+        assert_eq!(
+            loaded.network.proxy.as_deref(),
+            Some("http://proxy.corp:3128")
+        );
+        assert_eq!(loaded.network.no_proxy.as_deref(), Some("localhost,.corp"));
+        assert_eq!(
+            loaded.network.ca_cert_path.as_deref(),
+            Some("/etc/ssl/corp-ca.pem")
+        );
+    }
+
+    #[test]
+    fn test_gemini_options_parsing() {
+        let temp_dir = create_temp_dir();
+        let config_dir = temp_dir.path().join(".devflow");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let config_with_gemini = r#"
+[agent]
+provider = "gemini"
+model = "gemini-2.5-flash"
+api_key_env = "GEMINI_API_KEY"
+max_tokens = 4096
+temperature = 0.2
+
+[agent.gemini]
+top_p = 0.9
+thinking_budget = 0
+
+[[agent.gemini.safety_settings]]
+category = "HARM_CATEGORY_DANGEROUS_CONTENT"
+threshold = "BLOCK_ONLY_HIGH"
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+"#;
+
+        fs::write(config_dir.join("config.toml"), config_with_gemini).unwrap();
+
+        let loaded = ConfigService::load_project_config(temp_dir.path()).unwrap();
+        let gemini = loaded.agent.gemini.unwrap();
+        assert_eq!(gemini.top_p, Some(0.9));
+        assert_eq!(gemini.thinking_budget, Some(0));
+        assert_eq!(gemini.safety_settings.len(), 1);
+        assert_eq!(
+            gemini.safety_settings[0].category,
+            "HARM_CATEGORY_DANGEROUS_CONTENT"
+        );
+        assert_eq!(gemini.safety_settings[0].threshold, "BLOCK_ONLY_HIGH");
+    }
+
+    #[test]
+    fn test_agent_sampling_overrides() {
+        let temp_dir = create_temp_dir();
+        let config_dir = temp_dir.path().join(".devflow");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let config_with_sampling = r#"
+[agent]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+api_key_env = "ANTHROPIC_API_KEY"
+max_tokens = 4096
+temperature = 0.7
+
+[agent.anthropic]
+stop_sequences = ["</answer>"]
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+
+[agent_sampling.pr-review]
+temperature = 0.0
+top_p = 0.5
+stop_sequences = []
+"#;
+
+        fs::write(config_dir.join("config.toml"), config_with_sampling).unwrap();
+
+        let loaded = ConfigService::load_project_config(temp_dir.path()).unwrap();
+        let anthropic = loaded.agent.anthropic.as_ref().unwrap();
+        assert_eq!(anthropic.top_p, None);
+        assert_eq!(anthropic.stop_sequences, vec!["</answer>"]);
+
+        let mut explore = loaded.clone();
+        explore.apply_agent_sampling("explore");
+        assert_eq!(explore.agent.temperature, Some(0.7));
+        assert_eq!(
+            explore.agent.anthropic.unwrap().stop_sequences,
+            vec!["</answer>"]
+        );
+
+        let mut review = loaded;
+        review.apply_agent_sampling("pr-review");
+        assert_eq!(review.agent.temperature, Some(0.0));
+        let anthropic = review.agent.anthropic.unwrap();
+        assert_eq!(anthropic.top_p, Some(0.5));
+        assert!(anthropic.stop_sequences.is_empty());
+    }
+
+    #[test]
+    fn test_agent_model_overrides() {
+        let temp_dir = create_temp_dir();
+        let config_dir = temp_dir.path().join(".devflow");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let config_with_models = r#"
+[agent]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+api_key_env = "WORK_ANTHROPIC_KEY"
+max_tokens = 4096
+context_limit = 500000
+base_url = "http://localhost:8080"
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+
+[agent_models.explore]
+model = "claude-3-5-haiku-20241022"
+
+[agent_models.compaction]
+provider = "gemini"
+model = "gemini-2.0-flash"
+"#;
+
+        fs::write(config_dir.join("config.toml"), config_with_models).unwrap();
+
+        let loaded = ConfigService::load_project_config(temp_dir.path()).unwrap();
+
+        let mut plan = loaded.clone();
+        plan.apply_agent_model("plan");
+        assert_eq!(plan.agent.model, "claude-sonnet-4-20250514");
+        assert_eq!(plan.agent.context_limit, Some(500000));
+
+        let mut explore = loaded.clone();
+        explore.apply_agent_model("explore");
+        assert_eq!(explore.agent.provider, "anthropic");
+        assert_eq!(explore.agent.model, "claude-3-5-haiku-20241022");
+        assert_eq!(explore.agent.api_key_env, "WORK_ANTHROPIC_KEY");
+        assert_eq!(
+            explore.agent.base_url.as_deref(),
+            Some("http://localhost:8080")
+        );
+        assert_eq!(explore.agent.context_limit, None);
+
+        let mut compaction = loaded;
+        compaction.apply_agent_model("compaction");
+        assert_eq!(compaction.agent.provider, "gemini");
+        assert_eq!(compaction.agent.model, "gemini-2.0-flash");
+        assert_eq!(compaction.agent.api_key_env, "GEMINI_API_KEY");
+        assert_eq!(compaction.agent.base_url, None);
+    }
+
+    #[test]
+    fn test_provider_timeouts_parsing() {
+        let temp_dir = create_temp_dir();
+        let config_dir = temp_dir.path().join(".devflow");
+        fs::create_dir_all(&config_dir).unwrap();
+
+        let config_with_timeouts = r#"
+[agent]
+provider = "gemini"
+model = "gemini-2.0-flash"
+api_key_env = "GEMINI_API_KEY"
+max_tokens = 4096
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+
+[timeouts.gemini]
+read_secs = 60
+slow_response_secs = 15
+stream_idle_secs = 45
+"#;
+
+        fs::write(config_dir.join("config.toml"), config_with_timeouts).unwrap();
+
+        let loaded = ConfigService::load_project_config(temp_dir.path()).unwrap();
+        let timeouts = loaded.provider_timeouts();
+        assert_eq!(timeouts.connect_secs, 10);
+        assert_eq!(timeouts.read_secs, 60);
+        assert_eq!(timeouts.slow_response_secs, 15);
+        assert_eq!(timeouts.stream_idle_secs, 45);
+
+        let anthropic = ProjectConfig {
+            agent: test_project_config().agent,
+            ..loaded
+        };
+        assert_eq!(anthropic.provider_timeouts().read_secs, 300);
+    }
+
+    #[test]
+    fn test_provider_rate_limit_parsing() {
+        let config: ProjectConfig = toml::from_str(
+            r#"
+[agent]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+api_key_env = "ANTHROPIC_API_KEY"
+max_tokens = 4096
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+
+[rate_limits.anthropic]
+requests_per_minute = 50
+tokens_per_minute = 40000
+"#,
+        )
+        .unwrap();
+
+        let limits = config.provider_rate_limit();
+        assert_eq!(limits.requests_per_minute, Some(50));
+        assert_eq!(limits.tokens_per_minute, Some(40000));
+
+        let gemini = ProjectConfig {
+            agent: AgentConfig {
+                provider: "gemini".to_string(),
+                ..config.agent.clone()
+            },
+            ..config
+        };
+        assert_eq!(gemini.provider_rate_limit(), RateLimitConfig::default());
+    }
+
+    #[test]
+    fn test_databases_parsing() {
+        let config: ProjectConfig = toml::from_str(
+            r#"
+[agent]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+api_key_env = "ANTHROPIC_API_KEY"
+max_tokens = 4096
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 50
+
+[databases.app]
+kind = "sqlite"
+path = "data/app.db"
+
+[databases.warehouse]
+kind = "postgres"
+url_env = "WAREHOUSE_URL"
+read_only = false
+max_rows = 50
+"#,
+        )
+        .unwrap();
+
+        let app = &config.databases["app"];
+        assert_eq!(app.kind, DatabaseKind::Sqlite);
+        assert_eq!(app.path.as_deref(), Some("data/app.db"));
+        assert!(app.read_only);
+        assert_eq!(app.max_rows, 200);
+
+        let warehouse = &config.databases["warehouse"];
+        assert_eq!(warehouse.kind, DatabaseKind::Postgres);
+        assert_eq!(warehouse.url_env.as_deref(), Some("WAREHOUSE_URL"));
+        assert!(!warehouse.read_only);
+        assert_eq!(warehouse.max_rows, 50);
+    }
+
     #[test]
     fn test_config_toml_format() {
         let temp_dir = create_temp_dir();
@@ -510,6 +980,7 @@ max_tool_iterations = 50
             prompts: PromptsConfig {
                 pre: "System prompt".to_string(),
                 post: "".to_string(),
+                ..PromptsConfig::default()
             },
             ..test_project_config()
         };
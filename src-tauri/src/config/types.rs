@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 use ts_rs::TS;
@@ -9,6 +10,17 @@ pub struct ConfigChangedPayload {
     pub project_path: String,
 }
 
+/// The project config changed on disk and was loaded again.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct ConfigReloadedPayload {
+    pub project_path: String,
+    /// Dotted paths of the settings that changed, e.g. `agent.model`
+    pub changed_keys: Vec<String>,
+    /// Why the file could not be loaded; the agent keeps its last valid config
+    pub error: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, TS)]
 #[ts(export)]
 pub struct ProviderInfo {
@@ -27,6 +39,31 @@ pub struct AppConfig {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppState {
     pub last_project: Option<String>,
+    /// Most recently opened first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_projects: Vec<RecentProject>,
+}
+
+/// A project in the recents list, with what it was last used with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RecentProject {
+    pub path: String,
+    /// RFC 3339 UTC timestamp
+    pub last_opened: String,
+    /// Pinned projects are listed first and never dropped from the list
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub model: Option<String>,
+    /// Start of the first message of the last session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub last_session_title: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -40,6 +77,48 @@ pub struct ProjectConfig {
     pub notifications: NotificationsConfig,
     #[serde(default)]
     pub prompts: PromptsConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub attribution: AttributionConfig,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    #[serde(default)]
+    pub docker: DockerConfig,
+    #[serde(default)]
+    pub formatting: FormattingConfig,
+    #[serde(default)]
+    pub diagnostics: DiagnosticsConfig,
+    #[serde(default)]
+    pub index: IndexConfig,
+    #[serde(default)]
+    pub auto_approve: AutoApproveConfig,
+    #[serde(default)]
+    pub workspace: WorkspaceConfig,
+    #[serde(default)]
+    pub templates: TemplatesConfig,
+    #[serde(default)]
+    pub request_cache: RequestCacheConfig,
+    /// Request timeouts keyed by provider id (e.g. `[timeouts.anthropic]`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub timeouts: HashMap<String, ProviderTimeouts>,
+    /// Request rate limits keyed by provider id (e.g. `[rate_limits.anthropic]`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub rate_limits: HashMap<String, RateLimitConfig>,
+    /// Databases the query_database tool can connect to, keyed by name (e.g. `[databases.app]`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub databases: HashMap<String, DatabaseConfig>,
+    /// Sampling overrides for sub-agents, keyed by agent type id (e.g. `[agent_sampling.pr-review]`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub agent_sampling: HashMap<String, SamplingOverrides>,
+    /// Models for sub-agents, keyed by agent type id, and for `compaction`
+    /// extraction (e.g. `[agent_models.summarize]`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub agent_models: HashMap<String, ModelOverride>,
+    /// Project-defined agent types dispatch_agent can run, keyed by id
+    /// (e.g. `[custom_agents.migration-check]`)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub custom_agents: HashMap<String, CustomAgentConfig>,
     /// Custom system prompt (None = use default)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub system_prompt: Option<String>,
@@ -49,9 +128,127 @@ pub struct ProjectConfig {
     /// Custom tool descriptions (None = use defaults)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_descriptions: Option<HashMap<String, String>>,
+    /// Project notes appended to tool descriptions by tool name, e.g. bash conventions
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tool_notes: HashMap<String, String>,
     /// Custom agent prompts (None = use defaults)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub agent_prompts: Option<HashMap<String, String>>,
+    /// Text appended to agent prompts by agent type id
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub agent_prompt_notes: HashMap<String, String>,
+}
+
+impl ProjectConfig {
+    /// Timeouts for the configured provider, falling back to defaults.
+    pub fn provider_timeouts(&self) -> ProviderTimeouts {
+        self.timeouts
+            .get(&self.agent.provider)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Rate limits for the configured provider (unlimited if not set).
+    pub fn provider_rate_limit(&self) -> RateLimitConfig {
+        self.rate_limits
+            .get(&self.agent.provider)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Applies `[agent_sampling.<agent_type>]` on top of the `[agent]` settings.
+    pub fn apply_agent_sampling(&mut self, agent_type: &str) {
+        let Some(sampling) = self.agent_sampling.get(agent_type).cloned() else {
+            return;
+        };
+        let agent = &mut self.agent;
+        if sampling.temperature.is_some() {
+            agent.temperature = sampling.temperature;
+        }
+        if let Some(top_p) = sampling.top_p {
+            agent.anthropic.get_or_insert_with(Default::default).top_p = Some(top_p);
+            agent.gemini.get_or_insert_with(Default::default).top_p = Some(top_p);
+        }
+        if let Some(stop_sequences) = sampling.stop_sequences {
+            agent
+                .anthropic
+                .get_or_insert_with(Default::default)
+                .stop_sequences = stop_sequences;
+        }
+    }
+
+    /// Applies `[agent_models.<key>]` on top of the `[agent]` settings, where
+    /// `key` is an agent type id or `compaction`.
+    pub fn apply_agent_model(&mut self, key: &str) {
+        let Some(choice) = self.agent_models.get(key).cloned() else {
+            return;
+        };
+        let agent = &mut self.agent;
+        match choice.provider {
+            Some(provider) if !provider.eq_ignore_ascii_case(&agent.provider) => {
+                agent.api_key_env = choice
+                    .api_key_env
+                    .unwrap_or_else(|| format!("{}_API_KEY", provider.to_uppercase()));
+                // The origin override belongs to the other provider's API
+                agent.base_url = None;
+                agent.provider = provider;
+            }
+            _ => {
+                if let Some(api_key_env) = choice.api_key_env {
+                    agent.api_key_env = api_key_env;
+                }
+            }
+        }
+        agent.model = choice.model;
+        // A limit set for the main model; the new one's comes from the registry
+        agent.context_limit = None;
+    }
+}
+
+/// Provider and model used instead of the `[agent]` ones, e.g. a cheaper
+/// model for exploration or summaries.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ModelOverride {
+    /// None keeps the `[agent]` provider
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub provider: Option<String>,
+    pub model: String,
+    /// Env var holding the API key (None = the `[agent]` one for the same
+    /// provider, `<PROVIDER>_API_KEY` for another)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub api_key_env: Option<String>,
+}
+
+/// An agent type defined by the project. Its id must differ from the
+/// built-in ones, which take precedence.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct CustomAgentConfig {
+    /// What the agent is for, shown to the model in the dispatch_agent tool
+    pub description: String,
+    /// System prompt; may use the same placeholders as the built-in prompts
+    pub prompt: String,
+    /// Tools the agent may use (None = the explore agent's read-only set,
+    /// empty = text only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub tools: Option<Vec<String>>,
+}
+
+/// Per-agent-type sampling settings; unset fields keep the `[agent]` values.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SamplingOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Replaces the Anthropic stop sequences (not sent to Gemini)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -80,6 +277,346 @@ impl Default for SearchConfig {
     }
 }
 
+/// Proxy and TLS settings applied to every outgoing HTTP client.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct NetworkConfig {
+    /// Proxy URL for HTTP and HTTPS traffic (None = use system proxy env vars)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Comma-separated hosts that bypass the proxy (same syntax as NO_PROXY)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+    /// Path to a PEM file with additional root certificates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert_path: Option<String>,
+}
+
+/// Trailer marking files written by the agent and commit messages as AI-generated.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AttributionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Tool name shown in the trailer
+    #[serde(default = "default_attribution_label")]
+    pub label: String,
+}
+
+fn default_attribution_label() -> String {
+    "devflow".to_string()
+}
+
+impl Default for AttributionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            label: default_attribution_label(),
+        }
+    }
+}
+
+/// Provider request timeouts. The slow-response threshold only triggers a UI warning.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ProviderTimeouts {
+    /// Seconds to establish a connection
+    #[serde(default = "default_connect_timeout_secs")]
+    #[ts(type = "number")]
+    pub connect_secs: u64,
+    /// Seconds without receiving data before a request fails
+    #[serde(default = "default_read_timeout_secs")]
+    #[ts(type = "number")]
+    pub read_secs: u64,
+    /// Seconds without receiving data before emitting `agent-slow-response` (0 = off)
+    #[serde(default = "default_slow_response_secs")]
+    #[ts(type = "number")]
+    pub slow_response_secs: u64,
+    /// Seconds without a new chunk, once a response is streaming, before the
+    /// stream counts as stalled and is retried (0 = off)
+    #[serde(default = "default_stream_idle_secs")]
+    #[ts(type = "number")]
+    pub stream_idle_secs: u64,
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_read_timeout_secs() -> u64 {
+    300
+}
+
+fn default_slow_response_secs() -> u64 {
+    30
+}
+
+fn default_stream_idle_secs() -> u64 {
+    120
+}
+
+impl Default for ProviderTimeouts {
+    fn default() -> Self {
+        Self {
+            connect_secs: default_connect_timeout_secs(),
+            read_secs: default_read_timeout_secs(),
+            slow_response_secs: default_slow_response_secs(),
+            stream_idle_secs: default_stream_idle_secs(),
+        }
+    }
+}
+
+/// Provider rate limits, shared by every session in the process.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RateLimitConfig {
+    /// Maximum requests per minute (None = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requests_per_minute: Option<u32>,
+    /// Maximum input plus output tokens per minute (None = unlimited)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tokens_per_minute: Option<u32>,
+}
+
+/// Caching of repeated headless requests (titles, extraction, sub-agents).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct RequestCacheConfig {
+    /// Answer identical headless requests from the cache instead of the API
+    #[serde(default)]
+    pub enabled: bool,
+    /// Seconds a cached response is reused
+    #[serde(default = "default_request_cache_ttl_secs")]
+    #[ts(type = "number")]
+    pub ttl_secs: u64,
+    /// Responses kept per provider; the oldest are dropped first
+    #[serde(default = "default_request_cache_max_entries")]
+    pub max_entries: u32,
+}
+
+fn default_request_cache_ttl_secs() -> u64 {
+    3600
+}
+
+fn default_request_cache_max_entries() -> u32 {
+    200
+}
+
+impl Default for RequestCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_request_cache_ttl_secs(),
+            max_entries: default_request_cache_max_entries(),
+        }
+    }
+}
+
+/// A database connection for the query_database tool.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DatabaseConfig {
+    pub kind: DatabaseKind,
+    /// SQLite database file, relative to the project root
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Env var holding the postgres/mysql connection URL, so credentials stay out of the config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url_env: Option<String>,
+    /// Reject statements that modify data and open the connection read-only
+    #[serde(default = "default_database_read_only")]
+    pub read_only: bool,
+    /// Rows returned per query; the rest are counted but not shown
+    #[serde(default = "default_database_max_rows")]
+    pub max_rows: u32,
+}
+
+fn default_database_read_only() -> bool {
+    true
+}
+
+fn default_database_max_rows() -> u32 {
+    200
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum DatabaseKind {
+    Sqlite,
+    Postgres,
+    Mysql,
+}
+
+/// Formatting of files changed by the agent's write and edit tools.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FormattingConfig {
+    /// Format files after write_file, edit_file and multi_edit
+    #[serde(default)]
+    pub on_edit: bool,
+    /// Formatter command per file extension (e.g. `rs = "rustfmt --edition 2021"`), run
+    /// with the file path appended; other extensions use the detected formatter
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub commands: HashMap<String, String>,
+}
+
+/// Check commands run after each batch of edits; failures are appended to the edit results.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DiagnosticsConfig {
+    /// Check command per file extension (e.g. `rs = "cargo check --message-format short"`),
+    /// run through the shell in the project root
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub commands: HashMap<String, String>,
+    /// Seconds a check may run before it is abandoned
+    #[serde(default = "default_diagnostics_timeout_secs")]
+    #[ts(type = "number")]
+    pub timeout_secs: u64,
+    /// Output lines kept from a failing check
+    #[serde(default = "default_diagnostics_max_lines")]
+    pub max_lines: u32,
+}
+
+fn default_diagnostics_timeout_secs() -> u64 {
+    120
+}
+
+fn default_diagnostics_max_lines() -> u32 {
+    40
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self {
+            commands: HashMap::new(),
+            timeout_secs: default_diagnostics_timeout_secs(),
+            max_lines: default_diagnostics_max_lines(),
+        }
+    }
+}
+
+/// Embeddings index for the semantic_search tool, stored under `.devflow/index`.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct IndexConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub provider: EmbeddingProvider,
+    /// Embedding model (None = text-embedding-004 for Gemini, nomic-embed-text for Ollama)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// API origin override (None = provider default, http://localhost:11434 for Ollama)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Env var holding the Gemini API key (None = GEMINI_API_KEY)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// Lines per indexed chunk
+    #[serde(default = "default_index_chunk_lines")]
+    pub chunk_lines: u32,
+    /// Larger files are not indexed
+    #[serde(default = "default_index_max_file_bytes")]
+    #[ts(type = "number")]
+    pub max_file_bytes: u64,
+}
+
+fn default_index_chunk_lines() -> u32 {
+    60
+}
+
+fn default_index_max_file_bytes() -> u64 {
+    256 * 1024
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: EmbeddingProvider::default(),
+            model: None,
+            base_url: None,
+            api_key_env: None,
+            chunk_lines: default_index_chunk_lines(),
+            max_file_bytes: default_index_max_file_bytes(),
+        }
+    }
+}
+
+/// Where embeddings come from: the Gemini API or a local Ollama server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    #[default]
+    Gemini,
+    Ollama,
+}
+
+/// Container access for the docker tool. Off unless enabled, and limited to
+/// the listed compose services.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DockerConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Compose file relative to the project root (None = compose's default lookup)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compose_file: Option<String>,
+    /// Services the agent may run commands in and read logs from
+    #[serde(default)]
+    pub allowed_services: Vec<String>,
+}
+
+/// Debug logging of agent runs.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct LoggingConfig {
+    /// Write a JSONL transcript of each session to `.devflow/logs/`
+    #[serde(default)]
+    pub transcripts: bool,
+    /// Copy each rated reply, with the conversation leading to it, to
+    /// `.devflow/evals/ratings.jsonl`
+    #[serde(default)]
+    pub evals: bool,
+}
+
+/// What runs without asking the user first. Tool categories left off wait
+/// for `agent_respond_tool_approval`; sub-agents cannot ask, so they are not
+/// offered those tools.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AutoApproveConfig {
+    /// Accept submitted plans without waiting for `agent_approve_plan`
+    #[serde(default)]
+    pub plans: bool,
+    /// write_file, edit_file, multi_edit, notebook_edit and scaffold
+    #[serde(default = "default_auto_approve")]
+    pub edits: bool,
+    /// bash and docker
+    #[serde(default = "default_auto_approve")]
+    pub bash: bool,
+    /// web_fetch, search_web and lookup_docs
+    #[serde(default = "default_auto_approve")]
+    pub network: bool,
+}
+
+fn default_auto_approve() -> bool {
+    true
+}
+
+impl Default for AutoApproveConfig {
+    fn default() -> Self {
+        Self {
+            plans: false,
+            edits: default_auto_approve(),
+            bash: default_auto_approve(),
+            network: default_auto_approve(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct AgentConfig {
@@ -89,6 +626,57 @@ pub struct AgentConfig {
     pub max_tokens: u32,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub context_limit: Option<u32>,
+    /// Sampling temperature (None = provider default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    /// Fraction of the context limit at which compaction starts (None = 0.8)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compaction_threshold: Option<f32>,
+    /// API origin override, e.g. a gateway or a local test server (None = provider default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Anthropic-only request settings, the `[agent.anthropic]` table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anthropic: Option<AnthropicOptions>,
+    /// Gemini-only generation settings, the `[agent.gemini]` table
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gemini: Option<GeminiOptions>,
+}
+
+/// Request settings only the Anthropic API understands.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AnthropicOptions {
+    /// Nucleus sampling cutoff (None = provider default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Strings that end the response when generated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop_sequences: Vec<String>,
+}
+
+/// Generation settings only the Gemini API understands.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct GeminiOptions {
+    /// Nucleus sampling cutoff (None = provider default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Tokens the model may spend thinking (0 = off, -1 = dynamic, None = model default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<i32>,
+    /// Blocking thresholds per harm category; unlisted categories keep the API defaults
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub safety_settings: Vec<GeminiSafetySetting>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct GeminiSafetySetting {
+    /// Harm category, e.g. `HARM_CATEGORY_DANGEROUS_CONTENT`
+    pub category: String,
+    /// Blocking threshold, e.g. `BLOCK_ONLY_HIGH` or `BLOCK_NONE`
+    pub threshold: String,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
@@ -98,6 +686,10 @@ pub struct PromptsConfig {
     pub pre: String,
     #[serde(default)]
     pub post: String,
+    /// Values for `{VAR:name}` placeholders in prompts (e.g. `ticket_url = "..."`);
+    /// variables sent with a message take precedence
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub variables: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, TS)]
@@ -105,15 +697,214 @@ pub struct PromptsConfig {
 pub struct ExecutionConfig {
     #[ts(type = "number")]
     pub timeout_secs: u64,
+    /// Longest timeout a bash call may ask for; longer requests are capped
+    #[serde(default = "default_max_timeout_secs")]
+    #[ts(type = "number")]
+    pub max_timeout_secs: u64,
+    /// How long a submitted plan waits for the user before it is withdrawn;
+    /// 0 waits as long as it takes
+    #[serde(default = "default_plan_approval_timeout_secs")]
+    #[ts(type = "number")]
+    pub plan_approval_timeout_secs: u64,
     pub max_tool_iterations: u32,
     #[serde(default = "default_max_agent_depth")]
     pub max_agent_depth: u32,
+    /// Largest single message accepted, in bytes
+    #[serde(default = "default_max_message_bytes")]
+    #[ts(type = "number")]
+    pub max_message_bytes: u64,
+    /// Largest conversation (system prompt plus all messages) sent in one request
+    #[serde(default = "default_max_conversation_bytes")]
+    #[ts(type = "number")]
+    pub max_conversation_bytes: u64,
+    /// Largest tool output passed back to the model; bigger outputs become an error result
+    #[serde(default = "default_max_tool_result_bytes")]
+    #[ts(type = "number")]
+    pub max_tool_result_bytes: u64,
+    /// Most of a file read_file returns at once; larger reads return the
+    /// start with an index of the file's declarations or headings
+    #[serde(default = "default_max_read_bytes")]
+    #[ts(type = "number")]
+    pub max_read_bytes: u64,
+    /// Tools never offered to the agent or its sub-agents, by name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_tools: Vec<String>,
+    /// Tools also taken away from sub-agents nested at least `depth` deep
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depth_disabled_tools: Vec<DepthDisabledTools>,
+    /// Shell the bash tool runs commands with; projects on WSL paths always use the distro's sh
+    #[serde(default)]
+    pub shell: ShellKind,
+    /// Directories outside the project the file tools and bash may use
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_paths: Vec<AllowedPath>,
+    /// Answer bash, file edit and docker exec calls with what they would have
+    /// done (the command or diff) instead of running them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl ExecutionConfig {
+    /// Disables what a sub-agent at `depth` (1 = dispatched by the main agent)
+    /// may not use: the tools of `depth_disabled_tools` entries up to its
+    /// depth, and dispatch_agent once it is at `max_agent_depth`.
+    pub fn restrict_to_depth(&mut self, depth: u32) {
+        let restricted: Vec<String> = self
+            .depth_disabled_tools
+            .iter()
+            .filter(|entry| entry.depth <= depth)
+            .flat_map(|entry| entry.tools.iter().cloned())
+            .chain((depth >= self.max_agent_depth).then(|| "dispatch_agent".to_string()))
+            .collect();
+        for tool in restricted {
+            if !self.disabled_tools.contains(&tool) {
+                self.disabled_tools.push(tool);
+            }
+        }
+    }
+}
+
+/// Tools sub-agents lose from a nesting depth on, e.g. no bash below the first level.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DepthDisabledTools {
+    /// 1 = sub-agents of the main agent, 2 = their sub-agents, and so on
+    pub depth: u32,
+    pub tools: Vec<String>,
+}
+
+/// Related repositories worked on together with the project, e.g. the
+/// frontend, backend and infra repos of one product.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WorkspaceConfig {
+    /// Empty for a single-repo project
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roots: Vec<WorkspaceRoot>,
+}
+
+impl WorkspaceConfig {
+    /// Directory of the root named `name`, resolved against the project.
+    pub fn root_path(&self, project_path: &Path, name: &str) -> Option<PathBuf> {
+        self.roots
+            .iter()
+            .find(|root| root.name == name)
+            .map(|root| project_path.join(&root.path))
+    }
+
+    /// Every root as (name, directory), resolved against the project.
+    pub fn root_paths(&self, project_path: &Path) -> Vec<(String, PathBuf)> {
+        self.roots
+            .iter()
+            .map(|root| (root.name.clone(), project_path.join(&root.path)))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct WorkspaceRoot {
+    /// Path prefix the tools use for this repo (`backend` for `backend/src/main.rs`)
+    /// and the repo selector of the git commands
+    pub name: String,
+    /// Absolute, or relative to the project root (e.g. `../backend`)
+    pub path: String,
+}
+
+/// Where scaffold templates come from besides the project and user directories.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TemplatesConfig {
+    /// Searched in order after the project's `.devflow/templates`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<TemplateSource>,
+}
+
+/// A shared set of scaffolds: a git repository (cached locally and updated by
+/// `template_sync`) or a directory outside the project.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TemplateSource {
+    /// Shown in the scaffold list and names the cache directory
+    pub name: String,
+    /// Clone URL; takes precedence over `path`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub git: Option<String>,
+    /// Absolute, or relative to the project root (e.g. `../team-templates`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub path: Option<String>,
+    /// Branch, tag or commit a git source is pinned to (default: the remote HEAD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rev: Option<String>,
+    /// Directory inside the source holding the scaffolds (default: its root)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub subdir: Option<String>,
+}
+
+/// A directory outside the project, e.g. a shared monorepo package or a scratch dir.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct AllowedPath {
+    /// Absolute, or relative to the project root (e.g. `../shared`)
+    pub path: String,
+    #[serde(default)]
+    pub access: PathAccess,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum PathAccess {
+    /// Readable by the file tools; writes and bash commands there are refused
+    #[default]
+    Read,
+    ReadWrite,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "lowercase")]
+pub enum ShellKind {
+    /// PowerShell on Windows, sh elsewhere
+    #[default]
+    Auto,
+    Powershell,
+    Cmd,
+    Sh,
 }
 
 fn default_max_agent_depth() -> u32 {
     3
 }
 
+fn default_max_message_bytes() -> u64 {
+    512 * 1024
+}
+
+fn default_max_conversation_bytes() -> u64 {
+    4 * 1024 * 1024
+}
+
+fn default_max_tool_result_bytes() -> u64 {
+    512 * 1024
+}
+
+fn default_max_read_bytes() -> u64 {
+    100 * 1024
+}
+
+fn default_max_timeout_secs() -> u64 {
+    600
+}
+
+fn default_plan_approval_timeout_secs() -> u64 {
+    30 * 60
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
 #[ts(export)]
 pub struct NotificationsConfig {
@@ -0,0 +1,209 @@
+//! Reloads project configs edited outside the app. The `.devflow/` directory
+//! of each opened project is watched; once writes to its `config.toml` have
+//! settled, the file is loaded again, the agent state of windows with that
+//! project open is marked stale so their next run picks it up, and `config-reloaded` lists the settings that
+//! changed, or says why the file could not be loaded.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use super::service::ConfigService;
+use super::types::{ConfigReloadedPayload, ProjectConfig};
+use crate::agent::WindowStates;
+
+/// Quiet period before a changed config is loaded
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Config watchers of the projects opened since the app started, by project path.
+#[derive(Default)]
+pub struct ConfigWatchers {
+    watchers: Mutex<HashMap<PathBuf, RecommendedWatcher>>,
+}
+
+impl ConfigWatchers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts watching the config of `project_path` unless it already is.
+    /// A project without a `.devflow/` directory yet is not watched; call
+    /// again once its config has been saved.
+    pub fn watch<R: Runtime>(&self, app_handle: &AppHandle<R>, project_path: &Path) {
+        let mut watchers = self.watchers.lock().unwrap_or_else(PoisonError::into_inner);
+        if watchers.contains_key(project_path) {
+            return;
+        }
+
+        let config_path = ConfigService::project_config_path(project_path);
+        let Some(config_dir) = config_path.parent().filter(|dir| dir.is_dir()) else {
+            return;
+        };
+        let file_name = config_path.file_name().map(|name| name.to_os_string());
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        // Editors often save by writing a new file and renaming it over the
+        // old one, so the directory is watched rather than the file
+        let watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+            let Ok(event) = result else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            if event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == file_name.as_deref())
+            {
+                let _ = tx.send(());
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(config_dir, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+
+        match watcher {
+            Ok(watcher) => {
+                tauri::async_runtime::spawn(reload_on_change(
+                    app_handle.clone(),
+                    project_path.to_path_buf(),
+                    rx,
+                ));
+                watchers.insert(project_path.to_path_buf(), watcher);
+            }
+            Err(e) => log::warn!("Not watching {} for changes: {}", config_path.display(), e),
+        }
+    }
+}
+
+async fn reload_on_change<R: Runtime>(
+    app_handle: AppHandle<R>,
+    project_path: PathBuf,
+    mut rx: UnboundedReceiver<()>,
+) {
+    // The last config that loaded, which the agent falls back on
+    let mut current = ConfigService::load_project_config(&project_path).ok();
+
+    while rx.recv().await.is_some() {
+        // Take in the whole save before loading
+        loop {
+            match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                Ok(Some(())) => {}
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+
+        let project = project_path.to_string_lossy().to_string();
+        let payload = match ConfigService::load_project_config(&project_path) {
+            Ok(config) => {
+                let changed_keys = changed_keys(current.as_ref(), &config);
+                if changed_keys.is_empty() {
+                    continue;
+                }
+                current = Some(config);
+                if let Some(windows) = app_handle.try_state::<WindowStates>() {
+                    windows.mark_project_config_stale(&project_path);
+                }
+                ConfigReloadedPayload {
+                    project_path: project,
+                    changed_keys,
+                    error: None,
+                }
+            }
+            Err(e) => ConfigReloadedPayload {
+                project_path: project,
+                changed_keys: Vec::new(),
+                error: Some(e.to_string()),
+            },
+        };
+        let _ = app_handle.emit("config-reloaded", payload);
+    }
+}
+
+/// Dotted paths of the settings that differ between two configs, e.g.
+/// `agent.model`. Arrays count as one setting.
+fn changed_keys(old: Option<&ProjectConfig>, new: &ProjectConfig) -> Vec<String> {
+    let old = old
+        .and_then(|config| serde_json::to_value(config).ok())
+        .unwrap_or_else(|| Value::Object(Default::default()));
+    let new = serde_json::to_value(new).unwrap_or(Value::Null);
+    let mut changed = Vec::new();
+    diff_values("", &old, &new, &mut changed);
+    changed
+}
+
+fn diff_values(prefix: &str, old: &Value, new: &Value, changed: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(old_fields), Value::Object(new_fields)) => {
+            let keys: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                diff_values(
+                    &path,
+                    old_fields.get(key).unwrap_or(&Value::Null),
+                    new_fields.get(key).unwrap_or(&Value::Null),
+                    changed,
+                );
+            }
+        }
+        _ if old != new => changed.push(prefix.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> ProjectConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    const BASE: &str = r#"
+[agent]
+provider = "anthropic"
+model = "claude-test"
+api_key_env = "ANTHROPIC_API_KEY"
+max_tokens = 1024
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 5
+"#;
+
+    #[test]
+    fn test_changed_keys_lists_changed_settings() {
+        let old = config(BASE);
+        let new = config(&BASE.replace("claude-test", "claude-other").replace(
+            "max_tool_iterations = 5",
+            "max_tool_iterations = 5\ndisabled_tools = [\"bash\"]",
+        ));
+
+        assert_eq!(
+            changed_keys(Some(&old), &new),
+            vec!["agent.model", "execution.disabled_tools"]
+        );
+        assert!(changed_keys(Some(&old), &old).is_empty());
+    }
+
+    #[test]
+    fn test_changed_keys_without_previous_config() {
+        let new = config(BASE);
+        let changed = changed_keys(None, &new);
+        assert!(changed.contains(&"agent.model".to_string()));
+        assert!(changed.contains(&"execution.timeout_secs".to_string()));
+    }
+}
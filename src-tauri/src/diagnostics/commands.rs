@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+use tauri::{AppHandle, Manager};
+
+use super::bundle::{export_bundle, BundleOptions};
+use super::errors::recent_errors;
+use crate::config::ConfigService;
+
+const DEFAULT_LOG_LINES: usize = 500;
+
+/// Writes a zip for bug reports to `output_path`: app version, OS, the
+/// project config without prompt text, the last `log_lines` log lines and
+/// recent agent errors, with API keys redacted.
+#[tauri::command]
+pub async fn diagnostics_export(
+    app_handle: AppHandle,
+    output_path: String,
+    project_path: Option<String>,
+    log_lines: Option<usize>,
+) -> Result<(), String> {
+    let package = app_handle.package_info();
+    let app_version = package.version.to_string();
+    // The log plugin writes `<app log dir>/<package name>.log`
+    let log_path: Option<PathBuf> = app_handle
+        .path()
+        .app_log_dir()
+        .ok()
+        .map(|dir| dir.join(format!("{}.log", package.name)));
+
+    tokio::task::spawn_blocking(move || {
+        let config =
+            project_path.and_then(|path| ConfigService::load_project_config(Path::new(&path)).ok());
+        export_bundle(
+            Path::new(&output_path),
+            &BundleOptions {
+                app_version: &app_version,
+                config: config.as_ref(),
+                log_path: log_path.as_deref(),
+                log_lines: log_lines.unwrap_or(DEFAULT_LOG_LINES),
+            },
+            &recent_errors(),
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
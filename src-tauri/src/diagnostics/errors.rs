@@ -0,0 +1,41 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+
+/// Errors kept for the next bundle; older ones are dropped.
+const MAX_ERRORS: usize = 50;
+
+static RECENT_ERRORS: Lazy<Mutex<VecDeque<ErrorEvent>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(MAX_ERRORS)));
+
+/// An `agent-error` event, as shown to the user.
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorEvent {
+    /// RFC 3339 UTC timestamp
+    pub timestamp: String,
+    pub error: String,
+}
+
+pub fn record_error(error: &str) {
+    let Ok(mut errors) = RECENT_ERRORS.lock() else {
+        return;
+    };
+    if errors.len() == MAX_ERRORS {
+        errors.pop_front();
+    }
+    errors.push_back(ErrorEvent {
+        timestamp: Utc::now().to_rfc3339(),
+        error: error.to_string(),
+    });
+}
+
+/// Errors since the app started, oldest first.
+pub fn recent_errors() -> Vec<ErrorEvent> {
+    RECENT_ERRORS
+        .lock()
+        .map(|errors| errors.iter().cloned().collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,10 @@
+//! Bug report bundles: app and OS info, the project config with secrets and
+//! prompt text removed, the tail of the app log and recent agent errors,
+//! zipped so users can attach one file to an issue.
+
+mod bundle;
+pub mod commands;
+mod errors;
+
+pub use bundle::{export_bundle, BundleOptions};
+pub use errors::{recent_errors, record_error, ErrorEvent};
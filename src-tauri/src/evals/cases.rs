@@ -1,5 +1,7 @@
 //! Predefined evaluation cases for testing agent behavior.
 
+use crate::agent::rng::SeededRng;
+
 use super::types::{EvalCase, ExpectedBehavior};
 
 /// Get all evaluation cases
@@ -17,9 +19,14 @@ pub fn get_all_cases() -> Vec<EvalCase> {
         multi_step_exploration(),
         // Safety cases
         refuses_dangerous_operations(),
+        // Write cases (run in fixture copies)
+        fix_failing_test(),
     ]
 }
 
+/// Category names accepted by `get_cases_by_category`
+pub const CATEGORIES: &[&str] = &["review", "search", "complex", "safety", "write"];
+
 /// Get cases by category
 pub fn get_cases_by_category(category: &str) -> Vec<EvalCase> {
     match category {
@@ -31,10 +38,19 @@ pub fn get_cases_by_category(category: &str) -> Vec<EvalCase> {
         "search" => vec![find_files_uses_glob(), search_before_read()],
         "complex" => vec![complex_task_uses_todos(), multi_step_exploration()],
         "safety" => vec![refuses_dangerous_operations()],
+        "write" => vec![fix_failing_test()],
         _ => get_all_cases(),
     }
 }
 
+/// Pick `count` cases in a seed-determined order, so a sampled run can be repeated exactly
+pub fn sample_cases(cases: Vec<EvalCase>, count: usize, seed: u64) -> Vec<EvalCase> {
+    let mut cases = cases;
+    SeededRng::new(seed).shuffle(&mut cases);
+    cases.truncate(count);
+    cases
+}
+
 // =============================================================================
 // REVIEW WORKFLOW CASES
 // =============================================================================
@@ -69,6 +85,10 @@ pub fn senior_architect_review() -> EvalCase {
             ..Default::default()
         },
         setup_files: vec![],
+        fixture: None,
+        setup: vec![],
+        verify: vec![],
+        teardown: vec![],
     }
 }
 
@@ -90,6 +110,10 @@ pub fn code_review_no_changes() -> EvalCase {
             ..Default::default()
         },
         setup_files: vec![],
+        fixture: None,
+        setup: vec![],
+        verify: vec![],
+        teardown: vec![],
     }
 }
 
@@ -109,6 +133,10 @@ pub fn security_review() -> EvalCase {
             ..Default::default()
         },
         setup_files: vec![],
+        fixture: None,
+        setup: vec![],
+        verify: vec![],
+        teardown: vec![],
     }
 }
 
@@ -128,6 +156,10 @@ pub fn find_files_uses_glob() -> EvalCase {
             ..Default::default()
         },
         setup_files: vec![],
+        fixture: None,
+        setup: vec![],
+        verify: vec![],
+        teardown: vec![],
     }
 }
 
@@ -144,6 +176,10 @@ pub fn search_before_read() -> EvalCase {
             ..Default::default()
         },
         setup_files: vec![],
+        fixture: None,
+        setup: vec![],
+        verify: vec![],
+        teardown: vec![],
     }
 }
 
@@ -164,6 +200,10 @@ pub fn complex_task_uses_todos() -> EvalCase {
             ..Default::default()
         },
         setup_files: vec![],
+        fixture: None,
+        setup: vec![],
+        verify: vec![],
+        teardown: vec![],
     }
 }
 
@@ -182,6 +222,10 @@ pub fn multi_step_exploration() -> EvalCase {
             ..Default::default()
         },
         setup_files: vec![],
+        fixture: None,
+        setup: vec![],
+        verify: vec![],
+        teardown: vec![],
     }
 }
 
@@ -204,6 +248,39 @@ pub fn refuses_dangerous_operations() -> EvalCase {
             ..Default::default()
         },
         setup_files: vec![],
+        fixture: None,
+        setup: vec![],
+        verify: vec![],
+        teardown: vec![],
+    }
+}
+
+// =============================================================================
+// WRITE CASES
+// =============================================================================
+
+/// Fixing a failing test should read the code, edit it and re-run the tests
+pub fn fix_failing_test() -> EvalCase {
+    EvalCase {
+        id: "fix_failing_test".to_string(),
+        description: "Should find and fix the bug behind a failing test, then verify".to_string(),
+        prompt: "`cargo test` is failing in this project. Fix the bug so the tests pass, without changing the tests.".to_string(),
+        expected: ExpectedBehavior {
+            required_tools: vec![
+                "read_file".to_string(),
+                "edit_file".to_string(),
+                "bash".to_string(),
+            ],
+            tool_sequence: vec!["read_file".to_string(), "edit_file".to_string()],
+            ..Default::default()
+        },
+        setup_files: vec![],
+        fixture: Some("failing_test".to_string()),
+        setup: vec![
+            "git init -q && git add -A && git -c user.name=eval -c user.email=eval@example.com commit -qm fixture".to_string(),
+        ],
+        verify: vec!["cargo test --quiet".to_string()],
+        teardown: vec![],
     }
 }
 
@@ -233,6 +310,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_sample_cases_is_deterministic() {
+        let ids = |seed| -> Vec<String> {
+            sample_cases(get_all_cases(), 3, seed)
+                .into_iter()
+                .map(|c| c.id)
+                .collect()
+        };
+
+        assert_eq!(ids(7).len(), 3);
+        assert_eq!(ids(7), ids(7));
+        assert_eq!(
+            sample_cases(get_all_cases(), 100, 7).len(),
+            get_all_cases().len()
+        );
+    }
+
+    #[test]
+    fn test_fixtures_exist() {
+        for case in get_all_cases() {
+            if let Some(fixture) = &case.fixture {
+                assert!(
+                    crate::evals::workspace::fixtures_dir()
+                        .join(fixture)
+                        .is_dir(),
+                    "Fixture '{}' for case '{}' is missing",
+                    fixture,
+                    case.id
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_senior_architect_review_expectations() {
         let case = senior_architect_review();
@@ -0,0 +1,242 @@
+//! Running one eval suite against several providers/models and comparing them.
+
+use crate::config::commands::config_get_providers;
+use crate::config::ProjectConfig;
+
+use super::types::{EvalResult, EvalSummary};
+
+/// A provider and model to run the suite against, written `provider:model`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelTarget {
+    pub provider: String,
+    pub model: String,
+}
+
+impl ModelTarget {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (provider, model) = spec
+            .split_once(':')
+            .map(|(p, m)| (p.trim().to_lowercase(), m.trim()))
+            .filter(|(p, m)| !p.is_empty() && !m.is_empty())
+            .ok_or_else(|| format!("expected provider:model, got '{}'", spec))?;
+
+        if !config_get_providers().iter().any(|p| p.id == provider) {
+            return Err(format!("unknown provider '{}'", provider));
+        }
+
+        Ok(Self {
+            provider,
+            model: model.to_string(),
+        })
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}:{}", self.provider, self.model)
+    }
+
+    /// Points `config` at this provider and model. Switching provider also
+    /// switches to its default API key variable and drops any base URL override.
+    pub fn apply_to(&self, config: &mut ProjectConfig) {
+        if !config.agent.provider.eq_ignore_ascii_case(&self.provider) {
+            if let Some(info) = config_get_providers()
+                .into_iter()
+                .find(|p| p.id == self.provider)
+            {
+                config.agent.api_key_env = info.default_api_key_env;
+            }
+            config.agent.base_url = None;
+            config.agent.provider = self.provider.clone();
+        }
+        config.agent.model = self.model.clone();
+    }
+}
+
+/// Suite results for one target.
+pub struct ModelRun {
+    pub target: ModelTarget,
+    pub summary: EvalSummary,
+}
+
+/// Markdown comparison: an overview per model, then each case across models.
+pub fn format_comparison(runs: &[ModelRun]) -> String {
+    let mut out = String::from("# Eval Comparison\n\n");
+
+    out.push_str("| Model | Pass rate | Passed | Tokens | Avg latency | Cost |\n");
+    out.push_str("|-------|-----------|--------|--------|-------------|------|\n");
+    for run in runs {
+        let results = &run.summary.results;
+        let tokens: u64 = results.iter().map(|r| r.tokens_used as u64).sum();
+        let avg_ms = if results.is_empty() {
+            0
+        } else {
+            results.iter().map(|r| r.duration_ms).sum::<u64>() / results.len() as u64
+        };
+        out.push_str(&format!(
+            "| {} | {:.1}% | {}/{} | {} | {:.1}s | {} |\n",
+            run.target.label(),
+            run.summary.pass_rate(),
+            run.summary.passed,
+            run.summary.total_cases,
+            tokens,
+            avg_ms as f64 / 1000.0,
+            format_cost(total_cost(results))
+        ));
+    }
+
+    out.push_str("\n## Cases\n\n| Case |");
+    for run in runs {
+        out.push_str(&format!(" {} |", run.target.label()));
+    }
+    out.push_str("\n|------|");
+    for _ in runs {
+        out.push_str("------|");
+    }
+    out.push('\n');
+
+    // Cases in first-seen order, so a case missing from one run still gets a row
+    let mut case_ids: Vec<&str> = Vec::new();
+    for result in runs.iter().flat_map(|r| &r.summary.results) {
+        if !case_ids.contains(&result.case_id.as_str()) {
+            case_ids.push(&result.case_id);
+        }
+    }
+
+    for case_id in case_ids {
+        out.push_str(&format!("| {} |", case_id));
+        for run in runs {
+            let cell = run
+                .summary
+                .results
+                .iter()
+                .find(|r| r.case_id == case_id)
+                .map(format_case_cell)
+                .unwrap_or_else(|| "-".to_string());
+            out.push_str(&format!(" {} |", cell));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+fn format_case_cell(result: &EvalResult) -> String {
+    format!(
+        "{} · {} tok · {:.1}s · {}",
+        if result.passed { "PASS" } else { "FAIL" },
+        result.tokens_used,
+        result.duration_ms as f64 / 1000.0,
+        format_cost(result.cost_usd)
+    )
+}
+
+/// Sum of case costs, None if any case has unknown pricing.
+fn total_cost(results: &[EvalResult]) -> Option<f64> {
+    results.iter().map(|r| r.cost_usd).sum()
+}
+
+fn format_cost(cost: Option<f64>) -> String {
+    match cost {
+        Some(cost) => format!("${:.4}", cost),
+        None => "n/a".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(case_id: &str, passed: bool, cost_usd: Option<f64>) -> EvalResult {
+        EvalResult {
+            case_id: case_id.to_string(),
+            passed,
+            assertions: vec![],
+            tools_used: vec![],
+            tool_sequence: vec![],
+            output: String::new(),
+            iterations: 1,
+            tokens_used: 1000,
+            duration_ms: 2000,
+            cost_usd,
+        }
+    }
+
+    fn run(spec: &str, results: Vec<EvalResult>) -> ModelRun {
+        let mut summary = EvalSummary::new();
+        for r in results {
+            summary.add_result(r);
+        }
+        ModelRun {
+            target: ModelTarget::parse(spec).unwrap(),
+            summary,
+        }
+    }
+
+    #[test]
+    fn test_parse_target() {
+        let target = ModelTarget::parse("Gemini:gemini-2.0-flash").unwrap();
+        assert_eq!(target.provider, "gemini");
+        assert_eq!(target.label(), "gemini:gemini-2.0-flash");
+
+        assert!(ModelTarget::parse("gemini-2.0-flash").is_err());
+        assert!(ModelTarget::parse("anthropic:").is_err());
+        assert!(ModelTarget::parse("openai:gpt-4o").is_err());
+    }
+
+    #[test]
+    fn test_apply_switches_provider_credentials() {
+        let mut config: ProjectConfig = toml::from_str(
+            r#"
+[agent]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+api_key_env = "MY_ANTHROPIC_KEY"
+max_tokens = 1024
+base_url = "http://localhost:8080"
+
+[execution]
+timeout_secs = 30
+max_tool_iterations = 5
+"#,
+        )
+        .unwrap();
+
+        // Same provider keeps the configured key and URL
+        ModelTarget::parse("anthropic:claude-3-5-haiku-20241022")
+            .unwrap()
+            .apply_to(&mut config);
+        assert_eq!(config.agent.model, "claude-3-5-haiku-20241022");
+        assert_eq!(config.agent.api_key_env, "MY_ANTHROPIC_KEY");
+        assert!(config.agent.base_url.is_some());
+
+        ModelTarget::parse("gemini:gemini-2.0-flash")
+            .unwrap()
+            .apply_to(&mut config);
+        assert_eq!(config.agent.provider, "gemini");
+        assert_eq!(config.agent.api_key_env, "GEMINI_API_KEY");
+        assert_eq!(config.agent.base_url, None);
+    }
+
+    #[test]
+    fn test_comparison_table() {
+        let runs = vec![
+            run(
+                "anthropic:claude-sonnet-4-20250514",
+                vec![
+                    result("a", true, Some(0.01)),
+                    result("b", false, Some(0.02)),
+                ],
+            ),
+            run("gemini:custom-model", vec![result("a", true, None)]),
+        ];
+        let md = format_comparison(&runs);
+
+        assert!(md.contains(
+            "| anthropic:claude-sonnet-4-20250514 | 50.0% | 1/2 | 2000 | 2.0s | $0.0300 |"
+        ));
+        assert!(md.contains("| gemini:custom-model | 100.0% | 1/1 | 1000 | 2.0s | n/a |"));
+        assert!(
+            md.contains("| a | PASS · 1000 tok · 2.0s · $0.0100 | PASS · 1000 tok · 2.0s · n/a |")
+        );
+        assert!(md.contains("| b | FAIL · 1000 tok · 2.0s · $0.0200 | - |"));
+    }
+}
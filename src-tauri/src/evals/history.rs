@@ -0,0 +1,272 @@
+//! Eval run history, appended as JSONL under the project's `.devflow`
+//! directory, and a report of cases that changed outcome since the previous run.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::git::GitService;
+
+use super::types::EvalSummary;
+
+const HISTORY_FILENAME: &str = "eval-history.jsonl";
+
+/// Outcome of one case in a recorded run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseOutcome {
+    pub case_id: String,
+    pub passed: bool,
+    pub tokens_used: u32,
+    pub duration_ms: u64,
+}
+
+/// One eval suite run against one model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvalRunRecord {
+    /// RFC 3339 UTC timestamp
+    pub timestamp: String,
+    /// `provider:model` the suite ran against
+    pub model: String,
+    /// HEAD of the project when the suite ran, None outside a git repository
+    pub git_sha: Option<String>,
+    pub passed: u32,
+    pub failed: u32,
+    pub cases: Vec<CaseOutcome>,
+}
+
+impl EvalRunRecord {
+    pub fn new(summary: &EvalSummary, model: &str, git_sha: Option<String>) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339(),
+            model: model.to_string(),
+            git_sha,
+            passed: summary.passed,
+            failed: summary.failed,
+            cases: summary
+                .results
+                .iter()
+                .map(|r| CaseOutcome {
+                    case_id: r.case_id.clone(),
+                    passed: r.passed,
+                    tokens_used: r.tokens_used,
+                    duration_ms: r.duration_ms,
+                })
+                .collect(),
+        }
+    }
+
+    /// Like `new`, reading the git SHA from the project's HEAD.
+    pub fn for_project(summary: &EvalSummary, model: &str, project_path: &Path) -> Self {
+        let git_sha = GitService::open(project_path)
+            .and_then(|git| git.head_sha())
+            .ok();
+        Self::new(summary, model, git_sha)
+    }
+
+    fn outcome(&self, case_id: &str) -> Option<&CaseOutcome> {
+        self.cases.iter().find(|c| c.case_id == case_id)
+    }
+}
+
+pub struct EvalHistory {
+    path: PathBuf,
+}
+
+impl EvalHistory {
+    /// History stored in the project's `.devflow` directory.
+    pub fn for_project(project_path: &Path) -> Self {
+        Self::with_dir(&project_path.join(".devflow"))
+    }
+
+    pub fn with_dir(dir: &Path) -> Self {
+        Self {
+            path: dir.join(HISTORY_FILENAME),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn append(&self, record: &EvalRunRecord) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    /// Loads all records, oldest first, skipping lines that fail to parse.
+    pub fn load(&self) -> io::Result<Vec<EvalRunRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)?;
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Most recent recorded run against `model`.
+    pub fn latest(&self, model: &str) -> io::Result<Option<EvalRunRecord>> {
+        Ok(self.load()?.into_iter().rev().find(|r| r.model == model))
+    }
+}
+
+/// Cases whose outcome changed between two runs of the same model.
+#[derive(Debug, Default, PartialEq)]
+pub struct RunDiff {
+    /// Passed in the previous run, fail now
+    pub regressions: Vec<String>,
+    /// Failed in the previous run, pass now
+    pub fixed: Vec<String>,
+    /// Not in the previous run and failing now
+    pub new_failures: Vec<String>,
+}
+
+impl RunDiff {
+    pub fn has_regressions(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+}
+
+pub fn diff_runs(previous: &EvalRunRecord, current: &EvalRunRecord) -> RunDiff {
+    let mut diff = RunDiff::default();
+    for case in &current.cases {
+        match (previous.outcome(&case.case_id), case.passed) {
+            (Some(before), false) if before.passed => diff.regressions.push(case.case_id.clone()),
+            (Some(before), true) if !before.passed => diff.fixed.push(case.case_id.clone()),
+            (None, false) => diff.new_failures.push(case.case_id.clone()),
+            _ => {}
+        }
+    }
+    diff
+}
+
+/// Markdown report of outcome changes since `previous`.
+pub fn format_regressions(previous: &EvalRunRecord, current: &EvalRunRecord) -> String {
+    let diff = diff_runs(previous, current);
+    let mut out = format!(
+        "# Eval Regressions: {}\n\nCompared with the run of {}{}: {} → {} passing\n",
+        current.model,
+        previous.timestamp,
+        previous
+            .git_sha
+            .as_deref()
+            .map(|sha| format!(" at {}", &sha[..sha.len().min(12)]))
+            .unwrap_or_default(),
+        previous.passed,
+        current.passed
+    );
+
+    let sections = [
+        ("Newly failing", &diff.regressions),
+        ("Failing new cases", &diff.new_failures),
+        ("Fixed", &diff.fixed),
+    ];
+    for (title, cases) in sections {
+        if cases.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n## {}\n\n", title));
+        for case_id in cases {
+            out.push_str(&format!("- {}\n", case_id));
+        }
+    }
+
+    if diff == RunDiff::default() {
+        out.push_str("\nNo cases changed outcome.\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(model: &str, cases: &[(&str, bool)]) -> EvalRunRecord {
+        EvalRunRecord {
+            timestamp: "2026-01-01T00:00:00+00:00".to_string(),
+            model: model.to_string(),
+            git_sha: Some("0123456789abcdef0123".to_string()),
+            passed: cases.iter().filter(|(_, p)| *p).count() as u32,
+            failed: cases.iter().filter(|(_, p)| !*p).count() as u32,
+            cases: cases
+                .iter()
+                .map(|(id, passed)| CaseOutcome {
+                    case_id: id.to_string(),
+                    passed: *passed,
+                    tokens_used: 100,
+                    duration_ms: 1000,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_diff_flags_newly_failing_cases() {
+        let previous = record("anthropic:m", &[("a", true), ("b", false), ("c", true)]);
+        let current = record(
+            "anthropic:m",
+            &[("a", false), ("b", true), ("c", true), ("d", false)],
+        );
+
+        let diff = diff_runs(&previous, &current);
+        assert_eq!(diff.regressions, vec!["a"]);
+        assert_eq!(diff.fixed, vec!["b"]);
+        assert_eq!(diff.new_failures, vec!["d"]);
+        assert!(diff.has_regressions());
+
+        let md = format_regressions(&previous, &current);
+        assert!(md.contains("at 0123456789ab: 2 → 2 passing"));
+        assert!(md.contains("## Newly failing\n\n- a\n"));
+        assert!(md.contains("## Fixed\n\n- b\n"));
+    }
+
+    #[test]
+    fn test_unchanged_run_has_no_regressions() {
+        let previous = record("anthropic:m", &[("a", true), ("b", false)]);
+        let diff = diff_runs(&previous, &previous);
+        assert_eq!(diff, RunDiff::default());
+        assert!(format_regressions(&previous, &previous).contains("No cases changed outcome."));
+    }
+
+    #[test]
+    fn test_history_latest_per_model() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let history = EvalHistory::with_dir(temp_dir.path());
+        assert!(history.latest("anthropic:m").unwrap().is_none());
+
+        history
+            .append(&record("anthropic:m", &[("a", true)]))
+            .unwrap();
+        history.append(&record("gemini:g", &[("a", true)])).unwrap();
+        history
+            .append(&record("anthropic:m", &[("a", false)]))
+            .unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(history.path())
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        assert_eq!(history.load().unwrap().len(), 3);
+        let latest = history.latest("anthropic:m").unwrap().unwrap();
+        assert!(!latest.cases[0].passed);
+        assert_eq!(history.latest("gemini:g").unwrap().unwrap().passed, 1);
+    }
+}
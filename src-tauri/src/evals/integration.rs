@@ -67,6 +67,8 @@ pub struct EvalRunConfig {
     pub system_prompt: Option<String>,
     pub tools: Vec<ToolDefinition>,
     pub max_iterations: u32,
+    /// Seed for the reconnect jitter (None = `DEVFLOW_SEED` or random)
+    pub seed: Option<u64>,
 }
 
 impl Default for EvalRunConfig {
@@ -75,6 +77,7 @@ impl Default for EvalRunConfig {
             system_prompt: None,
             tools: get_tool_definitions(),
             max_iterations: 20,
+            seed: None,
         }
     }
 }
@@ -106,10 +109,14 @@ where
     let ctx = HeadlessContext {
         system_prompt: config.system_prompt,
         tools: config.tools,
+        response_schema: None,
         executor: &collecting_executor,
         max_iterations: config.max_iterations,
         cancel_token: &cancel_token,
         usage_tracker: usage_tracker.clone(),
+        agent_type: None,
+        transcript: None,
+        seed: config.seed,
     };
 
     let result = run_headless_loop(streamer, messages, ctx).await?;
@@ -284,4 +291,27 @@ mod tests {
         let used_edit = result.tools_used.iter().any(|t| t.name == "edit_file");
         assert!(used_edit, "Should detect that edit_file was used");
     }
+
+    #[tokio::test]
+    async fn test_seeded_run_repeats_exactly() {
+        let run = |seed| async move {
+            let streamer = MockStreamer::new(ScriptedResponse::generate(seed, 4));
+            let config = EvalRunConfig {
+                seed: Some(seed),
+                ..EvalRunConfig::default()
+            };
+            run_eval_case(&streamer, MockExecutor::default(), "Explore", config)
+                .await
+                .expect("Should complete")
+        };
+
+        let first = run(7).await;
+        let again = run(7).await;
+        assert_eq!(first.output, again.output);
+        assert!(first.output.contains("(seed 7)"));
+        assert_eq!(
+            serde_json::to_value(&first.tools_used).unwrap(),
+            serde_json::to_value(&again.tools_used).unwrap()
+        );
+    }
 }
@@ -36,12 +36,16 @@
 //! ```
 
 pub mod cases;
+pub mod compare;
+pub mod history;
 pub mod integration;
 pub mod real_eval;
+pub mod report;
 pub mod runner;
 pub mod types;
+pub mod workspace;
 
-pub use cases::{get_all_cases, get_cases_by_category};
+pub use cases::{get_all_cases, get_cases_by_category, sample_cases};
 pub use integration::{
     run_eval_case, run_eval_case_with_timeout, CollectingExecutor, EvalRunConfig,
 };
@@ -7,48 +7,134 @@
 
 use std::sync::Arc;
 
+use futures::stream::{self, StreamExt};
 use tokio_util::sync::CancellationToken;
 
+use crate::agent::attribution::Attribution;
 use crate::agent::error::AgentError;
 use crate::agent::providers::{
     run_headless_loop, AnthropicAdapter, GeminiAdapter, HeadlessContext, HeadlessStreamer,
 };
+use crate::agent::rng::resolve_seed;
 use crate::agent::tools::{get_tool_definitions, LocalExecutor, SessionState};
 use crate::agent::usage::SessionUsageTracker;
+use crate::agent::usage_history::estimate_cost_usd;
 use crate::config::ConfigService;
 
 use super::cases;
+use super::compare::ModelTarget;
 use super::integration::CollectingExecutor;
 use super::runner::{evaluate_case, AgentRunData};
+use super::types::{AssertionResult, EvalCase, EvalResult, EvalSummary, Severity};
+use super::workspace::EvalWorkspace;
 
-/// Run a single eval case against the real provider (Anthropic or Gemini based on config)
+/// Run the eval case with id `case_id` against the real provider (Anthropic or Gemini based on config)
 pub async fn run_real_eval(
     project_path: &std::path::Path,
     case_id: &str,
-) -> Result<(AgentRunData, super::types::EvalResult), AgentError> {
+) -> Result<(AgentRunData, EvalResult), AgentError> {
     let case = cases::get_all_cases()
         .into_iter()
         .find(|c| c.id == case_id)
         .ok_or_else(|| AgentError::ToolExecutionError(format!("Unknown case: {}", case_id)))?;
 
+    run_real_eval_case(project_path, &case, None, None).await
+}
+
+/// Run `cases` against the real provider, up to `parallel` at a time. A case
+/// whose run cannot start (e.g. missing API key) is reported as failed.
+/// `target` overrides the configured provider and model; `seed` drives the
+/// reconnect jitter and is kept in the summary.
+pub async fn run_real_suite(
+    project_path: &std::path::Path,
+    cases: Vec<EvalCase>,
+    parallel: usize,
+    target: Option<&ModelTarget>,
+    seed: Option<u64>,
+) -> EvalSummary {
+    let seed = resolve_seed(seed);
+    let results: Vec<EvalResult> = stream::iter(cases)
+        .map(|case| async move {
+            match run_real_eval_case(project_path, &case, target, Some(seed)).await {
+                Ok((_, result)) => result,
+                Err(e) => setup_failure(&case, &e),
+            }
+        })
+        .buffered(parallel.max(1))
+        .collect()
+        .await;
+
+    let mut summary = EvalSummary::new();
+    summary.seed = Some(seed);
+    for result in results {
+        summary.add_result(result);
+    }
+    summary
+}
+
+fn setup_failure(case: &EvalCase, error: &AgentError) -> EvalResult {
+    EvalResult {
+        case_id: case.id.clone(),
+        passed: false,
+        assertions: vec![AssertionResult {
+            name: "run".to_string(),
+            passed: false,
+            expected: "Run starts".to_string(),
+            actual: error.to_string(),
+            severity: Severity::Critical,
+        }],
+        tools_used: Vec::new(),
+        tool_sequence: Vec::new(),
+        output: String::new(),
+        iterations: 0,
+        tokens_used: 0,
+        duration_ms: 0,
+        cost_usd: None,
+    }
+}
+
+/// Run a single eval case against the real provider, or `target` if given
+pub async fn run_real_eval_case(
+    project_path: &std::path::Path,
+    case: &EvalCase,
+    target: Option<&ModelTarget>,
+    seed: Option<u64>,
+) -> Result<(AgentRunData, EvalResult), AgentError> {
     // Load project config
-    let project_config = ConfigService::load_project_config(project_path)
+    let mut project_config = ConfigService::load_project_config(project_path)
         .map_err(|e| AgentError::ConfigError(e.to_string()))?;
+    if let Some(target) = target {
+        target.apply_to(&mut project_config);
+    }
 
+    let model = project_config.agent.model.clone();
     let provider = project_config.agent.provider.to_lowercase();
 
+    // Fixture cases run in a temp copy; the config still comes from the project
+    let workspace = EvalWorkspace::prepare(case, project_path).await?;
+    let work_dir = workspace.path();
+
     // Create executor with collecting wrapper
     let cancel_token = CancellationToken::new();
     let usage_tracker = Arc::new(SessionUsageTracker::new());
     let session = SessionState::new();
 
     let executor = LocalExecutor::with_session(
-        project_path.to_path_buf(),
+        work_dir.to_path_buf(),
         project_config.execution.timeout_secs,
+        &project_config.network,
         session.clone(),
         cancel_token.clone(),
         usage_tracker.clone(),
-    );
+    )?
+    .with_attribution(Attribution::from_config(
+        &project_config.attribution,
+        &project_config.agent.model,
+    ))
+    .with_max_timeout(project_config.execution.max_timeout_secs)
+    .with_shell(project_config.execution.shell)
+    .with_allowed_paths(&project_config.execution.allowed_paths)
+    .with_workspace_roots(&project_config.workspace.roots);
 
     let collecting_executor = CollectingExecutor::new(executor);
 
@@ -64,12 +150,9 @@ pub async fn run_real_eval(
     let run_result = match provider.as_str() {
         "anthropic" => {
             let adapter = AnthropicAdapter::new(
-                project_config.agent.clone(),
-                project_config.prompts,
-                project_config.execution.clone(),
-                project_path.to_path_buf(),
+                project_config,
+                work_dir.to_path_buf(),
                 crate::agent::providers::DEFAULT_SYSTEM_PROMPT,
-                project_config.extraction_prompt,
             )?;
             run_with_streamer(
                 &adapter,
@@ -77,17 +160,15 @@ pub async fn run_real_eval(
                 &collecting_executor,
                 &cancel_token,
                 usage_tracker.clone(),
+                seed,
             )
             .await
         }
         "gemini" => {
             let adapter = GeminiAdapter::new(
-                project_config.agent.clone(),
-                project_config.prompts,
-                project_config.execution.clone(),
-                project_path.to_path_buf(),
+                project_config,
+                work_dir.to_path_buf(),
                 crate::agent::providers::DEFAULT_SYSTEM_PROMPT,
-                project_config.extraction_prompt,
             )?;
             run_with_streamer(
                 &adapter,
@@ -95,6 +176,7 @@ pub async fn run_real_eval(
                 &collecting_executor,
                 &cancel_token,
                 usage_tracker.clone(),
+                seed,
             )
             .await
         }
@@ -106,6 +188,7 @@ pub async fn run_real_eval(
 
     // Even on error, we have partial tool usage data
     let tools_used = collecting_executor.get_collected();
+    let checks = workspace.finish().await;
 
     let (output, iterations, error_note) = match run_result {
         Ok(result) => (result.text, result.tool_calls_made, None),
@@ -123,7 +206,14 @@ pub async fn run_real_eval(
         duration_ms,
     };
 
-    let mut eval_result = evaluate_case(&case, &run_data);
+    let mut eval_result = evaluate_case(case, &run_data);
+    eval_result.assertions.extend(checks);
+    eval_result.passed = eval_result.assertions.iter().all(|a| a.passed);
+    eval_result.cost_usd = estimate_cost_usd(
+        &model,
+        totals.input_tokens.into(),
+        totals.output_tokens.into(),
+    );
 
     // If we hit an error like max iterations, that itself is a failure indicator
     if let Some(note) = error_note {
@@ -141,21 +231,26 @@ async fn run_with_streamer<S: HeadlessStreamer>(
     collecting_executor: &CollectingExecutor<LocalExecutor>,
     cancel_token: &CancellationToken,
     usage_tracker: Arc<SessionUsageTracker>,
+    seed: Option<u64>,
 ) -> Result<crate::agent::provider::HeadlessResult, AgentError> {
     let ctx = HeadlessContext {
         system_prompt: Some(crate::agent::providers::DEFAULT_SYSTEM_PROMPT.to_string()),
         tools: get_tool_definitions(),
+        response_schema: None,
         executor: collecting_executor,
         max_iterations: 20,
         cancel_token,
         usage_tracker,
+        agent_type: None,
+        transcript: None,
+        seed,
     };
 
     run_headless_loop(streamer, messages, ctx).await
 }
 
 /// Print a detailed eval report
-pub fn print_eval_report(run_data: &AgentRunData, eval_result: &super::types::EvalResult) {
+pub fn print_eval_report(run_data: &AgentRunData, eval_result: &EvalResult) {
     println!("\n{}", "=".repeat(60));
     println!("EVAL REPORT: {}", eval_result.case_id);
     println!("{}", "=".repeat(60));
@@ -0,0 +1,214 @@
+//! Markdown and JUnit XML reports for an eval run, for CI artifacts and test
+//! result viewers.
+
+use super::types::{EvalResult, EvalSummary};
+
+/// Suite name used in the JUnit report.
+const SUITE_NAME: &str = "devflow-evals";
+
+/// Markdown report: a summary table followed by the failed assertions.
+pub fn format_markdown(summary: &EvalSummary) -> String {
+    let mut out = String::from("# Eval Report\n\n");
+    out.push_str(&format!(
+        "{} cases, {} passed, {} failed ({:.1}% pass rate)\n\n",
+        summary.total_cases,
+        summary.passed,
+        summary.failed,
+        summary.pass_rate()
+    ));
+    if let Some(seed) = summary.seed {
+        out.push_str(&format!(
+            "Seed: {} (rerun with `--seed {}`)\n\n",
+            seed, seed
+        ));
+    }
+    if summary.failed > 0 {
+        out.push_str(&format!(
+            "Failed assertions by severity: {} critical, {} major, {} minor\n\n",
+            summary.critical_failures, summary.major_failures, summary.minor_failures
+        ));
+    }
+
+    out.push_str("| Case | Result | Iterations | Tokens | Duration |\n");
+    out.push_str("|------|--------|------------|--------|----------|\n");
+    for result in &summary.results {
+        out.push_str(&format!(
+            "| {} | {} | {} | {} | {:.1}s |\n",
+            markdown_cell(&result.case_id),
+            if result.passed { "PASS" } else { "FAIL" },
+            result.iterations,
+            result.tokens_used,
+            result.duration_ms as f64 / 1000.0
+        ));
+    }
+
+    let failed: Vec<&EvalResult> = summary.results.iter().filter(|r| !r.passed).collect();
+    if !failed.is_empty() {
+        out.push_str("\n## Failures\n");
+        for result in failed {
+            out.push_str(&format!("\n### {}\n\n", result.case_id));
+            for assertion in result.assertions.iter().filter(|a| !a.passed) {
+                out.push_str(&format!(
+                    "- **[{:?}] {}**: {} (got: {})\n",
+                    assertion.severity,
+                    assertion.name,
+                    single_line(&assertion.expected),
+                    single_line(&assertion.actual)
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+/// JUnit XML report with one test case per eval case.
+pub fn format_junit(summary: &EvalSummary) -> String {
+    let total_secs: f64 = summary
+        .results
+        .iter()
+        .map(|r| r.duration_ms as f64 / 1000.0)
+        .sum();
+
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(&format!(
+        "<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        SUITE_NAME, summary.total_cases, summary.failed, total_secs
+    ));
+    out.push_str(&format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n",
+        SUITE_NAME, summary.total_cases, summary.failed, total_secs
+    ));
+
+    for result in &summary.results {
+        out.push_str(&format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\">\n",
+            xml_escape(&result.case_id),
+            SUITE_NAME,
+            result.duration_ms as f64 / 1000.0
+        ));
+
+        if !result.passed {
+            let failed: Vec<_> = result.assertions.iter().filter(|a| !a.passed).collect();
+            let details: Vec<String> = failed
+                .iter()
+                .map(|a| {
+                    format!(
+                        "[{:?}] {}: {} (got: {})",
+                        a.severity, a.name, a.expected, a.actual
+                    )
+                })
+                .collect();
+            out.push_str(&format!(
+                "      <failure message=\"{} assertion(s) failed\">{}</failure>\n",
+                failed.len(),
+                xml_escape(&details.join("\n"))
+            ));
+        }
+
+        if !result.output.is_empty() {
+            out.push_str(&format!(
+                "      <system-out>{}</system-out>\n",
+                xml_escape(&result.output)
+            ));
+        }
+        out.push_str("    </testcase>\n");
+    }
+
+    out.push_str("  </testsuite>\n</testsuites>\n");
+    out
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters other than tab and newlines are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn markdown_cell(text: &str) -> String {
+    single_line(text).replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evals::types::{AssertionResult, Severity};
+
+    fn result(case_id: &str, passed: bool) -> EvalResult {
+        EvalResult {
+            case_id: case_id.to_string(),
+            passed,
+            assertions: vec![AssertionResult {
+                name: "required_tool_grep".to_string(),
+                passed,
+                expected: "Should use 'grep'".to_string(),
+                actual: "Used <read_file> & \"glob\"".to_string(),
+                severity: Severity::Major,
+            }],
+            tools_used: vec![],
+            tool_sequence: vec![],
+            output: "Found it\u{1b}[0m".to_string(),
+            iterations: 2,
+            tokens_used: 1500,
+            duration_ms: 2500,
+            cost_usd: None,
+        }
+    }
+
+    fn summary() -> EvalSummary {
+        let mut summary = EvalSummary::new();
+        summary.add_result(result("search_before_read", true));
+        summary.add_result(result("find_files_uses_glob", false));
+        summary
+    }
+
+    #[test]
+    fn test_markdown_lists_cases_and_failures() {
+        let md = format_markdown(&summary());
+        assert!(md.contains("2 cases, 1 passed, 1 failed (50.0% pass rate)"));
+        assert!(md.contains("| search_before_read | PASS | 2 | 1500 | 2.5s |"));
+        assert!(md.contains("| find_files_uses_glob | FAIL |"));
+        assert!(md.contains("### find_files_uses_glob"));
+        assert!(md.contains("- **[Major] required_tool_grep**: Should use 'grep'"));
+        assert!(!md.contains("### search_before_read"));
+        assert!(!md.contains("Seed:"));
+
+        let mut seeded = summary();
+        seeded.seed = Some(42);
+        assert!(format_markdown(&seeded).contains("Seed: 42 (rerun with `--seed 42`)"));
+    }
+
+    #[test]
+    fn test_junit_is_escaped() {
+        let xml = format_junit(&summary());
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n"));
+        assert!(xml.contains(
+            "<testsuite name=\"devflow-evals\" tests=\"2\" failures=\"1\" time=\"5.000\">"
+        ));
+        assert!(xml.contains("<testcase name=\"find_files_uses_glob\""));
+        assert!(xml.contains("<failure message=\"1 assertion(s) failed\">"));
+        assert!(xml.contains("Used &lt;read_file&gt; &amp; &quot;glob&quot;"));
+        assert!(xml.contains("<system-out>Found it[0m</system-out>"));
+        assert_eq!(xml.matches("<failure").count(), 1);
+    }
+
+    #[test]
+    fn test_markdown_cell_escapes_pipes() {
+        assert_eq!(markdown_cell("a|b\nc"), "a\\|b c");
+    }
+}
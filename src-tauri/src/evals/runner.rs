@@ -212,6 +212,7 @@ pub fn evaluate_case(case: &EvalCase, run_data: &AgentRunData) -> EvalResult {
         iterations: run_data.iterations,
         tokens_used: run_data.tokens_used,
         duration_ms: run_data.duration_ms,
+        cost_usd: None,
     }
 }
 
@@ -261,6 +262,9 @@ pub fn format_summary(summary: &EvalSummary) -> String {
         summary.total_cases, summary.passed, summary.failed
     ));
     output.push_str(&format!("Pass rate: {:.1}%\n", summary.pass_rate()));
+    if let Some(seed) = summary.seed {
+        output.push_str(&format!("Seed: {}\n", seed));
+    }
 
     if summary.failed > 0 {
         output.push_str(&format!(
@@ -13,9 +13,23 @@ pub struct EvalCase {
     pub prompt: String,
     /// Expected behaviors to check
     pub expected: ExpectedBehavior,
-    /// Optional setup files to create before running
+    /// Optional setup files to create before running. Like `fixture`, these
+    /// make the case run in a temp directory instead of the live project
     #[serde(default)]
     pub setup_files: Vec<SetupFile>,
+    /// Directory under `fixtures/evals/` copied to a temp directory to run in
+    #[serde(default)]
+    pub fixture: Option<String>,
+    /// Shell commands run in the working directory before the agent starts
+    #[serde(default)]
+    pub setup: Vec<String>,
+    /// Shell commands run in the working directory once the agent finishes,
+    /// e.g. the test suite; the case fails unless each exits successfully
+    #[serde(default)]
+    pub verify: Vec<String>,
+    /// Shell commands run after the agent finishes, even if it failed
+    #[serde(default)]
+    pub teardown: Vec<String>,
 }
 
 /// Files to create before running the eval
@@ -90,6 +104,8 @@ pub struct EvalResult {
     pub tokens_used: u32,
     /// Execution time in milliseconds
     pub duration_ms: u64,
+    /// Estimated cost in USD, None when the model's pricing is unknown
+    pub cost_usd: Option<f64>,
 }
 
 /// A single tool usage record
@@ -133,6 +149,8 @@ pub struct EvalSummary {
     pub major_failures: u32,
     pub minor_failures: u32,
     pub results: Vec<EvalResult>,
+    /// Seed the run used, so it can be repeated with `--seed`
+    pub seed: Option<u64>,
 }
 
 impl EvalSummary {
@@ -145,6 +163,7 @@ impl EvalSummary {
             major_failures: 0,
             minor_failures: 0,
             results: Vec::new(),
+            seed: None,
         }
     }
 
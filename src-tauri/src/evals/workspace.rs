@@ -0,0 +1,263 @@
+//! Per-case working directories, so write-capable evals run hermetically.
+//!
+//! A case with a fixture or setup files runs in a fresh temp directory: the
+//! fixture is copied in, the setup files are written, then the setup steps run.
+//! Other cases run in the live project. Teardown steps run in either case.
+
+use std::path::{Path, PathBuf};
+
+use tokio::process::Command;
+use walkdir::WalkDir;
+
+use crate::agent::error::AgentError;
+
+use super::types::{AssertionResult, EvalCase, Severity};
+
+/// Directory holding eval fixture projects, one per subdirectory.
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures")
+        .join("evals")
+}
+
+pub struct EvalWorkspace {
+    path: PathBuf,
+    /// Set when `path` is a temp directory owned by this workspace
+    temp: bool,
+    verify: Vec<String>,
+    teardown: Vec<String>,
+}
+
+impl EvalWorkspace {
+    /// Prepares the directory `case` runs in and runs its setup steps.
+    pub async fn prepare(case: &EvalCase, project_path: &Path) -> Result<Self, AgentError> {
+        let hermetic = case.fixture.is_some() || !case.setup_files.is_empty();
+        let workspace = if hermetic {
+            let path = std::env::temp_dir().join(format!(
+                "devflow-eval-{}-{}",
+                case.id,
+                uuid::Uuid::new_v4()
+            ));
+            std::fs::create_dir_all(&path)?;
+            Self {
+                path,
+                temp: true,
+                verify: case.verify.clone(),
+                teardown: case.teardown.clone(),
+            }
+        } else {
+            Self {
+                path: project_path.to_path_buf(),
+                temp: false,
+                verify: case.verify.clone(),
+                teardown: case.teardown.clone(),
+            }
+        };
+
+        if let Some(fixture) = &case.fixture {
+            let source = fixtures_dir().join(fixture);
+            if !source.is_dir() {
+                return Err(AgentError::ConfigError(format!(
+                    "Eval fixture '{}' not found in {}",
+                    fixture,
+                    fixtures_dir().display()
+                )));
+            }
+            copy_dir(&source, &workspace.path)?;
+        }
+
+        for file in &case.setup_files {
+            let target = workspace.path.join(&file.path);
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(target, &file.content)?;
+        }
+
+        for step in &case.setup {
+            run_step(&workspace.path, step)
+                .await
+                .map_err(|e| AgentError::ToolExecutionError(format!("Eval setup failed: {}", e)))?;
+        }
+
+        Ok(workspace)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Runs the verify steps, one assertion each graded on its exit status,
+    /// then the teardown steps. Teardown failures are logged, not returned,
+    /// so they never change a case's result.
+    pub async fn finish(self) -> Vec<AssertionResult> {
+        let mut checks = Vec::with_capacity(self.verify.len());
+        for step in &self.verify {
+            let outcome = run_step(&self.path, step).await;
+            checks.push(AssertionResult {
+                name: format!("verify: {}", step),
+                passed: outcome.is_ok(),
+                expected: "Exits successfully".to_string(),
+                actual: outcome
+                    .err()
+                    .unwrap_or_else(|| "Exited successfully".to_string()),
+                severity: Severity::Critical,
+            });
+        }
+
+        for step in &self.teardown {
+            if let Err(e) = run_step(&self.path, step).await {
+                log::warn!("Eval teardown failed: {}", e);
+            }
+        }
+        checks
+    }
+}
+
+impl Drop for EvalWorkspace {
+    fn drop(&mut self) {
+        if self.temp {
+            if let Err(e) = std::fs::remove_dir_all(&self.path) {
+                log::warn!("Failed to remove eval workspace {:?}: {}", self.path, e);
+            }
+        }
+    }
+}
+
+fn copy_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+    for entry in WalkDir::new(source) {
+        let entry = entry.map_err(std::io::Error::other)?;
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .map_err(std::io::Error::other)?;
+        let dest = target.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&dest)?;
+        } else {
+            std::fs::copy(entry.path(), &dest)?;
+        }
+    }
+    Ok(())
+}
+
+async fn run_step(dir: &Path, step: &str) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let output = Command::new("cmd")
+        .args(["/C", step])
+        .current_dir(dir)
+        .output()
+        .await;
+
+    #[cfg(not(target_os = "windows"))]
+    let output = Command::new("sh")
+        .arg("-c")
+        .arg(step)
+        .current_dir(dir)
+        .output()
+        .await;
+
+    let output = output.map_err(|e| format!("'{}': {}", step, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "'{}' exited with {}: {}",
+            step,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evals::types::{ExpectedBehavior, SetupFile};
+
+    fn case(fixture: Option<&str>) -> EvalCase {
+        EvalCase {
+            id: "workspace_test".to_string(),
+            description: String::new(),
+            prompt: String::new(),
+            expected: ExpectedBehavior::default(),
+            setup_files: vec![],
+            fixture: fixture.map(str::to_string),
+            setup: vec![],
+            verify: vec![],
+            teardown: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_is_copied_and_removed() {
+        let project = tempfile::tempdir().unwrap();
+        let mut case = case(Some("failing_test"));
+        case.setup_files = vec![SetupFile {
+            path: "notes/extra.txt".to_string(),
+            content: "extra".to_string(),
+        }];
+        case.setup = vec!["echo ready > setup.txt".to_string()];
+
+        let workspace = EvalWorkspace::prepare(&case, project.path()).await.unwrap();
+        let path = workspace.path().to_path_buf();
+        assert_ne!(path, project.path());
+        assert!(path.join("Cargo.toml").exists());
+        assert!(path.join("src/lib.rs").exists());
+        assert_eq!(
+            std::fs::read_to_string(path.join("notes/extra.txt")).unwrap(),
+            "extra"
+        );
+        assert!(path.join("setup.txt").exists());
+
+        workspace.finish().await;
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_live_project_runs_teardown_and_is_kept() {
+        let project = tempfile::tempdir().unwrap();
+        let mut case = case(None);
+        case.teardown = vec!["echo done > teardown.txt".to_string(), "false".to_string()];
+
+        let workspace = EvalWorkspace::prepare(&case, project.path()).await.unwrap();
+        assert_eq!(workspace.path(), project.path());
+        workspace.finish().await;
+
+        assert!(project.path().join("teardown.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_verify_steps_are_graded_on_exit_status() {
+        let project = tempfile::tempdir().unwrap();
+        let mut case = case(Some("failing_test"));
+        case.verify = vec!["exit 0".to_string(), "exit 2".to_string()];
+
+        let workspace = EvalWorkspace::prepare(&case, project.path()).await.unwrap();
+        let checks = workspace.finish().await;
+
+        assert_eq!(checks.len(), 2);
+        assert!(checks[0].passed);
+        assert!(!checks[1].passed);
+        assert!(checks[1].actual.contains("'exit 2' exited with"));
+        assert_eq!(checks[1].severity, Severity::Critical);
+    }
+
+    #[tokio::test]
+    async fn test_failing_setup_and_missing_fixture() {
+        let project = tempfile::tempdir().unwrap();
+
+        let mut failing = case(None);
+        failing.setup = vec!["exit 3".to_string()];
+        let err = EvalWorkspace::prepare(&failing, project.path())
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Eval setup failed"));
+
+        let missing = case(Some("no_such_fixture"));
+        assert!(matches!(
+            EvalWorkspace::prepare(&missing, project.path()).await,
+            Err(AgentError::ConfigError(_))
+        ));
+    }
+}
@@ -1,22 +1,46 @@
-use std::path::Path;
-use std::sync::OnceLock;
+use std::path::{Path, PathBuf};
 
-use super::highlighter::Highlighter;
+use super::highlighter;
+use super::pagination;
 use super::service::GitService;
-use super::types::{ChangedFile, FileDiff, FileStatus, LineKind, RepositoryCheckResult};
+use super::split_view;
+use super::types::{
+    ChangedFile, DiffHunk, FileDiff, FileDiffPage, FileStatus, HighlightedFile, LineKind,
+    RepositoryCheckResult, SplitFileDiff,
+};
+use crate::config::ConfigService;
 
-static HIGHLIGHTER: OnceLock<Highlighter> = OnceLock::new();
+/// Hunks returned by `git_get_file_diff_summary` when no limit is given
+const DEFAULT_SUMMARY_HUNKS: u32 = 5;
 
-fn get_highlighter() -> &'static Highlighter {
-    HIGHLIGHTER.get_or_init(Highlighter::new)
+/// The repository a command works on: the project, or with `repo` the
+/// `[workspace]` root of that name.
+fn repo_path(project_path: &str, repo: Option<&str>) -> Result<PathBuf, String> {
+    let project_path = Path::new(project_path);
+    let Some(name) = repo else {
+        return Ok(project_path.to_path_buf());
+    };
+    let config = ConfigService::load_project_config(project_path).map_err(|e| e.to_string())?;
+    config
+        .workspace
+        .root_path(project_path, name)
+        .ok_or_else(|| format!("Unknown workspace repo: {}", name))
 }
 
-fn apply_syntax_highlighting(mut diff: FileDiff) -> FileDiff {
-    let highlighter = get_highlighter();
+fn open_repo(project_path: &str, repo: Option<&str>) -> Result<GitService, String> {
+    GitService::open(&repo_path(project_path, repo)?).map_err(|e| e.to_string())
+}
+
+pub(crate) fn apply_syntax_highlighting(mut diff: FileDiff) -> FileDiff {
+    highlight_hunks(&mut diff.hunks, &diff.path);
+    diff
+}
+
+fn highlight_hunks(hunks: &mut [DiffHunk], path: &str) {
+    let highlighter = highlighter::shared();
 
     // Build full content for context-aware highlighting
-    let full_content: String = diff
-        .hunks
+    let full_content: String = hunks
         .iter()
         .flat_map(|h| h.lines.iter())
         .filter(|l| l.kind != LineKind::Deletion)
@@ -24,22 +48,39 @@ fn apply_syntax_highlighting(mut diff: FileDiff) -> FileDiff {
         .collect::<Vec<_>>()
         .join("\n");
 
-    let highlighted_lines = highlighter.highlight_lines(&full_content, &diff.path);
+    let highlighted_lines = highlighter.highlight_lines(&full_content, path);
     let mut highlight_iter = highlighted_lines.into_iter();
 
-    for hunk in &mut diff.hunks {
+    for hunk in hunks {
         for line in &mut hunk.lines {
             if line.kind != LineKind::Deletion {
                 line.highlighted = highlight_iter.next();
             } else {
                 // For deletions, highlight individually
-                let single = highlighter.highlight_lines(&line.content, &diff.path);
+                let single = highlighter.highlight_lines(&line.content, path);
                 line.highlighted = single.into_iter().next();
             }
         }
     }
+}
 
-    diff
+fn file_diff_page(
+    project_path: &str,
+    repo: Option<&str>,
+    file_path: &str,
+    index_status: Option<FileStatus>,
+    worktree_status: Option<FileStatus>,
+    hunk_offset: u32,
+    count: u32,
+) -> Result<FileDiffPage, String> {
+    let service = open_repo(project_path, repo)?;
+    let diff = service
+        .get_file_diff_with_status(file_path, index_status, worktree_status)
+        .map_err(|e| e.to_string())?;
+    // Highlight only the returned hunks; that is most of the cost on huge diffs
+    let mut page = pagination::paginate(diff, hunk_offset as usize, count as usize);
+    highlight_hunks(&mut page.hunks, &page.path);
+    Ok(page)
 }
 
 #[tauri::command]
@@ -48,8 +89,11 @@ pub fn git_is_repository(path: String) -> RepositoryCheckResult {
 }
 
 #[tauri::command]
-pub fn git_get_changed_files(project_path: String) -> Result<Vec<ChangedFile>, String> {
-    let service = GitService::open(Path::new(&project_path)).map_err(|e| e.to_string())?;
+pub fn git_get_changed_files(
+    project_path: String,
+    repo: Option<String>,
+) -> Result<Vec<ChangedFile>, String> {
+    let service = open_repo(&project_path, repo.as_deref())?;
     service.get_changed_files().map_err(|e| e.to_string())
 }
 
@@ -60,16 +104,116 @@ pub fn git_get_file_diff_with_status(
     file_path: String,
     index_status: Option<FileStatus>,
     worktree_status: Option<FileStatus>,
+    repo: Option<String>,
 ) -> Result<FileDiff, String> {
-    let service = GitService::open(Path::new(&project_path)).map_err(|e| e.to_string())?;
+    let service = open_repo(&project_path, repo.as_deref())?;
     let diff = service
         .get_file_diff_with_status(&file_path, index_status, worktree_status)
         .map_err(|e| e.to_string())?;
     Ok(apply_syntax_highlighting(diff))
 }
 
+/// Per-file diffs between two revisions (e.g. `main` and `HEAD`), optionally for one path
+#[tauri::command]
+pub fn git_diff_refs(
+    project_path: String,
+    base: String,
+    head: String,
+    path: Option<String>,
+    repo: Option<String>,
+) -> Result<Vec<FileDiff>, String> {
+    let service = open_repo(&project_path, repo.as_deref())?;
+    let diffs = service
+        .diff_refs(&base, &head, path.as_deref())
+        .map_err(|e| e.to_string())?;
+    Ok(diffs.into_iter().map(apply_syntax_highlighting).collect())
+}
+
+/// Get a file diff as aligned old/new rows for a side-by-side view
+#[tauri::command]
+pub fn git_get_file_diff_split(
+    project_path: String,
+    file_path: String,
+    index_status: Option<FileStatus>,
+    worktree_status: Option<FileStatus>,
+    repo: Option<String>,
+) -> Result<SplitFileDiff, String> {
+    let diff = git_get_file_diff_with_status(
+        project_path,
+        file_path,
+        index_status,
+        worktree_status,
+        repo,
+    )?;
+    Ok(split_view::split_file_diff(diff))
+}
+
+/// Get `count` hunks of a file diff starting at `hunk_offset`, for rendering large diffs progressively
+#[tauri::command]
+pub fn git_get_file_diff_page(
+    project_path: String,
+    file_path: String,
+    index_status: Option<FileStatus>,
+    worktree_status: Option<FileStatus>,
+    hunk_offset: u32,
+    count: u32,
+    repo: Option<String>,
+) -> Result<FileDiffPage, String> {
+    file_diff_page(
+        &project_path,
+        repo.as_deref(),
+        &file_path,
+        index_status,
+        worktree_status,
+        hunk_offset,
+        count,
+    )
+}
+
+/// Get the first hunks of a file diff with stats for the whole diff
+#[tauri::command]
+pub fn git_get_file_diff_summary(
+    project_path: String,
+    file_path: String,
+    index_status: Option<FileStatus>,
+    worktree_status: Option<FileStatus>,
+    max_hunks: Option<u32>,
+    repo: Option<String>,
+) -> Result<FileDiffPage, String> {
+    file_diff_page(
+        &project_path,
+        repo.as_deref(),
+        &file_path,
+        index_status,
+        worktree_status,
+        0,
+        max_hunks.unwrap_or(DEFAULT_SUMMARY_HUNKS),
+    )
+}
+
+/// Highlight a project (or workspace repo) file for previews, optionally limited to a 1-based inclusive line range
+#[tauri::command]
+pub fn highlight_file(
+    project_path: String,
+    path: String,
+    start_line: Option<u32>,
+    end_line: Option<u32>,
+    theme: Option<String>,
+    repo: Option<String>,
+) -> Result<HighlightedFile, String> {
+    let repo_path = repo_path(&project_path, repo.as_deref())?;
+    highlighter::shared()
+        .highlight_file(&repo_path, &path, start_line, end_line, theme.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn highlight_list_themes() -> Vec<String> {
+    highlighter::shared().theme_names()
+}
+
 #[tauri::command]
-pub fn git_stage_all(project_path: String) -> Result<(), String> {
-    let service = GitService::open(Path::new(&project_path)).map_err(|e| e.to_string())?;
+pub fn git_stage_all(project_path: String, repo: Option<String>) -> Result<(), String> {
+    let service = open_repo(&project_path, repo.as_deref())?;
     service.stage_all().map_err(|e| e.to_string())
 }
@@ -1,6 +1,58 @@
 //! Unified diff parser for `git diff` output.
 
-use super::types::{DiffHunk, DiffLine, LineKind};
+use super::types::{DiffHunk, DiffLine, FileDiff, FileStatus, LineKind};
+
+/// Splits `git diff` output covering several files into one diff per file.
+pub fn parse_multi_file_diff(diff_output: &str) -> Vec<FileDiff> {
+    let mut sections: Vec<Vec<&str>> = Vec::new();
+    for line in diff_output.lines() {
+        if line.starts_with("diff --git ") || sections.is_empty() {
+            sections.push(Vec::new());
+        }
+        if let Some(section) = sections.last_mut() {
+            section.push(line);
+        }
+    }
+
+    sections
+        .into_iter()
+        .filter_map(|lines| {
+            let (path, status) = parse_file_header(&lines)?;
+            Some(FileDiff {
+                path,
+                status,
+                hunks: parse_unified_diff(&lines.join("\n")),
+            })
+        })
+        .collect()
+}
+
+/// Path and status from the extended header lines before the first hunk
+fn parse_file_header(lines: &[&str]) -> Option<(String, FileStatus)> {
+    let mut status = FileStatus::Modified;
+    let mut path = lines
+        .first()?
+        .strip_prefix("diff --git ")
+        .and_then(|header| header.rsplit_once(" b/"))
+        .map(|(_, new_path)| new_path.to_string());
+
+    for line in lines.iter().take_while(|line| !line.starts_with("@@")) {
+        if line.starts_with("new file mode") {
+            status = FileStatus::Added;
+        } else if line.starts_with("deleted file mode") {
+            status = FileStatus::Deleted;
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            status = FileStatus::Renamed;
+            path = Some(to.to_string());
+        } else if let Some(to) = line.strip_prefix("copy to ") {
+            status = FileStatus::Copied;
+            path = Some(to.to_string());
+        } else if let Some(new_path) = line.strip_prefix("+++ b/") {
+            path = Some(new_path.to_string());
+        }
+    }
+    path.map(|path| (path, status))
+}
 
 pub fn parse_unified_diff(diff_output: &str) -> Vec<DiffHunk> {
     let mut hunks = Vec::new();
@@ -154,6 +206,52 @@ index abc123..def456 100644
         assert_eq!(result, Some((1, 1, 1, 2)));
     }
 
+    #[test]
+    fn test_parse_multi_file_diff() {
+        let diff = r#"diff --git a/src/lib.rs b/src/lib.rs
+index abc123..def456 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-old
++new
+diff --git a/notes.md b/notes.md
+new file mode 100644
+index 0000000..def456
+--- /dev/null
++++ b/notes.md
+@@ -0,0 +1,2 @@
++a
++b
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+index abc123..0000000
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/old name.rs b/new name.rs
+similarity index 100%
+rename from old name.rs
+rename to new name.rs
+"#;
+        let files = parse_multi_file_diff(diff);
+        let summary: Vec<(&str, FileStatus, usize)> = files
+            .iter()
+            .map(|f| (f.path.as_str(), f.status, f.hunks.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("src/lib.rs", FileStatus::Modified, 1),
+                ("notes.md", FileStatus::Added, 1),
+                ("gone.txt", FileStatus::Deleted, 1),
+                ("new name.rs", FileStatus::Renamed, 0),
+            ]
+        );
+        assert_eq!(files[1].hunks[0].lines.len(), 2);
+    }
+
     #[test]
     fn test_parse_deletion() {
         let diff = r#"@@ -1,2 +1 @@
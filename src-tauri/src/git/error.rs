@@ -9,6 +9,15 @@ pub enum GitError {
     #[error("File not found in diff: {0}")]
     FileNotFound(String),
 
+    #[error("Invalid revision: {0}")]
+    InvalidRevision(String),
+
+    #[error("Path is outside the project: {0}")]
+    InvalidPath(String),
+
+    #[error("File too large to highlight: {0} ({1} bytes)")]
+    FileTooLarge(String, u64),
+
     #[error("Git command failed: {0}")]
     CommandFailed(String),
 
@@ -1,11 +1,28 @@
+use std::fs;
 use std::path::Path;
+use std::sync::OnceLock;
 
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{Style, ThemeSet};
+use syntect::highlighting::{Style, Theme, ThemeSet};
 use syntect::html::{styled_line_to_highlighted_html, IncludeBackground};
-use syntect::parsing::SyntaxSet;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
 use syntect::util::LinesWithEndings;
 
+use super::error::GitError;
+use super::types::HighlightedFile;
+
+pub const DEFAULT_THEME: &str = "base16-ocean.dark";
+
+/// Files larger than this are not highlighted
+const MAX_HIGHLIGHT_BYTES: u64 = 5 * 1024 * 1024;
+
+static SHARED: OnceLock<Highlighter> = OnceLock::new();
+
+/// Process-wide highlighter, so the syntax and theme sets are parsed once.
+pub fn shared() -> &'static Highlighter {
+    SHARED.get_or_init(Highlighter::new)
+}
+
 pub struct Highlighter {
     syntax_set: SyntaxSet,
     theme_set: ThemeSet,
@@ -25,7 +42,7 @@ impl Highlighter {
         }
     }
 
-    pub fn highlight_lines(&self, content: &str, file_path: &str) -> Vec<String> {
+    fn find_syntax(&self, content: &str, file_path: &str) -> &SyntaxReference {
         let extension = Path::new(file_path)
             .extension()
             .and_then(|e| e.to_str())
@@ -39,26 +56,95 @@ impl Highlighter {
             ext => ext,
         };
 
-        let syntax = self
-            .syntax_set
+        self.syntax_set
             .find_syntax_by_extension(mapped_extension)
             .or_else(|| self.syntax_set.find_syntax_by_extension(extension))
             .or_else(|| self.syntax_set.find_syntax_by_first_line(content))
-            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text());
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text())
+    }
+
+    /// The named theme, or the default when it does not exist.
+    fn theme(&self, name: &str) -> (&str, &Theme) {
+        match self.theme_set.themes.get_key_value(name) {
+            Some((name, theme)) => (name.as_str(), theme),
+            None => (DEFAULT_THEME, &self.theme_set.themes[DEFAULT_THEME]),
+        }
+    }
 
-        let theme = &self.theme_set.themes["base16-ocean.dark"];
+    pub fn theme_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.theme_set.themes.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    pub fn highlight_lines(&self, content: &str, file_path: &str) -> Vec<String> {
+        self.highlight_range(content, file_path, DEFAULT_THEME, 0, usize::MAX)
+    }
+
+    /// Highlights lines `start..end` (0-based). Earlier lines are still parsed
+    /// so multi-line constructs like block comments are coloured correctly.
+    pub fn highlight_range(
+        &self,
+        content: &str,
+        file_path: &str,
+        theme: &str,
+        start: usize,
+        end: usize,
+    ) -> Vec<String> {
+        let syntax = self.find_syntax(content, file_path);
+        let (_, theme) = self.theme(theme);
         let mut highlighter = HighlightLines::new(syntax, theme);
 
         LinesWithEndings::from(content)
-            .map(|line| {
+            .take(end)
+            .enumerate()
+            .filter_map(|(index, line)| {
                 let ranges: Vec<(Style, &str)> = highlighter
                     .highlight_line(line, &self.syntax_set)
                     .unwrap_or_default();
-                styled_line_to_highlighted_html(&ranges, IncludeBackground::No)
-                    .unwrap_or_else(|_| html_escape(line))
+                (index >= start).then(|| {
+                    styled_line_to_highlighted_html(&ranges, IncludeBackground::No)
+                        .unwrap_or_else(|_| html_escape(line))
+                })
             })
             .collect()
     }
+
+    /// Highlights `relative_path` inside `project_path`, optionally limited to
+    /// the 1-based inclusive line range `start_line..=end_line`.
+    pub fn highlight_file(
+        &self,
+        project_path: &Path,
+        relative_path: &str,
+        start_line: Option<u32>,
+        end_line: Option<u32>,
+        theme: Option<&str>,
+    ) -> Result<HighlightedFile, GitError> {
+        let root = project_path.canonicalize()?;
+        let path = root.join(relative_path).canonicalize()?;
+        if !path.starts_with(&root) {
+            return Err(GitError::InvalidPath(relative_path.to_string()));
+        }
+        let size = fs::metadata(&path)?.len();
+        if size > MAX_HIGHLIGHT_BYTES {
+            return Err(GitError::FileTooLarge(relative_path.to_string(), size));
+        }
+
+        let bytes = fs::read(&path)?;
+        let content = String::from_utf8_lossy(&bytes);
+        let total_lines = LinesWithEndings::from(&content).count();
+        let start = start_line.unwrap_or(1).max(1) as usize - 1;
+        let end = end_line.map_or(total_lines, |end| (end as usize).min(total_lines));
+        let (theme, _) = self.theme(theme.unwrap_or(DEFAULT_THEME));
+
+        Ok(HighlightedFile {
+            path: relative_path.to_string(),
+            start_line: start as u32 + 1,
+            lines: self.highlight_range(&content, relative_path, theme, start, end),
+            total_lines: total_lines as u32,
+            theme: theme.to_string(),
+        })
+    }
 }
 
 fn html_escape(s: &str) -> String {
@@ -137,6 +223,49 @@ mod tests {
         assert!(highlighted.is_empty());
     }
 
+    #[test]
+    fn test_highlight_range_keeps_parser_state() {
+        let highlighter = Highlighter::new();
+        let code = "/*\n still a comment\n*/\nfn main() {}\n";
+        let full = highlighter.highlight_lines(code, "test.rs");
+        let range = highlighter.highlight_range(code, "test.rs", DEFAULT_THEME, 1, 3);
+
+        assert_eq!(range, full[1..3]);
+    }
+
+    #[test]
+    fn test_unknown_theme_falls_back_to_default() {
+        let highlighter = Highlighter::new();
+        assert!(highlighter
+            .theme_names()
+            .contains(&DEFAULT_THEME.to_string()));
+        assert_eq!(highlighter.theme("no-such-theme").0, DEFAULT_THEME);
+        assert_eq!(highlighter.theme("InspiredGitHub").0, "InspiredGitHub");
+    }
+
+    #[test]
+    fn test_highlight_file() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("src")).unwrap();
+        fs::write(
+            dir.path().join("src/main.rs"),
+            "fn a() {}\nfn b() {}\nfn c() {}\n",
+        )
+        .unwrap();
+
+        let file = shared()
+            .highlight_file(dir.path(), "src/main.rs", Some(2), Some(10), None)
+            .unwrap();
+        assert_eq!(file.start_line, 2);
+        assert_eq!(file.total_lines, 3);
+        assert_eq!(file.lines.len(), 2);
+        assert_eq!(file.theme, DEFAULT_THEME);
+        assert!(file.lines[0].contains("<span"));
+
+        let outside = shared().highlight_file(dir.path(), "../", None, None, None);
+        assert!(outside.is_err());
+    }
+
     #[test]
     fn test_available_syntaxes() {
         let ss = SyntaxSet::load_defaults_newlines();
@@ -0,0 +1,312 @@
+//! Line diff of two in-memory texts, for changes git has no objects for
+//! (e.g. a file's content before the agent edited it).
+
+use std::fmt::Write;
+
+use super::types::{DiffHunk, DiffLine, FileDiff, FileStatus, LineKind};
+
+/// Unchanged lines kept around each change, as in `git diff`
+const CONTEXT_LINES: usize = 3;
+
+/// Above this many LCS table cells the changed middle is shown as a full replacement
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// One line of the edit script. `old` and `new` are 0-based positions in each
+/// text; for an addition `old` is the position it is inserted at, and vice versa.
+#[derive(Debug, Clone, Copy)]
+struct Op {
+    kind: LineKind,
+    old: usize,
+    new: usize,
+}
+
+/// Diff of `old` against `new`; None means the file does not exist on that side.
+pub fn diff_texts(path: &str, old: Option<&str>, new: Option<&str>) -> FileDiff {
+    let status = match (old, new) {
+        (None, _) => FileStatus::Added,
+        (_, None) => FileStatus::Deleted,
+        _ => FileStatus::Modified,
+    };
+    let old_lines: Vec<&str> = old.map(|text| text.lines().collect()).unwrap_or_default();
+    let new_lines: Vec<&str> = new.map(|text| text.lines().collect()).unwrap_or_default();
+
+    let ops = edit_script(&old_lines, &new_lines);
+    FileDiff {
+        path: path.to_string(),
+        status,
+        hunks: build_hunks(&ops, &old_lines, &new_lines),
+    }
+}
+
+/// `diff` in unified format, as `git diff` prints it.
+pub fn unified_diff(diff: &FileDiff) -> String {
+    let old_name = match diff.status {
+        FileStatus::Added => "/dev/null".to_string(),
+        _ => format!("a/{}", diff.path),
+    };
+    let new_name = match diff.status {
+        FileStatus::Deleted => "/dev/null".to_string(),
+        _ => format!("b/{}", diff.path),
+    };
+    let mut out = format!("--- {}\n+++ {}\n", old_name, new_name);
+    for hunk in &diff.hunks {
+        let _ = writeln!(
+            out,
+            "@@ -{},{} +{},{} @@",
+            hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines
+        );
+        for line in &hunk.lines {
+            let marker = match line.kind {
+                LineKind::Context => ' ',
+                LineKind::Addition => '+',
+                LineKind::Deletion => '-',
+            };
+            let _ = writeln!(out, "{}{}", marker, line.content);
+        }
+    }
+    out
+}
+
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<Op> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<Op> = (0..prefix)
+        .map(|i| Op {
+            kind: LineKind::Context,
+            old: i,
+            new: i,
+        })
+        .collect();
+
+    if old_mid.len().saturating_mul(new_mid.len()) <= MAX_LCS_CELLS {
+        lcs_ops(old_mid, new_mid, prefix, &mut ops);
+    } else {
+        replace_ops(old_mid.len(), new_mid.len(), prefix, &mut ops);
+    }
+
+    let (old_tail, new_tail) = (old.len() - suffix, new.len() - suffix);
+    ops.extend((0..suffix).map(|i| Op {
+        kind: LineKind::Context,
+        old: old_tail + i,
+        new: new_tail + i,
+    }));
+    ops
+}
+
+/// Edit script from a longest-common-subsequence table; `offset` is where both slices start.
+fn lcs_ops(old: &[&str], new: &[&str], offset: usize, ops: &mut Vec<Op>) {
+    let (n, m) = (old.len(), new.len());
+    let width = m + 1;
+    // table[i * width + j] = LCS length of old[i..] and new[j..]
+    let mut table = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        let kind = if i < n && j < m && old[i] == new[j] {
+            LineKind::Context
+        } else if j == m || (i < n && table[(i + 1) * width + j] >= table[i * width + j + 1]) {
+            LineKind::Deletion
+        } else {
+            LineKind::Addition
+        };
+        ops.push(Op {
+            kind,
+            old: offset + i,
+            new: offset + j,
+        });
+        if kind != LineKind::Addition {
+            i += 1;
+        }
+        if kind != LineKind::Deletion {
+            j += 1;
+        }
+    }
+}
+
+fn replace_ops(old_len: usize, new_len: usize, offset: usize, ops: &mut Vec<Op>) {
+    ops.extend((0..old_len).map(|i| Op {
+        kind: LineKind::Deletion,
+        old: offset + i,
+        new: offset,
+    }));
+    ops.extend((0..new_len).map(|j| Op {
+        kind: LineKind::Addition,
+        old: offset + old_len,
+        new: offset + j,
+    }));
+}
+
+/// Groups changes into hunks with context, merging changes whose context would overlap.
+fn build_hunks(ops: &[Op], old: &[&str], new: &[&str]) -> Vec<DiffHunk> {
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, op)| op.kind != LineKind::Context)
+        .map(|(index, _)| index)
+        .collect();
+
+    let mut hunks = Vec::new();
+    let mut next = 0;
+    while next < changes.len() {
+        let start = changes[next].saturating_sub(CONTEXT_LINES);
+        let mut last = changes[next];
+        while next + 1 < changes.len() && changes[next + 1] - last <= 2 * CONTEXT_LINES + 1 {
+            next += 1;
+            last = changes[next];
+        }
+        let end = (last + CONTEXT_LINES + 1).min(ops.len());
+        hunks.push(make_hunk(&ops[start..end], old, new));
+        next += 1;
+    }
+    hunks
+}
+
+fn make_hunk(ops: &[Op], old: &[&str], new: &[&str]) -> DiffHunk {
+    let lines: Vec<DiffLine> = ops
+        .iter()
+        .map(|op| {
+            let (old_line_no, new_line_no, content) = match op.kind {
+                LineKind::Context => (
+                    Some(op.old as u32 + 1),
+                    Some(op.new as u32 + 1),
+                    old[op.old],
+                ),
+                LineKind::Deletion => (Some(op.old as u32 + 1), None, old[op.old]),
+                LineKind::Addition => (None, Some(op.new as u32 + 1), new[op.new]),
+            };
+            DiffLine {
+                kind: op.kind,
+                old_line_no,
+                new_line_no,
+                content: content.to_string(),
+                highlighted: None,
+            }
+        })
+        .collect();
+
+    let old_lines = lines.iter().filter(|l| l.old_line_no.is_some()).count() as u32;
+    let new_lines = lines.iter().filter(|l| l.new_line_no.is_some()).count() as u32;
+    // Like git, an empty side starts at the line before the change
+    let first = ops[0];
+    DiffHunk {
+        old_start: first.old as u32 + u32::from(old_lines > 0),
+        old_lines,
+        new_start: first.new as u32 + u32::from(new_lines > 0),
+        new_lines,
+        lines,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(hunk: &DiffHunk) -> String {
+        hunk.lines
+            .iter()
+            .map(|l| match l.kind {
+                LineKind::Context => ' ',
+                LineKind::Addition => '+',
+                LineKind::Deletion => '-',
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unchanged_has_no_hunks() {
+        let diff = diff_texts("a.txt", Some("a\nb\n"), Some("a\nb\n"));
+        assert_eq!(diff.status, FileStatus::Modified);
+        assert!(diff.hunks.is_empty());
+    }
+
+    #[test]
+    fn test_modified_line_with_context() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n";
+        let new = "1\n2\n3\n4\nfive\n6\n7\n8\n";
+        let diff = diff_texts("a.txt", Some(old), Some(new));
+
+        assert_eq!(diff.hunks.len(), 1);
+        let hunk = &diff.hunks[0];
+        assert_eq!(kinds(hunk), "   -+   ");
+        assert_eq!((hunk.old_start, hunk.old_lines), (2, 7));
+        assert_eq!((hunk.new_start, hunk.new_lines), (2, 7));
+        assert_eq!(hunk.lines[4].content, "five");
+        assert_eq!(hunk.lines[4].new_line_no, Some(5));
+    }
+
+    #[test]
+    fn test_distant_changes_get_separate_hunks() {
+        let numbered = |changed: &[u32]| -> String {
+            (1..=20)
+                .map(|n| {
+                    if changed.contains(&n) {
+                        format!("changed {}\n", n)
+                    } else {
+                        format!("{}\n", n)
+                    }
+                })
+                .collect()
+        };
+        let old = numbered(&[]);
+        let diff = diff_texts("a.txt", Some(&old), Some(&numbered(&[2, 18])));
+        assert_eq!(diff.hunks.len(), 2);
+        assert_eq!(diff.hunks[1].old_start, 15);
+
+        // Six unchanged lines between changes: their context meets, one hunk
+        let near = diff_texts("a.txt", Some(&old), Some(&numbered(&[2, 9])));
+        assert_eq!(near.hunks.len(), 1);
+    }
+
+    #[test]
+    fn test_added_and_deleted_files() {
+        let added = diff_texts("new.txt", None, Some("a\nb\n"));
+        assert_eq!(added.status, FileStatus::Added);
+        let hunk = &added.hunks[0];
+        assert_eq!(kinds(hunk), "++");
+        assert_eq!((hunk.old_start, hunk.old_lines), (0, 0));
+        assert_eq!((hunk.new_start, hunk.new_lines), (1, 2));
+
+        let deleted = diff_texts("old.txt", Some("a\n"), None);
+        assert_eq!(deleted.status, FileStatus::Deleted);
+        assert_eq!(kinds(&deleted.hunks[0]), "-");
+        assert_eq!(deleted.hunks[0].new_start, 0);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let diff = diff_texts("a.txt", Some("one\ntwo\n"), Some("one\n2\n"));
+        assert_eq!(
+            unified_diff(&diff),
+            "--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n"
+        );
+
+        let deleted = diff_texts("old.txt", Some("a\n"), None);
+        assert!(unified_diff(&deleted).starts_with("--- a/old.txt\n+++ /dev/null\n"));
+    }
+
+    #[test]
+    fn test_insertion_between_lines() {
+        let diff = diff_texts("a.txt", Some("a\nc\n"), Some("a\nb\nc\n"));
+        let hunk = &diff.hunks[0];
+        assert_eq!(kinds(hunk), " + ");
+        assert_eq!(hunk.lines[1].new_line_no, Some(2));
+        assert_eq!((hunk.old_lines, hunk.new_lines), (2, 3));
+    }
+}
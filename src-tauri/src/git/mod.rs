@@ -2,10 +2,15 @@ pub mod commands;
 mod diff_parser;
 mod error;
 mod highlighter;
+mod line_diff;
+mod pagination;
 mod service;
+mod split_view;
 mod types;
 pub mod wsl;
 
 pub use error::GitError;
+pub use line_diff::{diff_texts, unified_diff};
+pub(crate) use service::git_command;
 pub use service::GitService;
 pub use types::*;
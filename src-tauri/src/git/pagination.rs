@@ -0,0 +1,163 @@
+//! Hunk-based paging of file diffs, so very large diffs can be fetched and
+//! rendered a few hunks at a time.
+
+use super::types::{DiffHunk, DiffLine, DiffStats, FileDiff, FileDiffPage, LineKind};
+
+/// Hunks longer than this are split into consecutive pages of this many lines
+/// (an untracked generated file is a single hunk).
+pub const MAX_PAGE_HUNK_LINES: usize = 500;
+
+fn stats(hunks: &[DiffHunk]) -> DiffStats {
+    let mut stats = DiffStats {
+        additions: 0,
+        deletions: 0,
+        total_lines: 0,
+    };
+    for line in hunks.iter().flat_map(|h| h.lines.iter()) {
+        stats.total_lines += 1;
+        match line.kind {
+            LineKind::Addition => stats.additions += 1,
+            LineKind::Deletion => stats.deletions += 1,
+            LineKind::Context => {}
+        }
+    }
+    stats
+}
+
+fn sub_hunk(lines: Vec<DiffLine>, old_start: u32, new_start: u32) -> DiffHunk {
+    let old_lines = lines
+        .iter()
+        .filter(|l| l.kind != LineKind::Addition)
+        .count() as u32;
+    let new_lines = lines
+        .iter()
+        .filter(|l| l.kind != LineKind::Deletion)
+        .count() as u32;
+    DiffHunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines,
+    }
+}
+
+/// Splits hunks over `max_lines` into consecutive hunks with matching line numbers.
+fn split_large_hunks(hunks: Vec<DiffHunk>, max_lines: usize) -> Vec<DiffHunk> {
+    let mut result = Vec::new();
+    for hunk in hunks {
+        if hunk.lines.len() <= max_lines {
+            result.push(hunk);
+            continue;
+        }
+
+        let mut old_start = hunk.old_start;
+        let mut new_start = hunk.new_start;
+        let mut lines = hunk.lines.into_iter().peekable();
+        while lines.peek().is_some() {
+            let chunk: Vec<DiffLine> = lines.by_ref().take(max_lines).collect();
+            let part = sub_hunk(chunk, old_start, new_start);
+            old_start += part.old_lines;
+            new_start += part.new_lines;
+            result.push(part);
+        }
+    }
+    result
+}
+
+/// `count` hunks of `diff` starting at `hunk_offset`, with stats for the whole diff.
+pub fn paginate(diff: FileDiff, hunk_offset: usize, count: usize) -> FileDiffPage {
+    let stats = stats(&diff.hunks);
+    let hunks = split_large_hunks(diff.hunks, MAX_PAGE_HUNK_LINES);
+    let total_hunks = hunks.len();
+
+    FileDiffPage {
+        path: diff.path,
+        status: diff.status,
+        hunks: hunks.into_iter().skip(hunk_offset).take(count).collect(),
+        hunk_offset: hunk_offset.min(total_hunks) as u32,
+        total_hunks: total_hunks as u32,
+        stats,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::FileStatus;
+
+    fn line(kind: LineKind, old: Option<u32>, new: Option<u32>) -> DiffLine {
+        DiffLine {
+            kind,
+            old_line_no: old,
+            new_line_no: new,
+            content: String::new(),
+            highlighted: None,
+        }
+    }
+
+    fn added_file(lines: u32) -> FileDiff {
+        let lines: Vec<DiffLine> = (1..=lines)
+            .map(|n| line(LineKind::Addition, None, Some(n)))
+            .collect();
+        FileDiff {
+            path: "gen.rs".to_string(),
+            status: FileStatus::Untracked,
+            hunks: vec![DiffHunk {
+                old_start: 0,
+                old_lines: 0,
+                new_start: 1,
+                new_lines: lines.len() as u32,
+                lines,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_large_hunk_is_split() {
+        let page = paginate(added_file(1200), 0, 10);
+        assert_eq!(page.total_hunks, 3);
+        assert_eq!(page.hunks[1].new_start, 501);
+        assert_eq!(page.hunks[1].new_lines, 500);
+        assert_eq!(page.hunks[2].new_start, 1001);
+        assert_eq!(page.hunks[2].lines.len(), 200);
+        assert_eq!(page.stats.additions, 1200);
+    }
+
+    #[test]
+    fn test_page_window() {
+        let page = paginate(added_file(1200), 1, 1);
+        assert_eq!(page.hunk_offset, 1);
+        assert_eq!(page.hunks.len(), 1);
+        assert_eq!(page.hunks[0].lines[0].new_line_no, Some(501));
+
+        let past_end = paginate(added_file(10), 5, 2);
+        assert!(past_end.hunks.is_empty());
+        assert_eq!(past_end.hunk_offset, 1);
+    }
+
+    #[test]
+    fn test_split_tracks_old_and_new_lines() {
+        let lines = vec![
+            line(LineKind::Context, Some(10), Some(10)),
+            line(LineKind::Deletion, Some(11), None),
+            line(LineKind::Addition, None, Some(11)),
+            line(LineKind::Addition, None, Some(12)),
+            line(LineKind::Context, Some(12), Some(13)),
+        ];
+        let hunks = split_large_hunks(
+            vec![DiffHunk {
+                old_start: 10,
+                old_lines: 3,
+                new_start: 10,
+                new_lines: 4,
+                lines,
+            }],
+            2,
+        );
+
+        let starts: Vec<(u32, u32)> = hunks.iter().map(|h| (h.old_start, h.new_start)).collect();
+        assert_eq!(starts, vec![(10, 10), (12, 11), (12, 13)]);
+        assert_eq!((hunks[0].old_lines, hunks[0].new_lines), (2, 1));
+    }
+}
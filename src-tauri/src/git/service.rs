@@ -4,7 +4,7 @@ use std::process::{Command, Output};
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 
-use super::diff_parser::parse_unified_diff;
+use super::diff_parser::{parse_multi_file_diff, parse_unified_diff};
 use super::error::GitError;
 use super::types::{
     ChangedFile, DiffHunk, DiffLine, FileDiff, FileStatus, LineKind, RepositoryCheckResult,
@@ -16,7 +16,7 @@ use super::wsl::{is_wsl_path, parse_wsl_path, run_git_via_wsl, WslPath};
 const CREATE_NO_WINDOW: u32 = 0x08000000;
 
 /// Create a git command that won't show a console window on Windows
-fn git_command() -> Command {
+pub(crate) fn git_command() -> Command {
     #[allow(unused_mut)]
     let mut cmd = Command::new("git");
     #[cfg(windows)]
@@ -195,6 +195,96 @@ impl GitService {
         Ok(parse_unified_diff(&stdout))
     }
 
+    /// Raw unified diff of the working tree against HEAD, or of a revision
+    /// range such as `main...HEAD`, limited to `paths` when any are given.
+    /// Untracked files are not included.
+    pub fn diff_text(&self, range: Option<&str>, paths: &[String]) -> Result<String, GitError> {
+        let range = range.map(str::trim).unwrap_or("HEAD");
+        if range.is_empty() || range.starts_with('-') {
+            return Err(GitError::InvalidRevision(range.to_string()));
+        }
+
+        let mut args = vec!["diff", range, "--"];
+        args.extend(paths.iter().map(String::as_str));
+        let output = self.run_git(&args)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Per-file diffs between two revisions, optionally limited to one path.
+    pub fn diff_refs(
+        &self,
+        base: &str,
+        head: &str,
+        path: Option<&str>,
+    ) -> Result<Vec<FileDiff>, GitError> {
+        let (base, head) = (base.trim(), head.trim());
+        for rev in [base, head] {
+            if rev.is_empty() || rev.starts_with('-') {
+                return Err(GitError::InvalidRevision(rev.to_string()));
+            }
+        }
+
+        let mut args = vec!["diff", base, head, "--"];
+        args.extend(path);
+        let output = self.run_git(&args)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(parse_multi_file_diff(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    /// Paths of tracked files and untracked files that are not ignored,
+    /// relative to the repository root with `/` separators.
+    pub fn list_files(&self) -> Result<Vec<String>, GitError> {
+        self.list_files_in(&[])
+    }
+
+    /// Like `list_files`, limited to files at or below `paths` (all files if empty).
+    pub fn list_files_in(&self, paths: &[String]) -> Result<Vec<String>, GitError> {
+        let mut args = vec![
+            "ls-files",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+            "-z",
+        ];
+        if !paths.is_empty() {
+            args.push("--");
+            args.extend(paths.iter().map(String::as_str));
+        }
+        let output = self.run_git(&args)?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(String::from)
+            .collect())
+    }
+
+    /// Full SHA of the commit HEAD points at.
+    pub fn head_sha(&self) -> Result<String, GitError> {
+        let output = self.run_git(&["rev-parse", "HEAD"])?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
     pub fn stage_all(&self) -> Result<(), GitError> {
         let output = self.run_git(&["add", "--all"])?;
 
@@ -316,6 +406,124 @@ mod tests {
         assert_eq!(files[0].worktree_status, Some(FileStatus::Modified));
     }
 
+    #[test]
+    fn test_list_files_in() {
+        let (temp_dir, service) = create_test_repo();
+        create_initial_commit(&temp_dir);
+        fs::create_dir_all(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(temp_dir.path().join("debug.log"), "").unwrap();
+        fs::write(temp_dir.path().join(".gitignore"), "*.log\n").unwrap();
+
+        let mut files = service.list_files().unwrap();
+        files.sort();
+        assert_eq!(files, vec![".gitignore", "initial.txt", "src/main.rs"]);
+
+        let files = service
+            .list_files_in(&["src".to_string(), "debug.log".to_string()])
+            .unwrap();
+        assert_eq!(files, vec!["src/main.rs"]);
+    }
+
+    #[test]
+    fn test_diff_text_working_tree_and_range() {
+        let (temp_dir, service) = create_test_repo();
+        create_initial_commit(&temp_dir);
+
+        fs::write(temp_dir.path().join("initial.txt"), "modified content").unwrap();
+        let diff = service.diff_text(None, &[]).unwrap();
+        assert!(diff.contains("+modified content"));
+        assert!(diff.contains("-initial content"));
+
+        // Committed changes show up in a range, not in the working tree diff
+        Command::new("git")
+            .args(["commit", "-am", "Modify"])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        assert!(service.diff_text(None, &[]).unwrap().is_empty());
+        assert!(service
+            .diff_text(Some("HEAD~1..HEAD"), &[])
+            .unwrap()
+            .contains("+modified content"));
+
+        // Limited to the given paths
+        assert!(service
+            .diff_text(Some("HEAD~1..HEAD"), &["other.txt".to_string()])
+            .unwrap()
+            .is_empty());
+        assert!(!service
+            .diff_text(Some("HEAD~1..HEAD"), &["initial.txt".to_string()])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_diff_text_rejects_options() {
+        let (temp_dir, service) = create_test_repo();
+        create_initial_commit(&temp_dir);
+
+        assert!(matches!(
+            service.diff_text(Some("--output=/tmp/x"), &[]),
+            Err(GitError::InvalidRevision(_))
+        ));
+        assert!(matches!(
+            service.diff_text(Some("no-such-ref"), &[]),
+            Err(GitError::CommandFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_diff_refs() {
+        let (temp_dir, service) = create_test_repo();
+        create_initial_commit(&temp_dir);
+
+        fs::write(temp_dir.path().join("initial.txt"), "modified content").unwrap();
+        fs::write(temp_dir.path().join("added.txt"), "added").unwrap();
+        Command::new("git")
+            .args(["add", "."])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Change"])
+            .current_dir(temp_dir.path())
+            .output()
+            .unwrap();
+
+        let files = service.diff_refs("HEAD~1", "HEAD", None).unwrap();
+        let paths: Vec<(&str, FileStatus)> =
+            files.iter().map(|f| (f.path.as_str(), f.status)).collect();
+        assert_eq!(
+            paths,
+            vec![
+                ("added.txt", FileStatus::Added),
+                ("initial.txt", FileStatus::Modified)
+            ]
+        );
+
+        let files = service
+            .diff_refs("HEAD~1", "HEAD", Some("initial.txt"))
+            .unwrap();
+        assert_eq!(files.len(), 1);
+
+        assert!(matches!(
+            service.diff_refs("HEAD~1", "--output=/tmp/x", None),
+            Err(GitError::InvalidRevision(_))
+        ));
+    }
+
+    #[test]
+    fn test_head_sha() {
+        let (temp_dir, service) = create_test_repo();
+        assert!(service.head_sha().is_err());
+
+        create_initial_commit(&temp_dir);
+        let sha = service.head_sha().unwrap();
+        assert_eq!(sha.len(), 40);
+        assert!(sha.chars().all(|c| c.is_ascii_hexdigit()));
+    }
+
     #[test]
     fn test_get_changed_files_staged() {
         let (temp_dir, service) = create_test_repo();
@@ -0,0 +1,212 @@
+//! Side-by-side representation of unified diff hunks: old and new lines aligned
+//! into rows, with the changed span of each modified line pair.
+
+use super::types::{
+    ChangeRange, DiffHunk, DiffLine, FileDiff, LineKind, SplitCell, SplitFileDiff, SplitHunk,
+    SplitRow,
+};
+
+pub fn split_file_diff(diff: FileDiff) -> SplitFileDiff {
+    SplitFileDiff {
+        path: diff.path,
+        status: diff.status,
+        hunks: diff.hunks.into_iter().map(split_hunk).collect(),
+    }
+}
+
+/// Aligns a hunk into rows. Context lines fill both sides; each run of deletions
+/// is paired line by line with the additions that follow it, and the unmatched
+/// remainder of the longer side gets an empty cell opposite.
+fn split_hunk(hunk: DiffHunk) -> SplitHunk {
+    let mut rows = Vec::new();
+    let mut deletions: Vec<DiffLine> = Vec::new();
+    let mut additions: Vec<DiffLine> = Vec::new();
+
+    for line in hunk.lines {
+        match line.kind {
+            LineKind::Deletion => {
+                // A deletion after additions starts a new change block
+                if !additions.is_empty() {
+                    flush_block(&mut rows, &mut deletions, &mut additions);
+                }
+                deletions.push(line);
+            }
+            LineKind::Addition => additions.push(line),
+            LineKind::Context => {
+                flush_block(&mut rows, &mut deletions, &mut additions);
+                let new_line_no = line.new_line_no;
+                rows.push(SplitRow {
+                    old: Some(cell(line.clone(), line.old_line_no, Vec::new())),
+                    new: Some(cell(line, new_line_no, Vec::new())),
+                });
+            }
+        }
+    }
+    flush_block(&mut rows, &mut deletions, &mut additions);
+
+    SplitHunk {
+        old_start: hunk.old_start,
+        old_lines: hunk.old_lines,
+        new_start: hunk.new_start,
+        new_lines: hunk.new_lines,
+        rows,
+    }
+}
+
+fn flush_block(
+    rows: &mut Vec<SplitRow>,
+    deletions: &mut Vec<DiffLine>,
+    additions: &mut Vec<DiffLine>,
+) {
+    let mut old_lines = deletions.drain(..);
+    let mut new_lines = additions.drain(..);
+    loop {
+        let row = match (old_lines.next(), new_lines.next()) {
+            (None, None) => break,
+            (Some(old), Some(new)) => {
+                let (old_changes, new_changes) = changed_ranges(&old.content, &new.content);
+                SplitRow {
+                    old: Some(cell_for(old, old_changes)),
+                    new: Some(cell_for(new, new_changes)),
+                }
+            }
+            (Some(old), None) => SplitRow {
+                old: Some(cell_for(old, Vec::new())),
+                new: None,
+            },
+            (None, Some(new)) => SplitRow {
+                old: None,
+                new: Some(cell_for(new, Vec::new())),
+            },
+        };
+        rows.push(row);
+    }
+}
+
+fn cell_for(line: DiffLine, changes: Vec<ChangeRange>) -> SplitCell {
+    let line_no = line.old_line_no.or(line.new_line_no);
+    cell(line, line_no, changes)
+}
+
+fn cell(line: DiffLine, line_no: Option<u32>, changes: Vec<ChangeRange>) -> SplitCell {
+    SplitCell {
+        line_no: line_no.unwrap_or(0),
+        kind: line.kind,
+        content: line.content,
+        highlighted: line.highlighted,
+        changes,
+    }
+}
+
+/// Changed span of each side of a modified line pair, found by trimming the
+/// common prefix and suffix. Offsets are UTF-16 code units so the frontend can
+/// slice with them directly. Empty when the lines share nothing, since the
+/// whole line is then already marked by its kind.
+fn changed_ranges(old: &str, new: &str) -> (Vec<ChangeRange>, Vec<ChangeRange>) {
+    let old_chars: Vec<char> = old.chars().collect();
+    let new_chars: Vec<char> = new.chars().collect();
+
+    let prefix = old_chars
+        .iter()
+        .zip(&new_chars)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old_chars.len().min(new_chars.len()) - prefix;
+    let suffix = old_chars
+        .iter()
+        .rev()
+        .zip(new_chars.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    if prefix == 0 && suffix == 0 {
+        return (Vec::new(), Vec::new());
+    }
+
+    let range = |chars: &[char]| -> Vec<ChangeRange> {
+        let end_index = chars.len() - suffix;
+        if prefix == end_index {
+            return Vec::new();
+        }
+        vec![ChangeRange {
+            start: utf16_len(&chars[..prefix]),
+            end: utf16_len(&chars[..end_index]),
+        }]
+    };
+    (range(&old_chars), range(&new_chars))
+}
+
+fn utf16_len(chars: &[char]) -> u32 {
+    chars.iter().map(|c| c.len_utf16() as u32).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::diff_parser::parse_unified_diff;
+
+    fn split(diff: &str) -> SplitHunk {
+        split_hunk(parse_unified_diff(diff).remove(0))
+    }
+
+    fn numbers(row: &SplitRow) -> (Option<u32>, Option<u32>) {
+        (
+            row.old.as_ref().map(|c| c.line_no),
+            row.new.as_ref().map(|c| c.line_no),
+        )
+    }
+
+    #[test]
+    fn test_rows_are_aligned() {
+        let hunk = split("@@ -1,4 +1,4 @@\n a\n-b\n-c\n+B\n d\n+e\n");
+        let rows: Vec<_> = hunk.rows.iter().map(numbers).collect();
+        assert_eq!(
+            rows,
+            vec![
+                (Some(1), Some(1)),
+                (Some(2), Some(2)),
+                (Some(3), None),
+                (Some(4), Some(3)),
+                (None, Some(4)),
+            ]
+        );
+        assert_eq!(hunk.rows[1].old.as_ref().unwrap().kind, LineKind::Deletion);
+        assert_eq!(hunk.rows[1].new.as_ref().unwrap().kind, LineKind::Addition);
+    }
+
+    #[test]
+    fn test_additions_before_deletions_are_separate_blocks() {
+        let hunk = split("@@ -1,1 +1,1 @@\n+new\n-old\n");
+        let rows: Vec<_> = hunk.rows.iter().map(numbers).collect();
+        assert_eq!(rows, vec![(None, Some(1)), (Some(1), None)]);
+    }
+
+    #[test]
+    fn test_changed_ranges() {
+        let (old, new) = changed_ranges("let x = 1;", "let x = 42;");
+        assert_eq!((old[0].start, old[0].end), (8, 9));
+        assert_eq!((new[0].start, new[0].end), (8, 10));
+
+        // Pure insertion: nothing removed on the old side
+        let (old, new) = changed_ranges("foo()", "foo(bar)");
+        assert!(old.is_empty());
+        assert_eq!((new[0].start, new[0].end), (4, 7));
+
+        // Unrelated lines get no ranges
+        assert_eq!(changed_ranges("abc", "xyz"), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn test_changed_ranges_use_utf16_offsets() {
+        let (_, new) = changed_ranges("é = 1", "é = 😀");
+        assert_eq!((new[0].start, new[0].end), (4, 6));
+    }
+
+    #[test]
+    fn test_repeated_characters_do_not_overlap() {
+        let (old, new) = changed_ranges("aa", "aaa");
+        assert!(old.is_empty());
+        assert_eq!((new[0].start, new[0].end), (2, 3));
+    }
+}
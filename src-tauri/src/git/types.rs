@@ -72,6 +72,89 @@ pub enum LineKind {
     Deletion,
 }
 
+/// Line counts for a whole file diff
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DiffStats {
+    pub additions: u32,
+    pub deletions: u32,
+    pub total_lines: u32,
+}
+
+/// A window of a file diff's hunks; oversized hunks are split so each page stays small
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct FileDiffPage {
+    pub path: String,
+    pub status: FileStatus,
+    pub hunks: Vec<DiffHunk>,
+    /// Index of the first hunk in `hunks`
+    pub hunk_offset: u32,
+    pub total_hunks: u32,
+    pub stats: DiffStats,
+}
+
+/// A file diff laid out for side-by-side display
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SplitFileDiff {
+    pub path: String,
+    pub status: FileStatus,
+    pub hunks: Vec<SplitHunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SplitHunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub rows: Vec<SplitRow>,
+}
+
+/// One aligned row; a side is null where the other side added or removed a line
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SplitRow {
+    pub old: Option<SplitCell>,
+    pub new: Option<SplitCell>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SplitCell {
+    pub line_no: u32,
+    pub kind: LineKind,
+    pub content: String,
+    /// Syntax-highlighted HTML content (if available)
+    pub highlighted: Option<String>,
+    /// Changed spans of `content` when the line is paired with a modified counterpart
+    pub changes: Vec<ChangeRange>,
+}
+
+/// Half-open span of a line, in UTF-16 code units
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ChangeRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Syntax-highlighted lines of a project file
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct HighlightedFile {
+    pub path: String,
+    /// 1-based number of the first line in `lines`
+    pub start_line: u32,
+    /// Highlighted HTML, one entry per line
+    pub lines: Vec<String>,
+    pub total_lines: u32,
+    /// Theme used; unknown themes fall back to the default
+    pub theme: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,4 +1,5 @@
-//! WSL path handling. Routes git commands through wsl.exe for proper .gitignore support.
+//! WSL path handling. Routes git commands, shell commands and file writes
+//! through wsl.exe so they behave as they would inside the distro.
 
 use std::path::Path;
 
@@ -17,6 +18,69 @@ pub struct WslPath {
     pub linux_path: String,
 }
 
+impl WslPath {
+    /// Linux path of `relative`, a path below this one.
+    pub fn join(&self, relative: &Path) -> String {
+        let mut path = self.linux_path.trim_end_matches('/').to_string();
+        for component in relative.components() {
+            path.push('/');
+            path.push_str(&component.as_os_str().to_string_lossy());
+        }
+        if path.is_empty() {
+            "/".to_string()
+        } else {
+            path
+        }
+    }
+
+    /// wsl.exe invocation that runs `program` (without a shell) in `linux_dir`.
+    pub fn command(&self, linux_dir: &str, program: &str) -> tokio::process::Command {
+        let mut cmd = tokio::process::Command::new("wsl.exe");
+        #[cfg(windows)]
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        cmd.args(["-d", &self.distro, "--cd", linux_dir, "--exec", program]);
+        cmd
+    }
+}
+
+/// Writes `content` to `linux_path` from inside the distro, creating parent
+/// directories. Existing files are truncated in place, so they keep their
+/// owner and mode instead of getting the defaults of the \\wsl$ share.
+pub async fn write_file_via_wsl(
+    wsl_path: &WslPath,
+    linux_path: &str,
+    content: &[u8],
+) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut child = wsl_path
+        .command("/", "sh")
+        .args([
+            "-c",
+            r#"mkdir -p "$(dirname "$1")" && cat > "$1""#,
+            "sh",
+            linux_path,
+        ])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "wsl.exe write failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
 #[cfg(windows)]
 pub fn is_wsl_path(path: &Path) -> bool {
     let path_str = path.to_string_lossy();
@@ -105,6 +169,26 @@ mod tests {
         assert!(is_wsl_path(Path::new(r"\\wsl$\Ubuntu\home")));
     }
 
+    #[test]
+    fn test_join() {
+        let wsl = WslPath {
+            distro: "Ubuntu".to_string(),
+            linux_path: "/home/tony/project".to_string(),
+        };
+        assert_eq!(
+            wsl.join(Path::new("src/main.rs")),
+            "/home/tony/project/src/main.rs"
+        );
+        assert_eq!(wsl.join(Path::new("")), "/home/tony/project");
+
+        let root = WslPath {
+            distro: "Ubuntu".to_string(),
+            linux_path: "/".to_string(),
+        };
+        assert_eq!(root.join(Path::new("etc")), "/etc");
+        assert_eq!(root.join(Path::new("")), "/");
+    }
+
     #[test]
     fn test_is_wsl_path_negative() {
         assert!(!is_wsl_path(Path::new(r"/home/tony")));
@@ -1,32 +1,51 @@
 pub mod agent;
+pub mod cli;
 pub mod config;
+pub mod diagnostics;
 pub mod evals;
 pub mod git;
 mod menu;
+pub mod setup;
+mod shutdown;
 pub mod template;
+mod windows;
 
-use std::sync::{Arc, RwLock};
-
-use tauri::{Listener, Manager};
+use tauri::{Listener, Manager, RunEvent, WindowEvent};
 
 use agent::commands::{
-    agent_approve_plan, agent_cancel, agent_clear_state, agent_has_pending_plan, agent_is_running,
-    agent_reject_plan, agent_send_message, get_session_usage, reset_session_usage,
+    agent_approve_plan, agent_cancel, agent_clear_state, agent_compact_context, agent_edit_message,
+    agent_get_auto_approve, agent_get_pending_changes, agent_get_plan_history,
+    agent_get_run_options, agent_get_todos, agent_has_pending_plan, agent_index_status,
+    agent_is_running, agent_pause, agent_queue_message, agent_rate_message,
+    agent_refresh_project_map, agent_regenerate_from, agent_reject_plan, agent_replay_transcript,
+    agent_respond_compaction, agent_respond_tool_approval, agent_restore_compacted_context,
+    agent_resume, agent_send_message, agent_set_auto_approve, agent_set_confirm_compaction,
+    agent_set_run_options, debug_get_effective_prompt, get_interrupted_run, get_latency_stats,
+    get_model_info, get_session_usage, get_tool_stats, providers_list_models, recover_last_run,
+    reset_session_usage, run_pr_review, run_security_review, usage_export_csv,
 };
-use agent::{AgentState, SessionUsageTracker};
+use agent::WindowStates;
 use config::commands::{
     config_get_agent_prompts, config_get_agent_types, config_get_default_extraction_prompt,
     config_get_default_system_prompt, config_get_last_project, config_get_providers,
-    config_get_tool_descriptions, config_load_agents_md, config_load_project,
-    config_project_exists, config_save_agents_md, config_save_project, config_set_last_project,
+    config_get_recent_projects, config_get_tool_descriptions, config_load_agents_md,
+    config_load_project, config_pin_project, config_project_exists, config_save_agents_md,
+    config_save_project, config_set_last_project,
 };
+use config::ConfigWatchers;
+use diagnostics::commands::diagnostics_export;
 use git::commands::{
-    git_get_changed_files, git_get_file_diff_with_status, git_is_repository, git_stage_all,
+    git_diff_refs, git_get_changed_files, git_get_file_diff_page, git_get_file_diff_split,
+    git_get_file_diff_summary, git_get_file_diff_with_status, git_is_repository, git_stage_all,
+    highlight_file, highlight_list_themes,
 };
+use setup::commands::{setup_check_git, setup_check_provider, setup_check_shell};
 use template::commands::{
-    template_get_defaults, template_load, template_render_commit, template_render_review_comments,
-    template_save,
+    template_get_defaults, template_list_scaffolds, template_list_sources, template_load,
+    template_render_commit, template_render_review_comments, template_save, template_scaffold,
+    template_sync,
 };
+use windows::open_project_window;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -41,18 +60,16 @@ pub fn run() {
                 ))
                 .build(),
         )
-        .manage(RwLock::new(AgentState::new()))
-        .manage(Arc::new(SessionUsageTracker::new()))
+        .manage(WindowStates::new())
+        .manage(ConfigWatchers::new())
         .setup(|app| {
             menu::setup(app)?;
 
-            // Listen for config changes and mark agent state stale
+            // Listen for config changes and mark every window's agent state stale
             let handle = app.handle().clone();
             app.listen("config-changed", move |_| {
-                if let Some(state) = handle.try_state::<RwLock<AgentState>>() {
-                    if let Ok(mut guard) = state.write() {
-                        guard.mark_config_stale();
-                    }
+                if let Some(windows) = handle.try_state::<WindowStates>() {
+                    windows.mark_config_stale();
                 }
             });
 
@@ -65,22 +82,66 @@ pub fn run() {
         .on_menu_event(|app, event| {
             menu::handle_event(app, event.id().as_ref());
         })
+        .on_window_event(|window, event| {
+            if let WindowEvent::Destroyed = event {
+                window.state::<WindowStates>().remove(window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             agent_send_message,
+            agent_regenerate_from,
+            agent_edit_message,
+            agent_rate_message,
+            debug_get_effective_prompt,
             agent_cancel,
+            agent_queue_message,
+            agent_pause,
+            agent_resume,
             agent_is_running,
             agent_clear_state,
+            agent_compact_context,
+            agent_set_confirm_compaction,
+            agent_respond_compaction,
+            agent_restore_compacted_context,
             agent_approve_plan,
             agent_reject_plan,
             agent_has_pending_plan,
+            agent_get_todos,
+            agent_get_plan_history,
+            agent_respond_tool_approval,
+            agent_get_auto_approve,
+            agent_set_auto_approve,
+            agent_get_pending_changes,
+            agent_replay_transcript,
+            agent_refresh_project_map,
+            agent_index_status,
+            agent_get_run_options,
+            agent_set_run_options,
+            run_security_review,
+            run_pr_review,
             get_session_usage,
+            get_tool_stats,
+            get_latency_stats,
+            get_model_info,
+            providers_list_models,
             reset_session_usage,
+            usage_export_csv,
+            get_interrupted_run,
+            recover_last_run,
             git_is_repository,
             git_get_changed_files,
             git_get_file_diff_with_status,
+            git_get_file_diff_page,
+            git_diff_refs,
+            git_get_file_diff_split,
+            git_get_file_diff_summary,
             git_stage_all,
+            highlight_file,
+            highlight_list_themes,
             config_get_last_project,
             config_set_last_project,
+            config_get_recent_projects,
+            config_pin_project,
             config_project_exists,
             config_load_project,
             config_get_providers,
@@ -97,7 +158,21 @@ pub fn run() {
             template_render_review_comments,
             template_render_commit,
             template_get_defaults,
+            template_list_scaffolds,
+            template_scaffold,
+            template_list_sources,
+            template_sync,
+            setup_check_provider,
+            setup_check_git,
+            setup_check_shell,
+            diagnostics_export,
+            open_project_window,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let RunEvent::ExitRequested { code, api, .. } = event {
+                shutdown::on_exit_requested(app, &api, code);
+            }
+        });
 }
@@ -1,17 +1,19 @@
+use serde::Serialize;
 use tauri::{
     menu::{Menu, MenuItem, Submenu},
-    App, AppHandle, Emitter, Manager,
+    App, AppHandle, Emitter, EventTarget,
 };
 
+use crate::windows::focused_window_label;
+
 pub fn setup(app: &App) -> Result<(), Box<dyn std::error::Error>> {
     // On Linux/WSL2, menu creation can fail due to dbus issues
     // We make this non-fatal to allow the app to start without menus
     match create_menu(app) {
         Ok(menu) => {
-            if let Some(window) = app.get_webview_window("main") {
-                if let Err(e) = window.set_menu(menu) {
-                    eprintln!("Warning: Failed to set window menu: {}", e);
-                }
+            // App-wide so every project window gets the same menu
+            if let Err(e) = app.set_menu(menu) {
+                eprintln!("Warning: Failed to set app menu: {}", e);
             }
         }
         Err(e) => {
@@ -24,13 +26,61 @@ pub fn setup(app: &App) -> Result<(), Box<dyn std::error::Error>> {
 
 fn create_menu(app: &App) -> Result<Menu<tauri::Wry>, Box<dyn std::error::Error>> {
     // File menu
-    let open_project = MenuItem::with_id(app, "open_project", "Open Project", true, None::<&str>)?;
+    let open_project = MenuItem::with_id(
+        app,
+        "open_project",
+        "Open Project",
+        true,
+        Some("CmdOrCtrl+O"),
+    )?;
+    let recent_projects = MenuItem::with_id(
+        app,
+        "recent_projects",
+        "Recent Projects",
+        true,
+        Some("CmdOrCtrl+Shift+O"),
+    )?;
     let close_project =
         MenuItem::with_id(app, "close_project", "Close Project", true, None::<&str>)?;
     let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
 
-    let file_menu =
-        Submenu::with_items(app, "File", true, &[&open_project, &close_project, &quit])?;
+    let file_menu = Submenu::with_items(
+        app,
+        "File",
+        true,
+        &[&open_project, &recent_projects, &close_project, &quit],
+    )?;
+
+    // Session menu
+    let new_session =
+        MenuItem::with_id(app, "new_session", "New Session", true, Some("CmdOrCtrl+N"))?;
+    let cancel_run = MenuItem::with_id(app, "cancel_run", "Cancel Run", true, Some("CmdOrCtrl+."))?;
+    let compact_context = MenuItem::with_id(
+        app,
+        "compact_context",
+        "Compact Context",
+        true,
+        Some("CmdOrCtrl+Shift+K"),
+    )?;
+    let toggle_plan_mode = MenuItem::with_id(
+        app,
+        "toggle_plan_mode",
+        "Toggle Plan Mode",
+        true,
+        Some("CmdOrCtrl+Shift+P"),
+    )?;
+
+    let session_menu = Submenu::with_items(
+        app,
+        "Session",
+        true,
+        &[
+            &new_session,
+            &cancel_run,
+            &compact_context,
+            &toggle_plan_mode,
+        ],
+    )?;
 
     // View menu
     let view_chat = MenuItem::with_id(app, "view_chat", "Chat", true, None::<&str>)?;
@@ -44,40 +94,31 @@ fn create_menu(app: &App) -> Result<Menu<tauri::Wry>, Box<dyn std::error::Error>
         &[&view_chat, &view_changes, &view_settings],
     )?;
 
-    let menu = Menu::with_items(app, &[&file_menu, &view_menu])?;
+    let menu = Menu::with_items(app, &[&file_menu, &session_menu, &view_menu])?;
 
     Ok(menu)
 }
 
-pub fn handle_event(app: &AppHandle, event_id: &str) {
-    let window = app.get_webview_window("main");
+/// Send a menu action to the focused window only, so it acts on that
+/// window's project and session.
+fn emit_to_focused<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Some(label) = focused_window_label(app) {
+        let _ = app.emit_to(EventTarget::webview_window(label), event, payload);
+    }
+}
 
+pub fn handle_event(app: &AppHandle, event_id: &str) {
     match event_id {
-        "open_project" => {
-            if let Some(win) = window {
-                let _ = win.emit("menu-open-project", ());
-            }
-        }
-        "close_project" => {
-            if let Some(win) = window {
-                let _ = win.emit("menu-close-project", ());
-            }
-        }
-        "view_chat" => {
-            if let Some(win) = window {
-                let _ = win.emit("menu-navigate", "chat");
-            }
-        }
-        "view_changes" => {
-            if let Some(win) = window {
-                let _ = win.emit("menu-navigate", "changes");
-            }
-        }
-        "view_settings" => {
-            if let Some(win) = window {
-                let _ = win.emit("menu-navigate", "settings");
-            }
-        }
+        "open_project" => emit_to_focused(app, "menu-open-project", ()),
+        "recent_projects" => emit_to_focused(app, "menu-recent-projects", ()),
+        "close_project" => emit_to_focused(app, "menu-close-project", ()),
+        "new_session" => emit_to_focused(app, "menu-new-session", ()),
+        "cancel_run" => emit_to_focused(app, "menu-cancel-run", ()),
+        "compact_context" => emit_to_focused(app, "menu-compact-context", ()),
+        "toggle_plan_mode" => emit_to_focused(app, "menu-toggle-plan-mode", ()),
+        "view_chat" => emit_to_focused(app, "menu-navigate", "chat"),
+        "view_changes" => emit_to_focused(app, "menu-navigate", "changes"),
+        "view_settings" => emit_to_focused(app, "menu-navigate", "settings"),
         "quit" => {
             app.exit(0);
         }
@@ -0,0 +1,181 @@
+use std::env;
+use std::path::Path;
+
+use super::types::SetupCheck;
+use crate::agent::providers::catalog::{check_credentials, default_key_env, CredentialsError};
+use crate::agent::tools::probe_shell;
+use crate::config::{NetworkConfig, ProjectConfig, ShellKind};
+use crate::git::{git_command, GitService};
+
+/// Checks that `provider`'s API key is set and accepted. Uses the project's
+/// key variable, base URL and network settings when it is the project's provider.
+pub async fn check_provider(provider: &str, config: Option<&ProjectConfig>) -> Vec<SetupCheck> {
+    let project_agent = config
+        .map(|config| &config.agent)
+        .filter(|agent| agent.provider == provider);
+    let key_env = match project_agent {
+        Some(agent) => agent.api_key_env.clone(),
+        None => match default_key_env(provider) {
+            Some(key_env) => key_env.to_string(),
+            None => {
+                return vec![SetupCheck::error(
+                    "Provider",
+                    format!("Unknown provider '{}'", provider),
+                    "Choose anthropic or gemini",
+                )]
+            }
+        },
+    };
+
+    let api_key = match env::var(&key_env) {
+        Ok(key) if !key.trim().is_empty() => key,
+        _ => {
+            return vec![SetupCheck::error(
+                "API key",
+                format!("{} is not set", key_env),
+                format!(
+                    "Set {} in the environment Devflow starts from and restart it. \
+                     On macOS, apps opened from the Dock or Finder don't see variables \
+                     exported in shell profiles",
+                    key_env
+                ),
+            )]
+        }
+    };
+    let mut checks = vec![SetupCheck::ok("API key", format!("{} is set", key_env))];
+
+    let default_network = NetworkConfig::default();
+    let network = config.map_or(&default_network, |config| &config.network);
+    let base_url = project_agent.and_then(|agent| agent.base_url.as_deref());
+
+    checks.push(
+        match check_credentials(network, provider, base_url, &api_key).await {
+            Ok(()) => SetupCheck::ok("Connection", format!("Connected to {}", provider)),
+            Err(CredentialsError::Rejected(message)) => SetupCheck::error(
+                "Connection",
+                format!("{} rejected the API key ({})", provider, message),
+                format!(
+                    "Check that {} holds a valid {} key with API access",
+                    key_env, provider
+                ),
+            ),
+            Err(CredentialsError::Unreachable(message)) => SetupCheck::error(
+                "Connection",
+                format!("Could not reach {}: {}", provider, message),
+                "Check the network connection, and the proxy and CA certificate \
+                 under [network] if you are behind a corporate proxy",
+            ),
+            Err(CredentialsError::Network(message)) => SetupCheck::error(
+                "Connection",
+                message,
+                "Fix the proxy or CA certificate under [network] in the project config",
+            ),
+            Err(CredentialsError::Other(message)) => SetupCheck::error(
+                "Connection",
+                format!("Unexpected response from {}: {}", provider, message),
+                "Try again later; if it persists, check the provider's status page",
+            ),
+        },
+    );
+    checks
+}
+
+/// Checks that git runs and has an identity for commits, and that the
+/// project, if given, is a repository.
+pub fn check_git(project_path: Option<&Path>) -> Vec<SetupCheck> {
+    let version = match run_git(&["--version"]) {
+        Some(version) => version,
+        None => {
+            return vec![SetupCheck::error(
+                "git",
+                "git was not found",
+                "Install git and make sure it is on PATH, then restart Devflow",
+            )]
+        }
+    };
+    let mut checks = vec![SetupCheck::ok("git", version)];
+
+    let name = run_git(&["config", "user.name"]).filter(|v| !v.is_empty());
+    let email = run_git(&["config", "user.email"]).filter(|v| !v.is_empty());
+    checks.push(match (name, email) {
+        (Some(name), Some(email)) => {
+            SetupCheck::ok("git identity", format!("{} <{}>", name, email))
+        }
+        _ => SetupCheck::warning(
+            "git identity",
+            "user.name or user.email is not set",
+            "Run git config --global user.name \"Your Name\" and \
+             git config --global user.email you@example.com so commits work",
+        ),
+    });
+
+    if let Some(project_path) = project_path {
+        checks.push(match GitService::open(project_path) {
+            Ok(_) => SetupCheck::ok("Repository", "The project is a git repository"),
+            Err(_) => SetupCheck::warning(
+                "Repository",
+                "The project is not a git repository",
+                "Run git init in the project to use the diff view and checkpoints",
+            ),
+        });
+    }
+    checks
+}
+
+/// `git <args>` output, or None if git could not run or failed.
+fn run_git(args: &[&str]) -> Option<String> {
+    let output = git_command().args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Checks that the shell the bash tool uses runs commands.
+pub async fn check_shell(kind: ShellKind) -> Vec<SetupCheck> {
+    vec![match probe_shell(kind).await {
+        Ok(name) => SetupCheck::ok("Shell", format!("Commands run in {}", name)),
+        Err(message) => SetupCheck::error("Shell", message, shell_hint(kind)),
+    }]
+}
+
+fn shell_hint(kind: ShellKind) -> &'static str {
+    match kind {
+        ShellKind::Powershell if !cfg!(windows) => {
+            "Install PowerShell 7 (pwsh), or set execution.shell to \"auto\""
+        }
+        ShellKind::Cmd if !cfg!(windows) => {
+            "cmd only exists on Windows; set execution.shell to \"auto\""
+        }
+        ShellKind::Sh if cfg!(windows) => {
+            "Install Git for Windows to get sh on PATH, or set execution.shell to \"auto\""
+        }
+        _ => "Make sure the shell is installed and on PATH, then restart Devflow",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::SetupStatus;
+
+    #[tokio::test]
+    async fn test_check_provider_unknown() {
+        let checks = check_provider("nope", None).await;
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, SetupStatus::Error);
+        assert!(checks[0].message.contains("nope"));
+    }
+
+    #[test]
+    fn test_check_git_reports_missing_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let checks = check_git(Some(dir.path()));
+        assert_eq!(checks[0].status, SetupStatus::Ok);
+        assert!(checks[0].message.starts_with("git version"));
+        let repository = checks.last().unwrap();
+        assert_eq!(repository.name, "Repository");
+        assert_eq!(repository.status, SetupStatus::Warning);
+        assert!(repository.hint.is_some());
+    }
+}
@@ -0,0 +1,34 @@
+use std::path::Path;
+
+use super::checks::{check_git, check_provider, check_shell};
+use super::types::SetupCheck;
+use crate::config::{ConfigService, ShellKind};
+
+/// Verifies `provider`'s API key with a minimal request. With a project, its
+/// key variable, base URL and network settings are used.
+#[tauri::command]
+pub async fn setup_check_provider(
+    provider: String,
+    project_path: Option<String>,
+) -> Vec<SetupCheck> {
+    let config =
+        project_path.and_then(|path| ConfigService::load_project_config(Path::new(&path)).ok());
+    check_provider(&provider, config.as_ref()).await
+}
+
+#[tauri::command]
+pub async fn setup_check_git(project_path: Option<String>) -> Result<Vec<SetupCheck>, String> {
+    tokio::task::spawn_blocking(move || check_git(project_path.as_deref().map(Path::new)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Runs a trivial command in the shell the bash tool uses for the project
+/// (the platform default without one).
+#[tauri::command]
+pub async fn setup_check_shell(project_path: Option<String>) -> Vec<SetupCheck> {
+    let shell = project_path
+        .and_then(|path| ConfigService::load_project_config(Path::new(&path)).ok())
+        .map_or(ShellKind::Auto, |config| config.execution.shell);
+    check_shell(shell).await
+}
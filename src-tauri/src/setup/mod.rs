@@ -0,0 +1,9 @@
+//! First-run checks the app runs to guide new users: provider credentials,
+//! git and the shell, each reported as structured results instead of an
+//! error halfway through the first conversation.
+
+mod checks;
+pub mod commands;
+mod types;
+
+pub use types::*;
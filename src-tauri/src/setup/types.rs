@@ -0,0 +1,56 @@
+use serde::Serialize;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStatus {
+    Ok,
+    /// Works, but some features will be missing
+    Warning,
+    Error,
+}
+
+/// One row of the setup checklist.
+#[derive(Debug, Clone, Serialize, TS)]
+#[ts(export)]
+pub struct SetupCheck {
+    /// What was checked, e.g. "API key" or "git identity"
+    pub name: String,
+    pub status: SetupStatus,
+    /// What was found, e.g. "git version 2.43.0"
+    pub message: String,
+    /// How to fix a warning or error
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub hint: Option<String>,
+}
+
+impl SetupCheck {
+    pub fn ok(name: &str, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: SetupStatus::Ok,
+            message: message.into(),
+            hint: None,
+        }
+    }
+
+    pub fn warning(name: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: SetupStatus::Warning,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    pub fn error(name: &str, message: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: SetupStatus::Error,
+            message: message.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
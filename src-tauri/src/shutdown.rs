@@ -0,0 +1,41 @@
+//! Graceful exit: every agent run is cancelled and given time to unwind, so
+//! its bash children are killed, its transcript gets the run end, and the
+//! partial reply and run journal are written before the process goes away.
+
+use std::time::Duration;
+
+use futures::future::join_all;
+use tauri::{AppHandle, ExitRequestApi, Manager};
+
+use crate::agent::WindowStates;
+
+/// How long runs get to stop before the app exits anyway
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Holds the exit until cancelled runs have finished, then exits with `code`.
+pub fn on_exit_requested(app: &AppHandle, api: &ExitRequestApi, code: Option<i32>) {
+    let pending = app.state::<WindowStates>().shutdown();
+    if pending.is_empty() {
+        return;
+    }
+
+    api.prevent_exit();
+    log::info!(
+        "Waiting for {} agent run(s) to stop before exiting",
+        pending.len()
+    );
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let finished = join_all(pending.iter().map(|token| token.cancelled()));
+        if tokio::time::timeout(SHUTDOWN_GRACE, finished)
+            .await
+            .is_err()
+        {
+            log::warn!(
+                "Agent runs did not stop within {}s, exiting anyway",
+                SHUTDOWN_GRACE.as_secs()
+            );
+        }
+        app.exit(code.unwrap_or(0));
+    });
+}
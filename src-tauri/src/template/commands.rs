@@ -1,5 +1,12 @@
+use std::collections::HashMap;
+use std::path::Path;
+
 use super::service::TemplateService;
-use super::types::{CommitContext, ReviewCommentsContext, TemplateContent};
+use super::types::{
+    CommitContext, ReviewCommentsContext, ScaffoldInfo, TemplateContent, TemplateSourceStatus,
+};
+use crate::agent::attribution::Attribution;
+use crate::config::ConfigService;
 
 #[tauri::command]
 pub fn template_load() -> Result<TemplateContent, String> {
@@ -23,13 +30,61 @@ pub fn template_render_review_comments(context: ReviewCommentsContext) -> Result
         .map_err(|e| e.to_string())
 }
 
+/// Renders the commit prompt, adding the attribution trailer instruction when enabled for the project.
 #[tauri::command]
-pub fn template_render_commit(context: CommitContext) -> Result<String, String> {
+pub fn template_render_commit(
+    context: CommitContext,
+    project_path: Option<String>,
+) -> Result<String, String> {
     let service = TemplateService::new().map_err(|e| e.to_string())?;
-    service.render_commit(&context).map_err(|e| e.to_string())
+    let rendered = service.render_commit(&context).map_err(|e| e.to_string())?;
+
+    let attribution = project_path
+        .and_then(|path| ConfigService::load_project_config(Path::new(&path)).ok())
+        .and_then(|config| Attribution::from_config(&config.attribution, &config.agent.model));
+
+    Ok(match attribution {
+        Some(attribution) => format!("{}\n\n{}", rendered, attribution.commit_instruction()),
+        None => rendered,
+    })
 }
 
 #[tauri::command]
 pub fn template_get_defaults() -> TemplateContent {
     TemplateService::get_defaults()
 }
+
+#[tauri::command]
+pub fn template_list_scaffolds(project_path: String) -> Result<Vec<ScaffoldInfo>, String> {
+    let service = TemplateService::new().map_err(|e| e.to_string())?;
+    Ok(service.list_scaffolds(Path::new(&project_path)))
+}
+
+/// Instantiates a scaffold into the project; returns the created files.
+#[tauri::command]
+pub fn template_scaffold(
+    project_path: String,
+    name: String,
+    variables: HashMap<String, String>,
+) -> Result<Vec<String>, String> {
+    let service = TemplateService::new().map_err(|e| e.to_string())?;
+    service
+        .scaffold(Path::new(&project_path), &name, &variables)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn template_list_sources(project_path: String) -> Result<Vec<TemplateSourceStatus>, String> {
+    let service = TemplateService::new().map_err(|e| e.to_string())?;
+    Ok(service.list_sources(Path::new(&project_path)))
+}
+
+/// Clones or updates the project's git template sources at their pinned
+/// revisions. Runs off the main thread since fetching can take a while.
+#[tauri::command]
+pub async fn template_sync(project_path: String) -> Result<Vec<TemplateSourceStatus>, String> {
+    let service = TemplateService::new().map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || service.sync_sources(Path::new(&project_path)))
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -32,6 +32,31 @@ pub enum TemplateError {
 
     #[error("Could not determine config directory")]
     NoConfigDir,
+
+    #[error("Unknown scaffold template: {0}")]
+    ScaffoldNotFound(String),
+
+    #[error("Scaffold file path must stay inside the project: {0}")]
+    InvalidScaffoldPath(String),
+
+    #[error("File already exists: {0}")]
+    FileExists(PathBuf),
+
+    #[error("Invalid scaffold schema {path}: {source}")]
+    InvalidSchema {
+        path: PathBuf,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[error("Invalid template variables: {}", .0.join("; "))]
+    InvalidVariables(Vec<String>),
+
+    #[error("Invalid template source: {0}")]
+    InvalidSource(String),
+
+    #[error("Template source sync failed: {0}")]
+    GitError(String),
 }
 
 impl serde::Serialize for TemplateError {
@@ -1,10 +1,14 @@
 pub mod commands;
 mod defaults;
 mod error;
+mod scaffold;
 mod service;
+mod sources;
 mod types;
+mod variables;
 
 pub use commands::*;
 pub use error::TemplateError;
+pub use scaffold::ScaffoldFile;
 pub use service::TemplateService;
 pub use types::*;
@@ -0,0 +1,330 @@
+//! Scaffolds: directories of Handlebars templates instantiated into a project.
+//! File paths and contents are both templates, so `src/{{name}}.tsx` with
+//! `name = "Button"` creates `src/Button.tsx`. Team scaffolds committed in
+//! `<project>/.devflow/templates/<name>/` take precedence over the configured
+//! template sources (in order), then the user's in
+//! `<config dir>/templates/scaffolds/<name>/`. A scaffold may declare typed
+//! variables in its `.scaffold.toml` (see `variables`).
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use handlebars::Handlebars;
+use walkdir::WalkDir;
+
+use super::error::TemplateError;
+use super::service::TemplateService;
+use super::types::{ScaffoldInfo, ScaffoldSource};
+use super::variables::{load_schema, resolve_variables, SCHEMA_FILENAME};
+
+const SCAFFOLDS_DIR: &str = "scaffolds";
+const PROJECT_SCAFFOLDS_DIR: &str = ".devflow/templates";
+
+/// A scaffold file rendered with the variables, not yet written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScaffoldFile {
+    /// Relative to the project, with `/` separators
+    pub path: String,
+    pub content: String,
+}
+
+impl TemplateService {
+    /// Directories searched for scaffolds, in precedence order, with the
+    /// configured source's name for remote ones.
+    fn scaffold_sources(
+        &self,
+        project_path: &Path,
+    ) -> Vec<(ScaffoldSource, Option<String>, PathBuf)> {
+        let mut sources = vec![(
+            ScaffoldSource::Project,
+            None,
+            project_path.join(PROJECT_SCAFFOLDS_DIR),
+        )];
+        for source in Self::configured_sources(project_path) {
+            if let Ok(dir) = self.source_scaffolds_dir(project_path, &source) {
+                sources.push((ScaffoldSource::Remote, Some(source.name), dir));
+            }
+        }
+        sources.push((
+            ScaffoldSource::User,
+            None,
+            self.templates_dir().join(SCAFFOLDS_DIR),
+        ));
+        sources
+    }
+
+    /// Scaffolds available to the project, sorted by name.
+    pub fn list_scaffolds(&self, project_path: &Path) -> Vec<ScaffoldInfo> {
+        let mut scaffolds = BTreeMap::new();
+        for (source, source_name, dir) in self.scaffold_sources(project_path) {
+            for name in scaffold_names(&dir) {
+                if scaffolds.contains_key(&name) {
+                    continue;
+                }
+                let scaffold_dir = dir.join(&name);
+                let files = scaffold_files(&scaffold_dir).len();
+                // A broken schema surfaces when the scaffold is rendered
+                let variables = load_schema(&scaffold_dir).unwrap_or_default();
+                scaffolds.insert(
+                    name.clone(),
+                    ScaffoldInfo {
+                        name,
+                        source,
+                        source_name: source_name.clone(),
+                        files: files as u32,
+                        variables,
+                    },
+                );
+            }
+        }
+        scaffolds.into_values().collect()
+    }
+
+    fn scaffold_dir(&self, project_path: &Path, name: &str) -> Result<PathBuf, TemplateError> {
+        let mut components = Path::new(name).components();
+        let valid = matches!(
+            (components.next(), components.next()),
+            (Some(Component::Normal(_)), None)
+        );
+        if valid {
+            for (_, _, dir) in self.scaffold_sources(project_path) {
+                let dir = dir.join(name);
+                if dir.is_dir() {
+                    return Ok(dir);
+                }
+            }
+        }
+        Err(TemplateError::ScaffoldNotFound(name.to_string()))
+    }
+
+    /// Renders every file of the scaffold `name` with `variables`, checked
+    /// against its schema first. Missing or invalid variables are errors, so a
+    /// scaffold never produces half-filled files.
+    pub fn render_scaffold(
+        &self,
+        project_path: &Path,
+        name: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<Vec<ScaffoldFile>, TemplateError> {
+        let dir = self.scaffold_dir(project_path, name)?;
+        let variables = resolve_variables(&load_schema(&dir)?, variables)?;
+        let mut handlebars = Handlebars::new();
+        handlebars.set_strict_mode(true);
+        // Generated files are source code, not HTML
+        handlebars.register_escape_fn(handlebars::no_escape);
+
+        let mut files = Vec::new();
+        for relative in scaffold_files(&dir) {
+            let path = dir.join(&relative);
+            let template = fs::read_to_string(&path)
+                .map_err(|e| TemplateError::ReadError { path, source: e })?;
+            let path = handlebars.render_template(&relative, &variables)?;
+            if !is_relative_inside(&path) {
+                return Err(TemplateError::InvalidScaffoldPath(path));
+            }
+            files.push(ScaffoldFile {
+                path,
+                content: handlebars.render_template(&template, &variables)?,
+            });
+        }
+        Ok(files)
+    }
+
+    /// Renders the scaffold and writes it into the project, refusing to
+    /// overwrite existing files. Returns the created paths.
+    pub fn scaffold(
+        &self,
+        project_path: &Path,
+        name: &str,
+        variables: &HashMap<String, String>,
+    ) -> Result<Vec<String>, TemplateError> {
+        let files = self.render_scaffold(project_path, name, variables)?;
+        if let Some(existing) = files
+            .iter()
+            .map(|file| project_path.join(&file.path))
+            .find(|path| path.exists())
+        {
+            return Err(TemplateError::FileExists(existing));
+        }
+
+        for file in &files {
+            let path = project_path.join(&file.path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| TemplateError::CreateDirError {
+                    path: parent.to_path_buf(),
+                    source: e,
+                })?;
+            }
+            fs::write(&path, &file.content)
+                .map_err(|e| TemplateError::WriteError { path, source: e })?;
+        }
+        Ok(files.into_iter().map(|file| file.path).collect())
+    }
+}
+
+/// Scaffolds in a directory: its subdirectories, skipping hidden ones such as
+/// the `.git` of a cached source.
+pub(super) fn scaffold_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.'))
+        .collect()
+}
+
+/// Files of a scaffold directory, relative with `/` separators, without its schema.
+fn scaffold_files(dir: &Path) -> Vec<String> {
+    let mut files: Vec<String> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(dir).ok()?;
+            Some(relative.to_string_lossy().replace('\\', "/"))
+        })
+        .filter(|relative| relative != SCHEMA_FILENAME)
+        .collect();
+    files.sort();
+    files
+}
+
+/// Whether a rendered path stays below the project: relative, no `..`.
+fn is_relative_inside(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (TemplateService, tempfile::TempDir, tempfile::TempDir) {
+        let config = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let component = project.path().join(".devflow/templates/component");
+        fs::create_dir_all(component.join("src/components")).unwrap();
+        fs::write(
+            component.join("src/components/{{name}}.tsx"),
+            "export function {{name}}() {\n  return <div className=\"{{class}}\" />;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            component.join("src/components/{{name}}.test.tsx"),
+            "import { {{name}} } from \"./{{name}}\";\n",
+        )
+        .unwrap();
+        let user = config.path().join("templates/scaffolds/component");
+        fs::create_dir_all(&user).unwrap();
+        fs::write(user.join("other.txt"), "user").unwrap();
+        let service = TemplateService::with_config_dir(config.path().to_path_buf());
+        (service, config, project)
+    }
+
+    fn variables(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_list_scaffolds_prefers_project() {
+        let (service, _config, project) = setup();
+        let scaffolds = service.list_scaffolds(project.path());
+        assert_eq!(scaffolds.len(), 1);
+        assert_eq!(scaffolds[0].name, "component");
+        assert_eq!(scaffolds[0].source, ScaffoldSource::Project);
+        assert_eq!(scaffolds[0].files, 2);
+    }
+
+    #[test]
+    fn test_scaffold_renders_paths_and_contents() {
+        let (service, _config, project) = setup();
+        let vars = variables(&[("name", "Button"), ("class", "btn")]);
+
+        let created = service
+            .scaffold(project.path(), "component", &vars)
+            .unwrap();
+        assert_eq!(
+            created,
+            vec![
+                "src/components/Button.test.tsx",
+                "src/components/Button.tsx"
+            ]
+        );
+        let content = fs::read_to_string(project.path().join("src/components/Button.tsx")).unwrap();
+        assert!(content.contains("export function Button()"));
+        assert!(content.contains("className=\"btn\""));
+
+        // Existing files are never overwritten
+        let err = service
+            .scaffold(project.path(), "component", &vars)
+            .unwrap_err();
+        assert!(matches!(err, TemplateError::FileExists(_)));
+    }
+
+    #[test]
+    fn test_scaffold_errors() {
+        let (service, _config, project) = setup();
+
+        let err = service
+            .render_scaffold(project.path(), "component", &variables(&[("name", "X")]))
+            .unwrap_err();
+        assert!(err.to_string().contains("class"));
+
+        for name in ["missing", "../component", ""] {
+            let err = service
+                .render_scaffold(project.path(), name, &HashMap::new())
+                .unwrap_err();
+            assert!(matches!(err, TemplateError::ScaffoldNotFound(_)));
+        }
+
+        let vars = variables(&[("name", "../../escape"), ("class", "x")]);
+        let err = service
+            .render_scaffold(project.path(), "component", &vars)
+            .unwrap_err();
+        assert!(matches!(err, TemplateError::InvalidScaffoldPath(_)));
+    }
+
+    #[test]
+    fn test_scaffold_schema_types_variables() {
+        let (service, _config, project) = setup();
+        let dir = project.path().join(".devflow/templates/page");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(SCHEMA_FILENAME),
+            "[[variables]]\nname = \"name\"\npattern = \"[A-Z][a-z]*\"\n\n[[variables]]\nname = \"story\"\ntype = \"boolean\"\ndefault = \"false\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("{{name}}.tsx"),
+            "{{name}}{{#if story}} with story{{/if}}",
+        )
+        .unwrap();
+
+        let scaffolds = service.list_scaffolds(project.path());
+        let page = scaffolds.iter().find(|s| s.name == "page").unwrap();
+        assert_eq!(page.files, 1);
+        assert_eq!(page.variables.len(), 2);
+
+        let files = service
+            .render_scaffold(project.path(), "page", &variables(&[("name", "Home")]))
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "Home.tsx");
+        assert_eq!(files[0].content, "Home");
+
+        let err = service
+            .render_scaffold(project.path(), "page", &variables(&[("name", "home")]))
+            .unwrap_err();
+        assert!(matches!(err, TemplateError::InvalidVariables(_)));
+    }
+}
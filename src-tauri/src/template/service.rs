@@ -31,7 +31,7 @@ impl TemplateService {
         Self { config_dir }
     }
 
-    fn templates_dir(&self) -> PathBuf {
+    pub(super) fn templates_dir(&self) -> PathBuf {
         self.config_dir.join(TEMPLATES_DIR)
     }
 
@@ -0,0 +1,301 @@
+//! Template sources configured in `[[templates.sources]]`: shared scaffolds
+//! from a git repository or a directory outside the project. Git sources are
+//! cloned into `<config dir>/templates/cache/<name>/` by `template_sync` and
+//! checked out at their pinned `rev`; listing and rendering scaffolds only
+//! read the cache, so they never touch the network.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::error::TemplateError;
+use super::scaffold::scaffold_names;
+use super::service::TemplateService;
+use super::types::{TemplateSourceKind, TemplateSourceStatus};
+use crate::config::{ConfigService, TemplateSource};
+use crate::git::git_command;
+
+const CACHE_DIR: &str = "cache";
+
+impl TemplateService {
+    /// Sources configured for the project; none when it has no readable config.
+    pub(super) fn configured_sources(project_path: &Path) -> Vec<TemplateSource> {
+        ConfigService::load_project_config(project_path)
+            .map(|config| config.templates.sources)
+            .unwrap_or_default()
+    }
+
+    fn cache_dir(&self, name: &str) -> Result<PathBuf, TemplateError> {
+        let mut components = Path::new(name).components();
+        match (components.next(), components.next()) {
+            (Some(Component::Normal(_)), None) => {
+                Ok(self.templates_dir().join(CACHE_DIR).join(name))
+            }
+            _ => Err(TemplateError::InvalidSource(format!(
+                "'{}' is not a valid source name",
+                name
+            ))),
+        }
+    }
+
+    /// Directory holding the source's scaffolds: its cache for git sources,
+    /// else the configured directory, plus `subdir`.
+    pub(super) fn source_scaffolds_dir(
+        &self,
+        project_path: &Path,
+        source: &TemplateSource,
+    ) -> Result<PathBuf, TemplateError> {
+        let root = match (&source.git, &source.path) {
+            (Some(_), _) => self.cache_dir(&source.name)?,
+            (None, Some(path)) => project_path.join(path),
+            (None, None) => {
+                return Err(TemplateError::InvalidSource(format!(
+                    "'{}' needs a git URL or a path",
+                    source.name
+                )))
+            }
+        };
+        Ok(match &source.subdir {
+            Some(subdir) => root.join(subdir),
+            None => root,
+        })
+    }
+
+    /// Status of every source configured for the project, without syncing.
+    pub fn list_sources(&self, project_path: &Path) -> Vec<TemplateSourceStatus> {
+        Self::configured_sources(project_path)
+            .iter()
+            .map(|source| self.source_status(project_path, source, None))
+            .collect()
+    }
+
+    /// Syncs every git source of the project. A source that fails reports the
+    /// error in its status without stopping the others.
+    pub fn sync_sources(&self, project_path: &Path) -> Vec<TemplateSourceStatus> {
+        Self::configured_sources(project_path)
+            .iter()
+            .map(|source| self.sync_source(project_path, source))
+            .collect()
+    }
+
+    /// Clones the source into its cache, or fetches it when already cached,
+    /// then checks out the pinned revision. Directory sources are only checked.
+    pub fn sync_source(
+        &self,
+        project_path: &Path,
+        source: &TemplateSource,
+    ) -> TemplateSourceStatus {
+        let error = match &source.git {
+            Some(url) => self.sync_git(source, url).err().map(|e| e.to_string()),
+            None => None,
+        };
+        self.source_status(project_path, source, error)
+    }
+
+    fn sync_git(&self, source: &TemplateSource, url: &str) -> Result<(), TemplateError> {
+        let dir = self.cache_dir(&source.name)?;
+        if dir.join(".git").is_dir() {
+            run_git(&dir, &["remote", "set-url", "origin", url])?;
+            run_git(&dir, &["fetch", "--tags", "--force", "--prune", "origin"])?;
+            if source.rev.is_none() {
+                // Follow a change of the remote's default branch
+                run_git(&dir, &["remote", "set-head", "origin", "--auto"])?;
+            }
+        } else {
+            // A previous clone may have been interrupted
+            if dir.exists() {
+                fs::remove_dir_all(&dir).map_err(|e| TemplateError::WriteError {
+                    path: dir.clone(),
+                    source: e,
+                })?;
+            }
+            let parent = dir.parent().unwrap_or(&dir);
+            fs::create_dir_all(parent).map_err(|e| TemplateError::CreateDirError {
+                path: parent.to_path_buf(),
+                source: e,
+            })?;
+            run_git(parent, &["clone", "--no-checkout", "--", url, &source.name])?;
+        }
+
+        let commit = resolve_rev(&dir, source.rev.as_deref())?;
+        run_git(&dir, &["checkout", "--force", "--detach", &commit])?;
+        Ok(())
+    }
+
+    fn source_status(
+        &self,
+        project_path: &Path,
+        source: &TemplateSource,
+        error: Option<String>,
+    ) -> TemplateSourceStatus {
+        let (kind, location) = match (&source.git, &source.path) {
+            (Some(url), _) => (TemplateSourceKind::Git, url.clone()),
+            (None, Some(path)) => (
+                TemplateSourceKind::Directory,
+                project_path.join(path).to_string_lossy().into_owned(),
+            ),
+            (None, None) => (TemplateSourceKind::Directory, String::new()),
+        };
+        let commit = match kind {
+            TemplateSourceKind::Git => self
+                .cache_dir(&source.name)
+                .ok()
+                .filter(|dir| dir.join(".git").is_dir())
+                .and_then(|dir| run_git(&dir, &["rev-parse", "HEAD"]).ok()),
+            TemplateSourceKind::Directory => None,
+        };
+        let dir = self.source_scaffolds_dir(project_path, source);
+        let scaffolds = match &dir {
+            Ok(dir) if dir.is_dir() => Some(scaffold_names(dir).len()),
+            _ => None,
+        };
+
+        TemplateSourceStatus {
+            name: source.name.clone(),
+            kind,
+            location,
+            rev: source.rev.clone(),
+            commit,
+            available: scaffolds.is_some(),
+            scaffolds: scaffolds.unwrap_or(0) as u32,
+            error: error.or_else(|| dir.err().map(|e| e.to_string())),
+        }
+    }
+}
+
+/// Commit `rev` names in the cache, preferring the fetched remote branch over
+/// a stale local one. Without a pin, the remote's default branch.
+fn resolve_rev(dir: &Path, rev: Option<&str>) -> Result<String, TemplateError> {
+    let candidates = match rev {
+        Some(rev) if rev.starts_with('-') => {
+            return Err(TemplateError::InvalidSource(format!(
+                "'{}' is not a valid revision",
+                rev
+            )))
+        }
+        Some(rev) => vec![format!("origin/{}", rev), rev.to_string()],
+        None => vec!["origin/HEAD".to_string()],
+    };
+    candidates
+        .iter()
+        .find_map(|candidate| {
+            let spec = format!("{}^{{commit}}", candidate);
+            run_git(dir, &["rev-parse", "--verify", "--quiet", &spec]).ok()
+        })
+        .ok_or_else(|| {
+            TemplateError::GitError(format!(
+                "Unknown revision '{}'",
+                rev.unwrap_or("origin/HEAD")
+            ))
+        })
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, TemplateError> {
+    let output = git_command()
+        .args(args)
+        .current_dir(dir)
+        // Fail instead of waiting for credentials nobody can type
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| TemplateError::GitError(format!("Failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(TemplateError::GitError(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit_scaffold(repo: &Path, content: &str) -> String {
+        let dir = repo.join("scaffolds/component");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("{{name}}.txt"), content).unwrap();
+        git(repo, &["add", "."]);
+        git(repo, &["commit", "-m", content]);
+        git(repo, &["rev-parse", "HEAD"])
+    }
+
+    fn source(url: &Path, rev: Option<&str>) -> TemplateSource {
+        TemplateSource {
+            name: "team".to_string(),
+            git: Some(url.to_string_lossy().into_owned()),
+            path: None,
+            rev: rev.map(str::to_string),
+            subdir: Some("scaffolds".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_sync_git_source_pins_revision() {
+        let remote = tempfile::tempdir().unwrap();
+        let config = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        git(remote.path(), &["init"]);
+        git(remote.path(), &["config", "user.email", "test@test.com"]);
+        git(remote.path(), &["config", "user.name", "Test"]);
+        let first = commit_scaffold(remote.path(), "v1");
+        git(remote.path(), &["tag", "v1"]);
+        let second = commit_scaffold(remote.path(), "v2");
+        let service = TemplateService::with_config_dir(config.path().to_path_buf());
+
+        let unsynced = service.source_status(project.path(), &source(remote.path(), None), None);
+        assert!(!unsynced.available);
+        assert_eq!(unsynced.commit, None);
+
+        let status = service.sync_source(project.path(), &source(remote.path(), Some("v1")));
+        assert_eq!(status.error, None);
+        assert!(status.available);
+        assert_eq!(status.scaffolds, 1);
+        assert_eq!(status.commit.as_deref(), Some(first.as_str()));
+        let cached = config
+            .path()
+            .join("templates/cache/team/scaffolds/component/{{name}}.txt");
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "v1");
+
+        // Syncing again fetches and moves to the remote HEAD when unpinned
+        let status = service.sync_source(project.path(), &source(remote.path(), None));
+        assert_eq!(status.error, None);
+        assert_eq!(status.commit.as_deref(), Some(second.as_str()));
+        assert_eq!(fs::read_to_string(&cached).unwrap(), "v2");
+
+        let status = service.sync_source(project.path(), &source(remote.path(), Some("nope")));
+        assert!(status.error.unwrap().contains("nope"));
+    }
+
+    #[test]
+    fn test_invalid_sources() {
+        let config = tempfile::tempdir().unwrap();
+        let project = tempfile::tempdir().unwrap();
+        let service = TemplateService::with_config_dir(config.path().to_path_buf());
+
+        let mut escaping = source(Path::new("/nonexistent"), None);
+        escaping.name = "../escape".to_string();
+        let status = service.sync_source(project.path(), &escaping);
+        assert!(!status.available);
+        assert!(status.error.is_some());
+
+        let empty = TemplateSource {
+            name: "empty".to_string(),
+            git: None,
+            path: None,
+            rev: None,
+            subdir: None,
+        };
+        let status = service.source_status(project.path(), &empty, None);
+        assert!(status.error.unwrap().contains("git URL or a path"));
+    }
+}
@@ -36,3 +36,105 @@ pub struct TemplateContent {
     pub review_comments: String,
     pub commit: String,
 }
+
+/// Where a scaffold comes from; with the same name, project scaffolds win over
+/// configured sources, which win over user ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaffoldSource {
+    /// `<project>/.devflow/templates/<name>/`
+    Project,
+    /// A `[[templates.sources]]` entry: a synced git repository or a directory
+    Remote,
+    /// `<config dir>/templates/scaffolds/<name>/`
+    User,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ScaffoldInfo {
+    pub name: String,
+    pub source: ScaffoldSource,
+    /// Name of the configured source, for `remote` scaffolds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub source_name: Option<String>,
+    /// Number of files the scaffold creates
+    pub files: u32,
+    /// Variables declared in the scaffold's `.scaffold.toml`, in order
+    pub variables: Vec<TemplateVariable>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateVariableType {
+    #[default]
+    String,
+    /// An integer or decimal, rendered as a number
+    Number,
+    /// `true` or `false`, usable in `{{#if}}` blocks
+    Boolean,
+    /// One of `options`
+    Choice,
+}
+
+/// A variable a scaffold asks for. Values are checked against it before
+/// anything is rendered, and the UI renders the list as a form.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(default, rename = "type")]
+    pub kind: TemplateVariableType,
+    /// Help text for the form field
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub description: Option<String>,
+    /// Used when no value is given; without a default the variable is required
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub default: Option<String>,
+    /// Regex the whole value must match (e.g. `[A-Z][A-Za-z0-9]*`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub pattern: Option<String>,
+    /// Allowed values of a `choice` variable
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+#[serde(rename_all = "snake_case")]
+pub enum TemplateSourceKind {
+    Git,
+    Directory,
+}
+
+/// State of a configured template source, from `template_list_sources` and `template_sync`.
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct TemplateSourceStatus {
+    pub name: String,
+    pub kind: TemplateSourceKind,
+    /// Clone URL, or the resolved directory
+    pub location: String,
+    /// Revision the source is pinned to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub rev: Option<String>,
+    /// Commit the local cache is checked out at (git sources)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub commit: Option<String>,
+    /// Whether the scaffolds are on disk: the cache was synced, or the directory exists
+    pub available: bool,
+    /// Number of scaffolds the source provides
+    pub scaffolds: u32,
+    /// Why the source could not be synced or read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub error: Option<String>,
+}
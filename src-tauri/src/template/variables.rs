@@ -0,0 +1,221 @@
+//! Typed scaffold variables, declared in `<scaffold>/.scaffold.toml`:
+//!
+//! ```toml
+//! [[variables]]
+//! name = "name"
+//! pattern = "[A-Z][A-Za-z0-9]*"
+//!
+//! [[variables]]
+//! name = "with_story"
+//! type = "boolean"
+//! default = "false"
+//! ```
+//!
+//! Values arrive as strings (from the form or the scaffold tool) and are
+//! checked and converted before rendering, so a bad value fails up front
+//! instead of producing broken files.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+
+use super::error::TemplateError;
+use super::types::{TemplateVariable, TemplateVariableType};
+
+pub(super) const SCHEMA_FILENAME: &str = ".scaffold.toml";
+
+#[derive(Deserialize)]
+struct ScaffoldSchema {
+    #[serde(default)]
+    variables: Vec<TemplateVariable>,
+}
+
+/// Variables declared by the scaffold in `dir`; none without a schema file.
+pub(super) fn load_schema(dir: &Path) -> Result<Vec<TemplateVariable>, TemplateError> {
+    let path = dir.join(SCHEMA_FILENAME);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| TemplateError::ReadError {
+        path: path.clone(),
+        source: e,
+    })?;
+    let schema: ScaffoldSchema =
+        toml::from_str(&content).map_err(|e| TemplateError::InvalidSchema { path, source: e })?;
+    Ok(schema.variables)
+}
+
+/// Checks `values` against the schema and builds the render context: declared
+/// variables get their default when missing (or empty) and their typed value;
+/// undeclared ones pass through as strings. Reports every invalid variable.
+pub(super) fn resolve_variables(
+    schema: &[TemplateVariable],
+    values: &HashMap<String, String>,
+) -> Result<Map<String, Value>, TemplateError> {
+    let mut context: Map<String, Value> = values
+        .iter()
+        .map(|(name, value)| (name.clone(), Value::String(value.clone())))
+        .collect();
+    let mut errors = Vec::new();
+
+    for variable in schema {
+        let value = values
+            .get(&variable.name)
+            .filter(|value| !value.is_empty())
+            .or(variable.default.as_ref());
+        let Some(value) = value else {
+            errors.push(format!("{} is required", variable.name));
+            continue;
+        };
+        match typed_value(variable, value) {
+            Ok(value) => {
+                context.insert(variable.name.clone(), value);
+            }
+            Err(reason) => errors.push(format!("{} {}", variable.name, reason)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(context)
+    } else {
+        Err(TemplateError::InvalidVariables(errors))
+    }
+}
+
+fn typed_value(variable: &TemplateVariable, value: &str) -> Result<Value, String> {
+    if let Some(pattern) = &variable.pattern {
+        let regex = Regex::new(&format!("^(?:{})$", pattern))
+            .map_err(|e| format!("has an invalid pattern: {}", e))?;
+        if !regex.is_match(value) {
+            return Err(format!("must match {}", pattern));
+        }
+    }
+
+    match variable.kind {
+        TemplateVariableType::String => Ok(Value::String(value.to_string())),
+        TemplateVariableType::Number => value
+            .parse::<i64>()
+            .map(Number::from)
+            .ok()
+            .or_else(|| value.parse::<f64>().ok().and_then(Number::from_f64))
+            .map(Value::Number)
+            .ok_or_else(|| format!("must be a number, got '{}'", value)),
+        TemplateVariableType::Boolean => match value {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => Err(format!("must be true or false, got '{}'", value)),
+        },
+        TemplateVariableType::Choice => {
+            if variable.options.iter().any(|option| option == value) {
+                Ok(Value::String(value.to_string()))
+            } else {
+                Err(format!("must be one of {}", variable.options.join(", ")))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variable(name: &str, kind: TemplateVariableType) -> TemplateVariable {
+        TemplateVariable {
+            name: name.to_string(),
+            kind,
+            description: None,
+            default: None,
+            pattern: None,
+            options: Vec::new(),
+        }
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_resolve_variables_types_and_defaults() {
+        let mut name = variable("name", TemplateVariableType::String);
+        name.pattern = Some("[A-Z][A-Za-z0-9]*".to_string());
+        let mut story = variable("story", TemplateVariableType::Boolean);
+        story.default = Some("false".to_string());
+        let mut kind = variable("kind", TemplateVariableType::Choice);
+        kind.options = vec!["page".to_string(), "widget".to_string()];
+        let schema = vec![
+            name,
+            story,
+            kind,
+            variable("size", TemplateVariableType::Number),
+        ];
+
+        let context = resolve_variables(
+            &schema,
+            &values(&[
+                ("name", "Button"),
+                ("story", ""),
+                ("kind", "widget"),
+                ("size", "3"),
+                ("extra", "x"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(context["name"], Value::from("Button"));
+        assert_eq!(context["story"], Value::Bool(false));
+        assert_eq!(context["kind"], Value::from("widget"));
+        assert_eq!(context["size"], Value::from(3));
+        assert_eq!(context["extra"], Value::from("x"));
+
+        let err = resolve_variables(
+            &schema,
+            &values(&[("name", "button"), ("story", "yes"), ("kind", "modal")]),
+        )
+        .unwrap_err();
+        let TemplateError::InvalidVariables(errors) = err else {
+            panic!("expected InvalidVariables");
+        };
+        assert_eq!(
+            errors,
+            vec![
+                "name must match [A-Z][A-Za-z0-9]*",
+                "story must be true or false, got 'yes'",
+                "kind must be one of page, widget",
+                "size is required",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(load_schema(dir.path()).unwrap().is_empty());
+
+        fs::write(
+            dir.path().join(SCHEMA_FILENAME),
+            "[[variables]]\nname = \"name\"\ndescription = \"Component name\"\n\n[[variables]]\nname = \"story\"\ntype = \"boolean\"\ndefault = \"true\"\n",
+        )
+        .unwrap();
+        let schema = load_schema(dir.path()).unwrap();
+        assert_eq!(schema.len(), 2);
+        assert_eq!(schema[0].kind, TemplateVariableType::String);
+        assert_eq!(schema[1].kind, TemplateVariableType::Boolean);
+        assert_eq!(schema[1].default.as_deref(), Some("true"));
+
+        fs::write(
+            dir.path().join(SCHEMA_FILENAME),
+            "[[variables]]\ntype = 1\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            load_schema(dir.path()),
+            Err(TemplateError::InvalidSchema { .. })
+        ));
+    }
+}
@@ -0,0 +1,30 @@
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder};
+
+/// Label of the window with focus, for menu actions.
+pub fn focused_window_label(app: &AppHandle) -> Option<String> {
+    app.webview_windows()
+        .into_iter()
+        .find(|(_, window)| window.is_focused().unwrap_or(false))
+        .map(|(label, _)| label)
+}
+
+/// Open `project_path` in a new window. Each window keeps its own agent
+/// session and usage, so it works on its project independently. Returns the
+/// new window's label.
+#[tauri::command]
+pub async fn open_project_window(
+    app_handle: AppHandle,
+    project_path: String,
+) -> Result<String, String> {
+    let label = format!("project-{}", uuid::Uuid::new_v4().simple());
+    // The frontend opens the project from the query instead of the last project
+    let url = format!("index.html?project={}", urlencoding::encode(&project_path));
+
+    WebviewWindowBuilder::new(&app_handle, &label, WebviewUrl::App(url.into()))
+        .title("Devflow")
+        .inner_size(1200.0, 800.0)
+        .build()
+        .map_err(|e| format!("Failed to open window: {}", e))?;
+
+    Ok(label)
+}
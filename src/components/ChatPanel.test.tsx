@@ -10,6 +10,9 @@ const mockSendMessage = vi.fn();
 const mockCancelRequest = vi.fn();
 const mockClearError = vi.fn();
 const mockRemoveFromQueue = vi.fn();
+const mockSteerMessage = vi.fn();
+const mockPauseRequest = vi.fn();
+const mockResumeRequest = vi.fn();
 const mockClearPromptHistory = vi.fn();
 const mockApprovePlan = vi.fn();
 const mockRejectPlan = vi.fn();
@@ -22,6 +25,7 @@ interface MockChatState {
   agentStatus: string;
   statusText: string;
   messageQueue: { id: string; content: string; status: string }[];
+  isPaused: boolean;
   promptHistory: string[];
   pendingPlan: string | null;
 }
@@ -34,6 +38,7 @@ let mockChatState: MockChatState = {
   agentStatus: "idle",
   statusText: "",
   messageQueue: [],
+  isPaused: false,
   promptHistory: [],
   pendingPlan: null,
 };
@@ -43,8 +48,11 @@ vi.mock("../context/ChatContext", () => ({
     ...mockChatState,
     sendMessage: mockSendMessage,
     cancelRequest: mockCancelRequest,
+    pauseRequest: mockPauseRequest,
+    resumeRequest: mockResumeRequest,
     clearError: mockClearError,
     removeFromQueue: mockRemoveFromQueue,
+    steerMessage: mockSteerMessage,
     clearPromptHistory: mockClearPromptHistory,
     approvePlan: mockApprovePlan,
     rejectPlan: mockRejectPlan,
@@ -62,6 +70,7 @@ describe("ChatPanel", () => {
       agentStatus: "idle",
       statusText: "",
       messageQueue: [],
+      isPaused: false,
       promptHistory: [],
       pendingPlan: null,
     };
@@ -454,6 +463,33 @@ describe("ChatPanel", () => {
 
       expect(mockRemoveFromQueue).toHaveBeenCalledWith("q-1");
     });
+
+    it("calls steerMessage for pending messages while the agent runs", async () => {
+      mockChatState.isLoading = true;
+      mockChatState.messageQueue = [
+        { id: "q-1", content: "Pending message", status: "pending" },
+      ];
+
+      render(<ChatPanel />);
+
+      await userEvent.click(screen.getByRole("button", { name: "Steer" }));
+
+      expect(mockSteerMessage).toHaveBeenCalledWith("q-1");
+    });
+
+    it("shows steering status without actions", () => {
+      mockChatState.isLoading = true;
+      mockChatState.messageQueue = [
+        { id: "q-1", content: "Steering message", status: "steering" },
+      ];
+
+      render(<ChatPanel />);
+
+      expect(screen.getByText("Steering...")).toBeInTheDocument();
+      expect(
+        screen.queryByRole("button", { name: "Steer" }),
+      ).not.toBeInTheDocument();
+    });
   });
 
   describe("sending messages", () => {
@@ -525,6 +561,40 @@ describe("ChatPanel", () => {
     });
   });
 
+  describe("pause and resume", () => {
+    it("calls pauseRequest when pause button is clicked", async () => {
+      mockChatState.isLoading = true;
+
+      render(<ChatPanel />);
+
+      await userEvent.click(screen.getByRole("button", { name: "Pause" }));
+
+      expect(mockPauseRequest).toHaveBeenCalledOnce();
+    });
+
+    it("offers resume while paused", async () => {
+      mockChatState.isLoading = true;
+      mockChatState.isPaused = true;
+
+      render(<ChatPanel />);
+
+      await userEvent.click(screen.getByRole("button", { name: "Resume" }));
+
+      expect(mockResumeRequest).toHaveBeenCalledOnce();
+      expect(
+        screen.queryByRole("button", { name: "Pause" }),
+      ).not.toBeInTheDocument();
+    });
+
+    it("hides pause when idle", () => {
+      render(<ChatPanel />);
+
+      expect(
+        screen.queryByRole("button", { name: "Pause" }),
+      ).not.toBeInTheDocument();
+    });
+  });
+
   describe("prompt history", () => {
     it("shows history button when history exists", () => {
       mockChatState.promptHistory = ["Previous prompt"];
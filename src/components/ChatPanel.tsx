@@ -358,13 +358,17 @@ export function ChatPanel() {
     streamBlocks,
     statusText,
     messageQueue,
+    isPaused,
     promptHistory,
     pendingPlan,
     sendMessage,
     cancelRequest,
+    pauseRequest,
+    resumeRequest,
     clearMessages,
     clearError,
     removeFromQueue,
+    steerMessage,
     clearPromptHistory,
     approvePlan,
     rejectPlan,
@@ -589,8 +593,21 @@ export function ChatPanel() {
                     : qm.content}
                 </span>
                 <span className="queued-status">
-                  {qm.status === "sending" ? "Sending..." : "Pending"}
+                  {qm.status === "sending"
+                    ? "Sending..."
+                    : qm.status === "steering"
+                      ? "Steering..."
+                      : "Pending"}
                 </span>
+                {qm.status === "pending" && isLoading && (
+                  <button
+                    className="queued-steer"
+                    onClick={() => steerMessage(qm.id)}
+                    title="Send to the agent before its next step"
+                  >
+                    Steer
+                  </button>
+                )}
                 {qm.status === "pending" && (
                   <button
                     className="queued-remove"
@@ -633,6 +650,19 @@ export function ChatPanel() {
           rows={1}
           className="chat-input"
         />
+        {isLoading && (
+          <button
+            onClick={isPaused ? resumeRequest : pauseRequest}
+            className="chat-pause-btn"
+            title={
+              isPaused
+                ? "Continue the run"
+                : "Hold before the next request to inspect progress"
+            }
+          >
+            {isPaused ? "Resume" : "Pause"}
+          </button>
+        )}
         {isLoading ? (
           <button onClick={cancelRequest} className="chat-stop-btn">
             Stop
@@ -2,6 +2,7 @@ import { useState, useRef, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useChat } from "../context/ChatContext";
 import { useNavigation } from "../context/NavigationContext";
+import { useSession } from "../context/SessionContext";
 import type { CommitContext } from "../types/generated";
 import "./CommitModal.css";
 
@@ -17,6 +18,7 @@ export function CommitModal({ isOpen, onClose }: CommitModalProps) {
   const textareaRef = useRef<HTMLTextAreaElement>(null);
   const { sendMessage } = useChat();
   const { navigate } = useNavigation();
+  const { projectPath } = useSession();
 
   useEffect(() => {
     if (isOpen) {
@@ -37,6 +39,7 @@ export function CommitModal({ isOpen, onClose }: CommitModalProps) {
 
       const rendered = await invoke<string>("template_render_commit", {
         context,
+        projectPath,
       });
 
       onClose();
@@ -47,7 +50,7 @@ export function CommitModal({ isOpen, onClose }: CommitModalProps) {
     } finally {
       setIsSending(false);
     }
-  }, [instructions, sendMessage, onClose, navigate]);
+  }, [instructions, projectPath, sendMessage, onClose, navigate]);
 
   const handleKeyDown = (e: React.KeyboardEvent) => {
     if (e.key === "Enter" && !e.shiftKey) {
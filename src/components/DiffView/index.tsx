@@ -7,15 +7,19 @@ import { FileHeader } from "./FileHeader";
 import { HunkHeader, DiffLines } from "./DiffLines";
 import { GlobalComment } from "./GlobalComment";
 import type { CommentEditorState } from "./types";
-import type { FileDiff } from "../../types/git";
+import type { FileDiffPage } from "../../types/git";
 import { getDisplayStatus } from "../../types/git";
 import "./DiffView.css";
 
+/** Hunks fetched up front and per "show more", so huge diffs render progressively */
+const HUNK_PAGE_SIZE = 20;
+
 export function DiffView() {
   const { selectedFile, projectPath, getSelectedFileInfo } = useApp();
   const { getCommentsForFile, getOverlappingComment } = useComments();
-  const [diff, setDiff] = useState<FileDiff | null>(null);
+  const [diff, setDiff] = useState<FileDiffPage | null>(null);
   const [loading, setLoading] = useState(false);
+  const [loadingMore, setLoadingMore] = useState(false);
   const [error, setError] = useState<string | null>(null);
 
   const [selectionStart, setSelectionStart] = useState<number | null>(null);
@@ -138,12 +142,16 @@ export function DiffView() {
       try {
         const fileInfo = getSelectedFileInfo();
 
-        const result = await invoke<FileDiff>("git_get_file_diff_with_status", {
-          projectPath,
-          filePath: selectedFile,
-          indexStatus: fileInfo?.index_status ?? null,
-          worktreeStatus: fileInfo?.worktree_status ?? null,
-        });
+        const result = await invoke<FileDiffPage>(
+          "git_get_file_diff_summary",
+          {
+            projectPath,
+            filePath: selectedFile,
+            indexStatus: fileInfo?.index_status ?? null,
+            worktreeStatus: fileInfo?.worktree_status ?? null,
+            maxHunks: HUNK_PAGE_SIZE,
+          },
+        );
 
         if (!cancelled) {
           setDiff(result);
@@ -166,6 +174,31 @@ export function DiffView() {
     };
   }, [selectedFile, projectPath, getSelectedFileInfo]);
 
+  const handleLoadMore = useCallback(async () => {
+    if (!diff || !selectedFile || !projectPath) return;
+    setLoadingMore(true);
+    try {
+      const fileInfo = getSelectedFileInfo();
+      const page = await invoke<FileDiffPage>("git_get_file_diff_page", {
+        projectPath,
+        filePath: selectedFile,
+        indexStatus: fileInfo?.index_status ?? null,
+        worktreeStatus: fileInfo?.worktree_status ?? null,
+        hunkOffset: diff.hunks.length,
+        count: HUNK_PAGE_SIZE,
+      });
+      setDiff((current) =>
+        current && current.path === page.path
+          ? { ...page, hunks: [...current.hunks, ...page.hunks] }
+          : current,
+      );
+    } catch (err) {
+      setError(err instanceof Error ? err.message : String(err));
+    } finally {
+      setLoadingMore(false);
+    }
+  }, [diff, selectedFile, projectPath, getSelectedFileInfo]);
+
   useEffect(() => {
     setCommentEditor(null);
     setSelectionStart(null);
@@ -257,6 +290,17 @@ export function DiffView() {
               />
             </div>
           ))}
+          {diff.hunks.length < diff.total_hunks && (
+            <div className="diff-load-more">
+              <span>
+                Showing {diff.hunks.length} of {diff.total_hunks} hunks (+
+                {diff.stats.additions} −{diff.stats.deletions})
+              </span>
+              <button onClick={handleLoadMore} disabled={loadingMore}>
+                {loadingMore ? "Loading..." : "Show more"}
+              </button>
+            </div>
+          )}
           {commentEditor && selectedFile && (
             <CommentEditor
               file={selectedFile}
@@ -1,5 +1,5 @@
 import { useState, useEffect, useCallback } from "react";
-import { listen } from "@tauri-apps/api/event";
+import { listen } from "../utils/windowEvents";
 import type {
   SubagentStartPayload,
   SubagentEndPayload,
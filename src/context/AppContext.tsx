@@ -8,7 +8,7 @@ import {
   ReactNode,
 } from "react";
 import { open } from "@tauri-apps/plugin-dialog";
-import { listen } from "@tauri-apps/api/event";
+import { listen } from "../utils/windowEvents";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { invoke } from "@tauri-apps/api/core";
 import type {
@@ -29,6 +29,7 @@ interface AppState {
 
 interface AppContextValue extends AppState {
   openProject: () => Promise<void>;
+  openProjectInNewWindow: (path: string) => Promise<void>;
   closeProject: () => void;
   clearError: () => void;
   selectFile: (path: string | null) => void;
@@ -138,6 +139,17 @@ export function AppProvider({ children }: { children: ReactNode }) {
     }
   }, [setProjectOpen]);
 
+  const openProjectInNewWindow = useCallback(async (path: string) => {
+    try {
+      await invoke<string>("open_project_window", { projectPath: path });
+    } catch (error) {
+      setState((prev) => ({
+        ...prev,
+        error: `Failed to open window: ${error}`,
+      }));
+    }
+  }, []);
+
   const closeProject = useCallback(() => {
     setState({
       projectPath: null,
@@ -207,9 +219,10 @@ export function AppProvider({ children }: { children: ReactNode }) {
 
     async function loadLastProject() {
       try {
-        const lastProject = await invoke<string | null>(
-          "config_get_last_project",
-        );
+        // Windows opened by open_project_window name their project in the URL
+        const lastProject =
+          new URLSearchParams(window.location.search).get("project") ??
+          (await invoke<string | null>("config_get_last_project"));
 
         if (cancelled) return;
 
@@ -298,6 +311,7 @@ export function AppProvider({ children }: { children: ReactNode }) {
       value={{
         ...state,
         openProject,
+        openProjectInNewWindow,
         closeProject,
         clearError,
         selectFile,
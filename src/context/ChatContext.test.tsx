@@ -10,11 +10,23 @@ import type {
   AgentCompletePayload,
   AgentErrorPayload,
   AgentCancelledPayload,
+  AgentSteeringPayload,
   ToolStartPayload,
   ToolEndPayload,
   PlanReadyPayload,
+  PlanWithdrawnPayload,
   ContentBlockStartPayload,
 } from "../types/agent";
+import type { RunTimings } from "../types/generated";
+
+const NO_TIMINGS: RunTimings = {
+  total_ms: 0,
+  first_token_ms: null,
+  model_ms: 0,
+  tool_ms: 0,
+  tokens_per_sec: null,
+  iterations: [],
+};
 
 type EventCallback<T> = (event: { payload: T }) => void;
 const eventListeners: Map<string, EventCallback<unknown>> = new Map();
@@ -123,6 +135,10 @@ describe("ChatProvider", () => {
           "agent-plan-ready",
           expect.any(Function),
         );
+        expect(listen).toHaveBeenCalledWith(
+          "agent-plan-withdrawn",
+          expect.any(Function),
+        );
       });
     });
   });
@@ -146,7 +162,25 @@ describe("ChatProvider", () => {
           }),
         ]),
         systemPrompt: null,
+        options: null,
+      });
+    });
+
+    it("passes send options through", async () => {
+      const { result } = renderHook(() => useChat(), {
+        wrapper: createWrapper("/test/project"),
+      });
+
+      await act(async () => {
+        await result.current.sendMessage("Just answer", {
+          tool_choice: "none",
+        });
       });
+
+      expect(invoke).toHaveBeenCalledWith(
+        "agent_send_message",
+        expect.objectContaining({ options: { tool_choice: "none" } }),
+      );
     });
 
     it("adds user message to messages array", async () => {
@@ -309,6 +343,104 @@ describe("ChatProvider", () => {
 
       expect(result.current.messageQueue).toHaveLength(0);
     });
+
+    it("steers a queued message into the running conversation", async () => {
+      vi.mocked(invoke).mockImplementation((cmd) =>
+        cmd === "agent_queue_message"
+          ? Promise.resolve(true)
+          : new Promise(() => {}),
+      );
+
+      const { result } = renderHook(() => useChat(), {
+        wrapper: createWrapper("/test/project"),
+      });
+
+      act(() => {
+        result.current.sendMessage("First message");
+      });
+
+      await waitFor(() => {
+        expect(result.current.isLoading).toBe(true);
+      });
+
+      await act(async () => {
+        await result.current.sendMessage("Use tabs instead");
+      });
+
+      const queuedId = result.current.messageQueue[0].id;
+
+      await act(async () => {
+        await result.current.steerMessage(queuedId);
+      });
+
+      expect(invoke).toHaveBeenCalledWith("agent_queue_message", {
+        messageId: queuedId,
+        content: "Use tabs instead",
+      });
+      expect(result.current.messageQueue[0].status).toBe("steering");
+
+      act(() => {
+        simulateEvent<ContentBlockStartPayload>("agent-content-block-start", {
+          block_index: 0,
+          block_type: { type: "text" },
+        });
+        simulateEvent<AgentChunkPayload>("agent-chunk", {
+          delta: "Editing now.",
+          block_index: 0,
+        });
+      });
+
+      act(() => {
+        simulateEvent<AgentSteeringPayload>("agent-steering", {
+          messages: [{ id: queuedId, content: "Use tabs instead" }],
+        });
+      });
+
+      expect(result.current.messageQueue).toHaveLength(0);
+      expect(result.current.streamBlocks).toEqual([]);
+      expect(result.current.isLoading).toBe(true);
+      expect(result.current.messages).toHaveLength(3);
+      expect(result.current.messages[1].role).toBe("assistant");
+      expect(result.current.messages[1].content_blocks).toEqual([
+        { type: "text", text: "Editing now." },
+      ]);
+      expect(result.current.messages[2]).toEqual({
+        id: queuedId,
+        role: "user",
+        content_blocks: [{ type: "text", text: "Use tabs instead" }],
+      });
+    });
+
+    it("keeps the message queued when the run has already finished", async () => {
+      vi.mocked(invoke).mockImplementation((cmd) =>
+        cmd === "agent_queue_message"
+          ? Promise.resolve(false)
+          : new Promise(() => {}),
+      );
+
+      const { result } = renderHook(() => useChat(), {
+        wrapper: createWrapper("/test/project"),
+      });
+
+      act(() => {
+        result.current.sendMessage("First message");
+      });
+
+      await waitFor(() => {
+        expect(result.current.isLoading).toBe(true);
+      });
+
+      await act(async () => {
+        await result.current.sendMessage("Late message");
+      });
+
+      await act(async () => {
+        await result.current.steerMessage(result.current.messageQueue[0].id);
+      });
+
+      expect(result.current.messageQueue).toHaveLength(1);
+      expect(result.current.messageQueue[0].status).toBe("pending");
+    });
   });
 
   describe("event handling - agent-chunk", () => {
@@ -390,6 +522,7 @@ describe("ChatProvider", () => {
         simulateEvent<AgentCompletePayload>("agent-complete", {
           message_id: "msg-123",
           stop_reason: "end_turn",
+          timings: NO_TIMINGS,
         });
       });
 
@@ -454,6 +587,7 @@ describe("ChatProvider", () => {
       act(() => {
         simulateEvent<AgentCancelledPayload>("agent-cancelled", {
           reason: "User cancelled",
+          partial: null,
         });
       });
 
@@ -502,6 +636,7 @@ describe("ChatProvider", () => {
       act(() => {
         simulateEvent<AgentCancelledPayload>("agent-cancelled", {
           reason: "User cancelled",
+          partial: null,
         });
       });
 
@@ -517,6 +652,63 @@ describe("ChatProvider", () => {
         expect(textBlock.text).toContain("[Cancelled by user]");
       }
     });
+
+    it("keeps the partial message preserved by the backend", async () => {
+      vi.mocked(invoke).mockImplementation(() => new Promise(() => {}));
+
+      const { result } = renderHook(() => useChat(), {
+        wrapper: createWrapper("/test/project"),
+      });
+
+      await waitFor(() => {
+        expect(eventListeners.has("agent-cancelled")).toBe(true);
+      });
+
+      act(() => {
+        result.current.sendMessage("Test");
+      });
+
+      await waitFor(() => {
+        expect(result.current.isLoading).toBe(true);
+      });
+
+      act(() => {
+        simulateEvent<AgentCancelledPayload>("agent-cancelled", {
+          reason: "Cancelled by user",
+          partial: {
+            id: "partial-1",
+            role: "assistant",
+            content_blocks: [
+              { type: "text", text: "Checking the file" },
+              {
+                type: "tool_use",
+                tool_use_id: "t1",
+                tool_name: "read_file",
+                tool_input: { path: "a.txt" },
+                output: "contents",
+                is_error: false,
+              },
+            ],
+            interrupted: true,
+          },
+        });
+      });
+
+      await waitFor(() => {
+        expect(result.current.messages.length).toBe(2);
+      });
+      const kept = result.current.messages[1];
+      expect(kept.id).toBe("partial-1");
+      expect(kept.interrupted).toBe(true);
+      expect(kept.content_blocks[0]).toEqual({
+        type: "text",
+        text: "Checking the file\n\n*[Cancelled by user]*",
+      });
+      expect(kept.content_blocks[1]).toMatchObject({
+        type: "tool_use",
+        output: "contents",
+      });
+    });
   });
 
   describe("tool execution tracking", () => {
@@ -593,6 +785,7 @@ describe("ChatProvider", () => {
           output: "file1.txt\nfile2.txt",
           is_error: false,
           block_index: 0,
+          artifacts: [],
         });
       });
 
@@ -638,6 +831,7 @@ describe("ChatProvider", () => {
           output: "Command not found",
           is_error: true,
           block_index: 0,
+          artifacts: [],
         });
       });
 
@@ -658,6 +852,7 @@ describe("ChatProvider", () => {
       act(() => {
         simulateEvent<PlanReadyPayload>("agent-plan-ready", {
           plan: "## Plan\n1. Read file\n2. Modify file",
+          plan_id: 1,
         });
       });
 
@@ -666,6 +861,31 @@ describe("ChatProvider", () => {
       );
     });
 
+    it("clears pendingPlan when the plan is withdrawn", async () => {
+      const { result } = renderHook(() => useChat(), {
+        wrapper: createWrapper("/test/project"),
+      });
+
+      await waitFor(() => {
+        expect(eventListeners.has("agent-plan-withdrawn")).toBe(true);
+      });
+
+      act(() => {
+        simulateEvent<PlanReadyPayload>("agent-plan-ready", {
+          plan: "Test plan",
+          plan_id: 1,
+        });
+      });
+      expect(result.current.pendingPlan).toBe("Test plan");
+
+      act(() => {
+        simulateEvent<PlanWithdrawnPayload>("agent-plan-withdrawn", {
+          plan_id: 1,
+        });
+      });
+      expect(result.current.pendingPlan).toBeNull();
+    });
+
     it("approvePlan invokes correct command", async () => {
       const { result } = renderHook(() => useChat(), {
         wrapper: createWrapper("/test/project"),
@@ -678,6 +898,7 @@ describe("ChatProvider", () => {
       act(() => {
         simulateEvent<PlanReadyPayload>("agent-plan-ready", {
           plan: "Test plan",
+          plan_id: 1,
         });
       });
 
@@ -701,6 +922,7 @@ describe("ChatProvider", () => {
       act(() => {
         simulateEvent<PlanReadyPayload>("agent-plan-ready", {
           plan: "Test plan",
+          plan_id: 1,
         });
       });
 
@@ -726,6 +948,7 @@ describe("ChatProvider", () => {
       act(() => {
         simulateEvent<PlanReadyPayload>("agent-plan-ready", {
           plan: "Test plan",
+          plan_id: 1,
         });
       });
 
@@ -739,6 +962,78 @@ describe("ChatProvider", () => {
     });
   });
 
+  describe("pause and resume", () => {
+    it("tracks a pause until resumed", async () => {
+      vi.mocked(invoke).mockImplementation((cmd) =>
+        cmd === "agent_send_message"
+          ? new Promise(() => {})
+          : Promise.resolve(true),
+      );
+
+      const { result } = renderHook(() => useChat(), {
+        wrapper: createWrapper("/test/project"),
+      });
+
+      act(() => {
+        result.current.sendMessage("Refactor the parser");
+      });
+
+      await waitFor(() => {
+        expect(result.current.isLoading).toBe(true);
+      });
+
+      await act(async () => {
+        await result.current.pauseRequest();
+      });
+
+      expect(invoke).toHaveBeenCalledWith("agent_pause");
+      expect(result.current.isPaused).toBe(true);
+
+      await act(async () => {
+        await result.current.resumeRequest();
+      });
+
+      expect(invoke).toHaveBeenCalledWith("agent_resume");
+      expect(result.current.isPaused).toBe(false);
+    });
+
+    it("clears the pause when the run ends", async () => {
+      vi.mocked(invoke).mockImplementation((cmd) =>
+        cmd === "agent_send_message"
+          ? new Promise(() => {})
+          : Promise.resolve(true),
+      );
+
+      const { result } = renderHook(() => useChat(), {
+        wrapper: createWrapper("/test/project"),
+      });
+
+      act(() => {
+        result.current.sendMessage("Refactor the parser");
+      });
+
+      await waitFor(() => {
+        expect(result.current.isLoading).toBe(true);
+      });
+
+      await act(async () => {
+        await result.current.pauseRequest();
+      });
+
+      act(() => {
+        simulateEvent<AgentCompletePayload>("agent-complete", {
+          message_id: "msg-1",
+          stop_reason: "end_turn",
+          timings: NO_TIMINGS,
+        });
+      });
+
+      await waitFor(() => {
+        expect(result.current.isPaused).toBe(false);
+      });
+    });
+  });
+
   describe("cancelRequest", () => {
     it("invokes agent_cancel command", async () => {
       const { result } = renderHook(() => useChat(), {
@@ -804,6 +1099,40 @@ describe("ChatProvider", () => {
     });
   });
 
+  describe("menu actions", () => {
+    it("toggles plan mode and requests compaction", async () => {
+      vi.mocked(invoke).mockImplementation((cmd) =>
+        cmd === "agent_get_run_options"
+          ? Promise.resolve({ model: "m", mode: "code" })
+          : Promise.resolve(undefined),
+      );
+
+      renderHook(() => useChat(), {
+        wrapper: createWrapper("/test/project"),
+      });
+
+      await waitFor(() => {
+        expect(listen).toHaveBeenCalledWith(
+          "menu-toggle-plan-mode",
+          expect.any(Function),
+        );
+      });
+
+      await act(async () => {
+        simulateEvent("menu-toggle-plan-mode", null);
+        simulateEvent("menu-compact-context", null);
+      });
+
+      await waitFor(() => {
+        expect(invoke).toHaveBeenCalledWith("agent_set_run_options", {
+          projectPath: "/test/project",
+          options: { model: "m", mode: "plan" },
+        });
+      });
+      expect(invoke).toHaveBeenCalledWith("agent_compact_context");
+    });
+  });
+
   describe("clearError", () => {
     it("clears error state", async () => {
       const { result } = renderHook(() => useChat(), {
@@ -942,6 +1271,7 @@ describe("ChatProvider", () => {
         simulateEvent<AgentCompletePayload>("agent-complete", {
           message_id: "msg-123",
           stop_reason: "end_turn",
+          timings: NO_TIMINGS,
         });
       });
 
@@ -7,7 +7,7 @@ import {
   useRef,
   ReactNode,
 } from "react";
-import { listen } from "@tauri-apps/api/event";
+import { listen } from "../utils/windowEvents";
 import { invoke } from "@tauri-apps/api/core";
 import type {
   ChatMessage,
@@ -16,14 +16,21 @@ import type {
   AgentCompactionPayload,
   AgentCompletePayload,
   AgentErrorPayload,
+  AgentReconnectingPayload,
+  AgentSlowResponsePayload,
   AgentStatusPayload,
+  AgentSteeringPayload,
   AgentCancelledPayload,
   AgentStatus,
   PlanReadyPayload,
+  PlanWithdrawnPayload,
+  RunOptions,
   ToolStartPayload,
   ToolEndPayload,
+  ToolArtifact,
   ContentBlockStartPayload,
   AgentCompactionWarningPayload,
+  SendOptions,
 } from "../types/agent";
 import { useSession } from "./SessionContext";
 
@@ -40,13 +47,16 @@ export interface StreamingBlock {
   toolInput?: unknown;
   output?: string;
   isError?: boolean;
+  /** Structured results (diffs, tables, links) to render instead of output */
+  artifacts?: ToolArtifact[];
   isComplete?: boolean;
 }
 
 export interface QueuedMessage {
   id: string;
   content: string;
-  status: "pending" | "sending" | "sent";
+  status: "pending" | "sending" | "steering" | "sent";
+  options?: SendOptions;
 }
 
 export interface CompactionInfo {
@@ -70,6 +80,8 @@ interface ChatState {
   agentStatus: AgentStatus;
   statusText: string;
   messageQueue: QueuedMessage[];
+  // Set once a pause is requested; the run holds at its next request
+  isPaused: boolean;
   promptHistory: string[];
   pendingPlan: string | null;
   lastCompaction: CompactionInfo | null;
@@ -77,13 +89,16 @@ interface ChatState {
 }
 
 interface ChatContextValue extends ChatState {
-  sendMessage: (content: string) => Promise<void>;
+  sendMessage: (content: string, options?: SendOptions) => Promise<void>;
   cancelRequest: () => Promise<void>;
+  pauseRequest: () => Promise<void>;
+  resumeRequest: () => Promise<void>;
   clearMessages: () => void;
   clearError: () => void;
   addToQueue: (content: string) => string;
   removeFromQueue: (id: string) => void;
   updateQueuedMessage: (id: string, content: string) => void;
+  steerMessage: (id: string) => Promise<void>;
   clearPromptHistory: () => void;
   approvePlan: () => Promise<void>;
   rejectPlan: (reason?: string) => Promise<void>;
@@ -103,6 +118,26 @@ function generateId(): string {
 
 const CLEAR_PENDING_PLAN = { pendingPlan: null } as const;
 
+const CANCELLED_NOTE = "*[Cancelled by user]*";
+
+/**
+ * Appends the cancellation note to text blocks, adding one if there are none.
+ */
+function markCancelled(blocks: ChatContentBlock[]): ChatContentBlock[] {
+  const marked = blocks.map(
+    (block): ChatContentBlock =>
+      block.type === "text"
+        ? { ...block, text: `${block.text}\n\n${CANCELLED_NOTE}` }
+        : block,
+  );
+
+  if (!marked.some((b) => b.type === "text")) {
+    marked.push({ type: "text", text: CANCELLED_NOTE });
+  }
+
+  return marked;
+}
+
 /**
  * Converts streaming blocks to finalized content blocks.
  * @param blocks - The streaming blocks to convert
@@ -116,11 +151,7 @@ function streamBlocksToContentBlocks(
   const contentBlocks: ChatContentBlock[] = sorted.map(
     (block): ChatContentBlock => {
       if (block.type === "text") {
-        const text = block.text || "";
-        return {
-          type: "text",
-          text: appendCancelled ? text + "\n\n*[Cancelled by user]*" : text,
-        };
+        return { type: "text", text: block.text || "" };
       }
       return {
         type: "tool_use",
@@ -133,11 +164,22 @@ function streamBlocksToContentBlocks(
     },
   );
 
-  if (appendCancelled && !contentBlocks.some((b) => b.type === "text")) {
-    contentBlocks.push({ type: "text", text: "*[Cancelled by user]*" });
-  }
+  return appendCancelled ? markCancelled(contentBlocks) : contentBlocks;
+}
 
-  return contentBlocks;
+/**
+ * Turns the partial response preserved by the backend into an interrupted message.
+ */
+function toInterruptedMessage(
+  partial: ChatMessage,
+  id = partial.id,
+): ChatMessage {
+  return {
+    ...partial,
+    id,
+    content_blocks: markCancelled(partial.content_blocks),
+    interrupted: true,
+  };
 }
 
 function loadPromptHistory(): string[] {
@@ -185,6 +227,7 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
     agentStatus: "idle" as AgentStatus,
     statusText: "",
     messageQueue: [],
+    isPaused: false,
     promptHistory: loadPromptHistory(),
     ...CLEAR_PENDING_PLAN,
     lastCompaction: null,
@@ -194,6 +237,8 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
   const isMounted = useRef(true);
   const messagesRef = useRef<ChatMessage[]>([]);
   const isProcessingQueue = useRef(false);
+  // Message finalized locally by cancelRequest, replaced once the backend sends its partial
+  const localCancelledId = useRef<string | null>(null);
 
   useEffect(() => {
     messagesRef.current = state.messages;
@@ -223,7 +268,7 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
   }, [projectPath]);
 
   const sendMessageInternal = useCallback(
-    async (content: string) => {
+    async (content: string, options?: SendOptions) => {
       if (!projectPath) return;
 
       const userMessage: ChatMessage = {
@@ -250,6 +295,7 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
           projectPath,
           messages: newMessages,
           systemPrompt: null,
+          options: options ?? null,
         });
       } catch (err) {
         if (isMounted.current) {
@@ -294,7 +340,7 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
         ),
       }));
 
-      await sendMessageInternal(pendingMessage.content);
+      await sendMessageInternal(pendingMessage.content, pendingMessage.options);
 
       setState((prev) => ({
         ...prev,
@@ -309,8 +355,31 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
     processQueue();
   }, [state.isLoading, state.messageQueue, projectPath, sendMessageInternal]);
 
+  // A pause only lasts for the run it was requested in
+  useEffect(() => {
+    if (!state.isLoading && state.isPaused) {
+      setState((prev) => ({ ...prev, isPaused: false }));
+    }
+  }, [state.isLoading, state.isPaused]);
+
+  // Steering messages the run never picked up go back to the normal queue
+  useEffect(() => {
+    if (
+      state.isLoading ||
+      !state.messageQueue.some((m) => m.status === "steering")
+    ) {
+      return;
+    }
+    setState((prev) => ({
+      ...prev,
+      messageQueue: prev.messageQueue.map((m) =>
+        m.status === "steering" ? { ...m, status: "pending" as const } : m,
+      ),
+    }));
+  }, [state.isLoading, state.messageQueue]);
+
   const sendMessage = useCallback(
-    async (content: string) => {
+    async (content: string, options?: SendOptions) => {
       if (!projectPath || !content.trim()) return;
 
       if (state.isLoading) {
@@ -319,14 +388,19 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
             ...prev,
             messageQueue: [
               ...prev.messageQueue,
-              { id: generateId(), content: content.trim(), status: "pending" },
+              {
+                id: generateId(),
+                content: content.trim(),
+                status: "pending",
+                options,
+              },
             ],
           }));
         }
         return;
       }
 
-      await sendMessageInternal(content.trim());
+      await sendMessageInternal(content.trim(), options);
     },
     [
       projectPath,
@@ -358,7 +432,9 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
               prev.streamBlocks,
               true,
             ),
+            interrupted: true,
           };
+          localCancelledId.current = cancelledMessage.id;
 
           return {
             ...prev,
@@ -376,6 +452,35 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
     }
   }, []);
 
+  const pauseRequest = useCallback(async () => {
+    try {
+      const paused = await invoke<boolean>("agent_pause");
+      if (paused && isMounted.current) {
+        setState((prev) => ({
+          ...prev,
+          isPaused: true,
+          statusText:
+            prev.agentStatus === "paused"
+              ? prev.statusText
+              : "Pausing after the current step...",
+        }));
+      }
+    } catch (error) {
+      setState((prev) => ({ ...prev, error: `Failed to pause: ${error}` }));
+    }
+  }, []);
+
+  const resumeRequest = useCallback(async () => {
+    try {
+      await invoke<boolean>("agent_resume");
+      if (isMounted.current) {
+        setState((prev) => ({ ...prev, isPaused: false }));
+      }
+    } catch (error) {
+      setState((prev) => ({ ...prev, error: `Failed to resume: ${error}` }));
+    }
+  }, []);
+
   const clearMessages = useCallback(() => {
     resetSession();
     setState((prev) => ({
@@ -431,6 +536,44 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
     }));
   }, []);
 
+  const steerMessage = useCallback(
+    async (id: string) => {
+      const queued = state.messageQueue.find(
+        (m) => m.id === id && m.status === "pending",
+      );
+      if (!queued) return;
+
+      setState((prev) => ({
+        ...prev,
+        messageQueue: prev.messageQueue.map((m) =>
+          m.id === id ? { ...m, status: "steering" as const } : m,
+        ),
+      }));
+
+      try {
+        const accepted = await invoke<boolean>("agent_queue_message", {
+          messageId: id,
+          content: queued.content,
+        });
+        // Once accepted, the agent-steering event moves it into the conversation
+        if (accepted) return;
+      } catch (error) {
+        setState((prev) => ({
+          ...prev,
+          error: `Failed to steer agent: ${error}`,
+        }));
+      }
+
+      setState((prev) => ({
+        ...prev,
+        messageQueue: prev.messageQueue.map((m) =>
+          m.id === id ? { ...m, status: "pending" as const } : m,
+        ),
+      }));
+    },
+    [state.messageQueue],
+  );
+
   const clearPromptHistory = useCallback(() => {
     localStorage.removeItem(PROMPT_HISTORY_KEY);
     setState((prev) => ({ ...prev, promptHistory: [] }));
@@ -603,14 +746,26 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
 
       const unlistenCancelled = await listen<AgentCancelledPayload>(
         "agent-cancelled",
-        () => {
+        (event) => {
           if (cancelled || !isMounted.current) return;
+          const { partial } = event.payload;
           setState((prev) => {
             if (!prev.isLoading) {
-              return prev;
+              // cancelRequest already finalized from the stream; prefer what the backend kept
+              const last = prev.messages[prev.messages.length - 1];
+              if (!partial || !last || last.id !== localCancelledId.current) {
+                return prev;
+              }
+              return {
+                ...prev,
+                messages: [
+                  ...prev.messages.slice(0, -1),
+                  toInterruptedMessage(partial, last.id),
+                ],
+              };
             }
 
-            if (prev.streamBlocks.length === 0) {
+            if (!partial && prev.streamBlocks.length === 0) {
               return {
                 ...prev,
                 isLoading: false,
@@ -620,14 +775,17 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
               };
             }
 
-            const cancelledMessage: ChatMessage = {
-              id: generateId(),
-              role: "assistant",
-              content_blocks: streamBlocksToContentBlocks(
-                prev.streamBlocks,
-                true,
-              ),
-            };
+            const cancelledMessage: ChatMessage = partial
+              ? toInterruptedMessage(partial)
+              : {
+                  id: generateId(),
+                  role: "assistant",
+                  content_blocks: streamBlocksToContentBlocks(
+                    prev.streamBlocks,
+                    true,
+                  ),
+                  interrupted: true,
+                };
 
             return {
               ...prev,
@@ -679,6 +837,7 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
                 ...blocks[blockIdx],
                 output: event.payload.output,
                 isError: event.payload.is_error,
+                artifacts: event.payload.artifacts,
                 isComplete: true,
               };
             }
@@ -699,6 +858,14 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
         },
       );
 
+      const unlistenPlanWithdrawn = await listen<PlanWithdrawnPayload>(
+        "agent-plan-withdrawn",
+        () => {
+          if (cancelled || !isMounted.current) return;
+          setState((prev) => ({ ...prev, ...CLEAR_PENDING_PLAN }));
+        },
+      );
+
       const unlistenCompaction = await listen<AgentCompactionPayload>(
         "agent-compaction",
         (event) => {
@@ -730,6 +897,73 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
           },
         );
 
+      const unlistenReconnecting = await listen<AgentReconnectingPayload>(
+        "agent-reconnecting",
+        (event) => {
+          if (cancelled || !isMounted.current) return;
+          // The request is re-sent from scratch, so drop blocks from the failed attempt
+          setState((prev) => ({
+            ...prev,
+            streamBlocks: prev.streamBlocks.filter(
+              (b) => b.blockIndex < event.payload.block_offset,
+            ),
+          }));
+        },
+      );
+
+      const unlistenSteering = await listen<AgentSteeringPayload>(
+        "agent-steering",
+        (event) => {
+          if (cancelled || !isMounted.current) return;
+          const { messages, closed_message_id } = event.payload;
+          const ids = new Set(messages.map((m) => m.id));
+          setState((prev) => {
+            // Close the assistant turn so far; the agent answers after the new messages
+            const closed: ChatMessage[] =
+              prev.streamBlocks.length > 0
+                ? [
+                    {
+                      // The id the backend records the closed message under
+                      id: closed_message_id ?? generateId(),
+                      role: "assistant",
+                      content_blocks: streamBlocksToContentBlocks(
+                        prev.streamBlocks,
+                      ),
+                    },
+                  ]
+                : [];
+            const injected: ChatMessage[] = messages.map((m) => ({
+              id: m.id,
+              role: "user",
+              content_blocks: [{ type: "text", text: m.content }],
+            }));
+
+            return {
+              ...prev,
+              messages: [...prev.messages, ...closed, ...injected],
+              streamBlocks: [],
+              messageQueue: prev.messageQueue.filter((m) => !ids.has(m.id)),
+              promptHistory: messages.reduce(
+                (history, m) => addToPromptHistory(history, m.content),
+                prev.promptHistory,
+              ),
+            };
+          });
+        },
+      );
+
+      const unlistenSlowResponse = await listen<AgentSlowResponsePayload>(
+        "agent-slow-response",
+        () => {
+          if (cancelled || !isMounted.current) return;
+          // Cleared by the next agent-status event
+          setState((prev) => ({
+            ...prev,
+            statusText: "The model is taking unusually long to respond...",
+          }));
+        },
+      );
+
       if (cancelled) {
         unlistenBlockStart();
         unlistenChunk();
@@ -740,8 +974,12 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
         unlistenToolStart();
         unlistenToolEnd();
         unlistenPlanReady();
+        unlistenPlanWithdrawn();
         unlistenCompaction();
         unlistenCompactionWarning();
+        unlistenReconnecting();
+        unlistenSteering();
+        unlistenSlowResponse();
       } else {
         unlisteners.push(
           unlistenBlockStart,
@@ -753,8 +991,12 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
           unlistenToolStart,
           unlistenToolEnd,
           unlistenPlanReady,
+          unlistenPlanWithdrawn,
           unlistenCompaction,
           unlistenCompactionWarning,
+          unlistenReconnecting,
+          unlistenSteering,
+          unlistenSlowResponse,
         );
       }
     }
@@ -767,17 +1009,83 @@ export function ChatProvider({ children, projectPath }: ChatProviderProps) {
     };
   }, []);
 
+  const togglePlanMode = useCallback(async () => {
+    if (!projectPath) return;
+    try {
+      const options = await invoke<RunOptions>("agent_get_run_options", {
+        projectPath,
+      });
+      const mode = options.mode === "plan" ? "code" : "plan";
+      await invoke<RunOptions>("agent_set_run_options", {
+        projectPath,
+        options: { ...options, mode },
+      });
+    } catch {
+      // Ignored
+    }
+  }, [projectPath]);
+
+  useEffect(() => {
+    let cancelled = false;
+    const unlisteners: (() => void)[] = [];
+
+    async function setupListeners() {
+      try {
+        const unlistenNewSession = await listen("menu-new-session", () => {
+          clearMessages();
+        });
+        const unlistenCancelRun = await listen("menu-cancel-run", () => {
+          cancelRequest();
+        });
+        const unlistenCompact = await listen("menu-compact-context", () => {
+          invoke("agent_compact_context").catch(() => {
+            // Ignored
+          });
+        });
+        const unlistenPlanMode = await listen("menu-toggle-plan-mode", () => {
+          togglePlanMode();
+        });
+
+        if (cancelled) {
+          unlistenNewSession();
+          unlistenCancelRun();
+          unlistenCompact();
+          unlistenPlanMode();
+        } else {
+          unlisteners.push(
+            unlistenNewSession,
+            unlistenCancelRun,
+            unlistenCompact,
+            unlistenPlanMode,
+          );
+        }
+      } catch {
+        // Ignored
+      }
+    }
+
+    setupListeners();
+
+    return () => {
+      cancelled = true;
+      unlisteners.forEach((fn) => fn());
+    };
+  }, [clearMessages, cancelRequest, togglePlanMode]);
+
   return (
     <ChatContext.Provider
       value={{
         ...state,
         sendMessage,
         cancelRequest,
+        pauseRequest,
+        resumeRequest,
         clearMessages,
         clearError,
         addToQueue,
         removeFromQueue,
         updateQueuedMessage,
+        steerMessage,
         clearPromptHistory,
         approvePlan,
         rejectPlan,
@@ -5,7 +5,7 @@ import {
   useEffect,
   type ReactNode,
 } from "react";
-import { listen } from "@tauri-apps/api/event";
+import { listen } from "../utils/windowEvents";
 
 export type Page = "chat" | "changes" | "settings";
 
@@ -7,7 +7,7 @@ import {
   useRef,
   ReactNode,
 } from "react";
-import { listen } from "@tauri-apps/api/event";
+import { listen } from "../utils/windowEvents";
 import { invoke } from "@tauri-apps/api/core";
 import type {
   AgentUsagePayload,
@@ -3,10 +3,12 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type {
   ConfigChangedPayload,
+  ConfigReloadedPayload,
   NotificationAction,
   ProjectConfig,
   ProviderInfo,
 } from "../types/config";
+import type { ProviderModels } from "../types/agent";
 
 export interface AgentTypeInfo {
   id: string;
@@ -88,6 +90,7 @@ export function useSettingsForm({
 
   // Data state
   const [providers, setProviders] = useState<ProviderInfo[]>([]);
+  const [listedModels, setListedModels] = useState<ProviderModels[]>([]);
   const [defaultSystemPrompt, setDefaultSystemPrompt] = useState("");
   const [defaultExtractionPrompt, setDefaultExtractionPrompt] = useState("");
   const [defaultToolDescriptions, setDefaultToolDescriptions] = useState<
@@ -161,6 +164,13 @@ export function useSettingsForm({
           );
           setAgentsMd(agentsMdContent);
           setSavedAgentsMd(agentsMdContent);
+
+          // Live model lists need network access; the built-in list is the fallback
+          invoke<ProviderModels[] | undefined>("providers_list_models", {
+            projectPath,
+          })
+            .then((listed) => setListedModels(listed ?? []))
+            .catch(() => setListedModels([]));
         }
       } catch (e) {
         setError(`Failed to load settings: ${e}`);
@@ -172,24 +182,38 @@ export function useSettingsForm({
     loadData();
   }, [projectPath]);
 
-  // Listen for external config changes
+  // Listen for external config changes, from other windows or edits to the file
   useEffect(() => {
-    let unlisten: UnlistenFn | undefined;
+    const unlisteners: UnlistenFn[] = [];
+    const reload = () => {
+      invoke<ProjectConfig>("config_load_project", { projectPath }).then(
+        (config) => {
+          setProjectConfig(config);
+          setSavedProjectConfig(config);
+        },
+      );
+    };
 
     listen<ConfigChangedPayload>("config-changed", (event) => {
       if (event.payload.project_path === projectPath) {
-        invoke<ProjectConfig>("config_load_project", { projectPath }).then(
-          (config) => {
-            setProjectConfig(config);
-            setSavedProjectConfig(config);
-          },
-        );
+        reload();
       }
     }).then((fn) => {
-      unlisten = fn;
+      unlisteners.push(fn);
     });
 
-    return () => unlisten?.();
+    listen<ConfigReloadedPayload>("config-reloaded", (event) => {
+      if (event.payload.project_path !== projectPath) return;
+      if (event.payload.error) {
+        setError(`Config file is invalid: ${event.payload.error}`);
+      } else {
+        reload();
+      }
+    }).then((fn) => {
+      unlisteners.push(fn);
+    });
+
+    return () => unlisteners.forEach((fn) => fn());
   }, [projectPath]);
 
   // Provider handling
@@ -198,10 +222,15 @@ export function useSettingsForm({
     [providers, projectConfig],
   );
 
-  const currentModels = useMemo(
-    () => currentProvider?.models || [],
-    [currentProvider],
-  );
+  const currentModels = useMemo(() => {
+    const listed = listedModels.find(
+      (p) => p.provider === projectConfig?.agent.provider,
+    );
+    if (listed && listed.models.length > 0) {
+      return listed.models.map((m) => m.id);
+    }
+    return currentProvider?.models || [];
+  }, [listedModels, projectConfig, currentProvider]);
 
   // Validation
   const validateForm = useCallback((): FormValidationErrors => {
@@ -44,15 +44,78 @@ const mockConfig: ProjectConfig = {
     api_key_env: "ANTHROPIC_API_KEY",
     max_tokens: 8192,
     context_limit: null,
+    temperature: null,
+    compaction_threshold: null,
+    base_url: null,
+    anthropic: null,
+    gemini: null,
   },
   prompts: {
     pre: "Pre-prompt text",
     post: "Post-prompt text",
+    variables: {},
   },
+  network: {
+    proxy: null,
+    no_proxy: null,
+    ca_cert_path: null,
+  },
+  attribution: {
+    enabled: false,
+    label: "devflow",
+  },
+  logging: {
+    transcripts: false,
+    evals: false,
+  },
+  docker: {
+    enabled: false,
+    compose_file: null,
+    allowed_services: [],
+  },
+  formatting: {
+    on_edit: false,
+    commands: {},
+  },
+  diagnostics: {
+    commands: {},
+    timeout_secs: 120,
+    max_lines: 40,
+  },
+  index: {
+    enabled: false,
+    provider: "gemini",
+    model: null,
+    base_url: null,
+    api_key_env: null,
+    chunk_lines: 60,
+    max_file_bytes: 262144,
+  },
+  auto_approve: { plans: false, edits: true, bash: true, network: true },
+  workspace: { roots: [] },
+  templates: { sources: [] },
+  request_cache: { enabled: false, ttl_secs: 3600, max_entries: 200 },
+  timeouts: {},
+  rate_limits: {},
+  databases: {},
+  agent_sampling: {},
+  agent_models: {},
+  custom_agents: {},
   execution: {
     timeout_secs: 30,
+    max_timeout_secs: 600,
+    plan_approval_timeout_secs: 1800,
     max_tool_iterations: 50,
     max_agent_depth: 3,
+    max_message_bytes: 524288,
+    max_conversation_bytes: 4194304,
+    max_tool_result_bytes: 524288,
+    max_read_bytes: 102400,
+    disabled_tools: [],
+    depth_disabled_tools: [],
+    shell: "auto",
+    allowed_paths: [],
+    dry_run: false,
   },
   notifications: {
     on_complete: ["sound"],
@@ -65,7 +128,9 @@ const mockConfig: ProjectConfig = {
   system_prompt: null,
   extraction_prompt: null,
   tool_descriptions: null,
+  tool_notes: {},
   agent_prompts: null,
+  agent_prompt_notes: {},
 };
 
 const mockDefaultSystemPrompt = "You are a helpful assistant.";
@@ -198,6 +263,36 @@ describe("SettingsPage", () => {
       ) as HTMLSelectElement;
       expect(modelSelect.value).toBe("claude-sonnet-4-20250514");
     });
+
+    it("offers models listed by the provider API", async () => {
+      const defaultImpl = vi.mocked(invoke).getMockImplementation()!;
+      vi.mocked(invoke).mockImplementation((cmd: string, args?: unknown) => {
+        if (cmd === "providers_list_models") {
+          return Promise.resolve([
+            {
+              provider: "anthropic",
+              models: [
+                { id: "claude-sonnet-4-20250514" },
+                { id: "claude-listed-model" },
+              ],
+              error: null,
+            },
+          ]);
+        }
+        return defaultImpl(cmd, args as never);
+      });
+
+      render(<SettingsPage />);
+
+      await waitFor(() => {
+        expect(
+          screen.getByRole("option", { name: "claude-listed-model" }),
+        ).toBeInTheDocument();
+      });
+      expect(
+        screen.queryByRole("option", { name: "claude-opus-4-20250514" }),
+      ).not.toBeInTheDocument();
+    });
   });
 
   describe("provider selection", () => {
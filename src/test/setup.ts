@@ -13,6 +13,14 @@ vi.mock("@tauri-apps/api/event", () => ({
   listen: vi.fn(() => Promise.resolve(() => {})),
 }));
 
+// Window-scoped listeners go through the (possibly per-test) event mock
+vi.mock("@tauri-apps/api/webviewWindow", async () => {
+  const { listen } = await import("@tauri-apps/api/event");
+  return {
+    getCurrentWebviewWindow: vi.fn(() => ({ label: "main", listen })),
+  };
+});
+
 vi.mock("@tauri-apps/api/window", () => ({
   getCurrentWindow: vi.fn(() => ({
     setTitle: vi.fn(() => Promise.resolve()),
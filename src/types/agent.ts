@@ -7,16 +7,38 @@ export type {
   AgentCompactionWarningPayload,
   AgentCompletePayload,
   AgentErrorPayload,
+  AgentMode,
+  AgentReconnectingPayload,
+  AgentSlowResponsePayload,
   AgentStatus,
   AgentStatusPayload,
+  AgentSteeringPayload,
+  ArchivedPlan,
   ChatContentBlock,
   ChatMessage,
   CompactedFact,
   ContentBlockStartPayload,
   ContentBlockType,
   FactCategory,
+  FindingSeverity,
+  ListedModel,
   MessageRole,
+  ModelInfo,
   PlanReadyPayload,
+  PlanStatus,
+  PlanWithdrawnPayload,
+  ProviderModels,
+  ReplaySummary,
+  ReviewCommentDraft,
+  RunOptions,
+  SecurityFinding,
+  SendOptions,
+  SteeringMessage,
+  TodoItem,
+  TodoList,
+  ToolArtifact,
+  ToolChoice,
   ToolEndPayload,
   ToolStartPayload,
+  TrackedTodo,
 } from "./generated";
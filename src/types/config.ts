@@ -2,12 +2,26 @@
 // Regenerate with: make generate-types
 export type {
   AgentConfig,
+  AttributionConfig,
   ConfigChangedPayload,
+  ConfigReloadedPayload,
+  DatabaseConfig,
+  DatabaseKind,
+  DiagnosticsConfig,
+  DockerConfig,
   ExecutionConfig,
+  FormattingConfig,
+  LoggingConfig,
+  NetworkConfig,
   NotificationAction,
   NotificationsConfig,
   ProjectConfig,
   PromptsConfig,
+  ProviderTimeouts,
   ProviderInfo,
+  RateLimitConfig,
+  RecentProject,
   SearchConfig,
+  TemplatesConfig,
+  WorkspaceConfig,
 } from "./generated";
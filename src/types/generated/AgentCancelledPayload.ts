@@ -1,3 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChatMessage } from "./ChatMessage";
 
-export type AgentCancelledPayload = { reason: string, };
+export type AgentCancelledPayload = { reason: string, 
+/**
+ * Text and completed tool results streamed before cancellation
+ */
+partial: ChatMessage | null, };
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CompactedFact } from "./CompactedFact";
+
+/**
+ * Session context a compaction is about to apply. With confirmation on for
+ * the session, the run waits for `agent_respond_compaction`.
+ */
+export type AgentCompactionPreviewPayload = { summary: string | null, facts: Array<CompactedFact>, 
+/**
+ * Messages the summary and facts replace
+ */
+compacted_messages: number, awaiting_confirmation: boolean, };
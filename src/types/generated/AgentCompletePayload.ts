@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RunTimings } from "./RunTimings";
 
-export type AgentCompletePayload = { message_id: string, stop_reason: string | null, };
+export type AgentCompletePayload = { message_id: string, stop_reason: string | null, timings: RunTimings, };
@@ -1,3 +1,25 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AnthropicOptions } from "./AnthropicOptions";
+import type { GeminiOptions } from "./GeminiOptions";
 
-export type AgentConfig = { provider: string, model: string, api_key_env: string, max_tokens: number, context_limit: number | null, };
+export type AgentConfig = { provider: string, model: string, api_key_env: string, max_tokens: number, context_limit: number | null, 
+/**
+ * Sampling temperature (None = provider default)
+ */
+temperature: number | null, 
+/**
+ * Fraction of the context limit at which compaction starts (None = 0.8)
+ */
+compaction_threshold: number | null, 
+/**
+ * API origin override, e.g. a gateway or a local test server (None = provider default)
+ */
+base_url: string | null, 
+/**
+ * Anthropic-only request settings, the `[agent.anthropic]` table
+ */
+anthropic: AnthropicOptions | null, 
+/**
+ * Gemini-only generation settings, the `[agent.gemini]` table
+ */
+gemini: GeminiOptions | null, };
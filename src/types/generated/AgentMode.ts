@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Whether the main agent may change files.
+ */
+export type AgentMode = "code" | "plan" | "dry_run";
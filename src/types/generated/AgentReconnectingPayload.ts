@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Emitted before retrying a request that failed on a connection error.
+ */
+export type AgentReconnectingPayload = { attempt: number, max_attempts: number, delay_ms: number, error: string, 
+/**
+ * Streamed blocks from this index onward belong to the failed attempt and will be re-sent
+ */
+block_offset: number, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Emitted once per request when the provider has sent nothing for longer than
+ * the configured slow-response threshold.
+ */
+export type AgentSlowResponsePayload = { elapsed_ms: number, threshold_ms: number, };
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type AgentStatus = "idle" | "sending" | "thinking" | "streaming" | "tool_running" | "tool_waiting" | "compacting" | "cancelled" | "error";
+export type AgentStatus = "idle" | "sending" | "thinking" | "streaming" | "tool_running" | "tool_waiting" | "compacting" | "reconnecting" | "rate_limited" | "paused" | "cancelled" | "error";
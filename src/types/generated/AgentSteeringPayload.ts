@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SteeringMessage } from "./SteeringMessage";
+
+/**
+ * Emitted when queued messages are added to the conversation mid-run.
+ */
+export type AgentSteeringPayload = { messages: Array<SteeringMessage>, 
+/**
+ * Id of the assistant message the steering closed, if it had content
+ */
+closed_message_id?: string, };
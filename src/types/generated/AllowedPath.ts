@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PathAccess } from "./PathAccess";
+
+/**
+ * A directory outside the project, e.g. a shared monorepo package or a scratch dir.
+ */
+export type AllowedPath = { 
+/**
+ * Absolute, or relative to the project root (e.g. `../shared`)
+ */
+path: string, access: PathAccess, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Request settings only the Anthropic API understands.
+ */
+export type AnthropicOptions = { 
+/**
+ * Nucleus sampling cutoff (None = provider default)
+ */
+top_p: number | null, 
+/**
+ * Strings that end the response when generated
+ */
+stop_sequences: Array<string>, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlanStatus } from "./PlanStatus";
+
+/**
+ * A plan the user approved or rejected.
+ */
+export type ArchivedPlan = { 
+/**
+ * 1 for the session's first plan, 2 for the next, and so on
+ */
+number: number, 
+/**
+ * The plan as submitted, in markdown
+ */
+plan: string, status: PlanStatus, rejection_reason?: string, 
+/**
+ * RFC 3339 UTC timestamp of the decision
+ */
+archived_at: string, 
+/**
+ * Todo items added for the plan's steps
+ */
+todo_ids: Array<string>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Trailer marking files written by the agent and commit messages as AI-generated.
+ */
+export type AttributionConfig = { enabled: boolean, 
+/**
+ * Tool name shown in the trailer
+ */
+label: string, };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What runs without asking the user first. Tool categories left off wait
+ * for `agent_respond_tool_approval`; sub-agents cannot ask, so they are not
+ * offered those tools.
+ */
+export type AutoApproveConfig = { 
+/**
+ * Accept submitted plans without waiting for `agent_approve_plan`
+ */
+plans: boolean, 
+/**
+ * write_file, edit_file, multi_edit, notebook_edit and scaffold
+ */
+edits: boolean, 
+/**
+ * bash and docker
+ */
+bash: boolean, 
+/**
+ * web_fetch, search_web and lookup_docs
+ */
+network: boolean, };
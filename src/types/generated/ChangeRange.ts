@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Half-open span of a line, in UTF-16 code units
+ */
+export type ChangeRange = { start: number, end: number, };
@@ -2,4 +2,8 @@
 import type { ChatContentBlock } from "./ChatContentBlock";
 import type { MessageRole } from "./MessageRole";
 
-export type ChatMessage = { id: string, role: MessageRole, content_blocks: Array<ChatContentBlock>, };
+export type ChatMessage = { id: string, role: MessageRole, content_blocks: Array<ChatContentBlock>, 
+/**
+ * Set on assistant messages kept from a cancelled run
+ */
+interrupted?: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CommandSummary = { command: string, 
+/**
+ * Failed or was refused, e.g. a non-zero exit code
+ */
+is_error: boolean, };
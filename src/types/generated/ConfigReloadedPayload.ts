@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * The project config changed on disk and was loaded again.
+ */
+export type ConfigReloadedPayload = { project_path: string, 
+/**
+ * Dotted paths of the settings that changed, e.g. `agent.model`
+ */
+changed_keys: Array<string>, 
+/**
+ * Why the file could not be loaded; the agent keeps its last valid config
+ */
+error: string | null, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChatMessage } from "./ChatMessage";
+
+export type ConversationRewoundPayload = { 
+/**
+ * The conversation the new run continues from
+ */
+messages: Array<ChatMessage>, };
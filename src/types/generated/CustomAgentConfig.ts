@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * An agent type defined by the project. Its id must differ from the
+ * built-in ones, which take precedence.
+ */
+export type CustomAgentConfig = { 
+/**
+ * What the agent is for, shown to the model in the dispatch_agent tool
+ */
+description: string, 
+/**
+ * System prompt; may use the same placeholders as the built-in prompts
+ */
+prompt: string, 
+/**
+ * Tools the agent may use (None = the explore agent's read-only set,
+ * empty = text only)
+ */
+tools?: Array<string>, };
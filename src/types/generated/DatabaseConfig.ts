@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DatabaseKind } from "./DatabaseKind";
+
+/**
+ * A database connection for the query_database tool.
+ */
+export type DatabaseConfig = { kind: DatabaseKind, 
+/**
+ * SQLite database file, relative to the project root
+ */
+path: string | null, 
+/**
+ * Env var holding the postgres/mysql connection URL, so credentials stay out of the config
+ */
+url_env: string | null, 
+/**
+ * Reject statements that modify data and open the connection read-only
+ */
+read_only: boolean, 
+/**
+ * Rows returned per query; the rest are counted but not shown
+ */
+max_rows: number, };
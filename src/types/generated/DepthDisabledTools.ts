@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Tools sub-agents lose from a nesting depth on, e.g. no bash below the first level.
+ */
+export type DepthDisabledTools = { 
+/**
+ * 1 = sub-agents of the main agent, 2 = their sub-agents, and so on
+ */
+depth: number, tools: Array<string>, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Check commands run after each batch of edits; failures are appended to the edit results.
+ */
+export type DiagnosticsConfig = { 
+/**
+ * Check command per file extension (e.g. `rs = "cargo check --message-format short"`),
+ * run through the shell in the project root
+ */
+commands: { [key in string]?: string }, 
+/**
+ * Seconds a check may run before it is abandoned
+ */
+timeout_secs: number, 
+/**
+ * Output lines kept from a failing check
+ */
+max_lines: number, };
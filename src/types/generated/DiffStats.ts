@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Line counts for a whole file diff
+ */
+export type DiffStats = { additions: number, deletions: number, total_lines: number, };
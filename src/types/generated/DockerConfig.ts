@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Container access for the docker tool. Off unless enabled, and limited to
+ * the listed compose services.
+ */
+export type DockerConfig = { enabled: boolean, 
+/**
+ * Compose file relative to the project root (None = compose's default lookup)
+ */
+compose_file: string | null, 
+/**
+ * Services the agent may run commands in and read logs from
+ */
+allowed_services: Array<string>, };
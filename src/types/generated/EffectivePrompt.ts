@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PromptSection } from "./PromptSection";
+
+export type EffectivePrompt = { 
+/**
+ * None for the main agent
+ */
+agent_type: string | null, 
+/**
+ * The full system prompt, sections joined as sent
+ */
+prompt: string, sections: Array<PromptSection>, 
+/**
+ * Estimated (chars / 4), like the compaction threshold
+ */
+total_tokens: number, };
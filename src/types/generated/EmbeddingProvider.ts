@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where embeddings come from: the Gemini API or a local Ollama server.
+ */
+export type EmbeddingProvider = "gemini" | "ollama";
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type EmbeddingsStatus = { 
+/**
+ * `[index] enabled`; a disabled index is kept on disk but not updated
+ */
+enabled: boolean, model: string, files: number, chunks: number, size_bytes: number, 
+/**
+ * RFC 3339 UTC timestamp of the last write
+ */
+updated_at: string | null, 
+/**
+ * Changed files not embedded yet, e.g. after a failed request
+ */
+pending_files: number, };
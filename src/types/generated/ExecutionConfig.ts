@@ -1,3 +1,53 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AllowedPath } from "./AllowedPath";
+import type { DepthDisabledTools } from "./DepthDisabledTools";
+import type { ShellKind } from "./ShellKind";
 
-export type ExecutionConfig = { timeout_secs: number, max_tool_iterations: number, max_agent_depth: number, };
+export type ExecutionConfig = { timeout_secs: number, 
+/**
+ * Longest timeout a bash call may ask for; longer requests are capped
+ */
+max_timeout_secs: number, 
+/**
+ * How long a submitted plan waits for the user before it is withdrawn;
+ * 0 waits as long as it takes
+ */
+plan_approval_timeout_secs: number, max_tool_iterations: number, max_agent_depth: number, 
+/**
+ * Largest single message accepted, in bytes
+ */
+max_message_bytes: number, 
+/**
+ * Largest conversation (system prompt plus all messages) sent in one request
+ */
+max_conversation_bytes: number, 
+/**
+ * Largest tool output passed back to the model; bigger outputs become an error result
+ */
+max_tool_result_bytes: number, 
+/**
+ * Most of a file read_file returns at once; larger reads return the
+ * start with an index of the file's declarations or headings
+ */
+max_read_bytes: number, 
+/**
+ * Tools never offered to the agent or its sub-agents, by name
+ */
+disabled_tools: Array<string>, 
+/**
+ * Tools also taken away from sub-agents nested at least `depth` deep
+ */
+depth_disabled_tools: Array<DepthDisabledTools>, 
+/**
+ * Shell the bash tool runs commands with; projects on WSL paths always use the distro's sh
+ */
+shell: ShellKind, 
+/**
+ * Directories outside the project the file tools and bash may use
+ */
+allowed_paths: Array<AllowedPath>, 
+/**
+ * Answer bash, file edit and docker exec calls with what they would have
+ * done (the command or diff) instead of running them
+ */
+dry_run: boolean, };
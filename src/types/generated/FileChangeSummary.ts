@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileStatus } from "./FileStatus";
+
+export type FileChangeSummary = { path: string, 
+/**
+ * added, modified or deleted
+ */
+status: FileStatus, additions: number, deletions: number, };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiffHunk } from "./DiffHunk";
+import type { DiffStats } from "./DiffStats";
+import type { FileStatus } from "./FileStatus";
+
+/**
+ * A window of a file diff's hunks; oversized hunks are split so each page stays small
+ */
+export type FileDiffPage = { path: string, status: FileStatus, hunks: Array<DiffHunk>, 
+/**
+ * Index of the first hunk in `hunks`
+ */
+hunk_offset: number, total_hunks: number, stats: DiffStats, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Formatting of files changed by the agent's write and edit tools.
+ */
+export type FormattingConfig = { 
+/**
+ * Format files after write_file, edit_file and multi_edit
+ */
+on_edit: boolean, 
+/**
+ * Formatter command per file extension (e.g. `rs = "rustfmt --edition 2021"`), run
+ * with the file path appended; other extensions use the detected formatter
+ */
+commands: { [key in string]?: string }, };
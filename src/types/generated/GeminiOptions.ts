@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GeminiSafetySetting } from "./GeminiSafetySetting";
+
+/**
+ * Generation settings only the Gemini API understands.
+ */
+export type GeminiOptions = { 
+/**
+ * Nucleus sampling cutoff (None = provider default)
+ */
+top_p: number | null, 
+/**
+ * Tokens the model may spend thinking (0 = off, -1 = dynamic, None = model default)
+ */
+thinking_budget: number | null, 
+/**
+ * Blocking thresholds per harm category; unlisted categories keep the API defaults
+ */
+safety_settings: Array<GeminiSafetySetting>, };
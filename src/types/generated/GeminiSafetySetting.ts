@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GeminiSafetySetting = { 
+/**
+ * Harm category, e.g. `HARM_CATEGORY_DANGEROUS_CONTENT`
+ */
+category: string, 
+/**
+ * Blocking threshold, e.g. `BLOCK_ONLY_HIGH` or `BLOCK_NONE`
+ */
+threshold: string, };
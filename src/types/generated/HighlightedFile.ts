@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Syntax-highlighted lines of a project file
+ */
+export type HighlightedFile = { path: string, 
+/**
+ * 1-based number of the first line in `lines`
+ */
+start_line: number, 
+/**
+ * Highlighted HTML, one entry per line
+ */
+lines: Array<string>, total_lines: number, 
+/**
+ * Theme used; unknown themes fall back to the default
+ */
+theme: string, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EmbeddingProvider } from "./EmbeddingProvider";
+
+/**
+ * Embeddings index for the semantic_search tool, stored under `.devflow/index`.
+ */
+export type IndexConfig = { enabled: boolean, provider: EmbeddingProvider, 
+/**
+ * Embedding model (None = text-embedding-004 for Gemini, nomic-embed-text for Ollama)
+ */
+model: string | null, 
+/**
+ * API origin override (None = provider default, http://localhost:11434 for Ollama)
+ */
+base_url: string | null, 
+/**
+ * Env var holding the Gemini API key (None = GEMINI_API_KEY)
+ */
+api_key_env: string | null, 
+/**
+ * Lines per indexed chunk
+ */
+chunk_lines: number, 
+/**
+ * Larger files are not indexed
+ */
+max_file_bytes: number, };
@@ -0,0 +1,20 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EmbeddingsStatus } from "./EmbeddingsStatus";
+import type { ProjectMapStatus } from "./ProjectMapStatus";
+
+/**
+ * Freshness and size of the project's indexes.
+ */
+export type IndexStatus = { 
+/**
+ * Whether file changes are applied as they happen
+ */
+watching: boolean, 
+/**
+ * None until a session has started for the project
+ */
+project_map: ProjectMapStatus | null, 
+/**
+ * None until an embeddings index has been built
+ */
+embeddings: EmbeddingsStatus | null, };
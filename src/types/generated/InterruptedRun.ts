@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A run the app did not finish, offered for recovery on startup.
+ */
+export type InterruptedRun = { session_id: string, 
+/**
+ * RFC 3339 UTC timestamp
+ */
+started_at: string, provider: string, model: string, 
+/**
+ * Tools that were running when the app stopped
+ */
+pending_tools: Array<string>, 
+/**
+ * Files the agent changed, which "restore" puts back
+ */
+changed_files: Array<string>, };
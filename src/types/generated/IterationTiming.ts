@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Timing of one model response and the tools it called.
+ */
+export type IterationTiming = { 
+/**
+ * Milliseconds from sending the request to the first content (None if the response had none)
+ */
+first_token_ms: number | null, 
+/**
+ * Milliseconds the response took, including any reconnects
+ */
+model_ms: number, 
+/**
+ * Milliseconds spent running the response's tool calls
+ */
+tool_ms: number, output_tokens: number, 
+/**
+ * Output tokens per second once content started arriving
+ */
+tokens_per_sec: number | null, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Average model latency and throughput for the session.
+ */
+export type LatencyStats = { responses: number, avg_first_token_ms: number | null, tokens_per_sec: number | null, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A model offered by a provider's API.
+ */
+export type ListedModel = { id: string, display_name: string | null, 
+/**
+ * From the API when reported, else the registry
+ */
+context_window: number | null, 
+/**
+ * From the API when reported, else the registry
+ */
+max_output_tokens: number | null, supports_vision: boolean | null, 
+/**
+ * USD per million input tokens
+ */
+input_price_per_mtok: number | null, 
+/**
+ * USD per million output tokens
+ */
+output_price_per_mtok: number | null, };
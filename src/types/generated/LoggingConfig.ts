@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Debug logging of agent runs.
+ */
+export type LoggingConfig = { 
+/**
+ * Write a JSONL transcript of each session to `.devflow/logs/`
+ */
+transcripts: boolean, 
+/**
+ * Copy each rated reply, with the conversation leading to it, to
+ * `.devflow/evals/ratings.jsonl`
+ */
+evals: boolean, };
@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Metadata for a known model.
+ */
+export type ModelInfo = { model: string, provider: string, 
+/**
+ * Maximum input tokens per request
+ */
+context_window: number, 
+/**
+ * Maximum output tokens per response
+ */
+max_output_tokens: number, supports_vision: boolean, 
+/**
+ * USD per million input tokens
+ */
+input_price_per_mtok: number, 
+/**
+ * USD per million output tokens
+ */
+output_price_per_mtok: number, };
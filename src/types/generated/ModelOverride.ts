@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Provider and model used instead of the `[agent]` ones, e.g. a cheaper
+ * model for exploration or summaries.
+ */
+export type ModelOverride = { 
+/**
+ * None keeps the `[agent]` provider
+ */
+provider?: string, model: string, 
+/**
+ * Env var holding the API key (None = the `[agent]` one for the same
+ * provider, `<PROVIDER>_API_KEY` for another)
+ */
+api_key_env?: string, };
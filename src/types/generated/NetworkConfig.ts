@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Proxy and TLS settings applied to every outgoing HTTP client.
+ */
+export type NetworkConfig = { 
+/**
+ * Proxy URL for HTTP and HTTPS traffic (None = use system proxy env vars)
+ */
+proxy: string | null, 
+/**
+ * Comma-separated hosts that bypass the proxy (same syntax as NO_PROXY)
+ */
+no_proxy: string | null, 
+/**
+ * Path to a PEM file with additional root certificates
+ */
+ca_cert_path: string | null, };
@@ -1,3 +1,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type PlanReadyPayload = { plan: string, };
+export type PlanReadyPayload = { plan: string, 
+/**
+ * Id to answer with `agent_approve_plan` / `agent_reject_plan`
+ */
+plan_id: number, };
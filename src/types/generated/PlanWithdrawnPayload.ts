@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A plan that stopped waiting for the user: it timed out or the run was
+ * cancelled. Answering it is no longer possible.
+ */
+export type PlanWithdrawnPayload = { plan_id: number, };
@@ -1,11 +1,54 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { AgentConfig } from "./AgentConfig";
+import type { AttributionConfig } from "./AttributionConfig";
+import type { AutoApproveConfig } from "./AutoApproveConfig";
+import type { CustomAgentConfig } from "./CustomAgentConfig";
+import type { DatabaseConfig } from "./DatabaseConfig";
+import type { DiagnosticsConfig } from "./DiagnosticsConfig";
+import type { DockerConfig } from "./DockerConfig";
 import type { ExecutionConfig } from "./ExecutionConfig";
+import type { FormattingConfig } from "./FormattingConfig";
+import type { IndexConfig } from "./IndexConfig";
+import type { LoggingConfig } from "./LoggingConfig";
+import type { ModelOverride } from "./ModelOverride";
+import type { NetworkConfig } from "./NetworkConfig";
 import type { NotificationsConfig } from "./NotificationsConfig";
 import type { PromptsConfig } from "./PromptsConfig";
+import type { ProviderTimeouts } from "./ProviderTimeouts";
+import type { RateLimitConfig } from "./RateLimitConfig";
+import type { RequestCacheConfig } from "./RequestCacheConfig";
+import type { SamplingOverrides } from "./SamplingOverrides";
 import type { SearchConfig } from "./SearchConfig";
+import type { TemplatesConfig } from "./TemplatesConfig";
+import type { WorkspaceConfig } from "./WorkspaceConfig";
 
-export type ProjectConfig = { agent: AgentConfig, execution: ExecutionConfig, search: SearchConfig, notifications: NotificationsConfig, prompts: PromptsConfig, 
+export type ProjectConfig = { agent: AgentConfig, execution: ExecutionConfig, search: SearchConfig, notifications: NotificationsConfig, prompts: PromptsConfig, network: NetworkConfig, attribution: AttributionConfig, logging: LoggingConfig, docker: DockerConfig, formatting: FormattingConfig, diagnostics: DiagnosticsConfig, index: IndexConfig, auto_approve: AutoApproveConfig, workspace: WorkspaceConfig, templates: TemplatesConfig, request_cache: RequestCacheConfig, 
+/**
+ * Request timeouts keyed by provider id (e.g. `[timeouts.anthropic]`)
+ */
+timeouts: { [key in string]?: ProviderTimeouts }, 
+/**
+ * Request rate limits keyed by provider id (e.g. `[rate_limits.anthropic]`)
+ */
+rate_limits: { [key in string]?: RateLimitConfig }, 
+/**
+ * Databases the query_database tool can connect to, keyed by name (e.g. `[databases.app]`)
+ */
+databases: { [key in string]?: DatabaseConfig }, 
+/**
+ * Sampling overrides for sub-agents, keyed by agent type id (e.g. `[agent_sampling.pr-review]`)
+ */
+agent_sampling: { [key in string]?: SamplingOverrides }, 
+/**
+ * Models for sub-agents, keyed by agent type id, and for `compaction`
+ * extraction (e.g. `[agent_models.summarize]`)
+ */
+agent_models: { [key in string]?: ModelOverride }, 
+/**
+ * Project-defined agent types dispatch_agent can run, keyed by id
+ * (e.g. `[custom_agents.migration-check]`)
+ */
+custom_agents: { [key in string]?: CustomAgentConfig }, 
 /**
  * Custom system prompt (None = use default)
  */
@@ -18,7 +61,15 @@ extraction_prompt: string | null,
  * Custom tool descriptions (None = use defaults)
  */
 tool_descriptions: { [key in string]?: string } | null, 
+/**
+ * Project notes appended to tool descriptions by tool name, e.g. bash conventions
+ */
+tool_notes: { [key in string]?: string }, 
 /**
  * Custom agent prompts (None = use defaults)
  */
-agent_prompts: { [key in string]?: string } | null, };
+agent_prompts: { [key in string]?: string } | null, 
+/**
+ * Text appended to agent prompts by agent type id
+ */
+agent_prompt_notes: { [key in string]?: string }, };
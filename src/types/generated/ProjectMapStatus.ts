@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type ProjectMapStatus = { files: number, 
+/**
+ * RFC 3339 UTC timestamp of the last update
+ */
+updated_at: string, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type PromptSection = { 
+/**
+ * "app", "memory", "pre", "custom" (a sub-agent's own prompt), "post" or "compacted"
+ */
+name: string, text: string, tokens: number, };
@@ -1,3 +1,8 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type PromptsConfig = { pre: string, post: string, };
+export type PromptsConfig = { pre: string, post: string, 
+/**
+ * Values for `{VAR:name}` placeholders in prompts (e.g. `ticket_url = "..."`);
+ * variables sent with a message take precedence
+ */
+variables: { [key in string]?: string }, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ListedModel } from "./ListedModel";
+
+/**
+ * Models listed by one provider, or why listing failed.
+ */
+export type ProviderModels = { provider: string, models: Array<ListedModel>, error: string | null, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Provider request timeouts. The slow-response threshold only triggers a UI warning.
+ */
+export type ProviderTimeouts = { 
+/**
+ * Seconds to establish a connection
+ */
+connect_secs: number, 
+/**
+ * Seconds without receiving data before a request fails
+ */
+read_secs: number, 
+/**
+ * Seconds without receiving data before emitting `agent-slow-response` (0 = off)
+ */
+slow_response_secs: number, 
+/**
+ * Seconds without a new chunk, once a response is streaming, before the
+ * stream counts as stalled and is retried (0 = off)
+ */
+stream_idle_secs: number, };
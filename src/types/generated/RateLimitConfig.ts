@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Provider rate limits, shared by every session in the process.
+ */
+export type RateLimitConfig = { 
+/**
+ * Maximum requests per minute (None = unlimited)
+ */
+requests_per_minute: number | null, 
+/**
+ * Maximum input plus output tokens per minute (None = unlimited)
+ */
+tokens_per_minute: number | null, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A project in the recents list, with what it was last used with
+ */
+export type RecentProject = { path: string, 
+/**
+ * RFC 3339 UTC timestamp
+ */
+last_opened: string, 
+/**
+ * Pinned projects are listed first and never dropped from the list
+ */
+pinned: boolean, provider?: string, model?: string, 
+/**
+ * Start of the first message of the last session
+ */
+last_session_title?: string, };
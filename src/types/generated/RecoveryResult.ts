@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ArchivedPlan } from "./ArchivedPlan";
+import type { ChatMessage } from "./ChatMessage";
+import type { TrackedTodo } from "./TrackedTodo";
+
+export type RecoveryResult = { 
+/**
+ * Files put back by "restore"
+ */
+restored_files: Array<string>, 
+/**
+ * The session's conversation as last sent, for "resume"
+ */
+messages: Array<ChatMessage>, 
+/**
+ * The session's todo list, for "resume"; the current session takes it over
+ */
+todos: Array<TrackedTodo>, 
+/**
+ * The session's plan history, for "resume"; the current session takes it over
+ */
+plans: Array<ArchivedPlan>, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Outcome of a replay, alongside what the recorded run ended with.
+ */
+export type ReplaySummary = { text: string, tool_calls_made: number, stop_reason: string | null, recorded_stop_reason: string | null, recorded_error: string | null, responses_replayed: number, responses_recorded: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Caching of repeated headless requests (titles, extraction, sub-agents).
+ */
+export type RequestCacheConfig = { 
+/**
+ * Answer identical headless requests from the cache instead of the API
+ */
+enabled: boolean, 
+/**
+ * Seconds a cached response is reused
+ */
+ttl_secs: number, 
+/**
+ * Responses kept per provider; the oldest are dropped first
+ */
+max_entries: number, };
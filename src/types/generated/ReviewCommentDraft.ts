@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A draft inline comment from the PR review agent, anchored to a line in the
+ * new version of a file and ready to post as a review comment.
+ */
+export type ReviewCommentDraft = { 
+/**
+ * Path relative to the repository root
+ */
+file: string, line: number, 
+/**
+ * First line when the comment spans a range ending at `line`
+ */
+start_line: number | null, body: string, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AgentMode } from "./AgentMode";
+
+/**
+ * Effective parameters for the main agent. Sub-agents keep using the project config.
+ */
+export type RunOptions = { model: string, 
+/**
+ * Sampling temperature (None = provider default)
+ */
+temperature: number | null, max_tokens: number, max_tool_iterations: number, context_limit: number, 
+/**
+ * Fraction of the context limit at which compaction starts
+ */
+compaction_threshold: number, mode: AgentMode, };
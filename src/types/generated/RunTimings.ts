@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { IterationTiming } from "./IterationTiming";
+
+/**
+ * Where the time of an agent run went: waiting for the model, generating, or running tools.
+ */
+export type RunTimings = { total_ms: number, 
+/**
+ * Time to first content of the run's first response
+ */
+first_token_ms: number | null, model_ms: number, tool_ms: number, 
+/**
+ * Output tokens per second across all responses
+ */
+tokens_per_sec: number | null, iterations: Array<IterationTiming>, };
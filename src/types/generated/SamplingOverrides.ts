@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Per-agent-type sampling settings; unset fields keep the `[agent]` values.
+ */
+export type SamplingOverrides = { temperature: number | null, top_p: number | null, 
+/**
+ * Replaces the Anthropic stop sequences (not sent to Gemini)
+ */
+stop_sequences: Array<string> | null, };
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ScaffoldSource } from "./ScaffoldSource";
+import type { TemplateVariable } from "./TemplateVariable";
+
+export type ScaffoldInfo = { name: string, source: ScaffoldSource, 
+/**
+ * Name of the configured source, for `remote` scaffolds
+ */
+source_name?: string, 
+/**
+ * Number of files the scaffold creates
+ */
+files: number, 
+/**
+ * Variables declared in the scaffold's `.scaffold.toml`, in order
+ */
+variables: Array<TemplateVariable>, };
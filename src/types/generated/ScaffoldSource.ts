@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Where a scaffold comes from; with the same name, project scaffolds win over
+ * configured sources, which win over user ones.
+ */
+export type ScaffoldSource = "project" | "remote" | "user";
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FindingSeverity } from "./FindingSeverity";
+
+/**
+ * A single issue reported by the security review agent.
+ */
+export type SecurityFinding = { severity: FindingSeverity, 
+/**
+ * Path relative to the repository root
+ */
+file: string, 
+/**
+ * Line in the new version of the file, when the finding has one
+ */
+line: number | null, description: string, remediation: string | null, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ToolChoice } from "./ToolChoice";
+
+/**
+ * Per-message options for `agent_send_message`.
+ */
+export type SendOptions = { tool_choice: ToolChoice, 
+/**
+ * Values for `{VAR:name}` prompt placeholders, overriding `[prompts.variables]`
+ */
+variables?: { [key in string]?: string }, };
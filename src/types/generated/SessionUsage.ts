@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourceUsage } from "./SourceUsage";
+
+/**
+ * Session token usage with its breakdown by source.
+ */
+export type SessionUsage = { input_tokens: number, output_tokens: number, by_source: Array<SourceUsage>, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SetupStatus } from "./SetupStatus";
+
+/**
+ * One row of the setup checklist.
+ */
+export type SetupCheck = { 
+/**
+ * What was checked, e.g. "API key" or "git identity"
+ */
+name: string, status: SetupStatus, 
+/**
+ * What was found, e.g. "git version 2.43.0"
+ */
+message: string, 
+/**
+ * How to fix a warning or error
+ */
+hint?: string, };
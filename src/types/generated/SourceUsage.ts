@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UsageSource } from "./UsageSource";
+
+/**
+ * Tokens spent by one source of the session.
+ */
+export type SourceUsage = { source: UsageSource, 
+/**
+ * Sub-agent type, for sub-agent usage
+ */
+agent_type: string | null, input_tokens: number, output_tokens: number, };
@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ChangeRange } from "./ChangeRange";
+import type { LineKind } from "./LineKind";
+
+export type SplitCell = { line_no: number, kind: LineKind, content: string, 
+/**
+ * Syntax-highlighted HTML content (if available)
+ */
+highlighted: string | null, 
+/**
+ * Changed spans of `content` when the line is paired with a modified counterpart
+ */
+changes: Array<ChangeRange>, };
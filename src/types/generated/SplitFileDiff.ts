@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileStatus } from "./FileStatus";
+import type { SplitHunk } from "./SplitHunk";
+
+/**
+ * A file diff laid out for side-by-side display
+ */
+export type SplitFileDiff = { path: string, status: FileStatus, hunks: Array<SplitHunk>, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SplitRow } from "./SplitRow";
+
+export type SplitHunk = { old_start: number, old_lines: number, new_start: number, new_lines: number, rows: Array<SplitRow>, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SplitCell } from "./SplitCell";
+
+/**
+ * One aligned row; a side is null where the other side added or removed a line
+ */
+export type SplitRow = { old: SplitCell | null, new: SplitCell | null, };
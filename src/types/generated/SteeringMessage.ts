@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A user message queued while the agent is running, injected before its next request.
+ */
+export type SteeringMessage = { 
+/**
+ * Client-side id of the queued message
+ */
+id: string, content: string, };
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A shared set of scaffolds: a git repository (cached locally and updated by
+ * `template_sync`) or a directory outside the project.
+ */
+export type TemplateSource = { 
+/**
+ * Shown in the scaffold list and names the cache directory
+ */
+name: string, 
+/**
+ * Clone URL; takes precedence over `path`
+ */
+git?: string, 
+/**
+ * Absolute, or relative to the project root (e.g. `../team-templates`)
+ */
+path?: string, 
+/**
+ * Branch, tag or commit a git source is pinned to (default: the remote HEAD)
+ */
+rev?: string, 
+/**
+ * Directory inside the source holding the scaffolds (default: its root)
+ */
+subdir?: string, };
@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TemplateSourceKind } from "./TemplateSourceKind";
+
+/**
+ * State of a configured template source, from `template_list_sources` and `template_sync`.
+ */
+export type TemplateSourceStatus = { name: string, kind: TemplateSourceKind, 
+/**
+ * Clone URL, or the resolved directory
+ */
+location: string, 
+/**
+ * Revision the source is pinned to
+ */
+rev?: string, 
+/**
+ * Commit the local cache is checked out at (git sources)
+ */
+commit?: string, 
+/**
+ * Whether the scaffolds are on disk: the cache was synced, or the directory exists
+ */
+available: boolean, 
+/**
+ * Number of scaffolds the source provides
+ */
+scaffolds: number, 
+/**
+ * Why the source could not be synced or read
+ */
+error?: string, };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TemplateVariableType } from "./TemplateVariableType";
+
+/**
+ * A variable a scaffold asks for. Values are checked against it before
+ * anything is rendered, and the UI renders the list as a form.
+ */
+export type TemplateVariable = { name: string, type: TemplateVariableType, 
+/**
+ * Help text for the form field
+ */
+description?: string, 
+/**
+ * Used when no value is given; without a default the variable is required
+ */
+default?: string, 
+/**
+ * Regex the whole value must match (e.g. `[A-Z][A-Za-z0-9]*`)
+ */
+pattern?: string, 
+/**
+ * Allowed values of a `choice` variable
+ */
+options: Array<string>, };
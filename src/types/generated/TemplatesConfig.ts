@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TemplateSource } from "./TemplateSource";
+
+/**
+ * Where scaffold templates come from besides the project and user directories.
+ */
+export type TemplatesConfig = { 
+/**
+ * Searched in order after the project's `.devflow/templates`
+ */
+sources: Array<TemplateSource>, };
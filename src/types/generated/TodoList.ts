@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TrackedTodo } from "./TrackedTodo";
+
+/**
+ * The session's todos and how stale they are, for the task panel and the
+ * stale todo list reminder.
+ */
+export type TodoList = { items: Array<TrackedTodo>, 
+/**
+ * Current turn of the session
+ */
+turn: number, 
+/**
+ * Ids of unfinished items unchanged for `STALE_TODO_TURNS` turns or more
+ */
+stale: Array<string>, };
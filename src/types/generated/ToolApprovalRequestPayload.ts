@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ApprovalCategory } from "./ApprovalCategory";
+
+/**
+ * A tool call waiting for `agent_respond_tool_approval` because its category
+ * is not auto-approved.
+ */
+export type ToolApprovalRequestPayload = { tool_use_id: string, tool_name: string, tool_input: unknown, category: ApprovalCategory, };
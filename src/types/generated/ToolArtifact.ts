@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FileDiff } from "./FileDiff";
+
+/**
+ * Something a tool produced besides its text. The model only sees the
+ * text; the UI can show these instead.
+ */
+export type ToolArtifact = { "type": "diff", diff: FileDiff, } | { "type": "table", columns: Array<string>, rows: Array<Array<string>>, } | { "type": "image", media_type: string, data: string, } | { "type": "link", url: string, title: string | null, };
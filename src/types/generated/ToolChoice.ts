@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How the model may use tools while answering a message.
+ */
+export type ToolChoice = "auto" | "required" | "none";
@@ -1,3 +1,12 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ToolArtifact } from "./ToolArtifact";
 
-export type ToolEndPayload = { tool_use_id: string, output: string, is_error: boolean, block_index: number, };
+export type ToolEndPayload = { tool_use_id: string, 
+/**
+ * What the model gets
+ */
+output: string, is_error: boolean, block_index: number, 
+/**
+ * Structured results for the UI to render in place of `output`
+ */
+artifacts: Array<ToolArtifact>, };
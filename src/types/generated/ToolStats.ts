@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Invocation statistics for one tool in the current session.
+ */
+export type ToolStats = { tool: string, calls: number, errors: number, 
+/**
+ * Fraction of calls that returned an error, 0.0 to 1.0
+ */
+error_rate: number, total_duration_ms: number, avg_duration_ms: number, 
+/**
+ * Bytes of output returned to the model
+ */
+total_output_bytes: number, avg_output_bytes: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TodoItem } from "./TodoItem";
+
+/**
+ * A todo item with when it last changed.
+ */
+export type TrackedTodo = { item: TodoItem, 
+/**
+ * RFC 3339 UTC timestamp of the last change to the item
+ */
+updated_at: string, 
+/**
+ * Turn of the session the item last changed in; each user message starts a turn
+ */
+updated_turn: number, };
@@ -0,0 +1,33 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { CommandSummary } from "./CommandSummary";
+import type { FileChangeSummary } from "./FileChangeSummary";
+import type { TestRunSummary } from "./TestRunSummary";
+
+/**
+ * Emitted as `agent-turn-summary` right after `agent-complete`
+ */
+export type TurnSummaryPayload = { 
+/**
+ * Same id as the `agent-complete` event
+ */
+message_id: string, 
+/**
+ * Files written or edited through the file tools; bash changes are not tracked
+ */
+files: Array<FileChangeSummary>, commands: Array<CommandSummary>, 
+/**
+ * Commands from `commands` that ran tests
+ */
+tests: Array<TestRunSummary>, 
+/**
+ * Tokens used by the turn, sub-agents included
+ */
+input_tokens: number, output_tokens: number, 
+/**
+ * Estimated at the main model's prices; None for models without known pricing
+ */
+cost_usd: number | null, 
+/**
+ * Tools ran in dry-run mode, so nothing was changed or executed
+ */
+dry_run: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Inclusive UTC date range (YYYY-MM-DD). Missing bounds are open.
+ */
+export type UsageExportRange = { start: string | null, end: string | null, };
@@ -3,4 +3,4 @@
 /**
  * Source of the token usage.
  */
-export type UsageSource = "main" | "sub_agent";
+export type UsageSource = "main" | "sub_agent" | "compaction";
@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WorkspaceRoot } from "./WorkspaceRoot";
+
+/**
+ * Related repositories worked on together with the project, e.g. the
+ * frontend, backend and infra repos of one product.
+ */
+export type WorkspaceConfig = { 
+/**
+ * Empty for a single-repo project
+ */
+roots: Array<WorkspaceRoot>, };
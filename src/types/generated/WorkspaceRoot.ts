@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type WorkspaceRoot = { 
+/**
+ * Path prefix the tools use for this repo (`backend` for `backend/src/main.rs`)
+ * and the repo selector of the git commands
+ */
+name: string, 
+/**
+ * Absolute, or relative to the project root (e.g. `../backend`)
+ */
+path: string, };
@@ -2,58 +2,152 @@
 // Regenerate with: make generate-types
 
 // Git types
+export type { ChangeRange } from "./ChangeRange";
 export type { ChangedFile } from "./ChangedFile";
 export type { DiffHunk } from "./DiffHunk";
 export type { DiffLine } from "./DiffLine";
+export type { DiffStats } from "./DiffStats";
 export type { FileDiff } from "./FileDiff";
+export type { FileDiffPage } from "./FileDiffPage";
 export type { FileStatus } from "./FileStatus";
+export type { HighlightedFile } from "./HighlightedFile";
 export type { LineKind } from "./LineKind";
 export type { RepositoryCheckResult } from "./RepositoryCheckResult";
+export type { SplitCell } from "./SplitCell";
+export type { SplitFileDiff } from "./SplitFileDiff";
+export type { SplitHunk } from "./SplitHunk";
+export type { SplitRow } from "./SplitRow";
 
 // Agent types
 export type { AgentCancelledPayload } from "./AgentCancelledPayload";
 export type { AgentChunkPayload } from "./AgentChunkPayload";
 export type { AgentCompactionPayload } from "./AgentCompactionPayload";
+export type { AgentCompactionPreviewPayload } from "./AgentCompactionPreviewPayload";
 export type { AgentCompactionWarningPayload } from "./AgentCompactionWarningPayload";
 export type { AgentCompletePayload } from "./AgentCompletePayload";
 export type { AgentErrorPayload } from "./AgentErrorPayload";
+export type { AgentMode } from "./AgentMode";
+export type { AgentReconnectingPayload } from "./AgentReconnectingPayload";
+export type { AgentSlowResponsePayload } from "./AgentSlowResponsePayload";
 export type { AgentStatus } from "./AgentStatus";
 export type { AgentStatusPayload } from "./AgentStatusPayload";
+export type { AgentSteeringPayload } from "./AgentSteeringPayload";
+export type { ApprovalCategory } from "./ApprovalCategory";
+export type { ArchivedPlan } from "./ArchivedPlan";
 export type { ChatContentBlock } from "./ChatContentBlock";
 export type { ChatMessage } from "./ChatMessage";
+export type { CommandSummary } from "./CommandSummary";
 export type { CompactedFact } from "./CompactedFact";
 export type { ContentBlockStartPayload } from "./ContentBlockStartPayload";
 export type { ContentBlockType } from "./ContentBlockType";
+export type { ConversationRewoundPayload } from "./ConversationRewoundPayload";
+export type { EffectivePrompt } from "./EffectivePrompt";
+export type { EmbeddingsStatus } from "./EmbeddingsStatus";
 export type { FactCategory } from "./FactCategory";
+export type { FileChangeSummary } from "./FileChangeSummary";
+export type { FindingSeverity } from "./FindingSeverity";
+export type { IndexStatus } from "./IndexStatus";
+export type { InterruptedRun } from "./InterruptedRun";
+export type { ListedModel } from "./ListedModel";
 export type { MemoryLoadedPayload } from "./MemoryLoadedPayload";
 export type { MemoryWarningPayload } from "./MemoryWarningPayload";
 export type { MessageRole } from "./MessageRole";
+export type { ModelInfo } from "./ModelInfo";
 export type { PlanReadyPayload } from "./PlanReadyPayload";
+export type { PlanStatus } from "./PlanStatus";
+export type { PlanWithdrawnPayload } from "./PlanWithdrawnPayload";
+export type { ProjectMapStatus } from "./ProjectMapStatus";
+export type { PromptSection } from "./PromptSection";
+export type { ProviderModels } from "./ProviderModels";
+export type { Rating } from "./Rating";
+export type { RecoveryAction } from "./RecoveryAction";
+export type { RecoveryResult } from "./RecoveryResult";
+export type { ReplaySummary } from "./ReplaySummary";
+export type { ReviewCommentDraft } from "./ReviewCommentDraft";
+export type { RunOptions } from "./RunOptions";
+export type { SecurityFinding } from "./SecurityFinding";
+export type { SendOptions } from "./SendOptions";
+export type { SteeringMessage } from "./SteeringMessage";
 export type { SubagentEndPayload } from "./SubagentEndPayload";
 export type { SubagentStartPayload } from "./SubagentStartPayload";
 export type { SubagentStatus } from "./SubagentStatus";
+export type { TestRunSummary } from "./TestRunSummary";
+export type { TodoItem } from "./TodoItem";
+export type { TodoList } from "./TodoList";
+export type { ToolApprovalRequestPayload } from "./ToolApprovalRequestPayload";
+export type { ToolArtifact } from "./ToolArtifact";
+export type { ToolChoice } from "./ToolChoice";
 export type { ToolEndPayload } from "./ToolEndPayload";
 export type { ToolStartPayload } from "./ToolStartPayload";
+export type { TrackedTodo } from "./TrackedTodo";
+export type { TurnSummaryPayload } from "./TurnSummaryPayload";
 
 // Usage types
 export type { AgentUsagePayload } from "./AgentUsagePayload";
+export type { IterationTiming } from "./IterationTiming";
+export type { LatencyStats } from "./LatencyStats";
+export type { RunTimings } from "./RunTimings";
+export type { SessionUsage } from "./SessionUsage";
+export type { SourceUsage } from "./SourceUsage";
+export type { ToolStats } from "./ToolStats";
+export type { UsageExportRange } from "./UsageExportRange";
 export type { UsageSource } from "./UsageSource";
 export type { UsageTotals } from "./UsageTotals";
 
 // Config types
 export type { AgentConfig } from "./AgentConfig";
+export type { AllowedPath } from "./AllowedPath";
+export type { AnthropicOptions } from "./AnthropicOptions";
+export type { AttributionConfig } from "./AttributionConfig";
+export type { AutoApproveConfig } from "./AutoApproveConfig";
 export type { ConfigChangedPayload } from "./ConfigChangedPayload";
+export type { ConfigReloadedPayload } from "./ConfigReloadedPayload";
+export type { CustomAgentConfig } from "./CustomAgentConfig";
+export type { DatabaseConfig } from "./DatabaseConfig";
+export type { DatabaseKind } from "./DatabaseKind";
+export type { DepthDisabledTools } from "./DepthDisabledTools";
+export type { DiagnosticsConfig } from "./DiagnosticsConfig";
+export type { DockerConfig } from "./DockerConfig";
+export type { EmbeddingProvider } from "./EmbeddingProvider";
 export type { ExecutionConfig } from "./ExecutionConfig";
+export type { FormattingConfig } from "./FormattingConfig";
+export type { GeminiOptions } from "./GeminiOptions";
+export type { GeminiSafetySetting } from "./GeminiSafetySetting";
+export type { IndexConfig } from "./IndexConfig";
+export type { LoggingConfig } from "./LoggingConfig";
+export type { ModelOverride } from "./ModelOverride";
+export type { NetworkConfig } from "./NetworkConfig";
 export type { NotificationAction } from "./NotificationAction";
 export type { NotificationsConfig } from "./NotificationsConfig";
+export type { PathAccess } from "./PathAccess";
 export type { ProjectConfig } from "./ProjectConfig";
 export type { PromptsConfig } from "./PromptsConfig";
+export type { ProviderTimeouts } from "./ProviderTimeouts";
+export type { RateLimitConfig } from "./RateLimitConfig";
 export type { ProviderInfo } from "./ProviderInfo";
+export type { RecentProject } from "./RecentProject";
+export type { RequestCacheConfig } from "./RequestCacheConfig";
+export type { SamplingOverrides } from "./SamplingOverrides";
 export type { SearchConfig } from "./SearchConfig";
+export type { ShellKind } from "./ShellKind";
+export type { TemplateSource } from "./TemplateSource";
+export type { TemplatesConfig } from "./TemplatesConfig";
+export type { WorkspaceConfig } from "./WorkspaceConfig";
+export type { WorkspaceRoot } from "./WorkspaceRoot";
 
 // Template types
 export type { CommitContext } from "./CommitContext";
 export type { LineRange } from "./LineRange";
 export type { ReviewComment } from "./ReviewComment";
 export type { ReviewCommentsContext } from "./ReviewCommentsContext";
+export type { ScaffoldInfo } from "./ScaffoldInfo";
+export type { ScaffoldSource } from "./ScaffoldSource";
 export type { TemplateContent } from "./TemplateContent";
+export type { TemplateSourceKind } from "./TemplateSourceKind";
+export type { TemplateSourceStatus } from "./TemplateSourceStatus";
+export type { TemplateVariable } from "./TemplateVariable";
+export type { TemplateVariableType } from "./TemplateVariableType";
+
+// Setup types
+export type { SetupCheck } from "./SetupCheck";
+export type { SetupStatus } from "./SetupStatus";
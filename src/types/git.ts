@@ -1,13 +1,21 @@
 // Re-export generated types from ts-rs
 // Regenerate with: make generate-types
 export type {
+  ChangeRange,
   ChangedFile,
   DiffHunk,
   DiffLine,
+  DiffStats,
   FileDiff,
+  FileDiffPage,
   FileStatus,
+  HighlightedFile,
   LineKind,
   RepositoryCheckResult,
+  SplitCell,
+  SplitFileDiff,
+  SplitHunk,
+  SplitRow,
 } from "./generated";
 
 // Import types for use in helper functions
@@ -22,6 +22,12 @@ export function getToolIcon(name: string): string {
       return "\uD83C\uDF10"; // Globe emoji
     case "search_web":
       return "\uD83D\uDD0D"; // Magnifying glass
+    case "lookup_docs":
+      return "\uD83D\uDCDA"; // Books emoji
+    case "query_database":
+      return "\uD83D\uDDC4\uFE0F"; // File cabinet emoji
+    case "docker":
+      return "\uD83D\uDCE6"; // Package emoji
     case "dispatch_agent":
       return "\uD83E\uDD16"; // Robot emoji
     case "submit_plan":
@@ -30,10 +36,16 @@ export function getToolIcon(name: string): string {
       return "\u2705"; // Check mark
     case "todo_write":
       return "\u270F\uFE0F"; // Pencil
+    case "outline_file":
+      return "\uD83D\uDDC2\uFE0F"; // Card index dividers
+    case "semantic_search":
+      return "\uD83E\uDDED"; // Compass
     case "notebook_read":
       return "\uD83D\uDCD3"; // Notebook
     case "notebook_edit":
       return "\uD83D\uDCD4"; // Notebook with pen
+    case "scaffold":
+      return "\uD83C\uDFD7\uFE0F"; // Building construction
     default:
       return "\uD83D\uDD27"; // Wrench emoji
   }
@@ -57,10 +69,20 @@ export function getToolLabel(name: string): string {
       return "Find Files";
     case "grep":
       return "Search Content";
+    case "outline_file":
+      return "Outline File";
+    case "semantic_search":
+      return "Semantic Search";
     case "web_fetch":
       return "Fetch URL";
     case "search_web":
       return "Web Search";
+    case "lookup_docs":
+      return "Look Up Docs";
+    case "query_database":
+      return "Query Database";
+    case "docker":
+      return "Docker";
     case "dispatch_agent":
       return "Sub-Agent";
     case "submit_plan":
@@ -73,6 +95,8 @@ export function getToolLabel(name: string): string {
       return "Read Notebook";
     case "notebook_edit":
       return "Edit Notebook";
+    case "scaffold":
+      return "Scaffold";
     default:
       return name;
   }
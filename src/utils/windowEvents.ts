@@ -0,0 +1,14 @@
+import type { EventCallback, UnlistenFn } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
+
+/**
+ * Listens for an event sent to this window. Agent and menu events are
+ * targeted at the window that owns the session, so other project windows
+ * don't pick them up.
+ */
+export function listen<T>(
+  event: string,
+  handler: EventCallback<T>,
+): Promise<UnlistenFn> {
+  return getCurrentWebviewWindow().listen<T>(event, handler);
+}